@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use voronoice::{BoundingBox, Voronoi};
+
+#[allow(dead_code)]
+mod bench_base;
+use bench_base::*;
+
+/// JSON encoding of the same data written by the binary format, as a baseline.
+fn write_json(voronoi: &Voronoi<BoundingBox>) -> String {
+    let sites: Vec<[f64; 2]> = voronoi.sites().iter().map(|p| [p.x, p.y]).collect();
+    let vertices: Vec<[f64; 2]> = voronoi.vertices().iter().map(|p| [p.x, p.y]).collect();
+    let triangulation = voronoi.triangulation();
+
+    serde_json::to_string(&(
+        sites,
+        vertices,
+        voronoi.cells(),
+        &triangulation.triangles,
+        &triangulation.halfedges,
+        &triangulation.hull,
+    ))
+    .unwrap()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let voronoi = create_random_builder(1_000_000).build().unwrap();
+    let mut binary = Vec::new();
+    voronoi.write_binary(&mut binary).unwrap();
+    let json = write_json(&voronoi);
+
+    let mut group = c.benchmark_group("serialization");
+    group.sample_size(10);
+    group.bench_function("write binary 1,000,000 sites", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::with_capacity(binary.len());
+            voronoi.write_binary(&mut buffer).unwrap();
+            buffer
+        })
+    });
+    group.bench_function("read binary 1,000,000 sites", |b| {
+        b.iter(|| Voronoi::read_binary(binary.as_slice()).unwrap())
+    });
+    group.bench_function("write json 1,000,000 sites", |b| {
+        b.iter(|| write_json(&voronoi))
+    });
+    group.bench_function("read json 1,000,000 sites", |b| {
+        b.iter(|| {
+            serde_json::from_str::<(
+                Vec<[f64; 2]>,
+                Vec<[f64; 2]>,
+                Vec<Vec<usize>>,
+                Vec<usize>,
+                Vec<usize>,
+                Vec<usize>,
+            )>(&json)
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+mod bench_base;
+use bench_base::*;
+
+/// Builds the neighbors of each cell, in order of its edges, in compressed sparse row form.
+fn csr(
+    edges: impl Iterator<Item = (usize, Option<usize>)>,
+    sites: usize,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut offsets = vec![0; sites + 1];
+    let mut neighbors = Vec::new();
+    for (site, neighbor) in edges {
+        if let Some(neighbor) = neighbor {
+            neighbors.push(neighbor);
+            offsets[site + 1] = neighbors.len();
+        }
+    }
+    for site in 0..sites {
+        offsets[site + 1] = offsets[site + 1].max(offsets[site]);
+    }
+
+    (offsets, neighbors)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let voronoi = create_random_builder(100_000)
+        .build()
+        .expect("Some voronoi expected");
+    let sites = voronoi.sites().len();
+
+    let mut group = c.benchmark_group("cell edges");
+    group.sample_size(10);
+    group.bench_function("100,000 sites, CSR adjacency from nested cell edges", |b| {
+        b.iter(|| {
+            csr(
+                voronoi.iter_cells().flat_map(|cell| {
+                    let site = cell.site();
+                    cell.iter_edge_metadata().map(move |e| (site, e.neighbor))
+                }),
+                sites,
+            )
+        })
+    });
+    group.bench_function("100,000 sites, CSR adjacency from flat cell edges", |b| {
+        b.iter(|| {
+            csr(
+                voronoi.iter_cell_edges().map(|e| (e.site, e.neighbor)),
+                sites,
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
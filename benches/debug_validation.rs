@@ -0,0 +1,30 @@
+//! Validation only runs with debug assertions, so run this benchmark with them enabled in the optimized profile:
+//! ```CARGO_PROFILE_BENCH_DEBUG_ASSERTIONS=true cargo bench --bench debug_validation```
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use voronoice::DebugValidation;
+
+mod bench_base;
+use bench_base::*;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("debug validation");
+    group.sample_size(20);
+    for level in [
+        DebugValidation::Off,
+        DebugValidation::Fast,
+        DebugValidation::Full,
+    ] {
+        group.bench_function(format!("100,000 random sites, {:?}", level), |b| {
+            b.iter_batched(
+                || create_random_builder(100_000).set_debug_validation(level),
+                |b| b.build(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::Rng;
+use voronoice::{Point, VoronoiBuilder};
+
+mod bench_base;
+use bench_base::*;
+
+fn random_sites(count: usize) -> Vec<Point> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| Point {
+            x: rng.gen_range(-1.0..1.0),
+            y: rng.gen_range(-1.0..1.0),
+        })
+        .collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert sites");
+    group.sample_size(10);
+    for &(size, count) in &[(10_000, 200), (100_000, 1_000)] {
+        let voronoi = create_random_builder(size)
+            .build()
+            .expect("Some voronoi expected");
+
+        group.bench_function(
+            format!("{} sites, {} inserted one at a time", size, count),
+            |b| {
+                b.iter_batched(
+                    || (voronoi.clone(), random_sites(count)),
+                    |(mut voronoi, sites)| {
+                        for p in sites {
+                            voronoi.insert_site(p).expect("Insert expected to succeed");
+                        }
+                        voronoi
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_function(
+            format!("{} sites, {} inserted as a batch", size, count),
+            |b| {
+                b.iter_batched(
+                    || (voronoi.clone(), random_sites(count)),
+                    |(mut voronoi, sites)| {
+                        voronoi
+                            .insert_sites(sites)
+                            .expect("Insert expected to succeed");
+                        voronoi
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        // what inserting in place saves: building the diagram again from all the sites
+        group.bench_function(
+            format!("{} sites, {} built again from all sites", size, count),
+            |b| {
+                b.iter_batched(
+                    || {
+                        let mut sites = voronoi.sites().clone();
+                        sites.extend(random_sites(count));
+                        sites
+                    },
+                    |sites| {
+                        VoronoiBuilder::default()
+                            .set_boundary(voronoi.boundary().clone())
+                            .set_sites(sites)
+                            .build()
+                            .expect("Some voronoi expected")
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
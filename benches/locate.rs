@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use voronoice::Point;
+
+mod bench_base;
+use bench_base::*;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let voronoi = create_random_builder(100_000)
+        .build()
+        .expect("Some voronoi expected");
+
+    // the centers of the pixels of a 256x256 heightmap over the boundary, in scanline order
+    let size = 256;
+    let pixels: Vec<Point> = (0..size * size)
+        .map(|i| Point {
+            x: -1.0 + 2.0 * ((i % size) as f64 + 0.5) / size as f64,
+            y: -1.0 + 2.0 * ((i / size) as f64 + 0.5) / size as f64,
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("cell containing point");
+    group.sample_size(10);
+    group.bench_function("100,000 sites, 256x256 scanline, unhinted", |b| {
+        b.iter(|| {
+            pixels
+                .iter()
+                .filter_map(|p| voronoi.cell_containing_point(black_box(p)))
+                .count()
+        })
+    });
+    group.bench_function("100,000 sites, 256x256 scanline, hinted", |b| {
+        b.iter(|| {
+            let mut hint = 0;
+            pixels
+                .iter()
+                .filter_map(|p| {
+                    let site = voronoi.cell_containing_point_from(black_box(p), hint)?;
+                    hint = site;
+                    Some(site)
+                })
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::Rng;
+use voronoice::{morph::Morpher, BoundingBox, Point, VoronoiBuilder};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let sites: Vec<Point> = (0..10_000)
+        .map(|_| Point {
+            x: rng.gen_range(-1.0..1.0),
+            y: rng.gen_range(-1.0..1.0),
+        })
+        .collect();
+    let builder = || VoronoiBuilder::<BoundingBox>::default().set_sites(sites.clone());
+    let before = builder().build().unwrap();
+    let after = builder()
+        .set_lloyd_relaxation_iterations(5)
+        .build()
+        .unwrap();
+    let mut morpher = Morpher::new(&before, &after).unwrap();
+
+    // a frame must take less than 16.7ms to morph at 60 frames per second
+    let mut group = c.benchmark_group("morph");
+    let mut frame = 0;
+    group.bench_function("10,000 sites, one frame", |b| {
+        b.iter(|| {
+            frame = (frame + 1) % 60;
+            morpher.at(frame as f64 / 59.0).unwrap().len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+mod bench_base;
+use bench_base::*;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let builder = create_random_builder(100_000);
+
+    let mut group = c.benchmark_group("relax sites");
+    group.sample_size(10);
+    for iterations in [1, 5] {
+        group.bench_function(
+            format!("100,000 sites, {} iterations, built", iterations),
+            |b| {
+                b.iter_batched(
+                    || builder.clone().set_lloyd_relaxation_iterations(iterations),
+                    |builder| builder.build().expect("Some voronoi expected"),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_function(
+            format!("100,000 sites, {} iterations, sites only", iterations),
+            |b| {
+                b.iter_batched(
+                    || builder.clone(),
+                    |builder| {
+                        builder
+                            .relax_sites(iterations)
+                            .expect("Relaxed sites expected")
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
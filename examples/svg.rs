@@ -3,7 +3,10 @@ use std::{fs::File, io::Write, path::PathBuf};
 use clap::StructOpt;
 use delaunator::{next_halfedge, EMPTY};
 use rand::Rng;
-use voronoice::{BoundingBox, ClipBehavior, Point, Voronoi, VoronoiBuilder};
+use voronoice::{
+    raster::{Colormap, RenderStyle},
+    BoundingBox, ClipBehavior, Point, Voronoi, VoronoiBuilder,
+};
 const CANVAS_SIZE: f64 = 800.;
 const CANVAS_MARGIN: f64 = 0.;
 const POINT_SIZE: usize = 2;
@@ -15,6 +18,7 @@ const VORONOI_EDGE_COLOR: &str = "blue";
 const TRIANGULATION_HULL_COLOR: &str = "green";
 const TRIANGULATION_LINE_COLOR: &str = "grey";
 const JITTER_RANGE_VALUE: f64 = 5.;
+const MISSING_DATA_COLOR: u32 = 0xcc_cccc;
 
 #[derive(clap::Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -102,6 +106,10 @@ struct Args {
     /// Adds a bit of jitter when redering circumcenters
     #[clap(long)]
     jitter: bool,
+
+    /// Fills each cell by its area, with a viridis color map
+    #[clap(long)]
+    choropleth: bool,
 }
 
 fn main() -> std::io::Result<()> {
@@ -162,6 +170,7 @@ fn main() -> std::io::Result<()> {
 <svg viewBox="0 0 {width} {height}" xmlns="http://www.w3.org/2000/svg">
 <rect width="100%" height="100%" fill="white" />
 <rect x="{bb_x}" y="{bb_y}" width="{bb_width}" height="{bb_height}" style="fill-opacity:0;stroke-opacity:0.25;stroke-width:3;stroke:rgb(0,0,0)" />
+    {cell_fills}
     {sites}
     {circumcenters}
     {voronoi_edges}
@@ -174,6 +183,11 @@ fn main() -> std::io::Result<()> {
         bb_y = bounding_box_top_left.y,
         bb_width = bounding_box_side,
         bb_height = bounding_box_side,
+        cell_fills = if args.choropleth {
+            render_cell_fills(&transform, &voronoi, &args)
+        } else {
+            "".to_string()
+        },
         sites = render_point(
             &transform,
             voronoi.sites(),
@@ -213,6 +227,39 @@ fn main() -> std::io::Result<()> {
     File::create(args.output_path)?.write_all(contents.as_bytes())
 }
 
+fn render_cell_fills(transform: &Transform, voronoi: &Voronoi<BoundingBox>, args: &Args) -> String {
+    let areas = voronoi
+        .iter_cells()
+        .map(|cell| cell.area())
+        .collect::<Vec<f64>>();
+    let legend = RenderStyle::Choropleth {
+        values: &areas,
+        colormap: Colormap::Viridis,
+        range: None,
+        missing: MISSING_DATA_COLOR,
+    }
+    .legend();
+    if let Some((min, max)) = legend.range {
+        println!("Cell areas from {} to {}", min, max);
+    }
+
+    voronoi.iter_cells().fold(String::new(), |acc, cell| {
+        if args.clip_behavior != ClipBehavior::Clip && cell.is_on_hull() {
+            // hull cells are not closed when clipping is disabled
+            return acc;
+        }
+
+        let points = cell.iter_vertices().fold(String::new(), |acc, p| {
+            let p = transform.transform(p);
+            acc + &format!("{},{} ", p.x, p.y)
+        });
+        acc + &format!(
+            r##"<polygon points="{points}" fill="#{color:06x}" />"##,
+            color = legend.color(areas[cell.site()])
+        )
+    })
+}
+
 fn render_triangles(transform: &Transform, voronoi: &Voronoi<BoundingBox>, labels: bool) -> String {
     let triangulation = voronoi.triangulation();
     let points = voronoi.sites();
@@ -0,0 +1,180 @@
+use std::{fs::File, num::NonZeroU32, path::PathBuf, rc::Rc};
+
+use voronoice::{raster::Rasterizer, BoundingBox, ConvexBoundary, Point, Voronoi, VoronoiBuilder};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    event_loop::EventLoop,
+    keyboard::{Key, NamedKey},
+    window::WindowBuilder,
+};
+
+const WINDOW_SIZE: u32 = 800;
+const BACKGROUND_COLOR: u32 = 0x20_2020;
+const OUTLINE_COLOR: u32 = 0x10_1010;
+const HIGHLIGHT_COLOR: u32 = 0xff_ffff;
+const PICK_RADIUS: f64 = 4.;
+const ZOOM_STEP: f64 = 1.1;
+const BUNDLE_PATH: &str = "failure_bundle.json";
+
+/// A pseudo random, but stable, color for each site.
+fn color(site: usize) -> u32 {
+    let hash = (site as u32 + 1).wrapping_mul(0x9e37_79b9);
+    0x40_4040 | (hash >> 8) & 0xbf_bfbf
+}
+
+/// Reads sites in the format of the bundled assets (e.g. [[1,0],[0,1],[1,1]]) and builds a diagram bounded by a box around them.
+fn load(path: &PathBuf) -> Voronoi<BoundingBox> {
+    let file = File::open(path).expect("Cannot open sites file");
+    let sites: Vec<[f64; 2]> = serde_json::from_reader(file).expect("Cannot parse sites file");
+    let sites: Vec<Point> = sites.iter().map(|&[x, y]| Point { x, y }).collect();
+
+    let n = sites.len() as f64;
+    let center = Point {
+        x: sites.iter().map(|p| p.x).sum::<f64>() / n,
+        y: sites.iter().map(|p| p.y).sum::<f64>() / n,
+    };
+    let farthest = sites
+        .iter()
+        .map(|p| ((p.x - center.x).powi(2) + (p.y - center.y).powi(2)).sqrt())
+        .fold(0., f64::max);
+
+    VoronoiBuilder::default()
+        .set_sites(sites)
+        .set_boundary(BoundingBox::new(center, farthest * 2., farthest * 2.))
+        .build()
+        .expect("Sites do not generate a valid diagram")
+}
+
+/// Writes the sites and the boundary vertices of a diagram, so a failure can be reproduced.
+fn write_bundle(voronoi: &Voronoi<BoundingBox>) {
+    let points = |points: &[Point]| points.iter().map(|p| [p.x, p.y]).collect::<Vec<[f64; 2]>>();
+    let bundle = serde_json::json!({
+        "sites": points(voronoi.sites()),
+        "boundary": points(voronoi.boundary().vertices()),
+    });
+    let file = File::create(BUNDLE_PATH).expect("Cannot create bundle file");
+    serde_json::to_writer_pretty(file, &bundle).expect("Cannot write bundle file");
+    println!("Wrote {} sites to {}", voronoi.sites().len(), BUNDLE_PATH);
+}
+
+fn fit_view(rasterizer: &mut Rasterizer, voronoi: &Voronoi<BoundingBox>) {
+    let boundary = voronoi.boundary();
+    let side = boundary.width().max(boundary.height());
+    let pixels = rasterizer.width().min(rasterizer.height()) as f64;
+    rasterizer.set_view(boundary.center().clone(), 0.9 * pixels / side);
+}
+
+fn main() {
+    let path: PathBuf = std::env::args_os()
+        .nth(1)
+        .expect("Usage: viewer <sites.json>")
+        .into();
+    let mut voronoi = load(&path);
+
+    let event_loop = EventLoop::new().expect("Cannot create event loop");
+    let window = Rc::new(
+        WindowBuilder::new()
+            .with_title(format!("voronoice - {}", path.display()))
+            .with_inner_size(winit::dpi::PhysicalSize::new(WINDOW_SIZE, WINDOW_SIZE))
+            .build(&event_loop)
+            .expect("Cannot create window"),
+    );
+    let context = softbuffer::Context::new(window.clone()).expect("Cannot create context");
+    let mut surface =
+        softbuffer::Surface::new(&context, window.clone()).expect("Cannot create surface");
+
+    let mut rasterizer = Rasterizer::new(WINDOW_SIZE as usize, WINDOW_SIZE as usize);
+    rasterizer.set_background(BACKGROUND_COLOR);
+    rasterizer.set_outline(Some(OUTLINE_COLOR));
+    fit_view(&mut rasterizer, &voronoi);
+
+    let mut cursor = PhysicalPosition::new(0., 0.);
+    let mut dragging = false;
+
+    event_loop
+        .run(move |event, target| {
+            let Event::WindowEvent { window_id, event } = event else {
+                return;
+            };
+            if window_id != window.id() {
+                return;
+            }
+
+            let mut hover_changed = false;
+            match event {
+                WindowEvent::CloseRequested => target.exit(),
+                WindowEvent::Resized(size) => {
+                    rasterizer.resize(size.width.max(1) as usize, size.height.max(1) as usize);
+                    hover_changed = true;
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    if dragging {
+                        rasterizer.pan(position.x - cursor.x, position.y - cursor.y);
+                    }
+                    cursor = position;
+                    hover_changed = true;
+                }
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                } => dragging = state == ElementState::Pressed,
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let lines = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y as f64,
+                        MouseScrollDelta::PixelDelta(p) => p.y / 20.,
+                    };
+                    rasterizer.zoom_at(cursor.x, cursor.y, ZOOM_STEP.powf(lines));
+                    hover_changed = true;
+                }
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.state == ElementState::Pressed =>
+                {
+                    match event.logical_key.as_ref() {
+                        Key::Character("r") => {
+                            // one Lloyd relaxation iteration, keeping the current view
+                            if let Some(relaxed) = VoronoiBuilder::from(&voronoi)
+                                .set_lloyd_relaxation_iterations(1)
+                                .build()
+                            {
+                                voronoi = relaxed;
+                            }
+                            hover_changed = true;
+                        }
+                        Key::Character("s") => write_bundle(&voronoi),
+                        Key::Character("f") => {
+                            fit_view(&mut rasterizer, &voronoi);
+                            hover_changed = true;
+                        }
+                        Key::Named(NamedKey::Escape) => target.exit(),
+                        _ => {}
+                    }
+                }
+                WindowEvent::RedrawRequested => {
+                    let (width, height) = (rasterizer.width() as u32, rasterizer.height() as u32);
+                    surface
+                        .resize(
+                            NonZeroU32::new(width).unwrap(),
+                            NonZeroU32::new(height).unwrap(),
+                        )
+                        .expect("Cannot resize surface");
+                    let mut buffer = surface.buffer_mut().expect("Cannot get surface buffer");
+                    buffer.copy_from_slice(rasterizer.pixels());
+                    buffer.present().expect("Cannot present surface buffer");
+                }
+                _ => {}
+            }
+
+            if hover_changed {
+                // rendering removes the previous highlight, redrawing only what changed
+                let p = rasterizer.to_world(cursor.x, cursor.y);
+                let picked = voronoi.pick(&p, PICK_RADIUS / rasterizer.scale());
+                rasterizer.render(&voronoi, color);
+                rasterizer.highlight(&voronoi, &picked, HIGHLIGHT_COLOR);
+                window.set_title(&format!("voronoice - {} - {:?}", path.display(), picked));
+                window.request_redraw();
+            }
+        })
+        .expect("Event loop failed");
+}
@@ -0,0 +1,249 @@
+use crate::{ConvexBoundary, NeighborSiteIterator, Voronoi};
+
+/// The cell adjacency graph of a Voronoi diagram in compressed sparse row (CSR) form.
+///
+/// The neighbors of site ```i``` are stored in ```neighbors()[offsets()[i]..offsets()[i + 1]]```, in the same order returned by [crate::VoronoiCell::iter_neighbors()].
+/// The graph is symmetric: ```j``` is a neighbor of ```i``` if, and only if, ```i``` is a neighbor of ```j```.
+///
+/// Use [Voronoi::adjacency()] or [crate::VoronoiTopology::adjacency()] to obtain an instance of this type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellAdjacency {
+    offsets: Vec<usize>,
+    neighbors: Vec<usize>,
+}
+
+impl CellAdjacency {
+    /// Builds the graph from the neighbors of each site, in site order.
+    pub(crate) fn from_neighbors<I: Iterator<Item = usize>>(rows: impl Iterator<Item = I>) -> Self {
+        let mut offsets = vec![0];
+        let mut neighbors = Vec::new();
+        for row in rows {
+            neighbors.extend(row);
+            offsets.push(neighbors.len());
+        }
+
+        Self { offsets, neighbors }
+    }
+
+    /// Gets the number of sites in the graph.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Returns ```true``` if the graph has no sites.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the neighbors of ```site```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist.
+    #[inline]
+    pub fn neighbors_of(&self, site: usize) -> &[usize] {
+        &self.neighbors[self.offsets[site]..self.offsets[site + 1]]
+    }
+
+    /// Gets the row offsets of the graph. There is one offset per site, plus a last offset equal to ```neighbors().len()```.
+    #[inline]
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Gets the concatenated neighbor lists of all sites.
+    #[inline]
+    pub fn neighbors(&self) -> &[usize] {
+        &self.neighbors
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Builds the cell adjacency graph of this diagram.
+    ///
+    /// The graph follows the same adjacency definition used by [crate::VoronoiCell::iter_neighbors()]:
+    /// two cells are neighbors if their clipped polygons share an edge longer than [Self::adjacency_epsilon()].
+    pub fn adjacency(&self) -> CellAdjacency {
+        CellAdjacency::from_neighbors(
+            (0..self.sites.len()).map(|site| NeighborSiteIterator::new(self, site)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::{utils, BoundingBox, Point, TopologicalNeighborSiteIterator, VoronoiBuilder};
+
+    use super::*;
+
+    /// Asserts that all adjacency APIs agree with each other and that adjacency is symmetric.
+    fn assert_consistent_adjacency<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let adjacency = voronoi.adjacency();
+        assert_eq!(adjacency.len(), voronoi.sites().len());
+
+        for cell in voronoi.iter_cells() {
+            let site = cell.site();
+            let neighbors: Vec<usize> = cell.iter_neighbors().collect();
+            assert_eq!(
+                adjacency.neighbors_of(site),
+                neighbors.as_slice(),
+                "CSR and iter_neighbors disagree for site {}.",
+                site
+            );
+
+            let edges: Vec<_> = cell.iter_edge_metadata().collect();
+            assert_eq!(
+                edges.iter().map(|e| e.vertices).collect::<Vec<_>>(),
+                cell.iter_edges().collect::<Vec<_>>(),
+                "Edge metadata is not aligned with iter_edges for site {}.",
+                site
+            );
+
+            for neighbor in &neighbors {
+                let length = cell.shared_edge_length(*neighbor);
+                assert!(
+                    length > voronoi.adjacency_epsilon(),
+                    "Site {} lists {} as neighbor but shared edge length is {}.",
+                    site,
+                    neighbor,
+                    length
+                );
+                assert!(utils::has_common_voronoi_edge(voronoi, site, *neighbor));
+                assert!(
+                    adjacency.neighbors_of(*neighbor).contains(&site),
+                    "Site {} lists {} as neighbor, but not the other way around.",
+                    site,
+                    neighbor
+                );
+                assert_eq!(length, voronoi.cell(*neighbor).shared_edge_length(site));
+
+                let edge_length: f64 = edges
+                    .iter()
+                    .filter(|e| e.neighbor == Some(*neighbor))
+                    .map(|e| e.length)
+                    .sum();
+                assert!(
+                    utils::abs_diff_eq(edge_length, length, 1e-9),
+                    "Edges of site {} facing {} add up to {}, expected {}.",
+                    site,
+                    neighbor,
+                    edge_length,
+                    length
+                );
+            }
+
+            for edge in &edges {
+                if let Some(neighbor) = edge.neighbor {
+                    assert!(
+                        neighbors.contains(&neighbor),
+                        "Edge of site {} faces {}, which is not a neighbor.",
+                        site,
+                        neighbor
+                    );
+                }
+            }
+
+            // the lengths measured in one pass over the cell are the lengths measured per pair
+            let lengths = utils::shared_voronoi_edge_lengths(voronoi, site);
+            for other in TopologicalNeighborSiteIterator::new(voronoi, site) {
+                let length = lengths
+                    .iter()
+                    .find_map(|&(n, length)| (n == other).then_some(length))
+                    .unwrap_or(0.0);
+                assert_eq!(
+                    length,
+                    cell.shared_edge_length(other),
+                    "Shared edge length of sites {} and {}.",
+                    site,
+                    other
+                );
+
+                if !neighbors.contains(&other) {
+                    assert!(
+                        cell.shared_edge_length(other) <= voronoi.adjacency_epsilon(),
+                        "Site {} and {} share an edge but are not neighbors.",
+                        site,
+                        other
+                    );
+                    assert!(!utils::has_common_voronoi_edge(voronoi, site, other));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cells_touching_at_corner_are_not_neighbors() -> std::io::Result<()> {
+        let voronoi = utils::test::new_voronoi_builder_from_asset("touching_corner1.json")?
+            .set_boundary(BoundingBox::new_centered_square(20.0))
+            .build()
+            .expect("Some voronoi expected");
+
+        // cells 0 and 1 only touch at the boundary corner (10, 10)
+        assert!(TopologicalNeighborSiteIterator::new(&voronoi, 0).any(|n| n == 1));
+        assert_eq!(voronoi.cell(0).shared_edge_length(1), 0.0);
+        assert!(!voronoi.cell(0).iter_neighbors().any(|n| n == 1));
+        assert!(!voronoi.cell(1).iter_neighbors().any(|n| n == 0));
+        assert_consistent_adjacency(&voronoi);
+
+        Ok(())
+    }
+
+    #[test]
+    fn adjacency_epsilon_drops_short_edges() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(10)
+            .set_adjacency_epsilon(1.0)
+            .build()
+            .expect("Some voronoi expected");
+
+        assert!(voronoi.adjacency().neighbors().is_empty());
+        assert_consistent_adjacency(&voronoi);
+    }
+
+    #[test]
+    fn random_tightly_clipped_adjacency_is_consistent() {
+        let mut rng = utils::test::rng();
+        for _ in 0..200 {
+            let size = rng.gen_range(5..60);
+            let sites: Vec<Point> = (0..size)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+
+            // shrink the boundary to the extents of the sites, so most hull cells get clipped
+            let (min_x, max_x, min_y, max_y) = sites.iter().fold(
+                (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+                |(min_x, max_x, min_y, max_y), p| {
+                    (
+                        min_x.min(p.x),
+                        max_x.max(p.x),
+                        min_y.min(p.y),
+                        max_y.max(p.y),
+                    )
+                },
+            );
+            let boundary = BoundingBox::new(
+                Point {
+                    x: (min_x + max_x) / 2.0,
+                    y: (min_y + max_y) / 2.0,
+                },
+                max_x - min_x,
+                max_y - min_y,
+            );
+
+            if let Some(voronoi) = VoronoiBuilder::default()
+                .set_sites(sites)
+                .set_boundary(boundary)
+                .build()
+            {
+                assert_consistent_adjacency(&voronoi);
+            }
+        }
+    }
+}
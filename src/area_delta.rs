@@ -0,0 +1,245 @@
+use std::fmt::Display;
+
+use crate::{ConvexBoundary, Voronoi};
+
+/// The threshold beyond which [Voronoi::area_delta()] reports the change of the area of a cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AreaThreshold {
+    /// Cells whose area changed by more than this many square units are reported, regardless of their size.
+    Absolute(f64),
+
+    /// Cells whose area changed by more than this fraction of their area in the first diagram are reported,
+    /// so a small change of a tiny cell may be reported while a larger change of a huge cell is not. Any change of a cell of no area is reported.
+    Relative(f64),
+}
+
+/// An error returned when comparing the areas of the cells of two diagrams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AreaDeltaError {
+    /// The diagrams do not have the same number of sites, given for the first and second diagram.
+    SiteCountMismatch(usize, usize),
+}
+
+impl Display for AreaDeltaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AreaDeltaError::SiteCountMismatch(a, b) => {
+                write!(
+                    f,
+                    "Diagrams have different numbers of sites: {} and {}",
+                    a, b
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AreaDeltaError {}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the cells whose area changed beyond ```threshold``` from this diagram to ```other```, e.g. after a batch of edits, without comparing their geometry.
+    ///
+    /// Cells are paired by site, and each cell reported comes with the change of its area, positive if it grew in ```other```, in ascending order of site.
+    ///
+    /// # Errors
+    ///
+    /// Returns [AreaDeltaError::SiteCountMismatch] if the diagrams do not have the same number of sites.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let before = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut after = before.clone();
+    /// after.move_site(55, Point { x: 0.03, y: 0.02 }).unwrap();
+    ///
+    /// // only the moved cell and its neighbors changed
+    /// let changed = before.area_delta(&after, AreaThreshold::Relative(1e-9)).unwrap();
+    /// assert!(changed.iter().any(|&(site, _)| site == 55));
+    /// assert!(changed.len() < 10);
+    ///```
+    pub fn area_delta(
+        &self,
+        other: &Voronoi<T>,
+        threshold: AreaThreshold,
+    ) -> Result<Vec<(usize, f64)>, AreaDeltaError> {
+        let (areas, other_areas) = paired_areas(self, other)?;
+
+        Ok(areas
+            .iter()
+            .zip(other_areas)
+            .enumerate()
+            .filter_map(|(site, (area, other_area))| {
+                let delta = other_area - area;
+                let limit = match threshold {
+                    AreaThreshold::Absolute(threshold) => threshold,
+                    AreaThreshold::Relative(fraction) => fraction * area,
+                };
+                (delta.abs() > limit).then_some((site, delta))
+            })
+            .collect())
+    }
+
+    /// Gets how different the areas of the cells of this diagram and ```other``` are: half the sum of the absolute changes of the areas of the cells paired by site.
+    ///
+    /// Diagrams of the same boundary and clip behavior cover the same area, so this is the area moved from the cells that shrank to the cells that grew,
+    /// 0.0 for diagrams with identical cells, and at most the area of the boundary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [AreaDeltaError::SiteCountMismatch] if the diagrams do not have the same number of sites.
+    pub fn total_variation(&self, other: &Voronoi<T>) -> Result<f64, AreaDeltaError> {
+        let (areas, other_areas) = paired_areas(self, other)?;
+
+        Ok(areas
+            .iter()
+            .zip(other_areas)
+            .map(|(area, other_area)| (other_area - area).abs())
+            .sum::<f64>()
+            / 2.0)
+    }
+}
+
+/// Gets the areas of the cells of both diagrams, as calculated by [crate::VoronoiCell::area()], if they have the same number of sites.
+fn paired_areas<T: ConvexBoundary>(
+    a: &Voronoi<T>,
+    b: &Voronoi<T>,
+) -> Result<(Vec<f64>, Vec<f64>), AreaDeltaError> {
+    if a.sites.len() != b.sites.len() {
+        return Err(AreaDeltaError::SiteCountMismatch(
+            a.sites.len(),
+            b.sites.len(),
+        ));
+    }
+
+    let areas = |voronoi: &Voronoi<T>| voronoi.iter_cells().map(|cell| cell.area()).collect();
+    Ok((areas(a), areas(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox, Point, VoronoiBuilder};
+
+    #[test]
+    fn identical_diagrams_report_no_change() {
+        let voronoi = random_voronoi(200);
+        let clone = voronoi.clone();
+        for threshold in [AreaThreshold::Absolute(0.0), AreaThreshold::Relative(0.0)] {
+            assert_eq!(voronoi.area_delta(&clone, threshold), Ok(Vec::new()));
+        }
+        assert_eq!(voronoi.total_variation(&clone), Ok(0.0));
+    }
+
+    #[test]
+    fn different_site_counts_are_an_error() {
+        let (a, b) = (random_voronoi(50), random_voronoi(40));
+        assert_eq!(
+            a.area_delta(&b, AreaThreshold::Absolute(0.1)),
+            Err(AreaDeltaError::SiteCountMismatch(50, 40))
+        );
+        assert_eq!(
+            a.total_variation(&b),
+            Err(AreaDeltaError::SiteCountMismatch(50, 40))
+        );
+    }
+
+    #[test]
+    fn moving_interior_site_reports_cell_and_neighbors() {
+        let voronoi = random_voronoi(300);
+        let site = voronoi
+            .iter_cells()
+            .find(|cell| {
+                !cell.is_on_hull() && cell.iter_neighbors().all(|n| !voronoi.cell(n).is_on_hull())
+            })
+            .expect("Some interior cell expected")
+            .site();
+        let mut moved = voronoi.clone();
+        let position = voronoi.sites()[site].clone();
+        moved
+            .move_site(
+                site,
+                Point {
+                    x: position.x + 0.01,
+                    y: position.y - 0.005,
+                },
+            )
+            .expect("Site expected to move");
+
+        // cells gaining or losing an edge with the moved cell are neighbors before or after the move
+        let mut expected: Vec<usize> = voronoi
+            .cell(site)
+            .iter_neighbors()
+            .chain(moved.cell(site).iter_neighbors())
+            .chain([site])
+            .collect();
+        expected.sort_unstable();
+        expected.dedup();
+
+        let changed = voronoi
+            .area_delta(&moved, AreaThreshold::Absolute(1e-12))
+            .expect("Same site counts expected");
+        let sites: Vec<usize> = changed.iter().map(|&(site, _)| site).collect();
+        assert_eq!(sites, expected);
+
+        // the area moved between cells adds up on both sides
+        let gained: f64 = changed.iter().map(|&(_, d)| d.max(0.0)).sum();
+        let variation = voronoi
+            .total_variation(&moved)
+            .expect("Same site counts expected");
+        assert!(variation > 0.0);
+        assert!((gained - variation).abs() < 1e-12);
+    }
+
+    #[test]
+    fn relative_and_absolute_thresholds_weigh_cell_sizes() {
+        // a huge cell in the lower left, and a tiny cell in the middle of a dense cluster in the upper right
+        let mut sites = vec![
+            Point { x: -0.6, y: -0.6 },
+            Point { x: 0.1, y: -0.7 },
+            Point { x: -0.7, y: 0.2 },
+            Point { x: 0.0, y: 0.1 },
+            Point { x: 0.7, y: -0.3 },
+            Point { x: -0.2, y: 0.7 },
+            Point { x: 0.9, y: 0.9 },
+        ];
+        for i in 0..5 {
+            for j in 0..5 {
+                sites.push(Point {
+                    x: 0.5 + 0.01 * i as f64 + 0.001 * j as f64,
+                    y: 0.5 + 0.01 * j as f64 - 0.0007 * i as f64,
+                });
+            }
+        }
+        let (huge, tiny) = (0, 7 + 2 * 5 + 2);
+        let before = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+
+        sites[huge].x += 0.02;
+        sites[huge].y += 0.01;
+        sites[tiny].x += 0.004;
+        sites[tiny].y += 0.002;
+        let after = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+
+        let reported = |threshold| -> Vec<usize> {
+            before
+                .area_delta(&after, threshold)
+                .expect("Same site counts expected")
+                .into_iter()
+                .map(|(site, _)| site)
+                .collect()
+        };
+        let absolute = reported(AreaThreshold::Absolute(1e-3));
+        assert!(absolute.contains(&huge) && !absolute.contains(&tiny));
+        let relative = reported(AreaThreshold::Relative(0.04));
+        assert!(relative.contains(&tiny) && !relative.contains(&huge));
+    }
+}
@@ -0,0 +1,387 @@
+use std::fmt::Display;
+
+use crate::{ConvexBoundary, Point, Voronoi};
+
+/// An error returned by [Voronoi::build_atlas()].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AtlasError {
+    /// The cells do not fit in the atlas, even with a slot of a single pixel each.
+    ///
+    /// ```required``` is the size of an atlas they fit in, with the same width as the atlas requested unless a single slot is wider than it,
+    /// and ```overflow``` is the number of rows of pixels that exceed the height of the atlas requested.
+    TooSmall { overflow: u32, required: (u32, u32) },
+}
+
+impl Display for AtlasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtlasError::TooSmall { overflow, required } => write!(
+                f,
+                "Cells exceed the atlas by {} rows, an atlas of {}x{} is required",
+                overflow, required.0, required.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+/// A rectangle of an atlas assigned to each cell of a diagram, with the transform from the coordinates of the diagram to the atlas.
+///
+/// Use [Voronoi::build_atlas()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct CellAtlas {
+    size: (u32, u32),
+    padding: u32,
+    scale: f64,
+
+    /// The ```(x, y, width, height)``` of the rectangle of each cell, in pixels.
+    rects: Vec<(u32, u32, u32, u32)>,
+
+    /// The corner of the bounding box of each cell with the lowest coordinates, which is mapped to the corner of its rectangle.
+    origins: Vec<Point>,
+}
+
+impl CellAtlas {
+    /// Gets the size of the atlas, in pixels.
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Gets the padding around each rectangle, in pixels.
+    #[inline]
+    pub fn padding(&self) -> u32 {
+        self.padding
+    }
+
+    /// Gets the number of pixels per unit of the coordinates of the diagram, the same for all cells.
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Gets the rectangle of the cell of ```site``` as ```(x, y, width, height)```, in pixels from the top left corner of the atlas.
+    ///
+    /// The rectangle contains the bounding box of the cell, scaled by [Self::scale()], and excludes the padding around it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist.
+    #[inline]
+    pub fn atlas_rect(&self, site: usize) -> (u32, u32, u32, u32) {
+        self.rects[site]
+    }
+
+    /// Maps ```p```, in the coordinates of the diagram, to texture coordinates of the atlas from the rectangle of the cell of ```site```.
+    ///
+    /// Texture coordinates go from 0 to 1 over the width and height of the atlas, from its top left corner, as the Y-axis points downwards in both.
+    /// The vertices of the cell are mapped within its rectangle; other points are mapped with the same transform, so they may fall outside of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist.
+    pub fn to_atlas_uv(&self, site: usize, p: &Point) -> (f32, f32) {
+        let (x, y, _, _) = self.rects[site];
+        let origin = &self.origins[site];
+        let u = (x as f64 + (p.x - origin.x) * self.scale) / self.size.0 as f64;
+        let v = (y as f64 + (p.y - origin.y) * self.scale) / self.size.1 as f64;
+
+        (u as f32, v as f32)
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Assigns each cell a rectangle of an atlas of ```atlas_size``` pixels, e.g. to bake a texture per cell.
+    ///
+    /// The bounding box of each cell is scaled by the same factor, see [CellAtlas::scale()], so all cells get the same texel density,
+    /// and surrounded by ```padding_px``` pixels on every side, so neighboring rectangles are at least twice that apart.
+    /// Boxes are packed on shelves, from the tallest to the shortest, and the scale is the largest one found for which the boxes fit.
+    /// Cells without area get a rectangle of a single pixel.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let atlas = v.build_atlas((512, 512), 2).unwrap();
+    /// let (x, y, width, height) = atlas.atlas_rect(0);
+    /// assert!(x + width <= 512 && y + height <= 512);
+    ///```
+    ///
+    /// # Errors
+    ///
+    /// Returns [AtlasError::TooSmall] if the cells do not fit even with rectangles of a single pixel.
+    pub fn build_atlas(
+        &self,
+        atlas_size: (u32, u32),
+        padding_px: u32,
+    ) -> Result<CellAtlas, AtlasError> {
+        let (origins, extents): (Vec<Point>, Vec<Point>) = self
+            .iter_cells()
+            .map(|cell| bounding_box(cell.iter_vertices(), cell.site_position()))
+            .unzip();
+
+        // slot heights are rounded up, which keeps the order of the heights, so the order can be calculated once
+        let mut order: Vec<usize> = (0..extents.len()).collect();
+        order.sort_by(|&a, &b| extents[b].y.partial_cmp(&extents[a].y).unwrap());
+
+        let (width, height) = (atlas_size.0 as u64, atlas_size.1 as u64);
+        let padding = padding_px as u64;
+        let pack_at =
+            |scale: f64, width: u64| pack_shelves(&extents, &order, scale, padding, width);
+
+        let mut best = match pack_at(0.0, width) {
+            Some((positions, used)) if used <= height => (0.0, positions),
+            _ => {
+                let width = width.max(1 + 2 * padding);
+                let (_, used) = pack_at(0.0, width).expect("Slots expected to fit the width");
+                return Err(AtlasError::TooSmall {
+                    overflow: saturate(used.saturating_sub(height)),
+                    required: (saturate(width), saturate(used)),
+                });
+            }
+        };
+
+        // no box can be larger than the atlas, and their total area cannot be larger than its area
+        let (max_width, max_height, total_area) =
+            extents.iter().fold((0.0, 0.0, 0.0), |(w, h, a), e| {
+                (e.x.max(w), e.y.max(h), a + e.x * e.y)
+            });
+        let mut high = [
+            width as f64 / max_width,
+            height as f64 / max_height,
+            (width as f64 * height as f64 / total_area).sqrt(),
+        ]
+        .iter()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+        let mut low = 0.0;
+        if high.is_finite() {
+            for _ in 0..64 {
+                let scale = (low + high) / 2.0;
+                match pack_at(scale, width) {
+                    Some((positions, used)) if used <= height => {
+                        low = scale;
+                        best = (scale, positions);
+                    }
+                    _ => high = scale,
+                }
+                if high - low <= low * 1e-6 {
+                    break;
+                }
+            }
+        }
+
+        let (scale, positions) = best;
+        let rects = positions
+            .into_iter()
+            .zip(&extents)
+            .map(|((x, y), extent)| {
+                let (w, h) = slot_size(extent, scale);
+                (
+                    (x + padding) as u32,
+                    (y + padding) as u32,
+                    w as u32,
+                    h as u32,
+                )
+            })
+            .collect();
+
+        Ok(CellAtlas {
+            size: atlas_size,
+            padding: padding_px,
+            scale,
+            rects,
+            origins,
+        })
+    }
+}
+
+/// Gets the corner with the lowest coordinates and the extent of the bounding box of ```vertices```, or of ```site``` if there are no vertices.
+fn bounding_box<'p>(vertices: impl Iterator<Item = &'p Point>, site: &Point) -> (Point, Point) {
+    let (min, max) = vertices.fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    );
+
+    if min.x <= max.x {
+        let extent = Point {
+            x: max.x - min.x,
+            y: max.y - min.y,
+        };
+        (min, extent)
+    } else {
+        (site.clone(), Point { x: 0.0, y: 0.0 })
+    }
+}
+
+/// Gets the size, in pixels, of a box of ```extent``` scaled by ```scale```. Boxes are at least a pixel wide and high.
+fn slot_size(extent: &Point, scale: f64) -> (u64, u64) {
+    let pixels = |length: f64| ((length * scale).ceil() as u64).max(1);
+    (pixels(extent.x), pixels(extent.y))
+}
+
+/// Packs the boxes of ```extents```, scaled by ```scale``` and padded by ```padding```, on shelves of a strip ```width``` pixels wide.
+///
+/// Boxes are placed in ```order```, left to right, and a new shelf is started when the box does not fit on the current one.
+/// Returns the top left corner of the padded slot of each box and the height of the shelves, or ```None``` if a slot is wider than the strip.
+fn pack_shelves(
+    extents: &[Point],
+    order: &[usize],
+    scale: f64,
+    padding: u64,
+    width: u64,
+) -> Option<(Vec<(u64, u64)>, u64)> {
+    let mut positions = vec![(0, 0); extents.len()];
+    let (mut x, mut shelf_y, mut shelf_height) = (0, 0, 0);
+    for &i in order {
+        let (w, h) = slot_size(&extents[i], scale);
+        let (w, h) = (w + 2 * padding, h + 2 * padding);
+        if w > width {
+            return None;
+        }
+
+        if x + w > width {
+            shelf_y += shelf_height;
+            x = 0;
+            shelf_height = 0;
+        }
+        positions[i] = (x, shelf_y);
+        x += w;
+        shelf_height = shelf_height.max(h);
+    }
+
+    Some((positions, shelf_y + shelf_height))
+}
+
+fn saturate(value: u64) -> u32 {
+    value.min(u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox, VoronoiBuilder};
+
+    #[test]
+    fn rects_are_padded_and_do_not_overlap() {
+        let voronoi = random_voronoi(300);
+        for &(size, padding) in &[((1024, 1024), 2), ((300, 700), 0), ((2048, 256), 5)] {
+            let atlas = voronoi.build_atlas(size, padding).expect("Atlas expected");
+            assert!(atlas.scale() > 0.0);
+
+            let padded: Vec<(u32, u32, u32, u32)> = (0..300)
+                .map(|site| {
+                    let (x, y, w, h) = atlas.atlas_rect(site);
+                    assert!(x >= padding && y >= padding);
+                    (x - padding, y - padding, w + 2 * padding, h + 2 * padding)
+                })
+                .collect();
+            for (a, &(x, y, w, h)) in padded.iter().enumerate() {
+                assert!(x + w <= size.0 && y + h <= size.1);
+                for &(ox, oy, ow, oh) in &padded[a + 1..] {
+                    let disjoint = x + w <= ox || ox + ow <= x || y + h <= oy || oy + oh <= y;
+                    assert!(disjoint, "Padded rects of cells overlap.");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cell_vertices_map_inside_their_rect() {
+        let voronoi = random_voronoi(500);
+        let (width, height) = (2000, 1000);
+        let atlas = voronoi
+            .build_atlas((width, height), 1)
+            .expect("Atlas expected");
+
+        for cell in voronoi.iter_cells() {
+            let (x, y, w, h) = atlas.atlas_rect(cell.site());
+            for p in cell.iter_vertices() {
+                let (u, v) = atlas.to_atlas_uv(cell.site(), p);
+                let (px, py) = (u as f64 * width as f64, v as f64 * height as f64);
+                assert!(px >= x as f64 - 1e-3 && px <= (x + w) as f64 + 1e-3);
+                assert!(py >= y as f64 - 1e-3 && py <= (y + h) as f64 + 1e-3);
+            }
+        }
+
+        // the boxes fill a fair part of the atlas
+        let used: u64 = (0..500)
+            .map(|site| {
+                let (_, _, w, h) = atlas.atlas_rect(site);
+                w as u64 * h as u64
+            })
+            .sum();
+        assert!(
+            used as f64 > 0.25 * (width * height) as f64,
+            "Used {}.",
+            used
+        );
+    }
+
+    #[test]
+    fn too_small_atlas_reports_required_size() {
+        let voronoi = random_voronoi(200);
+        let error = voronoi.build_atlas((40, 40), 1).unwrap_err();
+        let required = match error {
+            AtlasError::TooSmall { overflow, required } => {
+                assert_eq!(required.0, 40);
+                assert_eq!(overflow, required.1 - 40);
+                required
+            }
+        };
+
+        // 200 slots of 3x3 pixels, 13 in each row
+        assert_eq!(required, (40, 16 * 3));
+        assert!(voronoi.build_atlas(required, 1).is_ok());
+
+        // slots are wider than the atlas
+        assert_eq!(
+            voronoi.build_atlas((2, 100), 1).unwrap_err(),
+            AtlasError::TooSmall {
+                overflow: 500,
+                required: (3, 600)
+            }
+        );
+    }
+
+    #[test]
+    fn degenerate_cells_get_single_pixels() {
+        let voronoi = random_voronoi(10);
+        let site = Point { x: 0.5, y: 0.5 };
+        let (origin, extent) = bounding_box(std::iter::empty(), &site);
+        assert_eq!(origin, site);
+        assert_eq!(slot_size(&extent, 100.0), (1, 1));
+
+        let empty = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(Vec::new())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(empty.build_atlas((16, 16), 1).is_ok());
+        assert!(voronoi.build_atlas((16, 16), 0).is_ok());
+    }
+}
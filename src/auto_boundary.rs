@@ -0,0 +1,403 @@
+use crate::{utils::cicumcenter, ConvexBoundary, ConvexPolygon, Engine, Point};
+
+/// How [crate::VoronoiBuilder::set_auto_boundary()] chooses the boundary of a diagram from its sites.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutoBoundary {
+    /// The bounding box of the sites, grown by ```padding``` on each side.
+    TightBox { padding: f64 },
+
+    /// The convex hull of the sites, with each of its edges moved ```margin``` outwards.
+    HullWithMargin { margin: f64 },
+
+    /// The bounding box of the sites, grown by the smallest padding for which at most ```target_fraction``` of the cells are clipped by it.
+    ///
+    /// Cells of sites on the convex hull of the sites are unbounded, so they are always clipped. If they alone are more than ```target_fraction``` of the cells,
+    /// the box is grown until only they are clipped, and the fraction achieved is reported in [BuildStats::clipped_fraction].
+    MinimalBias { target_fraction: f64 },
+}
+
+/// Statistics about how a diagram was built.
+///
+/// Use [crate::Voronoi::build_stats()] to get the statistics of a diagram.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildStats {
+    /// The vertices of the boundary chosen by [crate::VoronoiBuilder::set_auto_boundary()], or ```None``` if the boundary was set explicitly.
+    pub auto_boundary: Option<Vec<Point>>,
+
+    /// The fraction of cells clipped by the boundary chosen by [crate::VoronoiBuilder::set_auto_boundary()], before relaxation.
+    /// This is ```None``` if the boundary was set explicitly or the sites do not generate a valid diagram.
+    pub clipped_fraction: Option<f64>,
+}
+
+/// Chooses the boundary of ```sites``` for ```auto```.
+pub(crate) fn choose_boundary(
+    auto: &AutoBoundary,
+    sites: &[Point],
+    engine: Engine,
+) -> (ConvexPolygon, BuildStats) {
+    let cells = ClippedCells::new(sites, engine);
+    let boundary = match *auto {
+        AutoBoundary::TightBox { padding } => grown_box(sites, padding),
+        AutoBoundary::HullWithMargin { margin } => match &cells {
+            Some(cells) => grown_hull(sites, &cells.hull, margin),
+            None => grown_box(sites, margin),
+        },
+        AutoBoundary::MinimalBias { target_fraction } => match &cells {
+            Some(cells) => minimal_bias_box(sites, cells, target_fraction),
+            None => grown_box(sites, 0.0),
+        },
+    };
+
+    let stats = BuildStats {
+        auto_boundary: Some(boundary.vertices().to_vec()),
+        clipped_fraction: cells.map(|cells| cells.clipped_fraction(&boundary)),
+    };
+    (boundary, stats)
+}
+
+/// The Delaunay triangulation of the sites, to find which cells a boundary clips without building them.
+///
+/// A cell is clipped if its site is on the hull, since the cell is unbounded, or if any of its Voronoi vertices is outside of the boundary.
+struct ClippedCells {
+    circumcenters: Vec<Point>,
+    triangles: Vec<usize>,
+    hull: Vec<usize>,
+    site_count: usize,
+}
+
+impl ClippedCells {
+    fn new(sites: &[Point], engine: Engine) -> Option<Self> {
+        let triangulation = engine.triangulate(sites);
+        if triangulation.triangles.is_empty() {
+            return None;
+        }
+
+        let circumcenters = triangulation
+            .triangles
+            .chunks_exact(3)
+            .map(|t| cicumcenter(&sites[t[0]], &sites[t[1]], &sites[t[2]]))
+            .collect();
+        Some(Self {
+            circumcenters,
+            triangles: triangulation.triangles,
+            hull: triangulation.hull,
+            site_count: sites.len(),
+        })
+    }
+
+    fn clipped_fraction<T: ConvexBoundary>(&self, boundary: &T) -> f64 {
+        let mut clipped = vec![false; self.site_count];
+        for &site in &self.hull {
+            clipped[site] = true;
+        }
+        for (triangle, circumcenter) in self.circumcenters.iter().enumerate() {
+            if !boundary.is_inside(circumcenter) {
+                for &site in &self.triangles[3 * triangle..3 * triangle + 3] {
+                    clipped[site] = true;
+                }
+            }
+        }
+
+        clipped.iter().filter(|&&c| c).count() as f64 / self.site_count as f64
+    }
+}
+
+/// Gets the ```(min, max)``` corners of the bounding box of ```points```.
+fn bounds<'p>(points: impl Iterator<Item = &'p Point>) -> (Point, Point) {
+    points.fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    )
+}
+
+fn grown_box(sites: &[Point], padding: f64) -> ConvexPolygon {
+    let (min, max) = bounds(sites.iter());
+    let (left, top, right, bottom) = (
+        min.x - padding,
+        min.y - padding,
+        max.x + padding,
+        max.y + padding,
+    );
+
+    // same orientation as the vertices of a BoundingBox
+    ConvexPolygon::new(vec![
+        Point { x: left, y: top },
+        Point { x: left, y: bottom },
+        Point {
+            x: right,
+            y: bottom,
+        },
+        Point { x: right, y: top },
+    ])
+}
+
+fn grown_hull(sites: &[Point], hull: &[usize], margin: f64) -> ConvexPolygon {
+    let mut hull: Vec<&Point> = hull.iter().map(|&s| &sites[s]).collect();
+    let n = hull.len();
+    let doubled_area: f64 = (0..n)
+        .map(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    if doubled_area > 0.0 {
+        hull.reverse();
+    }
+
+    // outward unit normals of each edge; with the orientation of the boundary, the interior is on the right of the edges
+    let normals: Vec<Point> = (0..n)
+        .map(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % n]);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let length = dx.hypot(dy);
+            Point {
+                x: -dy / length,
+                y: dx / length,
+            }
+        })
+        .collect();
+
+    // each vertex moves to the intersection of the edges next to it after they are moved
+    let vertices: Vec<Point> = (0..n)
+        .map(|i| {
+            let (previous, next) = (&normals[(i + n - 1) % n], &normals[i]);
+            let scale = margin / (1.0 + previous.x * next.x + previous.y * next.y);
+            Point {
+                x: hull[i].x + scale * (previous.x + next.x),
+                y: hull[i].y + scale * (previous.y + next.y),
+            }
+        })
+        .collect();
+
+    ConvexPolygon::new(strictly_convex(vertices))
+}
+
+/// Removes vertices where the polygon does not turn, or turns the wrong way by rounding errors, so it can be used as a [ConvexPolygon].
+fn strictly_convex(mut vertices: Vec<Point>) -> Vec<Point> {
+    let mut i = 0;
+    while vertices.len() > 3 && i < vertices.len() {
+        let n = vertices.len();
+        let (a, b, c) = (
+            &vertices[(i + n - 1) % n],
+            &vertices[i],
+            &vertices[(i + 1) % n],
+        );
+        if robust::orient2d(a.into(), b.into(), c.into()) >= 0.0 {
+            vertices.remove(i);
+            // the previous vertex may not turn anymore
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+
+    vertices
+}
+
+fn minimal_bias_box(sites: &[Point], cells: &ClippedCells, target_fraction: f64) -> ConvexPolygon {
+    let (min, max) = bounds(sites.iter());
+    let size = (max.x - min.x).max(max.y - min.y);
+
+    // the padding each Voronoi vertex needs to be inside the box
+    let needed: Vec<f64> = cells
+        .circumcenters
+        .iter()
+        .map(|c| {
+            (min.x - c.x)
+                .max(min.y - c.y)
+                .max(c.x - max.x)
+                .max(c.y - max.y)
+                .max(0.0)
+        })
+        .collect();
+
+    // the clipped cells only change at these paddings, and only decrease as the box grows
+    let mut candidates = needed.clone();
+    candidates.push(0.0);
+    candidates.sort_by(f64::total_cmp);
+    candidates.dedup();
+
+    let fraction = |padding: f64| {
+        let mut clipped = vec![false; cells.site_count];
+        for &site in &cells.hull {
+            clipped[site] = true;
+        }
+        for (triangle, &needed) in needed.iter().enumerate() {
+            if needed > padding {
+                for &site in &cells.triangles[3 * triangle..3 * triangle + 3] {
+                    clipped[site] = true;
+                }
+            }
+        }
+
+        clipped.iter().filter(|&&c| c).count() as f64 / cells.site_count as f64
+    };
+    let chosen = candidates
+        .partition_point(|&padding| fraction(padding) > target_fraction)
+        .min(candidates.len() - 1);
+
+    // pad halfway to the next candidate, so no Voronoi vertex is close to the boundary
+    let padding = match candidates.get(chosen + 1) {
+        Some(next) => (candidates[chosen] + next) / 2.0,
+        None => candidates[chosen] + 0.1 * (candidates[chosen] + size),
+    };
+    grown_box(sites, padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_sites, BoundingBox, Voronoi, VoronoiBuilder};
+
+    fn clipped_fraction<T: ConvexBoundary>(voronoi: &Voronoi<T>) -> f64 {
+        voronoi.iter_cells().filter(|c| c.is_on_hull()).count() as f64
+            / voronoi.sites().len() as f64
+    }
+
+    #[test]
+    fn minimal_bias_reaches_target_fraction() {
+        let sites = random_sites(1_000);
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(sites.clone())
+            .set_auto_boundary(AutoBoundary::MinimalBias {
+                target_fraction: 0.1,
+            })
+            .build()
+            .expect("Some voronoi expected");
+
+        // no site was removed
+        assert_eq!(voronoi.sites(), &sites);
+        assert!(sites.iter().all(|p| voronoi.boundary().is_inside(p)));
+
+        let stats = voronoi.build_stats();
+        assert_eq!(
+            stats.auto_boundary.as_deref(),
+            Some(voronoi.boundary().vertices())
+        );
+        let fraction = stats.clipped_fraction.expect("Some fraction expected");
+        assert!(fraction <= 0.1, "Clipped fraction {}.", fraction);
+        assert_eq!(fraction, clipped_fraction(&voronoi));
+
+        // a tight box clips at least as many cells
+        let tight = VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_auto_boundary(AutoBoundary::TightBox { padding: 0.0 })
+            .build()
+            .expect("Some voronoi expected");
+        assert!(tight.build_stats().clipped_fraction.unwrap() >= fraction);
+    }
+
+    #[test]
+    fn unreachable_target_clips_only_hull_cells() {
+        let sites = random_sites(100);
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_auto_boundary(AutoBoundary::MinimalBias {
+                target_fraction: 0.0,
+            })
+            .build()
+            .expect("Some voronoi expected");
+
+        let hull = voronoi.triangulation().hull.len() as f64 / 100.0;
+        assert_eq!(voronoi.build_stats().clipped_fraction, Some(hull));
+        assert_eq!(clipped_fraction(&voronoi), hull);
+    }
+
+    #[test]
+    fn boxes_and_hulls_contain_sites() {
+        let sites = random_sites(200);
+        for auto in [
+            AutoBoundary::TightBox { padding: 0.0 },
+            AutoBoundary::TightBox { padding: 0.5 },
+            AutoBoundary::HullWithMargin { margin: 0.0 },
+            AutoBoundary::HullWithMargin { margin: 0.1 },
+        ] {
+            let voronoi = VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_auto_boundary(auto)
+                .build()
+                .expect("Some voronoi expected");
+            assert_eq!(voronoi.sites().len(), sites.len(), "{:?}", auto);
+            assert!(sites.iter().all(|p| voronoi.boundary().is_inside(p)));
+            assert_eq!(
+                voronoi.build_stats().clipped_fraction,
+                Some(clipped_fraction(&voronoi))
+            );
+        }
+
+        // the margin is the distance from the hull to the edges of the boundary
+        let (boundary, _) = choose_boundary(
+            &AutoBoundary::HullWithMargin { margin: 0.1 },
+            &sites,
+            Engine::default(),
+        );
+        let vertices = boundary.vertices();
+        for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let closest = sites
+                .iter()
+                .map(|p| ((p.x - a.x) * dy - (p.y - a.y) * dx).abs() / dx.hypot(dy))
+                .fold(f64::INFINITY, f64::min);
+            assert!((closest - 0.1).abs() < 1e-9, "Distance {}.", closest);
+        }
+    }
+
+    #[test]
+    fn boundary_is_chosen_before_relaxation() {
+        let sites = random_sites(300);
+        let auto = AutoBoundary::MinimalBias {
+            target_fraction: 0.2,
+        };
+        let unrelaxed = VoronoiBuilder::default()
+            .set_sites(sites.clone())
+            .set_auto_boundary(auto)
+            .build()
+            .expect("Some voronoi expected");
+        let relaxed = VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_auto_boundary(auto)
+            .set_lloyd_relaxation_iterations(3)
+            .build()
+            .expect("Some voronoi expected");
+
+        assert_eq!(relaxed.build_stats(), unrelaxed.build_stats());
+        assert_eq!(
+            relaxed.boundary().vertices(),
+            unrelaxed.boundary().vertices()
+        );
+        assert_eq!(relaxed.sites().len(), 300);
+        assert!(relaxed
+            .sites()
+            .iter()
+            .all(|p| relaxed.boundary().is_inside(p)));
+    }
+
+    #[test]
+    fn explicit_boundaries_have_no_stats() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_sites(20))
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(voronoi.build_stats(), &BuildStats::default());
+    }
+}
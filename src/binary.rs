@@ -0,0 +1,660 @@
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    io::{self, BufReader, BufWriter, Read, Write},
+    sync::OnceLock,
+};
+
+use delaunator::{next_halfedge, Triangulation, EMPTY};
+
+use crate::{
+    diagnostics::DiagnosticsCache,
+    editing::{next_generation, RebuildOptions},
+    transaction::TransactionFlag,
+    AnyBoundary, BoundingBox, BuildStats, ClipBehavior, ConvexBoundary, ConvexPolygon, Engine,
+    Point, Voronoi,
+};
+
+/// Identifies the binary format, see [Voronoi::write_binary()].
+const MAGIC: [u8; 4] = *b"VRNC";
+
+/// Current version of the binary format.
+const VERSION: u32 = 1;
+
+/// Header flag set when the payload is compressed with zstd.
+const FLAG_ZSTD: u32 = 1;
+
+/// Encoding of [EMPTY] for indices.
+const EMPTY_INDEX: u32 = u32::MAX;
+
+/// Upper bound of elements preallocated when reading, so corrupted counts do not trigger huge allocations before the data is actually read.
+const MAX_PREALLOCATION: usize = 1 << 20;
+
+/// An error returned when reading a diagram with [Voronoi::read_binary()].
+#[derive(Debug)]
+pub enum DecodeError {
+    /// An I/O error other than reaching the end of the input.
+    Io(io::Error),
+
+    /// The input ended before the whole diagram was read.
+    Truncated,
+
+    /// The input does not start with the expected magic bytes.
+    InvalidMagic,
+
+    /// The format version is not supported by this version of the crate.
+    UnsupportedVersion(u32),
+
+    /// The payload is compressed, but the ```zstd``` feature is not enabled.
+    UnsupportedCompression,
+
+    /// The boundary stored in the input is not a [BoundingBox].
+    UnsupportedBoundary,
+
+    /// The input has inconsistent counts, indices out of bounds or a triangulation whose edges around a site cannot be walked.
+    Corrupted(&'static str),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Io(e) => write!(f, "I/O error: {}", e),
+            DecodeError::Truncated => write!(f, "Input is truncated"),
+            DecodeError::InvalidMagic => write!(f, "Input is not a voronoi diagram"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "Unsupported format version {}", v),
+            DecodeError::UnsupportedCompression => {
+                write!(f, "Input is compressed but the zstd feature is not enabled")
+            }
+            DecodeError::UnsupportedBoundary => write!(f, "Boundary is not a bounding box"),
+            DecodeError::Corrupted(reason) => write!(f, "Input is corrupted: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            DecodeError::Truncated
+        } else {
+            DecodeError::Io(e)
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Writes this diagram in a flat binary format, which can be read back with [Voronoi::read_binary()].
+    ///
+    /// The format starts with a header made of the magic bytes ```VRNC```, a format version and flags, followed by the counts of each section and the sections themselves:
+    /// boundary vertices, sites, Delaunay triangles, half-edges and hull, the left-most incoming half-edge of each site, Voronoi vertices, cell offsets and cell vertex indices.
+    /// Numbers are stored in little-endian; coordinates as ```f64``` and indices as ```u32```.
+    ///
+    /// Build warnings, build statistics and edit history are not written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [io::ErrorKind::InvalidInput] if the diagram has more elements than can be indexed by a ```u32```.
+    pub fn write_binary(&self, w: impl Write) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        write_header(&mut w, 0)?;
+        self.write_payload(&mut w)?;
+        w.flush()
+    }
+
+    /// Same as [Self::write_binary()], but compresses everything after the header with zstd at the given compression ```level```.
+    #[cfg(feature = "zstd")]
+    pub fn write_binary_compressed(&self, w: impl Write, level: i32) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        write_header(&mut w, FLAG_ZSTD)?;
+        let mut encoder = zstd::stream::write::Encoder::new(w, level)?;
+        self.write_payload(&mut encoder)?;
+        encoder.finish()?.flush()
+    }
+
+    fn write_payload(&self, w: &mut impl Write) -> io::Result<()> {
+        let triangulation = &self.triangulation;
+        let cell_indices: usize = self.cells.iter().map(|c| c.len()).sum();
+
+        w.write_all(&[clip_behavior_to_byte(self.clip_behavior)])?;
+        w.write_all(&self.adjacency_epsilon.to_le_bytes())?;
+        for count in [
+            self.boundary.vertices().len(),
+            self.sites.len(),
+            triangulation.triangles.len(),
+            triangulation.hull.len(),
+            self.circumcenters.len(),
+            cell_indices,
+        ] {
+            write_count(w, count)?;
+        }
+
+        write_points(w, self.boundary.vertices())?;
+        write_points(w, &self.sites)?;
+        write_indices(w, &triangulation.triangles)?;
+        write_indices(w, &triangulation.halfedges)?;
+        write_indices(w, &triangulation.hull)?;
+        write_indices(w, &self.site_to_incoming_leftmost_halfedge)?;
+        write_points(w, &self.circumcenters)?;
+
+        let mut offset = 0;
+        write_count(w, offset)?;
+        for cell in &self.cells {
+            offset += cell.len();
+            write_count(w, offset)?;
+        }
+        for cell in &self.cells {
+            write_indices(w, cell)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Voronoi<BoundingBox> {
+    /// Reads a diagram written by [Self::write_binary()].
+    ///
+    /// All counts and indices are validated, so truncated or corrupted input results in an error rather than a panic or an invalid diagram.
+    /// Only diagrams bounded by a [BoundingBox] can be read, use [Voronoi::read_binary_dyn()] to read diagrams with any boundary.
+    /// The triangulation engine is not stored, so edits to the diagram read use [Engine::default()].
+    pub fn read_binary(r: impl Read) -> Result<Voronoi<BoundingBox>, DecodeError> {
+        read_diagram(r, |vertices| bounding_box_from_vertices(&vertices))
+    }
+}
+
+impl Voronoi<AnyBoundary> {
+    /// Reads a diagram written by [Self::write_binary()], with any of the built-in boundaries.
+    ///
+    /// The boundary read is a [AnyBoundary::BoundingBox] if its vertices are those of a box, as written for diagrams bounded by a [BoundingBox],
+    /// and a [AnyBoundary::ConvexPolygon] otherwise. The input is validated as by [Voronoi::read_binary()].
+    pub fn read_binary_dyn(r: impl Read) -> Result<Voronoi<AnyBoundary>, DecodeError> {
+        read_diagram(r, |vertices| match bounding_box_from_vertices(&vertices) {
+            Ok(boundary) => Ok(boundary.into()),
+            Err(_) if ConvexPolygon::is_convex_ccw(&vertices) => {
+                Ok(ConvexPolygon::new(vertices).into())
+            }
+            Err(_) => Err(DecodeError::Corrupted(
+                "boundary is not convex and counter-clockwise",
+            )),
+        })
+    }
+}
+
+/// Reads the header and the payload of a diagram, building its boundary from the boundary vertices read with ```boundary```.
+fn read_diagram<T: ConvexBoundary>(
+    r: impl Read,
+    boundary: impl FnOnce(Vec<Point>) -> Result<T, DecodeError>,
+) -> Result<Voronoi<T>, DecodeError> {
+    let mut r = BufReader::new(r);
+
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(DecodeError::InvalidMagic);
+    }
+
+    let version = read_u32(&mut r)?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let flags = read_u32(&mut r)?;
+    if flags & !FLAG_ZSTD != 0 {
+        return Err(DecodeError::Corrupted("unknown flags"));
+    }
+
+    if flags & FLAG_ZSTD != 0 {
+        #[cfg(feature = "zstd")]
+        return read_payload(&mut zstd::stream::read::Decoder::with_buffer(r)?, boundary);
+
+        #[cfg(not(feature = "zstd"))]
+        return Err(DecodeError::UnsupportedCompression);
+    }
+
+    read_payload(&mut r, boundary)
+}
+
+fn read_payload<T: ConvexBoundary>(
+    r: &mut impl Read,
+    boundary: impl FnOnce(Vec<Point>) -> Result<T, DecodeError>,
+) -> Result<Voronoi<T>, DecodeError> {
+    let mut byte = [0; 1];
+    r.read_exact(&mut byte)?;
+    let clip_behavior = clip_behavior_from_byte(byte[0])?;
+    let adjacency_epsilon = read_f64(r)?;
+
+    let boundary_count = read_count(r)?;
+    let site_count = read_count(r)?;
+    let halfedge_count = read_count(r)?;
+    let hull_count = read_count(r)?;
+    let vertex_count = read_count(r)?;
+    let cell_index_count = read_count(r)?;
+    if halfedge_count % 3 != 0 {
+        return Err(DecodeError::Corrupted(
+            "half-edge count is not a multiple of 3",
+        ));
+    }
+    if vertex_count < halfedge_count / 3 {
+        return Err(DecodeError::Corrupted("fewer vertices than triangles"));
+    }
+
+    let boundary = boundary(read_points(r, boundary_count)?)?;
+    let sites = read_points(r, site_count)?;
+    let triangles = read_indices(r, halfedge_count, site_count, false)?;
+    let halfedges = read_indices(r, halfedge_count, halfedge_count, true)?;
+    if halfedges
+        .iter()
+        .enumerate()
+        .any(|(e, &o)| o != EMPTY && halfedges[o] != e)
+    {
+        return Err(DecodeError::Corrupted("half-edges are not symmetric"));
+    }
+    let hull = read_indices(r, hull_count, site_count, false)?;
+    let site_to_incoming_leftmost_halfedge = read_indices(r, site_count, halfedge_count, true)?;
+    if !edges_around_sites_end(&triangles, &halfedges, &site_to_incoming_leftmost_halfedge) {
+        return Err(DecodeError::Corrupted(
+            "edges around a site are not incoming to it",
+        ));
+    }
+    let circumcenters = read_points(r, vertex_count)?;
+
+    let offsets = read_indices(r, site_count + 1, cell_index_count + 1, false)?;
+    if offsets[0] != 0
+        || offsets[site_count] != cell_index_count
+        || offsets.windows(2).any(|w| w[0] > w[1])
+    {
+        return Err(DecodeError::Corrupted("cell offsets are inconsistent"));
+    }
+    let cells = offsets
+        .windows(2)
+        .map(|w| read_indices(r, w[1] - w[0], vertex_count, false))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let generation = next_generation();
+    Ok(Voronoi {
+        clipped_vertices: circumcenters.len().saturating_sub(triangles.len() / 3),
+        clipped_cells: None,
+        sites,
+        boundary,
+        triangulation: Triangulation {
+            triangles,
+            halfedges,
+            hull,
+        },
+        clip_behavior,
+        adjacency_epsilon,
+        engine: Engine::default(),
+        circumcenters,
+        site_to_incoming_leftmost_halfedge,
+        cells,
+        generation,
+        origin_generation: generation,
+        cell_generations: vec![generation; site_count],
+        edit_log: Vec::new(),
+        rebuild_options: RebuildOptions::default(),
+        warnings: Vec::new(),
+        stats: BuildStats::default(),
+        in_transaction: TransactionFlag::default(),
+        input_fingerprint: 0,
+        fan_indices: OnceLock::new(),
+        symmetry: None,
+        diagnostics: DiagnosticsCache::default(),
+    })
+}
+
+/// Whether walking the incoming edges around each site from ```incoming```, as the iterators over the cells do, ends on every site
+/// and only visits edges incoming to that site.
+///
+/// Each half-edge is incoming to a single site, so all the walks visit at most as many edges as there are.
+fn edges_around_sites_end(triangles: &[usize], halfedges: &[usize], incoming: &[usize]) -> bool {
+    let mut remaining = halfedges.len();
+    incoming.iter().enumerate().all(|(site, &start)| {
+        let mut edge = start;
+        while edge != EMPTY {
+            if remaining == 0 || triangles[next_halfedge(edge)] != site {
+                return false;
+            }
+            remaining -= 1;
+
+            edge = halfedges[next_halfedge(edge)];
+            if edge == start {
+                break;
+            }
+        }
+        true
+    })
+}
+
+fn bounding_box_from_vertices(vertices: &[Point]) -> Result<BoundingBox, DecodeError> {
+    match vertices {
+        [left_top, left_bottom, right_bottom, right_top]
+            if left_top.x == left_bottom.x
+                && right_top.x == right_bottom.x
+                && left_top.y == right_top.y
+                && left_bottom.y == right_bottom.y
+                && left_top.x <= right_top.x
+                && left_top.y <= left_bottom.y =>
+        {
+            Ok(BoundingBox::from_edges(
+                left_top.x,
+                left_top.y,
+                right_bottom.x,
+                right_bottom.y,
+            ))
+        }
+        _ => Err(DecodeError::UnsupportedBoundary),
+    }
+}
+
+fn clip_behavior_to_byte(clip_behavior: ClipBehavior) -> u8 {
+    match clip_behavior {
+        ClipBehavior::None => 0,
+        ClipBehavior::RemoveSitesOutsideBoundaryOnly => 1,
+        ClipBehavior::Clip => 2,
+    }
+}
+
+fn clip_behavior_from_byte(byte: u8) -> Result<ClipBehavior, DecodeError> {
+    match byte {
+        0 => Ok(ClipBehavior::None),
+        1 => Ok(ClipBehavior::RemoveSitesOutsideBoundaryOnly),
+        2 => Ok(ClipBehavior::Clip),
+        _ => Err(DecodeError::Corrupted("unknown clip behavior")),
+    }
+}
+
+fn write_header(w: &mut impl Write, flags: u32) -> io::Result<()> {
+    w.write_all(&MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    w.write_all(&flags.to_le_bytes())
+}
+
+fn write_count(w: &mut impl Write, count: usize) -> io::Result<()> {
+    w.write_all(&to_index(count)?.to_le_bytes())
+}
+
+fn write_indices(w: &mut impl Write, indices: &[usize]) -> io::Result<()> {
+    for &i in indices {
+        let i = if i == EMPTY {
+            EMPTY_INDEX
+        } else {
+            to_index(i)?
+        };
+        w.write_all(&i.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn write_points(w: &mut impl Write, points: &[Point]) -> io::Result<()> {
+    for p in points {
+        w.write_all(&p.x.to_le_bytes())?;
+        w.write_all(&p.y.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn to_index(i: usize) -> io::Result<u32> {
+    match u32::try_from(i) {
+        Ok(i) if i != EMPTY_INDEX => Ok(i),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Diagram is too large for the binary format",
+        )),
+    }
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32, DecodeError> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64(r: &mut impl Read) -> Result<f64, DecodeError> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn read_count(r: &mut impl Read) -> Result<usize, DecodeError> {
+    match read_u32(r)? {
+        EMPTY_INDEX => Err(DecodeError::Corrupted("invalid count")),
+        count => Ok(count as usize),
+    }
+}
+
+/// Reads ```count``` indices, validating they are less than ```bound```, or [EMPTY] if ```allow_empty```.
+fn read_indices(
+    r: &mut impl Read,
+    count: usize,
+    bound: usize,
+    allow_empty: bool,
+) -> Result<Vec<usize>, DecodeError> {
+    let mut indices = Vec::with_capacity(count.min(MAX_PREALLOCATION));
+    for _ in 0..count {
+        let i = match read_u32(r)? {
+            EMPTY_INDEX if allow_empty => EMPTY,
+            i if (i as usize) < bound => i as usize,
+            _ => return Err(DecodeError::Corrupted("index out of bounds")),
+        };
+        indices.push(i);
+    }
+
+    Ok(indices)
+}
+
+fn read_points(r: &mut impl Read, count: usize) -> Result<Vec<Point>, DecodeError> {
+    let mut points = Vec::with_capacity(count.min(MAX_PREALLOCATION));
+    for _ in 0..count {
+        let x = read_f64(r)?;
+        let y = read_f64(r)?;
+        points.push(Point { x, y });
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, VoronoiBuilder};
+
+    /// A diagram of random sites in a boundary off the origin, with a non-default adjacency epsilon, so both must round-trip.
+    fn offset_random_voronoi(size: usize) -> Voronoi<BoundingBox> {
+        VoronoiBuilder::default()
+            .set_sites(utils::test::random_sites(size))
+            .set_boundary(BoundingBox::new(Point { x: 0.1, y: -0.3 }, 1.7, 1.9))
+            .set_adjacency_epsilon(1e-9)
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    fn encode(voronoi: &Voronoi<BoundingBox>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        voronoi
+            .write_binary(&mut buffer)
+            .expect("Write expected to succeed");
+        buffer
+    }
+
+    #[test]
+    fn round_trip_preserves_geometry() {
+        let voronoi = offset_random_voronoi(1_000);
+        let decoded =
+            Voronoi::read_binary(encode(&voronoi).as_slice()).expect("Read expected to succeed");
+
+        assert!(utils::test::geometry_eq(&voronoi, &decoded));
+        assert_eq!(
+            utils::test::geometry_hash(&voronoi),
+            utils::test::geometry_hash(&decoded)
+        );
+        assert_eq!(decoded.adjacency_epsilon(), voronoi.adjacency_epsilon());
+        assert_eq!(
+            decoded.triangulation().halfedges,
+            voronoi.triangulation().halfedges
+        );
+        assert_eq!(decoded.triangulation().hull, voronoi.triangulation().hull);
+        assert_eq!(decoded.boundary().vertices(), voronoi.boundary().vertices());
+        assert_eq!(decoded.adjacency(), voronoi.adjacency());
+    }
+
+    #[test]
+    fn truncated_input_fails_gracefully() {
+        let buffer = encode(&offset_random_voronoi(50));
+        for len in (0..buffer.len()).step_by(7) {
+            assert!(
+                matches!(
+                    Voronoi::read_binary(&buffer[..len]),
+                    Err(DecodeError::Truncated)
+                ),
+                "Read of {} bytes must fail as truncated.",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted_input_fails_gracefully() {
+        let buffer = encode(&offset_random_voronoi(50));
+
+        let mut invalid_magic = buffer.clone();
+        invalid_magic[0] = b'X';
+        assert!(matches!(
+            Voronoi::read_binary(invalid_magic.as_slice()),
+            Err(DecodeError::InvalidMagic)
+        ));
+
+        let mut invalid_version = buffer.clone();
+        invalid_version[4] = 2;
+        assert!(matches!(
+            Voronoi::read_binary(invalid_version.as_slice()),
+            Err(DecodeError::UnsupportedVersion(2))
+        ));
+
+        // flip every byte after the header, one at a time, none must panic
+        let mut rng = utils::test::rng();
+        for i in 12..buffer.len() {
+            let mut corrupted = buffer.clone();
+            corrupted[i] ^= rng.gen_range(1..=255);
+            let _ = Voronoi::read_binary(corrupted.as_slice());
+        }
+
+        // the first index of the first cell is out of bounds
+        let mut out_of_bounds = buffer;
+        let first_cell_index = out_of_bounds.len() - 4 * cell_index_count(&out_of_bounds);
+        out_of_bounds[first_cell_index..first_cell_index + 4]
+            .copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+        assert!(matches!(
+            Voronoi::read_binary(out_of_bounds.as_slice()),
+            Err(DecodeError::Corrupted(_))
+        ));
+    }
+
+    #[test]
+    fn edges_around_sites_are_validated() {
+        let voronoi = offset_random_voronoi(50);
+        let triangulation = voronoi.triangulation();
+        let incoming = &voronoi.site_to_incoming_leftmost_halfedge;
+        assert!(edges_around_sites_end(
+            &triangulation.triangles,
+            &triangulation.halfedges,
+            incoming
+        ));
+
+        // the walk around the first site starts from an edge of the second, so it would return the cell of another site
+        let mut swapped = voronoi.clone();
+        swapped.site_to_incoming_leftmost_halfedge.swap(0, 1);
+        assert!(matches!(
+            Voronoi::read_binary(encode(&swapped).as_slice()),
+            Err(DecodeError::Corrupted(_))
+        ));
+
+        // the walk around an interior site is diverted across the opposite edge of its first triangle, keeping the half-edges symmetric
+        let (first, other) = (0..50)
+            .filter(|&s| !voronoi.cell(s).is_on_hull())
+            .map(|s| next_halfedge(incoming[s]))
+            .map(|first| (first, next_halfedge(first)))
+            .find(|&(_, other)| triangulation.halfedges[other] != EMPTY)
+            .expect("Some interior triangle expected");
+        let mut diverted = voronoi.clone();
+        let halfedges = &mut diverted.triangulation.halfedges;
+        let (twin, other_twin) = (halfedges[first], halfedges[other]);
+        halfedges[first] = other_twin;
+        halfedges[other_twin] = first;
+        halfedges[other] = twin;
+        halfedges[twin] = other;
+        assert!(matches!(
+            Voronoi::read_binary(encode(&diverted).as_slice()),
+            Err(DecodeError::Corrupted(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "zstd"))]
+    fn compressed_input_needs_zstd() {
+        let mut buffer = encode(&offset_random_voronoi(10));
+        buffer[8] |= FLAG_ZSTD as u8;
+        assert!(matches!(
+            Voronoi::read_binary(buffer.as_slice()),
+            Err(DecodeError::UnsupportedCompression)
+        ));
+    }
+
+    /// Reads the number of cell indices from the header of an encoded diagram.
+    fn cell_index_count(buffer: &[u8]) -> usize {
+        // header, clip behavior, adjacency epsilon and the 5 counts before the cell index count
+        let start = 12 + 1 + 8 + 5 * 4;
+        u32::from_le_bytes([
+            buffer[start],
+            buffer[start + 1],
+            buffer[start + 2],
+            buffer[start + 3],
+        ]) as usize
+    }
+
+    #[test]
+    fn unsupported_boundary_is_rejected() {
+        let voronoi = VoronoiBuilder::default()
+            .generate_square_sites(5)
+            .set_boundary(crate::ConvexPolygon::new(vec![
+                Point { x: -1.0, y: 0.0 },
+                Point { x: 0.0, y: 1.0 },
+                Point { x: 1.0, y: 0.0 },
+                Point { x: 0.0, y: -1.0 },
+            ]))
+            .build()
+            .expect("Some voronoi expected");
+        let mut buffer = Vec::new();
+        voronoi
+            .write_binary(&mut buffer)
+            .expect("Write expected to succeed");
+
+        assert!(matches!(
+            Voronoi::read_binary(buffer.as_slice()),
+            Err(DecodeError::UnsupportedBoundary)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn compressed_round_trip_preserves_geometry() {
+        let voronoi = offset_random_voronoi(1_000);
+        let mut buffer = Vec::new();
+        voronoi
+            .write_binary_compressed(&mut buffer, 3)
+            .expect("Write expected to succeed");
+        let decoded = Voronoi::read_binary(buffer.as_slice()).expect("Read expected to succeed");
+
+        assert!(utils::test::geometry_eq(&voronoi, &decoded));
+    }
+}
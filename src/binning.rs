@@ -0,0 +1,323 @@
+use crate::{utils::clip_half_plane, ConvexBoundary, Point, Voronoi, VoronoiCell};
+
+/// Fraction of the area of a bin below which an overlap is left by rounding, where an edge of the cell lies on an edge of the bin, and is dropped.
+const SLIVER_TOLERANCE: f64 = 1e-12;
+
+impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
+    /// Gets the exact area of the intersection of this cell with the axis-aligned rectangle from ```min``` to ```max```.
+    ///
+    /// The area is 0.0 if the cell and the rectangle do not overlap, or if ```max``` is not greater than ```min``` on both axes.
+    /// As for [Self::area()], the cells in the hull of a diagram built with clipping disabled are the polygons formed by their vertices.
+    pub fn intersection_area_rect(&self, min: &Point, max: &Point) -> f64 {
+        let polygon: Vec<Point> = self.iter_vertices().cloned().collect();
+        polygon_area(&clip_rect(&polygon, min, max))
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Splits the axis-aligned rectangle around the boundary of this diagram into a grid of ```cols``` by ```rows``` bins of equal size,
+    /// and gets, for each bin, the cells overlapping it along with the exact area of their overlap.
+    ///
+    /// The bin of column ```col``` and row ```row``` is at index ```row * cols + col```, with columns growing with X and rows with Y, from the corner of least X and Y.
+    /// The cells of each bin are in ascending order of site, and only cells overlapping the bin by a positive area are listed,
+    /// so the bins outside of the boundary are empty and the areas of each bin add up to the area of its intersection with the boundary.
+    /// Overlaps of less than a 1e-12 fraction of the area of the bin are rounding errors of cells only touching it, and are not listed.
+    ///
+    /// Each cell is only clipped to the bins its bounding rectangle overlaps, rather than to every bin.
+    /// No bins are returned if ```cols``` or ```rows``` is 0.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // the 2x2 square boundary is split into 4 bins of area 1, each fully covered by the cells overlapping it
+    /// let bins = voronoi.bin_areas(2, 2);
+    /// assert_eq!(bins.len(), 4);
+    /// for bin in bins {
+    ///     let covered: f64 = bin.iter().map(|&(_, area)| area).sum();
+    ///     assert!((covered - 1.0).abs() < 1e-9);
+    /// }
+    ///```
+    pub fn bin_areas(&self, cols: usize, rows: usize) -> Vec<Vec<(usize, f64)>> {
+        if cols == 0 || rows == 0 {
+            return Vec::new();
+        }
+
+        let (min, max) = bounds(self.boundary.vertices());
+        let sliver = SLIVER_TOLERANCE * (max.x - min.x) * (max.y - min.y) / (cols * rows) as f64;
+        let mut bins = vec![Vec::new(); cols * rows];
+        for cell in self.iter_cells() {
+            let polygon: Vec<Point> = cell.iter_vertices().cloned().collect();
+            if polygon.len() < 3 {
+                continue;
+            }
+
+            let (cell_min, cell_max) = bounds(&polygon);
+            let first_col = bin_index(cell_min.x, min.x, max.x, cols);
+            let last_col = bin_index(cell_max.x, min.x, max.x, cols);
+            let first_row = bin_index(cell_min.y, min.y, max.y, rows);
+            let last_row = bin_index(cell_max.y, min.y, max.y, rows);
+            for row in first_row..=last_row {
+                for col in first_col..=last_col {
+                    let bin_min = Point {
+                        x: bin_edge(col, min.x, max.x, cols),
+                        y: bin_edge(row, min.y, max.y, rows),
+                    };
+                    let bin_max = Point {
+                        x: bin_edge(col + 1, min.x, max.x, cols),
+                        y: bin_edge(row + 1, min.y, max.y, rows),
+                    };
+                    let area = polygon_area(&clip_rect(&polygon, &bin_min, &bin_max));
+                    if area > sliver {
+                        bins[row * cols + col].push((cell.site(), area));
+                    }
+                }
+            }
+        }
+
+        bins
+    }
+}
+
+/// Gets the coordinate of the edge ```i``` of ```n``` bins from ```min``` to ```max```, exactly ```max``` for the last one so that no gap is left by rounding.
+fn bin_edge(i: usize, min: f64, max: f64, n: usize) -> f64 {
+    if i == n {
+        max
+    } else {
+        min + (max - min) * i as f64 / n as f64
+    }
+}
+
+/// Gets the bin of ```n``` from ```min``` to ```max``` that coordinate ```x``` falls in, clamped to the bins.
+fn bin_index(x: f64, min: f64, max: f64, n: usize) -> usize {
+    let i = ((x - min) / (max - min) * n as f64).floor();
+    if i > 0.0 {
+        (i as usize).min(n - 1)
+    } else {
+        0
+    }
+}
+
+/// Gets the corners of least and greatest coordinates of the axis-aligned rectangle around ```points```.
+fn bounds(points: &[Point]) -> (Point, Point) {
+    points.iter().fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    )
+}
+
+/// Clips the convex ```polygon``` to the axis-aligned rectangle from ```min``` to ```max```.
+fn clip_rect(polygon: &[Point], min: &Point, max: &Point) -> Vec<Point> {
+    let clipped = clip_half_plane(polygon, |p| p.x - min.x);
+    let clipped = clip_half_plane(&clipped, |p| max.x - p.x);
+    let clipped = clip_half_plane(&clipped, |p| p.y - min.y);
+    clip_half_plane(&clipped, |p| max.y - p.y)
+}
+
+fn polygon_area(vertices: &[Point]) -> f64 {
+    let doubled: f64 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+
+    doubled.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        raster::Rasterizer, utils, utils::test::random_voronoi, ConvexPolygon, FitMode,
+        VoronoiBuilder,
+    };
+
+    /// A diagram of the random sites of [utils::test::random_sites()] inside ```boundary```.
+    fn random_voronoi_within<T: ConvexBoundary>(boundary: T, size: usize) -> Voronoi<T> {
+        let sites = utils::test::random_sites(size)
+            .into_iter()
+            .filter(|p| boundary.is_inside(p))
+            .collect();
+        VoronoiBuilder::default()
+            .set_boundary(boundary)
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    /// Gets the area of the intersection of the boundary of ```voronoi``` with the bin of index ```bin```.
+    fn bin_boundary_area<T: ConvexBoundary>(
+        voronoi: &Voronoi<T>,
+        cols: usize,
+        rows: usize,
+        bin: usize,
+    ) -> f64 {
+        let (min, max) = bounds(voronoi.boundary().vertices());
+        let (col, row) = (bin % cols, bin / cols);
+        polygon_area(&clip_rect(
+            voronoi.boundary().vertices(),
+            &Point {
+                x: bin_edge(col, min.x, max.x, cols),
+                y: bin_edge(row, min.y, max.y, rows),
+            },
+            &Point {
+                x: bin_edge(col + 1, min.x, max.x, cols),
+                y: bin_edge(row + 1, min.y, max.y, rows),
+            },
+        ))
+    }
+
+    #[test]
+    fn intersection_area_rect_of_whole_and_disjoint_rect() {
+        let voronoi = random_voronoi(50);
+        for cell in voronoi.iter_cells() {
+            let whole =
+                cell.intersection_area_rect(&Point { x: -2.0, y: -2.0 }, &Point { x: 2.0, y: 2.0 });
+            assert!(utils::abs_diff_eq(whole, cell.area(), 1e-12));
+            assert_eq!(
+                cell.intersection_area_rect(&Point { x: 1.0, y: 1.0 }, &Point { x: 2.0, y: 2.0 }),
+                0.0
+            );
+            assert_eq!(
+                cell.intersection_area_rect(&Point { x: 0.5, y: 0.5 }, &Point { x: -0.5, y: -0.5 }),
+                0.0
+            );
+        }
+    }
+
+    #[test]
+    fn bin_areas_are_conserved() {
+        let (cols, rows) = (7, 5);
+        let square = random_voronoi(300);
+        let hexagon = random_voronoi_within(ConvexPolygon::regular(6, 1.0), 300);
+        let check =
+            |bins: Vec<Vec<(usize, f64)>>, bin_area: &dyn Fn(usize) -> f64, cells: Vec<f64>| {
+                assert_eq!(bins.len(), cols * rows);
+                let mut cell_areas = vec![0.0; cells.len()];
+                for (i, bin) in bins.iter().enumerate() {
+                    assert!(bin.windows(2).all(|w| w[0].0 < w[1].0));
+                    let total: f64 = bin.iter().map(|&(_, area)| area).sum();
+                    assert!(
+                        utils::abs_diff_eq(total, bin_area(i), 1e-9),
+                        "Bin {} holds {} of area {}",
+                        i,
+                        total,
+                        bin_area(i)
+                    );
+                    for &(site, area) in bin {
+                        cell_areas[site] += area;
+                    }
+                }
+
+                // every cell is split among the bins it overlaps
+                for (split, area) in cell_areas.iter().zip(cells) {
+                    assert!(utils::abs_diff_eq(*split, area, 1e-9));
+                }
+            };
+
+        check(
+            square.bin_areas(cols, rows),
+            &|bin| bin_boundary_area(&square, cols, rows, bin),
+            square.iter_cells().map(|c| c.area()).collect(),
+        );
+        check(
+            hexagon.bin_areas(cols, rows),
+            &|bin| bin_boundary_area(&hexagon, cols, rows, bin),
+            hexagon.iter_cells().map(|c| c.area()).collect(),
+        );
+        assert!(square.bin_areas(0, rows).is_empty());
+    }
+
+    #[test]
+    fn bin_areas_agree_with_rasterization() {
+        let (bins_per_side, pixels_per_bin) = (4, 100);
+        let pixels = bins_per_side * pixels_per_bin;
+        let voronoi = random_voronoi(100);
+        let mut rasterizer = Rasterizer::new(pixels, pixels);
+        rasterizer.set_view_transform(
+            &voronoi.view_transform((pixels as u32, pixels as u32), FitMode::Contain),
+        );
+        rasterizer.render(&voronoi, |site| site as u32);
+
+        let pixel_area = (2.0 / pixels as f64).powi(2);
+        let mut counts = vec![vec![0.0; voronoi.sites().len()]; bins_per_side * bins_per_side];
+        for y in 0..pixels {
+            for x in 0..pixels {
+                let site = rasterizer.owner(x, y).expect("Every pixel covered");
+                counts[(y / pixels_per_bin) * bins_per_side + x / pixels_per_bin][site] +=
+                    pixel_area;
+            }
+        }
+
+        let bins = voronoi.bin_areas(bins_per_side, bins_per_side);
+        for (bin, counts) in bins.iter().zip(counts) {
+            let mut expected = vec![0.0; voronoi.sites().len()];
+            for &(site, area) in bin {
+                expected[site] = area;
+            }
+
+            // pixels on the edges of a cell are the only ones counted by a different cell
+            for (site, (exact, raster)) in expected.iter().zip(counts).enumerate() {
+                assert!(
+                    utils::abs_diff_eq(*exact, raster, 0.003),
+                    "Cell {} covers {} of the bin, rasterized as {}",
+                    site,
+                    exact,
+                    raster
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bins_outside_boundary_are_empty() {
+        let (cols, rows) = (10, 10);
+        let voronoi = random_voronoi_within(ConvexPolygon::regular(6, 1.0), 200);
+        let bins = voronoi.bin_areas(cols, rows);
+
+        let (min, max) = bounds(voronoi.boundary().vertices());
+        let bin_area = (max.x - min.x) * (max.y - min.y) / (cols * rows) as f64;
+        let mut outside = 0;
+        for (i, bin) in bins.iter().enumerate() {
+            // bins only touching the boundary may overlap it by a rounding error
+            let overlap = bin_boundary_area(&voronoi, cols, rows, i);
+            if overlap < SLIVER_TOLERANCE * bin_area {
+                assert!(
+                    bin.is_empty(),
+                    "Bin {} outside of the boundary holds {:?}",
+                    i,
+                    bin
+                );
+                outside += 1;
+            } else if overlap > 1e-9 {
+                assert!(!bin.is_empty());
+            }
+        }
+        assert!(outside > 0, "Some bin expected outside of the hexagon");
+    }
+}
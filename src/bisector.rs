@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+
+use crate::{utils::dist2, ConvexBoundary, Point, Voronoi};
+
+/// The line of points equidistant to two sites, whether or not it is part of the diagram.
+///
+/// The line is given by a point on it and a unit direction. Along the direction, the first site is on the same side as a cell is of its edges
+/// when walking them in the order of [crate::VoronoiCell::iter_edges()], so where the cells of the sites share an edge,
+/// the edge of the cell of the first site goes in the direction of the line and the outward normal of that edge points towards the second site.
+///
+/// Use [Voronoi::bisector()] or [Self::radical_axis()] to obtain an instance of this type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bisector {
+    a: Point,
+    b: Point,
+
+    /// The squared radii of the circles around ```a``` and ```b```, zero for sites.
+    powers: (f64, f64),
+
+    point: Point,
+    direction: Point,
+}
+
+impl Bisector {
+    /// Gets the radical axis of the circles centered at ```a``` and ```b``` with radii ```radius_a``` and ```radius_b```.
+    ///
+    /// This is the line of points with the same power with respect to both circles, i.e. the bisector of two sites of a power diagram weighted by the squared radii,
+    /// and it goes through the intersections of both circles if they intersect. With equal radii, it is the perpendicular bisector of ```a``` and ```b```.
+    /// If ```a``` and ```b``` are the same point, the direction is zero.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let axis = Bisector::radical_axis(&Point { x: 0.0, y: 0.0 }, 2.0, &Point { x: 3.0, y: 0.0 }, 1.0);
+    /// // x^2 - 2^2 = (3 - x)^2 - 1^2
+    /// assert!((axis.point().x - 2.0).abs() < 1e-12);
+    ///```
+    pub fn radical_axis(a: &Point, radius_a: f64, b: &Point, radius_b: f64) -> Self {
+        let powers = (radius_a * radius_a, radius_b * radius_b);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length2 = dx * dx + dy * dy;
+
+        // along the segment from a to b, the power of both circles is the same at t * |ab|
+        let (point, direction) = if length2 > 0.0 {
+            let t = (length2 + powers.0 - powers.1) / (2.0 * length2);
+            let length = length2.sqrt();
+            (
+                Point {
+                    x: a.x + t * dx,
+                    y: a.y + t * dy,
+                },
+                Point {
+                    x: dy / length,
+                    y: -dx / length,
+                },
+            )
+        } else {
+            (a.clone(), Point { x: 0.0, y: 0.0 })
+        };
+
+        Self {
+            a: a.clone(),
+            b: b.clone(),
+            powers,
+            point,
+            direction,
+        }
+    }
+
+    /// Gets a point on the line.
+    ///
+    /// For a bisector of two sites, this is the midpoint between them.
+    #[inline]
+    pub fn point(&self) -> &Point {
+        &self.point
+    }
+
+    /// Gets the unit direction of the line.
+    #[inline]
+    pub fn direction(&self) -> &Point {
+        &self.direction
+    }
+
+    /// Clips the line to ```boundary```, returning the points where it enters and leaves it, in the direction of the line.
+    ///
+    /// Returns ```None``` if the line does not cross the boundary, or only touches one of its vertices.
+    pub fn clip_to<T: ConvexBoundary>(&self, boundary: &T) -> Option<(Point, Point)> {
+        let along = Point {
+            x: self.point.x + self.direction.x,
+            y: self.point.y + self.direction.y,
+        };
+        match boundary.intersect_line(&self.point, &along) {
+            (Some(p), Some(q)) => {
+                let t = |v: &Point| {
+                    (v.x - self.point.x) * self.direction.x
+                        + (v.y - self.point.y) * self.direction.y
+                };
+                if t(&p) <= t(&q) {
+                    Some((p, q))
+                } else {
+                    Some((q, p))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets which of both sites ```p``` is closer to: [Ordering::Less] for the first site, [Ordering::Greater] for the second and [Ordering::Equal] on the line.
+    ///
+    /// For a [Self::radical_axis()], distances are the powers of ```p``` with respect to each circle.
+    pub fn side_of(&self, p: &Point) -> Ordering {
+        let to_a = dist2(p, &self.a) - self.powers.0;
+        let to_b = dist2(p, &self.b) - self.powers.1;
+        to_a.partial_cmp(&to_b).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the perpendicular bisector of sites ```a``` and ```b```, the line their cells would share an edge on if no other site was closer.
+    ///
+    /// Cells of the diagram are not weighted, so this is the [Bisector::radical_axis()] of both sites with radii of zero.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use std::cmp::Ordering;
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: -0.5, y: 0.0 }, Point { x: 0.5, y: 0.0 }, Point { x: 0.0, y: 0.5 }])
+    ///     .build()
+    ///     .unwrap();
+    /// let bisector = v.bisector(0, 1);
+    /// assert_eq!(bisector.side_of(&Point { x: -0.1, y: 0.9 }), Ordering::Less);
+    /// let (enter, leave) = bisector.clip_to(v.boundary()).unwrap();
+    /// assert!(enter.x.abs() < 1e-12 && leave.x.abs() < 1e-12);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```a``` or ```b``` do not exist.
+    pub fn bisector(&self, a: usize, b: usize) -> Bisector {
+        Bisector::radical_axis(&self.sites[a], 0.0, &self.sites[b], 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{fixtures, utils::test::random_sites, BoundingBox, ConvexPolygon, VoronoiBuilder};
+
+    /// Gets the distance from ```p``` to the line and its parameter along the line.
+    fn on_line(bisector: &Bisector, p: &Point) -> (f64, f64) {
+        let (dx, dy) = (p.x - bisector.point.x, p.y - bisector.point.y);
+        let d = &bisector.direction;
+        ((dx * d.y - dy * d.x).abs(), dx * d.x + dy * d.y)
+    }
+
+    fn assert_edges_on_bisectors<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        for cell in voronoi.iter_cells() {
+            for edge in cell.iter_edge_metadata() {
+                let neighbor = match edge.neighbor {
+                    Some(neighbor) if edge.length > 1e-9 => neighbor,
+                    _ => continue,
+                };
+                let bisector = voronoi.bisector(cell.site(), neighbor);
+                let (enter, leave) = bisector
+                    .clip_to(voronoi.boundary())
+                    .expect("Bisector of neighbors expected to cross the boundary");
+                let (_, t_enter) = on_line(&bisector, &enter);
+                let (_, t_leave) = on_line(&bisector, &leave);
+
+                // the shared edge is on the clipped bisector, in its direction
+                let (u, v) = edge.vertices;
+                let (u_distance, t_u) = on_line(&bisector, &voronoi.vertices()[u]);
+                let (v_distance, t_v) = on_line(&bisector, &voronoi.vertices()[v]);
+                assert!(u_distance < 1e-9 && v_distance < 1e-9);
+                assert!(
+                    t_u < t_v,
+                    "Edge {:?} goes against its bisector.",
+                    edge.vertices
+                );
+                assert!(t_enter - 1e-9 <= t_u && t_v <= t_leave + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_edges_lie_on_clipped_bisectors() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_sites(300))
+            .build()
+            .expect("Some voronoi expected");
+        assert_edges_on_bisectors(&voronoi);
+
+        let boundary = ConvexPolygon::regular(7, 1.0);
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(random_sites(300))
+            .set_boundary(boundary)
+            .build()
+            .expect("Some voronoi expected");
+        assert_edges_on_bisectors(&voronoi);
+    }
+
+    #[test]
+    fn side_of_agrees_with_distances() {
+        let sites = random_sites(20);
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        for p in random_sites(500) {
+            for a in 0..sites.len() {
+                let b = (a + 7) % sites.len();
+                let expected = dist2(&p, &sites[a])
+                    .partial_cmp(&dist2(&p, &sites[b]))
+                    .unwrap();
+                assert_eq!(voronoi.bisector(a, b).side_of(&p), expected);
+                assert_eq!(voronoi.bisector(b, a).side_of(&p), expected.reverse());
+            }
+        }
+
+        // the midpoint and points along the line are equidistant
+        let bisector = voronoi.bisector(0, 1);
+        assert_eq!(bisector.side_of(bisector.point()), Ordering::Equal);
+        let b = voronoi.bisector(1, 0);
+        assert_eq!(b.point(), bisector.point());
+        assert_eq!(b.direction().x, -bisector.direction().x);
+        assert_eq!(b.direction().y, -bisector.direction().y);
+    }
+
+    #[test]
+    fn radical_axis_goes_through_circle_intersections() {
+        let mut rng = crate::utils::test::rng();
+        for _ in 0..100 {
+            let a = Point {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+            };
+            let b = Point {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+            };
+            let d = dist2(&a, &b).sqrt();
+            // radii such that both circles intersect
+            let radius_a = rng.gen_range(0.6..1.0) * d;
+            let radius_b = rng.gen_range((d - radius_a).abs() * 1.01..(d + radius_a) * 0.99);
+            let axis = Bisector::radical_axis(&a, radius_a, &b, radius_b);
+
+            // the intersections of both circles, at x along ab and h to each side
+            let x = (d * d + radius_a * radius_a - radius_b * radius_b) / (2.0 * d);
+            let h = (radius_a * radius_a - x * x).sqrt();
+            let (ux, uy) = ((b.x - a.x) / d, (b.y - a.y) / d);
+            for &side in &[-1.0, 1.0] {
+                let p = Point {
+                    x: a.x + x * ux - side * h * uy,
+                    y: a.y + x * uy + side * h * ux,
+                };
+                assert!((dist2(&p, &a).sqrt() - radius_a).abs() < 1e-9);
+                assert!((dist2(&p, &b).sqrt() - radius_b).abs() < 1e-9);
+                let (distance, _) = on_line(&axis, &p);
+                assert!(
+                    distance < 1e-9,
+                    "Intersection {:?} is {} away.",
+                    p,
+                    distance
+                );
+            }
+
+            // powers decide the side rather than distances, so a site may be on the side of the other
+            for &(offset, expected) in &[(-1e-3, Ordering::Less), (1e-3, Ordering::Greater)] {
+                let shifted = Point {
+                    x: axis.point().x + offset * ux,
+                    y: axis.point().y + offset * uy,
+                };
+                assert_eq!(axis.side_of(&shifted), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn bisectors_outside_of_the_boundary_are_not_clipped() {
+        let (voronoi, _) = fixtures::three_sites_meeting_at_center();
+        let far = Bisector::radical_axis(&voronoi.sites()[0], 0.0, &Point { x: 10.0, y: 0.0 }, 0.0);
+        assert_eq!(far.point().x, 4.75);
+        assert_eq!(far.clip_to(voronoi.boundary()), None);
+    }
+}
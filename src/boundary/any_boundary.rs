@@ -0,0 +1,207 @@
+use super::{BoundingBox, ConvexBoundary, ConvexPolygon, Point};
+
+/// Any of the built-in boundaries, for diagrams whose boundary type is chosen at runtime.
+///
+/// Every query is forwarded to the boundary wrapped, so diagrams built with an [AnyBoundary] are the same as diagrams built with the wrapped boundary directly.
+/// Use [crate::DynVoronoi] and [crate::DynVoronoiBuilder] to name diagrams and builders with this boundary,
+/// and [crate::Voronoi::into_dyn()] to erase the boundary type of an existing diagram.
+///
+/// # Examples
+///
+///```
+/// use voronoice::*;
+/// let boundary: AnyBoundary = if std::env::args().count() > 1 {
+///     ConvexPolygon::regular(6, 1.0).into()
+/// } else {
+///     BoundingBox::default().into()
+/// };
+/// let v: DynVoronoi = DynVoronoiBuilder::with_boundary(boundary)
+///     .generate_circle_sites(10, 0.5)
+///     .build()
+///     .unwrap();
+/// assert_eq!(v.sites().len(), 11);
+///```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnyBoundary {
+    /// A [BoundingBox].
+    BoundingBox(BoundingBox),
+
+    /// A [ConvexPolygon].
+    ConvexPolygon(ConvexPolygon),
+}
+
+impl Default for AnyBoundary {
+    /// The default [BoundingBox], like the default boundary of a [crate::VoronoiBuilder].
+    fn default() -> Self {
+        AnyBoundary::BoundingBox(BoundingBox::default())
+    }
+}
+
+impl From<BoundingBox> for AnyBoundary {
+    fn from(boundary: BoundingBox) -> Self {
+        AnyBoundary::BoundingBox(boundary)
+    }
+}
+
+impl From<ConvexPolygon> for AnyBoundary {
+    fn from(boundary: ConvexPolygon) -> Self {
+        AnyBoundary::ConvexPolygon(boundary)
+    }
+}
+
+impl ConvexBoundary for AnyBoundary {
+    #[inline]
+    fn vertices(&self) -> &[Point] {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.vertices(),
+            AnyBoundary::ConvexPolygon(p) => p.vertices(),
+        }
+    }
+
+    #[inline]
+    fn is_inside(&self, point: &Point) -> bool {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.is_inside(point),
+            AnyBoundary::ConvexPolygon(p) => p.is_inside(point),
+        }
+    }
+
+    #[inline]
+    fn is_exclusively_inside(&self, point: &Point) -> bool {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.is_exclusively_inside(point),
+            AnyBoundary::ConvexPolygon(p) => p.is_exclusively_inside(point),
+        }
+    }
+
+    #[inline]
+    fn which_edge(&self, point: &Point) -> Option<usize> {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.which_edge(point),
+            AnyBoundary::ConvexPolygon(p) => p.which_edge(point),
+        }
+    }
+
+    #[inline]
+    fn next_edge(&self, edge: usize) -> usize {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.next_edge(edge),
+            AnyBoundary::ConvexPolygon(p) => p.next_edge(edge),
+        }
+    }
+
+    fn intersect_line(&self, a: &Point, b: &Point) -> (Option<Point>, Option<Point>) {
+        match self {
+            AnyBoundary::BoundingBox(boundary) => boundary.intersect_line(a, b),
+            AnyBoundary::ConvexPolygon(boundary) => boundary.intersect_line(a, b),
+        }
+    }
+
+    fn project_ray(&self, point: &Point, direction: &Point) -> (Option<Point>, Option<Point>) {
+        match self {
+            AnyBoundary::BoundingBox(b) => b.project_ray(point, direction),
+            AnyBoundary::ConvexPolygon(p) => p.project_ray(point, direction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils, utils::test::random_sites, AutoBoundary, ClipBehavior, DynVoronoi,
+        DynVoronoiBuilder, VoronoiBuilder,
+    };
+
+    #[test]
+    fn erased_builds_equal_static_builds() {
+        let sites = random_sites(500);
+        for &clip_behavior in &[
+            ClipBehavior::Clip,
+            ClipBehavior::RemoveSitesOutsideBoundaryOnly,
+            ClipBehavior::None,
+        ] {
+            let bbox = BoundingBox::new_centered(1.8, 1.5);
+            let erased = DynVoronoiBuilder::with_boundary(bbox.clone())
+                .set_sites(sites.clone())
+                .set_clip_behavior(clip_behavior)
+                .set_lloyd_relaxation_iterations(1)
+                .build()
+                .expect("Some voronoi expected");
+            let typed = VoronoiBuilder::default()
+                .set_boundary(bbox)
+                .set_sites(sites.clone())
+                .set_clip_behavior(clip_behavior)
+                .set_lloyd_relaxation_iterations(1)
+                .build()
+                .expect("Some voronoi expected");
+            assert!(utils::test::geometry_eq(&erased, &typed));
+            assert_eq!(erased.adjacency(), typed.adjacency());
+
+            let polygon = ConvexPolygon::regular(9, 0.9);
+            let erased = DynVoronoiBuilder::with_boundary(polygon.clone())
+                .set_sites(sites.clone())
+                .set_clip_behavior(clip_behavior)
+                .build()
+                .expect("Some voronoi expected");
+            let typed = VoronoiBuilder::default()
+                .set_boundary(polygon)
+                .set_sites(sites.clone())
+                .set_clip_behavior(clip_behavior)
+                .build()
+                .expect("Some voronoi expected");
+            assert!(utils::test::geometry_eq(&erased, &typed));
+            assert_eq!(erased.adjacency(), typed.adjacency());
+
+            // erasing the boundary of a diagram keeps everything else
+            let hash = utils::test::geometry_hash(&typed);
+            let generation = typed.generation();
+            let erased = typed.into_dyn();
+            assert_eq!(utils::test::geometry_hash(&erased), hash);
+            assert_eq!(erased.generation(), generation);
+        }
+    }
+
+    #[test]
+    fn erased_diagrams_support_downstream_apis() {
+        let sites = random_sites(200);
+        let polygon = ConvexPolygon::regular(7, 1.0);
+        let mut voronoi = DynVoronoiBuilder::with_boundary(polygon.clone())
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+
+        let mut bytes = Vec::new();
+        voronoi
+            .write_binary(&mut bytes)
+            .expect("Write expected to succeed");
+        let read = DynVoronoi::read_binary_dyn(bytes.as_slice()).expect("Read expected to succeed");
+        assert_eq!(read.boundary(), voronoi.boundary());
+        assert!(utils::test::geometry_eq(&read, &voronoi));
+
+        let mut rasterizer = crate::raster::Rasterizer::new(32, 32);
+        rasterizer.set_view(Point { x: 0.0, y: 0.0 }, 16.0);
+        assert!(rasterizer.render(&voronoi, |site| site as u32) > 0);
+
+        voronoi
+            .insert_site(Point { x: 0.1, y: 0.1 })
+            .expect("Insert expected to succeed");
+        assert_eq!(voronoi.boundary(), &AnyBoundary::from(polygon));
+
+        let auto = DynVoronoiBuilder::with_auto_boundary(AutoBoundary::TightBox { padding: 0.1 })
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        let typed = VoronoiBuilder::<ConvexPolygon>::default()
+            .set_sites(sites)
+            .set_auto_boundary(AutoBoundary::TightBox { padding: 0.1 })
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(
+            auto.boundary(),
+            &AnyBoundary::ConvexPolygon(typed.boundary().clone())
+        );
+        assert!(utils::test::geometry_eq(&auto, &typed));
+        assert_eq!(auto.build_stats(), typed.build_stats());
+    }
+}
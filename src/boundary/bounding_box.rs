@@ -1,3 +1,6 @@
+#[cfg(feature = "approx")]
+use approx::{AbsDiffEq, RelativeEq};
+
 use super::{ConvexBoundary, Point};
 use crate::utils::{abs_diff_eq, EQ_EPSILON};
 
@@ -38,6 +41,19 @@ impl BoundingBox {
         let right = center.x + width / 2.0;
         let top = center.y - height / 2.0;
         let bottom = center.y + height / 2.0;
+        Self::with_center(center, left, top, right, bottom)
+    }
+
+    /// Constructs a new bounding box from the coordinates of its edges, without any rounding of the coordinates.
+    pub(crate) fn from_edges(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        let center = Point {
+            x: (left + right) / 2.0,
+            y: (top + bottom) / 2.0,
+        };
+        Self::with_center(center, left, top, right, bottom)
+    }
+
+    fn with_center(center: Point, left: f64, top: f64, right: f64, bottom: f64) -> Self {
         Self {
             top_right: Point { x: right, y: top },
             bottom_left: Point { x: left, y: bottom },
@@ -119,6 +135,78 @@ impl BoundingBox {
     }
 }
 
+impl std::fmt::Display for BoundingBox {
+    /// Formats the box as its top left and bottom right corners, e.g. ```[-1, -1] to [1, 1]```.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}, {}] to [{}, {}]",
+            self.left(),
+            self.top(),
+            self.right(),
+            self.bottom()
+        )
+    }
+}
+
+/// Boxes are equal if the bit patterns of their edges are equal. So boxes with ```NaN``` edges with the same bits are equal,
+/// and, unlike when comparing the edges as numbers, ```0.0``` and ```-0.0``` are not. This is consistent with [Hash], so boxes can be used as keys of maps.
+impl PartialEq for BoundingBox {
+    fn eq(&self, other: &Self) -> bool {
+        crate::fingerprint::bits_eq(&self.vertices, &other.vertices)
+    }
+}
+
+impl Eq for BoundingBox {}
+
+impl std::hash::Hash for BoundingBox {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        crate::fingerprint::hash_points(&self.vertices, state);
+    }
+}
+
+#[cfg(feature = "approx")]
+impl AbsDiffEq for BoundingBox {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> Self::Epsilon {
+        f64::default_epsilon()
+    }
+
+    /// Compares the edges of both boxes.
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.left().abs_diff_eq(&other.left(), epsilon)
+            && self.top().abs_diff_eq(&other.top(), epsilon)
+            && self.right().abs_diff_eq(&other.right(), epsilon)
+            && self.bottom().abs_diff_eq(&other.bottom(), epsilon)
+    }
+}
+
+#[cfg(feature = "approx")]
+impl RelativeEq for BoundingBox {
+    fn default_max_relative() -> Self::Epsilon {
+        f64::default_max_relative()
+    }
+
+    /// Compares the edges of both boxes.
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool {
+        self.left()
+            .relative_eq(&other.left(), epsilon, max_relative)
+            && self.top().relative_eq(&other.top(), epsilon, max_relative)
+            && self
+                .right()
+                .relative_eq(&other.right(), epsilon, max_relative)
+            && self
+                .bottom()
+                .relative_eq(&other.bottom(), epsilon, max_relative)
+    }
+}
+
 impl ConvexBoundary for BoundingBox {
     fn vertices(&self) -> &[Point] {
         &self.vertices
@@ -249,6 +337,63 @@ impl ConvexBoundary for BoundingBox {
 mod tests {
     use super::*;
 
+    #[test]
+    fn display_shows_corners() {
+        assert_eq!(
+            BoundingBox::new(Point { x: 1.0, y: 0.5 }, 4.0, 3.0).to_string(),
+            "[-1, -1] to [3, 2]"
+        );
+    }
+
+    #[test]
+    fn equality_and_hash_follow_bits() {
+        use std::collections::HashSet;
+
+        let a = BoundingBox::new(Point { x: 1.0, y: 0.5 }, 4.0, 3.0);
+        let b = BoundingBox::from_edges(-1.0, -1.0, 3.0, 2.0);
+        assert_eq!(a, b);
+        assert_ne!(a, BoundingBox::from_edges(-1.0, -1.0, 3.0, 2.5));
+
+        // unlike numeric comparison, NaN equals itself and signed zeros differ
+        let nan = BoundingBox::from_edges(f64::NAN, -1.0, 1.0, 1.0);
+        assert_eq!(nan, nan.clone());
+        assert_ne!(
+            BoundingBox::from_edges(0.0, 0.0, 1.0, 1.0),
+            BoundingBox::from_edges(-0.0, 0.0, 1.0, 1.0)
+        );
+
+        let set: HashSet<BoundingBox> = vec![a, b, nan.clone()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&nan));
+    }
+
+    #[test]
+    #[cfg(feature = "approx")]
+    fn approx_comparisons() {
+        use approx::{assert_abs_diff_eq, assert_relative_ne};
+
+        let a = BoundingBox::new_centered_square(2.0);
+        let b = BoundingBox::new(Point { x: 1e-9, y: 0.0 }, 2.0, 2.0 + 1e-9);
+        assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+        assert!(!a.abs_diff_eq(&b, 1e-12));
+        assert!(a.relative_eq(&b, 1e-12, 1e-6));
+        assert_relative_ne!(a, BoundingBox::new_centered_square(2.1));
+
+        // the relative tolerance grows with the coordinates of the edges
+        let far = BoundingBox::new(Point { x: 1e9, y: 0.0 }, 2.0, 2.0);
+        let far_shifted = BoundingBox::new(
+            Point {
+                x: 1e9 + 1e-3,
+                y: 0.0,
+            },
+            2.0,
+            2.0,
+        );
+        assert!(far.relative_eq(&far_shifted, 1e-12, 1e-11));
+        assert!(!far.relative_eq(&far_shifted, 1e-12, 1e-13));
+        assert!(!far.abs_diff_eq(&far_shifted, 1e-4));
+    }
+
     fn line(x: f64, c: f64, d: f64) -> Point {
         Point { x, y: (x * c) + d }
     }
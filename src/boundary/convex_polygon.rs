@@ -18,6 +18,22 @@ impl Default for ConvexPolygon {
     }
 }
 
+/// Polygons are equal if they have the same vertices, in the same order, with the same bit patterns.
+/// See [crate::BoundingBox] for how ```NaN``` and ```-0.0``` compare.
+impl PartialEq for ConvexPolygon {
+    fn eq(&self, other: &Self) -> bool {
+        crate::fingerprint::bits_eq(&self.vertices, &other.vertices)
+    }
+}
+
+impl Eq for ConvexPolygon {}
+
+impl std::hash::Hash for ConvexPolygon {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        crate::fingerprint::hash_points(&self.vertices, state);
+    }
+}
+
 impl ConvexBoundary for ConvexPolygon {
     fn vertices(&self) -> &[Point] {
         &self.vertices
@@ -44,10 +60,13 @@ impl ConvexBoundary for ConvexPolygon {
             .zip(self.vertices.iter().cycle().skip(1))
             .enumerate()
         {
-            let edge_left = f64::min(a.x, b.x);
-            let edge_top = f64::min(a.y, b.y);
-            let edge_right = f64::max(a.x, b.x);
-            let edge_bottom = f64::max(a.y, b.y);
+            // allow for rounding errors of intersections, which are not exactly on axis-aligned edges
+            let extent = a.x.abs().max(a.y.abs()).max(b.x.abs()).max(b.y.abs());
+            let tolerance = EQ_EPSILON * (1.0 + extent);
+            let edge_left = f64::min(a.x, b.x) - tolerance;
+            let edge_top = f64::min(a.y, b.y) - tolerance;
+            let edge_right = f64::max(a.x, b.x) + tolerance;
+            let edge_bottom = f64::max(a.y, b.y) + tolerance;
 
             if edge_left <= point.x
                 && point.x <= edge_right
@@ -64,7 +83,8 @@ impl ConvexBoundary for ConvexPolygon {
                         (val.abs() - ISECT_EPSILON) / ISECT_EPSILON
                     );
                 }
-                if val.abs() <= ISECT_EPSILON {
+                // orient2d is twice the area of the triangle, which grows with the square of the coordinates
+                if val.abs() <= ISECT_EPSILON * extent.max(1.0).powi(2) {
                     return Some((i + 1) % self.vertices.len());
                 }
             }
@@ -98,7 +118,8 @@ impl ConvexBoundary for ConvexPolygon {
             // Given the line L parametrized as L(t) = a + t(b − a),
             // the intersect occurs at t = n · (v1 − a)/n · (b − a),
             // where n is perpendicular to the edge (v2 - v1).
-            if o_v1 * o_v2 < 0.0 {
+            // Vertices the line crosses are found above, so the edges next to them are not crossed again.
+            if o_v1 * o_v2 < 0.0 && o_v1.abs() > EQ_EPSILON && o_v2.abs() > EQ_EPSILON {
                 // Edge normal
                 let n_x = v1.y - v2.y;
                 let n_y = v2.x - v1.x;
@@ -120,7 +141,7 @@ impl ConvexBoundary for ConvexPolygon {
 }
 
 impl ConvexPolygon {
-    fn is_convex_ccw(vertices: &[Point]) -> bool {
+    pub(crate) fn is_convex_ccw(vertices: &[Point]) -> bool {
         for ((a, b), c) in vertices
             .iter()
             .zip(vertices.iter().cycle().skip(1))
@@ -216,6 +237,60 @@ mod test {
         assert_eq!(polygon.which_edge(&Point { x: 1.0, y: 1.0 }), None);
     }
 
+    #[test]
+    fn test_which_edge_at_large_coordinates() {
+        let scale = 1e6;
+        let polygon = ConvexPolygon::new(
+            dented_box()
+                .into_iter()
+                .map(|p| Point {
+                    x: p.x * scale,
+                    y: p.y * scale,
+                })
+                .collect(),
+        );
+
+        // one unit in the last place off the edge, far more than an absolute tolerance at this scale
+        let next_up = f64::from_bits(scale.to_bits() + 1);
+        assert_eq!(
+            polygon.which_edge(&Point {
+                x: next_up,
+                y: -0.5 * scale
+            }),
+            Some(1)
+        );
+        assert_eq!(
+            polygon.which_edge(&Point {
+                x: scale + 1e-3,
+                y: -0.5 * scale
+            }),
+            None
+        );
+
+        // intersections on the axis-aligned edges are found on them, despite rounding errors
+        for i in 1..100 {
+            let t = i as f64 / 100.0;
+            let (a, b) = (
+                Point {
+                    x: -scale,
+                    y: scale * (2.0 * t - 1.0),
+                },
+                Point {
+                    x: scale * 0.3,
+                    y: -scale * t,
+                },
+            );
+            let (first, second) = polygon.intersect_line(&a, &b);
+            for p in [first, second].iter().flatten() {
+                assert!(
+                    polygon.which_edge(p).is_some(),
+                    "{:?} is not on an edge.",
+                    p
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_intersect_line() {
         let polygon = ConvexPolygon::new(dented_box());
@@ -241,5 +316,13 @@ mod test {
                 Some(Point { x: 1.0, y: 0.0 })
             )
         );
+        // within rounding errors of a vertex, which is reported once, and not again as a crossing of the next edge
+        assert_eq!(
+            polygon.intersect_line(&Point { x: 2.0, y: -1e-16 }, &Point { x: -2.0, y: -1e-16 }),
+            (
+                Some(Point { x: 1.0, y: 0.0 }),
+                Some(Point { x: -1.0, y: -1e-16 })
+            )
+        );
     }
 }
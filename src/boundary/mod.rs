@@ -1,8 +1,10 @@
+mod any_boundary;
 mod bounding_box;
 mod convex_polygon;
 
 use std::{fmt::Display, str::FromStr};
 
+pub use any_boundary::AnyBoundary;
 pub use bounding_box::BoundingBox;
 pub use convex_polygon::ConvexPolygon;
 
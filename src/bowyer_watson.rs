@@ -0,0 +1,539 @@
+use std::collections::{HashMap, HashSet};
+
+use delaunator::{next_halfedge, Point, Triangulation, EMPTY};
+
+/// The vertex at infinity. Triangles with this vertex are ghost triangles, one per hull edge, covering the outside of the hull.
+const GHOST: usize = usize::MAX - 1;
+
+/// Points closer than this, on both axes, are treated as duplicates and only the first one inserted is triangulated.
+const DUPLICATE_EPSILON: f64 = delaunator::EPSILON;
+
+/// Side length of the grid points are snapped to when calculating their position on the Hilbert curve.
+const HILBERT_ORDER: u64 = 1 << 16;
+
+/// Triangulates ```points``` with an incremental Bowyer–Watson algorithm, inserting points in Hilbert curve order.
+///
+/// The output has the same shape as the output of [delaunator::triangulate()]: triangles and the hull have the same orientation,
+/// duplicate points are left out of the triangulation and collinear points produce no triangles.
+/// The outside of the hull is covered by ghost triangles sharing a vertex at infinity, so points outside the hull are inserted
+/// with the same cavity search as points inside of it and the hull is always the exact convex hull.
+pub(crate) fn triangulate(points: &[Point]) -> Triangulation {
+    let order = hilbert_order(points);
+    let mut mesh = match Mesh::with_seed_triangle(points, &order) {
+        Some(mesh) => mesh,
+        None => {
+            return Triangulation {
+                triangles: Vec::new(),
+                halfedges: Vec::new(),
+                hull: Vec::new(),
+            }
+        }
+    };
+
+    for &i in &order {
+        if !mesh.is_seed(i) {
+            mesh.insert(i);
+        }
+    }
+
+    mesh.into_triangulation()
+}
+
+struct Mesh<'p> {
+    points: &'p [Point],
+    seed: [usize; 3],
+
+    /// Vertices of each triangle, with the same orientation as [delaunator] triangles.
+    triangles: Vec<[usize; 3]>,
+
+    /// The triangle across edge ```j``` (from vertex ```j``` to vertex ```j + 1```) of each triangle.
+    neighbors: Vec<[usize; 3]>,
+
+    /// Whether each triangle is still part of the mesh. Triangles removed from a cavity are reused by the next insertions.
+    alive: Vec<bool>,
+    free: Vec<usize>,
+
+    /// A real triangle to start point location from.
+    last: usize,
+}
+
+impl<'p> Mesh<'p> {
+    /// Creates a mesh with the first three non-collinear points in ```order```, and its three ghost triangles.
+    fn with_seed_triangle(points: &'p [Point], order: &[usize]) -> Option<Self> {
+        let &a = order.first()?;
+        let &b = order
+            .iter()
+            .find(|&&i| !nearly_equals(&points[a], &points[i]))?;
+        let &c = order
+            .iter()
+            .find(|&&i| orient(&points[a], &points[b], &points[i]) != 0.0)?;
+
+        // keep the orientation of delaunator triangles
+        let [a, b, c] = if orient(&points[a], &points[b], &points[c]) < 0.0 {
+            [a, b, c]
+        } else {
+            [a, c, b]
+        };
+
+        Some(Self {
+            points,
+            seed: [a, b, c],
+            triangles: vec![[a, b, c], [b, a, GHOST], [c, b, GHOST], [a, c, GHOST]],
+            neighbors: vec![[1, 2, 3], [0, 3, 2], [0, 1, 3], [0, 2, 1]],
+            alive: vec![true; 4],
+            free: Vec::new(),
+            last: 0,
+        })
+    }
+
+    fn is_seed(&self, i: usize) -> bool {
+        self.seed.contains(&i)
+    }
+
+    fn is_ghost(&self, t: usize) -> bool {
+        self.triangles[t].contains(&GHOST)
+    }
+
+    /// Inserts point ```i```, re-triangulating the cavity of triangles in conflict with it.
+    fn insert(&mut self, i: usize) {
+        let points = self.points;
+        let p = &points[i];
+        let start = self.locate(p);
+        if !self.is_ghost(start)
+            && self.triangles[start]
+                .iter()
+                .any(|&v| nearly_equals(&self.points[v], p))
+        {
+            return;
+        }
+
+        // collect the cavity, and the edges on its border with the triangle on the other side
+        let mut cavity = vec![start];
+        let mut in_cavity = HashSet::new();
+        in_cavity.insert(start);
+        let mut border = Vec::new();
+        let mut k = 0;
+        while k < cavity.len() {
+            let t = cavity[k];
+            k += 1;
+            for j in 0..3 {
+                let n = self.neighbors[t][j];
+                if in_cavity.contains(&n) {
+                    continue;
+                }
+
+                if self.is_in_conflict(n, p) {
+                    in_cavity.insert(n);
+                    cavity.push(n);
+                } else {
+                    border.push((self.triangles[t][j], self.triangles[t][(j + 1) % 3], n));
+                }
+            }
+        }
+
+        for &t in &cavity {
+            self.alive[t] = false;
+            self.free.push(t);
+        }
+
+        // fan the border edges to the new point, linking the new triangles through the border vertices
+        let mut starting_at = HashMap::with_capacity(border.len());
+        let mut created = Vec::with_capacity(border.len());
+        for &(a, b, outside) in &border {
+            let t = self.allocate([a, b, i], [outside, EMPTY, EMPTY]);
+            let j = self.edge_index(outside, b, a);
+            self.neighbors[outside][j] = t;
+            starting_at.insert(a, t);
+            created.push(t);
+        }
+
+        for &t in &created {
+            let [_, b, _] = self.triangles[t];
+            let next = starting_at[&b];
+            self.neighbors[t][1] = next;
+            self.neighbors[next][2] = t;
+            if !self.is_ghost(t) {
+                self.last = t;
+            }
+        }
+    }
+
+    /// Walks from the last created triangle to the triangle containing ```p```, or to a ghost triangle if ```p``` is outside of the hull.
+    fn locate(&self, p: &Point) -> usize {
+        let mut t = self.last;
+        let mut from = EMPTY;
+        'walk: while !self.is_ghost(t) {
+            let [a, b, c] = self.triangles[t];
+            for &(j, u, v) in &[(0, a, b), (1, b, c), (2, c, a)] {
+                let n = self.neighbors[t][j];
+                if n != from && orient(&self.points[u], &self.points[v], p) > 0.0 {
+                    from = t;
+                    t = n;
+                    continue 'walk;
+                }
+            }
+
+            break;
+        }
+
+        t
+    }
+
+    /// Returns whether ```p``` is strictly inside the circumcircle of real triangle ```t```,
+    /// or strictly outside of the hull edge of ghost triangle ```t```, or in the interior of that edge.
+    fn is_in_conflict(&self, t: usize, p: &Point) -> bool {
+        let [a, b, c] = self.triangles[t];
+        let points = self.points;
+        if a == GHOST {
+            self.is_beyond_hull_edge(b, c, p)
+        } else if b == GHOST {
+            self.is_beyond_hull_edge(c, a, p)
+        } else if c == GHOST {
+            self.is_beyond_hull_edge(a, b, p)
+        } else {
+            incircle(&points[a], &points[b], &points[c], p) < 0.0
+        }
+    }
+
+    fn is_beyond_hull_edge(&self, a: usize, b: usize, p: &Point) -> bool {
+        let (a, b) = (&self.points[a], &self.points[b]);
+        let o = orient(a, b, p);
+        o < 0.0 || (o == 0.0 && (p.x - a.x) * (p.x - b.x) + (p.y - a.y) * (p.y - b.y) < 0.0)
+    }
+
+    /// Gets the index of edge ```a -> b``` of triangle ```t```.
+    fn edge_index(&self, t: usize, a: usize, b: usize) -> usize {
+        let v = &self.triangles[t];
+        (0..3)
+            .find(|&j| v[j] == a && v[(j + 1) % 3] == b)
+            .expect("Triangles are expected to share an edge")
+    }
+
+    fn allocate(&mut self, vertices: [usize; 3], neighbors: [usize; 3]) -> usize {
+        match self.free.pop() {
+            Some(t) => {
+                self.triangles[t] = vertices;
+                self.neighbors[t] = neighbors;
+                self.alive[t] = true;
+                t
+            }
+            None => {
+                self.triangles.push(vertices);
+                self.neighbors.push(neighbors);
+                self.alive.push(true);
+                self.triangles.len() - 1
+            }
+        }
+    }
+
+    /// Converts the real triangles to a [Triangulation] and walks the ghost triangles to produce the hull.
+    fn into_triangulation(self) -> Triangulation {
+        let mut index = vec![EMPTY; self.triangles.len()];
+        let mut triangles = Vec::new();
+        let mut hull_next = HashMap::new();
+        for (t, &alive) in self.alive.iter().enumerate() {
+            if !alive {
+                continue;
+            }
+
+            if self.is_ghost(t) {
+                // the real triangle across the ghost edge a -> b has edge b -> a, which is the orientation of the hull
+                let [a, b, c] = self.triangles[t];
+                let (a, b) = match (a == GHOST, b == GHOST) {
+                    (true, _) => (b, c),
+                    (_, true) => (c, a),
+                    _ => (a, b),
+                };
+                hull_next.insert(b, a);
+            } else {
+                index[t] = triangles.len() / 3;
+                triangles.extend_from_slice(&self.triangles[t]);
+            }
+        }
+
+        let mut halfedges = vec![EMPTY; triangles.len()];
+        for t in 0..self.triangles.len() {
+            if index[t] == EMPTY {
+                continue;
+            }
+
+            for j in 0..3 {
+                let n = self.neighbors[t][j];
+                if index[n] != EMPTY {
+                    let e = 3 * index[t] + j;
+                    let (a, b) = (triangles[e], triangles[next_halfedge(e)]);
+                    halfedges[e] = 3 * index[n] + self.edge_index(n, b, a);
+                }
+            }
+        }
+
+        let start = *hull_next.keys().min().expect("Hull expected to exist");
+        let mut hull = vec![start];
+        let mut current = hull_next[&start];
+        while current != start {
+            hull.push(current);
+            current = hull_next[&current];
+        }
+
+        Triangulation {
+            triangles,
+            halfedges,
+            hull,
+        }
+    }
+}
+
+/// Sorts point indices along a Hilbert curve over their bounding box, so consecutive insertions are close to each other.
+fn hilbert_order(points: &[Point]) -> Vec<usize> {
+    let (min, max) = points.iter().fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    );
+    let extent = (max.x - min.x).max(max.y - min.y);
+    let scale = if extent > 0.0 {
+        (HILBERT_ORDER - 1) as f64 / extent
+    } else {
+        0.0
+    };
+
+    let mut keys: Vec<(u64, usize)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let x = ((p.x - min.x) * scale) as u64;
+            let y = ((p.y - min.y) * scale) as u64;
+            (hilbert_index(x, y), i)
+        })
+        .collect();
+    keys.sort_unstable();
+
+    keys.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Calculates the distance along the Hilbert curve of cell ```(x, y)``` in a grid of side [HILBERT_ORDER].
+fn hilbert_index(mut x: u64, mut y: u64) -> u64 {
+    let mut d = 0;
+    let mut s = HILBERT_ORDER / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        d += s * s * ((3 * rx) ^ ry);
+
+        // rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    d
+}
+
+fn nearly_equals(a: &Point, b: &Point) -> bool {
+    (a.x - b.x).abs() <= DUPLICATE_EPSILON && (a.y - b.y).abs() <= DUPLICATE_EPSILON
+}
+
+fn orient(a: &Point, b: &Point, c: &Point) -> f64 {
+    robust::orient2d(
+        robust::Coord { x: a.x, y: a.y },
+        robust::Coord { x: b.x, y: b.y },
+        robust::Coord { x: c.x, y: c.y },
+    )
+}
+
+fn incircle(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+    robust::incircle(
+        robust::Coord { x: a.x, y: a.y },
+        robust::Coord { x: b.x, y: b.y },
+        robust::Coord { x: c.x, y: c.y },
+        robust::Coord { x: d.x, y: d.y },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, BoundingBox, ConvexBoundary, Engine, Voronoi, VoronoiBuilder};
+
+    /// Gets the triangles as sorted triples of sites, and the hull rotated to start at its lowest site.
+    fn normalized(triangulation: &Triangulation) -> (Vec<[usize; 3]>, Vec<usize>) {
+        let mut triangles: Vec<[usize; 3]> = triangulation
+            .triangles
+            .chunks(3)
+            .map(|t| {
+                let mut t = [t[0], t[1], t[2]];
+                t.sort_unstable();
+                t
+            })
+            .collect();
+        triangles.sort_unstable();
+
+        let mut hull = triangulation.hull.clone();
+        if let Some(start) = (0..hull.len()).min_by_key(|&i| hull[i]) {
+            hull.rotate_left(start);
+        }
+
+        (triangles, hull)
+    }
+
+    fn build<T: ConvexBoundary>(
+        sites: &[Point],
+        boundary: &T,
+        engine: Engine,
+        epsilon: f64,
+    ) -> Option<Voronoi<T>> {
+        VoronoiBuilder::default()
+            .set_sites(sites.to_vec())
+            .set_boundary(boundary.clone())
+            .set_adjacency_epsilon(epsilon)
+            .set_triangulator(engine)
+            .build()
+    }
+
+    /// Returns whether the diagrams built by both engines differ in geometry or in topology.
+    fn engines_disagree<T: ConvexBoundary>(sites: &[Point], boundary: &T, epsilon: f64) -> bool {
+        match (
+            build(sites, boundary, Engine::Delaunator, epsilon),
+            build(sites, boundary, Engine::BowyerWatson, epsilon),
+        ) {
+            (Some(a), Some(b)) => {
+                // neighbors are listed starting from a triangulation dependent edge, so compare them as sets
+                let (adjacency_a, adjacency_b) = (a.adjacency(), b.adjacency());
+                let topology_differs = (0..sites.len()).any(|s| {
+                    let mut na = adjacency_a.neighbors_of(s).to_vec();
+                    let mut nb = adjacency_b.neighbors_of(s).to_vec();
+                    na.sort_unstable();
+                    nb.sort_unstable();
+                    na != nb
+                });
+
+                topology_differs || !utils::test::geometry_approx_eq(&a, &b, epsilon)
+            }
+            (None, None) => false,
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn random_sites_match_delaunator() {
+        let mut rng = utils::test::rng();
+        for _ in 0..50 {
+            let size = rng.gen_range(3..500);
+            let points: Vec<Point> = (0..size)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+
+            let expected = delaunator::triangulate(&points);
+            let actual = triangulate(&points);
+            assert_eq!(normalized(&actual), normalized(&expected));
+            assert_eq!(actual.halfedges.len(), actual.triangles.len());
+            for (e, &twin) in actual.halfedges.iter().enumerate() {
+                if twin != EMPTY {
+                    assert_eq!(actual.halfedges[twin], e);
+                    assert_eq!(actual.triangles[e], actual.triangles[next_halfedge(twin)]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_sites_are_handled_like_delaunator() {
+        let collinear: Vec<Point> = (0..5)
+            .map(|i| Point {
+                x: i as f64,
+                y: 2.0 * i as f64,
+            })
+            .collect();
+        assert!(triangulate(&collinear).triangles.is_empty());
+
+        // duplicates are left out, and points on the hull are kept on it
+        let points = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 },
+        ];
+        assert_eq!(
+            normalized(&triangulate(&points)),
+            normalized(&delaunator::triangulate(&points))
+        );
+    }
+
+    #[test]
+    fn random_diagrams_are_valid() {
+        let mut rng = utils::test::rng();
+        for _ in 0..20 {
+            let sites: Vec<Point> = (0..200)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+            let voronoi = build(&sites, &BoundingBox::default(), Engine::BowyerWatson, 0.0)
+                .expect("Some voronoi expected");
+            assert_eq!(voronoi.triangulator(), Engine::BowyerWatson);
+            utils::test::validate_voronoi(&voronoi);
+        }
+    }
+
+    #[test]
+    fn engines_agree_on_assets() -> std::io::Result<()> {
+        for asset in utils::test::VALID_ASSETS.iter() {
+            let builder = utils::test::new_voronoi_builder_from_asset(asset)?;
+            let voronoi = builder.build().expect("Some voronoi expected");
+            let sites = voronoi.sites().clone();
+            let boundary = voronoi.boundary().clone();
+            let epsilon = 1e-9 * boundary.width().max(boundary.height());
+
+            if engines_disagree(&sites, &boundary, epsilon) {
+                let minimal = utils::test::minimize_failure(sites, |s| {
+                    engines_disagree(s, &boundary, epsilon)
+                });
+                panic!(
+                    "Engines disagree on {}. Minimal disagreeing sites: {:?}",
+                    asset, minimal
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimizer_finds_minimal_failing_subset() {
+        let items: Vec<usize> = (0..100).collect();
+        let minimal = utils::test::minimize_failure(items, |s| s.contains(&17) && s.contains(&60));
+        assert_eq!(minimal, vec![17, 60]);
+    }
+}
@@ -2,13 +2,19 @@ use delaunator::{next_halfedge, Triangulation, EMPTY};
 
 use super::{
     iterator::EdgesAroundSiteIterator,
-    utils::{self, site_of_incoming},
+    utils::{self, clip_half_plane, site_of_incoming},
     ClipBehavior, Point,
 };
-use crate::{boundary, utils::triangle_of_edge, ConvexBoundary};
+use crate::{
+    boundary, instrument::phase, sweep_hull::SweepHullArena, utils::triangle_of_edge,
+    ConvexBoundary,
+};
 
 const VORONOI_INFINITY: f64 = 1e+10_f64;
 
+/// Owner of a boundary vertex that was not calculated yet, see [CellBuilder::for_update()].
+const UNKNOWN_OWNER: usize = EMPTY - 1;
+
 #[derive(Debug)]
 pub struct CellBuilder<'t, T: ConvexBoundary> {
     triangulation: &'t Triangulation,
@@ -29,6 +35,30 @@ pub struct CellBuilderResult {
     pub site_to_incoming_leftmost_halfedge: Vec<usize>,
 }
 
+/// Scratch buffers used while building diagrams, which can be kept to build other diagrams without allocating them again.
+///
+/// The triangulation, the vertices and each cell are assembled in scratch buffers and then copied to vectors of their exact size,
+/// so each of them is allocated once. The vectors left are the parts of the diagram itself, one of which per cell.
+#[derive(Debug, Default)]
+pub(crate) struct CellBuilderArena {
+    pub(crate) triangulation: SweepHullArena,
+    vertices: Vec<Point>,
+    tmp_cell: Vec<usize>,
+    cell: Vec<usize>,
+    hull_extensions: Vec<usize>,
+    boundary_vertex_ownership: Vec<usize>,
+    is_vertex_inside_boundary: Vec<bool>,
+}
+
+impl CellBuilderArena {
+    /// Takes the empty buffer of the vertices, which [CellBuilder::build()] gives back.
+    pub(crate) fn take_vertices(&mut self) -> Vec<Point> {
+        let mut vertices = std::mem::take(&mut self.vertices);
+        vertices.clear();
+        vertices
+    }
+}
+
 impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
     pub fn new(
         triangulation: &'t Triangulation,
@@ -36,22 +66,25 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
         vertices: Vec<Point>,
         boundary: T,
         clip_behavior: ClipBehavior,
+        arena: &mut CellBuilderArena,
     ) -> Self {
         let site_to_incoming_leftmost_halfedge =
             calculate_incoming_edges(triangulation, sites.len());
-        let is_vertex_inside_boundary: Vec<bool> =
-            vertices.iter().map(|c| boundary.is_inside(c)).collect();
+        let mut is_vertex_inside_boundary = std::mem::take(&mut arena.is_vertex_inside_boundary);
+        is_vertex_inside_boundary.clear();
+        is_vertex_inside_boundary.extend(vertices.iter().map(|c| boundary.is_inside(c)));
 
-        let boundary_vertex_ownership = if clip_behavior == ClipBehavior::Clip {
+        let mut boundary_vertex_ownership = std::mem::take(&mut arena.boundary_vertex_ownership);
+        boundary_vertex_ownership.clear();
+        if clip_behavior == ClipBehavior::Clip {
             calculate_boundary_vertex_ownership(
                 &boundary.vertices(),
                 &triangulation,
                 sites,
                 &site_to_incoming_leftmost_halfedge,
-            )
-        } else {
-            Vec::with_capacity(0)
-        };
+                &mut boundary_vertex_ownership,
+            );
+        }
 
         Self {
             triangulation,
@@ -67,21 +100,111 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
         }
     }
 
-    pub fn build(mut self) -> CellBuilderResult {
+    /// Creates a builder to rebuild some of the cells of a diagram, whose ```vertices``` are the ```number_of_circumcenters``` circumcenters of ```triangulation```
+    /// followed by the vertices added by clipping, starting with the vertices of ```boundary```.
+    ///
+    /// Whether a vertex is inside the boundary, and which site owns each boundary vertex, is only calculated for the cells rebuilt.
+    pub fn for_update(
+        triangulation: &'t Triangulation,
+        sites: &'t Vec<Point>,
+        vertices: Vec<Point>,
+        site_to_incoming_leftmost_halfedge: Vec<usize>,
+        number_of_circumcenters: usize,
+        boundary: T,
+        arena: &mut CellBuilderArena,
+    ) -> Self {
+        let mut is_vertex_inside_boundary = std::mem::take(&mut arena.is_vertex_inside_boundary);
+        is_vertex_inside_boundary.clear();
+        let mut boundary_vertex_ownership = std::mem::take(&mut arena.boundary_vertex_ownership);
+        boundary_vertex_ownership.clear();
+        boundary_vertex_ownership.resize(boundary.vertices().len(), UNKNOWN_OWNER);
+
+        Self {
+            triangulation,
+            sites,
+            site_to_incoming_leftmost_halfedge,
+            is_vertex_inside_boundary,
+            boundary_vertex_ownership,
+            first_boundary_vertex_index: number_of_circumcenters,
+            number_of_circumcenters,
+            vertices,
+            boundary,
+            clip_behavior: ClipBehavior::Clip,
+        }
+    }
+
+    /// Builds the cells, and gives the scratch buffers back to ```arena```, along with the vertices, which are copied to the result.
+    pub fn build(mut self, arena: &mut CellBuilderArena) -> CellBuilderResult {
         // adds the vertices of the boundary as potential vertices for the voronoi
         if self.clip_behavior == ClipBehavior::Clip {
             self.calculate_boundary_vertices();
         }
 
-        let cells = self.build_cells();
+        let cells = self.build_cells(arena);
+        arena.boundary_vertex_ownership = self.boundary_vertex_ownership;
+        arena.is_vertex_inside_boundary = self.is_vertex_inside_boundary;
+        let vertices = self.vertices.clone();
+        arena.vertices = self.vertices;
 
         CellBuilderResult {
-            vertices: self.vertices,
+            vertices,
             site_to_incoming_leftmost_halfedge: self.site_to_incoming_leftmost_halfedge,
             cells,
         }
     }
 
+    /// Rebuilds and clips the cells of ```sites``` in ```cells```, and gives the scratch buffers back to ```arena```.
+    ///
+    /// Returns the vertices, with those added by clipping the cells appended, and the left-most incoming half-edge of each site.
+    pub fn rebuild_cells(
+        mut self,
+        sites: &[usize],
+        cells: &mut [Vec<usize>],
+        arena: &mut CellBuilderArena,
+    ) -> (Vec<Point>, Vec<usize>) {
+        let triangulation = self.triangulation;
+        let _phase = phase!(
+            debug_span,
+            "clipping",
+            hull_sites = sites
+                .iter()
+                .filter(|&&site| {
+                    triangulation.halfedges[self.site_to_incoming_leftmost_halfedge[site]] == EMPTY
+                })
+                .count()
+        );
+        let CellBuilderArena { tmp_cell, cell, .. } = arena;
+        for &site in sites {
+            let incoming = self.site_to_incoming_leftmost_halfedge[site];
+            tmp_cell.clear();
+            if triangulation.halfedges[incoming] == EMPTY {
+                // a hull cell is closed by the extensions of its incoming and outgoing hull edges, as when building all cells
+                let incoming_extension = self.extend_voronoi_vertex(incoming);
+                tmp_cell.push(incoming_extension);
+                let mut last = incoming;
+                for edge in EdgesAroundSiteIterator::new(triangulation, incoming) {
+                    tmp_cell.push(triangle_of_edge(edge));
+                    last = edge;
+                }
+                let outgoing_extension = self.extend_voronoi_vertex(next_halfedge(last));
+                tmp_cell.push(outgoing_extension);
+            } else {
+                tmp_cell.extend(
+                    EdgesAroundSiteIterator::new(triangulation, incoming).map(triangle_of_edge),
+                );
+            }
+
+            cell.clear();
+            self.clip_cell(tmp_cell, cell, site);
+            cells[site].clear();
+            cells[site].extend_from_slice(cell);
+        }
+
+        arena.boundary_vertex_ownership = self.boundary_vertex_ownership;
+        arena.is_vertex_inside_boundary = self.is_vertex_inside_boundary;
+        (self.vertices, self.site_to_incoming_leftmost_halfedge)
+    }
+
     pub fn calculate_boundary_vertices(&mut self) {
         // add all boundary vertices to the vertex list as they will be used for closing cells
 
@@ -94,41 +217,54 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
     /// Builds cells for each site.
     /// This won't extend not close the hull.
     /// This will not clip any edges to the bounding geometry.
-    fn build_cells(&mut self) -> Vec<Vec<usize>> {
+    fn build_cells(&mut self, arena: &mut CellBuilderArena) -> Vec<Vec<usize>> {
         let triangulation = self.triangulation;
         let sites: &Vec<Point> = self.sites;
         let num_of_sites = sites.len();
         let mut cells: Vec<Vec<usize>> = vec![Vec::new(); num_of_sites];
-        let mut tmp_cell = Vec::new();
+        let CellBuilderArena {
+            tmp_cell,
+            cell,
+            hull_extensions,
+            ..
+        } = arena;
 
         if self.clip_behavior == ClipBehavior::Clip {
             // For each hull edge a->b, extend its associated circumcenter vertex beyond the boundary
-            // Add this extended vertex to its cell, and the hull previous cell that shares the same extended vertex
-            for (&a, &b) in triangulation
-                .hull
-                .iter()
-                .zip(triangulation.hull.iter().cycle().skip(1))
-            {
+            // The extended vertex is shared by the cell of b, as its first vertex, and the cell of a, as its last vertex
+            let hull = &triangulation.hull;
+            let _phase = phase!(debug_span, "clipping", hull_sites = hull.len());
+            hull_extensions.clear();
+            for &b in hull.iter().cycle().skip(1).take(hull.len()) {
                 let hull_edge = self.site_to_incoming_leftmost_halfedge[b];
-                let extension = self.extend_voronoi_vertex(hull_edge);
-
-                // add extension as first vertex in incoming site
-                cells[b].push(extension);
-                cells[b].extend(
-                    EdgesAroundSiteIterator::new(triangulation, hull_edge)
-                        .map(utils::triangle_of_edge),
-                );
-
-                // add extension as last vertex in outgoing site
-                cells[a].push(extension);
+                hull_extensions.push(self.extend_voronoi_vertex(hull_edge));
             }
 
             // Clip hull sites
-            for &hull_site in &triangulation.hull {
-                let hull_cell = &mut cells[hull_site];
+            for (i, &hull_site) in hull.iter().enumerate() {
+                let incoming = hull_extensions[(i + hull.len() - 1) % hull.len()];
+                let outgoing = hull_extensions[i];
+                let circumcenters = EdgesAroundSiteIterator::new(
+                    triangulation,
+                    self.site_to_incoming_leftmost_halfedge[hull_site],
+                )
+                .map(utils::triangle_of_edge);
+
+                // the first hull site gets its outgoing extension before the others, as the hull edges are extended in order
                 tmp_cell.clear();
-                std::mem::swap(hull_cell, &mut tmp_cell);
-                self.clip_cell(&tmp_cell, hull_cell, hull_site);
+                if i == 0 {
+                    tmp_cell.push(outgoing);
+                    tmp_cell.push(incoming);
+                    tmp_cell.extend(circumcenters);
+                } else {
+                    tmp_cell.push(incoming);
+                    tmp_cell.extend(circumcenters);
+                    tmp_cell.push(outgoing);
+                }
+
+                cell.clear();
+                self.clip_cell(tmp_cell, cell, hull_site);
+                cells[hull_site] = cell.clone();
             }
         }
 
@@ -136,10 +272,9 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
         for edge in 0..triangulation.triangles.len() {
             // note: because we are not processing any hull sites here, all edges will be incoming edge of some site
             let site = site_of_incoming(triangulation, edge);
-            let cell = &mut cells[site];
 
             // if cell is empty, it hasn't been processed yet
-            if cell.len() == 0 {
+            if cells[site].len() == 0 {
                 #[cfg(debug_logs)]
                 println!();
                 #[cfg(debug_logs)]
@@ -147,14 +282,16 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
 
                 let circumcenter_iter =
                     EdgesAroundSiteIterator::new(triangulation, edge).map(utils::triangle_of_edge);
+                cell.clear();
                 if self.clip_behavior == ClipBehavior::Clip {
                     tmp_cell.clear();
                     tmp_cell.extend(circumcenter_iter);
-                    self.clip_cell(&tmp_cell, cell, site);
+                    self.clip_cell(tmp_cell, cell, site);
                 } else {
                     cell.extend(circumcenter_iter);
                     cell.reverse();
                 }
+                cells[site] = cell.clone();
 
                 #[cfg(debug_logs)]
                 println!("  [{site}/{edge}] Cell: {:?}", cell);
@@ -176,6 +313,13 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
             .next()
         {
             (inside.0, *inside.1, true)
+        } else if self.boundary.is_inside(&self.sites[site]) {
+            // the circumcenters, and the hull extensions, may be so far away that the polygon they form does not even reach the boundary
+            // the cell is then clipped as the intersection of half-planes instead, which always contains the site
+            #[cfg(feature = "tracing")]
+            tracing::debug!(name: "half_plane_clipping", target: "voronoice", site = site);
+            self.clip_half_planes(cell, site);
+            return;
         } else {
             // when cell is entirely outside the boundary, it will always fall into the loop case below that the edge needs to be clipped
             (0, tmp_cell[0], false)
@@ -200,8 +344,15 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
                 // voronoi edge has both vertices outside boundary
                 (false, false) => {
                     // but may cross the boundary, so check for an intersection
-                    let (first_clip, second_clip) = self.clip_voronoi_edge(prev, c);
-                    if let Some(first_clip) = first_clip {
+                    // unless both vertices are the same point, e.g. circumcenters of cocircular sites, which the line through them is not defined for
+                    let (first_clip, second_clip) = if self.are_same_vertex(prev, c) {
+                        (None, None)
+                    } else {
+                        self.clip_voronoi_edge(prev, c)
+                    };
+                    // an edge with both vertices outside crosses the convex boundary twice, or not at all
+                    // a single intersection comes from rounding errors in the direction of an edge between vertices very close to each other, which does not cross it
+                    if let (Some(first_clip), Some(second_clip)) = (first_clip, second_clip) {
                         if cell_open {
                             // an edge crossing the box still leaves the cell open, but it may be able to wrap around a boundary vertex
                             // this is an edge case, see degerated10.json input
@@ -212,12 +363,15 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
                             println!("  [{site}] Edge {prev} -> {c}. Edge outside box. The box was open.");
                         }
                         #[cfg(debug_logs)]
-                        println!("  [{site}] Edge {prev} -> {c}. First clip: {first_clip}. Second clip: {}", second_clip.unwrap_or_default());
-                        self.insert_edge_and_wrap_around_boundary_vertices(site, cell,
-                           first_clip,
-                second_clip.expect("Two intersection points need to occur when a line crosses the convex boundary"));
+                        println!("  [{site}] Edge {prev} -> {c}. First clip: {first_clip}. Second clip: {second_clip}");
+                        self.insert_edge_and_wrap_around_boundary_vertices(
+                            site,
+                            cell,
+                            first_clip,
+                            second_clip,
+                        );
                         #[cfg(debug_logs)]
-                        println!("  [{site}] Edge {prev} -> {c}. Edge outside box. Entered at {} and left at {}", first_clip, second_clip.unwrap());
+                        println!("  [{site}] Edge {prev} -> {c}. Edge outside box. Entered at {} and left at {}", first_clip, second_clip);
                     } else {
                         #[cfg(debug_logs)]
                         println!(
@@ -267,6 +421,43 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
         }
     }
 
+    /// Builds the cell of ```site``` by clipping the boundary with the half-plane of the points closer to ```site``` than to each of its Delaunay neighbors.
+    ///
+    /// Unlike [Self::clip_cell()], this does not depend on the position of the circumcenters, so it yields a non-empty cell for any site inside the boundary.
+    fn clip_half_planes(&mut self, cell: &mut Vec<usize>, site: usize) {
+        let triangulation = self.triangulation;
+        let s = &self.sites[site];
+        let mut neighbors: Vec<usize> = EdgesAroundSiteIterator::new(
+            triangulation,
+            self.site_to_incoming_leftmost_halfedge[site],
+        )
+        .flat_map(|incoming| {
+            [
+                triangulation.triangles[incoming],
+                triangulation.triangles[next_halfedge(next_halfedge(incoming))],
+            ]
+        })
+        .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+
+        let mut polygon = self.boundary.vertices().to_vec();
+        for neighbor in neighbors {
+            // points p closer to s than to n satisfy p . (n - s) <= (|n|² - |s|²) / 2
+            let n = &self.sites[neighbor];
+            let offset = (n.x * n.x + n.y * n.y - s.x * s.x - s.y * s.y) / 2.0;
+            polygon = clip_half_plane(&polygon, |p| offset - p.x * (n.x - s.x) - p.y * (n.y - s.y));
+        }
+
+        // corners of the boundary resolve to the boundary vertices, and points on its edges to the vertices clipped by neighboring cells
+        for p in polygon {
+            let v = self.add_new_vertex(p);
+            if cell.last() != Some(&v) && cell.first() != Some(&v) {
+                cell.push(v);
+            }
+        }
+    }
+
     /// Clip a voronoi edge on the edge of the bounding geometry, if the edge crosses the boundary.
     ///
     /// The edge may cross the bounding geometry once (when ```a``` is inside the boundary) or twice (when ```a``` and ```b``` are outside the boundary).
@@ -334,20 +525,20 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
 
         // first_edge is to the right of first_clip -> second_clip
         // second_edge is to the left of first_clip -> second_clip
-        if self.boundary_vertex_ownership[first_edge] == site {
+        if self.boundary_vertex_owner(first_edge, site) == site {
             // first_edge and all boundary vertices counter clockwise need to be added to this cell
             let mut edge = first_edge;
-            while edge != second_edge && self.boundary_vertex_ownership[edge] == site {
+            while edge != second_edge && self.boundary_vertex_owner(edge, site) == site {
                 cell.push(self.first_boundary_vertex_index + edge);
                 self.boundary_vertex_ownership[edge] = EMPTY; // prevent another edge from duplicating this boundary vertex in the cell
                 edge = self.boundary.next_edge(edge);
             }
             cell.push(second_clip);
-        } else if self.boundary_vertex_ownership[second_edge] == site {
+        } else if self.boundary_vertex_owner(second_edge, site) == site {
             // second_edge and all boundary vertices clockwise need to be added to this cell
             cell.push(second_clip);
             let mut edge = second_edge;
-            while edge != first_edge && self.boundary_vertex_ownership[edge] == site {
+            while edge != first_edge && self.boundary_vertex_owner(edge, site) == site {
                 cell.push(self.first_boundary_vertex_index + edge);
                 self.boundary_vertex_ownership[edge] = EMPTY;
                 edge = self.boundary.next_edge(edge);
@@ -361,44 +552,58 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
         println!("  [{site}] Edge {first_clip} ({first_edge}) -> {second_clip} ({second_edge}). Wrapping around {:?}", &cell[len-1..]);
     }
 
-    fn extend_voronoi_vertex(&mut self, hull_edge: usize) -> usize {
-        let circumcenter = triangle_of_edge(hull_edge);
-        let circumcenter_pos = &self.vertices[circumcenter];
-
-        let (a, b) = (
-            self.triangulation.triangles[hull_edge],
-            self.triangulation.triangles[next_halfedge(hull_edge)],
-        );
-
-        let a_pos = &self.sites[a];
-        let b_pos = &self.sites[b];
-
-        // the projection direction is orthogonal to the hull's edge (a -> b)
-        // put it just beyond boundary edge
-        let mut orthogonal = Point {
-            x: a_pos.y - b_pos.y,
-            y: b_pos.x - a_pos.x,
-        };
+    /// Gets the site owning the boundary vertex ```edge```, or [EMPTY] once it was added to a cell.
+    ///
+    /// When rebuilding some cells, the owner is only calculated the first time it is needed, walking from ```site```, whose cell is being clipped close to the vertex.
+    fn boundary_vertex_owner(&mut self, edge: usize, site: usize) -> usize {
+        if self.boundary_vertex_ownership[edge] == UNKNOWN_OWNER {
+            self.boundary_vertex_ownership[edge] =
+                crate::iterator::shortest_path_iter_from_triangulation(
+                    self.triangulation,
+                    self.sites,
+                    &self.site_to_incoming_leftmost_halfedge,
+                    site,
+                    self.boundary.vertices()[edge].clone(),
+                )
+                .last()
+                .expect("There must be one site that is the closest.");
+        }
 
-        // normalizing the orthogonal vector
-        let ortho_length = (orthogonal.x * orthogonal.x + orthogonal.y * orthogonal.y).sqrt();
-        orthogonal.x *= 1.0 / ortho_length;
-        orthogonal.y *= 1.0 / ortho_length;
+        self.boundary_vertex_ownership[edge]
+    }
 
-        // project to "inifity"
-        let projected = Point {
-            x: circumcenter_pos.x + orthogonal.x * VORONOI_INFINITY,
-            y: circumcenter_pos.y + orthogonal.y * VORONOI_INFINITY,
-        };
+    fn extend_voronoi_vertex(&mut self, hull_edge: usize) -> usize {
+        let projected = extend_hull_circumcenter(
+            self.triangulation,
+            self.sites,
+            &self.vertices[triangle_of_edge(hull_edge)],
+            hull_edge,
+        );
         let v = self.add_new_vertex(projected);
 
         #[cfg(debug_logs)]
-        println!("  Hull edge {hull_edge} (circumcenter {circumcenter}) extended orthogonally to {a} -> {b} at {}", v);
+        println!(
+            "  Hull edge {hull_edge} (circumcenter {}) extended orthogonally at {v}",
+            triangle_of_edge(hull_edge)
+        );
         v
     }
 
+    /// Gets whether ```vertex``` is a circumcenter inside the boundary. This is calculated on the fly when rebuilding some cells, see [Self::for_update()].
     fn is_vertex_inside_boundary(&self, vertex: usize) -> bool {
-        *self.is_vertex_inside_boundary.get(vertex).unwrap_or(&false)
+        match self.is_vertex_inside_boundary.get(vertex) {
+            Some(&inside) => inside,
+            None => {
+                vertex < self.number_of_circumcenters
+                    && self.boundary.is_inside(&self.vertices[vertex])
+            }
+        }
+    }
+
+    fn are_same_vertex(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.vertices[a], &self.vertices[b]);
+        utils::abs_diff_eq(a.x, b.x, utils::EQ_EPSILON)
+            && utils::abs_diff_eq(a.y, b.y, utils::EQ_EPSILON)
     }
 
     /// Adds a new vertex if it doesn't already exist.
@@ -426,15 +631,52 @@ impl<'t, T: ConvexBoundary> CellBuilder<'t, T> {
     }
 }
 
+/// Extends the circumcenter of the triangle of ```hull_edge``` to "infinity", orthogonally to and beyond the hull edge.
+///
+/// This is the vertex closing the open Voronoi edge dual to ```hull_edge``` before it gets clipped.
+pub(crate) fn extend_hull_circumcenter(
+    triangulation: &Triangulation,
+    sites: &[Point],
+    circumcenter: &Point,
+    hull_edge: usize,
+) -> Point {
+    let (a, b) = (
+        triangulation.triangles[hull_edge],
+        triangulation.triangles[next_halfedge(hull_edge)],
+    );
+
+    let a_pos = &sites[a];
+    let b_pos = &sites[b];
+
+    // the projection direction is orthogonal to the hull's edge (a -> b)
+    // put it just beyond boundary edge
+    let mut orthogonal = Point {
+        x: a_pos.y - b_pos.y,
+        y: b_pos.x - a_pos.x,
+    };
+
+    // normalizing the orthogonal vector
+    let ortho_length = (orthogonal.x * orthogonal.x + orthogonal.y * orthogonal.y).sqrt();
+    orthogonal.x *= 1.0 / ortho_length;
+    orthogonal.y *= 1.0 / ortho_length;
+
+    // project to "inifity"
+    Point {
+        x: circumcenter.x + orthogonal.x * VORONOI_INFINITY,
+        y: circumcenter.y + orthogonal.y * VORONOI_INFINITY,
+    }
+}
+
 /// Calculates to which sites each boundary vertex belongs to.
 fn calculate_boundary_vertex_ownership(
     boundary_vertices: &[Point],
     triangulation: &Triangulation,
     sites: &Vec<Point>,
     site_to_incoming_leftmost_halfedge: &Vec<usize>,
-) -> Vec<usize> {
+    vertex_owners: &mut Vec<usize>,
+) {
     // vertices counter-clockwise
-    let mut vertex_owners: Vec<usize> = Vec::with_capacity(boundary_vertices.len());
+    vertex_owners.reserve(boundary_vertices.len());
 
     // the end of the shortest path between any site and a point is the site containing that point
     // we use this to figure out which sites own which boundary vertices
@@ -458,11 +700,12 @@ fn calculate_boundary_vertex_ownership(
         site = owner;
         vertex_owners.push(owner);
     }
-
-    vertex_owners
 }
 
-fn calculate_incoming_edges(triangulation: &Triangulation, num_of_sites: usize) -> Vec<usize> {
+pub(crate) fn calculate_incoming_edges(
+    triangulation: &Triangulation,
+    num_of_sites: usize,
+) -> Vec<usize> {
     // create map between site and its left-most incoming half-edge
     // this is especially important for the sites along the convex hull boundary when iterating over its neighoring sites
     let mut site_to_incoming_leftmost_halfedge = vec![EMPTY; num_of_sites];
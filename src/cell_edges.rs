@@ -0,0 +1,263 @@
+use delaunator::{next_halfedge, EMPTY};
+
+use crate::{utils, ClipBehavior, ConvexBoundary, Point, Voronoi};
+
+/// An edge of a cell, as returned by [Voronoi::iter_cell_edges()].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellEdgeRef<'v> {
+    /// The site of the cell.
+    pub site: usize,
+
+    /// The position of this edge within the cell: the edge starting at the ```edge```-th vertex of [crate::VoronoiCell::triangles()].
+    pub edge: usize,
+
+    /// The pair of indices into [Voronoi::vertices()] that form this edge, as returned by [crate::VoronoiCell::iter_edges()].
+    pub vertices: (usize, usize),
+
+    /// The positions of [Self::vertices], borrowed from [Voronoi::vertices()].
+    pub endpoints: (&'v Point, &'v Point),
+
+    /// The site on the other side of this edge, if any, as for [crate::CellEdge::neighbor].
+    pub neighbor: Option<usize>,
+
+    /// Whether this edge lies along the boundary. This is always ```false``` unless the cells are clipped, with [ClipBehavior::Clip].
+    pub on_boundary: bool,
+}
+
+/// Iterates over the edges of all cells, walking [Voronoi::cells()] directly.
+#[derive(Clone)]
+struct CellEdgeIterator<'v, T: ConvexBoundary> {
+    voronoi: &'v Voronoi<T>,
+    site: usize,
+    edge: usize,
+    remaining: usize,
+}
+
+impl<'v, T: ConvexBoundary> CellEdgeIterator<'v, T> {
+    /// Gets the neighbor across the edge ```u -> v``` of the cell of ```site```, and whether the edge is on the boundary.
+    ///
+    /// An edge between the circumcenters of two Delaunay triangles sharing an edge with ```site``` is dual to that edge, so the neighbor is its other site,
+    /// and it is not on the boundary. Other edges, such as edges to vertices added by clipping, search for the neighbor around the site.
+    fn neighbor(&self, site: usize, u: usize, v: usize) -> (Option<usize>, bool) {
+        let voronoi = self.voronoi;
+        let triangles = voronoi.number_of_triangles();
+        if u < triangles && v < triangles {
+            let triangulation = &voronoi.triangulation;
+            let dual = (3 * u..3 * u + 3).find_map(|e| {
+                let twin = triangulation.halfedges[e];
+                let (a, b) = (
+                    triangulation.triangles[e],
+                    triangulation.triangles[next_halfedge(e)],
+                );
+                match (
+                    twin != EMPTY && utils::triangle_of_edge(twin) == v,
+                    a == site,
+                ) {
+                    (true, true) => Some(b),
+                    (true, false) if b == site => Some(a),
+                    _ => None,
+                }
+            });
+            if let Some(n) = dual
+                .filter(|&n| utils::cyclic_pairs(&voronoi.cells[n]).any(|(x, y)| x == v && y == u))
+            {
+                // the shared edge is at least as long as this edge
+                let long = utils::dist2(&voronoi.circumcenters[u], &voronoi.circumcenters[v])
+                    .sqrt()
+                    > voronoi.adjacency_epsilon;
+                return (
+                    Some(n).filter(|&n| long || utils::has_common_voronoi_edge(voronoi, site, n)),
+                    false,
+                );
+            }
+        }
+
+        // the midpoint of an edge of a clipped cell is on the boundary only if the whole edge is, as the boundary is convex
+        let (pu, pv) = (&voronoi.circumcenters[u], &voronoi.circumcenters[v]);
+        let midpoint = Point {
+            x: (pu.x + pv.x) / 2.0,
+            y: (pu.y + pv.y) / 2.0,
+        };
+        (
+            utils::edge_neighbor(voronoi, site, u, v),
+            voronoi.clip_behavior == ClipBehavior::Clip
+                && voronoi.boundary.which_edge(&midpoint).is_some(),
+        )
+    }
+}
+
+impl<'v, T: ConvexBoundary> Iterator for CellEdgeIterator<'v, T> {
+    type Item = CellEdgeRef<'v>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cells = &self.voronoi.cells;
+        while self.edge >= cells.get(self.site)?.len() {
+            self.site += 1;
+            self.edge = 0;
+        }
+
+        let (site, edge, cell) = (self.site, self.edge, &cells[self.site]);
+        let (u, v) = (cell[edge], cell[(edge + 1) % cell.len()]);
+        let (neighbor, on_boundary) = self.neighbor(site, u, v);
+        self.edge += 1;
+        self.remaining -= 1;
+
+        let vertices = &self.voronoi.circumcenters;
+        Some(CellEdgeRef {
+            site,
+            edge,
+            vertices: (u, v),
+            endpoints: (&vertices[u], &vertices[v]),
+            neighbor,
+            on_boundary,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: ConvexBoundary> ExactSizeIterator for CellEdgeIterator<'_, T> {}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets an iterator over the edges of all cells, in a single pass: the edges of each cell in counter-clockwise order, the cells in order of site.
+    ///
+    /// The edges and their neighbors are the same as those of [crate::VoronoiCell::iter_edge_metadata()] for each cell of [Self::iter_cells()] in turn,
+    /// but are read directly from the cells of the diagram, without calculating lengths or unclipped endpoints. The number of edges left is always known,
+    /// so the output can be allocated up front.
+    ///
+    /// As for [crate::VoronoiCell::iter_edges()], the last edge of a hull cell closes it when clipping is disabled, and should be discarded.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(5)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let edges = voronoi.iter_cell_edges();
+    /// let mut faces = Vec::with_capacity(edges.len());
+    /// faces.extend(edges.filter(|e| e.on_boundary).map(|e| (e.site, e.endpoints)));
+    /// assert_eq!(faces.len(), 4 * 5);
+    ///```
+    pub fn iter_cell_edges(&self) -> impl ExactSizeIterator<Item = CellEdgeRef<'_>> + Clone {
+        CellEdgeIterator {
+            voronoi: self,
+            site: 0,
+            edge: 0,
+            remaining: self.cells.iter().map(Vec::len).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, CellEdge, ClipBehavior, VoronoiBuilder};
+
+    fn random_builder(size: usize) -> VoronoiBuilder<BoundingBox> {
+        VoronoiBuilder::default().set_sites(utils::test::random_sites(size))
+    }
+
+    fn assert_matches_cells<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let expected: Vec<(usize, usize, CellEdge)> = voronoi
+            .iter_cells()
+            .flat_map(|cell| {
+                let site = cell.site();
+                cell.iter_edge_metadata()
+                    .enumerate()
+                    .map(move |(i, e)| (site, i, e))
+            })
+            .collect();
+
+        let mut edges = voronoi.iter_cell_edges();
+        assert_eq!(edges.len(), expected.len());
+        for (site, i, e) in expected {
+            let edge = edges.next().expect("Edge expected");
+            assert_eq!((edge.site, edge.edge, edge.vertices), (site, i, e.vertices));
+            assert_eq!(
+                edge.endpoints,
+                (
+                    &voronoi.vertices()[e.vertices.0],
+                    &voronoi.vertices()[e.vertices.1]
+                )
+            );
+            assert_eq!(edge.neighbor, e.neighbor, "Edge {} of cell {}.", i, site);
+            if edge.on_boundary {
+                assert_eq!(e.neighbor, None);
+            }
+        }
+        assert_eq!(edges.next(), None);
+    }
+
+    #[test]
+    fn edges_match_cell_edges() {
+        for asset in utils::test::VALID_ASSETS.iter() {
+            let voronoi = utils::test::new_voronoi_builder_from_asset(asset)
+                .expect("Asset expected")
+                .build()
+                .expect("Some voronoi expected");
+            assert_matches_cells(&voronoi);
+        }
+
+        let builder = random_builder(500);
+        for clip_behavior in [
+            ClipBehavior::Clip,
+            ClipBehavior::None,
+            ClipBehavior::RemoveSitesOutsideBoundaryOnly,
+        ] {
+            let voronoi = builder
+                .clone()
+                .set_boundary(BoundingBox::new_centered_square(1.5))
+                .set_clip_behavior(clip_behavior)
+                .build()
+                .expect("Some voronoi expected");
+            assert_matches_cells(&voronoi);
+        }
+
+        // welding and decimation change the vertices of the cells
+        let voronoi = builder
+            .set_vertex_welding(1e-2)
+            .set_max_cell_vertices(5)
+            .build()
+            .expect("Some voronoi expected");
+        assert_matches_cells(&voronoi);
+    }
+
+    #[test]
+    fn size_hint_is_exact() {
+        let voronoi = random_builder(200).build().expect("Some voronoi expected");
+        let mut edges = voronoi.iter_cell_edges();
+        let total: usize = voronoi.cells().iter().map(Vec::len).sum();
+        for remaining in (0..=total).rev() {
+            assert_eq!(edges.size_hint(), (remaining, Some(remaining)));
+            assert_eq!(edges.next().is_some(), remaining > 0);
+        }
+        assert_eq!(edges.len(), 0);
+    }
+
+    #[test]
+    fn boundary_edges_cover_boundary() {
+        let builder = random_builder(300);
+        let voronoi = builder.clone().build().expect("Some voronoi expected");
+        let boundary: f64 = voronoi
+            .iter_cell_edges()
+            .filter(|e| e.on_boundary)
+            .map(|e| utils::dist2(e.endpoints.0, e.endpoints.1).sqrt())
+            .sum();
+        assert!(
+            (boundary - 8.0).abs() < 1e-9,
+            "Boundary length {}.",
+            boundary
+        );
+
+        let unclipped = builder
+            .set_clip_behavior(ClipBehavior::None)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(unclipped.iter_cell_edges().all(|e| !e.on_boundary));
+    }
+}
@@ -0,0 +1,341 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{ConvexBoundary, Voronoi};
+
+/// A sparse map of values keyed by site, tied to the diagram it was created from.
+///
+/// Use [Voronoi::new_cell_map()] to obtain an instance of this type.
+///
+/// The map records the [Voronoi::generation()] it is synchronized with, so it stays valid as the diagram is edited:
+/// lookups translate site indices through the edits performed since, and entries whose cell changed after the value was inserted
+/// (see [Voronoi::cell_generation()]) are reported as [StaleEntry].
+/// Call [Self::sync()] or [Self::sync_with()] to bring the stored keys up to date, dropping or migrating the entries of removed sites.
+#[derive(Debug, Clone)]
+pub struct CellMap<V> {
+    generation: u64,
+    entries: BTreeMap<usize, Entry<V>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<V> {
+    /// The generation of the diagram when the value was inserted.
+    generation: u64,
+    value: V,
+}
+
+/// An error returned when looking a value up in a [CellMap] whose cell has been edited after the value was inserted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaleEntry {
+    /// The site that was looked up.
+    pub site: usize,
+
+    /// The generation of the diagram when the value was inserted.
+    pub inserted: u64,
+
+    /// The generation in which the cell was last edited.
+    pub edited: u64,
+}
+
+impl Display for StaleEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Entry of site {} was inserted in generation {}, but the cell was edited in generation {}",
+            self.site, self.inserted, self.edited
+        )
+    }
+}
+
+impl std::error::Error for StaleEntry {}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Creates an empty [CellMap] tied to this diagram.
+    pub fn new_cell_map<V>(&self) -> CellMap<V> {
+        CellMap {
+            generation: self.generation,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V> CellMap<V> {
+    /// Gets the generation of the diagram the keys of this map are synchronized with.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Gets the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns ```true``` if the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Gets the value associated with ```site```, a site index of the current generation of ```voronoi```.
+    ///
+    /// ```Ok(None)``` is returned if there is no value for the site, and [StaleEntry] if the cell was edited after the value was inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this map was not created from ```voronoi```, or from a diagram it was cloned from, or if the edits since its generation were discarded
+    /// by [Voronoi::trim_edits_before()].
+    pub fn get<T: ConvexBoundary>(
+        &self,
+        voronoi: &Voronoi<T>,
+        site: usize,
+    ) -> Result<Option<&V>, StaleEntry> {
+        let entry = match self
+            .key_of(voronoi, site)
+            .and_then(|k| self.entries.get(&k))
+        {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let edited = voronoi.cell_generation(site);
+        if edited > entry.generation {
+            Err(StaleEntry {
+                site,
+                inserted: entry.generation,
+                edited,
+            })
+        } else {
+            Ok(Some(&entry.value))
+        }
+    }
+
+    /// Inserts a value for ```site```, a site index of the current generation of ```voronoi```, returning the previous value, if any.
+    ///
+    /// The map is synchronized with ```voronoi``` first, as by [Self::sync()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist, or if this map was not created from ```voronoi```.
+    pub fn insert<T: ConvexBoundary>(
+        &mut self,
+        voronoi: &Voronoi<T>,
+        site: usize,
+        value: V,
+    ) -> Option<V> {
+        assert!(site < voronoi.sites().len(), "Site {} does not exist", site);
+
+        self.sync(voronoi);
+        self.entries
+            .insert(
+                site,
+                Entry {
+                    generation: voronoi.generation(),
+                    value,
+                },
+            )
+            .map(|e| e.value)
+    }
+
+    /// Removes the value of ```site```, a site index of the current generation of ```voronoi```, returning it, if any.
+    ///
+    /// The map is synchronized with ```voronoi``` first, as by [Self::sync()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this map was not created from ```voronoi```.
+    pub fn remove<T: ConvexBoundary>(&mut self, voronoi: &Voronoi<T>, site: usize) -> Option<V> {
+        self.sync(voronoi);
+        self.entries.remove(&site).map(|e| e.value)
+    }
+
+    /// Gets an iterator over the entries of the map, in site order.
+    ///
+    /// Sites are indices of [Self::generation()]; call [Self::sync()] first to iterate over the indices of the current diagram generation.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &V)> + '_ {
+        self.entries.iter().map(|(&site, e)| (site, &e.value))
+    }
+
+    /// Retains only the entries for which ```f``` returns ```true```.
+    ///
+    /// Sites are indices of [Self::generation()], as in [Self::iter()].
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut V) -> bool) {
+        self.entries.retain(|&site, e| f(site, &mut e.value));
+    }
+
+    /// Synchronizes the keys of this map with the current generation of ```voronoi```, dropping the values of removed and merged sites.
+    ///
+    /// This is the same as calling [Self::sync_with()] with a callback that returns ```None```.
+    pub fn sync<T: ConvexBoundary>(&mut self, voronoi: &Voronoi<T>) {
+        self.sync_with(voronoi, |_, _, _| None);
+    }
+
+    /// Synchronizes the keys of this map with the current generation of ```voronoi```.
+    ///
+    /// ```remap``` is called for every entry whose site was removed, with the index of the site right before the edit that removed it,
+    /// the index of the site it was merged into (or ```None``` if it was just removed) and the value.
+    /// If the site was merged and ```remap``` returns a value, that value replaces the entry of the site it was merged into.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this map was not created from ```voronoi```, or from a diagram it was cloned from, or if the edits since its generation were discarded
+    /// by [Voronoi::trim_edits_before()].
+    pub fn sync_with<T: ConvexBoundary>(
+        &mut self,
+        voronoi: &Voronoi<T>,
+        mut remap: impl FnMut(usize, Option<usize>, V) -> Option<V>,
+    ) {
+        let edits = voronoi
+            .edits_since(self.generation)
+            .expect("Cell map was not created from this diagram.");
+
+        for &(generation, ref edit) in edits.iter() {
+            let mut entries = BTreeMap::new();
+            let mut removed = Vec::new();
+            for (site, entry) in std::mem::take(&mut self.entries) {
+                match edit.forward(site) {
+                    Ok(new) => {
+                        entries.insert(new, entry);
+                    }
+                    Err(into) => removed.push((site, into, entry.value)),
+                }
+            }
+
+            for (site, into, value) in removed {
+                if let (Some(value), Some(into)) = (remap(site, into, value), into) {
+                    entries.insert(into, Entry { generation, value });
+                }
+            }
+
+            self.entries = entries;
+        }
+
+        self.generation = voronoi.generation();
+    }
+
+    /// Translates ```site``` from the current generation of ```voronoi``` back to the generation of this map.
+    fn key_of<T: ConvexBoundary>(&self, voronoi: &Voronoi<T>, site: usize) -> Option<usize> {
+        voronoi
+            .edits_since(self.generation)
+            .expect("Cell map was not created from this diagram.")
+            .iter()
+            .rev()
+            .try_fold(site, |site, (_, edit)| edit.backward(site))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::square_voronoi, Point};
+
+    #[test]
+    fn entries_survive_unrelated_edits() {
+        let mut voronoi = square_voronoi(6);
+        let mut map = voronoi.new_cell_map();
+        map.insert(&voronoi, 2, "city");
+        map.insert(&voronoi, 20, "town");
+        map.insert(&voronoi, 30, "village");
+
+        voronoi
+            .move_site(30, Point { x: 0.45, y: 0.1 })
+            .expect("Move expected to succeed");
+        voronoi
+            .insert_site(Point { x: -0.42, y: 0.37 })
+            .expect("Insert expected to succeed");
+        voronoi.remove_site(10).expect("Remove expected to succeed");
+
+        assert_eq!(map.get(&voronoi, 2), Ok(Some(&"city")));
+        assert_eq!(map.get(&voronoi, 19), Ok(Some(&"town")));
+        assert_eq!(map.get(&voronoi, 20), Ok(None));
+        assert_eq!(map.get(&voronoi, voronoi.sites().len() - 1), Ok(None));
+        assert!(
+            matches!(map.get(&voronoi, 29), Err(StaleEntry { site: 29, .. })),
+            "Moved cell must be stale."
+        );
+
+        map.sync(&voronoi);
+        assert_eq!(map.generation(), voronoi.generation());
+        assert_eq!(
+            map.iter().map(|(s, v)| (s, *v)).collect::<Vec<_>>(),
+            vec![(2, "city"), (19, "town"), (29, "village")]
+        );
+    }
+
+    #[test]
+    fn removal_invalidates_removed_key() {
+        let mut voronoi = square_voronoi(6);
+        let mut map = voronoi.new_cell_map();
+        for site in [2, 5, 9] {
+            map.insert(&voronoi, site, site);
+        }
+
+        voronoi.remove_site(5).expect("Remove expected to succeed");
+        assert_eq!(map.get(&voronoi, 2), Ok(Some(&2)));
+        assert_eq!(map.get(&voronoi, 5), Ok(None));
+        assert_eq!(map.get(&voronoi, 8), Ok(Some(&9)));
+
+        let mut remapped = Vec::new();
+        map.sync_with(&voronoi, |old, new, value| {
+            remapped.push((old, new, value));
+            None
+        });
+        assert_eq!(remapped, vec![(5, None, 5)]);
+        assert_eq!(
+            map.iter().map(|(s, v)| (s, *v)).collect::<Vec<_>>(),
+            vec![(2, 2), (8, 9)]
+        );
+    }
+
+    #[test]
+    fn merge_remaps_entries() {
+        let mut voronoi = square_voronoi(6);
+        let mut map = voronoi.new_cell_map();
+        map.insert(&voronoi, 4, 10);
+        map.insert(&voronoi, 7, 5);
+        map.insert(&voronoi, 12, 1);
+
+        let into = voronoi
+            .merge_sites(4, 7)
+            .expect("Merge expected to succeed");
+        assert_eq!(into, 4);
+        assert!(map.get(&voronoi, 4).is_err(), "Merged cell must be stale.");
+        assert_eq!(map.get(&voronoi, 11), Ok(Some(&1)));
+
+        let mut remapped = Vec::new();
+        map.sync_with(&voronoi, |old, new, value| {
+            remapped.push((old, new));
+            Some(value + 10)
+        });
+        assert_eq!(remapped, vec![(7, Some(4))]);
+        assert_eq!(map.get(&voronoi, 4), Ok(Some(&15)));
+        assert_eq!(map.get(&voronoi, 11), Ok(Some(&1)));
+    }
+
+    #[test]
+    fn retain_keeps_site_order() {
+        let voronoi = square_voronoi(6);
+        let mut map = voronoi.new_cell_map();
+        for site in [30, 3, 17, 8] {
+            map.insert(&voronoi, site, site * 2);
+        }
+
+        map.retain(|site, value| {
+            *value += 1;
+            site != 17
+        });
+        assert_eq!(
+            map.iter().map(|(s, v)| (s, *v)).collect::<Vec<_>>(),
+            vec![(3, 7), (8, 17), (30, 61)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not created from this diagram")]
+    fn map_from_other_diagram_panics() {
+        let voronoi = square_voronoi(6);
+        let other = square_voronoi(6);
+        let map = voronoi.new_cell_map::<()>();
+        let _ = map.get(&other, 0);
+    }
+}
@@ -0,0 +1,447 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{utils::dist2, ConvexBoundary, Point, Voronoi};
+
+/// The distance, relative to the size of the boundary, within which vertices are welded into one by [Voronoi::to_dcel()].
+const WELD_TOLERANCE: f64 = 1e-9;
+
+/// A half of an edge of a [Dcel], along the boundary of the face on its left as the face is walked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// The vertex this half-edge leaves from.
+    pub origin: usize,
+
+    /// The half-edge on the other side of the edge, going the opposite way.
+    pub twin: usize,
+
+    /// The half-edge following this one along the boundary of its face.
+    pub next: usize,
+
+    /// The half-edge preceding this one along the boundary of its face.
+    pub prev: usize,
+
+    /// The face this half-edge bounds.
+    pub face: usize,
+}
+
+/// A face of a [Dcel]: the cell of a site, or the outer face around the diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DcelFace {
+    /// The site of the cell, or ```None``` for the outer face.
+    pub site: Option<usize>,
+
+    /// A half-edge on the boundary of the face, or ```None``` if the cell has no area.
+    pub half_edge: Option<usize>,
+}
+
+/// An error returned by [Dcel::validate()], with the half-edge or face where the structure is broken where applicable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DcelError {
+    /// The twin of the half-edge is itself, does not have it as its twin, or does not leave from the vertex the half-edge ends at.
+    BrokenTwin(usize),
+
+    /// The next half-edge does not have it as its previous half-edge, or the other way around.
+    BrokenNext(usize),
+
+    /// The half-edge does not bound the same face as its next half-edge, or the face does not have the half-edge it refers to on its boundary.
+    BrokenFace(usize),
+
+    /// The Euler characteristic ```V - E + F``` is not ```2```, so the diagram is not a single connected planar subdivision.
+    EulerCharacteristic(i64),
+}
+
+impl Display for DcelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DcelError::BrokenTwin(h) => write!(f, "Half-edge {} has a broken twin link", h),
+            DcelError::BrokenNext(h) => write!(f, "Half-edge {} has a broken next or prev link", h),
+            DcelError::BrokenFace(h) => write!(f, "Half-edge {} has a broken face link", h),
+            DcelError::EulerCharacteristic(c) => {
+                write!(f, "Euler characteristic is {} instead of 2", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DcelError {}
+
+/// The doubly connected edge list of a diagram, for editing its topology.
+///
+/// Vertices at the same position are a single vertex, see [Voronoi::to_dcel()], so every edge between two cells, and every edge of the boundary, is a pair of twin half-edges.
+/// Face ```i``` is the cell of site ```i```, and the last face is the outer face, bounded by twins of the edges of the cells on the boundary.
+/// Half-edges bounding a cell are in the order of its vertices, see [crate::VoronoiCell::iter_vertices()], and the half-edges of the outer face go the opposite way.
+///
+/// Use [Voronoi::to_dcel()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct Dcel {
+    vertices: Vec<Point>,
+    half_edges: Vec<HalfEdge>,
+    faces: Vec<DcelFace>,
+}
+
+impl Dcel {
+    /// Gets the vertices of the diagram, each at a distinct position, within the weld tolerance of the vertices of the cells.
+    #[inline]
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Gets the half-edges, with the half-edges bounding each cell in the order of the sites, followed by the half-edges of the outer face.
+    #[inline]
+    pub fn half_edges(&self) -> &[HalfEdge] {
+        &self.half_edges
+    }
+
+    /// Gets the faces, one for each site followed by the outer face.
+    #[inline]
+    pub fn faces(&self) -> &[DcelFace] {
+        &self.faces
+    }
+
+    /// Gets the index of the outer face, the last of [Self::faces()].
+    #[inline]
+    pub fn outer_face(&self) -> usize {
+        self.faces.len() - 1
+    }
+
+    /// Gets an iterator over the half-edges bounding ```face```, following their next links from [DcelFace::half_edge].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```face``` does not exist.
+    pub fn iter_face(&self, face: usize) -> impl Iterator<Item = usize> + '_ {
+        let first = self.faces[face].half_edge;
+        let mut current = first;
+        std::iter::from_fn(move || {
+            let h = current?;
+            let next = self.half_edges[h].next;
+            current = if Some(next) == first {
+                None
+            } else {
+                Some(next)
+            };
+            Some(h)
+        })
+    }
+
+    /// Validates the links of every half-edge and face, and Euler's formula ```V - E + F = 2``` for the vertices, edges and faces with edges.
+    pub fn validate(&self) -> Result<(), DcelError> {
+        for (h, edge) in self.half_edges.iter().enumerate() {
+            let twin = self.half_edges.get(edge.twin);
+            if edge.twin == h
+                || twin.is_none_or(|t| t.twin != h)
+                || self.half_edges.get(edge.next).map(|n| n.origin) != twin.map(|t| t.origin)
+            {
+                return Err(DcelError::BrokenTwin(h));
+            }
+
+            if self.half_edges[edge.next].prev != h
+                || self.half_edges.get(edge.prev).map(|p| p.next) != Some(h)
+            {
+                return Err(DcelError::BrokenNext(h));
+            }
+
+            if self.half_edges[edge.next].face != edge.face || edge.face >= self.faces.len() {
+                return Err(DcelError::BrokenFace(h));
+            }
+        }
+
+        let mut faces = 0;
+        for (f, face) in self.faces.iter().enumerate() {
+            if let Some(h) = face.half_edge {
+                if self.half_edges.get(h).map(|e| e.face) != Some(f) {
+                    return Err(DcelError::BrokenFace(h));
+                }
+                faces += 1;
+            }
+        }
+
+        let characteristic =
+            self.vertices.len() as i64 - (self.half_edges.len() / 2) as i64 + faces;
+        if characteristic != 2 {
+            return Err(DcelError::EulerCharacteristic(characteristic));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Builds the doubly connected edge list of this diagram, see [Dcel].
+    ///
+    /// Only the vertices of cells are kept. Vertices closer than a billionth of the size of the boundary are welded into one, as cells clipped independently may
+    /// compute a shared vertex with different rounding errors, and edges between welded vertices, such as those between the circumcenters of cocircular sites, are dropped.
+    /// The diagram is expected to be clipped: with [crate::ClipBehavior::None] the cells on the hull are not closed and the result does not validate.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(3)
+    ///     .build()
+    ///     .unwrap();
+    /// let dcel = v.to_dcel();
+    /// assert!(dcel.validate().is_ok());
+    /// assert_eq!(dcel.faces().len(), 10);
+    /// // the center cell is a square
+    /// assert_eq!(dcel.iter_face(4).count(), 4);
+    ///```
+    pub fn to_dcel(&self) -> Dcel {
+        let tolerance = weld_tolerance(self.boundary.vertices());
+        let mut vertices: Vec<Point> = Vec::new();
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        let mut weld = |p: &Point| {
+            let key = (
+                (p.x / tolerance).floor() as i64,
+                (p.y / tolerance).floor() as i64,
+            );
+            for x in key.0 - 1..=key.0 + 1 {
+                for y in key.1 - 1..=key.1 + 1 {
+                    if let Some(&v) = grid.get(&(x, y)).and_then(|near| {
+                        near.iter()
+                            .find(|&&v| dist2(&vertices[v], p) <= tolerance * tolerance)
+                    }) {
+                        return v;
+                    }
+                }
+            }
+
+            vertices.push(p.clone());
+            grid.entry(key).or_default().push(vertices.len() - 1);
+            vertices.len() - 1
+        };
+
+        let mut half_edges: Vec<HalfEdge> = Vec::new();
+        let mut faces = Vec::with_capacity(self.sites.len() + 1);
+        let mut edge_of: HashMap<(usize, usize), usize> = HashMap::new();
+        for cell in self.iter_cells() {
+            let mut loop_vertices: Vec<usize> = cell.iter_vertices().map(&mut weld).collect();
+            loop_vertices.dedup();
+            while loop_vertices.len() > 1 && loop_vertices.first() == loop_vertices.last() {
+                loop_vertices.pop();
+            }
+
+            let face = faces.len();
+            if loop_vertices.len() < 3 {
+                faces.push(DcelFace {
+                    site: Some(cell.site()),
+                    half_edge: None,
+                });
+                continue;
+            }
+
+            let first = half_edges.len();
+            let n = loop_vertices.len();
+            for (i, (&a, &b)) in loop_vertices
+                .iter()
+                .zip(loop_vertices.iter().cycle().skip(1))
+                .enumerate()
+            {
+                let h = first + i;
+                let twin = edge_of.remove(&(b, a)).unwrap_or(usize::MAX);
+                if twin != usize::MAX {
+                    half_edges[twin].twin = h;
+                } else {
+                    edge_of.insert((a, b), h);
+                }
+                half_edges.push(HalfEdge {
+                    origin: a,
+                    twin,
+                    next: first + (i + 1) % n,
+                    prev: first + (i + n - 1) % n,
+                    face,
+                });
+            }
+            faces.push(DcelFace {
+                site: Some(cell.site()),
+                half_edge: Some(first),
+            });
+        }
+
+        // the half-edges left without a twin are on the boundary, and their twins bound the outer face
+        let outer = faces.len();
+        let mut unmatched: Vec<usize> = edge_of.into_values().collect();
+        unmatched.sort_unstable();
+        let mut outer_from: HashMap<usize, usize> = HashMap::new();
+        for &h in &unmatched {
+            let twin = half_edges.len();
+            let origin = half_edges[half_edges[h].next].origin;
+            half_edges[h].twin = twin;
+            outer_from.insert(origin, twin);
+            half_edges.push(HalfEdge {
+                origin,
+                twin: h,
+                next: usize::MAX,
+                prev: usize::MAX,
+                face: outer,
+            });
+        }
+        for &h in &unmatched {
+            let twin = half_edges[h].twin;
+            // the outer face goes the opposite way, so its next half-edge leaves from where the cell edge starts
+            if let Some(&next) = outer_from.get(&half_edges[h].origin) {
+                half_edges[twin].next = next;
+                half_edges[next].prev = twin;
+            }
+        }
+        faces.push(DcelFace {
+            site: None,
+            half_edge: unmatched.first().map(|&h| half_edges[h].twin),
+        });
+
+        Dcel {
+            vertices,
+            half_edges,
+            faces,
+        }
+    }
+}
+
+/// Gets the distance within which vertices are welded, for a boundary of ```vertices```.
+fn weld_tolerance(vertices: &[Point]) -> f64 {
+    let (mut min, mut max) = (
+        Point {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        },
+        Point {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        },
+    );
+    for v in vertices {
+        min = Point {
+            x: min.x.min(v.x),
+            y: min.y.min(v.y),
+        };
+        max = Point {
+            x: max.x.max(v.x),
+            y: max.y.max(v.y),
+        };
+    }
+
+    (WELD_TOLERANCE * (max.x - min.x).max(max.y - min.y)).max(f64::MIN_POSITIVE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::*, BoundingBox, TopologicalNeighborSiteIterator, VoronoiBuilder};
+
+    /// Gets the positions of the vertices of ```site```, without consecutive positions within the weld tolerance.
+    fn cell_loop<T: ConvexBoundary>(voronoi: &Voronoi<T>, site: usize) -> Vec<Point> {
+        let tolerance = weld_tolerance(voronoi.boundary().vertices());
+        let near = |a: &Point, b: &Point| dist2(a, b).sqrt() <= 2.0 * tolerance;
+        let mut vertices: Vec<Point> = voronoi.cell(site).iter_vertices().cloned().collect();
+        vertices.dedup_by(|b, a| near(a, b));
+        while vertices.len() > 1 && near(&vertices[0], &vertices[vertices.len() - 1]) {
+            vertices.pop();
+        }
+        vertices
+    }
+
+    fn assert_dcel_matches<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let dcel = voronoi.to_dcel();
+        let tolerance = weld_tolerance(voronoi.boundary().vertices());
+        assert_eq!(dcel.validate(), Ok(()));
+        assert_eq!(dcel.faces().len(), voronoi.sites().len() + 1);
+        assert_eq!(dcel.faces()[dcel.outer_face()].site, None);
+
+        for (site, face) in dcel.faces()[..voronoi.sites().len()].iter().enumerate() {
+            assert_eq!(face.site, Some(site));
+            let walk: Vec<Point> = dcel
+                .iter_face(site)
+                .map(|h| dcel.vertices()[dcel.half_edges()[h].origin].clone())
+                .collect();
+            let expected = cell_loop(voronoi, site);
+            if expected.len() < 3 {
+                assert!(walk.is_empty());
+            } else {
+                assert_eq!(walk.len(), expected.len(), "Face walk of site {}", site);
+                for (p, q) in walk.iter().zip(expected.iter()) {
+                    assert!(
+                        dist2(p, q).sqrt() <= 2.0 * tolerance,
+                        "Face walk of site {}",
+                        site
+                    );
+                }
+            }
+        }
+
+        for (h, edge) in dcel.half_edges().iter().enumerate() {
+            let twin = &dcel.half_edges()[edge.twin];
+            assert_eq!(twin.twin, h);
+            assert_ne!(edge.twin, h);
+            assert_eq!(dcel.half_edges()[edge.next].prev, h);
+            assert_eq!(dcel.half_edges()[edge.prev].next, h);
+            assert_eq!(dcel.half_edges()[edge.next].origin, twin.origin);
+            // cells only share an edge with their neighbors in the triangulation, and cells on the boundary with the outer face
+            if edge.face != dcel.outer_face() && twin.face != dcel.outer_face() {
+                assert!(TopologicalNeighborSiteIterator::new(voronoi, edge.face)
+                    .any(|neighbor| neighbor == twin.face));
+            }
+        }
+
+        let faces = dcel
+            .faces()
+            .iter()
+            .filter(|f| f.half_edge.is_some())
+            .count();
+        assert_eq!(
+            dcel.vertices().len() as i64 - (dcel.half_edges().len() / 2) as i64 + faces as i64,
+            2
+        );
+    }
+
+    #[test]
+    fn dcel_of_assets_is_consistent() {
+        for asset in VALID_ASSETS {
+            let voronoi = new_voronoi_builder_from_asset(asset)
+                .expect("Asset expected to load")
+                .build()
+                .expect("Some voronoi expected");
+            assert_dcel_matches(&voronoi);
+        }
+    }
+
+    #[test]
+    fn dcel_of_random_and_degenerate_sites_is_consistent() {
+        let sites = crate::utils::test::random_sites(500);
+        let random = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+        assert_dcel_matches(&random);
+
+        // cocircular sites have circumcenters a few ulps apart, welded into a single vertex
+        let square = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(6)
+            .build()
+            .expect("Some voronoi expected");
+        assert_dcel_matches(&square);
+        assert_eq!(square.to_dcel().vertices().len(), 7 * 7);
+    }
+
+    #[test]
+    fn broken_links_are_reported() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(3)
+            .build()
+            .expect("Some voronoi expected");
+        let dcel = voronoi.to_dcel();
+
+        let mut broken = dcel.clone();
+        broken.half_edges[0].twin = 0;
+        assert_eq!(broken.validate(), Err(DcelError::BrokenTwin(0)));
+
+        let mut broken = dcel.clone();
+        let next = broken.half_edges[0].next;
+        broken.half_edges[next].prev = next;
+        assert!(matches!(broken.validate(), Err(DcelError::BrokenNext(_))));
+
+        let mut broken = dcel;
+        broken.vertices.push(Point { x: 5.0, y: 5.0 });
+        assert_eq!(broken.validate(), Err(DcelError::EulerCharacteristic(3)));
+    }
+}
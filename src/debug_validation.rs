@@ -0,0 +1,358 @@
+use std::path::PathBuf;
+
+use crate::{ClipBehavior, ConvexBoundary, Point, Voronoi};
+
+/// The relative error bound of the orientation determinant computed with floating point arithmetic, as in the fast path of [robust::orient2d()].
+const ORIENT_ERROR_BOUND: f64 = (3.0 + 16.0 * f64::EPSILON / 2.0) * f64::EPSILON / 2.0;
+
+/// How much of a built diagram is validated in debug builds, see [crate::VoronoiBuilder::set_debug_validation()].
+///
+/// Validation only runs when debug assertions are enabled. In release builds every level is the same as [DebugValidation::Off].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DebugValidation {
+    /// Nothing is validated.
+    #[default]
+    Off,
+
+    /// A single pass over the edges of each cell checks that its vertices are finite, that it is ordered counter-clockwise and that it contains its site.
+    ///
+    /// This does not detect cells that are concave or outside of the boundary, as long as they still wind around their site.
+    Fast,
+
+    /// Each cell is checked to be a convex polygon of finite vertices, ordered counter-clockwise, that contains its site and is within the boundary.
+    ///
+    /// Cells left very slightly concave by [crate::VoronoiBuilder::set_vertex_welding()] fail this validation.
+    Full,
+}
+
+/// Validates ```voronoi``` at ```level```, panicking with the first invalid cell.
+///
+/// The diagram is written with [Voronoi::write_binary()] to the temporary directory before panicking, so the failure can be reproduced.
+pub(crate) fn debug_validate<T: ConvexBoundary>(voronoi: &Voronoi<T>, level: DebugValidation) {
+    let validate = match level {
+        DebugValidation::Off => return,
+        DebugValidation::Fast => fast_validate_cell,
+        DebugValidation::Full => validate_cell,
+    };
+
+    for site in 0..voronoi.sites.len() {
+        if let Err(reason) = validate(voronoi, site) {
+            let bundle = write_failure_bundle(voronoi).map_or_else(
+                |e| format!("Failed to write the diagram: {}", e),
+                |path| format!("Wrote the diagram to '{}'", path.display()),
+            );
+            panic!(
+                "{:?} debug validation failed for cell {}: {}. {}.",
+                level, site, reason, bundle
+            );
+        }
+    }
+}
+
+/// Checks that the vertices of the cell of ```site``` are finite, ordered counter-clockwise and around its site, in a single pass over its edges.
+///
+/// Cells on the hull are only checked if they were clipped, otherwise they are open.
+fn fast_validate_cell<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+) -> Result<(), &'static str> {
+    if voronoi.clip_behavior != ClipBehavior::Clip && voronoi.cell(site).is_on_hull() {
+        return Ok(());
+    }
+
+    let vertices = &voronoi.circumcenters;
+    let cell = &voronoi.cells[site];
+    let position = &voronoi.sites[site];
+    // the orientations of the site relative to the edges sum to twice the signed area of the cell, so they also check its winding,
+    // and a vertex that is not finite makes both orientations of its edges not finite
+    let mut orientation = 0.0;
+    let mut a = match cell.last() {
+        Some(&last) => &vertices[last],
+        None => return Err("not counter-clockwise"),
+    };
+    for &b in cell {
+        let b = &vertices[b];
+
+        // the exact predicate is only needed when the site is not clearly on the inner side of the edge
+        let (left, right) = (
+            (b.x - a.x) * (position.y - a.y),
+            (b.y - a.y) * (position.x - a.x),
+        );
+        let edge_orientation = left - right;
+        orientation += edge_orientation;
+        if edge_orientation >= -ORIENT_ERROR_BOUND * (left.abs() + right.abs()) {
+            if !edge_orientation.is_finite() {
+                return Err("vertex is not finite");
+            }
+            if robust::orient2d(a.into(), b.into(), position.into()) > 0.0 {
+                return Err("site is outside of the cell");
+            }
+        }
+        a = b;
+    }
+
+    if !orientation.is_finite() {
+        Err("vertex is not finite")
+    } else if orientation < 0.0 {
+        Ok(())
+    } else {
+        Err("not counter-clockwise")
+    }
+}
+
+/// Checks that the cell of ```site``` is a convex polygon of finite vertices, ordered counter-clockwise, that contains its site and is within the boundary.
+///
+/// Cells on the hull are only checked if they were clipped, otherwise they are open.
+pub(crate) fn validate_cell<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+) -> Result<(), &'static str> {
+    let cell = voronoi.cell(site);
+    let clipped = voronoi.clip_behavior == ClipBehavior::Clip;
+    if !clipped && cell.is_on_hull() {
+        return Ok(());
+    }
+
+    let vertices: Vec<&Point> = cell.iter_vertices().collect();
+    if vertices
+        .iter()
+        .any(|p| !p.x.is_finite() || !p.y.is_finite())
+    {
+        return Err("vertex is not finite");
+    }
+
+    if vertices.len() < 3 {
+        return Err("fewer than three vertices");
+    }
+
+    let area: f64 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| (b.x - a.x) * (b.y + a.y))
+        .sum();
+    if area <= 0.0 {
+        return Err("not counter-clockwise");
+    }
+
+    let n = vertices.len();
+    for i in 0..n {
+        let (a, b, c) = (vertices[i], vertices[(i + 1) % n], vertices[(i + 2) % n]);
+        if robust::orient2d(a.into(), b.into(), c.into()) > 0.0 {
+            return Err("not convex");
+        }
+
+        if robust::orient2d(a.into(), b.into(), cell.site_position().into()) > 0.0 {
+            return Err("site is outside of the cell");
+        }
+    }
+
+    if clipped
+        && vertices
+            .iter()
+            .any(|p| !voronoi.boundary().is_inside(p) && voronoi.boundary().which_edge(p).is_none())
+    {
+        return Err("vertex is outside of the boundary");
+    }
+
+    Ok(())
+}
+
+/// Writes ```voronoi``` to a file in the temporary directory, named after its input fingerprint, returning the path of the file.
+fn write_failure_bundle<T: ConvexBoundary>(voronoi: &Voronoi<T>) -> std::io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "voronoice-failure-{:016x}.bin",
+        voronoi.input_fingerprint
+    ));
+    voronoi.write_binary(std::fs::File::create(&path)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use super::*;
+    use crate::{
+        utils::{dist2, test::*},
+        BoundingBox, VoronoiBuilder,
+    };
+
+    type Corruption = fn(&mut Voronoi<BoundingBox>);
+
+    fn builder() -> VoronoiBuilder<BoundingBox> {
+        VoronoiBuilder::default().set_sites(
+            (0..200)
+                .map(|i| Point {
+                    x: 1.8 * ((i as f64 * 0.618034) % 1.0) - 0.9,
+                    y: 1.8 * ((i as f64 * 0.754878) % 1.0) - 0.9,
+                })
+                .collect(),
+        )
+    }
+
+    fn interior_site(voronoi: &Voronoi<BoundingBox>) -> usize {
+        (0..voronoi.sites.len())
+            .find(|&s| !voronoi.cell(s).is_on_hull())
+            .expect("Some interior cell expected")
+    }
+
+    fn not_finite(voronoi: &mut Voronoi<BoundingBox>) {
+        let site = interior_site(voronoi);
+        let vertex = voronoi.cells[site][0];
+        voronoi.circumcenters[vertex].x = f64::NAN;
+    }
+
+    fn infinite(voronoi: &mut Voronoi<BoundingBox>) {
+        let site = interior_site(voronoi);
+        let vertex = voronoi.cells[site][0];
+        voronoi.circumcenters[vertex].y = f64::INFINITY;
+    }
+
+    fn reversed(voronoi: &mut Voronoi<BoundingBox>) {
+        let site = interior_site(voronoi);
+        voronoi.cells[site].reverse();
+    }
+
+    /// Dents an edge of a cell towards its site, so the cell is concave but still winds around its site.
+    fn concave(voronoi: &mut Voronoi<BoundingBox>) {
+        let site = interior_site(voronoi);
+        let (a, b) = (
+            &voronoi.circumcenters[voronoi.cells[site][0]],
+            &voronoi.circumcenters[voronoi.cells[site][1]],
+        );
+        let s = &voronoi.sites[site];
+        let dent = Point {
+            x: 0.75 * (a.x + b.x) / 2.0 + 0.25 * s.x,
+            y: 0.75 * (a.y + b.y) / 2.0 + 0.25 * s.y,
+        };
+        voronoi.circumcenters.push(dent);
+        let vertex = voronoi.circumcenters.len() - 1;
+        voronoi.cells[site].insert(1, vertex);
+    }
+
+    /// Builds at ```level``` with ```corruption```, returning the panic message if validation failed.
+    fn validation_failure(level: DebugValidation, corruption: Corruption) -> Option<String> {
+        catch_unwind(AssertUnwindSafe(|| {
+            builder()
+                .set_debug_validation(level)
+                .set_corruption(corruption)
+                .build()
+        }))
+        .err()
+        .map(|panic| {
+            panic
+                .downcast_ref::<String>()
+                .cloned()
+                .expect("Panic message expected")
+        })
+    }
+
+    #[test]
+    fn valid_diagrams_pass_validation() {
+        for level in [DebugValidation::Fast, DebugValidation::Full] {
+            builder()
+                .set_debug_validation(level)
+                .build()
+                .expect("Some voronoi expected");
+            for asset in VALID_ASSETS {
+                new_voronoi_builder_from_asset(asset)
+                    .expect("Asset expected to load")
+                    .set_debug_validation(level)
+                    .build()
+                    .expect("Some voronoi expected");
+            }
+        }
+    }
+
+    #[test]
+    fn each_level_catches_what_it_documents() {
+        // a reversed cell has its site on the outer side of its edges, which the single pass of fast validation finds first
+        let cases: [(Corruption, Option<&str>, &str); 4] = [
+            (
+                not_finite,
+                Some("vertex is not finite"),
+                "vertex is not finite",
+            ),
+            (
+                infinite,
+                Some("vertex is not finite"),
+                "vertex is not finite",
+            ),
+            (
+                reversed,
+                Some("site is outside of the cell"),
+                "not counter-clockwise",
+            ),
+            (concave, None, "not convex"),
+        ];
+        for (corruption, fast_reason, full_reason) in cases {
+            assert_eq!(validation_failure(DebugValidation::Off, corruption), None);
+
+            let fast = validation_failure(DebugValidation::Fast, corruption);
+            assert_eq!(fast.is_some(), fast_reason.is_some(), "{:?}", fast);
+            if let (Some(message), Some(reason)) = (fast, fast_reason) {
+                assert!(message.contains(reason), "{}", message);
+            }
+
+            let full = validation_failure(DebugValidation::Full, corruption)
+                .expect("Full validation expected to fail");
+            assert!(full.contains(full_reason), "{}", full);
+        }
+    }
+
+    #[test]
+    fn edits_are_validated_again() {
+        let mut voronoi = builder()
+            .set_debug_validation(DebugValidation::Fast)
+            .build()
+            .expect("Some voronoi expected");
+        reversed(&mut voronoi);
+
+        // the site moved is the farthest from the reversed cell, so the edit does not rebuild it
+        let corrupted = voronoi.sites[interior_site(&voronoi)].clone();
+        let site = (0..voronoi.sites.len())
+            .max_by(|&a, &b| {
+                dist2(&voronoi.sites[a], &corrupted)
+                    .total_cmp(&dist2(&voronoi.sites[b], &corrupted))
+            })
+            .expect("Some site expected");
+        let position = Point {
+            x: 0.99 * voronoi.sites[site].x,
+            y: 0.99 * voronoi.sites[site].y,
+        };
+        let message = catch_unwind(AssertUnwindSafe(|| voronoi.move_site(site, position)))
+            .err()
+            .and_then(|panic| panic.downcast_ref::<String>().cloned())
+            .expect("Edit expected to fail validation");
+        assert!(
+            message.contains("site is outside of the cell"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn failures_write_the_diagram() {
+        let message = validation_failure(DebugValidation::Fast, reversed)
+            .expect("Fast validation expected to fail");
+        let voronoi = builder().build().expect("Some voronoi expected");
+        let site = interior_site(&voronoi);
+        assert!(
+            message.contains(&format!("failed for cell {}:", site)),
+            "{}",
+            message
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "voronoice-failure-{:016x}.bin",
+            voronoi.input_fingerprint()
+        ));
+        assert!(message.contains(&path.display().to_string()), "{}", message);
+        let written = Voronoi::read_binary(std::fs::File::open(&path).expect("Bundle expected"))
+            .expect("Bundle expected to decode");
+        assert_eq!(written.sites(), voronoi.sites());
+        let mut vertices: Vec<Point> = voronoi.cell(site).iter_vertices().cloned().collect();
+        vertices.reverse();
+        assert!(written.cell(site).iter_vertices().eq(vertices.iter()));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{BuildWarning, ClipBehavior, ConvexBoundary, Point, Voronoi};
+
+/// Removes boundary corners from every cell with more than ```max_vertices``` vertices, recording a [BuildWarning::SimplifiedCell] for each simplified cell.
+///
+/// Only the corners of the boundary polygon that belong to a single cell are removed; circumcenters and the points where Voronoi edges were clipped are kept,
+/// so the diagram topology and the edges shared by cells are not changed. Removing vertices from a convex polygon keeps it convex and within the original polygon.
+///
+/// Each run of corners between two kept vertices is decimated evenly: corners are kept at evenly spaced positions along the run,
+/// so each new edge replaces at most ```⌈(c + 1) / (k + 1)⌉``` boundary edges, where ```c``` is the number of corners in the run and ```k``` the number kept.
+/// If the simplified cell would not contain its site, more corners are kept.
+pub(crate) fn cap_cell_vertices<T: ConvexBoundary>(voronoi: &mut Voronoi<T>, max_vertices: usize) {
+    if voronoi.clip_behavior != ClipBehavior::Clip {
+        return;
+    }
+
+    // boundary corners are added right after the circumcenters
+    let first_corner = voronoi.number_of_triangles();
+    let mut corner_owners = vec![0; voronoi.boundary.vertices().len()];
+    for &v in voronoi.cells.iter().flatten() {
+        if v >= first_corner && v < first_corner + corner_owners.len() {
+            corner_owners[v - first_corner] += 1;
+        }
+    }
+    let is_removable = |v: usize| {
+        v >= first_corner
+            && v < first_corner + corner_owners.len()
+            && corner_owners[v - first_corner] == 1
+    };
+
+    for site in 0..voronoi.cells.len() {
+        let cell = &voronoi.cells[site];
+        if cell.len() <= max_vertices {
+            continue;
+        }
+
+        if let Some(simplified) = decimate(
+            cell,
+            &voronoi.circumcenters,
+            &voronoi.sites[site],
+            is_removable,
+            max_vertices,
+        ) {
+            let area_loss = polygon_area(cell, &voronoi.circumcenters)
+                - polygon_area(&simplified, &voronoi.circumcenters);
+            voronoi.warnings.push(BuildWarning::SimplifiedCell {
+                site,
+                vertices: simplified.len(),
+                area_loss,
+            });
+            voronoi.cells[site] = simplified;
+            voronoi.fan_indices.take();
+        }
+    }
+}
+
+/// Decimates the removable vertices of ```cell``` so it has at most ```max_vertices``` vertices, or as few as possible while still containing ```site```.
+///
+/// Returns ```None``` if no vertex can be removed.
+fn decimate(
+    cell: &[usize],
+    vertices: &[Point],
+    site: &Point,
+    is_removable: impl Fn(usize) -> bool,
+    max_vertices: usize,
+) -> Option<Vec<usize>> {
+    // start from a kept vertex, so each run of removable vertices is contiguous
+    let start = cell.iter().position(|&v| !is_removable(v))?;
+    let mut runs: Vec<(usize, Vec<usize>)> = Vec::new();
+    for &v in cell[start..].iter().chain(cell[..start].iter()) {
+        match runs.last_mut() {
+            Some((_, run)) if is_removable(v) => run.push(v),
+            _ => runs.push((v, Vec::new())),
+        }
+    }
+
+    let removable: usize = runs.iter().map(|(_, run)| run.len()).sum();
+    let fixed = cell.len() - removable;
+    let budget = max_vertices.saturating_sub(fixed);
+
+    (budget..removable).find_map(|budget| {
+        let kept = distribute(&runs, budget, removable);
+        let simplified: Vec<usize> = runs
+            .iter()
+            .zip(kept)
+            .flat_map(|((v, run), k)| std::iter::once(*v).chain(evenly_spaced(run, k)))
+            .collect();
+
+        if contains(&simplified, vertices, site) {
+            Some(simplified)
+        } else {
+            None
+        }
+    })
+}
+
+/// Distributes ```budget``` kept vertices among ```runs``` in proportion to their lengths.
+fn distribute(runs: &[(usize, Vec<usize>)], budget: usize, removable: usize) -> Vec<usize> {
+    let mut kept: Vec<usize> = runs
+        .iter()
+        .map(|(_, run)| budget * run.len() / removable)
+        .collect();
+
+    // hand out the remainder to the runs with the most removed vertices
+    let mut remainder = budget - kept.iter().sum::<usize>();
+    while remainder > 0 {
+        let (i, _) = runs
+            .iter()
+            .enumerate()
+            .max_by_key(|&(i, (_, run))| (run.len() - kept[i], std::cmp::Reverse(i)))
+            .expect("Runs expected to be non empty");
+        kept[i] += 1;
+        remainder -= 1;
+    }
+
+    kept
+}
+
+/// Keeps ```k``` vertices of ```run```, at evenly spaced positions between the kept vertices before and after the run.
+fn evenly_spaced(run: &[usize], k: usize) -> impl Iterator<Item = usize> + '_ {
+    // position 0 is the kept vertex before the run and c + 1 the one after it, rounded to the nearest position
+    let c = run.len();
+    (1..=k).map(move |i| run[(2 * i * (c + 1) + k + 1) / (2 * (k + 1)) - 1])
+}
+
+/// Returns whether ```p``` is inside, or on the edges of, the convex polygon ```polygon```.
+fn contains(polygon: &[usize], vertices: &[Point], p: &Point) -> bool {
+    let orientation = polygon_signed_area(polygon, vertices).signum();
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .all(|(&a, &b)| {
+            let (a, b) = (&vertices[a], &vertices[b]);
+            ((b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)) * orientation >= 0.0
+        })
+}
+
+fn polygon_signed_area(polygon: &[usize], vertices: &[Point]) -> f64 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(&a, &b)| {
+            let (a, b) = (&vertices[a], &vertices[b]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn polygon_area(polygon: &[usize], vertices: &[Point]) -> f64 {
+    polygon_signed_area(polygon, vertices).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConvexPolygon, VoronoiBuilder};
+
+    const BOUNDARY_VERTICES: usize = 64;
+
+    fn circle_builder(sites: usize) -> VoronoiBuilder<ConvexPolygon> {
+        VoronoiBuilder::default()
+            .generate_circle_sites(sites, 0.35)
+            .set_boundary(ConvexPolygon::regular(BOUNDARY_VERTICES as i32, 1.0))
+    }
+
+    #[test]
+    fn vertex_cap_is_respected() {
+        for sites in [3, 4, 5, 8] {
+            let original = circle_builder(sites)
+                .build()
+                .expect("Some voronoi expected");
+            assert!(original.iter_cells().any(|c| c.triangles().len() > 10));
+
+            for cap in [4, 6, 10] {
+                let voronoi = circle_builder(sites)
+                    .set_max_cell_vertices(cap)
+                    .build()
+                    .expect("Some voronoi expected");
+
+                for (cell, original_cell) in voronoi.iter_cells().zip(original.iter_cells()) {
+                    // circumcenters and clip points are kept, so the cap may not be reachable
+                    let fixed = original_cell
+                        .iter_triangles()
+                        .filter(|&v| {
+                            v < original.number_of_triangles()
+                                || v >= original.number_of_triangles() + BOUNDARY_VERTICES
+                        })
+                        .count();
+                    assert!(
+                        cell.triangles().len() <= cap.max(fixed),
+                        "Cell {} has {} vertices, cap is {}.",
+                        cell.site(),
+                        cell.triangles().len(),
+                        cap
+                    );
+                    assert!(contains(
+                        cell.triangles(),
+                        voronoi.vertices(),
+                        cell.site_position()
+                    ));
+                    assert!(cell
+                        .iter_triangles()
+                        .all(|v| original_cell.triangles().contains(&v)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn circumcenters_are_never_removed() {
+        let original = circle_builder(5).build().expect("Some voronoi expected");
+        let voronoi = circle_builder(5)
+            .set_max_cell_vertices(3)
+            .build()
+            .expect("Some voronoi expected");
+        let first_corner = original.number_of_triangles();
+
+        for (cell, original_cell) in voronoi.iter_cells().zip(original.iter_cells()) {
+            let kept: Vec<usize> = original_cell
+                .iter_triangles()
+                .filter(|&v| v < first_corner || v >= first_corner + BOUNDARY_VERTICES)
+                .collect();
+            assert_eq!(
+                cell.iter_triangles()
+                    .filter(|&v| v < first_corner || v >= first_corner + BOUNDARY_VERTICES)
+                    .collect::<Vec<usize>>(),
+                kept,
+                "Non corner vertices of cell {} changed.",
+                cell.site()
+            );
+        }
+        assert_eq!(voronoi.adjacency(), original.adjacency());
+    }
+
+    #[test]
+    fn area_loss_is_within_bound() {
+        let radius: f64 = 1.0;
+        for sites in [3, 4, 8] {
+            let original = circle_builder(sites)
+                .build()
+                .expect("Some voronoi expected");
+            let cap = 8;
+            let voronoi = circle_builder(sites)
+                .set_max_cell_vertices(cap)
+                .build()
+                .expect("Some voronoi expected");
+            let first_corner = original.number_of_triangles();
+
+            let simplified: Vec<_> = voronoi
+                .build_warnings()
+                .iter()
+                .filter_map(|w| match w {
+                    BuildWarning::SimplifiedCell {
+                        site,
+                        vertices,
+                        area_loss,
+                    } => Some((*site, *vertices, *area_loss)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                simplified.len(),
+                original
+                    .iter_cells()
+                    .filter(|c| c.triangles().len() > cap)
+                    .count()
+            );
+
+            for (site, vertices, area_loss) in simplified {
+                let original_cell = original.cell(site);
+                let cell = voronoi.cell(site);
+                assert_eq!(vertices, cell.triangles().len());
+                assert!((original_cell.area() - cell.area() - area_loss).abs() < 1e-12);
+
+                // every cell in these diagrams has a single run of corners
+                let corners = original_cell
+                    .iter_triangles()
+                    .filter(|&v| v >= first_corner && v < first_corner + BOUNDARY_VERTICES)
+                    .count();
+                let kept = cell
+                    .iter_triangles()
+                    .filter(|&v| v >= first_corner && v < first_corner + BOUNDARY_VERTICES)
+                    .count();
+                let spanned = (corners + kept + 1) / (kept + 1);
+                let angle = 2.0 * std::f64::consts::PI * spanned as f64 / BOUNDARY_VERTICES as f64;
+                let bound = (kept + 1) as f64 * radius * radius * angle.powi(3) / 12.0;
+                assert!(
+                    area_loss > 0.0 && area_loss <= bound,
+                    "Cell {} lost {}, bound is {}.",
+                    site,
+                    area_loss,
+                    bound
+                );
+            }
+        }
+    }
+}
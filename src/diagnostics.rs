@@ -0,0 +1,194 @@
+use std::sync::OnceLock;
+
+use delaunator::{next_halfedge, EMPTY};
+
+use crate::{utils::dist2, ConvexBoundary, Voronoi};
+
+/// The cells with the largest and the smallest area of a diagram.
+///
+/// Use [Voronoi::extreme_cells()] to obtain an instance of this type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtremeCells {
+    /// The site of the cell with the largest area.
+    pub largest: usize,
+
+    /// The site of the cell with the smallest area.
+    pub smallest: usize,
+
+    /// The area of the cell of [Self::largest].
+    pub largest_area: f64,
+
+    /// The area of the cell of [Self::smallest].
+    pub smallest_area: f64,
+}
+
+/// Diagnostics of a diagram, each calculated on first access.
+///
+/// The cache is reset whenever the diagram is edited and moves on to a new generation.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DiagnosticsCache {
+    closest_site_pair: OnceLock<Option<(usize, usize, f64)>>,
+    extreme_cells: OnceLock<Option<ExtremeCells>>,
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the two sites closest to each other, in increasing order, and the distance between them.
+    ///
+    /// The closest pair of sites is always joined by an edge of the Delaunay triangulation, so only those edges are scanned.
+    /// The result is calculated on first access and cached until the diagram is edited.
+    /// Returns ```None``` if the diagram has less than three sites, and therefore no triangulation.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![
+    ///         Point { x: -0.5, y: 0.0 },
+    ///         Point { x: 0.5, y: 0.0 },
+    ///         Point { x: 0.0, y: 0.5 },
+    ///         Point { x: 0.6, y: 0.1 },
+    ///     ])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let (a, b, distance) = voronoi.closest_site_pair().unwrap();
+    /// assert_eq!((a, b), (1, 3));
+    /// assert!((distance - 0.02f64.sqrt()).abs() < 1e-12);
+    ///```
+    pub fn closest_site_pair(&self) -> Option<(usize, usize, f64)> {
+        *self.diagnostics.closest_site_pair.get_or_init(|| {
+            let triangulation = &self.triangulation;
+            (0..triangulation.triangles.len())
+                // each edge shared by two triangles is visited only from its lowest half-edge
+                .filter(|&e| triangulation.halfedges[e] == EMPTY || e < triangulation.halfedges[e])
+                .map(|e| {
+                    let a = triangulation.triangles[e];
+                    let b = triangulation.triangles[next_halfedge(e)];
+                    (a.min(b), a.max(b), dist2(&self.sites[a], &self.sites[b]))
+                })
+                .min_by(|x, y| x.2.total_cmp(&y.2))
+                .map(|(a, b, distance2)| (a, b, distance2.sqrt()))
+        })
+    }
+
+    /// Gets the cells with the largest and the smallest area, as calculated by [crate::VoronoiCell::area()].
+    /// The first cell found is returned if several cells have the same area.
+    ///
+    /// The result is calculated on first access and cached until the diagram is edited.
+    /// Returns ```None``` if the diagram has no cells.
+    /// If the diagram was built with clipping disabled, the cells in the hull are not closed, so their areas are not meaningful.
+    pub fn extreme_cells(&self) -> Option<ExtremeCells> {
+        *self.diagnostics.extreme_cells.get_or_init(|| {
+            self.iter_cells()
+                .map(|cell| (cell.site(), cell.area()))
+                .fold(None, |extremes, (site, area)| {
+                    Some(match extremes {
+                        None => ExtremeCells {
+                            largest: site,
+                            smallest: site,
+                            largest_area: area,
+                            smallest_area: area,
+                        },
+                        Some(mut extremes) => {
+                            if area > extremes.largest_area {
+                                extremes.largest = site;
+                                extremes.largest_area = area;
+                            }
+                            if area < extremes.smallest_area {
+                                extremes.smallest = site;
+                                extremes.smallest_area = area;
+                            }
+                            extremes
+                        }
+                    })
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox, Point, VoronoiBuilder};
+
+    fn brute_force_closest_pair(sites: &[Point]) -> f64 {
+        let mut closest = f64::INFINITY;
+        for (i, a) in sites.iter().enumerate() {
+            for b in &sites[i + 1..] {
+                closest = closest.min(dist2(a, b).sqrt());
+            }
+        }
+        closest
+    }
+
+    #[test]
+    fn closest_pair_matches_brute_force() {
+        for _ in 0..10 {
+            let voronoi = random_voronoi(300);
+            let (a, b, distance) = voronoi.closest_site_pair().expect("Closest pair expected");
+            assert!(a < b);
+            assert_eq!(
+                distance,
+                dist2(&voronoi.sites()[a], &voronoi.sites()[b]).sqrt()
+            );
+            assert_eq!(distance, brute_force_closest_pair(voronoi.sites()));
+        }
+    }
+
+    #[test]
+    fn extreme_cells_match_area_scan() {
+        let voronoi = random_voronoi(300);
+        let extremes = voronoi.extreme_cells().expect("Extreme cells expected");
+        let areas: Vec<f64> = voronoi.iter_cells().map(|c| c.area()).collect();
+
+        assert_eq!(extremes.largest_area, areas[extremes.largest]);
+        assert_eq!(extremes.smallest_area, areas[extremes.smallest]);
+        assert!(areas
+            .iter()
+            .all(|&a| extremes.smallest_area <= a && a <= extremes.largest_area));
+    }
+
+    #[test]
+    fn edits_invalidate_cache() {
+        let mut voronoi = random_voronoi(50);
+        let (_, _, distance) = voronoi.closest_site_pair().expect("Closest pair expected");
+        let extremes = voronoi.extreme_cells().expect("Extreme cells expected");
+
+        // moving the site of the smallest cell right next to another one, towards the center, makes them the closest pair
+        let mover = extremes.smallest;
+        let target = if mover == 0 { 1 } else { 0 };
+        let near = Point {
+            x: voronoi.sites()[target].x - voronoi.sites()[target].x.signum() * distance / 4.0,
+            y: voronoi.sites()[target].y,
+        };
+        voronoi
+            .move_site(mover, near)
+            .expect("Move expected to succeed");
+
+        let (new_a, new_b, new_distance) =
+            voronoi.closest_site_pair().expect("Closest pair expected");
+        assert_eq!((new_a, new_b), (target.min(mover), target.max(mover)));
+        assert!(new_distance < distance);
+        assert_eq!(new_distance, brute_force_closest_pair(voronoi.sites()));
+
+        let new_extremes = voronoi.extreme_cells().expect("Extreme cells expected");
+        assert_ne!(new_extremes.smallest_area, extremes.smallest_area);
+        let areas: Vec<f64> = voronoi.iter_cells().map(|c| c.area()).collect();
+        assert_eq!(new_extremes.smallest_area, areas[new_extremes.smallest]);
+        assert_eq!(new_extremes.largest_area, areas[new_extremes.largest]);
+    }
+
+    #[test]
+    fn diagrams_without_triangles_have_no_closest_pair() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(vec![Point { x: 0.0, y: 0.0 }])
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(voronoi.closest_site_pair(), None);
+        assert_eq!(
+            voronoi.extreme_cells().map(|e| (e.largest, e.smallest)),
+            Some((0, 0))
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::f64::consts::TAU;
+
+use crate::{
+    utils::clip_half_plane, ConvexBoundary, Point, TopologicalNeighborSiteIterator, Voronoi,
+    VoronoiCell,
+};
+
+/// The number of segments a full turn of arc is approximated by in [VoronoiCell::dilated()].
+const DEFAULT_ARC_SAMPLES: usize = 32;
+
+/// A cell expanded outwards by a distance and clipped to the boundary, as returned by [VoronoiCell::dilated()].
+#[derive(Debug, Clone)]
+pub struct DilatedCell<'v, T: ConvexBoundary> {
+    voronoi: &'v Voronoi<T>,
+    site: usize,
+    distance: f64,
+    vertices: Vec<Point>,
+}
+
+impl<'v, T: ConvexBoundary> DilatedCell<'v, T> {
+    /// Gets the site of the cell dilated.
+    #[inline]
+    pub fn site(&self) -> usize {
+        self.site
+    }
+
+    /// Gets the distance the cell was dilated by.
+    #[inline]
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Gets the vertices of the dilated polygon, which is convex and oriented as the cells of the diagram.
+    #[inline]
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Gets the area of the dilated polygon.
+    pub fn area(&self) -> f64 {
+        signed_area(&self.vertices).abs()
+    }
+
+    /// Gets the neighboring cells the dilated polygon overlaps, with the area of each overlap, ordered by site.
+    ///
+    /// Cells are searched outwards from the cell dilated, ring by ring of neighbors in the triangulation, stopping at cells not overlapped. The cell dilated is not included,
+    /// so the areas add up to the area of the dilated polygon minus the area of the cell.
+    pub fn overlaps(&self) -> Vec<(usize, f64)> {
+        let mut overlaps = Vec::new();
+        if self.distance == 0.0 {
+            return overlaps;
+        }
+
+        let mut visited = vec![false; self.voronoi.sites.len()];
+        visited[self.site] = true;
+        let mut frontier = vec![self.site];
+        while let Some(site) = frontier.pop() {
+            for neighbor in TopologicalNeighborSiteIterator::new(self.voronoi, site) {
+                if visited[neighbor] {
+                    continue;
+                }
+                visited[neighbor] = true;
+
+                let cell: Vec<Point> = self
+                    .voronoi
+                    .cell(neighbor)
+                    .iter_vertices()
+                    .cloned()
+                    .collect();
+                let area = signed_area(&clip_convex(&self.vertices, &cell)).abs();
+                if area > 0.0 {
+                    overlaps.push((neighbor, area));
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        overlaps.sort_unstable_by_key(|&(site, _)| site);
+        overlaps
+    }
+}
+
+impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
+    /// Dilates this cell by ```distance```: the Minkowski sum of the cell and a disk of radius ```distance```, clipped to the boundary of the diagram.
+    ///
+    /// The dilated polygon is made of the edges of the cell offset outwards, joined by arcs around its vertices, each approximated by segments
+    /// whose vertices are on the arc, so its area is slightly smaller than the exact dilation. See [Self::dilated_with_arc_samples()] to control the approximation.
+    /// A ```distance``` of zero gives the vertices of the cell, unchanged.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(4)
+    ///     .build()
+    ///     .unwrap();
+    /// let dilated = v.cell(5).dilated(0.1);
+    /// assert!(dilated.area() > v.cell(5).area());
+    /// assert_eq!(dilated.overlaps().len(), 8);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```distance``` is negative or not finite.
+    pub fn dilated(&self, distance: f64) -> DilatedCell<'v, T> {
+        self.dilated_with_arc_samples(distance, DEFAULT_ARC_SAMPLES)
+    }
+
+    /// Same as [Self::dilated()], with arcs approximated by ```arc_samples``` segments for each full turn.
+    ///
+    /// Each arc has at least one segment, so corners are always cut by a chord of the disk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```distance``` is negative or not finite.
+    pub fn dilated_with_arc_samples(
+        &self,
+        distance: f64,
+        arc_samples: usize,
+    ) -> DilatedCell<'v, T> {
+        assert!(
+            distance.is_finite() && distance >= 0.0,
+            "Dilation distance must be finite and not negative, got {}",
+            distance
+        );
+
+        let cell: Vec<Point> = self.iter_vertices().cloned().collect();
+        let vertices = if distance == 0.0 {
+            cell
+        } else {
+            let dilated = dilate(&cell, distance, arc_samples.max(1));
+            clip_convex(&dilated, self.voronoi().boundary().vertices())
+        };
+
+        DilatedCell {
+            voronoi: self.voronoi(),
+            site: self.site(),
+            distance,
+            vertices,
+        }
+    }
+}
+
+/// Gets the signed area of ```polygon```, positive if its vertices are oriented counter-clockwise with the Y-axis pointing upwards.
+fn signed_area(polygon: &[Point]) -> f64 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Gets the Minkowski sum of the convex ```polygon``` and a disk of radius ```distance```, with arcs of ```arc_samples``` segments per full turn.
+fn dilate(polygon: &[Point], distance: f64, arc_samples: usize) -> Vec<Point> {
+    // repeated vertices, such as the first vertex closing the cells on the hull, have no edge between them
+    let mut polygon = polygon.to_vec();
+    polygon.dedup();
+    while polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+
+    let orientation = signed_area(&polygon).signum();
+    let n = polygon.len();
+    // the angle of the outward normal of each edge, leaving from the vertex of the same index
+    let normals: Vec<f64> = (0..n)
+        .map(|i| {
+            let (a, b) = (&polygon[i], &polygon[(i + 1) % n]);
+            (-orientation * (b.x - a.x)).atan2(orientation * (b.y - a.y))
+        })
+        .collect();
+
+    let mut dilated = Vec::new();
+    for (i, vertex) in polygon.iter().enumerate() {
+        let from = normals[(i + n - 1) % n];
+        let mut sweep = (normals[i] - from).rem_euclid(TAU);
+        if orientation < 0.0 {
+            sweep -= TAU;
+        }
+        if n == 1 {
+            // a single point is dilated into a disk
+            sweep = TAU;
+        } else if sweep.abs() >= TAU - 1e-12 {
+            // the edges are collinear and no arc is needed
+            sweep = 0.0;
+        }
+
+        let segments = ((arc_samples as f64 * sweep.abs() / TAU).ceil() as usize).max(1);
+        for j in 0..=segments {
+            let angle = from + sweep * j as f64 / segments as f64;
+            let point = Point {
+                x: vertex.x + distance * angle.cos(),
+                y: vertex.y + distance * angle.sin(),
+            };
+            if dilated.last() != Some(&point) {
+                dilated.push(point);
+            }
+        }
+    }
+
+    while dilated.len() > 1 && dilated.first() == dilated.last() {
+        dilated.pop();
+    }
+
+    dilated
+}
+
+/// Clips ```polygon``` to the convex polygon ```clip```, keeping the part inside every edge of it. Both may be oriented either way.
+fn clip_convex(polygon: &[Point], clip: &[Point]) -> Vec<Point> {
+    let orientation = signed_area(clip).signum();
+    let mut clipped = polygon.to_vec();
+    for (a, b) in clip.iter().zip(clip.iter().cycle().skip(1)) {
+        if clipped.is_empty() {
+            break;
+        }
+        clipped = clip_half_plane(&clipped, |p| {
+            orientation * ((b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x))
+        });
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, utils::test::random_voronoi, BoundingBox, VoronoiBuilder};
+
+    /// Gets the cell nearest to the origin, which is far enough from the boundary for any small dilation.
+    fn central_cell(voronoi: &Voronoi<BoundingBox>) -> VoronoiCell<'_, BoundingBox> {
+        voronoi
+            .iter_cells()
+            .min_by(|a, b| {
+                let origin = Point { x: 0.0, y: 0.0 };
+                utils::dist2(a.site_position(), &origin)
+                    .total_cmp(&utils::dist2(b.site_position(), &origin))
+            })
+            .expect("Some cell expected")
+    }
+
+    #[test]
+    fn zero_distance_is_identity() {
+        let voronoi = random_voronoi(100);
+        for cell in voronoi.iter_cells() {
+            let dilated = cell.dilated(0.0);
+            let vertices: Vec<Point> = cell.iter_vertices().cloned().collect();
+            assert_eq!(dilated.vertices(), vertices.as_slice());
+            assert_eq!(dilated.area(), cell.area());
+            assert!(dilated.overlaps().is_empty());
+        }
+    }
+
+    #[test]
+    fn interior_cell_area_grows_by_perimeter_and_disk() {
+        let voronoi = random_voronoi(200);
+        let cell = central_cell(&voronoi);
+        let perimeter: f64 = cell.iter_edge_metadata().map(|edge| edge.length).sum();
+
+        let d = 0.05;
+        let dilated = cell.dilated_with_arc_samples(d, 4096);
+        assert!(dilated
+            .vertices()
+            .iter()
+            .all(|p| voronoi.boundary().is_inside(p)));
+        let expected = cell.area() + perimeter * d + PI * d * d;
+        assert!(
+            utils::abs_diff_eq(dilated.area(), expected, 1e-4 * expected),
+            "Dilated area {} expected to be {}",
+            dilated.area(),
+            expected
+        );
+
+        // the overlaps partition what the cell grew by
+        let overlapped: f64 = dilated.overlaps().iter().map(|(_, area)| area).sum();
+        assert!(utils::abs_diff_eq(
+            overlapped,
+            dilated.area() - cell.area(),
+            1e-9
+        ));
+    }
+
+    #[test]
+    fn overlaps_match_independent_intersections() {
+        let voronoi = random_voronoi(150);
+        let mut rng = utils::test::rng();
+        for _ in 0..10 {
+            let cell = voronoi.cell(rng.gen_range(0..voronoi.sites().len()));
+            let d = rng.gen_range(0.01..0.3);
+            let dilated = cell.dilated(d);
+            let overlaps = dilated.overlaps();
+
+            // intersect the dilated polygon with every other cell of the diagram
+            let expected: Vec<(usize, f64)> = voronoi
+                .iter_cells()
+                .filter(|other| other.site() != cell.site())
+                .filter_map(|other| {
+                    let vertices: Vec<Point> = other.iter_vertices().cloned().collect();
+                    let area = signed_area(&clip_convex(dilated.vertices(), &vertices)).abs();
+                    (area > 1e-12).then_some((other.site(), area))
+                })
+                .collect();
+            let found: Vec<(usize, f64)> = overlaps
+                .iter()
+                .copied()
+                .filter(|&(_, area)| area > 1e-12)
+                .collect();
+            assert_eq!(found.len(), expected.len());
+            for ((site, area), (expected_site, expected_area)) in found.iter().zip(expected.iter())
+            {
+                assert_eq!(site, expected_site);
+                assert!(utils::abs_diff_eq(*area, *expected_area, 1e-12));
+            }
+
+            // overlap areas are bounded by the cells overlapped
+            for &(site, area) in overlaps.iter() {
+                assert!(area <= voronoi.cell(site).area() + 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn dilation_is_clipped_to_boundary() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(4)
+            .build()
+            .expect("Some voronoi expected");
+        // a corner cell dilated past the boundary only grows into its neighbors
+        let dilated = voronoi.cell(0).dilated(0.1);
+        assert!(dilated
+            .vertices()
+            .iter()
+            .all(|p| voronoi.boundary().is_inside(p)));
+        let overlapped: f64 = dilated.overlaps().iter().map(|(_, area)| area).sum();
+        assert!(utils::abs_diff_eq(
+            dilated.area(),
+            voronoi.cell(0).area() + overlapped,
+            1e-12
+        ));
+        assert_eq!(
+            dilated
+                .overlaps()
+                .iter()
+                .map(|&(site, _)| site)
+                .collect::<Vec<usize>>(),
+            vec![1, 4, 5]
+        );
+    }
+}
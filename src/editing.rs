@@ -0,0 +1,1092 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    cell_builder::CellBuilderArena,
+    debug_validation::debug_validate,
+    decimation::cap_cell_vertices,
+    diagnostics::DiagnosticsCache,
+    fan::FanIndexCache,
+    incremental::{SiteChanges, SiteUpdate},
+    instrument::phase,
+    separation::enforce_min_separation,
+    short_edges::eliminate_short_edges,
+    symmetry::{find_mirrors, symmetrize, tolerance},
+    utils::{cycle_positions, same_rotation, EQ_EPSILON},
+    welding::weld_vertices,
+    BuildError, BuildStats, ConvexBoundary, DebugValidation, Point, SeparationPolicy, SymmetryAxis,
+    Voronoi,
+};
+
+/// Source of diagram generations. Generations are unique across all diagrams, so a diagram and its clones diverge as soon as either is edited.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// Batches of more inserted sites than the existing sites divided by this are inserted by rebuilding the diagram, which is then faster than inserting them in place.
+const SITES_PER_BATCH_SITE: usize = 16;
+
+/// Allocates a new, unique, diagram generation.
+pub(crate) fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// An error returned when editing a [Voronoi] diagram. The diagram is left unchanged when an edit fails.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditError {
+    /// The site index does not exist in the diagram.
+    SiteOutOfRange(usize),
+
+    /// The position is not within the boundary of the diagram and would be removed when clipping.
+    OutsideBoundary(Point),
+
+    /// Both site indices given to [Voronoi::merge_sites()] are the same.
+    SameSite(usize),
+
+    /// The resulting set of sites does not generate a valid diagram (e.g. all sites are collinear).
+    Degenerate,
+
+    /// The diagram was built with [crate::VoronoiBuilder::set_min_separation()], and these pairs of sites would be closer than the minimum separation,
+    /// with their distance. Edits never drop or merge sites, whatever the [SeparationPolicy] of the build.
+    SitesTooClose(Vec<(usize, usize, f64)>),
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::SiteOutOfRange(site) => write!(f, "Site {} does not exist", site),
+            EditError::OutsideBoundary(p) => {
+                write!(f, "Position ({}, {}) is outside of the boundary", p.x, p.y)
+            }
+            EditError::SameSite(site) => write!(f, "Cannot merge site {} with itself", site),
+            EditError::Degenerate => write!(f, "Sites do not generate a valid diagram"),
+            EditError::SitesTooClose(pairs) => write!(
+                f,
+                "{} pairs of sites would be closer than the minimum separation",
+                pairs.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// The options a diagram was built with that are applied again every time it is edited.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RebuildOptions {
+    pub(crate) short_edge_elimination: Option<(f64, usize)>,
+    pub(crate) max_cell_vertices: Option<usize>,
+    pub(crate) vertex_welding: Option<f64>,
+    pub(crate) symmetry: Option<SymmetryAxis>,
+    pub(crate) min_separation: Option<(f64, SeparationPolicy)>,
+    pub(crate) debug_validation: DebugValidation,
+}
+
+/// What became of a site given to [Voronoi::insert_sites()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The site was inserted, with this index.
+    Inserted(usize),
+
+    /// The site is at the same position as the site of this index, already in the diagram or inserted earlier in the batch, so it was not inserted.
+    Duplicate(usize),
+
+    /// The site is not within the boundary of the diagram and would be removed when clipping, so it was not inserted.
+    OutsideBoundary,
+}
+
+/// The outcome of each site of a batch inserted by [Voronoi::insert_sites()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertBatchReport {
+    /// The outcome of each site, in the order the sites were given.
+    pub outcomes: Vec<InsertOutcome>,
+}
+
+impl InsertBatchReport {
+    /// Gets the number of sites inserted.
+    pub fn inserted(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|o| matches!(o, InsertOutcome::Inserted(_)))
+            .count()
+    }
+}
+
+/// A record of an edit performed on a diagram, in terms of site indices.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Edit {
+    Moved(usize),
+    /// The sites were moved at once, in increasing order, see [Voronoi::relax_local()].
+    MovedBatch(Vec<usize>),
+    /// Sites are always inserted at the end, so no other index is affected.
+    Inserted(usize),
+    /// The ```count``` sites from ```first``` were inserted at once, see [Voronoi::insert_sites()].
+    InsertedBatch {
+        first: usize,
+        count: usize,
+    },
+    Removed(usize),
+    /// Site ```removed``` was merged into ```into```, given as the index after the removal.
+    Merged {
+        removed: usize,
+        into: usize,
+    },
+}
+
+impl Edit {
+    /// Maps a site index from before this edit to after it.
+    /// ```Err``` is returned if the site does not exist anymore, with the site it was merged into, if any.
+    pub(crate) fn forward(&self, site: usize) -> Result<usize, Option<usize>> {
+        match *self {
+            Edit::Moved(_)
+            | Edit::MovedBatch(_)
+            | Edit::Inserted(_)
+            | Edit::InsertedBatch { .. } => Ok(site),
+            Edit::Removed(r) if site == r => Err(None),
+            Edit::Merged { removed, into } if site == removed => Err(Some(into)),
+            Edit::Removed(r) | Edit::Merged { removed: r, .. } if site > r => Ok(site - 1),
+            _ => Ok(site),
+        }
+    }
+
+    /// Maps a site index from after this edit to before it. ```None``` is returned if the site did not exist before this edit.
+    pub(crate) fn backward(&self, site: usize) -> Option<usize> {
+        match *self {
+            Edit::Inserted(s) if site == s => None,
+            Edit::InsertedBatch { first, count } if (first..first + count).contains(&site) => None,
+            Edit::Removed(r) | Edit::Merged { removed: r, .. } if site >= r => Some(site + 1),
+            _ => Some(site),
+        }
+    }
+
+    /// Gets the name of this edit and the site it was performed on, as traced by the ```edit``` span. The site removed is given for a merge.
+    #[cfg(feature = "tracing")]
+    fn traced(&self) -> (&'static str, usize) {
+        match *self {
+            Edit::Moved(site) => ("move", site),
+            Edit::MovedBatch(ref sites) => ("move_batch", sites[0]),
+            Edit::Inserted(site) => ("insert", site),
+            Edit::InsertedBatch { first, .. } => ("insert_batch", first),
+            Edit::Removed(site) => ("remove", site),
+            Edit::Merged { removed, .. } => ("merge", removed),
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the generation of this diagram.
+    ///
+    /// A new, unique, generation is assigned when a diagram is built and every time it is edited.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Gets the generation in which the cell of ```site``` last changed: the site was inserted, moved or merged into, or the vertices of its cell changed
+    /// as a consequence of an edit, e.g. the neighbors of a moved site.
+    #[inline]
+    pub fn cell_generation(&self, site: usize) -> u64 {
+        self.cell_generations[site]
+    }
+
+    /// Moves ```site``` to ```position``` and updates the diagram.
+    ///
+    /// Edits of a clipped diagram are applied in place: the triangulation only changes around the sites edited, and only the cells of the sites whose neighbors
+    /// changed are built and clipped again, so an edit takes a time that depends on the number of cells it reshapes rather than on the size of the diagram.
+    /// Removing a site still shifts the index of every site after it, which takes a time linear in the number of sites, without any geometry.
+    /// Vertices left unused by the cells rebuilt stay in [Self::vertices()] until they are dropped, after enough edits.
+    ///
+    /// The diagram is rebuilt from all of its sites instead, which takes as long as building it, if it is not clipped, if the triangulation cannot be changed
+    /// in place around nearly degenerate sites, or if it was built with an option that changes the whole diagram, such as [crate::VoronoiBuilder::set_short_edge_elimination()],
+    /// [crate::VoronoiBuilder::set_max_cell_vertices()] and [crate::VoronoiBuilder::set_vertex_welding()], which are applied again. Every edit replaces the [Self::build_warnings()].
+    /// The sites are not moved again by Lloyd relaxation, and the boundary is kept even if it was chosen by [crate::VoronoiBuilder::set_auto_boundary()],
+    /// so the [Self::build_stats()] are reset. A diagram built with [crate::VoronoiBuilder::set_symmetry()] stays symmetric as long as its sites are,
+    /// e.g. when a site and its mirror are both moved, and loses its [Self::symmetry_axis()] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [EditError::SitesTooClose] if the diagram was built with [crate::VoronoiBuilder::set_min_separation()] and the site would be too close to another.
+    pub fn move_site(&mut self, site: usize, position: Point) -> Result<(), EditError> {
+        self.check_site(site)?;
+        self.check_position(&position)?;
+
+        let changes = SiteChanges {
+            moved: vec![(site, position)],
+            ..SiteChanges::default()
+        };
+        self.edit_sites(changes, Edit::Moved(site))
+    }
+
+    /// Inserts a new site at ```position``` and updates the diagram, in place if possible, see [Self::move_site()]. The new site is appended, so no existing site index changes.
+    ///
+    /// Returns the index of the new site.
+    pub fn insert_site(&mut self, position: Point) -> Result<usize, EditError> {
+        self.check_position(&position)?;
+
+        let site = self.sites.len();
+        let changes = SiteChanges {
+            inserted: vec![position],
+            ..SiteChanges::default()
+        };
+        self.edit_sites(changes, Edit::Inserted(site))?;
+
+        Ok(site)
+    }
+
+    /// Inserts a batch of ```new_sites``` and updates the diagram once, which is faster than inserting them one at a time with [Self::insert_site()].
+    ///
+    /// Sites outside of the boundary and sites at the same position as another one are not inserted, without failing the rest of the batch.
+    /// The other sites are appended in the order given, so no existing site index changes and the diagram has the same cells as one built from all the sites.
+    /// Duplicates are found by locating the new sites in spatial order, each from the cell of the previous one.
+    /// The sites are inserted in place in the same order, see [Self::move_site()], each located from the previous one, and each cell reshaped by the batch is built
+    /// and clipped once, after all of them are inserted. The diagram is not changed, and keeps its generation, if no site is inserted.
+    ///
+    /// Inserting in place takes a time proportional to the size of the batch, while building the diagram again takes a time proportional to the number of sites,
+    /// so a batch of more than about one new site for every 16 existing sites is inserted by rebuilding the diagram from all the sites instead.
+    /// For example, inserting 1,000 random sites into a diagram of 100,000 takes about a quarter of the time needed to build it, and 6,000 takes about as long.
+    ///
+    /// Returns the outcome of each site given.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let report = voronoi
+    ///     .insert_sites(vec![
+    ///         Point { x: 0.42, y: -0.31 },
+    ///         Point { x: 5.0, y: 0.0 },
+    ///         Point { x: 0.42, y: -0.31 },
+    ///     ])
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     report.outcomes,
+    ///     vec![InsertOutcome::Inserted(100), InsertOutcome::OutsideBoundary, InsertOutcome::Duplicate(100)]
+    /// );
+    /// assert_eq!(voronoi.sites().len(), 101);
+    ///```
+    ///
+    /// # Errors
+    ///
+    /// Returns [EditError::Degenerate], leaving the diagram unchanged, if the sites do not generate a valid diagram.
+    pub fn insert_sites(&mut self, new_sites: Vec<Point>) -> Result<InsertBatchReport, EditError> {
+        // the outcome of each new site, with the new site it duplicates until indices are assigned
+        let mut outcomes = vec![InsertOutcome::OutsideBoundary; new_sites.len()];
+        let mut copy_of = vec![None; new_sites.len()];
+        let mut kept = vec![false; new_sites.len()];
+        let mut hint = 0;
+        let mut previous: Option<usize> = None;
+        for i in spatial_order(&new_sites) {
+            let position = &new_sites[i];
+            if self.check_position(position).is_err() {
+                continue;
+            }
+
+            // equal positions are consecutive in spatial order, the first given first
+            if let Some(j) = previous.filter(|&j| new_sites[j] == *position) {
+                copy_of[i] = Some(j);
+                continue;
+            }
+            if let Some(site) = self.cell_containing_point_from(position, hint) {
+                hint = site;
+                if self.sites[site] == *position {
+                    outcomes[i] = InsertOutcome::Duplicate(site);
+                    continue;
+                }
+            }
+
+            kept[i] = true;
+            previous = Some(i);
+        }
+
+        let first = self.sites.len();
+        let mut inserted = Vec::new();
+        let mut indices = vec![0; new_sites.len()];
+        for (i, position) in new_sites.into_iter().enumerate() {
+            if kept[i] {
+                indices[i] = first + inserted.len();
+                outcomes[i] = InsertOutcome::Inserted(indices[i]);
+                inserted.push(position);
+            }
+        }
+        for (i, copy) in copy_of.iter().enumerate() {
+            if let Some(&j) = copy.as_ref() {
+                outcomes[i] = InsertOutcome::Duplicate(indices[j]);
+            }
+        }
+
+        let count = inserted.len();
+        if count > 0 {
+            let changes = SiteChanges {
+                inserted,
+                ..SiteChanges::default()
+            };
+            self.edit_sites(changes, Edit::InsertedBatch { first, count })?;
+        }
+
+        Ok(InsertBatchReport { outcomes })
+    }
+
+    /// Removes ```site``` and updates the diagram, in place if possible, see [Self::move_site()]. The index of every site after ```site``` is decreased by one.
+    pub fn remove_site(&mut self, site: usize) -> Result<(), EditError> {
+        self.check_site(site)?;
+
+        let changes = SiteChanges {
+            removed: Some(site),
+            ..SiteChanges::default()
+        };
+        self.edit_sites(changes, Edit::Removed(site))
+    }
+
+    /// Merges the cell of site ```remove``` into the cell of site ```keep``` and updates the diagram, in place if possible, see [Self::move_site()].
+    ///
+    /// The site ```remove``` is removed, shifting the index of every site after it, and ```keep``` keeps its position.
+    /// Returns the index of ```keep``` after the merge.
+    pub fn merge_sites(&mut self, keep: usize, remove: usize) -> Result<usize, EditError> {
+        self.check_site(keep)?;
+        self.check_site(remove)?;
+        if keep == remove {
+            return Err(EditError::SameSite(keep));
+        }
+
+        let into = if keep > remove { keep - 1 } else { keep };
+        let changes = SiteChanges {
+            removed: Some(remove),
+            ..SiteChanges::default()
+        };
+        self.edit_sites(
+            changes,
+            Edit::Merged {
+                removed: remove,
+                into,
+            },
+        )?;
+
+        Ok(into)
+    }
+
+    /// Discards the record of the edits up to ```generation```, which every edit adds to, so a diagram edited for a long time does not grow without limit.
+    ///
+    /// The record is how a [crate::CellMap] of an earlier generation follows the site indices to the current one, so maps of a generation before ```generation```
+    /// cannot be synchronized with this diagram anymore, while those of ```generation``` or later are unaffected. Nothing is discarded if this diagram never had
+    /// that generation, or while a [crate::Transaction] is open on it.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(5)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut map = voronoi.new_cell_map::<()>();
+    /// for i in 0..100 {
+    ///     voronoi.move_site(12, Point { x: 0.001 * i as f64, y: 0.0 }).unwrap();
+    ///     map.sync(&voronoi);
+    ///     voronoi.trim_edits_before(map.generation());
+    /// }
+    ///```
+    pub fn trim_edits_before(&mut self, generation: u64) {
+        if self.in_transaction.is_open() {
+            return;
+        }
+
+        if let Some(i) = self.edit_log.iter().position(|(g, _)| *g == generation) {
+            self.edit_log.drain(..=i);
+            self.origin_generation = generation;
+        }
+    }
+
+    /// Returns the edits performed since ```generation```, with the generation each resulted in, or ```None``` if this diagram never had that generation
+    /// or its edits since were discarded by [Self::trim_edits_before()].
+    pub(crate) fn edits_since(&self, generation: u64) -> Option<&[(u64, Edit)]> {
+        let start = if generation == self.origin_generation {
+            0
+        } else {
+            self.edit_log.iter().position(|(g, _)| *g == generation)? + 1
+        };
+
+        Some(&self.edit_log[start..])
+    }
+
+    fn check_site(&self, site: usize) -> Result<(), EditError> {
+        if site < self.sites.len() {
+            Ok(())
+        } else {
+            Err(EditError::SiteOutOfRange(site))
+        }
+    }
+
+    fn check_position(&self, position: &Point) -> Result<(), EditError> {
+        if self.clip_behavior == crate::ClipBehavior::None || self.boundary.is_inside(position) {
+            Ok(())
+        } else {
+            Err(EditError::OutsideBoundary(position.clone()))
+        }
+    }
+
+    /// Applies ```changes``` to the sites, in place if no option the diagram was built with needs to be applied again to the whole diagram, and rebuilds it otherwise.
+    pub(crate) fn edit_sites(&mut self, changes: SiteChanges, edit: Edit) -> Result<(), EditError> {
+        let _phase = phase!(
+            info_span,
+            "edit",
+            operation = edit.traced().0,
+            site = edit.traced().1
+        );
+        let options = self.rebuild_options;
+        let in_place = options.short_edge_elimination.is_none()
+            && options.max_cell_vertices.is_none()
+            && options.vertex_welding.is_none()
+            && options.symmetry.is_none()
+            && options.min_separation.is_none()
+            && (changes.inserted.len() <= 1
+                || changes.inserted.len() * SITES_PER_BATCH_SITE <= self.sites.len());
+        match in_place.then(|| self.update_sites(&changes)).flatten() {
+            Some(update) => {
+                self.finish_update(update, edit);
+                Ok(())
+            }
+            None => self.rebuild(changes.apply(&self.sites), edit),
+        }
+    }
+
+    /// Records an edit applied in place by [Self::update_sites()]. The cells reshaped by the edit get the new generation, and the fans of the cells rebuilt are recalculated.
+    fn finish_update(&mut self, update: SiteUpdate, edit: Edit) {
+        let generation = next_generation();
+        self.advance_cell_generations(&edit, generation);
+        let mut positions = Vec::new();
+        for (site, old) in &update.rebuilt {
+            if let Some(old) = old {
+                positions.clear();
+                // the circumcenters of cocircular sites may have been calculated from other triangles
+                cycle_positions(
+                    &self.cells[*site],
+                    &self.circumcenters,
+                    EQ_EPSILON,
+                    &mut positions,
+                );
+                if !same_rotation(&update.old_positions[old.clone()], &positions, EQ_EPSILON) {
+                    self.cell_generations[*site] = generation;
+                }
+            }
+        }
+
+        let rebuilt: Vec<usize> = update.rebuilt.iter().map(|(site, _)| *site).collect();
+        let fan_indices = self.fan_indices.take().map(|fans| {
+            fans.remap(
+                &edit,
+                &self.cells,
+                &self.circumcenters,
+                &self.cell_generations,
+                &rebuilt,
+                &update.remap,
+                generation,
+            )
+        });
+        self.set_fan_indices(fan_indices);
+
+        self.symmetry = None;
+        self.warnings.clear();
+        self.stats = BuildStats::default();
+        self.diagnostics = DiagnosticsCache::default();
+        self.generation = generation;
+        self.edit_log.push((generation, edit));
+        if cfg!(debug_assertions) {
+            debug_validate(self, self.rebuild_options.debug_validation);
+        }
+    }
+
+    /// Gives the sites edited their new generation, and the sites inserted their first.
+    fn advance_cell_generations(&mut self, edit: &Edit, generation: u64) {
+        match *edit {
+            Edit::Moved(site) => self.cell_generations[site] = generation,
+            Edit::MovedBatch(ref sites) => {
+                for &site in sites {
+                    self.cell_generations[site] = generation;
+                }
+            }
+            Edit::Inserted(_) => self.cell_generations.push(generation),
+            Edit::InsertedBatch { first, count } => {
+                self.cell_generations.resize(first + count, generation)
+            }
+            Edit::Removed(site) => {
+                self.cell_generations.remove(site);
+            }
+            Edit::Merged { removed, into } => {
+                self.cell_generations.remove(removed);
+                self.cell_generations[into] = generation;
+            }
+        }
+    }
+
+    fn set_fan_indices(&mut self, fans: Option<FanIndexCache>) {
+        if let Some(fans) = fans {
+            let _ = self.fan_indices.set(fans);
+        }
+    }
+
+    /// Rebuilds the diagram from ```sites```, applying again the options it was built with.
+    pub(crate) fn rebuild(&mut self, sites: Vec<Point>, edit: Edit) -> Result<(), EditError> {
+        let options = self.rebuild_options;
+        let sites = match options.min_separation {
+            Some((distance, _)) => {
+                match enforce_min_separation(sites, distance, SeparationPolicy::Error) {
+                    Ok((sites, _)) => sites,
+                    Err(BuildError::SitesTooClose(pairs)) => {
+                        return Err(EditError::SitesTooClose(pairs))
+                    }
+                    Err(_) => return Err(EditError::Degenerate),
+                }
+            }
+            None => sites,
+        };
+        let mirrors = options.symmetry.and_then(|axis| {
+            find_mirrors(&sites, &axis, tolerance(&self.boundary)).map(|mirrors| (axis, mirrors))
+        });
+        let mut rebuilt = Voronoi::new(
+            sites,
+            self.boundary.clone(),
+            self.clip_behavior,
+            self.adjacency_epsilon,
+            self.engine,
+            &mut CellBuilderArena::default(),
+        )
+        .ok_or(EditError::Degenerate)?;
+
+        // the same steps as building, which skips those that change the cells of a symmetric diagram
+        match mirrors {
+            Some((axis, mirrors)) => {
+                symmetrize(&mut rebuilt, &axis);
+                rebuilt.symmetry = Some((axis, mirrors));
+            }
+            None => {
+                if let Some((min_edge_length, max_passes)) = options.short_edge_elimination {
+                    rebuilt = eliminate_short_edges(rebuilt, min_edge_length, max_passes).0;
+                }
+                if let Some(max_vertices) = options.max_cell_vertices {
+                    cap_cell_vertices(&mut rebuilt, max_vertices);
+                }
+                if let Some(epsilon) = options.vertex_welding {
+                    weld_vertices(&mut rebuilt, epsilon);
+                }
+            }
+        }
+        if cfg!(debug_assertions) {
+            debug_validate(&rebuilt, options.debug_validation);
+        }
+
+        let generation = rebuilt.generation;
+        self.advance_cell_generations(&edit, generation);
+
+        // the cells reshaped by the edit changed as well, ignoring the ulps by which the circumcenters of cocircular sites differ, as when edited in place
+        let (mut old_positions, mut positions) = (Vec::new(), Vec::new());
+        for (site, cell) in rebuilt.cells.iter().enumerate() {
+            if let Some(old) = edit.backward(site) {
+                old_positions.clear();
+                positions.clear();
+                cycle_positions(
+                    &self.cells[old],
+                    &self.circumcenters,
+                    EQ_EPSILON,
+                    &mut old_positions,
+                );
+                cycle_positions(cell, &rebuilt.circumcenters, EQ_EPSILON, &mut positions);
+                if !same_rotation(&old_positions, &positions, EQ_EPSILON) {
+                    self.cell_generations[site] = generation;
+                }
+            }
+        }
+
+        let fan_indices = self.fan_indices.take().map(|fans| {
+            fans.patch(
+                &edit,
+                &self.cells,
+                &self.circumcenters,
+                &rebuilt.cells,
+                &rebuilt.circumcenters,
+                &self.cell_generations,
+                generation,
+            )
+        });
+        self.set_fan_indices(fan_indices);
+
+        self.sites = rebuilt.sites;
+        self.triangulation = rebuilt.triangulation;
+        self.circumcenters = rebuilt.circumcenters;
+        self.clipped_vertices = rebuilt.clipped_vertices;
+        self.clipped_cells = None;
+        self.site_to_incoming_leftmost_halfedge = rebuilt.site_to_incoming_leftmost_halfedge;
+        self.cells = rebuilt.cells;
+        self.symmetry = rebuilt.symmetry;
+        self.warnings = rebuilt.warnings;
+        self.stats = BuildStats::default();
+        self.diagnostics = DiagnosticsCache::default();
+        self.generation = generation;
+        self.edit_log.push((generation, edit));
+
+        Ok(())
+    }
+}
+
+/// Gets the indices of ```points``` in the order of the Morton curve over their bounding rectangle, so that consecutive points are usually close.
+///
+/// Points at the same position are consecutive, in the order given.
+pub(crate) fn spatial_order(points: &[Point]) -> Vec<usize> {
+    let (min, max) = points
+        .iter()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), p| {
+                (
+                    (min.0.min(p.x), min.1.min(p.y)),
+                    (max.0.max(p.x), max.1.max(p.y)),
+                )
+            },
+        );
+    let quantize = |v: f64, min: f64, max: f64| -> u64 {
+        let t = if max > min {
+            (v - min) / (max - min)
+        } else {
+            0.0
+        };
+        (t.clamp(0.0, 1.0) * u32::MAX as f64) as u64
+    };
+    let keys: Vec<u64> = points
+        .iter()
+        .map(|p| {
+            interleave(quantize(p.x, min.0, max.0)) | interleave(quantize(p.y, min.1, max.1)) << 1
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys[a]
+            .cmp(&keys[b])
+            .then(points[a].x.total_cmp(&points[b].x))
+            .then(points[a].y.total_cmp(&points[b].y))
+            .then(a.cmp(&b))
+    });
+
+    order
+}
+
+/// Spreads the lower 32 bits of ```v``` to the even bits of the result.
+fn interleave(mut v: u64) -> u64 {
+    v &= 0xffff_ffff;
+    v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+    v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, utils::test::square_voronoi, BoundingBox, VoronoiBuilder};
+
+    #[test]
+    fn edits_rebuild_diagram() {
+        let sites = utils::test::random_sites(50);
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+        let site_count = voronoi.sites().len();
+
+        voronoi
+            .move_site(3, Point { x: 0.1, y: 0.05 })
+            .expect("Move expected to succeed");
+        let inserted = voronoi
+            .insert_site(Point { x: 0.42, y: -0.31 })
+            .expect("Insert expected to succeed");
+        assert_eq!(inserted, site_count);
+        voronoi.remove_site(0).expect("Remove expected to succeed");
+        let into = voronoi
+            .merge_sites(10, 5)
+            .expect("Merge expected to succeed");
+        assert_eq!(into, 9);
+
+        assert_eq!(voronoi.sites().len(), site_count - 1);
+        assert_eq!(voronoi.cells().len(), site_count - 1);
+        utils::test::validate_voronoi(&voronoi);
+    }
+
+    /// Gets whether the cell of ```site``` has the same vertices in both diagrams, up to the rounding of the circumcenters of cocircular sites.
+    fn same_cell<T: ConvexBoundary>(a: &Voronoi<T>, b: &Voronoi<T>, site: usize) -> bool {
+        let (mut a_positions, mut b_positions) = (Vec::new(), Vec::new());
+        cycle_positions(
+            a.cell(site).triangles(),
+            a.vertices(),
+            EQ_EPSILON,
+            &mut a_positions,
+        );
+        cycle_positions(
+            b.cell(site).triangles(),
+            b.vertices(),
+            EQ_EPSILON,
+            &mut b_positions,
+        );
+        same_rotation(&a_positions, &b_positions, EQ_EPSILON)
+    }
+
+    #[test]
+    fn cell_generations_track_changed_cells() {
+        let mut voronoi = square_voronoi(6);
+        let original = voronoi.clone();
+        let built = voronoi.generation();
+        assert!(voronoi
+            .iter_cells()
+            .all(|c| voronoi.cell_generation(c.site()) == built));
+
+        voronoi
+            .move_site(7, Point { x: -0.1, y: 0.2 })
+            .expect("Move expected to succeed");
+        assert!(voronoi.generation() > built);
+        // the diagonal neighbors of the grid only share a vertex with the cell
+        let neighbors: Vec<usize> = original
+            .cell(7)
+            .iter_neighbors()
+            .filter(|&n| original.cell(7).shared_edge_length(n) > 1e-9)
+            .collect();
+        for site in 0..voronoi.sites().len() {
+            let expected = if site == 7 || !same_cell(&original, &voronoi, site) {
+                voronoi.generation()
+            } else {
+                built
+            };
+            assert_eq!(voronoi.cell_generation(site), expected, "Site {}.", site);
+        }
+
+        // the neighbors reshaped by the move changed, cells far from it did not
+        assert!(neighbors
+            .iter()
+            .all(|&n| voronoi.cell_generation(n) == voronoi.generation()));
+        assert_eq!(voronoi.cell_generation(35), built);
+    }
+
+    #[test]
+    fn failed_edits_leave_diagram_unchanged() {
+        let mut voronoi = square_voronoi(6);
+        let generation = voronoi.generation();
+        let sites = voronoi.sites().clone();
+
+        assert_eq!(
+            voronoi.move_site(100, Point { x: 0.0, y: 0.0 }),
+            Err(EditError::SiteOutOfRange(100))
+        );
+        assert_eq!(
+            voronoi.insert_site(Point { x: 10.0, y: 0.0 }),
+            Err(EditError::OutsideBoundary(Point { x: 10.0, y: 0.0 }))
+        );
+        assert_eq!(voronoi.merge_sites(2, 2), Err(EditError::SameSite(2)));
+
+        assert_eq!(voronoi.generation(), generation);
+        assert_eq!(voronoi.sites(), &sites);
+    }
+
+    #[test]
+    fn insert_sites_matches_build_of_all_sites() {
+        // inserted in place, then into a diagram small enough to be rebuilt
+        for &size in &[4_000, 500] {
+            insert_batch_into(size);
+        }
+    }
+
+    fn insert_batch_into(size: usize) {
+        let mut rng = utils::test::rng();
+        let mut random_sites = |count: usize| -> Vec<Point> {
+            (0..count)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect()
+        };
+        let (sites, new_sites) = (random_sites(size), random_sites(200));
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        let built = voronoi.generation();
+
+        let report = voronoi
+            .insert_sites(new_sites.clone())
+            .expect("Insert expected to succeed");
+        assert_eq!(report.inserted(), 200);
+        assert!(report
+            .outcomes
+            .iter()
+            .enumerate()
+            .all(|(i, &o)| o == InsertOutcome::Inserted(size + i)));
+
+        let expected = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.into_iter().chain(new_sites.iter().cloned()).collect())
+            .build()
+            .expect("Some voronoi expected");
+        // the triangulation differs where sites are cocircular
+        assert!(utils::test::geometry_approx_eq(&voronoi, &expected, 1e-9));
+
+        // the batch is a single edit, and every inserted or reshaped cell has its generation
+        assert_eq!(voronoi.edits_since(built).map(|edits| edits.len()), Some(1));
+        let original = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(expected.sites()[..size].to_vec())
+            .build()
+            .expect("Some voronoi expected");
+        for site in 0..voronoi.sites().len() {
+            let generation = if site < size && same_cell(&original, &voronoi, site) {
+                built
+            } else {
+                voronoi.generation()
+            };
+            assert_eq!(voronoi.cell_generation(site), generation);
+        }
+
+        // inserting one site at a time ends the same
+        let mut looped = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(expected.sites()[..size].to_vec())
+            .build()
+            .expect("Some voronoi expected");
+        for p in new_sites {
+            looped.insert_site(p).expect("Insert expected to succeed");
+        }
+        assert!(utils::test::geometry_approx_eq(&voronoi, &looped, 1e-9));
+    }
+
+    #[test]
+    fn insert_sites_reports_each_outcome() {
+        let mut voronoi = square_voronoi(6);
+        let existing = voronoi.sites()[7].clone();
+        let report = voronoi
+            .insert_sites(vec![
+                Point { x: 0.31, y: 0.12 },
+                Point { x: 3.0, y: 0.0 },
+                existing.clone(),
+                Point { x: -0.27, y: 0.44 },
+                Point { x: 0.31, y: 0.12 },
+                Point { x: 0.0, y: -1.5 },
+                Point { x: -0.27, y: 0.44 },
+                existing,
+            ])
+            .expect("Insert expected to succeed");
+
+        assert_eq!(
+            report.outcomes,
+            vec![
+                InsertOutcome::Inserted(36),
+                InsertOutcome::OutsideBoundary,
+                InsertOutcome::Duplicate(7),
+                InsertOutcome::Inserted(37),
+                InsertOutcome::Duplicate(36),
+                InsertOutcome::OutsideBoundary,
+                InsertOutcome::Duplicate(37),
+                InsertOutcome::Duplicate(7),
+            ]
+        );
+        assert_eq!(report.inserted(), 2);
+        assert_eq!(voronoi.sites().len(), 38);
+        assert_eq!(voronoi.sites()[36], Point { x: 0.31, y: 0.12 });
+        assert_eq!(voronoi.sites()[37], Point { x: -0.27, y: 0.44 });
+        let expected = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(voronoi.sites().clone())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(utils::test::geometry_approx_eq(&voronoi, &expected, 1e-9));
+
+        // nothing inserted leaves the diagram as it was
+        let generation = voronoi.generation();
+        let report = voronoi
+            .insert_sites(vec![Point { x: 5.0, y: 5.0 }, Point { x: 0.31, y: 0.12 }])
+            .expect("Insert expected to succeed");
+        assert_eq!(
+            report.outcomes,
+            vec![InsertOutcome::OutsideBoundary, InsertOutcome::Duplicate(36)]
+        );
+        assert_eq!(voronoi.generation(), generation);
+    }
+
+    #[test]
+    fn edit_translates_indices_both_ways() {
+        let removed = Edit::Removed(3);
+        assert_eq!(removed.forward(2), Ok(2));
+        assert_eq!(removed.forward(3), Err(None));
+        assert_eq!(removed.forward(4), Ok(3));
+        assert_eq!(removed.backward(2), Some(2));
+        assert_eq!(removed.backward(3), Some(4));
+
+        let merged = Edit::Merged {
+            removed: 1,
+            into: 4,
+        };
+        assert_eq!(merged.forward(1), Err(Some(4)));
+        assert_eq!(merged.forward(5), Ok(4));
+
+        let inserted = Edit::Inserted(8);
+        assert_eq!(inserted.forward(7), Ok(7));
+        assert_eq!(inserted.backward(8), None);
+
+        let batch = Edit::InsertedBatch { first: 8, count: 3 };
+        assert_eq!(batch.forward(7), Ok(7));
+        assert_eq!(batch.backward(7), Some(7));
+        assert_eq!(batch.backward(10), None);
+        assert_eq!(batch.backward(11), Some(11));
+    }
+
+    #[test]
+    fn edits_apply_build_options() {
+        let builder = VoronoiBuilder::default()
+            .set_boundary(crate::ConvexPolygon::regular(64, 1.5))
+            .set_sites(utils::test::random_sites(300))
+            .set_short_edge_elimination(1e-3, 3)
+            .set_max_cell_vertices(6)
+            .set_vertex_welding(1e-3)
+            .set_debug_validation(DebugValidation::Fast);
+        let mut voronoi = builder.clone().build().expect("Some voronoi expected");
+        voronoi
+            .move_site(3, Point { x: 0.1, y: 0.05 })
+            .expect("Move expected to succeed");
+        voronoi.remove_site(10).expect("Remove expected to succeed");
+
+        // the same as building the edited sites with the same options
+        let expected = builder
+            .set_sites(voronoi.sites().clone())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(utils::test::geometry_eq(&voronoi, &expected));
+        assert_eq!(voronoi.build_warnings(), expected.build_warnings());
+        assert!(voronoi
+            .build_warnings()
+            .iter()
+            .any(|w| matches!(w, crate::BuildWarning::SimplifiedCell { .. })));
+    }
+
+    #[test]
+    fn edits_replace_warnings_and_stats() {
+        let mut sites = utils::test::random_sites(50);
+        sites.push(Point {
+            x: sites[0].x + 1e-4,
+            y: sites[0].y,
+        });
+        let mut voronoi = VoronoiBuilder::<crate::ConvexPolygon>::default()
+            .set_sites(sites)
+            .set_min_separation(1e-3, SeparationPolicy::DropLater)
+            .set_auto_boundary(crate::AutoBoundary::TightBox { padding: 0.1 })
+            .build()
+            .expect("Some voronoi expected");
+        assert!(matches!(
+            voronoi.build_warnings(),
+            [crate::BuildWarning::DroppedSite { site: 50, .. }]
+        ));
+        assert!(voronoi.build_stats().auto_boundary.is_some());
+
+        voronoi
+            .move_site(1, Point { x: 0.0, y: 0.0 })
+            .expect("Move expected to succeed");
+        assert!(voronoi.build_warnings().is_empty());
+        assert_eq!(voronoi.build_stats(), &BuildStats::default());
+    }
+
+    #[test]
+    fn edits_keep_min_separation() {
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(6)
+            .set_min_separation(0.1, SeparationPolicy::Average)
+            .build()
+            .expect("Some voronoi expected");
+        let generation = voronoi.generation();
+        let near = Point {
+            x: voronoi.sites()[8].x + 0.05,
+            y: voronoi.sites()[8].y,
+        };
+
+        match voronoi.move_site(7, near.clone()) {
+            Err(EditError::SitesTooClose(pairs)) => {
+                assert_eq!(pairs.len(), 1);
+                assert_eq!((pairs[0].0, pairs[0].1), (7, 8));
+            }
+            result => panic!("Sites too close expected, got {:?}.", result),
+        }
+        assert!(matches!(
+            voronoi.insert_site(near),
+            Err(EditError::SitesTooClose(_))
+        ));
+        assert_eq!(voronoi.generation(), generation);
+    }
+
+    #[test]
+    fn edits_keep_symmetry_of_symmetric_sites() {
+        let axis = SymmetryAxis::Vertical { x: 0.0 };
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(
+                utils::test::random_sites(100)
+                    .into_iter()
+                    .filter(|p| p.x < 0.0)
+                    .collect(),
+            )
+            .set_symmetry(axis, crate::WrongSideSites::Ignore)
+            .build()
+            .expect("Some voronoi expected");
+        let mirror = voronoi.mirror_of(4).expect("Mirror expected");
+        assert_ne!(mirror, 4);
+
+        // moving a site alone breaks the symmetry, moving its mirror as well restores it
+        voronoi
+            .move_site(4, Point { x: -0.5, y: 0.25 })
+            .expect("Move expected to succeed");
+        assert_eq!(voronoi.symmetry_axis(), None);
+        voronoi
+            .move_site(mirror, Point { x: 0.5, y: 0.25 })
+            .expect("Move expected to succeed");
+        assert_eq!(voronoi.symmetry_axis(), Some(axis));
+        assert_eq!(voronoi.mirror_of(4), Some(mirror));
+        for cell in voronoi.iter_cells() {
+            let mirrored = voronoi.cell(voronoi.mirror_of(cell.site()).unwrap());
+            for v in cell.iter_vertices() {
+                let r = axis.reflect(v);
+                assert!(mirrored.iter_vertices().any(|w| *w == r));
+            }
+        }
+    }
+
+    #[test]
+    fn trimmed_edits_are_forgotten() {
+        let mut voronoi = square_voronoi(6);
+        let built = voronoi.generation();
+        let mut generations = Vec::new();
+        for i in 0..10 {
+            voronoi
+                .move_site(
+                    7,
+                    Point {
+                        x: -0.1 - 0.01 * i as f64,
+                        y: 0.2,
+                    },
+                )
+                .expect("Move expected to succeed");
+            generations.push(voronoi.generation());
+        }
+
+        voronoi.trim_edits_before(generations[5]);
+        assert_eq!(voronoi.edits_since(built), None);
+        assert_eq!(voronoi.edits_since(generations[4]), None);
+        assert_eq!(voronoi.edits_since(generations[5]).map(<[_]>::len), Some(4));
+        assert_eq!(voronoi.edits_since(generations[7]).map(<[_]>::len), Some(2));
+        assert_eq!(voronoi.edit_log.len(), 4);
+
+        // unknown generations keep the log
+        voronoi.trim_edits_before(built);
+        assert_eq!(voronoi.edit_log.len(), 4);
+        voronoi.trim_edits_before(voronoi.generation());
+        assert!(voronoi.edit_log.is_empty());
+        assert_eq!(voronoi.edits_since(voronoi.generation()), Some(&[][..]));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{editing::Edit, incremental::VertexRemap, Point};
+
+/// Triangle fans of every cell of a diagram, as triplets of indices into [crate::Voronoi::vertices()], ready to be used as an index buffer.
+///
+/// Each cell is fanned from its first vertex: the triangles of a cell with vertices ```v0, v1, ..., vn``` are ```(v0, v1, v2), (v0, v2, v3), ..., (v0, vn-1, vn)```,
+/// in the same orientation as the cell. No extra vertex is added. Triangles with zero area, such as those with a repeated vertex
+/// or collinear vertices where an edge was clipped, are skipped.
+///
+/// Use [crate::Voronoi::fan_indices()] to obtain an instance of this type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanIndexCache {
+    triangles: Vec<[usize; 3]>,
+    offsets: Vec<usize>,
+    generation: u64,
+    cell_generations: Vec<u64>,
+}
+
+impl FanIndexCache {
+    /// Builds the fans of all ```cells```.
+    pub(crate) fn new(cells: &[Vec<usize>], vertices: &[Point], generation: u64) -> Self {
+        let mut cache = Self {
+            triangles: Vec::new(),
+            offsets: vec![0],
+            generation,
+            cell_generations: Vec::with_capacity(cells.len()),
+        };
+        for cell in cells {
+            cache.push_fan(cell, vertices, generation);
+        }
+
+        cache
+    }
+
+    /// Updates the fans of a diagram after ```edit``` rebuilt it, giving the fans of the cells reshaped by the edit, those whose ```cell_generations``` is ```generation```, the new generation.
+    ///
+    /// The other cells keep their fan generation. They keep their fan as well if they have the same vertex indices at the same positions as before the edit,
+    /// and have it fanned again over the new indices of their vertices otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn patch(
+        &self,
+        edit: &Edit,
+        old_cells: &[Vec<usize>],
+        old_vertices: &[Point],
+        cells: &[Vec<usize>],
+        vertices: &[Point],
+        cell_generations: &[u64],
+        generation: u64,
+    ) -> Self {
+        let mut cache = Self {
+            triangles: Vec::with_capacity(self.triangles.len()),
+            offsets: vec![0],
+            generation,
+            cell_generations: Vec::with_capacity(cells.len()),
+        };
+
+        for (site, cell) in cells.iter().enumerate() {
+            let old_site = match edit.backward(site) {
+                Some(old_site) if cell_generations[site] != generation => old_site,
+                _ => {
+                    cache.push_fan(cell, vertices, generation);
+                    continue;
+                }
+            };
+
+            let old_cell = &old_cells[old_site];
+            if old_cell == cell && cell.iter().all(|&v| old_vertices.get(v) == vertices.get(v)) {
+                cache
+                    .triangles
+                    .extend_from_slice(self.cell_triangles(old_site));
+                cache.offsets.push(cache.triangles.len());
+                cache.cell_generations.push(self.cell_generations[old_site]);
+            } else {
+                cache.push_fan(cell, vertices, self.cell_generations[old_site]);
+            }
+        }
+
+        cache
+    }
+
+    /// Updates the fans of a diagram after ```edit``` was applied in place, giving the fans of the cells reshaped by the edit, those whose ```cell_generations``` is ```generation```,
+    /// the new generation.
+    ///
+    /// The other cells keep their fan generation. The cells ```rebuilt```, given in increasing order, have their fan calculated again over the new indices of their vertices,
+    /// and the others keep their fan, with the indices of its vertices mapped by ```remap```.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn remap(
+        &self,
+        edit: &Edit,
+        cells: &[Vec<usize>],
+        vertices: &[Point],
+        cell_generations: &[u64],
+        rebuilt: &[usize],
+        remap: &VertexRemap,
+        generation: u64,
+    ) -> Self {
+        let mut cache = Self {
+            triangles: Vec::with_capacity(self.triangles.len()),
+            offsets: vec![0],
+            generation,
+            cell_generations: Vec::with_capacity(cells.len()),
+        };
+
+        let mut rebuilt = rebuilt.iter().peekable();
+        for (site, cell) in cells.iter().enumerate() {
+            let was_rebuilt = rebuilt.next_if_eq(&&site).is_some();
+            let old_site = match edit.backward(site) {
+                Some(old_site) if cell_generations[site] != generation => old_site,
+                _ => {
+                    cache.push_fan(cell, vertices, generation);
+                    continue;
+                }
+            };
+
+            let old = self.cell_triangles(old_site);
+            if was_rebuilt {
+                cache.push_fan(cell, vertices, self.cell_generations[old_site]);
+                continue;
+            }
+            if remap.is_identity() {
+                cache.triangles.extend_from_slice(old);
+            } else {
+                cache
+                    .triangles
+                    .extend(old.iter().map(|t| t.map(|v| remap.map(v))));
+            }
+            cache.offsets.push(cache.triangles.len());
+            cache.cell_generations.push(self.cell_generations[old_site]);
+        }
+
+        cache
+    }
+
+    fn push_fan(&mut self, cell: &[usize], vertices: &[Point], generation: u64) {
+        if let Some((&first, rest)) = cell.split_first() {
+            let triangles = rest
+                .iter()
+                .zip(rest.iter().skip(1))
+                .map(|(&b, &c)| [first, b, c])
+                .filter(|&[a, b, c]| {
+                    a != b
+                        && b != c
+                        && c != a
+                        && robust::orient2d(
+                            robust::Coord {
+                                x: vertices[a].x,
+                                y: vertices[a].y,
+                            },
+                            robust::Coord {
+                                x: vertices[b].x,
+                                y: vertices[b].y,
+                            },
+                            robust::Coord {
+                                x: vertices[c].x,
+                                y: vertices[c].y,
+                            },
+                        ) != 0.0
+                });
+            self.triangles.extend(triangles);
+        }
+
+        self.offsets.push(self.triangles.len());
+        self.cell_generations.push(generation);
+    }
+
+    /// Gets the total number of triangles of all cells.
+    #[inline]
+    pub fn total_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Gets the triangles of all cells, in site order.
+    #[inline]
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Gets the triangles of the cell of ```site```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is out of range.
+    #[inline]
+    pub fn cell_triangles(&self, site: usize) -> &[[usize; 3]] {
+        &self.triangles[self.offsets[site]..self.offsets[site + 1]]
+    }
+
+    /// Gets the range of [Self::triangles()] with the triangles of the cell of ```site```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is out of range.
+    #[inline]
+    pub fn cell_range(&self, site: usize) -> std::ops::Range<usize> {
+        self.offsets[site]..self.offsets[site + 1]
+    }
+
+    /// Gets the generation of the diagram these fans belong to. See [crate::Voronoi::generation()].
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Gets the generation in which the fan of the cell of ```site``` last changed shape, or the generation of the fans if the cell has not been reshaped since they were built.
+    ///
+    /// Fans are only recalculated for the cells reshaped when the diagram is edited, as given by [crate::Voronoi::cell_generation()], so renderers need to
+    /// process only those cells again. The triangles of the other cells cover the same positions, although the indices of their vertices follow any change
+    /// to [crate::Voronoi::vertices()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is out of range.
+    #[inline]
+    pub fn cell_generation(&self, site: usize) -> u64 {
+        self.cell_generations[site]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_voronoi, ClipBehavior, VoronoiBuilder};
+
+    fn triangle_area(vertices: &[Point], [a, b, c]: [usize; 3]) -> f64 {
+        let (a, b, c) = (&vertices[a], &vertices[b], &vertices[c]);
+        ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / 2.0
+    }
+
+    #[test]
+    fn triangle_areas_add_up_to_cell_areas() {
+        let voronoi = random_voronoi(200);
+        let fans = voronoi.fan_indices();
+        assert_eq!(fans.generation(), voronoi.generation());
+
+        let mut total = 0;
+        for cell in voronoi.iter_cells() {
+            let triangles = fans.cell_triangles(cell.site());
+            total += triangles.len();
+            assert!(triangles.len() <= cell.triangles().len() - 2);
+
+            // all triangles have the orientation of the cell
+            let areas: Vec<f64> = triangles
+                .iter()
+                .map(|&t| triangle_area(voronoi.vertices(), t))
+                .collect();
+            let sign = areas[0].signum();
+            assert!(areas.iter().all(|a| a.signum() == sign));
+            let area: f64 = areas.iter().sum();
+            assert!((area.abs() - cell.area()).abs() < 1e-12);
+        }
+        assert_eq!(fans.total_triangles(), total);
+        assert_eq!(fans.triangles().len(), total);
+    }
+
+    #[test]
+    fn move_site_recalculates_only_reshaped_cells() {
+        let mut voronoi = random_voronoi(200);
+        let built = voronoi.fan_indices().clone();
+
+        let site = 100;
+        let neighbors: Vec<usize> = voronoi.cell(site).iter_neighbors().collect();
+        let position = Point {
+            x: voronoi.sites()[site].x + 0.01,
+            y: voronoi.sites()[site].y,
+        };
+        voronoi
+            .move_site(site, position)
+            .expect("Move expected to succeed");
+
+        let fans = voronoi.fan_indices();
+        assert_eq!(
+            fans,
+            &FanIndexCache::new(voronoi.cells(), voronoi.vertices(), voronoi.generation())
+                .with_generations(fans)
+        );
+
+        let recalculated: Vec<usize> = (0..voronoi.sites().len())
+            .filter(|&s| fans.cell_generation(s) == voronoi.generation())
+            .collect();
+        let reshaped: Vec<usize> = (0..voronoi.sites().len())
+            .filter(|&s| voronoi.cell_generation(s) == voronoi.generation())
+            .collect();
+        assert_eq!(recalculated, reshaped);
+        assert!(recalculated.contains(&site));
+        assert!(neighbors.iter().all(|n| recalculated.contains(n)));
+        for s in 0..voronoi.sites().len() {
+            if !recalculated.contains(&s) {
+                assert_eq!(fans.cell_generation(s), built.cell_generation(s));
+            }
+        }
+    }
+
+    #[test]
+    fn other_edits_keep_fans_of_shifted_cells() {
+        // edited in place, then rebuilt as the cells are not clipped
+        for &clip in &[true, false] {
+            let mut voronoi = random_voronoi(100);
+            if !clip {
+                voronoi = VoronoiBuilder::default()
+                    .set_sites(voronoi.sites().clone())
+                    .set_clip_behavior(ClipBehavior::RemoveSitesOutsideBoundaryOnly)
+                    .build()
+                    .expect("Some voronoi expected");
+            }
+            let built = voronoi.fan_indices().generation();
+            voronoi.remove_site(10).expect("Remove expected to succeed");
+            voronoi
+                .insert_site(Point { x: 0.25, y: 0.5 })
+                .expect("Insert expected to succeed");
+            voronoi
+                .merge_sites(3, 4)
+                .expect("Merge expected to succeed");
+
+            let fans = voronoi.fan_indices();
+            assert_eq!(
+                fans,
+                &FanIndexCache::new(voronoi.cells(), voronoi.vertices(), voronoi.generation())
+                    .with_generations(fans)
+            );
+            for site in 0..voronoi.sites().len() {
+                assert_eq!(
+                    fans.cell_generation(site),
+                    voronoi.cell_generation(site).max(built)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_first_access_builds_one_cache() {
+        let voronoi = random_voronoi(1_000);
+        let fans: Vec<&FanIndexCache> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| voronoi.fan_indices()))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+
+        assert!(fans.iter().all(|&f| std::ptr::eq(f, fans[0])));
+        assert_eq!(
+            fans[0],
+            &FanIndexCache::new(voronoi.cells(), voronoi.vertices(), voronoi.generation())
+        );
+    }
+
+    impl FanIndexCache {
+        /// Copies the cell generations of ```other```, which may have been patched, to compare the fans themselves.
+        fn with_generations(mut self, other: &FanIndexCache) -> Self {
+            self.cell_generations = other.cell_generations.clone();
+            self
+        }
+    }
+}
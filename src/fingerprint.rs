@@ -0,0 +1,182 @@
+use std::hash::Hasher;
+
+use crate::Point;
+
+/// Multiplier of the FxHash mixing step, an odd constant spreading each word over the upper bits.
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// A fast, non-cryptographic hasher for fingerprints of the inputs of a diagram.
+///
+/// Each 64-bit word is mixed into the state with a rotation, an exclusive or and a multiplication by an odd constant.
+/// Every step is a bijection of the state, so changing any single word, e.g. a single bit of a coordinate, always changes the result.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FingerprintHasher {
+    hash: u64,
+}
+
+impl Hasher for FingerprintHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u64(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+
+    #[inline]
+    fn write_u8(&mut self, byte: u8) {
+        self.write_u64(byte as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        // the final mix of splitmix64, also a bijection, so low bits depend on every word
+        let mut z = self.hash;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Hashes the bit patterns of the coordinates of ```p```.
+#[inline]
+pub(crate) fn hash_point<H: Hasher>(p: &Point, state: &mut H) {
+    state.write_u64(p.x.to_bits());
+    state.write_u64(p.y.to_bits());
+}
+
+/// Hashes the number of points and the bit patterns of their coordinates.
+pub(crate) fn hash_points<H: Hasher>(points: &[Point], state: &mut H) {
+    state.write_usize(points.len());
+    points.iter().for_each(|p| hash_point(p, state));
+}
+
+/// Returns whether both slices have the same points, with the same bit patterns.
+pub(crate) fn bits_eq(a: &[Point], b: &[Point]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(p, q)| p.x.to_bits() == q.x.to_bits() && p.y.to_bits() == q.y.to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test::random_sites, BoundingBox, ClipBehavior, ConvexBoundary, ConvexPolygon,
+        VoronoiBuilder,
+    };
+
+    /// Counts the words written, to check points are only hashed once.
+    #[derive(Default)]
+    struct CountingHasher {
+        inner: FingerprintHasher,
+        writes: usize,
+    }
+
+    impl Hasher for CountingHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            self.writes += 1;
+            self.inner.write(bytes);
+        }
+
+        fn write_u64(&mut self, word: u64) {
+            self.writes += 1;
+            self.inner.write_u64(word);
+        }
+
+        fn finish(&self) -> u64 {
+            self.inner.finish()
+        }
+    }
+
+    fn assert_fingerprint_kept<T: ConvexBoundary>(builder: VoronoiBuilder<T>) {
+        let fingerprint = builder.fingerprint();
+        let voronoi = builder.build().expect("Some voronoi expected");
+        assert_eq!(voronoi.input_fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn fingerprint_is_the_same_before_and_after_building() {
+        let sites = random_sites(500);
+        let builder = || VoronoiBuilder::<BoundingBox>::default().set_sites(sites.clone());
+        assert_eq!(builder().fingerprint(), builder().fingerprint());
+
+        assert_fingerprint_kept(builder());
+        // sites outside of the boundary are removed when building, but are part of the input
+        assert_fingerprint_kept(builder().set_boundary(BoundingBox::new_centered_square(1.0)));
+        assert_fingerprint_kept(builder().set_clip_behavior(ClipBehavior::None));
+        assert_fingerprint_kept(builder().set_lloyd_relaxation_iterations(2));
+        assert_fingerprint_kept(
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_boundary(ConvexPolygon::regular(7, 1.0)),
+        );
+        assert_fingerprint_kept(
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_auto_boundary(crate::AutoBoundary::TightBox { padding: 0.1 }),
+        );
+    }
+
+    #[test]
+    fn single_bit_changes_alter_fingerprint() {
+        let sites = random_sites(100);
+        let fingerprint = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .fingerprint();
+
+        for site in [0, 37, 99] {
+            for bit in [0, 31, 63] {
+                let mut changed = sites.clone();
+                changed[site].y = f64::from_bits(changed[site].y.to_bits() ^ (1 << bit));
+                let changed = VoronoiBuilder::<BoundingBox>::default()
+                    .set_sites(changed)
+                    .fingerprint();
+                assert_ne!(changed, fingerprint, "Site {}, bit {}.", site, bit);
+            }
+        }
+
+        let configurations = [
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_boundary(BoundingBox::new_centered_square(2.5)),
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_clip_behavior(ClipBehavior::RemoveSitesOutsideBoundaryOnly),
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_lloyd_relaxation_iterations(1),
+            VoronoiBuilder::default().set_sites(sites[..99].to_vec()),
+        ];
+        for builder in configurations {
+            assert_ne!(builder.fingerprint(), fingerprint);
+        }
+    }
+
+    #[test]
+    fn hashing_is_a_single_pass_over_sites() {
+        let hashed_words = |size: usize| {
+            let mut hasher = CountingHasher::default();
+            VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(random_sites(size))
+                .hash_input(&mut hasher);
+            hasher.writes
+        };
+
+        // each site is hashed once, as its two coordinates
+        let configuration = hashed_words(0);
+        for size in [1, 10, 10_000] {
+            assert_eq!(hashed_words(size), configuration + 2 * size);
+        }
+    }
+}
@@ -0,0 +1,535 @@
+//! Small diagrams whose geometry is known exactly, for testing code that builds on this crate.
+//!
+//! Each fixture returns the diagram built by this crate along with its [ExpectedGeometry]. The expected geometry is derived analytically from the
+//! placement of the sites, without running any of the algorithms of this crate, so asserting a diagram against it also detects regressions of the crate itself.
+//!
+//! Available with the ```test-utils``` feature.
+//!
+//! # Examples
+//!
+//!```
+//! use voronoice::fixtures;
+//! let (voronoi, expected) = fixtures::square_of_four_sites();
+//! expected.assert_matches(&voronoi, 1e-12);
+//! assert_eq!(expected.areas, vec![1.0; 4]);
+//!```
+
+use std::f64::consts::PI;
+
+use crate::{BoundingBox, ConvexBoundary, ConvexPolygon, Point, Voronoi, VoronoiBuilder};
+
+/// The exact geometry of the cells of a fixture, in the order of its sites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedGeometry {
+    /// The vertices of each cell, in counter-clockwise order from any vertex, each vertex once.
+    pub vertices: Vec<Vec<Point>>,
+
+    /// The area of each cell.
+    pub areas: Vec<f64>,
+
+    /// The neighbors of each cell, sorted, as reported by [crate::VoronoiCell::iter_neighbors()].
+    pub neighbors: Vec<Vec<usize>>,
+}
+
+impl ExpectedGeometry {
+    /// Asserts that the cells of ```voronoi``` have the expected geometry, within an absolute tolerance of ```epsilon``` on each coordinate and area.
+    ///
+    /// Vertices are compared in counter-clockwise order, but the comparison may start at any vertex. Cells repeat a vertex where more than three sites are
+    /// cocircular, so consecutive vertices within the tolerance of each other are compared once.
+    ///
+    /// # Panics
+    ///
+    /// Panics, describing the first cell that differs, if the geometry of ```voronoi``` is not the one expected.
+    pub fn assert_matches<T: ConvexBoundary>(&self, voronoi: &Voronoi<T>, epsilon: f64) {
+        assert_eq!(
+            voronoi.sites().len(),
+            self.vertices.len(),
+            "Diagram has {} sites, {} expected.",
+            voronoi.sites().len(),
+            self.vertices.len()
+        );
+
+        let close =
+            |p: &Point, q: &Point| (p.x - q.x).abs() <= epsilon && (p.y - q.y).abs() <= epsilon;
+        for cell in voronoi.iter_cells() {
+            let site = cell.site();
+            let mut vertices: Vec<Point> = Vec::new();
+            for v in cell.iter_vertices() {
+                if !vertices.last().is_some_and(|last| close(last, v)) {
+                    vertices.push(v.clone());
+                }
+            }
+            while vertices.len() > 1 && close(&vertices[0], &vertices[vertices.len() - 1]) {
+                vertices.pop();
+            }
+
+            let expected = &self.vertices[site];
+            let n = expected.len();
+            let same = vertices.len() == n
+                && (0..n.max(1))
+                    .any(|start| (0..n).all(|i| close(&vertices[(start + i) % n], &expected[i])));
+            assert!(
+                same,
+                "Cell {}: vertices {:?}, {:?} expected.",
+                site, vertices, expected
+            );
+
+            let area = cell.area();
+            assert!(
+                (area - self.areas[site]).abs() <= epsilon,
+                "Cell {}: area {}, {} expected.",
+                site,
+                area,
+                self.areas[site]
+            );
+
+            let mut neighbors: Vec<usize> = cell.iter_neighbors().collect();
+            neighbors.sort_unstable();
+            assert_eq!(
+                neighbors, self.neighbors[site],
+                "Cell {}: neighbors {:?}, {:?} expected.",
+                site, neighbors, self.neighbors[site]
+            );
+        }
+    }
+}
+
+/// Three sites, at ```(-0.5, 0)```, ```(0.5, 0)``` and ```(0, 0.5)```, in the default [BoundingBox], from ```-1``` to ```1``` on both axes.
+///
+/// The cells meet at the origin: the first two share the edge along ```x = 0``` above it, and the third cell is the triangle below both diagonal bisectors,
+/// which end at the corners of the box.
+pub fn three_sites_meeting_at_center() -> (Voronoi<BoundingBox>, ExpectedGeometry) {
+    let voronoi = build(
+        vec![point(-0.5, 0.0), point(0.5, 0.0), point(0.0, 0.5)],
+        BoundingBox::default(),
+    );
+
+    // the bisectors of the third site and either of the first two are y = -x and y = x
+    let expected = ExpectedGeometry {
+        vertices: vec![
+            vec![
+                point(0.0, 0.0),
+                point(0.0, -1.0),
+                point(-1.0, -1.0),
+                point(-1.0, 1.0),
+            ],
+            vec![
+                point(0.0, 0.0),
+                point(1.0, 1.0),
+                point(1.0, -1.0),
+                point(0.0, -1.0),
+            ],
+            vec![point(0.0, 0.0), point(-1.0, 1.0), point(1.0, 1.0)],
+        ],
+        areas: vec![1.5, 1.5, 1.0],
+        neighbors: vec![vec![1, 2], vec![0, 2], vec![0, 1]],
+    };
+
+    (voronoi, expected)
+}
+
+/// Four cocircular sites, at ```(±0.5, ±0.5)```, splitting the default [BoundingBox] into its quarters.
+///
+/// Sites are ordered counter-clockwise from the top left one. Cells of opposite sites only touch at the origin, so they are not neighbors.
+pub fn square_of_four_sites() -> (Voronoi<BoundingBox>, ExpectedGeometry) {
+    let quarters = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    let voronoi = build(
+        quarters
+            .iter()
+            .map(|&(sx, sy)| point(0.5 * sx, 0.5 * sy))
+            .collect(),
+        BoundingBox::default(),
+    );
+
+    // each quarter goes from the origin along the X-axis to the corner and back along the Y-axis,
+    // which is counter-clockwise in the top right and bottom left quarters, and clockwise in the others
+    let vertices = quarters
+        .iter()
+        .map(|&(sx, sy)| {
+            let mut quarter = vec![
+                point(0.0, 0.0),
+                point(sx, 0.0),
+                point(sx, sy),
+                point(0.0, sy),
+            ];
+            if sx == sy {
+                quarter[1..].reverse();
+            }
+            quarter
+        })
+        .collect();
+    let expected = ExpectedGeometry {
+        vertices,
+        areas: vec![1.0; 4],
+        neighbors: vec![vec![1, 3], vec![0, 2], vec![1, 3], vec![0, 2]],
+    };
+
+    (voronoi, expected)
+}
+
+/// Five sites forming a plus sign in the default [BoundingBox]: ```(-0.5, 0)```, ```(0.5, 0)```, the origin, ```(0, 0.5)``` and ```(0, -0.5)```, in this order.
+///
+/// The cell of the center is the square of side 0.5 around it, and each of the other cells is the trapezoid between it and a side of the box,
+/// bounded by the diagonals of the box.
+pub fn plus_of_five_sites() -> (Voronoi<BoundingBox>, ExpectedGeometry) {
+    let voronoi = build(
+        vec![
+            point(-0.5, 0.0),
+            point(0.5, 0.0),
+            point(0.0, 0.0),
+            point(0.0, 0.5),
+            point(0.0, -0.5),
+        ],
+        BoundingBox::default(),
+    );
+
+    let expected = ExpectedGeometry {
+        vertices: vec![
+            vec![
+                point(-0.25, 0.25),
+                point(-0.25, -0.25),
+                point(-1.0, -1.0),
+                point(-1.0, 1.0),
+            ],
+            vec![
+                point(0.25, -0.25),
+                point(0.25, 0.25),
+                point(1.0, 1.0),
+                point(1.0, -1.0),
+            ],
+            vec![
+                point(-0.25, -0.25),
+                point(-0.25, 0.25),
+                point(0.25, 0.25),
+                point(0.25, -0.25),
+            ],
+            vec![
+                point(0.25, 0.25),
+                point(-0.25, 0.25),
+                point(-1.0, 1.0),
+                point(1.0, 1.0),
+            ],
+            vec![
+                point(-0.25, -0.25),
+                point(0.25, -0.25),
+                point(1.0, -1.0),
+                point(-1.0, -1.0),
+            ],
+        ],
+        // a trapezoid of parallel sides 0.5 and 2 at a distance of 0.75
+        areas: vec![0.9375, 0.9375, 0.25, 0.9375, 0.9375],
+        neighbors: vec![
+            vec![2, 3, 4],
+            vec![2, 3, 4],
+            vec![0, 1, 3, 4],
+            vec![0, 1, 2],
+            vec![0, 1, 2],
+        ],
+    };
+
+    (voronoi, expected)
+}
+
+/// The origin and ```n``` sites around it at a distance of 0.4, in the regular polygon of ```n``` vertices at a distance of 1 from the origin
+/// whose vertices are on the bisectors of consecutive sites.
+///
+/// The origin is the first site, and the others start on the positive X axis and are ordered counter-clockwise. The cell of the origin is a regular polygon
+/// and the others are the trapezoids between it and the edges of the boundary.
+///
+/// # Panics
+///
+/// Panics if ```n``` is less than 3.
+pub fn regular_ngon_sites(n: usize) -> (Voronoi<ConvexPolygon>, ExpectedGeometry) {
+    assert!(
+        n >= 3,
+        "At least 3 sites are needed around the center, got {}.",
+        n
+    );
+
+    // with the Y-axis pointing downwards, counter-clockwise is towards decreasing angles
+    let radius = 0.4;
+    let half_angle = PI / n as f64;
+    let direction = |angle: f64, length: f64| point(length * angle.cos(), length * angle.sin());
+    let site_angle = |k: usize| -2.0 * half_angle * k as f64;
+
+    // the bisectors of consecutive sites go through the origin, at a distance of radius / 2 from the sites along the edges of the cell of the origin
+    let inner = 0.5 * radius / half_angle.cos();
+    let boundary = ConvexPolygon::new(
+        (0..n)
+            .map(|k| direction(site_angle(k) - half_angle, 1.0))
+            .collect(),
+    );
+    let mut sites = vec![point(0.0, 0.0)];
+    sites.extend((0..n).map(|k| direction(site_angle(k), radius)));
+    let voronoi = build(sites, boundary);
+
+    let mut vertices = vec![(0..n)
+        .map(|k| direction(site_angle(k) - half_angle, inner))
+        .collect()];
+    vertices.extend((0..n).map(|k| {
+        let (before, after) = (site_angle(k) + half_angle, site_angle(k) - half_angle);
+        vec![
+            direction(before, inner),
+            direction(before, 1.0),
+            direction(after, 1.0),
+            direction(after, inner),
+        ]
+    }));
+
+    // the polygons are made of n triangles of two sides of the same length, at an angle of 2π / n
+    let triangle = |length: f64| 0.5 * length * length * (2.0 * half_angle).sin();
+    let mut areas = vec![n as f64 * triangle(inner)];
+    areas.extend((0..n).map(|_| triangle(1.0) - triangle(inner)));
+
+    let mut neighbors: Vec<Vec<usize>> = vec![(1..=n).collect()];
+    neighbors.extend((0..n).map(|k| {
+        let mut around = vec![0, 1 + (k + 1) % n, 1 + (k + n - 1) % n];
+        around.sort_unstable();
+        around.dedup();
+        around
+    }));
+
+    (
+        voronoi,
+        ExpectedGeometry {
+            vertices,
+            areas,
+            neighbors,
+        },
+    )
+}
+
+/// A hexagonal lattice of ```cols``` sites per row and ```rows``` rows, at a distance of 1 from their neighbors, with odd rows shifted right by half the distance.
+///
+/// Sites are ordered by row, then by column, starting at the origin, with rows ```√3 / 2``` apart downwards. Cells are the regular hexagons of the lattice,
+/// pointing up and down, clipped by the box from ```0``` to ```cols - 0.5``` on the X-axis and from the middle of the top edges of the hexagons
+/// of the first row to that of the bottom edges of the last row on the Y-axis. This box is fully tiled by the hexagons, so no cell extends behind its hexagon.
+/// The first site of even rows and the last site of odd rows are on the boundary.
+///
+/// # Panics
+///
+/// Panics if ```cols``` or ```rows``` is less than 2.
+pub fn hex_lattice(cols: usize, rows: usize) -> (Voronoi<BoundingBox>, ExpectedGeometry) {
+    assert!(
+        cols >= 2 && rows >= 2,
+        "At least 2 rows of 2 sites are needed, got {} by {}.",
+        cols,
+        rows
+    );
+
+    // the circumradius of the hexagons, and the distance between rows
+    let circumradius = 1.0 / 3f64.sqrt();
+    let row_height = 1.5 * circumradius;
+    let sites: Vec<Point> = (0..rows)
+        .flat_map(|r| {
+            (0..cols).map(move |c| point(c as f64 + 0.5 * (r % 2) as f64, r as f64 * row_height))
+        })
+        .collect();
+    let (left, top, right, bottom) = (
+        0.0,
+        -0.5 * circumradius,
+        cols as f64 - 0.5,
+        (rows - 1) as f64 * row_height + 0.5 * circumradius,
+    );
+    let voronoi = build(
+        sites.clone(),
+        BoundingBox::from_edges(left, top, right, bottom),
+    );
+
+    let inside = [
+        (1.0, 0.0, left),
+        (0.0, 1.0, top),
+        (-1.0, 0.0, -right),
+        (0.0, -1.0, -bottom),
+    ];
+    let vertices: Vec<Vec<Point>> = sites
+        .iter()
+        .map(|s| {
+            // counter-clockwise from the top vertex
+            let hexagon = vec![
+                point(s.x, s.y - circumradius),
+                point(s.x - 0.5, s.y - 0.5 * circumradius),
+                point(s.x - 0.5, s.y + 0.5 * circumradius),
+                point(s.x, s.y + circumradius),
+                point(s.x + 0.5, s.y + 0.5 * circumradius),
+                point(s.x + 0.5, s.y - 0.5 * circumradius),
+            ];
+            inside
+                .iter()
+                .fold(hexagon, |polygon, &(a, b, c)| clip(&polygon, a, b, c))
+        })
+        .collect();
+    let areas = vertices
+        .iter()
+        .map(|polygon| polygon_area(polygon))
+        .collect();
+
+    // lattice neighbors share an edge of the length of the circumradius, perpendicular to them at their midpoint, which may be partially outside of the box
+    let neighbors = sites
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            sites
+                .iter()
+                .enumerate()
+                .filter(|&(j, b)| {
+                    let (dx, dy) = (b.x - a.x, b.y - a.y);
+                    if j == i || (dx * dx + dy * dy - 1.0).abs() > 1e-9 {
+                        return false;
+                    }
+                    let middle = point(0.5 * (a.x + b.x), 0.5 * (a.y + b.y));
+                    let half = 0.5 * circumradius;
+                    let edge = vec![
+                        point(middle.x - half * dy, middle.y + half * dx),
+                        point(middle.x + half * dy, middle.y - half * dx),
+                    ];
+                    let edge = inside
+                        .iter()
+                        .fold(edge, |segment, &(a, b, c)| clip_segment(&segment, a, b, c));
+                    edge.len() == 2
+                        && (edge[0].x - edge[1].x).abs() + (edge[0].y - edge[1].y).abs() > 0.0
+                })
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect();
+
+    (
+        voronoi,
+        ExpectedGeometry {
+            vertices,
+            areas,
+            neighbors,
+        },
+    )
+}
+
+fn build<T: ConvexBoundary>(sites: Vec<Point>, boundary: T) -> Voronoi<T> {
+    VoronoiBuilder::default()
+        .set_sites(sites)
+        .set_boundary(boundary)
+        .build()
+        .expect("Fixture sites expected to generate a diagram")
+}
+
+#[inline]
+fn point(x: f64, y: f64) -> Point {
+    Point { x, y }
+}
+
+/// Clips ```polygon``` to the half-plane ```a x + b y >= c```, keeping the order of its vertices and each vertex once.
+fn clip(polygon: &[Point], a: f64, b: f64, c: f64) -> Vec<Point> {
+    let side = |p: &Point| a * p.x + b * p.y - c;
+    let mut clipped: Vec<Point> = Vec::with_capacity(polygon.len() + 1);
+    let mut push = |p: Point| {
+        if clipped.last() != Some(&p) {
+            clipped.push(p);
+        }
+    };
+    for (p, q) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (sp, sq) = (side(p), side(q));
+        if sp >= 0.0 {
+            push(p.clone());
+        }
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            let t = sp / (sp - sq);
+            push(point(p.x + t * (q.x - p.x), p.y + t * (q.y - p.y)));
+        }
+    }
+    if clipped.len() > 1 && clipped.first() == clipped.last() {
+        clipped.pop();
+    }
+    clipped
+}
+
+/// Clips the segment ```segment``` to the half-plane ```a x + b y >= c```, or returns no point if it is outside of it.
+fn clip_segment(segment: &[Point], a: f64, b: f64, c: f64) -> Vec<Point> {
+    if segment.len() != 2 {
+        return Vec::new();
+    }
+    let side = |p: &Point| a * p.x + b * p.y - c;
+    let (p, q) = (&segment[0], &segment[1]);
+    let (sp, sq) = (side(p), side(q));
+    let cut = || {
+        let t = sp / (sp - sq);
+        point(p.x + t * (q.x - p.x), p.y + t * (q.y - p.y))
+    };
+    match (sp >= 0.0, sq >= 0.0) {
+        (true, true) => vec![p.clone(), q.clone()],
+        (true, false) => vec![p.clone(), cut()],
+        (false, true) => vec![cut(), q.clone()],
+        (false, false) => Vec::new(),
+    }
+}
+
+/// Gets the area of a polygon whose vertices are counter-clockwise, with the Y-axis pointing downwards.
+fn polygon_area(polygon: &[Point]) -> f64 {
+    0.5 * polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(p, q)| q.x * p.y - p.x * q.y)
+        .sum::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_match_their_expected_geometry() {
+        let (voronoi, expected) = three_sites_meeting_at_center();
+        expected.assert_matches(&voronoi, 1e-12);
+        let (voronoi, expected) = square_of_four_sites();
+        expected.assert_matches(&voronoi, 1e-12);
+        let (voronoi, expected) = plus_of_five_sites();
+        expected.assert_matches(&voronoi, 1e-12);
+        for n in 3..12 {
+            let (voronoi, expected) = regular_ngon_sites(n);
+            expected.assert_matches(&voronoi, 1e-9);
+        }
+        for &(cols, rows) in &[(2, 2), (3, 2), (5, 4), (8, 7)] {
+            let (voronoi, expected) = hex_lattice(cols, rows);
+            expected.assert_matches(&voronoi, 1e-9);
+        }
+    }
+
+    #[test]
+    fn expected_geometry_is_consistent() {
+        let fixtures = vec![
+            three_sites_meeting_at_center().1,
+            square_of_four_sites().1,
+            plus_of_five_sites().1,
+            hex_lattice(5, 4).1,
+        ];
+        for expected in fixtures
+            .into_iter()
+            .chain((3..8).map(|n| regular_ngon_sites(n).1))
+        {
+            // the areas are those of the counter-clockwise polygons
+            for (vertices, &area) in expected.vertices.iter().zip(expected.areas.iter()) {
+                assert!((polygon_area(vertices) - area).abs() < 1e-12);
+                assert!(area > 0.0);
+            }
+
+            // neighbors are mutual
+            for (site, neighbors) in expected.neighbors.iter().enumerate() {
+                for &neighbor in neighbors {
+                    assert!(expected.neighbors[neighbor].contains(&site));
+                }
+            }
+        }
+
+        // the hexagons tile the box, 4.5 wide and 5.5 half circumradii high
+        let (_, hexagons) = hex_lattice(5, 4);
+        let total: f64 = hexagons.areas.iter().sum();
+        assert!((total - 4.5 * 5.5 / 3f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell 1: area")]
+    fn mismatches_are_reported() {
+        let (voronoi, mut expected) = three_sites_meeting_at_center();
+        expected.areas[1] = 1.4;
+        expected.assert_matches(&voronoi, 1e-12);
+    }
+}
@@ -0,0 +1,331 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::{utils::next_up, ConvexBoundary, Voronoi};
+
+/// Defines which cells are allowed to drain out of the diagram when computing flow directions.
+///
+/// See [Voronoi::flow_directions_with_outlets()].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FlowOutlet {
+    /// Every cell on the hull of the diagram is an outlet and drains out through the boundary.
+    /// Interior cells without a strictly lower neighbor are local minima.
+    #[default]
+    Hull,
+
+    /// Hull cells are routed like any other cell, so only local minima (cells without a strictly lower neighbor) are outlets.
+    LocalMinima,
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Calculates, for each cell, the neighboring cell it drains to given an ```elevation``` value per site.
+    ///
+    /// This is the same as calling [Self::flow_directions_with_outlets()] with [FlowOutlet::Hull].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```elevation``` does not have one value per site.
+    pub fn flow_directions(&self, elevation: &[f64]) -> Vec<Option<usize>> {
+        self.flow_directions_with_outlets(elevation, FlowOutlet::default())
+    }
+
+    /// Calculates, for each cell, the neighboring cell it drains to given an ```elevation``` value per site.
+    ///
+    /// Each cell drains to its lowest neighbor (as returned by [crate::VoronoiCell::iter_neighbors()]), as long as that neighbor is strictly lower than the cell itself.
+    /// Ties between equally low neighbors are resolved in favor of the lowest site index.
+    /// ```None``` is returned for outlets, as defined by ```outlet```, and for local minima.
+    ///
+    /// Because water only ever flows to a strictly lower cell, the resulting drainage graph is a forest and cannot contain cycles.
+    /// Use [Self::fill_depressions()] beforehand to guarantee that every interior cell drains towards the boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```elevation``` does not have one value per site.
+    pub fn flow_directions_with_outlets(
+        &self,
+        elevation: &[f64],
+        outlet: FlowOutlet,
+    ) -> Vec<Option<usize>> {
+        assert_eq!(
+            elevation.len(),
+            self.sites.len(),
+            "One elevation value per site is expected."
+        );
+
+        self.iter_cells()
+            .map(|cell| {
+                if outlet == FlowOutlet::Hull && cell.is_on_hull() {
+                    return None;
+                }
+
+                let site = cell.site();
+                let mut lowest: Option<usize> = None;
+                for neighbor in cell.iter_neighbors() {
+                    if elevation[neighbor] >= elevation[site] {
+                        continue;
+                    }
+
+                    lowest = match lowest {
+                        Some(l)
+                            if elevation[l] < elevation[neighbor]
+                                || (elevation[l] == elevation[neighbor] && l < neighbor) =>
+                        {
+                            Some(l)
+                        }
+                        _ => Some(neighbor),
+                    };
+                }
+
+                lowest
+            })
+            .collect()
+    }
+
+    /// Calculates the flow accumulation of each cell, i.e. the number of cells that drain through it, including itself.
+    ///
+    /// ```directions``` is the drainage forest returned by [Self::flow_directions()].
+    /// Cells are processed in topological order, from the sources down to the outlets, so each cell is visited once.
+    /// The accumulation values of all outlets (cells whose direction is ```None```) add up to the number of cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```directions``` does not have one value per site, or if it contains a cycle.
+    pub fn flow_accumulation(&self, directions: &[Option<usize>]) -> Vec<u32> {
+        assert_eq!(
+            directions.len(),
+            self.sites.len(),
+            "One flow direction per site is expected."
+        );
+
+        let mut upstream_count = vec![0_usize; directions.len()];
+        for &d in directions.iter().flatten() {
+            upstream_count[d] += 1;
+        }
+
+        // start from the sources, cells that nothing drains into
+        let mut stack: Vec<usize> = (0..directions.len())
+            .filter(|&c| upstream_count[c] == 0)
+            .collect();
+        let mut accumulation = vec![1_u32; directions.len()];
+        let mut processed = 0;
+
+        while let Some(cell) = stack.pop() {
+            processed += 1;
+            if let Some(downstream) = directions[cell] {
+                accumulation[downstream] += accumulation[cell];
+                upstream_count[downstream] -= 1;
+                if upstream_count[downstream] == 0 {
+                    stack.push(downstream);
+                }
+            }
+        }
+
+        assert_eq!(
+            processed,
+            directions.len(),
+            "Flow directions must not contain cycles."
+        );
+
+        accumulation
+    }
+
+    /// Fills depressions in ```elevation``` so that every cell drains to the hull of the diagram.
+    ///
+    /// This is an implementation of the priority-flood algorithm: starting from the hull cells, cells are flooded in order of elevation and
+    /// any cell lower than the cell it was reached from is raised to the smallest representable value above it.
+    /// As a result, every interior cell has a strictly lower neighbor and [Self::flow_directions()] returns ```None``` only for hull cells.
+    /// Cells that are not reachable from the hull keep their original elevation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```elevation``` does not have one value per site.
+    pub fn fill_depressions(&self, elevation: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            elevation.len(),
+            self.sites.len(),
+            "One elevation value per site is expected."
+        );
+
+        let mut filled = elevation.to_vec();
+        let mut visited = vec![false; elevation.len()];
+        let mut queue = BinaryHeap::new();
+
+        for cell in self.iter_cells().filter(|c| c.is_on_hull()) {
+            visited[cell.site()] = true;
+            queue.push(FloodCell {
+                elevation: filled[cell.site()],
+                site: cell.site(),
+            });
+        }
+
+        while let Some(FloodCell { site, .. }) = queue.pop() {
+            for neighbor in self.cell(site).iter_neighbors() {
+                if visited[neighbor] {
+                    continue;
+                }
+
+                visited[neighbor] = true;
+                filled[neighbor] = filled[neighbor].max(next_up(filled[site]));
+                queue.push(FloodCell {
+                    elevation: filled[neighbor],
+                    site: neighbor,
+                });
+            }
+        }
+
+        filled
+    }
+}
+
+/// Entry of the priority-flood queue. Ordered so that the lowest cell is popped first from a max-heap.
+struct FloodCell {
+    elevation: f64,
+    site: usize,
+}
+
+impl PartialEq for FloodCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FloodCell {}
+
+impl PartialOrd for FloodCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FloodCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .elevation
+            .total_cmp(&self.elevation)
+            .then_with(|| other.site.cmp(&self.site))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        utils::{dist2, test::random_voronoi},
+        Point,
+    };
+
+    fn assert_no_cycles(directions: &[Option<usize>]) {
+        for start in 0..directions.len() {
+            let mut cell = start;
+            let mut steps = 0;
+            while let Some(next) = directions[cell] {
+                cell = next;
+                steps += 1;
+                assert!(
+                    steps <= directions.len(),
+                    "Cycle found starting from cell {}.",
+                    start
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cone_drains_radially_outward() {
+        let voronoi = random_voronoi(300);
+        let center = Point { x: 0.0, y: 0.0 };
+        let elevation: Vec<f64> = voronoi
+            .sites()
+            .iter()
+            .map(|s| 1.0 - dist2(s, &center).sqrt())
+            .collect();
+
+        let directions = voronoi.flow_directions(&elevation);
+        assert_no_cycles(&directions);
+        for (cell, direction) in directions.iter().enumerate() {
+            if let Some(downstream) = *direction {
+                assert!(
+                    dist2(&voronoi.sites()[downstream], &center)
+                        > dist2(&voronoi.sites()[cell], &center),
+                    "Cell {} drains inwards to {}.",
+                    cell,
+                    downstream
+                );
+            } else {
+                assert!(
+                    voronoi.cell(cell).is_on_hull(),
+                    "Cell {} is a pit on a cone.",
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_elevation_has_no_cycles() {
+        let mut rng = crate::utils::test::rng();
+        for _ in 0..50 {
+            let voronoi = random_voronoi(200);
+            let elevation: Vec<f64> = (0..voronoi.sites().len())
+                .map(|_| rng.gen_range(0.0..10.0))
+                .collect();
+
+            for outlet in [FlowOutlet::Hull, FlowOutlet::LocalMinima] {
+                let directions = voronoi.flow_directions_with_outlets(&elevation, outlet);
+                assert_no_cycles(&directions);
+
+                let accumulation = voronoi.flow_accumulation(&directions);
+                let outlets: u32 = directions
+                    .iter()
+                    .zip(accumulation.iter())
+                    .filter(|(d, _)| d.is_none())
+                    .map(|(_, a)| a)
+                    .sum();
+                assert_eq!(outlets as usize, voronoi.sites().len());
+            }
+        }
+    }
+
+    #[test]
+    fn filled_depressions_drain_to_hull() {
+        let mut rng = crate::utils::test::rng();
+        let voronoi = random_voronoi(500);
+        let elevation: Vec<f64> = (0..voronoi.sites().len())
+            .map(|_| rng.gen_range(0.0..10.0))
+            .collect();
+
+        let filled = voronoi.fill_depressions(&elevation);
+        for (f, e) in filled.iter().zip(elevation.iter()) {
+            assert!(f >= e, "Filling must never lower a cell.");
+        }
+
+        let directions = voronoi.flow_directions(&filled);
+        assert_no_cycles(&directions);
+        for (cell, direction) in directions.iter().enumerate() {
+            assert_eq!(
+                direction.is_none(),
+                voronoi.cell(cell).is_on_hull(),
+                "Only hull cells are outlets after filling, cell {cell}."
+            );
+        }
+
+        let accumulation = voronoi.flow_accumulation(&directions);
+        let outlets: u32 = voronoi
+            .iter_cells()
+            .filter(|c| c.is_on_hull())
+            .map(|c| accumulation[c.site()])
+            .sum();
+        assert_eq!(outlets as usize, voronoi.sites().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "cycles")]
+    fn accumulation_rejects_cycles() {
+        let voronoi = random_voronoi(10);
+        let mut directions = vec![None; voronoi.sites().len()];
+        directions[0] = Some(1);
+        directions[1] = Some(0);
+        voronoi.flow_accumulation(&directions);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{ConvexBoundary, Point, Voronoi};
+
+/// Where a vertex of a cell is on the boundary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BoundaryPosition {
+    /// The corner at this index of [ConvexBoundary::vertices()].
+    Corner(usize),
+
+    /// Within the edge starting at this index of [ConvexBoundary::vertices()].
+    Edge(usize),
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the cells on the boundary in the order they appear walking the boundary counter-clockwise, i.e. in the order of [ConvexBoundary::vertices()].
+    ///
+    /// The walk starts at the cell containing the first vertex of the boundary. A cell is listed once for each stretch of the boundary it covers:
+    /// cells wrapping around a corner appear once, but a cell covering two disjoint stretches of the boundary appears once for each stretch.
+    /// This happens, for instance, when a small cell cuts off a corner from a larger cell covering both edges next to it.
+    /// Cells only touching the boundary at a single point are not listed.
+    ///
+    /// Only cells clipped by the boundary are found, so this is empty unless the diagram was built with [crate::ClipBehavior::Clip].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(3)
+    ///     .build()
+    ///     .unwrap();
+    /// // all cells but the center one are on the boundary
+    /// assert_eq!(v.hull_cells_ccw().len(), 8);
+    ///```
+    pub fn hull_cells_ccw(&self) -> Vec<usize> {
+        let corners = self.boundary.vertices();
+        let mut spans: Vec<Vec<(f64, usize)>> = vec![Vec::new(); corners.len()];
+        for (site, cell) in self.cells.iter().enumerate() {
+            if !cell.iter().any(|&v| v >= self.number_of_triangles()) {
+                continue;
+            }
+
+            for (&a, &b) in cell.iter().zip(cell.iter().cycle().skip(1)) {
+                let (pa, pb) = match (self.boundary_position(a), self.boundary_position(b)) {
+                    (Some(pa), Some(pb)) => (pa, pb),
+                    _ => continue,
+                };
+                let edge = match (pa, pb) {
+                    (BoundaryPosition::Corner(c), BoundaryPosition::Corner(d))
+                        if d == self.boundary.next_edge(c) =>
+                    {
+                        c
+                    }
+                    (BoundaryPosition::Corner(c), BoundaryPosition::Edge(e))
+                    | (BoundaryPosition::Edge(e), BoundaryPosition::Edge(c))
+                        if c == e =>
+                    {
+                        e
+                    }
+                    (BoundaryPosition::Edge(e), BoundaryPosition::Corner(d))
+                        if d == self.boundary.next_edge(e) =>
+                    {
+                        e
+                    }
+                    // a chord through the interior, between different boundary edges
+                    _ => continue,
+                };
+
+                let (start, end) = (&self.circumcenters[a], &self.circumcenters[b]);
+                if start != end {
+                    spans[edge].push((position_along(corners, edge, start), site));
+                }
+            }
+        }
+
+        let mut cells: Vec<usize> = Vec::new();
+        for mut edge_spans in spans {
+            edge_spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (_, site) in edge_spans {
+                if cells.last() != Some(&site) {
+                    cells.push(site);
+                }
+            }
+        }
+
+        // the cell containing the first corner also ends the walk if it wraps around that corner
+        if cells.len() > 1 && cells.first() == cells.last() {
+            cells.pop();
+        }
+
+        cells
+    }
+
+    /// Gets the cells on the boundary in the order they appear walking the boundary clockwise.
+    ///
+    /// The walk starts at the same cell as [Self::hull_cells_ccw()], and then lists the other cells in reverse order.
+    pub fn hull_cells_cw(&self) -> Vec<usize> {
+        let mut cells = self.hull_cells_ccw();
+        if let Some((_, rest)) = cells.split_first_mut() {
+            rest.reverse();
+        }
+
+        cells
+    }
+
+    fn boundary_position(&self, vertex: usize) -> Option<BoundaryPosition> {
+        // circumcenters are within the boundary, all other vertices were added by clipping
+        if vertex < self.number_of_triangles() {
+            return None;
+        }
+
+        let p = &self.circumcenters[vertex];
+        let corners = self.boundary.vertices();
+        if let Some(corner) = corners.iter().position(|c| c == p) {
+            return Some(BoundaryPosition::Corner(corner));
+        }
+
+        // the index returned is the end of the edge
+        self.boundary
+            .which_edge(p)
+            .map(|e| BoundaryPosition::Edge((e + corners.len() - 1) % corners.len()))
+    }
+}
+
+/// Gets the position of ```p``` along the boundary ```edge```, from 0 at its first vertex to 1 at the next.
+fn position_along(corners: &[Point], edge: usize, p: &Point) -> f64 {
+    let a = &corners[edge];
+    let b = &corners[(edge + 1) % corners.len()];
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    ((p.x - a.x) * dx + (p.y - a.y) * dy) / (dx * dx + dy * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils, utils::test::random_sites, BoundingBox, ClipBehavior, ConvexPolygon, VoronoiBuilder,
+    };
+
+    fn assert_hull_walk<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let walk = voronoi.hull_cells_ccw();
+
+        // cells may appear more than once, but never twice in a row
+        let mut cells = walk.clone();
+        cells.sort_unstable();
+        cells.dedup();
+        let expected: Vec<usize> = voronoi
+            .iter_cells()
+            .filter(|c| c.is_on_hull())
+            .map(|c| c.site())
+            .collect();
+        assert_eq!(cells, expected);
+
+        let first_corner = &voronoi.boundary().vertices()[0];
+        assert!(voronoi
+            .cell(walk[0])
+            .iter_vertices()
+            .any(|p| p == first_corner));
+
+        // consecutive cells, including the last and the first, meet on the boundary
+        for (&a, &b) in walk.iter().zip(walk.iter().cycle().skip(1)) {
+            assert_ne!(a, b, "Cell {} repeated in {:?}.", a, walk);
+            let shared = voronoi.cell(a).iter_vertices().any(|p| {
+                voronoi
+                    .cell(b)
+                    .iter_vertices()
+                    .any(|q| utils::dist2(p, q) < 1e-18)
+            });
+            assert!(shared, "Cells {} and {} do not meet.", a, b);
+        }
+
+        let mut reversed = voronoi.hull_cells_cw();
+        assert_eq!(reversed[0], walk[0]);
+        reversed[1..].reverse();
+        assert_eq!(reversed, walk);
+    }
+
+    #[test]
+    fn random_walks_cover_hull_in_order() {
+        for _ in 0..10 {
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(random_sites(200))
+                .build()
+                .expect("Some voronoi expected");
+            assert_hull_walk(&voronoi);
+
+            let voronoi = VoronoiBuilder::default()
+                .set_sites(random_sites(200))
+                .set_boundary(ConvexPolygon::regular(7, 1.0))
+                .build()
+                .expect("Some voronoi expected");
+            assert_hull_walk(&voronoi);
+        }
+    }
+
+    #[test]
+    fn grid_walk_goes_around_the_corners() {
+        let sites = (0..9)
+            .map(|i| Point {
+                x: (i % 3) as f64 * 0.5 - 0.5,
+                y: (i / 3) as f64 * 0.5 - 0.5,
+            })
+            .collect();
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+        assert_hull_walk(&voronoi);
+
+        // from the top left corner down the left edge, with the Y-axis pointing downwards
+        let walk: Vec<&Point> = voronoi
+            .hull_cells_ccw()
+            .iter()
+            .map(|&s| &voronoi.sites()[s])
+            .collect();
+        let expected = [
+            (-0.5, -0.5),
+            (-0.5, 0.0),
+            (-0.5, 0.5),
+            (0.0, 0.5),
+            (0.5, 0.5),
+            (0.5, 0.0),
+            (0.5, -0.5),
+            (0.0, -0.5),
+        ];
+        assert_eq!(walk.len(), expected.len());
+        for (p, &(x, y)) in walk.iter().zip(expected.iter()) {
+            assert_eq!((p.x, p.y), (x, y));
+        }
+    }
+
+    #[test]
+    fn unclipped_diagrams_have_no_hull_walk() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_sites(50))
+            .set_clip_behavior(ClipBehavior::None)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(voronoi.hull_cells_ccw().is_empty());
+        assert!(voronoi.hull_cells_cw().is_empty());
+    }
+}
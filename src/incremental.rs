@@ -0,0 +1,1228 @@
+use delaunator::{next_halfedge, prev_halfedge, Triangulation, EMPTY};
+
+use crate::{
+    cell_builder::{CellBuilder, CellBuilderArena},
+    instrument::phase,
+    iterator::EdgesAroundSiteIterator,
+    utils::{cicumcenter, cycle_positions, triangle_of_edge, EQ_EPSILON},
+    ClipBehavior, ConvexBoundary, Point, Voronoi,
+};
+
+/// Points closer than this, on both axes, to a site already triangulated are duplicates, which delaunator leaves out of the triangulation.
+const DUPLICATE_EPSILON: f64 = delaunator::EPSILON;
+
+/// Sites with more Delaunay neighbors than this are not removed in place, as filling the hole they leave takes a time cubic in their number of neighbors.
+const MAX_REMOVED_DEGREE: usize = 64;
+
+/// Vertices added by clipping that are no longer used by any cell are dropped once there are this many more vertices added by clipping than after the last compaction.
+const MIN_VERTICES_TO_COMPACT: usize = 64;
+
+/// Changes to the sites of a diagram, see [Voronoi::update_sites()].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SiteChanges {
+    /// Sites moved, with their new position, in the order they are moved.
+    pub(crate) moved: Vec<(usize, Point)>,
+
+    /// Positions of the sites appended, after the sites are moved.
+    pub(crate) inserted: Vec<Point>,
+
+    /// A site removed last, decreasing the index of every site after it.
+    pub(crate) removed: Option<usize>,
+}
+
+impl SiteChanges {
+    /// Gets ```sites``` with these changes applied.
+    pub(crate) fn apply(&self, sites: &[Point]) -> Vec<Point> {
+        let mut sites = sites.to_vec();
+        for (site, position) in &self.moved {
+            sites[*site] = position.clone();
+        }
+        sites.extend(self.inserted.iter().cloned());
+        if let Some(site) = self.removed {
+            sites.remove(site);
+        }
+
+        sites
+    }
+}
+
+/// The cells rebuilt by [Voronoi::update_sites()], and how the vertex indices of the other cells changed.
+#[derive(Debug)]
+pub(crate) struct SiteUpdate {
+    /// The sites whose cells were rebuilt, in increasing order, with the range of ```old_positions``` holding the vertices of their cell before the update,
+    /// unless they were inserted.
+    pub(crate) rebuilt: Vec<(usize, Option<std::ops::Range<usize>>)>,
+
+    /// The positions of the vertices of the cells rebuilt before the update, see [crate::utils::cycle_positions()].
+    pub(crate) old_positions: Vec<Point>,
+
+    /// Maps the vertex indices of the cells that were not rebuilt.
+    pub(crate) remap: VertexRemap,
+}
+
+/// How the vertex indices of the cells not rebuilt by [Voronoi::update_sites()] changed.
+///
+/// The circumcenters of triangles moved to fill the slots of removed triangles take the index of their new slot, and the vertices added by clipping
+/// are shifted when the number of triangles changes, and renumbered if the unused ones are dropped.
+#[derive(Debug)]
+pub(crate) struct VertexRemap {
+    triangles_before: usize,
+    triangles_after: usize,
+
+    /// The triangles moved, from and to, ordered by the slot they were moved from.
+    relocated: Vec<(usize, usize)>,
+
+    /// The index of each vertex added by clipping after the shift once unused vertices are dropped, or [EMPTY] if dropped.
+    compacted: Option<Vec<usize>>,
+}
+
+impl VertexRemap {
+    /// Gets whether every vertex keeps its index.
+    pub(crate) fn is_identity(&self) -> bool {
+        self.triangles_before == self.triangles_after
+            && self.relocated.is_empty()
+            && self.compacted.is_none()
+    }
+
+    /// Maps the index of a vertex of a cell that was not rebuilt.
+    #[inline]
+    pub(crate) fn map(&self, vertex: usize) -> usize {
+        let shifted = if vertex >= self.triangles_before {
+            vertex + self.triangles_after - self.triangles_before
+        } else if self.relocated.is_empty() {
+            vertex
+        } else {
+            match self
+                .relocated
+                .binary_search_by_key(&vertex, |&(from, _)| from)
+            {
+                Ok(i) => self.relocated[i].1,
+                Err(_) => vertex,
+            }
+        };
+
+        match &self.compacted {
+            Some(compacted) if shifted >= self.triangles_after => {
+                compacted[shifted - self.triangles_after]
+            }
+            _ => shifted,
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Applies ```changes``` to the sites in place: the triangulation is only changed around the sites moved, inserted and removed,
+    /// and only the cells of the sites whose Delaunay neighbors changed are rebuilt and clipped again, each once.
+    ///
+    /// Each site inserted, and each site moved to its new position, is located by walking the triangulation from a site close to it, the previous site inserted or
+    /// a former neighbor of the site moved. The triangles in conflict with it are replaced by a fan around it. The hole left by a site removed, or by a site moved
+    /// away, is filled by clipping the ears of the polygon of its neighbors whose circumcircles hold none of them.
+    ///
+    /// Vertices only used by the cells rebuilt before are left in [Self::vertices()]. Those added by clipping are dropped once they are more than twice,
+    /// plus [MIN_VERTICES_TO_COMPACT], as many as when the diagram was built or last compacted, while circumcenters are dropped with their triangle.
+    ///
+    /// Returns ```None```, leaving the diagram unchanged, if the changes cannot be applied in place: the diagram is not clipped or has no triangles, a site would
+    /// be at the same position as another, a site removed has more than [MAX_REMOVED_DEGREE] neighbors, no triangle would be left, or the triangulation
+    /// is not exactly Delaunay around the changes, which may happen for nearly degenerate sites, as delaunator does not use exact predicates.
+    pub(crate) fn update_sites(&mut self, changes: &SiteChanges) -> Option<SiteUpdate> {
+        // the vertices added by clipping start with the vertices of the boundary, which cells are closed with
+        let number_of_triangles = self.number_of_triangles();
+        let boundary = self.boundary.vertices();
+        if self.clip_behavior != ClipBehavior::Clip
+            || number_of_triangles == 0
+            || self
+                .circumcenters
+                .get(number_of_triangles..number_of_triangles + boundary.len())
+                != Some(boundary)
+        {
+            return None;
+        }
+
+        let number_of_sites = self.sites.len();
+        let phase = phase!(
+            debug_span,
+            "triangulation",
+            sites = number_of_sites + changes.inserted.len(),
+            triangles = tracing::field::Empty
+        );
+        let mut editor = TriangulationEditor::new(
+            &mut self.triangulation,
+            &mut self.site_to_incoming_leftmost_halfedge,
+        );
+        let mut moved_from = Vec::with_capacity(changes.moved.len());
+        if editor
+            .apply(&mut self.sites, changes, &mut moved_from)
+            .is_none()
+            || editor.number_of_triangles() == 0
+        {
+            editor.rollback();
+            for (site, position) in moved_from.into_iter().rev() {
+                self.sites[site] = position;
+            }
+            self.sites.truncate(number_of_sites);
+            return None;
+        }
+
+        let change = editor.finish(changes.removed);
+        phase.record("triangles", change.triangles);
+        drop(phase);
+        if let Some(site) = changes.removed {
+            self.sites.remove(site);
+        }
+
+        // the positions of the vertices of the cells to rebuild, before they are rebuilt
+        let old_site = |site: usize| -> Option<usize> {
+            let site = match changes.removed {
+                Some(removed) if site >= removed => site + 1,
+                _ => site,
+            };
+            (site < number_of_sites).then_some(site)
+        };
+        let mut old_positions = Vec::new();
+        let rebuilt: Vec<(usize, Option<std::ops::Range<usize>>)> = change
+            .touched
+            .iter()
+            .map(|&site| {
+                let old = old_site(site).map(|old| {
+                    let start = old_positions.len();
+                    cycle_positions(
+                        &self.cells[old],
+                        &self.circumcenters,
+                        EQ_EPSILON,
+                        &mut old_positions,
+                    );
+                    start..old_positions.len()
+                });
+                (site, old)
+            })
+            .collect();
+
+        // circumcenters of the new triangles, and of the triangles moved, which keep their circumcenter
+        let triangles = change.triangles;
+        if triangles > number_of_triangles {
+            let placeholders = vec![Point { x: 0.0, y: 0.0 }; triangles - number_of_triangles];
+            self.circumcenters
+                .splice(number_of_triangles..number_of_triangles, placeholders);
+        }
+        for &(from, to) in &change.relocated {
+            if from < number_of_triangles {
+                self.circumcenters[to] = self.circumcenters[from].clone();
+            }
+        }
+        if triangles < number_of_triangles {
+            self.circumcenters.drain(triangles..number_of_triangles);
+        }
+        for &t in &change.created {
+            self.circumcenters[t] = cicumcenter(
+                &self.sites[self.triangulation.triangles[3 * t]],
+                &self.sites[self.triangulation.triangles[3 * t + 1]],
+                &self.sites[self.triangulation.triangles[3 * t + 2]],
+            );
+        }
+
+        // the other cells keep their vertices, under their new indices
+        let mut clipped_cells = self.clipped_cells.take().unwrap_or_else(|| {
+            (0..number_of_sites)
+                .filter(|&site| self.cells[site].iter().any(|&v| v >= number_of_triangles))
+                .collect()
+        });
+        if let Some(site) = changes.removed {
+            self.cells.remove(site);
+            clipped_cells.retain(|&s| s != site);
+            clipped_cells
+                .iter_mut()
+                .filter(|s| **s > site)
+                .for_each(|s| *s -= 1);
+        }
+        self.cells.resize(self.sites.len(), Vec::new());
+        let mut remap = VertexRemap {
+            triangles_before: number_of_triangles,
+            triangles_after: triangles,
+            relocated: change.relocated,
+            compacted: None,
+        };
+        // a circumcenter is only used by the cells of the sites of its triangle, so only these need to follow the triangles moved,
+        // while the vertices added by clipping of any cell may need to be shifted
+        for &(from, to) in remap
+            .relocated
+            .iter()
+            .filter(|&&(from, _)| from < number_of_triangles)
+        {
+            for &site in &self.triangulation.triangles[3 * to..3 * to + 3] {
+                for v in self.cells[site].iter_mut().filter(|v| **v == from) {
+                    *v = to;
+                }
+            }
+        }
+        if triangles != number_of_triangles {
+            for &site in &clipped_cells {
+                for v in self.cells[site]
+                    .iter_mut()
+                    .filter(|v| **v >= number_of_triangles)
+                {
+                    *v = *v + triangles - number_of_triangles;
+                }
+            }
+        }
+
+        let sites: Vec<usize> = rebuilt.iter().map(|(site, _)| *site).collect();
+        let phase = phase!(
+            debug_span,
+            "cells",
+            sites = sites.len(),
+            vertices = tracing::field::Empty
+        );
+        let arena = &mut CellBuilderArena::default();
+        let builder = CellBuilder::for_update(
+            &self.triangulation,
+            &self.sites,
+            std::mem::take(&mut self.circumcenters),
+            std::mem::take(&mut self.site_to_incoming_leftmost_halfedge),
+            triangles,
+            self.boundary.clone(),
+            arena,
+        );
+        let (vertices, incoming) = builder.rebuild_cells(&sites, &mut self.cells, arena);
+        self.circumcenters = vertices;
+        self.site_to_incoming_leftmost_halfedge = incoming;
+        phase.record("vertices", self.circumcenters.len());
+        drop(phase);
+
+        let cells = &self.cells;
+        clipped_cells.retain(|site| sites.binary_search(site).is_err());
+        clipped_cells.extend(
+            sites
+                .iter()
+                .filter(|&&site| cells[site].iter().any(|&v| v >= triangles)),
+        );
+        clipped_cells.sort_unstable();
+        self.clipped_cells = Some(clipped_cells);
+
+        let clipped = self.circumcenters.len() - triangles;
+        if clipped > 2 * self.clipped_vertices + MIN_VERTICES_TO_COMPACT {
+            remap.compacted = Some(self.compact_clipped_vertices());
+        }
+
+        Some(SiteUpdate {
+            rebuilt,
+            old_positions,
+            remap,
+        })
+    }
+
+    /// Drops the vertices added by clipping that no cell uses, keeping the boundary vertices, and renumbers the vertices of the cells.
+    ///
+    /// Returns the new index of each vertex added by clipping, or [EMPTY] if it was dropped.
+    fn compact_clipped_vertices(&mut self) -> Vec<usize> {
+        let triangles = self.number_of_triangles();
+        let mut compacted = vec![EMPTY; self.circumcenters.len() - triangles];
+        compacted
+            .iter_mut()
+            .take(self.boundary.vertices().len())
+            .for_each(|index| *index = 0);
+        let clipped_cells = self.clipped_cells.take().unwrap_or_default();
+        for &site in &clipped_cells {
+            for &v in self.cells[site].iter().filter(|&&v| v >= triangles) {
+                compacted[v - triangles] = 0;
+            }
+        }
+
+        let mut next = triangles;
+        for (i, index) in compacted.iter_mut().enumerate() {
+            if *index != EMPTY {
+                *index = next;
+                self.circumcenters.swap(next, triangles + i);
+                next += 1;
+            }
+        }
+        self.circumcenters.truncate(next);
+        for &site in &clipped_cells {
+            for v in self.cells[site].iter_mut().filter(|v| **v >= triangles) {
+                *v = compacted[*v - triangles];
+            }
+        }
+        self.clipped_vertices = next - triangles;
+        self.clipped_cells = Some(clipped_cells);
+
+        compacted
+    }
+}
+
+/// The part of the triangulation changed by [TriangulationEditor::finish()].
+struct TriangulationChange {
+    /// The number of triangles after the changes.
+    triangles: usize,
+
+    /// The triangles created, which need their circumcenter calculated.
+    created: Vec<usize>,
+
+    /// The triangles moved to fill the slots of removed triangles, from and to, ordered by the slot they were moved from.
+    relocated: Vec<(usize, usize)>,
+
+    /// The sites whose Delaunay neighbors may have changed, in increasing order.
+    touched: Vec<usize>,
+}
+
+/// Edits a triangulation in place, recording the original value of everything it changes, so every change can be rolled back until it is finished.
+struct TriangulationEditor<'a> {
+    triangulation: &'a mut Triangulation,
+    incoming: &'a mut Vec<usize>,
+
+    /// The number of half-edges and the number of sites before the changes.
+    half_edges: usize,
+    sites: usize,
+
+    /// The original site and opposite half-edge of each half-edge changed, and the original incoming half-edge of each site changed, in order.
+    edge_log: Vec<(usize, usize, usize)>,
+    incoming_log: Vec<(usize, usize)>,
+
+    /// The hull before the changes, once changed.
+    hull: Option<Vec<usize>>,
+
+    /// The triangles removed whose slots are not reused yet, and the triangles and sites touched by the changes.
+    free: Vec<usize>,
+    created: Vec<usize>,
+    touched: Vec<usize>,
+
+    /// The triangles in conflict with an inserted site and the hull edges it is beyond, which are few, kept to avoid allocating them again.
+    cavity: Vec<usize>,
+    ghosts: Vec<usize>,
+}
+
+impl<'a> TriangulationEditor<'a> {
+    fn new(triangulation: &'a mut Triangulation, incoming: &'a mut Vec<usize>) -> Self {
+        Self {
+            half_edges: triangulation.triangles.len(),
+            sites: incoming.len(),
+            triangulation,
+            incoming,
+            edge_log: Vec::new(),
+            incoming_log: Vec::new(),
+            hull: None,
+            free: Vec::new(),
+            created: Vec::new(),
+            touched: Vec::new(),
+            cavity: Vec::new(),
+            ghosts: Vec::new(),
+        }
+    }
+
+    fn number_of_triangles(&self) -> usize {
+        self.triangulation.triangles.len() / 3 - self.free.len()
+    }
+
+    /// Applies ```changes``` to ```sites``` and to the triangulation, recording the original position of each site moved in ```moved_from```.
+    ///
+    /// Returns ```None``` as soon as a change cannot be applied, leaving the changes applied until then to be rolled back.
+    fn apply(
+        &mut self,
+        sites: &mut Vec<Point>,
+        changes: &SiteChanges,
+        moved_from: &mut Vec<(usize, Point)>,
+    ) -> Option<()> {
+        for (site, position) in &changes.moved {
+            let neighbor = self.remove(sites, *site)?;
+            moved_from.push((
+                *site,
+                std::mem::replace(&mut sites[*site], position.clone()),
+            ));
+            let hint = self.nearby_site(sites, position, neighbor);
+            self.insert(sites, *site, hint)?;
+        }
+
+        // inserted in spatial order, each located from the previous one
+        let first = sites.len();
+        sites.extend(changes.inserted.iter().cloned());
+        let order = crate::editing::spatial_order(&changes.inserted);
+        let mut hint = match (changes.moved.last(), order.first()) {
+            (Some((site, _)), _) => *site,
+            (None, Some(&i)) => self.nearby_site(sites, &changes.inserted[i], 0),
+            (None, None) => 0,
+        };
+        for i in order {
+            self.insert(sites, first + i, hint)?;
+            hint = first + i;
+        }
+
+        if let Some(site) = changes.removed {
+            self.remove(sites, site)?;
+        }
+
+        Some(())
+    }
+
+    /// Inserts ```site```, which is not in the triangulation, locating it from the triangles around ```hint```.
+    ///
+    /// Returns ```None``` without changing anything if the site is a duplicate or the triangulation is not Delaunay around it.
+    fn insert(&mut self, sites: &[Point], site: usize, hint: usize) -> Option<()> {
+        let p = &sites[site];
+        let start = match self.incoming[hint] {
+            EMPTY => 0,
+            edge => triangle_of_edge(edge),
+        };
+        let location = self.locate(sites, p, start)?;
+
+        // the triangles whose circumcircle holds the site, and the hull edges it is beyond, or in the interior of
+        let mut stack = Vec::new();
+        self.cavity.clear();
+        self.ghosts.clear();
+        match location {
+            Location::Inside(t) => {
+                self.cavity.push(t);
+                stack.push((t, false));
+            }
+            Location::Outside(edge) => {
+                self.ghosts.push(edge);
+                stack.push((edge, true));
+            }
+        }
+        while let Some((element, ghost)) = stack.pop() {
+            let mut visit = |editor: &mut Self, element: usize, ghost: bool| {
+                if ghost {
+                    if !editor.ghosts.contains(&element)
+                        && editor.is_beyond_hull_edge(sites, element, p)
+                    {
+                        editor.ghosts.push(element);
+                        stack.push((element, true));
+                    }
+                } else if !editor.cavity.contains(&element)
+                    && editor.is_in_circumcircle(sites, element, p)
+                {
+                    editor.cavity.push(element);
+                    stack.push((element, false));
+                }
+            };
+
+            if ghost {
+                visit(self, triangle_of_edge(element), false);
+                let previous = self.incoming[self.triangulation.triangles[element]];
+                visit(self, previous, true);
+                let next = self.outgoing_hull_edge(element)?;
+                visit(self, next, true);
+            } else {
+                for edge in 3 * element..3 * element + 3 {
+                    match self.triangulation.halfedges[edge] {
+                        EMPTY => visit(self, edge, true),
+                        opposite => visit(self, triangle_of_edge(opposite), false),
+                    }
+                }
+            }
+        }
+
+        // the border of the cavity, as edges x -> y with the half-edge on the other side, each fanned to the site
+        let triangles = &self.triangulation.triangles;
+        let halfedges = &self.triangulation.halfedges;
+        let mut border = Vec::new();
+        for &t in &self.cavity {
+            for edge in 3 * t..3 * t + 3 {
+                let (x, y) = (triangles[edge], triangles[next_halfedge(edge)]);
+                if nearly_equals(&sites[x], p) {
+                    return None;
+                }
+                match halfedges[edge] {
+                    EMPTY if !self.ghosts.contains(&edge) => border.push((x, y, EMPTY)),
+                    EMPTY => {}
+                    opposite if !self.cavity.contains(&triangle_of_edge(opposite)) => {
+                        border.push((x, y, opposite))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for &edge in &self.ghosts {
+            let (u, v) = (triangles[edge], triangles[next_halfedge(edge)]);
+            if nearly_equals(&sites[u], p) || nearly_equals(&sites[v], p) {
+                return None;
+            }
+            if !self.cavity.contains(&triangle_of_edge(edge)) {
+                border.push((v, u, edge));
+            }
+        }
+        border.sort_unstable_by_key(|&(x, _, _)| x);
+        if border
+            .iter()
+            .zip(border.iter().skip(1))
+            .any(|(a, b)| a.0 == b.0)
+            || border
+                .iter()
+                .any(|&(x, y, _)| orient(&sites[x], &sites[y], p) >= 0.0)
+        {
+            return None;
+        }
+
+        // the hull edges u0 -> ... -> uk the site is beyond are replaced by u0 -> site -> uk
+        let hull_splice = if self.ghosts.is_empty() {
+            None
+        } else {
+            let mut first = self
+                .ghosts
+                .iter()
+                .filter(|&&edge| !self.ghosts.contains(&self.incoming[triangles[edge]]));
+            let start = triangles[*first.next()?];
+            if first.next().is_some() {
+                return None;
+            }
+            let hull = &self.triangulation.hull;
+            let i = hull.iter().position(|&v| v == start)?;
+            let mut end = i;
+            for _ in 0..self.ghosts.len() {
+                end = (end + 1) % hull.len();
+                if !self.ghosts.contains(&self.incoming[hull[end]]) {
+                    return None;
+                }
+            }
+            Some((i, self.ghosts.len()))
+        };
+
+        // nothing fails from here on
+        // in a fixed order, so the same edits always give the same triangulation
+        self.cavity.sort_unstable_by(|a, b| b.cmp(a));
+        self.free.extend_from_slice(&self.cavity);
+        let mut created = Vec::with_capacity(border.len());
+        for &(x, y, opposite) in &border {
+            let t = self.allocate();
+            self.set(3 * t, x, opposite);
+            self.set(3 * t + 1, y, EMPTY);
+            self.set(3 * t + 2, site, EMPTY);
+            if opposite != EMPTY {
+                self.set_opposite(opposite, 3 * t);
+            }
+            created.push(t);
+        }
+        for (i, &(_, y, _)) in border.iter().enumerate() {
+            if let Ok(j) = border.binary_search_by_key(&y, |&(x, _, _)| x) {
+                self.set_opposite(3 * created[i] + 1, 3 * created[j] + 2);
+                self.set_opposite(3 * created[j] + 2, 3 * created[i] + 1);
+            }
+        }
+        if let Some((i, count)) = hull_splice {
+            let hull = self.hull_mut();
+            if i + count < hull.len() {
+                hull.splice(i + 1..i + count, [site]);
+            } else {
+                hull.rotate_left(i);
+                hull.splice(1..count, [site]);
+            }
+        }
+
+        self.touch(&created);
+        Some(())
+    }
+
+    /// Removes ```site``` from the triangulation, filling the hole it leaves. The site keeps its index, without any triangle.
+    ///
+    /// Returns one of its former neighbors, or ```None``` without changing anything if the hole cannot be filled.
+    fn remove(&mut self, sites: &[Point], site: usize) -> Option<usize> {
+        let triangles = &self.triangulation.triangles;
+        let halfedges = &self.triangulation.halfedges;
+        let start = self.incoming[site];
+        let star: Vec<usize> = EdgesAroundSiteIterator::new(&*self.triangulation, start)
+            .take(MAX_REMOVED_DEGREE + 1)
+            .collect();
+        if star.len() > MAX_REMOVED_DEGREE {
+            return None;
+        }
+
+        // the neighbors of the site, in order, and the half-edge on the far side of the edge between consecutive neighbors, directed from one to the next,
+        // which is [EMPTY] for hull edges
+        let closed = halfedges[start] != EMPTY;
+        let mut chain: Vec<usize> = star.iter().map(|&e| triangles[e]).collect();
+        let mut outer: Vec<Side> = star
+            .iter()
+            .map(|&e| Side::Outer(halfedges[prev_halfedge(e)]))
+            .collect();
+        if !closed {
+            chain.push(triangles[prev_halfedge(*star.last()?)]);
+        }
+
+        // clip ears until the hole is filled, or until the chain of neighbors of a site on the hull is convex and becomes part of the hull
+        let mut ears: Vec<([usize; 3], [Side; 3])> = Vec::new();
+        loop {
+            let m = chain.len();
+            if closed && m == 3 {
+                let vertices = [chain[1], chain[0], chain[2]];
+                if orient(
+                    &sites[vertices[0]],
+                    &sites[vertices[1]],
+                    &sites[vertices[2]],
+                ) >= 0.0
+                {
+                    return None;
+                }
+                ears.push((vertices, [outer[0], outer[2], outer[1]]));
+                break;
+            }
+
+            let candidates = if closed { m } else { m.saturating_sub(2) };
+            let ear = (0..candidates).find(|&j| {
+                let [x, y, z] = [chain[(j + 1) % m], chain[j], chain[(j + 2) % m]];
+                let (px, py, pz) = (&sites[x], &sites[y], &sites[z]);
+                orient(px, py, pz) < 0.0
+                    && chain.iter().all(|&q| {
+                        q == x || q == y || q == z || incircle(px, py, pz, &sites[q]) >= 0.0
+                    })
+            });
+            match ear {
+                Some(j) => {
+                    let (i1, i2) = ((j + 1) % m, (j + 2) % m);
+                    ears.push((
+                        [chain[i1], chain[j], chain[i2]],
+                        [outer[j], Side::Unset, outer[i1]],
+                    ));
+                    outer[j] = Side::Ear(ears.len() - 1);
+                    chain.remove(i1);
+                    outer.remove(i1);
+                }
+                None if closed => return None,
+                None => {
+                    // what is left of the chain is convex, unless the triangulation was not Delaunay around the site,
+                    // and is left with no triangle on the side of the site
+                    if (0..candidates).any(|j| {
+                        orient(&sites[chain[j + 1]], &sites[chain[j]], &sites[chain[j + 2]]) < 0.0
+                    }) || outer.iter().any(|side| matches!(side, Side::Outer(EMPTY)))
+                    {
+                        return None;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // the site is replaced by the rest of the chain in the hull
+        let hull_splice = if closed {
+            None
+        } else {
+            let hull = &self.triangulation.hull;
+            let i = hull.iter().position(|&v| v == site)?;
+            let n = hull.len();
+            if hull[(i + n - 1) % n] != chain[0] || hull[(i + 1) % n] != chain[chain.len() - 1] {
+                return None;
+            }
+            Some(i)
+        };
+        let hint = chain[0];
+        let star_triangles: Vec<usize> = star.iter().map(|&e| triangle_of_edge(e)).collect();
+
+        // nothing fails from here on
+        self.free.extend(star_triangles);
+        let created: Vec<usize> = ears.iter().map(|_| self.allocate()).collect();
+        let half_edge = |side: Side| match side {
+            Side::Outer(edge) => edge,
+            Side::Ear(ear) => 3 * created[ear] + 1,
+            Side::Unset => EMPTY,
+        };
+        for (&t, (vertices, sides)) in created.iter().zip(&ears) {
+            for j in 0..3 {
+                let opposite = half_edge(sides[j]);
+                self.set(3 * t + j, vertices[j], opposite);
+                if opposite != EMPTY {
+                    self.set_opposite(opposite, 3 * t + j);
+                }
+            }
+        }
+        if let Some(i) = hull_splice {
+            for side in &outer {
+                let edge = half_edge(*side);
+                self.set_opposite(edge, EMPTY);
+            }
+            let rest = chain[1..chain.len() - 1].to_vec();
+            self.hull_mut().splice(i..i + 1, rest);
+        }
+
+        // every neighbor keeps a triangle on the far side of its edges with the next neighbor
+        self.set_incoming(site, EMPTY);
+        for side in &outer {
+            let edge = half_edge(*side);
+            if edge != EMPTY {
+                self.touch_edge(edge);
+                self.touch_edge(prev_halfedge(edge));
+            }
+        }
+        self.touched.push(site);
+        self.touch(&created);
+        Some(hint)
+    }
+
+    /// Gets the site closest to ```p``` among ```candidate``` and a sample of about the cube root of the number of sites in the triangulation,
+    /// to locate ```p``` from, so the walk is shorter than from a site far from it.
+    fn nearby_site(&self, sites: &[Point], p: &Point, candidate: usize) -> usize {
+        let distance = |site: usize| {
+            let q = &sites[site];
+            (q.x - p.x) * (q.x - p.x) + (q.y - p.y) * (q.y - p.y)
+        };
+        let stride = ((self.sites as f64).powf(2.0 / 3.0) as usize).max(1);
+        (0..self.sites)
+            .step_by(stride)
+            .filter(|&site| self.incoming[site] != EMPTY)
+            .fold(candidate, |best, site| {
+                if distance(site) < distance(best) {
+                    site
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Walks from triangle ```t``` towards ```p```, returning the triangle that contains it, or a hull edge it is beyond.
+    ///
+    /// Returns ```None``` if the walk does not end, which only happens if the triangulation is not Delaunay.
+    fn locate(&self, sites: &[Point], p: &Point, mut t: usize) -> Option<Location> {
+        let triangles = &self.triangulation.triangles;
+        'walk: for _ in 0..=triangles.len() / 3 {
+            for edge in 3 * t..3 * t + 3 {
+                let (u, v) = (triangles[edge], triangles[next_halfedge(edge)]);
+                if orient(&sites[u], &sites[v], p) > 0.0 {
+                    match self.triangulation.halfedges[edge] {
+                        EMPTY => return Some(Location::Outside(edge)),
+                        opposite => t = triangle_of_edge(opposite),
+                    }
+                    continue 'walk;
+                }
+            }
+
+            return Some(Location::Inside(t));
+        }
+
+        None
+    }
+
+    /// Returns whether ```p``` is strictly inside the circumcircle of triangle ```t```.
+    fn is_in_circumcircle(&self, sites: &[Point], t: usize, p: &Point) -> bool {
+        let triangles = &self.triangulation.triangles;
+        incircle(
+            &sites[triangles[3 * t]],
+            &sites[triangles[3 * t + 1]],
+            &sites[triangles[3 * t + 2]],
+            p,
+        ) < 0.0
+    }
+
+    /// Returns whether ```p``` is strictly beyond the hull edge ```edge```, or in its interior.
+    fn is_beyond_hull_edge(&self, sites: &[Point], edge: usize, p: &Point) -> bool {
+        let triangles = &self.triangulation.triangles;
+        let (a, b) = (
+            &sites[triangles[edge]],
+            &sites[triangles[next_halfedge(edge)]],
+        );
+        let o = orient(a, b, p);
+        o > 0.0 || (o == 0.0 && (p.x - a.x) * (p.x - b.x) + (p.y - a.y) * (p.y - b.y) < 0.0)
+    }
+
+    /// Gets the hull edge leaving the site hull edge ```edge``` points to.
+    fn outgoing_hull_edge(&self, edge: usize) -> Option<usize> {
+        let mut e = next_halfedge(edge);
+        for _ in 0..self.triangulation.triangles.len() {
+            match self.triangulation.halfedges[e] {
+                EMPTY => return Some(e),
+                opposite => e = next_halfedge(opposite),
+            }
+        }
+
+        None
+    }
+
+    /// Sets the left-most incoming half-edge of the sites of ```triangles```, once for each site, and records them as touched.
+    fn touch(&mut self, triangles: &[usize]) {
+        let first = self.touched.len();
+        for &t in triangles {
+            for edge in 3 * t..3 * t + 3 {
+                let site = self.triangulation.triangles[next_halfedge(edge)];
+                if !self.touched[first..].contains(&site) {
+                    self.touch_edge(edge);
+                }
+            }
+        }
+        self.created.extend_from_slice(triangles);
+    }
+
+    /// Sets the left-most incoming half-edge of the site ```edge``` points to, found by turning around the site from ```edge```, and records it as touched.
+    fn touch_edge(&mut self, edge: usize) {
+        let site = self.triangulation.triangles[next_halfedge(edge)];
+        let mut e = edge;
+        for _ in 0..self.triangulation.triangles.len() {
+            match self.triangulation.halfedges[e] {
+                EMPTY => break,
+                opposite => e = prev_halfedge(opposite),
+            }
+            if e == edge {
+                break;
+            }
+        }
+
+        self.set_incoming(site, e);
+        self.touched.push(site);
+    }
+
+    fn allocate(&mut self) -> usize {
+        match self.free.pop() {
+            Some(t) => t,
+            None => {
+                let t = self.triangulation.triangles.len() / 3;
+                self.triangulation.triangles.extend_from_slice(&[EMPTY; 3]);
+                self.triangulation.halfedges.extend_from_slice(&[EMPTY; 3]);
+                t
+            }
+        }
+    }
+
+    fn set(&mut self, edge: usize, site: usize, opposite: usize) {
+        let triangulation = &mut *self.triangulation;
+        if edge < self.half_edges {
+            self.edge_log.push((
+                edge,
+                triangulation.triangles[edge],
+                triangulation.halfedges[edge],
+            ));
+        }
+        triangulation.triangles[edge] = site;
+        triangulation.halfedges[edge] = opposite;
+    }
+
+    fn set_opposite(&mut self, edge: usize, opposite: usize) {
+        let site = self.triangulation.triangles[edge];
+        self.set(edge, site, opposite);
+    }
+
+    fn set_incoming(&mut self, site: usize, edge: usize) {
+        if site >= self.incoming.len() {
+            self.incoming.resize(site + 1, EMPTY);
+        }
+        if site < self.sites {
+            self.incoming_log.push((site, self.incoming[site]));
+        }
+        self.incoming[site] = edge;
+    }
+
+    fn hull_mut(&mut self) -> &mut Vec<usize> {
+        if self.hull.is_none() {
+            self.hull = Some(self.triangulation.hull.clone());
+        }
+
+        &mut self.triangulation.hull
+    }
+
+    /// Undoes every change.
+    fn rollback(self) {
+        let triangulation = self.triangulation;
+        for &(edge, site, opposite) in self.edge_log.iter().rev() {
+            triangulation.triangles[edge] = site;
+            triangulation.halfedges[edge] = opposite;
+        }
+        triangulation.triangles.truncate(self.half_edges);
+        triangulation.halfedges.truncate(self.half_edges);
+        for &(site, edge) in self.incoming_log.iter().rev() {
+            self.incoming[site] = edge;
+        }
+        self.incoming.truncate(self.sites);
+        if let Some(hull) = self.hull {
+            triangulation.hull = hull;
+        }
+    }
+
+    /// Keeps the changes, moving the last triangles to the slots of the removed ones, and removes site ```removed```, if any, from the indices of the sites.
+    fn finish(mut self, removed: Option<usize>) -> TriangulationChange {
+        let triangulation = &mut *self.triangulation;
+        let mut free = std::mem::take(&mut self.free);
+        free.sort_unstable();
+        let is_free = |t: usize| free.binary_search(&t).is_ok();
+        let mut created: Vec<usize> = self
+            .created
+            .iter()
+            .copied()
+            .filter(|&t| !is_free(t))
+            .collect();
+        created.sort_unstable();
+        created.dedup();
+
+        let mut end = triangulation.triangles.len() / 3;
+        let mut relocated = Vec::new();
+        for &slot in &free {
+            while end > slot && is_free(end - 1) {
+                end -= 1;
+            }
+            if end <= slot {
+                break;
+            }
+
+            end -= 1;
+            for j in 0..3 {
+                let (from, to) = (3 * end + j, 3 * slot + j);
+                triangulation.triangles[to] = triangulation.triangles[from];
+                let opposite = triangulation.halfedges[from];
+                triangulation.halfedges[to] = opposite;
+                if opposite != EMPTY {
+                    triangulation.halfedges[opposite] = to;
+                }
+                let site = triangulation.triangles[next_halfedge(from)];
+                if self.incoming[site] == from {
+                    self.incoming[site] = to;
+                }
+            }
+            relocated.push((end, slot));
+        }
+        triangulation.triangles.truncate(3 * end);
+        triangulation.halfedges.truncate(3 * end);
+        relocated.reverse();
+        for t in created.iter_mut() {
+            if let Ok(i) = relocated.binary_search_by_key(t, |&(from, _)| from) {
+                *t = relocated[i].1;
+            }
+        }
+
+        let mut touched = self.touched;
+        if let Some(removed) = removed {
+            let renumber = |v: &mut usize| {
+                if *v > removed {
+                    *v -= 1;
+                }
+            };
+            triangulation.triangles.iter_mut().for_each(renumber);
+            triangulation.hull.iter_mut().for_each(renumber);
+            self.incoming.remove(removed);
+            touched.retain(|&site| site != removed);
+            touched.iter_mut().for_each(renumber);
+        }
+        touched.sort_unstable();
+        touched.dedup();
+
+        TriangulationChange {
+            triangles: end,
+            created,
+            relocated,
+            touched,
+        }
+    }
+}
+
+/// Where a site is in a triangulation, see [TriangulationEditor::locate()].
+enum Location {
+    /// Inside of, or on the edges of, a triangle.
+    Inside(usize),
+
+    /// Beyond a hull edge.
+    Outside(usize),
+}
+
+/// What is across an edge of the polygon of the neighbors of a removed site, see [TriangulationEditor::remove()].
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    /// A half-edge of a triangle outside of the polygon.
+    Outer(usize),
+
+    /// The middle edge of an ear clipped earlier.
+    Ear(usize),
+
+    /// Nothing yet.
+    Unset,
+}
+
+fn nearly_equals(a: &Point, b: &Point) -> bool {
+    (a.x - b.x).abs() <= DUPLICATE_EPSILON && (a.y - b.y).abs() <= DUPLICATE_EPSILON
+}
+
+fn orient(a: &Point, b: &Point, c: &Point) -> f64 {
+    robust::orient2d(a.into(), b.into(), c.into())
+}
+
+fn incircle(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+    robust::incircle(a.into(), b.into(), c.into(), d.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        utils::{self, test::random_voronoi},
+        BoundingBox, ConvexPolygon, VoronoiBuilder,
+    };
+
+    /// Checks that the triangulation of ```voronoi``` is consistent and Delaunay, and that its cells match those of a fresh build of its sites.
+    fn assert_matches_fresh_build<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let triangulation = &voronoi.triangulation;
+        let (triangles, halfedges) = (&triangulation.triangles, &triangulation.halfedges);
+        let sites = voronoi.sites();
+        for edge in 0..triangles.len() {
+            let opposite = halfedges[edge];
+            if opposite != EMPTY {
+                assert_eq!(halfedges[opposite], edge);
+                assert_eq!(triangles[opposite], triangles[next_halfedge(edge)]);
+                let far = &sites[triangles[prev_halfedge(opposite)]];
+                let t = triangle_of_edge(edge);
+                let [a, b, c] = [0, 1, 2].map(|j| &sites[triangles[3 * t + j]]);
+                assert!(
+                    incircle(a, b, c, far) >= 0.0,
+                    "Edge {} is not Delaunay.",
+                    edge
+                );
+            }
+            if edge % 3 == 0 {
+                let [a, b, c] = [0, 1, 2].map(|j| &sites[triangles[edge + j]]);
+                assert!(
+                    orient(a, b, c) < 0.0,
+                    "Triangle {} is not oriented.",
+                    edge / 3
+                );
+            }
+        }
+        for (site, &incoming) in voronoi
+            .site_to_incoming_leftmost_halfedge
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(triangles[next_halfedge(incoming)], site);
+            let on_hull = triangulation.hull.contains(&site);
+            assert_eq!(halfedges[incoming] == EMPTY, on_hull, "Site {}.", site);
+        }
+        let hull_edges = halfedges.iter().filter(|&&h| h == EMPTY).count();
+        assert_eq!(hull_edges, triangulation.hull.len());
+        for (i, &site) in triangulation.hull.iter().enumerate() {
+            let next = triangulation.hull[(i + 1) % triangulation.hull.len()];
+            let edge = voronoi.site_to_incoming_leftmost_halfedge[next];
+            assert_eq!(triangles[edge], site);
+        }
+
+        let expected = VoronoiBuilder::<T>::default()
+            .set_sites(sites.clone())
+            .set_boundary(voronoi.boundary().clone())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(utils::test::geometry_approx_eq(voronoi, &expected, 1e-9));
+    }
+
+    fn random_point(rng: &mut impl Rng, extent: f64) -> Point {
+        Point {
+            x: rng.gen_range(-extent..extent),
+            y: rng.gen_range(-extent..extent),
+        }
+    }
+
+    #[test]
+    fn random_updates_match_fresh_builds() {
+        let mut rng = utils::test::rng();
+        let mut voronoi = random_voronoi(300);
+        for i in 0..300 {
+            let site = rng.gen_range(0..voronoi.sites().len());
+            let changes = match rng.gen_range(0..4) {
+                0 => SiteChanges {
+                    moved: vec![(site, random_point(&mut rng, 1.0))],
+                    ..SiteChanges::default()
+                },
+                1 => SiteChanges {
+                    inserted: (0..rng.gen_range(1..20))
+                        .map(|_| random_point(&mut rng, 1.0))
+                        .collect(),
+                    ..SiteChanges::default()
+                },
+                2 => SiteChanges {
+                    removed: Some(site),
+                    ..SiteChanges::default()
+                },
+                _ => SiteChanges {
+                    moved: (0..rng.gen_range(1..10))
+                        .map(|_| {
+                            let site = rng.gen_range(0..voronoi.sites().len());
+                            (site, random_point(&mut rng, 1.0))
+                        })
+                        .collect(),
+                    inserted: vec![random_point(&mut rng, 1.0)],
+                    removed: Some(site),
+                },
+            };
+            let expected = changes.apply(voronoi.sites());
+
+            assert!(voronoi.update_sites(&changes).is_some(), "Update {}.", i);
+            assert_eq!(voronoi.sites(), &expected);
+            assert_matches_fresh_build(&voronoi);
+        }
+        utils::test::validate_voronoi(&voronoi);
+    }
+
+    #[test]
+    fn updates_change_hull() {
+        // sites away from the boundary, so the hull changes as sites are inserted and removed around it
+        let mut rng = utils::test::rng();
+        let sites = (0..50).map(|_| random_point(&mut rng, 0.5)).collect();
+        let mut voronoi = VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_boundary(BoundingBox::new_centered_square(4.0))
+            .build()
+            .expect("Some voronoi expected");
+
+        for _ in 0..100 {
+            let hull_site = voronoi.triangulation.hull[0];
+            let changes = SiteChanges {
+                moved: vec![(hull_site, random_point(&mut rng, 0.5))],
+                inserted: vec![random_point(&mut rng, 1.5)],
+                removed: Some(voronoi.triangulation.hull[1]),
+            };
+            assert!(voronoi.update_sites(&changes).is_some());
+            assert_matches_fresh_build(&voronoi);
+        }
+    }
+
+    #[test]
+    fn updates_clip_to_polygon() {
+        // sites within the inscribed circle of the boundary
+        let mut rng = utils::test::rng();
+        let mut voronoi = VoronoiBuilder::default()
+            .set_sites((0..200).map(|_| random_point(&mut rng, 0.7)).collect())
+            .set_boundary(ConvexPolygon::regular(7, 1.2))
+            .build()
+            .expect("Some voronoi expected");
+        for _ in 0..100 {
+            let site = rng.gen_range(0..voronoi.sites().len());
+            let changes = SiteChanges {
+                moved: vec![(site, random_point(&mut rng, 0.7))],
+                inserted: vec![random_point(&mut rng, 0.7)],
+                removed: Some((site + 1) % voronoi.sites().len()),
+            };
+            assert!(voronoi.update_sites(&changes).is_some());
+            assert_matches_fresh_build(&voronoi);
+        }
+    }
+
+    #[test]
+    fn failed_update_leaves_diagram_unchanged() {
+        let mut voronoi = random_voronoi(100);
+        let original = voronoi.clone();
+        let changes = SiteChanges {
+            moved: vec![(3, Point { x: 0.1, y: 0.2 })],
+            inserted: vec![Point { x: 0.3, y: 0.4 }, voronoi.sites()[7].clone()],
+            removed: Some(5),
+        };
+
+        assert!(voronoi.update_sites(&changes).is_none());
+        assert!(utils::test::geometry_eq(&voronoi, &original));
+        assert_eq!(voronoi.triangulation.hull, original.triangulation.hull);
+        assert_eq!(
+            voronoi.site_to_incoming_leftmost_halfedge,
+            original.site_to_incoming_leftmost_halfedge
+        );
+    }
+
+    #[test]
+    fn unused_clipped_vertices_are_dropped() {
+        let mut rng = utils::test::rng();
+        let mut voronoi = random_voronoi(100);
+        let clipped = voronoi.clipped_vertices;
+        for _ in 0..500 {
+            // sites near the boundary, whose cells are clipped
+            let site = voronoi
+                .iter_cells()
+                .find(|c| c.is_on_hull())
+                .expect("Hull cell expected")
+                .site();
+            let changes = SiteChanges {
+                moved: vec![(site, random_point(&mut rng, 1.0))],
+                ..SiteChanges::default()
+            };
+            assert!(voronoi.update_sites(&changes).is_some());
+            let clipped_now = voronoi.vertices().len() - voronoi.number_of_triangles();
+            assert!(clipped_now <= 2 * voronoi.clipped_vertices + MIN_VERTICES_TO_COMPACT);
+        }
+        assert!(voronoi.clipped_vertices < 2 * clipped);
+        assert_matches_fresh_build(&voronoi);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::BuildWarning;
+
+/// A phase of building or editing a diagram, traced as a span that records its duration in ```duration_us``` when dropped.
+///
+/// Without the ```tracing``` feature this is empty, and recording a field does nothing. Use [phase!] to enter a phase.
+pub(crate) struct Phase {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl Phase {
+    #[cfg(feature = "tracing")]
+    pub(crate) fn enter(span: tracing::Span) -> Self {
+        Self {
+            span: span.entered(),
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Records ```value``` in the field ```field``` of the span, which must have been declared as ```tracing::field::Empty``` when entering the phase.
+    #[inline(always)]
+    pub(crate) fn record(&self, _field: &str, _value: usize) {
+        #[cfg(feature = "tracing")]
+        self.span.record(_field, _value as u64);
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Phase {
+    fn drop(&mut self) {
+        self.span
+            .record("duration_us", self.start.elapsed().as_micros() as u64);
+    }
+}
+
+/// Enters a [Phase], traced as a span of the given level macro, name and fields, in the ```voronoice``` target.
+///
+/// The fields are neither compiled nor evaluated without the ```tracing``` feature.
+macro_rules! phase {
+    ($level:ident, $name:literal $(, $($field:tt)+)?) => {{
+        #[cfg(feature = "tracing")]
+        let phase = $crate::instrument::Phase::enter(tracing::$level!(
+            target: "voronoice",
+            $name,
+            $($($field)+,)?
+            duration_us = tracing::field::Empty
+        ));
+        #[cfg(not(feature = "tracing"))]
+        let phase = $crate::instrument::Phase {};
+        phase
+    }};
+}
+pub(crate) use phase;
+
+/// Emits a ```warning``` event for each of ```warnings```, in order.
+#[inline(always)]
+pub(crate) fn trace_warnings(_warnings: &[BuildWarning]) {
+    #[cfg(feature = "tracing")]
+    for warning in _warnings {
+        let (kind, sites) = match warning {
+            BuildWarning::ShortEdge { sites, .. } => ("ShortEdge", vec![sites.0, sites.1]),
+            BuildWarning::SimplifiedCell { site, .. } => ("SimplifiedCell", vec![*site]),
+            BuildWarning::CollapsedEdge { sites } => ("CollapsedEdge", vec![sites.0, sites.1]),
+            BuildWarning::DroppedSite { site, kept, .. } => ("DroppedSite", vec![*site, *kept]),
+            BuildWarning::MergedSites { sites } => ("MergedSites", sites.clone()),
+        };
+        tracing::warn!(name: "warning", target: "voronoice", kind = kind, sites = ?sites);
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+        Layer, Registry,
+    };
+
+    use crate::{BoundingBox, Point, SeparationPolicy, VoronoiBuilder};
+
+    #[derive(Debug, Default)]
+    struct Fields(HashMap<&'static str, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+    }
+
+    #[derive(Debug)]
+    struct RecordedSpan {
+        name: &'static str,
+        parents: Vec<&'static str>,
+        fields: Fields,
+    }
+
+    #[derive(Debug)]
+    struct RecordedEvent {
+        name: &'static str,
+        span: Option<usize>,
+        fields: Fields,
+    }
+
+    /// A layer recording the spans and the events, in order.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        events: Arc<Mutex<Vec<RecordedEvent>>>,
+
+        /// The recorded span of each id, as ids are reused once spans are closed.
+        ids: Arc<Mutex<HashMap<u64, usize>>>,
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+            let span = ctx.span(id).expect("Span expected");
+            let mut parents = Vec::new();
+            let mut parent = span.parent();
+            while let Some(p) = parent {
+                parents.push(p.name());
+                parent = p.parent();
+            }
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            let mut spans = self.spans.lock().unwrap();
+            self.ids.lock().unwrap().insert(id.into_u64(), spans.len());
+            spans.push(RecordedSpan {
+                name: span.name(),
+                parents,
+                fields,
+            });
+        }
+
+        fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, S>) {
+            let index = self.ids.lock().unwrap()[&id.into_u64()];
+            values.record(&mut self.spans.lock().unwrap()[index].fields);
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.events.lock().unwrap().push(RecordedEvent {
+                name: event.metadata().name(),
+                span: ctx
+                    .event_span(event)
+                    .map(|s| self.ids.lock().unwrap()[&s.id().into_u64()]),
+                fields,
+            });
+        }
+    }
+
+    fn record(f: impl FnOnce()) -> Recorder {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(Registry::default().with(recorder.clone()), f);
+        recorder
+    }
+
+    /// Gets the chain of span names from the root down to each span recorded.
+    fn paths(recorder: &Recorder) -> Vec<Vec<&'static str>> {
+        let spans = recorder.spans.lock().unwrap();
+        let mut paths: Vec<Vec<&'static str>> = spans
+            .iter()
+            .map(|s| {
+                let mut path: Vec<&'static str> = s.parents.iter().rev().copied().collect();
+                path.push(s.name);
+                path
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    #[test]
+    fn builds_trace_phase_hierarchy() {
+        let recorder = record(|| {
+            VoronoiBuilder::<BoundingBox>::default()
+                .generate_square_sites(5)
+                .set_lloyd_relaxation_iterations(1)
+                .set_vertex_welding(1e-9)
+                .build()
+                .expect("Some voronoi expected");
+        });
+
+        let paths = paths(&recorder);
+        for expected in [
+            vec!["build"],
+            vec!["build", "triangulation"],
+            vec!["build", "circumcenters"],
+            vec!["build", "cells"],
+            vec!["build", "cells", "clipping"],
+            vec!["build", "relaxation"],
+            vec!["build", "relaxation", "build", "cells", "clipping"],
+            vec!["build", "vertex_welding"],
+        ] {
+            assert!(
+                paths.contains(&expected),
+                "{:?} not in {:?}",
+                expected,
+                paths
+            );
+        }
+
+        let spans = recorder.spans.lock().unwrap();
+        assert!(spans.iter().all(|s| s.fields.0.contains_key("duration_us")));
+        let build = spans
+            .iter()
+            .find(|s| s.name == "build" && s.parents.is_empty())
+            .expect("Build span expected");
+        assert_eq!(build.fields.0["sites"], "25");
+        assert_eq!(build.fields.0["cells"], "25");
+        assert_eq!(build.fields.0["warnings"], "0");
+        let triangulation = spans
+            .iter()
+            .find(|s| s.name == "triangulation")
+            .expect("Triangulation span expected");
+        assert_eq!(triangulation.fields.0["sites"], "25");
+        assert_eq!(triangulation.fields.0["triangles"], "32");
+    }
+
+    #[test]
+    fn build_warnings_are_traced_with_their_sites() {
+        let mut sites = vec![
+            Point { x: -0.5, y: -0.5 },
+            Point { x: 0.5, y: -0.5 },
+            Point { x: 0.5, y: 0.5 },
+            Point { x: -0.5, y: 0.5 },
+        ];
+        sites.push(Point {
+            x: 0.5,
+            y: 0.5 + 1e-4,
+        });
+        let recorder = record(|| {
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(sites)
+                .set_min_separation(1e-3, SeparationPolicy::DropLater)
+                .build()
+                .expect("Some voronoi expected");
+            assert_eq!(voronoi.build_warnings().len(), 1);
+        });
+
+        let spans = recorder.spans.lock().unwrap();
+        let events = recorder.events.lock().unwrap();
+        let warnings: Vec<&RecordedEvent> = events.iter().filter(|e| e.name == "warning").collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].fields.0["kind"], "DroppedSite");
+        assert_eq!(warnings[0].fields.0["sites"], "[4, 2]");
+        let span = &spans[warnings[0].span.expect("Warning expected within a span")];
+        assert_eq!((span.name, span.parents.len()), ("build", 0));
+    }
+
+    #[test]
+    fn edits_trace_rebuild() {
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(5)
+            .build()
+            .expect("Some voronoi expected");
+        let recorder = record(|| {
+            voronoi
+                .move_site(7, Point { x: 0.01, y: 0.02 })
+                .expect("Move expected to succeed");
+            voronoi.remove_site(3).expect("Removal expected to succeed");
+        });
+
+        let paths = paths(&recorder);
+        assert!(paths.contains(&vec!["edit", "triangulation"]));
+        assert!(paths.contains(&vec!["edit", "cells", "clipping"]));
+        let spans = recorder.spans.lock().unwrap();
+        let mut edits: Vec<(&str, &str)> = spans
+            .iter()
+            .filter(|s| s.name == "edit")
+            .map(|s| {
+                (
+                    s.fields.0["operation"].as_str(),
+                    s.fields.0["site"].as_str(),
+                )
+            })
+            .collect();
+        edits.sort_unstable();
+        assert_eq!(edits, vec![("move", "7"), ("remove", "3")]);
+    }
+}
@@ -0,0 +1,231 @@
+use delaunator::EMPTY;
+
+use crate::{iterator::shortest_path_iter, utils::dist2, ConvexBoundary, Point, Voronoi};
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Interpolates a value per site into a value per Voronoi vertex, i.e. one value for each entry of [Self::vertices()].
+    ///
+    /// Vertices that are circumcenters of a Delaunay triangle are interpolated with the barycentric weights of the circumcenter within its triangle.
+    /// Circumcenters of obtuse triangles fall outside of the triangle; in such case the barycentric weights are clamped to zero and renormalized,
+    /// which is the same as interpolating at the closest point on the triangle. This means the interpolated value never falls outside of the range of the three site values,
+    /// at the cost of a linear field not being reproduced exactly for those vertices.
+    ///
+    /// Vertices added during hull closing and clipping (see [Self::vertices()]) are interpolated from the sites of the cells they belong to, weighted by their inverse distance to the vertex.
+    /// A vertex on a clipped Voronoi edge is equidistant to both sites of the edge and receives the average of their values, whereas a boundary corner receives the value of the cell that owns it.
+    /// Vertices that are not part of any cell receive the value of their closest site.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site_values``` does not have one value per site.
+    pub fn vertex_values(&self, site_values: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            site_values.len(),
+            self.sites.len(),
+            "One value per site is expected."
+        );
+
+        let num_of_triangles = self.number_of_triangles();
+        let mut values = vec![0.0; self.circumcenters.len()];
+
+        for (t, value) in values.iter_mut().enumerate().take(num_of_triangles) {
+            let a = self.triangulation.triangles[3 * t];
+            let b = self.triangulation.triangles[3 * t + 1];
+            let c = self.triangulation.triangles[3 * t + 2];
+            let [wa, wb, wc] = clamped_barycentric_weights(
+                &self.sites[a],
+                &self.sites[b],
+                &self.sites[c],
+                &self.circumcenters[t],
+            );
+
+            *value = wa * site_values[a] + wb * site_values[b] + wc * site_values[c];
+        }
+
+        // accumulate inverse distance weights of the cells each added vertex belongs to
+        let mut weights = vec![0.0; self.circumcenters.len() - num_of_triangles];
+        let mut owners = vec![(EMPTY, 0_usize); weights.len()];
+        for (site, cell) in self.cells.iter().enumerate() {
+            for &v in cell.iter().filter(|&&v| v >= num_of_triangles) {
+                let i = v - num_of_triangles;
+                let distance = dist2(&self.sites[site], &self.circumcenters[v]).sqrt();
+                if owners[i].1 == usize::MAX {
+                    // vertex is already owned by the site it is on
+                    continue;
+                }
+
+                if distance == 0.0 {
+                    // vertex is on the site itself
+                    owners[i] = (site, usize::MAX);
+                    continue;
+                }
+
+                if owners[i].0 == EMPTY {
+                    owners[i].0 = site;
+                }
+                owners[i].1 += 1;
+                weights[i] += 1.0 / distance;
+                values[v] += site_values[site] / distance;
+            }
+        }
+
+        let mut closest_site = 0;
+        for (i, (&weight, &(owner, count))) in weights.iter().zip(owners.iter()).enumerate() {
+            let v = num_of_triangles + i;
+            values[v] = if count == 1 || count == usize::MAX {
+                site_values[owner]
+            } else if count > 1 {
+                values[v] / weight
+            } else {
+                // not part of any cell, walk the triangulation to the closest site
+                if let Some(site) =
+                    shortest_path_iter(self, closest_site, self.circumcenters[v].clone()).last()
+                {
+                    closest_site = site;
+                }
+                site_values[closest_site]
+            };
+        }
+
+        values
+    }
+}
+
+/// Calculates the barycentric weights of ```p``` within triangle ```a```, ```b```, ```c```, clamping negative weights to zero.
+fn clamped_barycentric_weights(a: &Point, b: &Point, c: &Point, p: &Point) -> [f64; 3] {
+    let area = signed_area(a, b, c);
+    if area == 0.0 {
+        return [1. / 3., 1. / 3., 1. / 3.];
+    }
+
+    let weights = [
+        (signed_area(p, b, c) / area).max(0.0),
+        (signed_area(a, p, c) / area).max(0.0),
+        (signed_area(a, b, p) / area).max(0.0),
+    ];
+    let sum: f64 = weights.iter().sum();
+
+    [weights[0] / sum, weights[1] / sum, weights[2] / sum]
+}
+
+fn signed_area(a: &Point, b: &Point, c: &Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, BoundingBox, VoronoiBuilder};
+
+    fn linear(p: &Point) -> f64 {
+        3.0 * p.x - 2.0 * p.y + 0.5
+    }
+
+    #[test]
+    fn linear_field_is_exact_for_circumcenters_inside_triangle() {
+        let sites = utils::test::random_sites(500);
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .unwrap();
+        let site_values: Vec<f64> = voronoi.sites().iter().map(linear).collect();
+        let values = voronoi.vertex_values(&site_values);
+
+        let triangles = &voronoi.triangulation().triangles;
+        let mut checked = 0;
+        for t in 0..triangles.len() / 3 {
+            let [a, b, c] = [triangles[3 * t], triangles[3 * t + 1], triangles[3 * t + 2]]
+                .map(|s| &voronoi.sites()[s]);
+            let circumcenter = &voronoi.vertices()[t];
+            let area = signed_area(a, b, c);
+            let inside = [
+                signed_area(circumcenter, b, c),
+                signed_area(a, circumcenter, c),
+                signed_area(a, b, circumcenter),
+            ]
+            .iter()
+            .all(|w| w / area >= 0.0);
+
+            if inside {
+                checked += 1;
+                assert!(
+                    (values[t] - linear(circumcenter)).abs() < 1e-9,
+                    "Triangle {t}: expected {}, got {}",
+                    linear(circumcenter),
+                    values[t]
+                );
+            }
+        }
+
+        assert!(
+            checked > 0,
+            "Some circumcenters must be inside their triangle."
+        );
+    }
+
+    #[test]
+    fn added_vertices_are_interpolated_from_their_cells() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(4)
+            .build()
+            .unwrap();
+        let site_values: Vec<f64> = (0..voronoi.sites().len()).map(|s| s as f64).collect();
+        let values = voronoi.vertex_values(&site_values);
+
+        // corners of the boundary are owned by a single cell
+        for cell in voronoi.iter_cells() {
+            for &v in cell.triangles() {
+                let vertex = &voronoi.vertices()[v];
+                if voronoi.boundary().vertices().contains(vertex) {
+                    assert_eq!(values[v], site_values[cell.site()]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vertex_on_site_keeps_site_value() {
+        // the first site is on a corner of the boundary, which is a vertex of its cell
+        let mut sites = vec![Point { x: -1.0, y: -1.0 }];
+        sites.extend(utils::test::random_sites(20));
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .unwrap();
+        let corner = voronoi
+            .cell(0)
+            .triangles()
+            .iter()
+            .copied()
+            .find(|&v| voronoi.vertices()[v] == voronoi.sites()[0])
+            .expect("Corner vertex expected");
+
+        // the corner is visited again by the cells after the cell of its site
+        let last = voronoi.sites().len() - 1;
+        voronoi.cells[1].push(corner);
+        voronoi.cells[last].push(corner);
+
+        let site_values: Vec<f64> = (0..voronoi.sites().len()).map(|s| s as f64).collect();
+        let values = voronoi.vertex_values(&site_values);
+        assert_eq!(values[corner], site_values[0]);
+    }
+
+    #[test]
+    fn values_are_finite_for_examples() -> std::io::Result<()> {
+        for path in utils::test::VALID_ASSETS {
+            let voronoi = utils::test::new_voronoi_builder_from_asset(path)?
+                .build()
+                .expect("Some voronoi expected");
+            let site_values: Vec<f64> = voronoi.sites().iter().map(linear).collect();
+            let values = voronoi.vertex_values(&site_values);
+
+            assert_eq!(values.len(), voronoi.vertices().len());
+            assert!(
+                values.iter().all(|v| v.is_finite()),
+                "All values must be finite for {}.",
+                path
+            );
+        }
+
+        Ok(())
+    }
+}
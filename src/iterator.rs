@@ -117,13 +117,14 @@ impl<'t> Iterator for TopologicalNeighborSiteIterator<'t> {
 /// Iterates over sites that are adjacent in the voronoi diagram.
 ///
 /// This iterator expands on [TopologicalNeighborSiteIterator] by taking into account the clipping effect of voronoi edges to decide whether two sites are neighbors.
-/// In this iterator, two sites are considered neighbors if their voronoi cells share a common edge in the voronoi graph.
-/// Sites on the hull may get disconnected by this classification if their common voronoi edge gets clipped away because it lies beyond the bounding geometry.
+/// In this iterator, two sites are considered neighbors if their clipped voronoi cells share a common edge longer than the diagram's adjacency epsilon (see [utils::has_common_voronoi_edge]).
+/// Sites may get disconnected by this classification if their common voronoi edge gets clipped away because it lies beyond the bounding geometry,
+/// or if their cells only touch at a single point.
 #[derive(Clone, Debug)]
 pub struct NeighborSiteIterator<'t, T: ConvexBoundary> {
     voronoi: &'t Voronoi<T>,
     topo_neighbor_iter: TopologicalNeighborSiteIterator<'t>,
-    site: usize,
+    shared_edge_lengths: Vec<(usize, f64)>,
 }
 
 impl<'t, T: ConvexBoundary> NeighborSiteIterator<'t, T> {
@@ -131,7 +132,8 @@ impl<'t, T: ConvexBoundary> NeighborSiteIterator<'t, T> {
         Self {
             voronoi,
             topo_neighbor_iter: TopologicalNeighborSiteIterator::new(voronoi, site),
-            site,
+            // measured once for the whole cell rather than once per topological neighbor
+            shared_edge_lengths: utils::shared_voronoi_edge_lengths(voronoi, site),
         }
     }
 }
@@ -141,34 +143,15 @@ impl<'t, T: ConvexBoundary> Iterator for NeighborSiteIterator<'t, T> {
 
     /// Get the next neighboring site.
     fn next(&mut self) -> Option<Self::Item> {
-        let prev_last_incoming = self.topo_neighbor_iter.last_incoming;
-
-        if let Some(neighbor) = self.topo_neighbor_iter.next() {
-            // if first neighbor and on hull, need special check for clipping
-            if prev_last_incoming == EMPTY
-                && self.voronoi.triangulation.halfedges[self.topo_neighbor_iter.last_incoming]
-                    == EMPTY
-            {
-                if utils::has_common_voronoi_edge(self.voronoi, self.site, neighbor) {
-                    Some(neighbor)
-                } else {
-                    // not connected on the voronoi diagram
-                    // move to next
-                    self.next()
-                }
-            } else if self.topo_neighbor_iter.last_incoming == EMPTY {
-                // last neighbor, need speciail check for clipping
-                if utils::has_common_voronoi_edge(self.voronoi, self.site, neighbor) {
-                    Some(neighbor)
-                } else {
-                    None
-                }
-            } else {
-                Some(neighbor)
-            }
-        } else {
-            None
-        }
+        let epsilon = self.voronoi.adjacency_epsilon;
+        let lengths = &self.shared_edge_lengths;
+
+        // any topological neighbor may lose its common edge to clipping, not only the ones on the hull
+        self.topo_neighbor_iter.find(|&neighbor| {
+            lengths
+                .iter()
+                .any(|&(n, length)| n == neighbor && length > epsilon)
+        })
     }
 }
 
@@ -303,21 +286,12 @@ mod test {
     use delaunator::Point;
 
     use super::*;
-    use crate::{utils::test::assert_list_eq, BoundingBox, VoronoiBuilder};
+    use crate::{fixtures, utils::test::assert_list_eq, BoundingBox, VoronoiBuilder};
 
     #[test]
     fn iter_neighbors_hull_test() {
-        let sites = vec![
-            Point { x: -0.5, y: 0.0 },
-            Point { x: 0.5, y: 0.0 },
-            Point { x: 0.0, y: 0.0 },
-            Point { x: 0.0, y: 0.5 },
-            Point { x: 0.0, y: -0.5 },
-        ];
-        let v = VoronoiBuilder::<BoundingBox>::default()
-            .set_sites(sites)
-            .build()
-            .unwrap();
+        let (v, expected) = fixtures::plus_of_five_sites();
+        expected.assert_matches(&v, 1e-12);
 
         let neighbors: Vec<usize> = TopologicalNeighborSiteIterator::new(&v, 0).collect();
         assert_eq!(neighbors.len(), 3, "There are 3 neighboring sites");
@@ -328,17 +302,7 @@ mod test {
 
     #[test]
     fn iter_neighbors_inner_test() {
-        let sites = vec![
-            Point { x: -0.5, y: 0.0 },
-            Point { x: 0.5, y: 0.0 },
-            Point { x: 0.0, y: 0.0 },
-            Point { x: 0.0, y: 0.5 },
-            Point { x: 0.0, y: -0.5 },
-        ];
-        let v = VoronoiBuilder::<BoundingBox>::default()
-            .set_sites(sites)
-            .build()
-            .unwrap();
+        let (v, _) = fixtures::plus_of_five_sites();
         let neighbors: Vec<usize> = TopologicalNeighborSiteIterator::new(&v, 2).collect();
         assert_eq!(neighbors.len(), 4, "There are 4 neighboring sites");
         assert_eq!(neighbors[0], 3);
@@ -43,26 +43,155 @@
 //! println!("The first vertex position for the first voronoi cell is at {:?}",
 //!     all_voronoi_cell_vertices[indexed_voronoi_cells[0][0]]);
 //!```
+//!
+//! # Tracing
+//!
+//! With the ```tracing``` feature, building and editing diagrams is instrumented with [tracing](https://docs.rs/tracing) spans and events, all in the ```voronoice``` target.
+//! Without it, the instrumentation is not compiled at all. The names of the spans and events and their fields are stable, and only ever extended.
+//!
+//! Every span records its duration, in microseconds, in the field ```duration_us``` when closed. Counts are recorded as unsigned integers.
+//!
+//! | Span | Level | Parent | Fields |
+//! |------|-------|--------|--------|
+//! | ```build``` | INFO | none, or ```relaxation``` | ```sites``` given, ```cells``` built and ```warnings``` reported, the latter two missing if the build failed |
+//! | ```triangulation``` | DEBUG | ```build``` or ```edit``` | ```sites``` triangulated, ```triangles``` found. For an edit applied in place, the sites and triangles after the edit |
+//! | ```circumcenters``` | DEBUG | ```build``` or ```edit``` | ```triangles``` |
+//! | ```cells``` | DEBUG | ```build``` or ```edit``` | ```sites```, ```vertices``` of the diagram. Cells not on the hull are clipped as they are built, within this span. For an edit applied in place, the ```sites``` whose cells are rebuilt |
+//! | ```clipping``` | DEBUG | ```cells``` | ```hull_sites```, whose cells are extended and clipped to the boundary. For an edit applied in place, every cell rebuilt is clipped within this span, and ```hull_sites``` counts those on the hull |
+//! | ```relaxation``` | DEBUG | ```build``` | ```iteration```, starting at 0. Holds the ```build``` of the relaxed sites |
+//! | ```short_edge_elimination``` | DEBUG | ```build``` | ```passes``` run, ```warnings``` left |
+//! | ```cell_vertex_cap``` | DEBUG | ```build``` | ```warnings``` added |
+//! | ```vertex_welding``` | DEBUG | ```build``` | ```warnings``` added |
+//! | ```edit``` | INFO | none | ```operation```, one of ```move```, ```move_batch```, ```insert```, ```insert_batch```, ```remove``` and ```merge```, and the ```site``` edited, the one removed for ```merge``` and the first one moved or inserted for the batches. [Voronoi::relax_local()] edits a ```move_batch``` per iteration |
+//!
+//! | Event | Level | Parent | Fields |
+//! |-------|-------|--------|--------|
+//! | ```warning``` | WARN | ```build``` | ```kind```, the name of the [BuildWarning] variant, and the ```sites``` it refers to, formatted as a list |
+//! | ```half_plane_clipping``` | DEBUG | ```cells``` or ```clipping``` | ```site``` whose cell was clipped by half-planes, as its polygon of circumcenters does not reach the boundary |
+//!
+//! # Spade
+//!
+//! With the ```spade``` feature, sites can be taken from a [spade](https://docs.rs/spade) Delaunay triangulation with [VoronoiBuilder::from_spade()],
+//! and a diagram can be handed to spade with [Voronoi::to_spade()]. Both keep the order of the sites.
 
+mod adjacency;
+mod area_delta;
+mod atlas;
+mod auto_boundary;
+pub mod batch;
+mod binary;
+mod binning;
+mod bisector;
 mod boundary;
+#[cfg(feature = "alt-triangulator")]
+mod bowyer_watson;
 mod cell_builder;
+mod cell_edges;
+mod cell_map;
+mod dcel;
+mod debug_validation;
+mod decimation;
+mod diagnostics;
+mod dilation;
+mod editing;
+mod fan;
+mod fingerprint;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures;
+mod flow;
+mod hull;
+mod incremental;
+mod instrument;
+mod interpolation;
 mod iterator;
+mod local_relaxation;
+pub mod morph;
+mod partition;
+mod perimeter;
+mod pick;
+mod quasi_random;
+mod query;
+pub mod raster;
+mod restrict;
+mod sdf;
+mod separation;
+mod short_edges;
+mod smoothing;
+#[cfg(feature = "spade")]
+mod spade_interop;
+pub mod sphere;
+mod split;
+mod sweep_hull;
+mod symmetry;
+mod topology;
+mod transaction;
+mod triangulator;
 mod utils;
+mod view;
 mod voronoi_builder;
 mod voronoi_cell;
+mod weights;
+mod welding;
 
-pub use boundary::{BoundingBox, ClipBehavior, ConvexBoundary, ConvexPolygon};
+pub use adjacency::CellAdjacency;
+pub use area_delta::{AreaDeltaError, AreaThreshold};
+pub use atlas::{AtlasError, CellAtlas};
+pub use auto_boundary::{AutoBoundary, BuildStats};
+pub use binary::DecodeError;
+pub use bisector::Bisector;
+pub use boundary::{AnyBoundary, BoundingBox, ClipBehavior, ConvexBoundary, ConvexPolygon};
+pub use cell_edges::CellEdgeRef;
+pub use cell_map::{CellMap, StaleEntry};
+pub use dcel::{Dcel, DcelError, DcelFace, HalfEdge};
+pub use debug_validation::DebugValidation;
 pub use delaunator::Point;
-use delaunator::{triangulate, Triangulation, EMPTY};
+use delaunator::{Triangulation, EMPTY};
+pub use diagnostics::ExtremeCells;
+pub use dilation::DilatedCell;
+pub use editing::{EditError, InsertBatchReport, InsertOutcome};
+pub use fan::FanIndexCache;
+pub use flow::FlowOutlet;
 pub use iterator::{CellPathIterator, NeighborSiteIterator, TopologicalNeighborSiteIterator};
-pub use voronoi_builder::VoronoiBuilder;
-pub use voronoi_cell::VoronoiCell;
+pub use local_relaxation::RelaxLocalReport;
+pub use partition::PartitionPiece;
+pub use perimeter::PerimeterWalker;
+pub use pick::PickResult;
+pub use query::{QueryFields, QueryOutput};
+pub use restrict::RestrictedDiagram;
+pub use sdf::RegionSdf;
+pub use separation::SeparationPolicy;
+pub use split::SplitResult;
+pub use symmetry::{SymmetryAxis, WrongSideSites};
+pub use topology::VoronoiTopology;
+pub use transaction::{Transaction, TransactionError};
+pub use triangulator::Engine;
+pub use view::{FitMode, ViewTransform};
+pub use voronoi_builder::{BuildError, BuildWarning, RelaxationReport, VoronoiBuilder};
+pub use voronoi_cell::{CellEdge, VoronoiCell};
+pub use weights::WeightOptions;
+
+use std::sync::OnceLock;
+
+use self::{
+    cell_builder::*,
+    diagnostics::DiagnosticsCache,
+    editing::{next_generation, Edit, RebuildOptions},
+    instrument::phase,
+    transaction::TransactionFlag,
+    utils::cicumcenter,
+};
+
+/// A [Voronoi] diagram whose boundary type is chosen at runtime. See [AnyBoundary].
+pub type DynVoronoi = Voronoi<AnyBoundary>;
 
-use self::{cell_builder::*, utils::cicumcenter};
+/// A [VoronoiBuilder] of diagrams whose boundary type is chosen at runtime. See [AnyBoundary].
+pub type DynVoronoiBuilder = VoronoiBuilder<AnyBoundary>;
 
 /// The dual Delaunay-Voronoi graph.
 ///
 /// To obtain an instance of this type, use [VoronoiBuilder].
+///
+/// Cloning a diagram is deep: its sites, triangulation, vertices and cells are all copied, which costs about as much memory as the diagram itself.
 #[derive(Clone)]
 pub struct Voronoi<T: ConvexBoundary> {
     /// These are the sites of each voronoi cell.
@@ -72,6 +201,12 @@ pub struct Voronoi<T: ConvexBoundary> {
     triangulation: Triangulation,
     clip_behavior: ClipBehavior,
 
+    /// The minimum length of a shared voronoi edge for two cells to be considered neighbors.
+    adjacency_epsilon: f64,
+
+    /// The engine used to triangulate the sites, and to re-triangulate them when the diagram is edited.
+    engine: Engine,
+
     /// The circumcenter of each triangle (indexed by triangle / triangle's starting half-edge).
     ///
     /// For a given voronoi cell, its vertices are the circumcenters of its associated triangles.
@@ -79,80 +214,342 @@ pub struct Voronoi<T: ConvexBoundary> {
     /// voronoi cell vertices added to close sites on the convex hull or otherwise used for clipping edges that fell outside the bounding geometry.
     circumcenters: Vec<Point>,
 
+    /// The number of vertices added by clipping when the diagram was built, or when the vertices no cell used were last dropped after editing it.
+    clipped_vertices: usize,
+
+    /// The sites whose cells have vertices added by clipping, in increasing order, once found by the first edit applied in place.
+    clipped_cells: Option<Vec<usize>>,
+
     /// A map of each site to its left-most incomig half-edge.
     site_to_incoming_leftmost_halfedge: Vec<usize>,
 
     /// A map for each voronoi cell and the associated delaunay triangles whose centroids are the cell's vertices.
     /// For any site ```i```, the associated voronoi cell associated triangles are represented by ```cell_triangles[i]```.
     cells: Vec<Vec<usize>>,
+
+    /// The current generation of this diagram and the generation its edit log starts from, the one it was built with unless trimmed. See [Self::generation()].
+    generation: u64,
+    origin_generation: u64,
+
+    /// The generation each cell last changed in. See [Self::cell_generation()].
+    cell_generations: Vec<u64>,
+
+    /// The edits performed since the diagram was built, with the generation they resulted in.
+    edit_log: Vec<(u64, Edit)>,
+
+    /// The options of the build that edits apply again.
+    rebuild_options: RebuildOptions,
+
+    /// Non-fatal issues found while building the diagram.
+    warnings: Vec<BuildWarning>,
+
+    /// Statistics about how the diagram was built.
+    stats: BuildStats,
+
+    /// Whether a [Transaction] is open on this diagram. Never copied by cloning.
+    in_transaction: TransactionFlag,
+
+    /// The fingerprint of the inputs of the build. See [Self::input_fingerprint()].
+    input_fingerprint: u64,
+
+    /// The triangle fans of the cells, built on first access. See [Self::fan_indices()].
+    fan_indices: OnceLock<FanIndexCache>,
+
+    /// The axis of a mirror-symmetric diagram and the mirror of each site. See [Self::mirror_of()].
+    symmetry: Option<(SymmetryAxis, Vec<usize>)>,
+
+    /// Diagnostics calculated on first access. See [Self::closest_site_pair()] and [Self::extreme_cells()].
+    diagnostics: DiagnosticsCache,
 }
 
 impl<T: ConvexBoundary> std::fmt::Debug for Voronoi<T> {
+    /// Formats a summary of the diagram: number of sites and vertices, the extents of the boundary and the number of cells on the hull.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        struct Boundary<'a>(&'a [Point]);
+        impl std::fmt::Debug for Boundary<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let (min, max) = self.0.iter().fold(
+                    ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]),
+                    |(min, max), p| {
+                        (
+                            [min[0].min(p.x), min[1].min(p.y)],
+                            [max[0].max(p.x), max[1].max(p.y)],
+                        )
+                    },
+                );
+
+                f.debug_struct("Boundary")
+                    .field("vertices", &self.0.len())
+                    .field("min", &min)
+                    .field("max", &max)
+                    .finish()
+            }
+        }
+
         f.debug_struct("Voronoi")
-            .field("sites", &self.sites)
-            .field("circumcenters", &self.circumcenters)
-            .field("cell_triangles", &self.cells)
+            .field("sites", &self.sites.len())
+            .field("vertices", &self.circumcenters.len())
+            .field("boundary", &Boundary(self.boundary.vertices()))
+            .field("clip_behavior", &self.clip_behavior)
+            .field(
+                "hull_cells",
+                &self.iter_cells().filter(|c| c.is_on_hull()).count(),
+            )
             .finish()
     }
 }
 
+/// Removes the sites not within ```boundary```, unless ```clip_behavior``` keeps them.
+///
+/// Every site, kept or not, is passed to ```inspect``` in order, in the same pass.
+fn sites_inside_boundary<T: ConvexBoundary>(
+    sites: Vec<Point>,
+    boundary: &T,
+    clip_behavior: ClipBehavior,
+    mut inspect: impl FnMut(&Point),
+) -> Vec<Point> {
+    match clip_behavior {
+        ClipBehavior::RemoveSitesOutsideBoundaryOnly | ClipBehavior::Clip => sites
+            .into_iter()
+            .filter(|p| {
+                inspect(p);
+                boundary.is_inside(p)
+            })
+            .collect::<Vec<Point>>(),
+        ClipBehavior::None => {
+            sites.iter().for_each(inspect);
+            sites
+        }
+    }
+}
+
 // When reading this code think about 'edge' as the starting edge for a triangle
 // So you can say that the starting edge indexes the triangle
 // For instances, diag.triangles.len() is the number of starting edges and triangles in the triangulation, you can think of diag.triangles[e] as 'e' as being both the index of the
 // starting edge and the triangle it represents. When dealing with an arbitraty edge, it may not be a starting edge. You can get the starting edge by dividing the edge by 3 and flooring it.
 impl<T: ConvexBoundary> Voronoi<T> {
-    fn new(sites: Vec<Point>, boundary: T, clip_behavior: ClipBehavior) -> Option<Self> {
-        // remove any points not within the boundary
-        let sites = match clip_behavior {
-            ClipBehavior::RemoveSitesOutsideBoundaryOnly | ClipBehavior::Clip => sites
-                .into_iter()
-                .filter(|p| boundary.is_inside(p))
-                .collect::<Vec<Point>>(),
-            ClipBehavior::None => sites,
-        };
+    fn new(
+        sites: Vec<Point>,
+        boundary: T,
+        clip_behavior: ClipBehavior,
+        adjacency_epsilon: f64,
+        engine: Engine,
+        arena: &mut CellBuilderArena,
+    ) -> Option<Self> {
+        let sites = sites_inside_boundary(sites, &boundary, clip_behavior, |_| {});
+        let triangulation = engine.triangulate_in(&sites, &mut arena.triangulation);
 
-        let triangulation = triangulate(&sites);
+        Self::from_triangulation(
+            sites,
+            triangulation,
+            boundary,
+            clip_behavior,
+            adjacency_epsilon,
+            engine,
+            arena,
+        )
+    }
 
+    /// Builds the cells of the diagram of ```sites```, given their triangulation.
+    fn from_triangulation(
+        sites: Vec<Point>,
+        triangulation: Triangulation,
+        boundary: T,
+        clip_behavior: ClipBehavior,
+        adjacency_epsilon: f64,
+        engine: Engine,
+        arena: &mut CellBuilderArena,
+    ) -> Option<Self> {
         // triangulation.triangles is the indexing of each half-edge to source site
         // 3 * t, 3 * t + 1 and 3 * t + 2 are the vertices of a triangle in this vector
         let num_of_triangles = triangulation.triangles.len() / 3;
         if num_of_triangles == 0 {
-            return None;
+            // more than one site without triangles are collinear
+            return if sites.len() <= 1 {
+                Some(Self::without_triangles(
+                    sites,
+                    boundary,
+                    clip_behavior,
+                    adjacency_epsilon,
+                    engine,
+                ))
+            } else {
+                None
+            };
         }
 
         // calculate circuncenter of each triangle, these will be the vertices of the voronoi cells
-        let circumcenters = (0..num_of_triangles)
-            .map(|t| {
+        let circumcenters = {
+            let _phase = phase!(debug_span, "circumcenters", triangles = num_of_triangles);
+            let mut circumcenters = arena.take_vertices();
+            circumcenters.extend((0..num_of_triangles).map(|t| {
                 cicumcenter(
                     &sites[triangulation.triangles[3 * t]],
                     &sites[triangulation.triangles[3 * t + 1]],
                     &sites[triangulation.triangles[3 * t + 2]],
                 )
-            })
-            .collect();
+            }));
+            circumcenters
+        };
 
         // create cell builder to build cells and update circumcenters
-        let cell_builder = CellBuilder::new(
-            &triangulation,
-            &sites,
-            circumcenters,
-            boundary.clone(),
-            clip_behavior,
-        );
-        let result = cell_builder.build();
+        let result = {
+            let phase = phase!(
+                debug_span,
+                "cells",
+                sites = sites.len(),
+                vertices = tracing::field::Empty
+            );
+            let cell_builder = CellBuilder::new(
+                &triangulation,
+                &sites,
+                circumcenters,
+                boundary.clone(),
+                clip_behavior,
+                arena,
+            );
+            let result = cell_builder.build(arena);
+            phase.record("vertices", result.vertices.len());
+            result
+        };
+        let generation = next_generation();
+        let cell_generations = vec![generation; sites.len()];
 
         Some(Voronoi {
+            clipped_vertices: result.vertices.len() - num_of_triangles,
+            clipped_cells: None,
             boundary,
             site_to_incoming_leftmost_halfedge: result.site_to_incoming_leftmost_halfedge,
             triangulation,
             sites,
             clip_behavior,
+            adjacency_epsilon,
+            engine,
             circumcenters: result.vertices,
             cells: result.cells,
+            generation,
+            origin_generation: generation,
+            cell_generations,
+            edit_log: Vec::new(),
+            rebuild_options: RebuildOptions::default(),
+            warnings: Vec::new(),
+            stats: BuildStats::default(),
+            in_transaction: TransactionFlag::default(),
+            input_fingerprint: 0,
+            fan_indices: OnceLock::new(),
+            symmetry: None,
+            diagnostics: DiagnosticsCache::default(),
         })
     }
 
+    /// Builds the diagram of no sites, which has no cells, or of a single site, whose cell is the whole boundary.
+    ///
+    /// The cell of a single site is the boundary even without clipping, as hull cells are extended up to the boundary then.
+    fn without_triangles(
+        sites: Vec<Point>,
+        boundary: T,
+        clip_behavior: ClipBehavior,
+        adjacency_epsilon: f64,
+        engine: Engine,
+    ) -> Self {
+        let circumcenters = if !sites.is_empty() {
+            boundary.vertices().to_vec()
+        } else {
+            Vec::new()
+        };
+        let cells = sites
+            .iter()
+            .map(|_| (0..circumcenters.len()).collect())
+            .collect();
+        let triangulation = Triangulation {
+            triangles: Vec::new(),
+            halfedges: Vec::new(),
+            hull: (0..sites.len()).collect(),
+        };
+        let generation = next_generation();
+
+        Voronoi {
+            clipped_vertices: circumcenters.len(),
+            clipped_cells: None,
+            boundary,
+            site_to_incoming_leftmost_halfedge: vec![EMPTY; sites.len()],
+            triangulation,
+            cell_generations: vec![generation; sites.len()],
+            sites,
+            clip_behavior,
+            adjacency_epsilon,
+            engine,
+            circumcenters,
+            cells,
+            generation,
+            origin_generation: generation,
+            edit_log: Vec::new(),
+            rebuild_options: RebuildOptions::default(),
+            warnings: Vec::new(),
+            stats: BuildStats::default(),
+            in_transaction: TransactionFlag::default(),
+            input_fingerprint: 0,
+            fan_indices: OnceLock::new(),
+            symmetry: None,
+            diagnostics: DiagnosticsCache::default(),
+        }
+    }
+
+    /// Replaces the boundary of this diagram by ```f``` applied to it, keeping everything else, including the generation.
+    /// No transaction is open on the new diagram, as a transaction edits the diagram it was started on.
+    ///
+    /// The new boundary must have the same vertices, as the cells are not recalculated.
+    pub(crate) fn map_boundary<U: ConvexBoundary>(self, f: impl FnOnce(T) -> U) -> Voronoi<U> {
+        Voronoi {
+            sites: self.sites,
+            boundary: f(self.boundary),
+            triangulation: self.triangulation,
+            clip_behavior: self.clip_behavior,
+            adjacency_epsilon: self.adjacency_epsilon,
+            engine: self.engine,
+            circumcenters: self.circumcenters,
+            clipped_vertices: self.clipped_vertices,
+            clipped_cells: self.clipped_cells,
+            site_to_incoming_leftmost_halfedge: self.site_to_incoming_leftmost_halfedge,
+            cells: self.cells,
+            generation: self.generation,
+            origin_generation: self.origin_generation,
+            cell_generations: self.cell_generations,
+            edit_log: self.edit_log,
+            rebuild_options: self.rebuild_options,
+            warnings: self.warnings,
+            stats: self.stats,
+            in_transaction: TransactionFlag::default(),
+            input_fingerprint: self.input_fingerprint,
+            fan_indices: self.fan_indices,
+            symmetry: self.symmetry,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    /// Erases the boundary type of this diagram, see [AnyBoundary]. The diagram is otherwise unchanged.
+    pub fn into_dyn(self) -> DynVoronoi
+    where
+        T: Into<AnyBoundary>,
+    {
+        self.map_boundary(Into::into)
+    }
+
+    /// Gets the number of sites, and cells, of the diagram.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Returns whether the diagram has no sites, and thus no cells.
+    ///
+    /// Building a diagram of no sites, e.g. when all of them are outside of the boundary, results in an empty diagram rather than in an error.
+    /// All queries on it return empty results.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
     /// Borrows an immutable reference to the sites associated with the Voronoi graph.
     /// This is a reference to the unaltered site-point collection provided for the construction of this Voronoi graph.
     #[inline]
@@ -218,6 +615,27 @@ impl<T: ConvexBoundary> Voronoi<T> {
         &self.circumcenters
     }
 
+    /// Gets the triangle fans of the cells, as triplets of indices into [Self::vertices()].
+    ///
+    /// The fans are built the first time this is called, and are safe to build from multiple threads at once. Editing the diagram updates them,
+    /// recalculating only the fans of the cells reshaped, see [Self::cell_generation()] and [FanIndexCache].
+    ///
+    /// # Examples
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let fans = v.fan_indices();
+    /// let index_buffer: Vec<usize> = fans.triangles().iter().flatten().copied().collect();
+    /// assert_eq!(index_buffer.len(), 3 * fans.total_triangles());
+    ///```
+    pub fn fan_indices(&self) -> &FanIndexCache {
+        self.fan_indices
+            .get_or_init(|| FanIndexCache::new(&self.cells, &self.circumcenters, self.generation))
+    }
+
     /// Gets a reference to the underlying delaunay triangulation.
     ///
     /// ```triangulation().triangles``` is a vector of indices to sites where each triple represents a triangle on the dual Delaunay triangulation associated with this Voronoi graph.
@@ -232,6 +650,43 @@ impl<T: ConvexBoundary> Voronoi<T> {
         &self.boundary
     }
 
+    /// Gets the minimum length of a shared Voronoi edge for two cells to be considered neighbors.
+    ///
+    /// See [VoronoiBuilder::set_adjacency_epsilon()].
+    pub fn adjacency_epsilon(&self) -> f64 {
+        self.adjacency_epsilon
+    }
+
+    /// Gets the engine used to triangulate the sites.
+    ///
+    /// See [VoronoiBuilder::set_triangulator()].
+    pub fn triangulator(&self) -> Engine {
+        self.engine
+    }
+
+    /// Gets the non-fatal issues found while building this diagram.
+    ///
+    /// See [BuildWarning].
+    pub fn build_warnings(&self) -> &[BuildWarning] {
+        &self.warnings
+    }
+
+    /// Gets the statistics about how this diagram was built.
+    ///
+    /// See [BuildStats].
+    pub fn build_stats(&self) -> &BuildStats {
+        &self.stats
+    }
+
+    /// Gets the fingerprint of the inputs this diagram was built from: its sites, boundary and the configuration of the builder.
+    ///
+    /// This is the value [VoronoiBuilder::fingerprint()] returns before building, so diagrams can be memoized by it without building them first.
+    /// It is calculated while building, in the same pass that removes the sites outside of the boundary. Edits do not change it,
+    /// and diagrams read by [Voronoi::read_binary()] have a fingerprint of 0.
+    pub fn input_fingerprint(&self) -> u64 {
+        self.input_fingerprint
+    }
+
     /// Gets the number of Delaunay triangles.
     fn number_of_triangles(&self) -> usize {
         self.triangulation.triangles.len() / 3
@@ -305,6 +760,89 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn coincident_vertices_outside_boundary_are_not_clipped() -> std::io::Result<()> {
+        // mirrored sites, whose cells on the axis have two vertices outside the polygon that are the same point up to rounding errors,
+        // so the line through them may be found to cross the polygon once
+        let boundary = ConvexPolygon::regular(5, 1.0);
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(utils::test::sites_from_asset("coincident_vertices1.json")?)
+            .set_boundary(boundary)
+            .build()
+            .expect("Some voronoi expected");
+        // clipped vertices are on the slanted edges of the polygon up to rounding errors, which debug validation allows for
+        for site in 0..voronoi.sites().len() {
+            assert_eq!(debug_validation::validate_cell(&voronoi, site), Ok(()));
+        }
+
+        let area: f64 = voronoi.iter_cells().map(|c| c.area()).sum();
+        let expected = 2.5 * (0.4 * std::f64::consts::PI).sin();
+        assert!(
+            utils::abs_diff_eq(area, expected, 1e-9),
+            "Cells cover {} instead of the boundary",
+            area
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn boundaries_far_within_circumcenters_are_tiled() -> std::io::Result<()> {
+        for (path, boundary) in [
+            // the circumcenter is about 5e10 away from the sites, beyond the extension of the hull edges
+            (
+                "far_circumcenters1.json",
+                BoundingBox::new_centered_square(0.01),
+            ),
+            // only a thin strip of sites is kept, so most circumcenters are far to its left and right
+            (
+                "thin_boundary1.json",
+                BoundingBox::new(Point { x: 3.0, y: 1.0 }, 2.0, 150.0),
+            ),
+        ] {
+            let voronoi = utils::test::new_voronoi_builder_from_asset(path)?
+                .set_boundary(boundary.clone())
+                .build()
+                .expect("Some voronoi expected");
+            utils::test::validate_voronoi(&voronoi);
+
+            let mut area = 0.0;
+            for cell in voronoi.iter_cells() {
+                assert!(
+                    cell.iter_vertices().count() >= 3,
+                    "Cell {} of {} is empty",
+                    cell.site(),
+                    path
+                );
+                // the site is on the same side of every edge of the convex cell
+                let vertices: Vec<&Point> = cell.iter_vertices().collect();
+                let sides: Vec<f64> = vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .map(|(a, b)| {
+                        let p = cell.site_position();
+                        (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+                    })
+                    .collect();
+                assert!(
+                    sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0),
+                    "Site {} of {} is outside its cell",
+                    cell.site(),
+                    path
+                );
+                area += cell.area();
+            }
+            assert!(
+                utils::abs_diff_eq(area, boundary.width() * boundary.height(), 1e-9 * area),
+                "Cells of {} cover {} instead of the boundary",
+                path,
+                area
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn collinear_sites() {
         let voronoi = VoronoiBuilder::<BoundingBox>::default()
@@ -323,4 +861,188 @@ mod tests {
             "Collinear points do not generate valid voronoi"
         );
     }
+
+    /// Calls every query of the public API on ```voronoi```, checking that none of them panics or returns ```NaN```.
+    fn exercise_public_api<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let n = voronoi.len();
+        assert_eq!(voronoi.is_empty(), n == 0);
+        assert_eq!(voronoi.sites().len(), n);
+        assert_eq!(voronoi.cells().len(), n);
+        assert_eq!(voronoi.iter_cells().count(), n);
+        assert!(voronoi.triangulation().triangles.is_empty());
+        assert_eq!(voronoi.triangulation().hull.len(), n);
+        assert!(voronoi.build_warnings().is_empty());
+        assert!(voronoi
+            .vertices()
+            .iter()
+            .all(|p| !p.x.is_nan() && !p.y.is_nan()));
+
+        let outside = Point { x: 10.0, y: 10.0 };
+        let center = Point { x: 0.0, y: 0.0 };
+        for cell in voronoi.iter_cells() {
+            assert!(cell.is_on_hull());
+            assert_eq!(cell.iter_neighbors().count(), 0);
+            assert_eq!(
+                cell.iter_path(outside.clone()).collect::<Vec<_>>(),
+                vec![cell.site()]
+            );
+            assert_eq!(cell.iter_edges().count(), cell.triangles().len());
+            assert!(cell.iter_edge_metadata().all(|e| e.neighbor.is_none()));
+            assert_eq!(cell.shared_edge_length(cell.site()), 0.0);
+            assert!(cell.area() > 0.0);
+            assert_eq!(cell.unclipped_vertices().count(), 0);
+            cell.support(&Point { x: 1.0, y: 0.0 });
+            cell.farthest_vertex_from(&center);
+            let walker = cell.perimeter_walker();
+            assert!(walker.length() > 0.0);
+            assert!(!walker.position_at(0.5).x.is_nan());
+            assert!(matches!(
+                cell.split_by_line(&center, &Point { x: 1.0, y: 0.0 }),
+                SplitResult::Split { .. }
+            ));
+            assert!(format!("{:?}", cell).contains("is_on_hull: true"));
+        }
+
+        assert_eq!(voronoi.adjacency().len(), n);
+        assert!(voronoi.adjacency().neighbors().is_empty());
+        let fans = voronoi.fan_indices();
+        assert_eq!(
+            fans.total_triangles(),
+            n * voronoi.vertices().len().saturating_sub(2)
+        );
+        assert_eq!(voronoi.hull_cells_ccw(), (0..n).collect::<Vec<_>>());
+        assert_eq!(voronoi.hull_cells_cw(), (0..n).collect::<Vec<_>>());
+
+        for p in [&center, &outside] {
+            let clipped_away =
+                voronoi.clip_behavior == ClipBehavior::Clip && !voronoi.boundary().is_inside(p);
+            let expected = if n == 0 || clipped_away {
+                PickResult::Outside
+            } else {
+                PickResult::Cell(0)
+            };
+            assert_eq!(voronoi.pick(p, 0.01), expected);
+        }
+
+        let weights = voronoi.dirichlet_weights(&WeightOptions::default());
+        assert_eq!(weights, vec![1.0; n]);
+        let values = vec![0.5; n];
+        assert_eq!(
+            voronoi.weighted_mean(&values, &WeightOptions::default()),
+            Some(0.5).filter(|_| n > 0)
+        );
+        let vertex_values = voronoi.vertex_values(&values);
+        assert!(vertex_values.iter().all(|v| *v == 0.5));
+        let directions = voronoi.flow_directions(&values);
+        assert_eq!(directions, vec![None; n]);
+        assert_eq!(voronoi.flow_accumulation(&directions), vec![1; n]);
+        assert_eq!(voronoi.fill_depressions(&values), values);
+        assert_eq!(voronoi.smooth(&values, 2, 0.5), values);
+        let atlas = voronoi.build_atlas((16, 16), 1).expect("Atlas expected");
+        assert!(atlas.scale().is_finite());
+        let sdf = voronoi.region_sdf(&(0..n).collect::<Vec<_>>());
+        assert_eq!(sdf.boundary_loops().len(), n);
+        let pieces = voronoi.partition(&vec![0; n]);
+        assert_eq!(pieces.len(), n);
+        for piece in pieces {
+            let local = piece.voronoi.expect("Some local voronoi expected");
+            assert_eq!(local.len(), 1);
+            assert!(piece.ghosts.is_empty());
+        }
+        let mut map = voronoi.new_cell_map();
+        if n > 0 {
+            assert_eq!(map.insert(voronoi, 0, 1), None);
+        }
+        map.sync(voronoi);
+        assert_eq!(map.iter().count(), n);
+
+        let mut rasterizer = raster::Rasterizer::new(16, 16);
+        rasterizer.set_view(center.clone(), 4.0);
+        rasterizer.render(voronoi, |_| 0xFF_FF_FF);
+        assert_eq!(rasterizer.owner(8, 8), if n > 0 { Some(0) } else { None });
+        rasterizer.highlight(voronoi, &voronoi.pick(&center, 0.0), 0);
+
+        let mut edited = voronoi.clone();
+        let mut transaction = edited.transaction().expect("Transaction expected");
+        let site = transaction.insert_site(Point { x: 0.1, y: 0.2 });
+        assert_eq!(site.is_ok(), n == 0, "Two sites are collinear");
+        transaction.commit().expect("Commit expected to succeed");
+        if n == 1 {
+            edited
+                .move_site(0, Point { x: 0.3, y: 0.4 })
+                .expect("Move expected to succeed");
+            edited.remove_site(0).expect("Remove expected to succeed");
+            assert!(edited.is_empty());
+        } else {
+            assert_eq!(edited.len(), 1);
+        }
+
+        let mut morpher = morph::Morpher::new(voronoi, voronoi).expect("Morpher expected");
+        assert_eq!(morpher.at(0.5).expect("Frame expected").len(), n);
+    }
+
+    #[test]
+    fn empty_and_single_site_diagrams() {
+        let polygon = ConvexPolygon::regular(7, 1.0);
+        for sites in [vec![], vec![Point { x: -0.1, y: 0.1 }]] {
+            for &clip_behavior in &[
+                ClipBehavior::Clip,
+                ClipBehavior::RemoveSitesOutsideBoundaryOnly,
+                ClipBehavior::None,
+            ] {
+                let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                    .set_sites(sites.clone())
+                    .set_clip_behavior(clip_behavior)
+                    .build()
+                    .expect("Some voronoi expected");
+                exercise_public_api(&voronoi);
+
+                let voronoi = VoronoiBuilder::default()
+                    .set_sites(sites.clone())
+                    .set_boundary(polygon.clone())
+                    .set_clip_behavior(clip_behavior)
+                    .set_lloyd_relaxation_iterations(2)
+                    .build()
+                    .expect("Some voronoi expected");
+                exercise_public_api(&voronoi);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_region_filters_give_empty_diagrams() {
+        // every site is outside of the boundary
+        let sites = vec![Point { x: 2.0, y: 2.0 }, Point { x: -3.0, y: 1.0 }];
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .set_short_edge_elimination(0.1, 2)
+            .set_max_cell_vertices(4)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(voronoi.is_empty());
+
+        let mut bytes = Vec::new();
+        voronoi
+            .write_binary(&mut bytes)
+            .expect("Write expected to succeed");
+        let read = Voronoi::read_binary(bytes.as_slice()).expect("Read expected to succeed");
+        assert!(read.is_empty());
+        assert!(utils::test::geometry_eq(&read, &voronoi));
+
+        let topology = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build_topology()
+            .expect("Some topology expected");
+        assert!(topology.adjacency().is_empty());
+        let upgraded = topology
+            .with_geometry(BoundingBox::default())
+            .expect("Some voronoi expected");
+        assert!(utils::test::geometry_eq(&upgraded, &voronoi));
+
+        let diagrams = batch::build_all(vec![(sites, BoundingBox::default())]);
+        assert!(diagrams[0]
+            .as_ref()
+            .expect("Some voronoi expected")
+            .is_empty());
+    }
 }
@@ -0,0 +1,225 @@
+use crate::{
+    editing::Edit,
+    incremental::SiteChanges,
+    utils::{calculate_approximated_cetroid, dist2},
+    ConvexBoundary, Voronoi,
+};
+
+/// The outcome of [Voronoi::relax_local()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelaxLocalReport {
+    /// The sites that were relaxed, in increasing order.
+    pub region: Vec<usize>,
+
+    /// The largest distance a site of the region moved in each iteration that was run.
+    ///
+    /// Holds fewer values than the iterations requested if updating the diagram failed, in which case the diagram is left as it was after the last iteration that succeeded.
+    pub displacements: Vec<f64>,
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Runs [Lloyd relaxation](https://en.wikipedia.org/wiki/Lloyd%27s_algorithm) on the neighborhood of ```seeds``` only.
+    ///
+    /// The region relaxed holds the seeds and every site at most ```rings``` neighbors away from one of them.
+    /// Each iteration moves the sites of the region to the centroids of their cells, as [crate::VoronoiBuilder::set_lloyd_relaxation_iterations()] does,
+    /// and updates the diagram with a single edit of all the sites moved, in place around them as [Self::move_site()] does.
+    /// Sites out of the region are never moved, so their positions are kept bit for bit.
+    ///
+    /// The moved sites are edited directly, see [Self::cell_generation()]. If the diagram was built with clipping disabled, the cells in the hull are not closed
+    /// and their centroids are not meaningful.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of ```seeds``` is not a site of the diagram.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .set_lloyd_relaxation_iterations(5)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// voronoi.move_site(44, Point { x: 0.02, y: -0.03 }).unwrap();
+    /// let report = voronoi.relax_local(&[44], 2, 10);
+    /// assert!(report.region.contains(&44));
+    /// assert_eq!(report.displacements.len(), 10);
+    ///```
+    pub fn relax_local(
+        &mut self,
+        seeds: &[usize],
+        rings: usize,
+        iterations: usize,
+    ) -> RelaxLocalReport {
+        let region = self.rings_around(seeds, rings);
+        let mut displacements = Vec::with_capacity(iterations);
+
+        for _ in 0..iterations {
+            let mut changes = SiteChanges::default();
+            let mut displacement: f64 = 0.0;
+            for &site in &region {
+                let centroid = calculate_approximated_cetroid(self.cell(site).iter_vertices());
+                displacement = displacement.max(dist2(&centroid, &self.sites[site]).sqrt());
+                if centroid != self.sites[site] {
+                    changes.moved.push((site, centroid));
+                }
+            }
+
+            if !changes.moved.is_empty() {
+                let moved = changes.moved.iter().map(|&(site, _)| site).collect();
+                if self.edit_sites(changes, Edit::MovedBatch(moved)).is_err() {
+                    break;
+                }
+            }
+            displacements.push(displacement);
+        }
+
+        RelaxLocalReport {
+            region,
+            displacements,
+        }
+    }
+
+    /// Gets the sites at most ```rings``` neighbors away from any of ```seeds```, in increasing order.
+    fn rings_around(&self, seeds: &[usize], rings: usize) -> Vec<usize> {
+        let mut in_region = vec![false; self.sites.len()];
+        let mut frontier = Vec::new();
+        for &seed in seeds {
+            assert!(seed < self.sites.len(), "Site {} does not exist", seed);
+            if !in_region[seed] {
+                in_region[seed] = true;
+                frontier.push(seed);
+            }
+        }
+
+        for _ in 0..rings {
+            let mut next = Vec::new();
+            for site in frontier {
+                for neighbor in self.cell(site).iter_neighbors() {
+                    if !in_region[neighbor] {
+                        in_region[neighbor] = true;
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        (0..self.sites.len()).filter(|&s| in_region[s]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, BoundingBox, Point, VoronoiBuilder};
+
+    /// A diagram of ```100``` evenly spread sites, relaxed until they barely move.
+    fn relaxed_voronoi() -> Voronoi<BoundingBox> {
+        let sites = (0..100)
+            .map(|i| Point {
+                x: 1.8 * ((i as f64 * 0.618034) % 1.0) - 0.9,
+                y: 1.8 * ((i as f64 * 0.754878) % 1.0) - 0.9,
+            })
+            .collect();
+        VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_lloyd_relaxation_iterations(100)
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    fn disturb(voronoi: &mut Voronoi<BoundingBox>, seeds: &[usize]) {
+        for &seed in seeds {
+            let p = voronoi.sites()[seed].clone();
+            voronoi
+                .move_site(
+                    seed,
+                    Point {
+                        x: p.x + 0.04,
+                        y: p.y - 0.03,
+                    },
+                )
+                .expect("Move expected to succeed");
+        }
+    }
+
+    #[test]
+    fn sites_out_of_region_are_unchanged() {
+        let mut voronoi = relaxed_voronoi();
+        let seeds = [40, 77];
+        disturb(&mut voronoi, &seeds);
+        let before = voronoi.sites().clone();
+        let generation = voronoi.generation();
+
+        let report = voronoi.relax_local(&seeds, 2, 15);
+        assert!(seeds.iter().all(|s| report.region.contains(s)));
+        assert!(report.region.len() < before.len());
+        for (site, (after, before)) in voronoi.sites().iter().zip(&before).enumerate() {
+            if report.region.binary_search(&site).is_err() {
+                assert_eq!(after.x.to_bits(), before.x.to_bits());
+                assert_eq!(after.y.to_bits(), before.y.to_bits());
+            }
+        }
+        utils::test::validate_voronoi(&voronoi);
+
+        // each iteration is a single edit of the sites it moved, updating the diagram as building it again would
+        assert_eq!(
+            voronoi.edits_since(generation).map(|edits| edits.len()),
+            Some(report.displacements.iter().filter(|&&d| d > 0.0).count())
+        );
+        let built = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(voronoi.sites().clone())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(utils::test::geometry_approx_eq(&voronoi, &built, 1e-9));
+    }
+
+    #[test]
+    fn region_converges() {
+        let mut voronoi = relaxed_voronoi();
+        disturb(&mut voronoi, &[66]);
+
+        let report = voronoi.relax_local(&[66], 2, 30);
+        assert_eq!(report.displacements.len(), 30);
+        assert!(report.displacements[0] > 0.01);
+        assert!(report.displacements[29] < report.displacements[0] * 0.05);
+    }
+
+    #[test]
+    fn local_relaxation_is_close_to_global_relaxation() {
+        let mut voronoi = relaxed_voronoi();
+        let seeds = [53];
+        disturb(&mut voronoi, &seeds);
+        let global = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(voronoi.sites().clone())
+            .set_lloyd_relaxation_iterations(30)
+            .build()
+            .expect("Some voronoi expected");
+
+        let report = voronoi.relax_local(&seeds, 3, 30);
+        for &site in &report.region {
+            let distance = utils::dist2(&voronoi.sites()[site], &global.sites()[site]).sqrt();
+            assert!(
+                distance < 0.005,
+                "Site {} is {} away from the global relaxation",
+                site,
+                distance
+            );
+        }
+    }
+
+    #[test]
+    fn no_rings_relaxes_seeds_only() {
+        let mut voronoi = relaxed_voronoi();
+        disturb(&mut voronoi, &[30]);
+        let generation = voronoi.generation();
+
+        let report = voronoi.relax_local(&[30, 30], 0, 1);
+        assert_eq!(report.region, vec![30]);
+        assert_eq!(voronoi.cell_generation(30), voronoi.generation());
+        assert!(voronoi.generation() > generation);
+    }
+}
@@ -0,0 +1,277 @@
+//! Morphing between two diagrams of the same number of sites, e.g. before and after relaxation.
+//!
+//! Interpolating the vertices of paired cells directly does not work: the cells of both diagrams usually have different numbers of vertices,
+//! and even when they do not, the interpolated polygons may self-intersect or overlap their neighbors. Instead, the sites are interpolated
+//! and the diagram of the interpolated sites is built, so every frame is a valid Voronoi diagram and cells change continuously with ```t```.
+
+use std::fmt::Display;
+
+use crate::{
+    cell_builder::CellBuilderArena, fingerprint::bits_eq, ClipBehavior, ConvexBoundary, Engine,
+    Point, Voronoi,
+};
+
+/// An error returned when morphing between two diagrams.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MorphError {
+    /// The diagrams do not have the same number of sites, given for the first and second diagram.
+    SiteCountMismatch(usize, usize),
+
+    /// The diagrams do not have the same boundary.
+    DifferentBoundaries,
+
+    /// The sites interpolated at this ```t``` do not generate a valid diagram (e.g. all sites are collinear).
+    Degenerate(f64),
+}
+
+impl Display for MorphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MorphError::SiteCountMismatch(a, b) => {
+                write!(
+                    f,
+                    "Diagrams have different numbers of sites: {} and {}",
+                    a, b
+                )
+            }
+            MorphError::DifferentBoundaries => write!(f, "Diagrams have different boundaries"),
+            MorphError::Degenerate(t) => {
+                write!(
+                    f,
+                    "Sites interpolated at {} do not generate a valid diagram",
+                    t
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MorphError {}
+
+/// Builds the cells of the diagram of the sites of ```a``` and ```b``` interpolated at ```t```, where 0 is ```a``` and 1 is ```b```.
+///
+/// Cells are paired by site index. To build many frames, use a [Morpher], which reuses its buffers.
+///
+/// # Examples
+///
+///```
+/// use voronoice::*;
+/// let builder = || VoronoiBuilder::<BoundingBox>::default().generate_circle_sites(10, 0.5);
+/// let before = builder().build().unwrap();
+/// let after = builder().set_lloyd_relaxation_iterations(5).build().unwrap();
+/// let halfway = morph::interpolate(&before, &after, 0.5).unwrap();
+/// assert_eq!(halfway.len(), before.sites().len());
+///```
+pub fn interpolate<T: ConvexBoundary>(
+    a: &Voronoi<T>,
+    b: &Voronoi<T>,
+    t: f64,
+) -> Result<Vec<Vec<Point>>, MorphError> {
+    Ok(Morpher::new(a, b)?.at(t)?.to_vec())
+}
+
+/// Builds the frames of the morph between two diagrams, see [interpolate()].
+///
+/// The boundary and configuration of the first diagram are kept, along with the buffers used to interpolate the sites, build the cells and return their polygons,
+/// so building a frame only allocates what building a diagram from scratch must.
+#[derive(Debug)]
+pub struct Morpher<T: ConvexBoundary> {
+    from: Vec<Point>,
+    to: Vec<Point>,
+    boundary: T,
+    clip_behavior: ClipBehavior,
+    adjacency_epsilon: f64,
+    engine: Engine,
+    arena: CellBuilderArena,
+    sites: Vec<Point>,
+    polygons: Vec<Vec<Point>>,
+}
+
+impl<T: ConvexBoundary> Morpher<T> {
+    /// Creates a morph from diagram ```a``` to diagram ```b```.
+    ///
+    /// Both diagrams must have the same number of sites and the same boundary, which is the case when ```b``` is ```a``` after relaxation.
+    pub fn new(a: &Voronoi<T>, b: &Voronoi<T>) -> Result<Self, MorphError> {
+        if a.sites.len() != b.sites.len() {
+            return Err(MorphError::SiteCountMismatch(a.sites.len(), b.sites.len()));
+        }
+        if !bits_eq(a.boundary.vertices(), b.boundary.vertices()) {
+            return Err(MorphError::DifferentBoundaries);
+        }
+
+        Ok(Self {
+            from: a.sites.clone(),
+            to: b.sites.clone(),
+            boundary: a.boundary.clone(),
+            clip_behavior: a.clip_behavior,
+            adjacency_epsilon: a.adjacency_epsilon,
+            engine: a.engine,
+            arena: CellBuilderArena::default(),
+            sites: Vec::with_capacity(a.sites.len()),
+            polygons: Vec::new(),
+        })
+    }
+
+    /// Builds the cells of the frame at ```t```, as the polygon of each cell, in the order of the sites.
+    ///
+    /// The polygons are the vertices of the cells of [Self::diagram_at()], in the order of [crate::VoronoiCell::iter_vertices()].
+    pub fn at(&mut self, t: f64) -> Result<&[Vec<Point>], MorphError> {
+        let voronoi = self.diagram_at(t)?;
+
+        self.polygons.resize_with(voronoi.sites.len(), Vec::new);
+        for (polygon, cell) in self.polygons.iter_mut().zip(voronoi.iter_cells()) {
+            polygon.clear();
+            polygon.extend(cell.iter_vertices().cloned());
+        }
+
+        // recover the buffer of the sites for the next frame
+        self.sites = voronoi.sites;
+        Ok(&self.polygons)
+    }
+
+    /// Builds the diagram of the frame at ```t```.
+    ///
+    /// At 0 and 1 the sites are exactly those of the first and second diagram, so the diagrams built are the same as them,
+    /// unless they were post-processed with short edge elimination or a cell vertex cap.
+    /// ```t``` is not limited to ```[0, 1]```, but extrapolated sites outside of the boundary are removed, and then [MorphError::Degenerate] is returned.
+    pub fn diagram_at(&mut self, t: f64) -> Result<Voronoi<T>, MorphError> {
+        let mut sites = std::mem::take(&mut self.sites);
+        sites.clear();
+        // the weighted sum, rather than a + t (b - a), is exact at both ends
+        sites.extend(self.from.iter().zip(&self.to).map(|(a, b)| Point {
+            x: (1.0 - t) * a.x + t * b.x,
+            y: (1.0 - t) * a.y + t * b.y,
+        }));
+
+        let voronoi = Voronoi::new(
+            sites,
+            self.boundary.clone(),
+            self.clip_behavior,
+            self.adjacency_epsilon,
+            self.engine,
+            &mut self.arena,
+        )
+        .ok_or(MorphError::Degenerate(t))?;
+        if voronoi.sites.len() == self.from.len() {
+            Ok(voronoi)
+        } else {
+            Err(MorphError::Degenerate(t))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, utils::test::random_sites, BoundingBox, ConvexPolygon, VoronoiBuilder};
+
+    fn relaxation_pair(size: usize) -> (Voronoi<BoundingBox>, Voronoi<BoundingBox>) {
+        let sites = random_sites(size);
+        let before = VoronoiBuilder::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        let after = VoronoiBuilder::default()
+            .set_sites(sites)
+            .set_lloyd_relaxation_iterations(3)
+            .build()
+            .expect("Some voronoi expected");
+
+        (before, after)
+    }
+
+    #[test]
+    fn ends_reproduce_inputs() {
+        let (before, after) = relaxation_pair(300);
+        let mut morpher = Morpher::new(&before, &after).expect("Morpher expected");
+
+        let start = morpher.diagram_at(0.0).expect("Start expected");
+        assert!(utils::test::geometry_eq(&start, &before));
+        let end = morpher.diagram_at(1.0).expect("End expected");
+        assert!(utils::test::geometry_eq(&end, &after));
+
+        let polygons = morpher.at(1.0).expect("End expected").to_vec();
+        for (polygon, cell) in polygons.iter().zip(after.iter_cells()) {
+            assert!(polygon.iter().eq(cell.iter_vertices()));
+        }
+        assert_eq!(interpolate(&before, &after, 1.0), Ok(polygons));
+    }
+
+    #[test]
+    fn intermediate_frames_are_valid() {
+        for _ in 0..5 {
+            let (before, after) = relaxation_pair(200);
+            let mut morpher = Morpher::new(&before, &after).expect("Morpher expected");
+            for i in 1..10 {
+                let t = i as f64 / 10.0;
+                let frame = morpher.diagram_at(t).expect("Frame expected");
+                assert_eq!(frame.sites().len(), before.sites().len());
+                utils::test::validate_voronoi(&frame);
+
+                // the polygons of the frame are those of its diagram
+                let polygons = morpher.at(t).expect("Frame expected");
+                assert_eq!(polygons.len(), frame.sites().len());
+                for (polygon, cell) in polygons.iter().zip(frame.iter_cells()) {
+                    assert!(polygon.iter().eq(cell.iter_vertices()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_diagrams_are_rejected() {
+        let (before, _) = relaxation_pair(50);
+        let fewer = VoronoiBuilder::default()
+            .set_sites(random_sites(40))
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(
+            Morpher::new(&before, &fewer).unwrap_err(),
+            MorphError::SiteCountMismatch(50, 40)
+        );
+        assert_eq!(
+            interpolate(&before, &fewer, 0.5),
+            Err(MorphError::SiteCountMismatch(50, 40))
+        );
+
+        let larger = VoronoiBuilder::default()
+            .set_sites(before.sites().clone())
+            .set_boundary(BoundingBox::new_centered_square(3.0))
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(
+            Morpher::new(&before, &larger).unwrap_err(),
+            MorphError::DifferentBoundaries
+        );
+    }
+
+    #[test]
+    fn morphs_within_polygons() {
+        let boundary = ConvexPolygon::regular(9, 1.0);
+        let sites: Vec<Point> = random_sites(100)
+            .into_iter()
+            .filter(|p| boundary.is_inside(p))
+            .collect();
+        let builder = || {
+            VoronoiBuilder::default()
+                .set_sites(sites.clone())
+                .set_boundary(boundary.clone())
+        };
+        let before = builder().build().expect("Some voronoi expected");
+        let after = builder()
+            .set_lloyd_relaxation_iterations(2)
+            .build()
+            .expect("Some voronoi expected");
+
+        let mut morpher = Morpher::new(&before, &after).expect("Morpher expected");
+        let start = morpher.diagram_at(0.0).expect("Start expected");
+        assert!(utils::test::geometry_eq(&start, &before));
+        for t in [0.3, 0.6] {
+            // sites interpolated within a convex boundary stay within it
+            let frame = morpher.diagram_at(t).expect("Frame expected");
+            assert_eq!(frame.sites().len(), sites.len());
+        }
+        let end = morpher.diagram_at(1.0).expect("End expected");
+        assert!(utils::test::geometry_eq(&end, &after));
+    }
+}
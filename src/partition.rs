@@ -0,0 +1,302 @@
+use crate::{
+    ConvexBoundary, ConvexPolygon, Point, TopologicalNeighborSiteIterator, Voronoi, VoronoiBuilder,
+};
+
+/// The part of a diagram owned by one partition, as returned by [Voronoi::partition()].
+#[derive(Debug, Clone)]
+pub struct PartitionPiece {
+    /// The sites owned by this partition, in ascending order.
+    pub owned: Vec<usize>,
+
+    /// The sites owned by other partitions that share a Delaunay edge with an owned site, in ascending order.
+    ///
+    /// These include all neighbors of owned cells returned by [crate::VoronoiCell::iter_neighbors()].
+    pub ghosts: Vec<usize>,
+
+    /// The diagram of the owned and ghost sites, within the part of the boundary around them.
+    ///
+    /// Its sites are the owned sites followed by the ghost sites, so the cell of site ```i < owned.len()``` is the cell of ```owned[i]``` in the original diagram.
+    /// This is ```None``` if those sites do not generate a valid diagram (e.g. all sites are collinear).
+    pub voronoi: Option<Voronoi<ConvexPolygon>>,
+
+    /// The site in the original diagram of each site of [Self::voronoi], i.e. the owned sites followed by the ghost sites.
+    pub local_to_global: Vec<usize>,
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Splits this diagram by the partition that ```owner``` assigns each site to, for domain decomposition.
+    ///
+    /// Partitions are numbered from zero to the greatest owner, and a [PartitionPiece] is returned for each, even if it owns no sites.
+    /// Each piece has a local diagram of its owned sites and their ghost sites, which are all the sites the Voronoi cells of the owned sites depend on,
+    /// so the cells of the owned sites are the same as in this diagram, up to rounding errors. The cells of ghost sites are not complete.
+    ///
+    /// The local boundary is the part of the boundary of this diagram within a box fitted around the owned cells and the ghost sites,
+    /// so cells of owned sites on the boundary are clipped by the same boundary edges.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// // split by the left and right halves
+    /// let owner: Vec<u32> = v.sites().iter().map(|p| if p.x < 0.0 { 0 } else { 1 }).collect();
+    /// let pieces = v.partition(&owner);
+    /// assert_eq!(pieces[0].owned.len(), 50);
+    /// assert_eq!(pieces[0].ghosts.len(), 10);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```owner``` does not have an entry for each site.
+    pub fn partition(&self, owner: &[u32]) -> Vec<PartitionPiece> {
+        assert_eq!(
+            owner.len(),
+            self.sites.len(),
+            "There must be one owner for each site."
+        );
+
+        let partitions = owner.iter().max().map_or(0, |&o| o as usize + 1);
+        let mut owned = vec![Vec::new(); partitions];
+        for (site, &o) in owner.iter().enumerate() {
+            owned[o as usize].push(site);
+        }
+
+        owned
+            .into_iter()
+            .enumerate()
+            .map(|(partition, owned)| self.partition_piece(owner, partition as u32, owned))
+            .collect()
+    }
+
+    fn partition_piece(&self, owner: &[u32], partition: u32, owned: Vec<usize>) -> PartitionPiece {
+        let mut ghosts: Vec<usize> = owned
+            .iter()
+            .flat_map(|&site| TopologicalNeighborSiteIterator::new(self, site))
+            .filter(|&neighbor| owner[neighbor] != partition)
+            .collect();
+        ghosts.sort_unstable();
+        ghosts.dedup();
+
+        let local_to_global: Vec<usize> = owned.iter().chain(ghosts.iter()).copied().collect();
+        let voronoi = if owned.is_empty() {
+            None
+        } else {
+            let points = owned
+                .iter()
+                .flat_map(|&site| self.cells[site].iter().map(|&v| &self.circumcenters[v]))
+                .chain(ghosts.iter().map(|&site| &self.sites[site]));
+            let boundary = clip_to_box(self.boundary.vertices(), fitted_box(points));
+
+            VoronoiBuilder::default()
+                .set_sites(
+                    local_to_global
+                        .iter()
+                        .map(|&site| self.sites[site].clone())
+                        .collect(),
+                )
+                .set_boundary(ConvexPolygon::new(boundary))
+                .set_clip_behavior(self.clip_behavior)
+                .set_adjacency_epsilon(self.adjacency_epsilon)
+                .set_triangulator(self.engine)
+                .build()
+        };
+
+        PartitionPiece {
+            owned,
+            ghosts,
+            voronoi,
+            local_to_global,
+        }
+    }
+}
+
+/// Gets the ```(min, max)``` corners of a box around ```points```, with some margin so none of them is on its edges.
+fn fitted_box<'p>(points: impl Iterator<Item = &'p Point>) -> (Point, Point) {
+    let (min, max) = points.fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    );
+
+    let margin = 0.1 * (max.x - min.x).max(max.y - min.y) + f64::EPSILON;
+    (
+        Point {
+            x: min.x - margin,
+            y: min.y - margin,
+        },
+        Point {
+            x: max.x + margin,
+            y: max.y + margin,
+        },
+    )
+}
+
+/// Clips the convex polygon ```vertices``` to the box ```(min, max)```. The polygon keeps its orientation.
+fn clip_to_box(vertices: &[Point], (min, max): (Point, Point)) -> Vec<Point> {
+    // each side of the box is a limit on X or Y, and the side of the limit that is kept
+    let sides = [
+        (true, min.x, 1.0),
+        (true, max.x, -1.0),
+        (false, min.y, 1.0),
+        (false, max.y, -1.0),
+    ];
+
+    let mut polygon = vertices.to_vec();
+    for (is_x, limit, sign) in sides {
+        let distance = |p: &Point| sign * (if is_x { p.x } else { p.y } - limit);
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, p) in polygon.iter().enumerate() {
+            let q = &polygon[(i + 1) % polygon.len()];
+            let (dp, dq) = (distance(p), distance(q));
+            if dp >= 0.0 {
+                clipped.push(p.clone());
+            }
+            if (dp > 0.0 && dq < 0.0) || (dp < 0.0 && dq > 0.0) {
+                let t = dp / (dp - dq);
+                // the intersection is exactly on the side of the box
+                clipped.push(if is_x {
+                    Point {
+                        x: limit,
+                        y: p.y + t * (q.y - p.y),
+                    }
+                } else {
+                    Point {
+                        x: p.x + t * (q.x - p.x),
+                        y: limit,
+                    }
+                });
+            }
+        }
+        clipped.dedup();
+        while clipped.len() > 1 && clipped.first() == clipped.last() {
+            clipped.pop();
+        }
+        polygon = clipped;
+    }
+
+    polygon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox};
+
+    /// Owns each site by its quadrant.
+    fn quadrants(voronoi: &Voronoi<BoundingBox>) -> Vec<u32> {
+        voronoi
+            .sites()
+            .iter()
+            .map(|p| (p.x >= 0.0) as u32 + 2 * (p.y >= 0.0) as u32)
+            .collect()
+    }
+
+    #[test]
+    fn owned_cells_are_the_same_as_in_the_diagram() {
+        let voronoi = random_voronoi(400);
+        let owner = quadrants(&voronoi);
+        let pieces = voronoi.partition(&owner);
+        assert_eq!(pieces.len(), 4);
+
+        for piece in &pieces {
+            let local = piece.voronoi.as_ref().expect("Some local voronoi expected");
+            assert_eq!(local.sites().len(), piece.local_to_global.len());
+
+            for (i, &site) in piece.owned.iter().enumerate() {
+                assert_eq!(piece.local_to_global[i], site);
+                assert_eq!(&local.sites()[i], &voronoi.sites()[site]);
+
+                let expected: Vec<Point> = voronoi.cell(site).iter_vertices().cloned().collect();
+                crate::assert_cell_approx_eq!(local.cell(i), &expected, 1e-12);
+
+                let mut neighbors: Vec<usize> = TopologicalNeighborSiteIterator::new(local, i)
+                    .map(|n| piece.local_to_global[n])
+                    .collect();
+                let mut expected: Vec<usize> =
+                    TopologicalNeighborSiteIterator::new(&voronoi, site).collect();
+                neighbors.sort_unstable();
+                expected.sort_unstable();
+                assert_eq!(neighbors, expected, "Neighbors of site {}.", site);
+            }
+        }
+    }
+
+    #[test]
+    fn pieces_split_sites_and_surround_them_with_ghosts() {
+        let voronoi = random_voronoi(200);
+        let owner = quadrants(&voronoi);
+        let pieces = voronoi.partition(&owner);
+
+        let mut owned: Vec<usize> = pieces
+            .iter()
+            .flat_map(|p| p.owned.iter().copied())
+            .collect();
+        owned.sort_unstable();
+        assert_eq!(owned, (0..voronoi.sites().len()).collect::<Vec<usize>>());
+
+        for (partition, piece) in pieces.iter().enumerate() {
+            assert!(piece.owned.iter().all(|&s| owner[s] == partition as u32));
+            assert!(piece.ghosts.iter().all(|&s| owner[s] != partition as u32));
+            assert!(piece.ghosts.windows(2).all(|w| w[0] < w[1]));
+            for &site in &piece.owned {
+                for neighbor in voronoi.cell(site).iter_neighbors() {
+                    assert!(
+                        piece.owned.contains(&neighbor) || piece.ghosts.contains(&neighbor),
+                        "Neighbor {} of site {} is missing.",
+                        neighbor,
+                        site
+                    );
+                }
+            }
+
+            // the local boundary is within the boundary
+            let local = piece.voronoi.as_ref().expect("Some local voronoi expected");
+            assert!(local
+                .boundary()
+                .vertices()
+                .iter()
+                .all(|p| voronoi.boundary().is_inside(p)));
+        }
+    }
+
+    #[test]
+    fn empty_partitions_have_no_diagram() {
+        let voronoi = random_voronoi(50);
+        let pieces = voronoi.partition(&[2; 50]);
+        assert_eq!(pieces.len(), 3);
+        assert!(pieces[0].owned.is_empty() && pieces[0].voronoi.is_none());
+        assert!(pieces[1].owned.is_empty() && pieces[1].voronoi.is_none());
+        assert_eq!(pieces[2].owned.len(), 50);
+        assert!(pieces[2].ghosts.is_empty());
+
+        let local = pieces[2]
+            .voronoi
+            .as_ref()
+            .expect("Some local voronoi expected");
+        assert!(crate::utils::test::geometry_approx_eq(
+            &voronoi, local, 1e-12
+        ));
+    }
+}
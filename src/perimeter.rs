@@ -0,0 +1,309 @@
+use crate::Point;
+
+/// Evaluates positions and normals along the perimeter of a cell, parameterized by arc length.
+///
+/// The perimeter starts at the first vertex of the cell and follows its edges in the same order as [crate::VoronoiCell::iter_edges()].
+/// Parameters beyond the length of the perimeter, or negative, wrap around it. All queries run in ```O(log n)``` on the number of vertices of the cell.
+///
+/// Use [crate::VoronoiCell::perimeter_walker()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct PerimeterWalker<'v> {
+    cell: &'v [usize],
+    vertices: &'v [Point],
+
+    /// The arc length at the start of each edge, followed by the length of the perimeter.
+    prefix: Vec<f64>,
+
+    /// The outward unit normal of each edge, or ```None``` for edges of zero length.
+    normals: Vec<Option<Point>>,
+}
+
+impl<'v> PerimeterWalker<'v> {
+    pub(crate) fn new(cell: &'v [usize], vertices: &'v [Point]) -> Self {
+        let n = cell.len();
+        let edge = |i: usize| (&vertices[cell[i]], &vertices[cell[(i + 1) % n]]);
+
+        // the normals point outwards regardless of the orientation of the cell
+        let doubled_area: f64 = (0..n)
+            .map(|i| {
+                let (a, b) = edge(i);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        let orientation = if doubled_area > 0.0 { 1.0 } else { -1.0 };
+
+        let mut prefix = Vec::with_capacity(n + 1);
+        let mut normals = Vec::with_capacity(n);
+        let mut length = 0.0;
+        for i in 0..n {
+            let (a, b) = edge(i);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let edge_length = dx.hypot(dy);
+            prefix.push(length);
+            normals.push(if edge_length > 0.0 {
+                Some(Point {
+                    x: orientation * dy / edge_length,
+                    y: -orientation * dx / edge_length,
+                })
+            } else {
+                None
+            });
+            length += edge_length;
+        }
+        prefix.push(length);
+
+        Self {
+            cell,
+            vertices,
+            prefix,
+            normals,
+        }
+    }
+
+    /// Gets the length of the perimeter.
+    #[inline]
+    pub fn length(&self) -> f64 {
+        self.prefix.last().copied().unwrap_or(0.0)
+    }
+
+    /// Gets the edge containing the point at arc length ```t```, and the position of the point within the edge, from 0 at its first vertex to 1 at its second vertex.
+    ///
+    /// The edge is given as its position in [crate::VoronoiCell::iter_edges()]. Edges of zero length, from repeated vertices, are never returned.
+    /// Vertices belong to the edge they start, so ```t``` at the start of an edge returns that edge and exactly 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell has no vertices.
+    pub fn segment_at(&self, t: f64) -> (usize, f64) {
+        assert!(!self.cell.is_empty(), "Cell has no vertices.");
+
+        let length = self.length();
+        let t = self.wrap(t);
+        // the last edge starting at or before t, which skips edges of zero length
+        let edge = self.prefix[..self.cell.len()].partition_point(|&p| p <= t) - 1;
+        let edge_length = self.prefix[edge + 1] - self.prefix[edge];
+        let local_t = if edge_length > 0.0 && length > 0.0 {
+            ((t - self.prefix[edge]) / edge_length).min(1.0)
+        } else {
+            0.0
+        };
+
+        (edge, local_t)
+    }
+
+    /// Gets the position of the point at arc length ```t``` along the perimeter.
+    ///
+    /// At the arc length of a vertex, i.e. the sum of the lengths of the edges before it, this is exactly the vertex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell has no vertices.
+    pub fn position_at(&self, t: f64) -> Point {
+        let (edge, local_t) = self.segment_at(t);
+        let (a, b) = self.edge(edge);
+        if local_t == 0.0 {
+            a.clone()
+        } else {
+            Point {
+                x: a.x + local_t * (b.x - a.x),
+                y: a.y + local_t * (b.y - a.y),
+            }
+        }
+    }
+
+    /// Gets the outward unit normal of the perimeter at arc length ```t```.
+    ///
+    /// This is the normal of the edge containing the point, or, at a vertex, the normal along the bisector of the angle between the edges meeting at it.
+    /// If the cell has no edges of non-zero length, the normal is ```(0, 0)```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell has no vertices.
+    pub fn normal_at(&self, t: f64) -> Point {
+        let (edge, local_t) = self.segment_at(t);
+        let normal = match &self.normals[edge] {
+            Some(normal) => normal.clone(),
+            None => return Point { x: 0.0, y: 0.0 },
+        };
+        if local_t > 0.0 {
+            return normal;
+        }
+
+        // the previous edge of non-zero length
+        let n = self.cell.len();
+        let previous = (1..n)
+            .map(|i| (edge + n - i) % n)
+            .find_map(|e| self.normals[e].as_ref());
+        match previous {
+            Some(previous) => {
+                let (x, y) = (normal.x + previous.x, normal.y + previous.y);
+                let bisector_length = x.hypot(y);
+                if bisector_length > 0.0 {
+                    Point {
+                        x: x / bisector_length,
+                        y: y / bisector_length,
+                    }
+                } else {
+                    normal
+                }
+            }
+            None => normal,
+        }
+    }
+
+    fn edge(&self, edge: usize) -> (&'v Point, &'v Point) {
+        let n = self.cell.len();
+        (
+            &self.vertices[self.cell[edge]],
+            &self.vertices[self.cell[(edge + 1) % n]],
+        )
+    }
+
+    /// Wraps ```t``` into ```[0, length)```.
+    fn wrap(&self, t: f64) -> f64 {
+        let length = self.length();
+        if length > 0.0 {
+            let t = t.rem_euclid(length);
+            // the remainder of a tiny negative t may round to length
+            if t < length {
+                t
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::{utils, utils::test::random_voronoi, BoundingBox, Point, VoronoiBuilder};
+
+    #[test]
+    fn vertices_are_at_prefix_sums() {
+        let voronoi = random_voronoi(100);
+        for cell in voronoi.iter_cells() {
+            let walker = cell.perimeter_walker();
+            let vertices: Vec<&Point> = cell.iter_vertices().collect();
+            let mut t = 0.0;
+            for (i, (a, b)) in cell.iter_edges().enumerate() {
+                let (a, b) = (&voronoi.vertices()[a], &voronoi.vertices()[b]);
+                let edge_length = (b.x - a.x).hypot(b.y - a.y);
+                if edge_length > 0.0 {
+                    assert_eq!(walker.segment_at(t), (i, 0.0));
+                    assert_eq!(&walker.position_at(t), vertices[i]);
+                }
+                t += edge_length;
+            }
+
+            assert!(utils::abs_diff_eq(walker.length(), t, 1e-12));
+        }
+    }
+
+    #[test]
+    fn normals_are_outward_unit_vectors() {
+        let mut rng = utils::test::rng();
+        let voronoi = random_voronoi(100);
+        for cell in voronoi.iter_cells() {
+            let walker = cell.perimeter_walker();
+            let centroid = utils::calculate_approximated_cetroid(cell.iter_vertices());
+            for i in 0..20 {
+                // points within edges, and vertices
+                let t = if i % 2 == 0 {
+                    rng.gen_range(0.0..walker.length())
+                } else {
+                    let vertex = rng.gen_range(0..cell.triangles().len());
+                    cell.iter_edges()
+                        .take(vertex)
+                        .map(|(a, b)| {
+                            let (a, b) = (&voronoi.vertices()[a], &voronoi.vertices()[b]);
+                            (b.x - a.x).hypot(b.y - a.y)
+                        })
+                        .sum()
+                };
+
+                let p = walker.position_at(t);
+                let normal = walker.normal_at(t);
+                assert!(utils::abs_diff_eq(normal.x.hypot(normal.y), 1.0, 1e-12));
+                let outward = (p.x - centroid.x) * normal.x + (p.y - centroid.y) * normal.y;
+                assert!(
+                    outward > 0.0,
+                    "Normal {:?} at {:?} of cell {:?}.",
+                    normal,
+                    p,
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn square_normals_and_bisectors() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(vec![
+                Point { x: 0.5, y: 0.5 },
+                Point { x: -0.5, y: 0.5 },
+                Point { x: -0.5, y: -0.5 },
+                Point { x: 0.5, y: -0.5 },
+            ])
+            .build()
+            .expect("Some voronoi expected");
+        let cell = voronoi.cell(0);
+        let walker = cell.perimeter_walker();
+        assert_eq!(walker.length(), 4.0);
+
+        // the corner opposite to the origin, and the middle of the edges around it
+        let corner = (0..4)
+            .map(|i| i as f64)
+            .find(|&t| walker.position_at(t) == Point { x: 1.0, y: 1.0 })
+            .expect("Corner expected at a vertex");
+        let diagonal = std::f64::consts::FRAC_1_SQRT_2;
+        let normal = walker.normal_at(corner);
+        assert!(utils::abs_diff_eq(normal.x, diagonal, 1e-12));
+        assert!(utils::abs_diff_eq(normal.y, diagonal, 1e-12));
+        for t in [corner - 0.5, corner + 0.5] {
+            let p = walker.position_at(t);
+            let normal = walker.normal_at(t);
+            if p.x == 1.0 {
+                assert_eq!(normal, Point { x: 1.0, y: 0.0 });
+            } else {
+                assert_eq!(p.y, 1.0);
+                assert_eq!(normal, Point { x: 0.0, y: 1.0 });
+            }
+        }
+    }
+
+    #[test]
+    fn parameters_wrap_around() {
+        let voronoi = random_voronoi(20);
+        let mut rng = utils::test::rng();
+        for cell in voronoi.iter_cells() {
+            let walker = cell.perimeter_walker();
+            let length = walker.length();
+
+            // continuity across the start of the perimeter
+            let before = walker.position_at(length - 1e-9);
+            let start = walker.position_at(length);
+            let after = walker.position_at(1e-9);
+            assert_eq!(start, walker.position_at(0.0));
+            assert!(utils::dist2(&before, &start).sqrt() < 1e-8);
+            assert!(utils::dist2(&after, &start).sqrt() < 1e-8);
+            assert_eq!(walker.normal_at(length), walker.normal_at(0.0));
+
+            for _ in 0..10 {
+                let t = rng.gen_range(0.0..length);
+                let (edge, local_t) = walker.segment_at(t);
+                for wrapped in [t + length, t - length, t + 3.0 * length] {
+                    let (wrapped_edge, wrapped_t) = walker.segment_at(wrapped);
+                    assert_eq!(wrapped_edge, edge);
+                    assert!(utils::abs_diff_eq(wrapped_t, local_t, 1e-9));
+                    let (p, q) = (walker.position_at(t), walker.position_at(wrapped));
+                    assert!(utils::dist2(&p, &q).sqrt() < 1e-9);
+                }
+            }
+        }
+    }
+}
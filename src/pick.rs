@@ -0,0 +1,593 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::{
+    iterator::shortest_path_iter,
+    utils::{closest_point_on_segment, dist2, EQ_EPSILON},
+    ClipBehavior, ConvexBoundary, Point, TopologicalNeighborSiteIterator, Voronoi,
+};
+
+/// The element of a Voronoi diagram picked by [Voronoi::pick()].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickResult {
+    /// A Voronoi vertex, given as an index into [Voronoi::vertices()], and the sorted sites of the cells meeting at it.
+    Vertex { vertex: usize, cells: Vec<usize> },
+
+    /// The Voronoi edge shared by two cells, given as their sites in ascending order, and the point on the edge closest to the picked point.
+    Edge {
+        cells: (usize, usize),
+        closest: Point,
+    },
+
+    /// The cell of the site containing the picked point.
+    Cell(usize),
+
+    /// The picked point is outside of the diagram and not close to any of its vertices or edges.
+    Outside,
+}
+
+/// A Voronoi edge shared by two cells, oriented as in the cell of the lowest site.
+struct SharedEdge {
+    cells: (usize, usize),
+    vertices: (usize, usize),
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Picks the element of the diagram under ```p```, allowing for a pick ```radius```.
+    ///
+    /// Elements are resolved in priority order: the Voronoi vertex closest to ```p``` within ```radius```, else the Voronoi edge closest to ```p``` within ```radius```,
+    /// else the cell containing ```p```. Only vertices where at least three cells meet and edges shared by two neighboring cells (see [crate::VoronoiCell::iter_neighbors()]) are picked,
+    /// so points close to the boundary resolve to the edge reaching it or to their cell.
+    /// Distance ties are broken by the lowest vertex index or pair of sites, so the result only changes when ```p``` crosses the tie itself.
+    ///
+    /// When clipping is enabled, points outside of the boundary that are not close to a vertex or edge are [PickResult::Outside].
+    /// Otherwise cells are not bounded and every point is contained by the cell of its closest site.
+    /// In a diagram without sites, every point is [PickResult::Outside].
+    pub fn pick(&self, p: &Point, radius: f64) -> PickResult {
+        if self.sites.is_empty() {
+            return PickResult::Outside;
+        }
+
+        let site = self.locate(p);
+        let edges = self.shared_edges_near(site, p, radius);
+        let radius2 = radius * radius;
+
+        let mut vertices: Vec<(f64, usize)> = edges
+            .iter()
+            .flat_map(|e| [e.vertices.0, e.vertices.1])
+            .map(|v| (dist2(&self.circumcenters[v], p), v))
+            .filter(|&(d, _)| d <= radius2)
+            .collect();
+        vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        vertices.dedup();
+        for (_, vertex) in vertices {
+            let cells = self.cells_meeting_at(&edges, vertex);
+            if cells.len() >= 3 {
+                return PickResult::Vertex { vertex, cells };
+            }
+        }
+
+        let edge = edges
+            .iter()
+            .map(|e| {
+                let closest = closest_point_on_segment(
+                    &self.circumcenters[e.vertices.0],
+                    &self.circumcenters[e.vertices.1],
+                    p,
+                );
+                (dist2(&closest, p), e.cells, closest)
+            })
+            .filter(|&(d, _, _)| d <= radius2)
+            .min_by(|(d1, c1, _), (d2, c2, _)| d1.partial_cmp(d2).unwrap().then(c1.cmp(c2)));
+        if let Some((_, cells, closest)) = edge {
+            return PickResult::Edge { cells, closest };
+        }
+
+        if self.clip_behavior == ClipBehavior::Clip && !self.boundary.is_inside(p) {
+            PickResult::Outside
+        } else {
+            PickResult::Cell(site)
+        }
+    }
+
+    /// Finds the site closest to ```p```, by walking the triangulation from site ```0```.
+    fn locate(&self, p: &Point) -> usize {
+        self.locate_from(0, p)
+    }
+
+    /// Finds the site closest to ```p```, by walking the triangulation from site ```start```.
+    ///
+    /// The walk is shorter the closer ```start``` is to ```p```, e.g. the site located for a previous point of a coherent sequence.
+    pub(crate) fn locate_from(&self, start: usize, p: &Point) -> usize {
+        shortest_path_iter(self, start, p.clone())
+            .last()
+            .expect("Path expected to contain at least the starting site")
+    }
+
+    /// Gets the site of the cell containing ```point```, the site closest to it, by walking the triangulation.
+    ///
+    /// Points equidistant to several sites, on a shared Voronoi edge or vertex, are given the lowest of those sites.
+    /// When clipping is enabled, points outside of the boundary are not contained by any cell and are ```None```.
+    /// Otherwise cells are not bounded and every point is contained by the cell of its closest site.
+    /// In a diagram without sites, every point is ```None```.
+    ///
+    /// Prefer [Self::cell_containing_point_from()] to locate a spatially coherent sequence of points, e.g. the pixels of a scanline.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: -0.5, y: 0.0 }, Point { x: 0.5, y: 0.0 }, Point { x: 0.0, y: 0.5 }])
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(v.cell_containing_point(&Point { x: 0.4, y: -0.3 }), Some(1));
+    /// // on the edge shared by the cells of sites 0 and 1
+    /// assert_eq!(v.cell_containing_point(&Point { x: 0.0, y: -0.5 }), Some(0));
+    /// assert_eq!(v.cell_containing_point(&Point { x: 2.0, y: 0.0 }), None);
+    ///```
+    pub fn cell_containing_point(&self, point: &Point) -> Option<usize> {
+        self.cell_containing_point_from(point, 0)
+    }
+
+    /// Same as [Self::cell_containing_point()], but walking the triangulation from the site ```hint```.
+    ///
+    /// The walk is shorter the closer the cell of ```hint``` is to ```point```, so passing the site found for the previous point of a spatially coherent sequence
+    /// makes each query take about constant time. The result is the same for every ```hint```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the diagram has sites and ```hint``` is not one of them.
+    pub fn cell_containing_point_from(&self, point: &Point, hint: usize) -> Option<usize> {
+        if self.sites.is_empty()
+            || (self.clip_behavior == ClipBehavior::Clip && !self.boundary.is_inside(point))
+        {
+            return None;
+        }
+
+        assert!(hint < self.sites.len(), "Site {} does not exist", hint);
+        let closest = self.locate_from(hint, point);
+
+        // sites as close as the closest are connected by the triangulation, as they are on a circle empty of sites around the point
+        let distance = dist2(&self.sites[closest], point);
+        let mut lowest = closest;
+        let mut visited = BTreeSet::from([closest]);
+        let mut stack = vec![closest];
+        while let Some(site) = stack.pop() {
+            lowest = lowest.min(site);
+            for neighbor in TopologicalNeighborSiteIterator::new(self, site) {
+                if dist2(&self.sites[neighbor], point) == distance && visited.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        Some(lowest)
+    }
+
+    /// Gets the sorted sites of the cells sharing an edge that ends at ```vertex```, or at a vertex at the same position.
+    fn cells_meeting_at(&self, edges: &[SharedEdge], vertex: usize) -> Vec<usize> {
+        let position = &self.circumcenters[vertex];
+        edges
+            .iter()
+            .filter(|e| {
+                same_position(&self.circumcenters[e.vertices.0], position)
+                    || same_position(&self.circumcenters[e.vertices.1], position)
+            })
+            .flat_map(|e| [e.cells.0, e.cells.1])
+            .collect::<BTreeSet<usize>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Collects the shared edges of all cells that come within ```radius``` of ```p```, starting from the cell of ```site``` and its neighbors.
+    ///
+    /// The part of the diagram within ```radius``` is connected, so it is enough to expand from cells that have at least one edge within ```radius```.
+    fn shared_edges_near(&self, site: usize, p: &Point, radius: f64) -> Vec<SharedEdge> {
+        let radius2 = radius * radius;
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(site);
+        queue.push_back(site);
+        for neighbor in TopologicalNeighborSiteIterator::new(self, site) {
+            if visited.insert(neighbor) {
+                queue.push_back(neighbor);
+            }
+        }
+
+        let mut edges = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            let cell = self.cell(current);
+            let is_near = cell.iter_edges().any(|(u, v)| {
+                let closest =
+                    closest_point_on_segment(&self.circumcenters[u], &self.circumcenters[v], p);
+                dist2(&closest, p) <= radius2
+            });
+            if !is_near {
+                continue;
+            }
+
+            edges.extend(cell.iter_edge_metadata().filter_map(|e| {
+                let neighbor = e.neighbor?;
+                let (u, v) = e.vertices;
+                Some(if current < neighbor {
+                    SharedEdge {
+                        cells: (current, neighbor),
+                        vertices: (u, v),
+                    }
+                } else {
+                    SharedEdge {
+                        cells: (neighbor, current),
+                        vertices: (v, u),
+                    }
+                })
+            }));
+
+            for neighbor in TopologicalNeighborSiteIterator::new(self, current) {
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        edges
+    }
+}
+
+/// Returns whether two vertices are at the same position, up to the rounding errors of circumcenters calculated from different cocircular triangles.
+fn same_position(a: &Point, b: &Point) -> bool {
+    let tolerance = EQ_EPSILON * (1.0 + a.x.abs().max(a.y.abs()).max(b.x.abs()).max(b.y.abs()));
+    (a.x - b.x).abs() <= tolerance && (a.y - b.y).abs() <= tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{BoundingBox, VoronoiBuilder};
+
+    /// Three sites whose cells meet at vertex ```(0, 0.11 / 1.2)```; cells 0 and 1 share the vertical edge below it, along ```x = 0```.
+    fn small_voronoi() -> Voronoi<BoundingBox> {
+        VoronoiBuilder::default()
+            .set_sites(vec![
+                Point { x: -0.5, y: 0.0 },
+                Point { x: 0.5, y: 0.0 },
+                Point { x: 0.0, y: 0.6 },
+            ])
+            .set_boundary(BoundingBox::new_centered_square(2.0))
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    fn shared_vertex(voronoi: &Voronoi<BoundingBox>) -> (usize, Point) {
+        let vertex = voronoi
+            .cell(0)
+            .iter_triangles()
+            .find(|&v| {
+                voronoi.cell(1).triangles().contains(&v) && voronoi.cell(2).triangles().contains(&v)
+            })
+            .expect("Cells expected to meet at a vertex");
+        (vertex, voronoi.vertices()[vertex].clone())
+    }
+
+    /// Picks by checking every vertex and edge of the diagram.
+    fn brute_force_pick<T: ConvexBoundary>(
+        voronoi: &Voronoi<T>,
+        p: &Point,
+        radius: f64,
+    ) -> PickResult {
+        let everything = (0..voronoi.sites().len()).collect::<Vec<_>>();
+        let edges = everything
+            .iter()
+            .flat_map(|&s| {
+                voronoi.cell(s).iter_edge_metadata().filter_map(move |e| {
+                    e.neighbor.filter(|&n| s < n).map(|n| ((s, n), e.vertices))
+                })
+            })
+            .collect::<Vec<_>>();
+        let radius2 = radius * radius;
+
+        let mut vertices: Vec<(f64, usize)> = edges
+            .iter()
+            .flat_map(|(_, (u, v))| [*u, *v])
+            .map(|v| (dist2(&voronoi.vertices()[v], p), v))
+            .filter(|&(d, _)| d <= radius2)
+            .collect();
+        vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (_, vertex) in vertices {
+            let cells: Vec<usize> = everything
+                .iter()
+                .copied()
+                .filter(|&s| voronoi.cell(s).triangles().contains(&vertex))
+                .collect();
+            if cells.len() >= 3 {
+                return PickResult::Vertex { vertex, cells };
+            }
+        }
+
+        let edge = edges
+            .iter()
+            .map(|&(cells, (u, v))| {
+                let closest =
+                    closest_point_on_segment(&voronoi.vertices()[u], &voronoi.vertices()[v], p);
+                (dist2(&closest, p), cells, closest)
+            })
+            .filter(|&(d, _, _)| d <= radius2)
+            .min_by(|(d1, c1, _), (d2, c2, _)| d1.partial_cmp(d2).unwrap().then(c1.cmp(c2)));
+        if let Some((_, cells, closest)) = edge {
+            return PickResult::Edge { cells, closest };
+        }
+
+        match (0..voronoi.sites().len()).min_by(|&a, &b| {
+            dist2(&voronoi.sites()[a], p)
+                .partial_cmp(&dist2(&voronoi.sites()[b], p))
+                .unwrap()
+        }) {
+            Some(site) if voronoi.boundary().is_inside(p) => PickResult::Cell(site),
+            _ => PickResult::Outside,
+        }
+    }
+
+    #[test]
+    fn picks_vertex_within_radius() {
+        let voronoi = small_voronoi();
+        let (vertex, position) = shared_vertex(&voronoi);
+
+        // approaching from below, along the edge shared by cells 0 and 1
+        for distance in [0.0, 0.01, 0.099] {
+            let p = Point {
+                x: position.x,
+                y: position.y - distance,
+            };
+            assert_eq!(
+                voronoi.pick(&p, 0.1),
+                PickResult::Vertex {
+                    vertex,
+                    cells: vec![0, 1, 2]
+                },
+                "Unexpected pick at distance {}.",
+                distance
+            );
+        }
+    }
+
+    #[test]
+    fn picks_edge_within_radius() {
+        let voronoi = small_voronoi();
+
+        for offset in [-0.099, -0.01, 0.0, 0.01, 0.099] {
+            let p = Point { x: offset, y: -0.5 };
+            match voronoi.pick(&p, 0.1) {
+                PickResult::Edge { cells, closest } => {
+                    assert_eq!(cells, (0, 1));
+                    assert!(closest.x.abs() < 1e-12 && (closest.y + 0.5).abs() < 1e-12);
+                }
+                pick => panic!("Expected edge at offset {}, got {:?}.", offset, pick),
+            }
+        }
+    }
+
+    #[test]
+    fn picks_cell_or_outside_beyond_radius() {
+        let voronoi = small_voronoi();
+
+        assert_eq!(
+            voronoi.pick(&Point { x: -0.5, y: -0.5 }, 0.1),
+            PickResult::Cell(0)
+        );
+        assert_eq!(
+            voronoi.pick(&Point { x: 0.5, y: -0.5 }, 0.1),
+            PickResult::Cell(1)
+        );
+        assert_eq!(
+            voronoi.pick(&Point { x: 0.0, y: 0.8 }, 0.1),
+            PickResult::Cell(2)
+        );
+
+        // boundary corners and edges are not shared by cells
+        assert_eq!(
+            voronoi.pick(&Point { x: -0.99, y: -0.99 }, 0.1),
+            PickResult::Cell(0)
+        );
+        assert_eq!(
+            voronoi.pick(&Point { x: 3.0, y: 0.0 }, 0.1),
+            PickResult::Outside
+        );
+
+        // just outside of the boundary, but close to the edge shared by cells 0 and 1
+        assert!(matches!(
+            voronoi.pick(&Point { x: 0.01, y: -1.02 }, 0.1),
+            PickResult::Edge { cells: (0, 1), .. }
+        ));
+    }
+
+    #[test]
+    fn pick_is_stable_around_radius() {
+        let voronoi = small_voronoi();
+        let (vertex, position) = shared_vertex(&voronoi);
+        let radius = 0.1;
+
+        // just within and just beyond the pick radius of the edge
+        let within = Point {
+            x: radius * (1.0 - 1e-9),
+            y: -0.5,
+        };
+        let beyond = Point {
+            x: radius * (1.0 + 1e-9),
+            y: -0.5,
+        };
+        assert!(matches!(
+            voronoi.pick(&within, radius),
+            PickResult::Edge { cells: (0, 1), .. }
+        ));
+        assert_eq!(voronoi.pick(&beyond, radius), PickResult::Cell(1));
+
+        // along the edge, just within and just beyond the pick radius of the vertex
+        let within = Point {
+            x: position.x,
+            y: position.y - radius * (1.0 - 1e-9),
+        };
+        let beyond = Point {
+            x: position.x,
+            y: position.y - radius * (1.0 + 1e-9),
+        };
+        assert!(
+            matches!(voronoi.pick(&within, radius), PickResult::Vertex { vertex: v, .. } if v == vertex)
+        );
+        assert!(matches!(
+            voronoi.pick(&beyond, radius),
+            PickResult::Edge { cells: (0, 1), .. }
+        ));
+    }
+
+    #[test]
+    fn random_picks_match_brute_force() {
+        let mut rng = crate::utils::test::rng();
+        for _ in 0..10 {
+            let sites = (0..100)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(sites)
+                .build()
+                .expect("Some voronoi expected");
+
+            for _ in 0..50 {
+                let p = Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                };
+                let radius = rng.gen_range(0.0..0.3);
+                assert_eq!(
+                    voronoi.pick(&p, radius),
+                    brute_force_pick(&voronoi, &p, radius),
+                    "Pick mismatch at ({}, {}) with radius {}.",
+                    p.x,
+                    p.y,
+                    radius
+                );
+            }
+        }
+    }
+
+    /// Gets the lowest of the sites closest to ```p``` by checking every site, or ```None``` if ```p``` is outside of the boundary.
+    fn brute_force_cell<T: ConvexBoundary>(voronoi: &Voronoi<T>, p: &Point) -> Option<usize> {
+        (0..voronoi.sites().len())
+            .min_by(|&a, &b| {
+                dist2(&voronoi.sites()[a], p)
+                    .partial_cmp(&dist2(&voronoi.sites()[b], p))
+                    .unwrap()
+                    .then(a.cmp(&b))
+            })
+            .filter(|_| voronoi.boundary().is_inside(p))
+    }
+
+    #[test]
+    fn cell_containing_point_matches_brute_force() -> std::io::Result<()> {
+        let mut rng = crate::utils::test::rng();
+        for asset in crate::utils::test::VALID_ASSETS {
+            let voronoi = crate::utils::test::new_voronoi_builder_from_asset(asset)?
+                .build()
+                .expect("Some voronoi expected");
+            let boundary = voronoi.boundary();
+            let (center, half_width, half_height) = (
+                boundary.center(),
+                boundary.width() / 2.0,
+                boundary.height() / 2.0,
+            );
+
+            let mut hint = 0;
+            for _ in 0..3000 {
+                // some points are outside of the boundary
+                let p = Point {
+                    x: center.x + rng.gen_range(-1.1..1.1) * half_width,
+                    y: center.y + rng.gen_range(-1.1..1.1) * half_height,
+                };
+                let expected = brute_force_cell(&voronoi, &p);
+                assert_eq!(
+                    voronoi.cell_containing_point(&p),
+                    expected,
+                    "Cell mismatch at ({}, {}) of {}",
+                    p.x,
+                    p.y,
+                    asset
+                );
+                assert_eq!(voronoi.cell_containing_point_from(&p, hint), expected);
+                hint = expected.unwrap_or(hint);
+            }
+
+            // hull cells are clipped at the corners of the boundary
+            for corner in boundary.vertices() {
+                let p = Point {
+                    x: corner.x + (center.x - corner.x) * 1e-9,
+                    y: corner.y + (center.y - corner.y) * 1e-9,
+                };
+                let site = voronoi.cell_containing_point(&p);
+                assert!(site.is_some());
+                assert_eq!(site, brute_force_cell(&voronoi, &p));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn cell_containing_point_breaks_ties_by_lowest_site() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(10)
+            .build()
+            .expect("Some voronoi expected");
+
+        // the vertices where four cells of the grid meet, and the midpoints of the edges two cells share
+        for (i, j) in [(2, 3), (5, 5), (0, 8)] {
+            let corner = Point {
+                x: -0.45 + 0.1 * i as f64,
+                y: -0.45 + 0.1 * j as f64,
+            };
+            let tied: Vec<usize> = (0..voronoi.sites().len())
+                .filter(|&s| (dist2(&voronoi.sites()[s], &corner) - 0.005).abs() < 1e-12)
+                .collect();
+            assert_eq!(tied.len(), 4);
+            let expected = brute_force_cell(&voronoi, &corner);
+            for hint in 0..voronoi.sites().len() {
+                assert_eq!(voronoi.cell_containing_point_from(&corner, hint), expected);
+            }
+        }
+
+        let midpoint = Point { x: -0.5, y: -0.45 };
+        for hint in [0, 1, 10, 11, 99] {
+            assert_eq!(voronoi.cell_containing_point_from(&midpoint, hint), Some(0));
+        }
+    }
+
+    #[test]
+    fn cell_containing_point_outside_is_none() {
+        let voronoi = small_voronoi();
+        for p in [
+            Point { x: 1.5, y: 0.0 },
+            Point {
+                x: -1.0 - 1e-9,
+                y: 0.5,
+            },
+            Point { x: 0.2, y: 3.0 },
+        ] {
+            assert_eq!(voronoi.cell_containing_point(&p), None);
+        }
+        assert_eq!(
+            voronoi.cell_containing_point(&Point { x: -0.9, y: -0.9 }),
+            Some(0)
+        );
+
+        let unclipped = VoronoiBuilder::<BoundingBox>::from(&voronoi)
+            .set_clip_behavior(ClipBehavior::None)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(
+            unclipped.cell_containing_point(&Point { x: 1.5, y: 0.0 }),
+            Some(1)
+        );
+    }
+}
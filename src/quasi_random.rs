@@ -0,0 +1,193 @@
+use crate::{ConvexBoundary, Point};
+
+/// The plastic number, the unique real root of ```x³ = x + 1```, whose powers give the R2 sequence the lowest discrepancy.
+const PLASTIC_NUMBER: f64 = 1.324_717_957_244_746;
+
+/// Gets the point of index ```i``` of the [Halton sequence](https://en.wikipedia.org/wiki/Halton_sequence) of ```bases```, in the unit square.
+pub(crate) fn halton(i: u64, bases: (u32, u32)) -> (f64, f64) {
+    (radical_inverse(i, bases.0), radical_inverse(i, bases.1))
+}
+
+/// Gets the point of index ```i``` of the R2 sequence, the additive recurrence of the inverse powers of the plastic number, in the unit square.
+pub(crate) fn r2(i: u64) -> (f64, f64) {
+    let a = (
+        1.0 / PLASTIC_NUMBER,
+        1.0 / (PLASTIC_NUMBER * PLASTIC_NUMBER),
+    );
+    (
+        (0.5 + a.0 * i as f64).fract(),
+        (0.5 + a.1 * i as f64).fract(),
+    )
+}
+
+/// Maps the points ```sequence``` gives for indices from 1 onwards, shifted by ```rotation``` modulo 1, onto the axis-aligned box around the vertices of ```boundary```,
+/// skipping the points outside of it until ```count``` of them are within it.
+pub(crate) fn sequence_sites<T: ConvexBoundary>(
+    boundary: &T,
+    count: usize,
+    rotation: (f64, f64),
+    sequence: impl Fn(u64) -> (f64, f64),
+) -> Vec<Point> {
+    let (min, max) = boundary.vertices().iter().fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), v| {
+            (
+                Point {
+                    x: min.x.min(v.x),
+                    y: min.y.min(v.y),
+                },
+                Point {
+                    x: max.x.max(v.x),
+                    y: max.y.max(v.y),
+                },
+            )
+        },
+    );
+
+    let mut sites = Vec::with_capacity(count);
+    let mut i = 0;
+    while sites.len() < count {
+        i += 1;
+        let (u, v) = sequence(i);
+        let site = Point {
+            x: min.x + (u + rotation.0).rem_euclid(1.0) * (max.x - min.x),
+            y: min.y + (v + rotation.1).rem_euclid(1.0) * (max.y - min.y),
+        };
+        if boundary.is_inside(&site) {
+            sites.push(site);
+        }
+    }
+
+    sites
+}
+
+/// Gets the radical inverse of ```i``` in ```base```: its digits in that base mirrored around the radix point.
+fn radical_inverse(mut i: u64, base: u32) -> f64 {
+    let base = base as u64;
+    let mut inverse = 0.0;
+    let mut scale = 1.0 / base as f64;
+    while i > 0 {
+        inverse += (i % base) as f64 * scale;
+        i /= base;
+        scale /= base as f64;
+    }
+
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{BoundingBox, ConvexPolygon, Voronoi, VoronoiBuilder};
+
+    /// Gets the ratio of the largest to the smallest area of the cells not on the hull, whose areas depend on the boundary.
+    fn area_ratio<T: ConvexBoundary>(voronoi: &Voronoi<T>) -> f64 {
+        let (min, max) = voronoi
+            .iter_cells()
+            .filter(|cell| !cell.is_on_hull())
+            .map(|cell| cell.area())
+            .fold((f64::INFINITY, 0.0_f64), |(min, max), area| {
+                (min.min(area), max.max(area))
+            });
+        max / min
+    }
+
+    #[test]
+    fn radical_inverse_mirrors_digits() {
+        assert_eq!(radical_inverse(0, 2), 0.0);
+        assert_eq!(radical_inverse(1, 2), 0.5);
+        assert_eq!(radical_inverse(6, 2), 0.375);
+        assert_eq!(radical_inverse(5, 3), 2.0 / 3.0 + 1.0 / 9.0);
+    }
+
+    #[test]
+    fn sequences_are_deterministic() {
+        let generate = |rotation| {
+            let halton = VoronoiBuilder::<BoundingBox>::default()
+                .generate_rotated_halton_sites(200, (2, 3), rotation)
+                .build()
+                .expect("Some voronoi expected");
+            let r2 = VoronoiBuilder::<BoundingBox>::default()
+                .generate_rotated_r2_sites(200, rotation)
+                .build()
+                .expect("Some voronoi expected");
+            (halton.sites().clone(), r2.sites().clone())
+        };
+
+        assert_eq!(generate((0.0, 0.0)), generate((0.0, 0.0)));
+        assert_eq!(generate((0.3, 0.7)), generate((0.3, 0.7)));
+        assert_ne!(generate((0.0, 0.0)).0, generate((0.3, 0.7)).0);
+        assert_ne!(generate((0.0, 0.0)).1, generate((0.3, 0.7)).1);
+    }
+
+    #[test]
+    fn exact_count_within_hexagon() {
+        let hexagon = ConvexPolygon::regular(6, 1.0);
+        for count in [1, 7, 250] {
+            for builder in [
+                VoronoiBuilder::default()
+                    .set_boundary(hexagon.clone())
+                    .generate_halton_sites(count, (2, 3)),
+                VoronoiBuilder::default()
+                    .set_boundary(hexagon.clone())
+                    .generate_rotated_halton_sites(count, (3, 5), (0.5, 0.5)),
+                VoronoiBuilder::default()
+                    .set_boundary(hexagon.clone())
+                    .generate_r2_sites(count),
+                VoronoiBuilder::default()
+                    .set_boundary(hexagon.clone())
+                    .generate_rotated_r2_sites(count, (0.9, 0.1)),
+            ] {
+                let voronoi = builder.build().expect("Some voronoi expected");
+                assert_eq!(voronoi.sites().len(), count);
+                assert!(voronoi.sites().iter().all(|s| hexagon.is_inside(s)));
+            }
+        }
+    }
+
+    #[test]
+    fn cells_are_more_even_than_random() {
+        let count = 500;
+        let mut rng = crate::utils::test::rng();
+        let random = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(
+                (0..count)
+                    .map(|_| Point {
+                        x: rng.gen_range(-1.0..1.0),
+                        y: rng.gen_range(-1.0..1.0),
+                    })
+                    .collect(),
+            )
+            .build()
+            .expect("Some voronoi expected");
+        let halton = VoronoiBuilder::<BoundingBox>::default()
+            .generate_halton_sites(count, (2, 3))
+            .build()
+            .expect("Some voronoi expected");
+        let r2 = VoronoiBuilder::<BoundingBox>::default()
+            .generate_r2_sites(count)
+            .build()
+            .expect("Some voronoi expected");
+
+        let random = area_ratio(&random);
+        for ratio in [area_ratio(&halton), area_ratio(&r2)] {
+            assert!(
+                ratio < 5.0 && ratio * 2.5 < random,
+                "Area ratio {} not much smaller than {} of random sites",
+                ratio,
+                random
+            );
+        }
+    }
+}
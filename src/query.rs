@@ -0,0 +1,467 @@
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use crate::{ClipBehavior, ConvexBoundary, Point, Voronoi};
+
+/// The number of points queried by each task, when queries run in parallel.
+#[cfg(feature = "rayon")]
+const CHUNK_SIZE: usize = 1024;
+
+/// A set of the outputs of [Voronoi::query_batch()], combined with ```|```.
+///
+/// # Examples
+///
+///```
+/// use voronoice::*;
+/// let fields = QueryFields::CELL | QueryFields::AREA;
+/// assert!(fields.contains(QueryFields::AREA));
+/// assert!(!fields.contains(QueryFields::CENTROID));
+/// assert_eq!(QueryFields::from_bits_truncate(fields.bits()), fields);
+///```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct QueryFields(u8);
+
+impl QueryFields {
+    /// No outputs.
+    pub const NONE: Self = Self(0);
+
+    /// The site of the cell containing each point, see [QueryOutput::cells].
+    pub const CELL: Self = Self(1);
+
+    /// The area of the cell containing each point, see [QueryOutput::areas].
+    pub const AREA: Self = Self(1 << 1);
+
+    /// The centroid of the cell containing each point, see [QueryOutput::centroids].
+    pub const CENTROID: Self = Self(1 << 2);
+
+    /// The distance from each point to the closest edge of its cell, see [QueryOutput::edge_distances].
+    pub const EDGE_DISTANCE: Self = Self(1 << 3);
+
+    /// All outputs.
+    pub const ALL: Self = Self(0b1111);
+
+    /// Gets the bits of the outputs in this set, e.g. to pass it through a foreign function interface.
+    #[inline]
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Gets the set of the outputs of ```bits```, ignoring the bits that are not outputs.
+    #[inline]
+    pub fn from_bits_truncate(bits: u8) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    /// Returns whether all outputs of ```other``` are in this set.
+    #[inline]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for QueryFields {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for QueryFields {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for QueryFields {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+/// The outputs of [Voronoi::query_batch()], as flat arrays with one entry per point queried, in the order of the points.
+///
+/// Only the outputs requested are written; the others are left as they were. Points outside of the diagram get the sentinel value of each output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryOutput {
+    /// The site of the cell containing each point, as returned by [Voronoi::pick()] with a radius of zero, or [QueryOutput::OUTSIDE].
+    pub cells: Vec<usize>,
+
+    /// The area of the cell containing each point, as returned by [crate::VoronoiCell::area()], or ```NaN```.
+    pub areas: Vec<f64>,
+
+    /// The centroid of the cell containing each point, as returned by [crate::VoronoiCell::centroid()], or ```NaN```.
+    /// Both coordinates of each centroid are stored one after the other, so there are two values per point.
+    pub centroids: Vec<f64>,
+
+    /// The distance from each point to the closest edge of its cell, as returned by [crate::VoronoiCell::distance_to_edges()], or ```NaN```.
+    pub edge_distances: Vec<f64>,
+}
+
+impl QueryOutput {
+    /// The site returned for points outside of the diagram.
+    pub const OUTSIDE: usize = usize::MAX;
+
+    /// Creates outputs with the capacity to query ```points``` points for ```fields```, so queries do not allocate.
+    pub fn with_capacity(fields: QueryFields, points: usize) -> Self {
+        let capacity = |field: QueryFields, per_point: usize| {
+            if fields.contains(field) {
+                points * per_point
+            } else {
+                0
+            }
+        };
+
+        Self {
+            cells: Vec::with_capacity(capacity(QueryFields::CELL, 1)),
+            areas: Vec::with_capacity(capacity(QueryFields::AREA, 1)),
+            centroids: Vec::with_capacity(capacity(QueryFields::CENTROID, 2)),
+            edge_distances: Vec::with_capacity(capacity(QueryFields::EDGE_DISTANCE, 1)),
+        }
+    }
+}
+
+/// The slices of the outputs requested, for a range of the points queried.
+struct Columns<'o> {
+    cells: Option<&'o mut [usize]>,
+    areas: Option<&'o mut [f64]>,
+    centroids: Option<&'o mut [f64]>,
+    edge_distances: Option<&'o mut [f64]>,
+}
+
+impl<'o> Columns<'o> {
+    /// Splits the columns before point ```mid```.
+    #[cfg(feature = "rayon")]
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        fn split<T>(column: Option<&mut [T]>, at: usize) -> (Option<&mut [T]>, Option<&mut [T]>) {
+            match column {
+                Some(column) => {
+                    let (a, b) = column.split_at_mut(at);
+                    (Some(a), Some(b))
+                }
+                None => (None, None),
+            }
+        }
+
+        let (cells, other_cells) = split(self.cells, mid);
+        let (areas, other_areas) = split(self.areas, mid);
+        let (centroids, other_centroids) = split(self.centroids, 2 * mid);
+        let (edge_distances, other_edge_distances) = split(self.edge_distances, mid);
+        (
+            Self {
+                cells,
+                areas,
+                centroids,
+                edge_distances,
+            },
+            Self {
+                cells: other_cells,
+                areas: other_areas,
+                centroids: other_centroids,
+                edge_distances: other_edge_distances,
+            },
+        )
+    }
+}
+
+/// Clears ```column``` and fills it with ```len``` sentinels if the output is requested.
+fn reset<T: Clone>(
+    column: &mut Vec<T>,
+    requested: bool,
+    len: usize,
+    sentinel: T,
+) -> Option<&mut [T]> {
+    if requested {
+        column.clear();
+        column.resize(len, sentinel);
+        Some(column)
+    } else {
+        None
+    }
+}
+
+impl<T: ConvexBoundary + Sync> Voronoi<T> {
+    /// Locates a batch of ```points``` and writes the ```fields``` requested about the cells containing them to ```out```, in a single call.
+    ///
+    /// Each output is the same as its single point counterpart, see [QueryOutput]. Each point is located by walking the triangulation from the site of the previous point,
+    /// so spatially coherent batches (e.g. a scanline or a path) are located faster. With the ```rayon``` feature, the points are split in chunks queried in parallel.
+    ///
+    /// The outputs requested are cleared and resized to the number of points, so if they have the capacity, e.g. from [QueryOutput::with_capacity()] or a previous query,
+    /// and the ```rayon``` feature is disabled, no memory is allocated.
+    /// If the diagram was built with clipping disabled, the cells in the hull are not closed, so their areas, centroids and edge distances are not meaningful.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: -0.5, y: 0.0 }, Point { x: 0.5, y: 0.0 }, Point { x: 0.0, y: 0.5 }])
+    ///     .build()
+    ///     .unwrap();
+    /// let points = [Point { x: -0.9, y: 0.2 }, Point { x: 0.1, y: -0.1 }, Point { x: 2.0, y: 0.0 }];
+    /// let mut out = QueryOutput::default();
+    /// v.query_batch(&points, QueryFields::CELL | QueryFields::EDGE_DISTANCE, &mut out);
+    /// assert_eq!(out.cells, vec![0, 1, QueryOutput::OUTSIDE]);
+    /// assert!((out.edge_distances[1] - 0.1).abs() < 1e-12);
+    /// assert!(out.areas.is_empty());
+    ///```
+    pub fn query_batch(&self, points: &[Point], fields: QueryFields, out: &mut QueryOutput) {
+        if fields == QueryFields::NONE {
+            return;
+        }
+
+        let n = points.len();
+        let columns = Columns {
+            cells: reset(
+                &mut out.cells,
+                fields.contains(QueryFields::CELL),
+                n,
+                QueryOutput::OUTSIDE,
+            ),
+            areas: reset(
+                &mut out.areas,
+                fields.contains(QueryFields::AREA),
+                n,
+                f64::NAN,
+            ),
+            centroids: reset(
+                &mut out.centroids,
+                fields.contains(QueryFields::CENTROID),
+                2 * n,
+                f64::NAN,
+            ),
+            edge_distances: reset(
+                &mut out.edge_distances,
+                fields.contains(QueryFields::EDGE_DISTANCE),
+                n,
+                f64::NAN,
+            ),
+        };
+
+        #[cfg(feature = "rayon")]
+        self.query_parallel(points, columns);
+
+        #[cfg(not(feature = "rayon"))]
+        self.query_range(points, columns);
+    }
+
+    /// Queries the points by halves in parallel, until they fit in a chunk.
+    #[cfg(feature = "rayon")]
+    fn query_parallel(&self, points: &[Point], columns: Columns) {
+        if points.len() <= CHUNK_SIZE {
+            self.query_range(points, columns);
+        } else {
+            let mid = points.len() / 2;
+            let (left, right) = points.split_at(mid);
+            let (left_columns, right_columns) = columns.split_at(mid);
+            rayon::join(
+                || self.query_parallel(left, left_columns),
+                || self.query_parallel(right, right_columns),
+            );
+        }
+    }
+
+    /// Queries the points in order, each located from the site of the previous point inside the diagram.
+    fn query_range(&self, points: &[Point], mut columns: Columns) {
+        let mut hint = 0;
+        for (i, p) in points.iter().enumerate() {
+            // points outside keep the sentinels
+            if self.sites.is_empty()
+                || (self.clip_behavior == ClipBehavior::Clip && !self.boundary.is_inside(p))
+            {
+                continue;
+            }
+
+            let site = self.locate_from(hint, p);
+            hint = site;
+            let cell = self.cell(site);
+            if let Some(cells) = columns.cells.as_deref_mut() {
+                cells[i] = site;
+            }
+            if let Some(areas) = columns.areas.as_deref_mut() {
+                areas[i] = cell.area();
+            }
+            if let Some(centroids) = columns.centroids.as_deref_mut() {
+                let centroid = cell.centroid();
+                centroids[2 * i] = centroid.x;
+                centroids[2 * i + 1] = centroid.y;
+            }
+            if let Some(edge_distances) = columns.edge_distances.as_deref_mut() {
+                edge_distances[i] = cell.distance_to_edges(p);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    use rand::Rng;
+
+    use super::*;
+    use crate::{BoundingBox, ConvexPolygon, PickResult, VoronoiBuilder};
+
+    /// Counts the allocations of each thread, so tests running in parallel do not disturb each other.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    fn random_points(size: usize, range: f64) -> Vec<Point> {
+        let mut rng = crate::utils::test::rng();
+        (0..size)
+            .map(|_| Point {
+                x: rng.gen_range(-range..range),
+                y: rng.gen_range(-range..range),
+            })
+            .collect()
+    }
+
+    fn assert_columns_match_single_queries<T: ConvexBoundary + Sync>(voronoi: &Voronoi<T>) {
+        // some points outside of the boundary too
+        let points = random_points(3000, 1.2);
+        let mut out = QueryOutput::default();
+        voronoi.query_batch(&points, QueryFields::ALL, &mut out);
+        assert_eq!(out.cells.len(), points.len());
+        assert_eq!(out.centroids.len(), 2 * points.len());
+
+        for (i, p) in points.iter().enumerate() {
+            let site = out.cells[i];
+            match voronoi.pick(p, 0.0) {
+                PickResult::Outside => {
+                    assert_eq!(site, QueryOutput::OUTSIDE);
+                    assert!(out.areas[i].is_nan() && out.edge_distances[i].is_nan());
+                    assert!(out.centroids[2 * i].is_nan() && out.centroids[2 * i + 1].is_nan());
+                    continue;
+                }
+                PickResult::Cell(expected) => assert_eq!(site, expected),
+                // exactly on an edge or vertex, in either cell
+                _ => {}
+            }
+
+            let cell = voronoi.cell(site);
+            assert_eq!(out.areas[i], cell.area());
+            let centroid = cell.centroid();
+            assert_eq!(
+                (out.centroids[2 * i], out.centroids[2 * i + 1]),
+                (centroid.x, centroid.y)
+            );
+            assert_eq!(out.edge_distances[i], cell.distance_to_edges(p));
+        }
+    }
+
+    #[test]
+    fn columns_match_single_queries() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_points(500, 1.0))
+            .build()
+            .expect("Some voronoi expected");
+        assert_columns_match_single_queries(&voronoi);
+
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(random_points(500, 1.0))
+            .set_boundary(ConvexPolygon::regular(6, 1.0))
+            .build()
+            .expect("Some voronoi expected");
+        assert_columns_match_single_queries(&voronoi);
+
+        // more points than a chunk, in a coherent scanline order
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_points(200, 1.0))
+            .build()
+            .expect("Some voronoi expected");
+        let points: Vec<Point> = (0..100)
+            .flat_map(|y| {
+                (0..100).map(move |x| Point {
+                    x: x as f64 / 50.0 - 0.99,
+                    y: y as f64 / 50.0 - 0.99,
+                })
+            })
+            .collect();
+        let mut out = QueryOutput::default();
+        voronoi.query_batch(&points, QueryFields::CELL, &mut out);
+        for (p, &site) in points.iter().zip(&out.cells) {
+            assert_eq!(voronoi.pick(p, 0.0), PickResult::Cell(site));
+        }
+    }
+
+    #[test]
+    fn unrequested_columns_are_untouched() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_points(100, 1.0))
+            .build()
+            .expect("Some voronoi expected");
+        let points = random_points(50, 1.0);
+        let mut out = QueryOutput {
+            cells: vec![7; 3],
+            areas: vec![1.5; 4],
+            centroids: vec![2.5; 5],
+            edge_distances: vec![3.5; 6],
+        };
+
+        voronoi.query_batch(
+            &points,
+            QueryFields::AREA | QueryFields::EDGE_DISTANCE,
+            &mut out,
+        );
+        assert_eq!(out.cells, vec![7; 3]);
+        assert_eq!(out.centroids, vec![2.5; 5]);
+        assert_eq!(out.areas.len(), 50);
+        assert_eq!(out.edge_distances.len(), 50);
+
+        voronoi.query_batch(&points, QueryFields::NONE, &mut out);
+        assert_eq!(out.areas.len(), 50);
+        assert_eq!(out.cells, vec![7; 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "rayon"))]
+    fn reserved_outputs_are_not_reallocated() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_points(1000, 1.0))
+            .build()
+            .expect("Some voronoi expected");
+        let points = random_points(5000, 1.2);
+        let mut out = QueryOutput::with_capacity(QueryFields::ALL, points.len());
+
+        let before = ALLOCATIONS.with(|a| a.get());
+        voronoi.query_batch(&points, QueryFields::ALL, &mut out);
+        voronoi.query_batch(
+            &points[..100],
+            QueryFields::CELL | QueryFields::CENTROID,
+            &mut out,
+        );
+        assert_eq!(ALLOCATIONS.with(|a| a.get()), before);
+        assert_eq!(out.cells.len(), 100);
+
+        // without the capacity, the outputs are allocated
+        let mut out = QueryOutput::default();
+        voronoi.query_batch(&points, QueryFields::ALL, &mut out);
+        assert!(ALLOCATIONS.with(|a| a.get()) > before);
+    }
+}
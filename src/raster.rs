@@ -0,0 +1,1116 @@
+//! A minimal software rasterizer for Voronoi diagrams, meant for debugging and visual inspection.
+//!
+//! [Rasterizer] draws the cells of a diagram into a ```0RGB``` pixel buffer, which can be presented directly by windowing libraries such as softbuffer.
+//! It keeps track of the diagram generation it last rendered (see [Voronoi::generation()]) so that, after sites are moved,
+//! only the cells that changed since are drawn again.
+//!
+//! Cells can also be filled from a value per site through a [Colormap], with [Rasterizer::render_style()], which returns the [ChoroplethLegend] to draw alongside.
+//! [Rasterizer::render_style_svg()] exports the same fill as an SVG document through the same view.
+
+use delaunator::EMPTY;
+
+use crate::{
+    editing::Edit, ConvexBoundary, PickResult, Point, RestrictedDiagram, ViewTransform, Voronoi,
+};
+
+/// Renders [Voronoi] diagrams into a pixel buffer, redrawing only the cells that changed between renders when possible.
+///
+/// World coordinates are mapped to pixels through a view: the world point at the center of the buffer and the number of pixels per world unit,
+/// held as a [ViewTransform]. As in the rest of the crate, the Y-axis points downwards, so rows grow with world Y.
+/// Pixel (```x```, ```y```) is covered by the cell containing [ViewTransform::apply_inverse()] of its center, (```x + 0.5```, ```y + 0.5```),
+/// so other output drawn through the same transform lines up with the rendered cells.
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    view: ViewTransform,
+    background: u32,
+    outline: Option<u32>,
+    pixels: Vec<u32>,
+    owners: Vec<usize>,
+
+    /// The generation last rendered, and the neighbors of each cell at that generation.
+    rendered: Option<u64>,
+    neighbors: Vec<Vec<usize>>,
+
+    /// Cells drawn over by [Self::highlight()] since the last render.
+    highlighted: Vec<usize>,
+}
+
+impl Rasterizer {
+    /// Creates a rasterizer for a buffer of ```width``` by ```height``` pixels, viewing the world origin at one pixel per unit.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            view: ViewTransform::new(
+                (width as u32, height as u32),
+                Point { x: 0.0, y: 0.0 },
+                (1.0, 1.0),
+            ),
+            background: 0,
+            outline: None,
+            pixels: vec![0; width * height],
+            owners: vec![EMPTY; width * height],
+            rendered: None,
+            neighbors: Vec::new(),
+            highlighted: Vec::new(),
+        }
+    }
+
+    /// Gets the width of the buffer, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the height of the buffer, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the pixels of the buffer, row by row, as ```0RGB``` values.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Gets the site whose cell was drawn at pixel (```x```, ```y```), or ```None``` if no cell was drawn there or the pixel is out of the buffer.
+    pub fn owner(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(self.owners[y * self.width + x]).filter(|&s| s != EMPTY)
+        } else {
+            None
+        }
+    }
+
+    /// Resizes the buffer, keeping the world point at its center and the scale. The next render draws every cell.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.view = ViewTransform::new(
+            (width as u32, height as u32),
+            self.view.center().clone(),
+            self.view.scale(),
+        );
+        self.pixels = vec![self.background; width * height];
+        self.owners = vec![EMPTY; width * height];
+        self.invalidate();
+    }
+
+    /// Gets the world point at the center of the buffer.
+    pub fn center(&self) -> &Point {
+        self.view.center()
+    }
+
+    /// Gets the number of pixels per world unit, horizontally. See [Self::view_transform()] for the scale of each axis.
+    pub fn scale(&self) -> f64 {
+        self.view.scale().0
+    }
+
+    /// Gets the transform from world coordinates to pixels of this view.
+    pub fn view_transform(&self) -> &ViewTransform {
+        &self.view
+    }
+
+    /// Sets the view: the world point at the center of the buffer and the number of pixels per world unit. The next render draws every cell.
+    pub fn set_view(&mut self, center: Point, scale: f64) {
+        self.set_scaled_view(center, (scale, scale));
+    }
+
+    /// Sets the view to ```transform```, such as one obtained from [Voronoi::view_transform()], resizing the buffer to its viewport if needed.
+    /// The next render draws every cell.
+    pub fn set_view_transform(&mut self, transform: &ViewTransform) {
+        let (width, height) = transform.viewport();
+        if (width as usize, height as usize) != (self.width, self.height) {
+            self.resize(width as usize, height as usize);
+        }
+        self.set_scaled_view(transform.center().clone(), transform.scale());
+    }
+
+    /// Moves the view by ```dx```, ```dy``` pixels, so the world under a pixel moves by that amount on the screen.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let (scale_x, scale_y) = self.view.scale();
+        let center = Point {
+            x: self.center().x - dx / scale_x,
+            y: self.center().y - dy / scale_y,
+        };
+        self.set_scaled_view(center, (scale_x, scale_y));
+    }
+
+    /// Multiplies the scale by ```factor```, keeping the world point under pixel (```x```, ```y```) in place.
+    pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
+        let anchor = self.to_world(x, y);
+        let (scale_x, scale_y) = self.view.scale();
+        let (scale_x, scale_y) = (scale_x * factor, scale_y * factor);
+        let center = Point {
+            x: anchor.x - (x - self.width as f64 / 2.0) / scale_x,
+            y: anchor.y - (y - self.height as f64 / 2.0) / scale_y,
+        };
+        self.set_scaled_view(center, (scale_x, scale_y));
+    }
+
+    fn set_scaled_view(&mut self, center: Point, scale: (f64, f64)) {
+        self.view = ViewTransform::new((self.width as u32, self.height as u32), center, scale);
+        self.invalidate();
+    }
+
+    /// Sets the color of pixels not covered by any cell. The next render draws every cell.
+    pub fn set_background(&mut self, color: u32) {
+        self.background = color;
+        self.invalidate();
+    }
+
+    /// Sets the color cell edges are drawn with, or ```None``` to not draw them. The next render draws every cell.
+    pub fn set_outline(&mut self, color: Option<u32>) {
+        self.outline = color;
+        self.invalidate();
+    }
+
+    /// Converts a position in pixels to world coordinates. The center of pixel (```x```, ```y```) is at (```x + 0.5```, ```y + 0.5```).
+    pub fn to_world(&self, x: f64, y: f64) -> Point {
+        self.view.to_world(x, y)
+    }
+
+    /// Converts world coordinates to a position in pixels.
+    pub fn to_screen(&self, p: &Point) -> (f64, f64) {
+        self.view.to_pixels(p)
+    }
+
+    /// Forgets what was rendered, so the next render draws every cell.
+    pub fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+
+    /// Renders ```voronoi```, filling each cell with ```color(site)```, and returns the number of cells drawn.
+    ///
+    /// If ```voronoi``` is the diagram last rendered, possibly after calls to [Voronoi::move_site()], only the cells highlighted since
+    /// and the cells that changed since, as given by [Voronoi::cell_generation()], are drawn, since no other cell changed shape.
+    /// Otherwise, such as for a different diagram, after sites were inserted, removed or merged, or after the view changed,
+    /// the buffer is cleared and every cell is drawn.
+    pub fn render<T: ConvexBoundary>(
+        &mut self,
+        voronoi: &Voronoi<T>,
+        color: impl Fn(usize) -> u32,
+    ) -> usize {
+        let mut dirty = match self.dirty_cells(voronoi) {
+            Some(dirty) => dirty,
+            None => {
+                let background = self.background;
+                self.pixels.iter_mut().for_each(|p| *p = background);
+                self.owners.iter_mut().for_each(|o| *o = EMPTY);
+                self.neighbors = vec![Vec::new(); voronoi.sites().len()];
+                (0..voronoi.sites().len()).collect()
+            }
+        };
+        dirty.append(&mut self.highlighted);
+        dirty.retain(|&site| site < voronoi.sites().len());
+        dirty.sort_unstable();
+        dirty.dedup();
+
+        for &site in &dirty {
+            self.neighbors[site] = voronoi.cell(site).iter_neighbors().collect();
+            self.fill_cell(voronoi, site, color(site));
+        }
+        if let Some(outline) = self.outline {
+            // filling may have covered the outline of neighboring cells as well
+            let mut outlined = dirty.clone();
+            for &site in &dirty {
+                outlined.extend_from_slice(&self.neighbors[site]);
+            }
+            outlined.sort_unstable();
+            outlined.dedup();
+            for site in outlined {
+                self.outline_cell(voronoi, site, outline);
+            }
+        }
+
+        self.rendered = Some(voronoi.generation());
+        dirty.len()
+    }
+
+    /// Renders the cells kept by ```restricted```, filling each with ```color(site)```, and returns the number of cells drawn.
+    ///
+    /// The buffer is always cleared and every kept cell is drawn, so discarded cells are left as background. The next render draws every cell again.
+    pub fn render_restricted<T: ConvexBoundary>(
+        &mut self,
+        restricted: &RestrictedDiagram<'_, T>,
+        color: impl Fn(usize) -> u32,
+    ) -> usize {
+        let voronoi = restricted.voronoi();
+        let background = self.background;
+        self.pixels.iter_mut().for_each(|p| *p = background);
+        self.owners.iter_mut().for_each(|o| *o = EMPTY);
+        self.highlighted.clear();
+        self.rendered = None;
+
+        for &site in restricted.sites() {
+            self.fill_cell(voronoi, site, color(site));
+        }
+        if let Some(outline) = self.outline {
+            for &site in restricted.sites() {
+                self.outline_cell(voronoi, site, outline);
+            }
+        }
+
+        restricted.len()
+    }
+
+    /// Renders ```voronoi```, filling each cell as set by ```style```, and returns the legend of the colors used.
+    ///
+    /// The buffer is always cleared and every cell is drawn, as the colors may change while the diagram does not.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::{*, raster::*};
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(4)
+    ///     .build()
+    ///     .unwrap();
+    /// let areas: Vec<f64> = voronoi.iter_cells().map(|cell| cell.area()).collect();
+    ///
+    /// let mut rasterizer = Rasterizer::new(64, 64);
+    /// rasterizer.set_view(Point { x: 0.0, y: 0.0 }, 32.0);
+    /// let legend = rasterizer.render_style(
+    ///     &voronoi,
+    ///     &RenderStyle::Choropleth { values: &areas, colormap: Colormap::Viridis, range: None, missing: 0x80_8080 },
+    /// );
+    /// let (min, max) = legend.range.unwrap();
+    /// assert!(min < max);
+    /// assert_eq!(legend.color(max), Colormap::Viridis.color(1.0));
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the values of a [RenderStyle::Choropleth] are fewer than the sites of ```voronoi```.
+    pub fn render_style<T: ConvexBoundary>(
+        &mut self,
+        voronoi: &Voronoi<T>,
+        style: &RenderStyle<'_>,
+    ) -> ChoroplethLegend {
+        let legend = style.legend();
+        let RenderStyle::Choropleth { values, .. } = style;
+        assert!(
+            values.len() >= voronoi.sites().len(),
+            "Expected a value for each of the {} sites, got {}.",
+            voronoi.sites().len(),
+            values.len()
+        );
+
+        self.invalidate();
+        self.render(voronoi, |site| legend.color(values[site]));
+        self.invalidate();
+        legend
+    }
+
+    /// Exports ```voronoi``` as an SVG document the size of the buffer, filling each cell as set by ```style```, and returns it with the legend of the colors used.
+    ///
+    /// Cells are polygons mapped through the view of the rasterizer, over the background, and are outlined if an outline color is set,
+    /// so the document shows the same fill as [Self::render_style()]. The buffer is left untouched. The output only depends on its inputs,
+    /// so it can be snapshot.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::{*, raster::*};
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(4)
+    ///     .build()
+    ///     .unwrap();
+    /// let areas: Vec<f64> = voronoi.iter_cells().map(|cell| cell.area()).collect();
+    ///
+    /// let mut rasterizer = Rasterizer::new(64, 64);
+    /// rasterizer.set_view(Point { x: 0.0, y: 0.0 }, 32.0);
+    /// let (svg, legend) = rasterizer.render_style_svg(
+    ///     &voronoi,
+    ///     &RenderStyle::Choropleth { values: &areas, colormap: Colormap::Viridis, range: None, missing: 0x80_8080 },
+    /// );
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(svg.matches("<polygon").count(), 16);
+    /// assert!(legend.range.is_some());
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the values of a [RenderStyle::Choropleth] are fewer than the sites of ```voronoi```.
+    pub fn render_style_svg<T: ConvexBoundary>(
+        &self,
+        voronoi: &Voronoi<T>,
+        style: &RenderStyle<'_>,
+    ) -> (String, ChoroplethLegend) {
+        let legend = style.legend();
+        let RenderStyle::Choropleth { values, .. } = style;
+        assert!(
+            values.len() >= voronoi.sites().len(),
+            "Expected a value for each of the {} sites, got {}.",
+            voronoi.sites().len(),
+            values.len()
+        );
+
+        let stroke = match self.outline {
+            Some(outline) => format!(
+                r##" stroke="#{:06x}" stroke-width="1""##,
+                outline & 0xff_ffff
+            ),
+            None => String::new(),
+        };
+        let mut svg = format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect width="{width}" height="{height}" fill="#{background:06x}" />"##,
+            width = self.width,
+            height = self.height,
+            background = self.background & 0xff_ffff
+        );
+        for site in 0..voronoi.sites().len() {
+            let cell = voronoi.cell(site);
+            if cell.triangles().len() < 3 {
+                continue;
+            }
+
+            let points = cell
+                .iter_vertices()
+                .map(|v| {
+                    let (x, y) = self.to_screen(v);
+                    format!("{},{}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            svg += &format!(
+                r##"<polygon points="{points}" fill="#{color:06x}"{stroke} />"##,
+                color = legend.color(values[site]) & 0xff_ffff
+            );
+        }
+        svg += "</svg>";
+
+        (svg, legend)
+    }
+
+    /// Draws the element picked by [Voronoi::pick()] with ```color```: the whole cell, the shared edge, or a small square around the vertex.
+    ///
+    /// Only pixels of the cells involved are drawn over, and those cells are drawn again by the next render.
+    pub fn highlight<T: ConvexBoundary>(
+        &mut self,
+        voronoi: &Voronoi<T>,
+        pick: &PickResult,
+        color: u32,
+    ) {
+        match pick {
+            PickResult::Cell(site) => {
+                self.fill_cell(voronoi, *site, color);
+                self.highlighted.push(*site);
+            }
+            PickResult::Edge { cells: (a, b), .. } => {
+                let cell_b = voronoi.cell(*b);
+                let shared = voronoi
+                    .cell(*a)
+                    .iter_edges()
+                    .filter(|&(u, v)| cell_b.iter_edges().any(|(x, y)| x == v && y == u))
+                    .collect::<Vec<_>>();
+                let owners = [*a, *b];
+                for (u, v) in shared {
+                    let (u, v) = (&voronoi.vertices()[u], &voronoi.vertices()[v]);
+                    self.draw_line(u, v, color, |o| owners.contains(&o));
+                }
+                self.highlighted.extend_from_slice(&owners);
+            }
+            PickResult::Vertex { vertex, cells } => {
+                let (x, y) = self.to_screen(&voronoi.vertices()[*vertex]);
+                let (x, y) = (x.floor() as i64, y.floor() as i64);
+                for py in y - 2..=y + 2 {
+                    for px in x - 2..=x + 2 {
+                        self.set_pixel(px, py, color, |o| cells.contains(&o));
+                    }
+                }
+                self.highlighted.extend_from_slice(cells);
+            }
+            PickResult::Outside => {}
+        }
+    }
+
+    /// Returns the cells that need to be drawn again to render ```voronoi```, or ```None``` if every cell does.
+    fn dirty_cells<T: ConvexBoundary>(&self, voronoi: &Voronoi<T>) -> Option<Vec<usize>> {
+        let rendered = self.rendered?;
+        let edits = voronoi.edits_since(rendered)?;
+        if edits
+            .iter()
+            .all(|(_, edit)| matches!(edit, Edit::Moved(_) | Edit::MovedBatch(_)))
+        {
+            // moves keep the index of every site, so the cells to draw are those that changed, including any reshaped by the build options
+            Some(
+                (0..voronoi.sites().len())
+                    .filter(|&site| voronoi.cell_generation(site) > rendered)
+                    .collect(),
+            )
+        } else {
+            // the other edits change the number of sites, and the index of some, so the owners of the pixels drawn no longer match
+            None
+        }
+    }
+
+    fn fill_cell<T: ConvexBoundary>(&mut self, voronoi: &Voronoi<T>, site: usize, color: u32) {
+        let polygon: Vec<(f64, f64)> = voronoi
+            .cell(site)
+            .iter_vertices()
+            .map(|v| self.to_screen(v))
+            .collect();
+        if polygon.len() < 3 {
+            return;
+        }
+
+        let (min_y, max_y) = polygon
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, y)| {
+                (min.min(y), max.max(y))
+            });
+        let first_row = ((min_y - 0.5).ceil().max(0.0)) as usize;
+        let last_row = ((max_y - 0.5).ceil().min(self.height as f64)).max(0.0) as usize;
+
+        for row in first_row..last_row {
+            // pixel centers within [left, right) are covered, so cells sharing an edge do not overlap
+            let y = row as f64 + 0.5;
+            let (left, right) = polygon
+                .iter()
+                .zip(polygon.iter().cycle().skip(1))
+                .filter(|((_, ay), (_, by))| (*ay <= y) != (*by <= y))
+                .map(|((ax, ay), (bx, by))| ax + (y - ay) * (bx - ax) / (by - ay))
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                    (min.min(x), max.max(x))
+                });
+            if left >= right {
+                continue;
+            }
+
+            let first_column = ((left - 0.5).ceil().max(0.0)) as usize;
+            let last_column = ((right - 0.5).ceil().min(self.width as f64)).max(0.0) as usize;
+            for column in first_column..last_column {
+                let i = row * self.width + column;
+                self.pixels[i] = color;
+                self.owners[i] = site;
+            }
+        }
+    }
+
+    fn outline_cell<T: ConvexBoundary>(&mut self, voronoi: &Voronoi<T>, site: usize, color: u32) {
+        let cell = voronoi.cell(site);
+        if cell.triangles().len() < 2 {
+            return;
+        }
+
+        for (u, v) in cell.iter_edges() {
+            let (u, v) = (&voronoi.vertices()[u], &voronoi.vertices()[v]);
+            self.draw_line(u, v, color, |_| true);
+        }
+    }
+
+    /// Draws a line between two world points, only over pixels whose owner passes ```filter```.
+    fn draw_line(&mut self, a: &Point, b: &Point, color: u32, filter: impl Fn(usize) -> bool) {
+        // edges are shared by two cells in opposite directions, draw both the same way
+        let (a, b) = if (a.x, a.y) <= (b.x, b.y) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let (ax, ay) = self.to_screen(a);
+        let (bx, by) = self.to_screen(b);
+
+        // lines are clamped to a margin around the buffer, as very long edges are far out of view
+        let limit = 2.0 * (self.width + self.height) as f64;
+        let steps = (bx - ax).abs().max((by - ay).abs()).ceil().min(4.0 * limit) as usize;
+        for step in 0..=steps {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                step as f64 / steps as f64
+            };
+            let (x, y) = (ax + (bx - ax) * t, ay + (by - ay) * t);
+            self.set_pixel(x.floor() as i64, y.floor() as i64, color, &filter);
+        }
+    }
+
+    fn set_pixel(&mut self, x: i64, y: i64, color: u32, filter: impl Fn(usize) -> bool) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+
+        let i = y as usize * self.width + x as usize;
+        if filter(self.owners[i]) {
+            self.pixels[i] = color;
+        }
+    }
+}
+
+/// Stops of an approximation of the viridis color map, from dark purple to yellow, evenly spaced.
+const VIRIDIS: [u32; 9] = [
+    0x44_0154, 0x47_2c7a, 0x3b_518b, 0x2c_718e, 0x21_908d, 0x27_ad81, 0x5c_c863, 0xaa_dc32,
+    0xfd_e725,
+];
+
+/// Stops of a blue to white to red diverging color map, evenly spaced.
+const DIVERGING: [u32; 9] = [
+    0x21_66ac, 0x43_93c3, 0x92_c5de, 0xd1_e5f0, 0xf7_f7f7, 0xfd_dbc7, 0xf4_a582, 0xd6_604d,
+    0xb2_182b,
+];
+
+/// Stops of a black to white color map.
+const GRAYSCALE: [u32; 2] = [0x00_0000, 0xff_ffff];
+
+/// A color map, from positions in ```[0, 1]``` to ```0RGB``` colors, as the pixels of a [Rasterizer].
+///
+/// Each map is a small table of evenly spaced colors, linearly interpolated between, so the ends of the range get exactly the first and last color of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    /// From dark purple through blue and green to yellow, increasing in lightness, for values of a single sign.
+    Viridis,
+
+    /// From blue through white at the middle to red, for values diverging from a center, such as signed changes.
+    Diverging,
+
+    /// From black to white.
+    Grayscale,
+}
+
+impl Colormap {
+    /// Gets the color at ```t```, clamped to ```[0, 1]```: the first color of the map at 0.0 and the last at 1.0. NaN gets the first color.
+    pub fn color(&self, t: f64) -> u32 {
+        let stops: &[u32] = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Diverging => &DIVERGING,
+            Colormap::Grayscale => &GRAYSCALE,
+        };
+        let position = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) } * (stops.len() - 1) as f64;
+        let i = (position.floor() as usize).min(stops.len() - 2);
+        let fraction = position - i as f64;
+
+        let channel = |color: u32, shift: u32| ((color >> shift) & 0xff) as f64;
+        [16, 8, 0].iter().fold(0, |color, &shift| {
+            let (a, b) = (channel(stops[i], shift), channel(stops[i + 1], shift));
+            color | (((a + (b - a) * fraction).round() as u32) << shift)
+        })
+    }
+}
+
+/// How [Rasterizer::render_style()] fills the cells of a diagram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderStyle<'a> {
+    /// Fills each cell with the color of the value of its site, ```values[site]```, mapped by ```colormap``` over ```range```.
+    ///
+    /// With a range of ```(low, high)```, ```low``` gets the first color of the map and ```high``` the last, and values beyond are clamped.
+    /// If ```range``` is ```None```, it is the lowest and highest of the values. Values that are not finite, such as NaN for missing data,
+    /// are filled with ```missing``` instead, and are ignored when finding the range.
+    Choropleth {
+        values: &'a [f64],
+        colormap: Colormap,
+        range: Option<(f64, f64)>,
+        missing: u32,
+    },
+}
+
+impl RenderStyle<'_> {
+    /// Gets the legend of the colors this style fills cells with, with the range realized from the values if not given.
+    pub fn legend(&self) -> ChoroplethLegend {
+        let RenderStyle::Choropleth {
+            values,
+            colormap,
+            range,
+            missing,
+        } = *self;
+        let range = range.or_else(|| {
+            values
+                .iter()
+                .copied()
+                .filter(|v| v.is_finite())
+                .fold(None, |range, v| match range {
+                    Some((low, high)) => Some((f64::min(low, v), f64::max(high, v))),
+                    None => Some((v, v)),
+                })
+        });
+
+        ChoroplethLegend {
+            range,
+            colormap,
+            missing,
+        }
+    }
+}
+
+/// The normalization of the values of a [RenderStyle::Choropleth], to draw a legend of the colors rendered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChoroplethLegend {
+    /// The values mapped to the first and last colors of the map, or ```None``` if no range was given and no value is finite, so every cell is missing data.
+    pub range: Option<(f64, f64)>,
+
+    /// The color map used.
+    pub colormap: Colormap,
+
+    /// The color of cells whose value is not finite.
+    pub missing: u32,
+}
+
+impl ChoroplethLegend {
+    /// Gets the color a cell of ```value``` is filled with.
+    ///
+    /// If the range is a single value, every finite value gets the color at the middle of the map.
+    pub fn color(&self, value: f64) -> u32 {
+        match self.range {
+            Some((low, high)) if value.is_finite() => {
+                let t = if low == high {
+                    0.5
+                } else {
+                    (value - low) / (high - low)
+                };
+                self.colormap.color(t)
+            }
+            _ => self.missing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        fingerprint::FingerprintHasher, utils, utils::test::random_voronoi, BoundingBox,
+        VoronoiBuilder,
+    };
+
+    const SIZE: usize = 200;
+    const HIGHLIGHT: u32 = 0xff_ffff;
+
+    /// The hash of the pixels of the choropleth rendered by ```choropleth_output_is_deterministic```.
+    const SNAPSHOT: u64 = 16829935641486783249;
+
+    fn color(site: usize) -> u32 {
+        (site as u32 + 1).wrapping_mul(0x9e_3779) & 0xfe_fefe
+    }
+
+    /// A rasterizer viewing the whole default bounding box, with a margin.
+    fn rasterizer() -> Rasterizer {
+        let mut rasterizer = Rasterizer::new(SIZE, SIZE);
+        rasterizer.set_view(Point { x: 0.0, y: 0.0 }, SIZE as f64 / 2.2);
+        rasterizer
+    }
+
+    /// Picks the pixel center, with a radius of half a pixel.
+    fn pick_pixel<T: ConvexBoundary>(
+        rasterizer: &Rasterizer,
+        voronoi: &Voronoi<T>,
+        x: usize,
+        y: usize,
+    ) -> PickResult {
+        let p = rasterizer.to_world(x as f64 + 0.5, y as f64 + 0.5);
+        voronoi.pick(&p, 0.5 / rasterizer.scale())
+    }
+
+    #[test]
+    fn pixels_are_owned_by_picked_cell() {
+        let voronoi = random_voronoi(50);
+        let mut rasterizer = rasterizer();
+        assert_eq!(rasterizer.render(&voronoi, color), 50);
+
+        let mut checked = 0;
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let i = y * SIZE + x;
+                match pick_pixel(&rasterizer, &voronoi, x, y) {
+                    PickResult::Cell(site) => {
+                        checked += 1;
+                        assert_eq!(rasterizer.owner(x, y), Some(site), "Pixel ({}, {}).", x, y);
+                        assert_eq!(rasterizer.pixels()[i], color(site));
+                    }
+                    PickResult::Outside => assert_eq!(rasterizer.owner(x, y), None),
+                    _ => {}
+                }
+            }
+        }
+        assert!(checked > SIZE * SIZE / 2);
+    }
+
+    #[test]
+    fn moving_a_site_redraws_its_neighborhood() {
+        let mut voronoi = random_voronoi(100);
+        let mut rasterizer = rasterizer();
+        rasterizer.render(&voronoi, color);
+        assert_eq!(rasterizer.render(&voronoi, color), 0);
+
+        for (site, x) in [(0, 0.1), (10, -0.3), (50, 0.5)] {
+            let rendered = voronoi.generation();
+            let mut neighborhood: Vec<usize> = voronoi.cell(site).iter_neighbors().collect();
+            voronoi
+                .move_site(site, Point { x, y: -0.2 })
+                .expect("Move expected to succeed");
+            neighborhood.extend(voronoi.cell(site).iter_neighbors());
+            neighborhood.push(site);
+
+            // cells further away may change by rounding errors only, as their vertices are calculated again
+            let changed: Vec<usize> = (0..100)
+                .filter(|&s| voronoi.cell_generation(s) > rendered)
+                .collect();
+            assert!(neighborhood.iter().all(|s| changed.contains(s)));
+            let drawn = rasterizer.render(&voronoi, color);
+            assert!(drawn == changed.len() && drawn < 100);
+
+            let mut fresh = rasterizer.clone();
+            fresh.invalidate();
+            assert_eq!(fresh.render(&voronoi, color), 100);
+            assert!(
+                fresh.pixels() == rasterizer.pixels(),
+                "Pixels differ after moving site {}.",
+                site
+            );
+        }
+
+        // other edits draw everything
+        voronoi.remove_site(0).expect("Remove expected to succeed");
+        assert_eq!(rasterizer.render(&voronoi, color), 99);
+    }
+
+    /// Asserts the pixels are the same as those of drawing every cell again.
+    fn assert_matches_full_render<T: ConvexBoundary>(
+        rasterizer: &Rasterizer,
+        voronoi: &Voronoi<T>,
+    ) {
+        let mut fresh = rasterizer.clone();
+        fresh.invalidate();
+        assert_eq!(fresh.render(voronoi, color), voronoi.sites().len());
+        assert!(fresh.pixels() == rasterizer.pixels());
+    }
+
+    #[test]
+    fn moves_redraw_cells_changed_by_build_options() {
+        // welding changes cells beyond the neighbors of a moved site
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(utils::test::random_sites(100))
+            .set_vertex_welding(1e-2)
+            .build()
+            .expect("Some voronoi expected");
+        let mut rasterizer = rasterizer();
+        rasterizer.render(&voronoi, color);
+        let rendered = voronoi.generation();
+
+        for (site, x) in [(3, 0.2), (40, -0.6), (70, 0.4)] {
+            voronoi
+                .move_site(site, Point { x, y: 0.3 })
+                .expect("Move expected to succeed");
+        }
+        let changed = (0..100)
+            .filter(|&site| voronoi.cell_generation(site) > rendered)
+            .count();
+        assert_eq!(rasterizer.render(&voronoi, color), changed);
+        assert!(changed < 100);
+        assert_matches_full_render(&rasterizer, &voronoi);
+    }
+
+    #[test]
+    fn other_edits_and_view_changes_draw_every_cell() {
+        let mut voronoi = random_voronoi(100);
+        let mut rasterizer = rasterizer();
+        rasterizer.render(&voronoi, color);
+
+        let edits: [fn(&mut Voronoi<BoundingBox>); 4] = [
+            |v| {
+                v.insert_site(Point { x: 0.05, y: 0.07 })
+                    .expect("Insert expected to succeed");
+            },
+            |v| {
+                v.insert_sites(vec![Point { x: -0.15, y: 0.3 }, Point { x: 0.4, y: -0.35 }])
+                    .expect("Insert expected to succeed");
+            },
+            |v| v.remove_site(3).expect("Remove expected to succeed"),
+            |v| {
+                v.merge_sites(4, 5).expect("Merge expected to succeed");
+            },
+        ];
+        for edit in edits {
+            edit(&mut voronoi);
+            assert_eq!(rasterizer.render(&voronoi, color), voronoi.sites().len());
+            assert_matches_full_render(&rasterizer, &voronoi);
+        }
+
+        let views: [fn(&mut Rasterizer); 4] = [
+            |r| r.pan(3.0, -2.0),
+            |r| r.zoom_at(10.0, 20.0, 1.5),
+            |r| r.set_view(Point { x: 0.1, y: 0.0 }, 80.0),
+            |r| r.resize(150, 120),
+        ];
+        for change in views {
+            change(&mut rasterizer);
+            assert_eq!(rasterizer.render(&voronoi, color), voronoi.sites().len());
+            assert_matches_full_render(&rasterizer, &voronoi);
+        }
+    }
+
+    #[test]
+    fn highlight_matches_pick() {
+        let mut rng = utils::test::rng();
+        let voronoi = random_voronoi(30);
+        let mut rasterizer = rasterizer();
+        rasterizer.render(&voronoi, color);
+        let rendered = rasterizer.pixels().to_vec();
+
+        let mut picked = [0; 3];
+        for _ in 0..500 {
+            let (x, y) = (rng.gen_range(0..SIZE), rng.gen_range(0..SIZE));
+            let pick = pick_pixel(&rasterizer, &voronoi, x, y);
+            rasterizer.highlight(&voronoi, &pick, HIGHLIGHT);
+
+            let highlighted = rasterizer.pixels()[y * SIZE + x] == HIGHLIGHT;
+            match pick {
+                PickResult::Outside => assert!(!highlighted),
+                PickResult::Cell(_) => {
+                    picked[0] += 1;
+                    assert!(highlighted);
+                }
+                PickResult::Edge { .. } | PickResult::Vertex { .. } => {
+                    // the edge or vertex passes within half a pixel of the pixel center, so it is drawn on this pixel or next to it
+                    picked[if let PickResult::Edge { .. } = pick {
+                        1
+                    } else {
+                        2
+                    }] += 1;
+                    let near = |dx: i64, dy: i64| {
+                        let (x, y) = (x as i64 + dx, y as i64 + dy);
+                        x >= 0
+                            && y >= 0
+                            && (x as usize) < SIZE
+                            && (y as usize) < SIZE
+                            && rasterizer.pixels()[y as usize * SIZE + x as usize] == HIGHLIGHT
+                    };
+                    assert!(
+                        (-1..=1).any(|dx| (-1..=1).any(|dy| near(dx, dy))),
+                        "{:?} not highlighted.",
+                        pick
+                    );
+                }
+            }
+
+            rasterizer.render(&voronoi, color);
+            assert!(rasterizer.pixels() == &rendered[..]);
+        }
+        assert!(picked[0] > 0 && picked[1] > 0, "Picked {:?}.", picked);
+    }
+
+    #[test]
+    fn renders_assets() {
+        for asset in utils::test::VALID_ASSETS.iter() {
+            let voronoi = utils::test::new_voronoi_builder_from_asset(asset)
+                .expect("Asset expected")
+                .build()
+                .expect("Some voronoi expected");
+            let boundary = voronoi.boundary();
+            let mut rasterizer = Rasterizer::new(SIZE, SIZE);
+            rasterizer.set_view(
+                boundary.center().clone(),
+                SIZE as f64 / boundary.width().max(boundary.height()),
+            );
+            rasterizer.set_outline(Some(0));
+            assert_eq!(rasterizer.render(&voronoi, color), voronoi.sites().len());
+
+            // the boundary fills the view
+            let covered = (0..SIZE)
+                .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+                .filter(|&(x, y)| rasterizer.owner(x, y).is_some())
+                .count();
+            assert!(
+                covered >= SIZE * SIZE * 99 / 100,
+                "Asset {} covers {} pixels.",
+                asset,
+                covered
+            );
+        }
+    }
+
+    const MISSING: u32 = 0x12_3456;
+
+    /// Gets the color of a pixel owned by each cell, checking all the pixels of the cell have the same color.
+    fn cell_colors(rasterizer: &Rasterizer, sites: usize) -> Vec<Option<u32>> {
+        let mut colors = vec![None; sites];
+        for y in 0..rasterizer.height() {
+            for x in 0..rasterizer.width() {
+                if let Some(site) = rasterizer.owner(x, y) {
+                    let pixel = rasterizer.pixels()[y * rasterizer.width() + x];
+                    assert_eq!(*colors[site].get_or_insert(pixel), pixel);
+                }
+            }
+        }
+        colors
+    }
+
+    fn choropleth(values: &[f64], range: Option<(f64, f64)>) -> RenderStyle<'_> {
+        RenderStyle::Choropleth {
+            values,
+            colormap: Colormap::Viridis,
+            range,
+            missing: MISSING,
+        }
+    }
+
+    #[test]
+    fn colormap_ends_are_table_ends() {
+        assert_eq!(Colormap::Viridis.color(0.0), VIRIDIS[0]);
+        assert_eq!(Colormap::Viridis.color(1.0), VIRIDIS[8]);
+        assert_eq!(Colormap::Diverging.color(0.5), DIVERGING[4]);
+        assert_eq!(Colormap::Diverging.color(-3.0), DIVERGING[0]);
+        assert_eq!(Colormap::Grayscale.color(0.5), 0x80_8080);
+        assert_eq!(Colormap::Grayscale.color(7.0), 0xff_ffff);
+    }
+
+    #[test]
+    fn extreme_values_get_end_colors() {
+        let voronoi = random_voronoi(60);
+        let values: Vec<f64> = (0..60).map(|site| (site as f64 - 20.0) * 0.37).collect();
+        let mut rasterizer = rasterizer();
+        let legend = rasterizer.render_style(&voronoi, &choropleth(&values, None));
+        assert_eq!(legend.range, Some((values[0], values[59])));
+
+        let colors = cell_colors(&rasterizer, 60);
+        assert_eq!(colors[0], Some(VIRIDIS[0]));
+        assert_eq!(colors[59], Some(VIRIDIS[8]));
+        for (site, color) in colors.iter().enumerate() {
+            if let Some(color) = color {
+                assert_eq!(*color, legend.color(values[site]));
+            }
+        }
+    }
+
+    #[test]
+    fn missing_values_get_missing_color() {
+        let voronoi = random_voronoi(40);
+        let mut values: Vec<f64> = (0..40).map(|site| site as f64).collect();
+        values[3] = f64::NAN;
+        values[39] = f64::INFINITY;
+        let mut rasterizer = rasterizer();
+        let legend = rasterizer.render_style(&voronoi, &choropleth(&values, None));
+        assert_eq!(legend.range, Some((0.0, 38.0)));
+
+        let colors = cell_colors(&rasterizer, 40);
+        assert_eq!(colors[3], Some(MISSING));
+        assert_eq!(colors[39], Some(MISSING));
+        assert_eq!(colors[38], Some(VIRIDIS[8]));
+        assert_eq!(
+            colors.iter().filter(|&&c| c == Some(MISSING)).count(),
+            2,
+            "Only missing values expected to get the missing color."
+        );
+
+        // without any finite value there is no range, and every cell is missing data
+        let values = vec![f64::NAN; 40];
+        let legend = rasterizer.render_style(&voronoi, &choropleth(&values, None));
+        assert_eq!(legend.range, None);
+        assert!(cell_colors(&rasterizer, 40)
+            .iter()
+            .all(|&c| c == Some(MISSING) || c.is_none()));
+    }
+
+    #[test]
+    fn explicit_range_overrides_values() {
+        let voronoi = random_voronoi(40);
+        let values: Vec<f64> = (0..40).map(|site| site as f64).collect();
+        let mut rasterizer = rasterizer();
+        let legend = rasterizer.render_style(&voronoi, &choropleth(&values, Some((10.0, 20.0))));
+        assert_eq!(legend.range, Some((10.0, 20.0)));
+
+        // values beyond the range are clamped to its ends
+        let colors = cell_colors(&rasterizer, 40);
+        for site in (0..=10).chain(20..40) {
+            let expected = if site <= 10 { VIRIDIS[0] } else { VIRIDIS[8] };
+            assert!(colors[site].is_none() || colors[site] == Some(expected));
+        }
+        assert_eq!(legend.color(15.0), Colormap::Viridis.color(0.5));
+
+        let auto = choropleth(&values, None).legend();
+        assert_eq!(auto.range, Some((0.0, 39.0)));
+        assert_ne!(auto.color(15.0), legend.color(15.0));
+    }
+
+    #[test]
+    fn choropleth_output_is_deterministic() {
+        let hash = |rasterizer: &Rasterizer| {
+            let mut hasher = FingerprintHasher::default();
+            rasterizer
+                .pixels()
+                .iter()
+                .for_each(|&p| hasher.write_u32(p));
+            hasher.finish()
+        };
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(8)
+            .set_lloyd_relaxation_iterations(1)
+            .build()
+            .expect("Some voronoi expected");
+        let values: Vec<f64> = voronoi.iter_cells().map(|cell| cell.area()).collect();
+        let style = RenderStyle::Choropleth {
+            values: &values,
+            colormap: Colormap::Diverging,
+            range: None,
+            missing: MISSING,
+        };
+
+        let mut first = rasterizer();
+        first.set_outline(Some(0));
+        first.render_style(&voronoi, &style);
+
+        // rendering other values first does not change the output
+        let mut second = rasterizer();
+        second.set_outline(Some(0));
+        second.render_style(&voronoi, &choropleth(&values, None));
+        second.render_style(&voronoi, &style);
+        assert_eq!(hash(&first), hash(&second));
+        assert_ne!(hash(&first), {
+            second.render_style(&voronoi, &choropleth(&values, None));
+            hash(&second)
+        });
+        assert_eq!(hash(&first), SNAPSHOT);
+    }
+
+    #[test]
+    fn svg_export_fills_cells_as_rendered() {
+        let voronoi = random_voronoi(60);
+        let mut values: Vec<f64> = (0..60).map(|site| (site as f64 - 20.0) * 0.37).collect();
+        values[7] = f64::NAN;
+        let style = choropleth(&values, None);
+        let mut rasterizer = rasterizer();
+        rasterizer.set_background(0x20_2020);
+        let (svg, legend) = rasterizer.render_style_svg(&voronoi, &style);
+        assert_eq!(legend, style.legend());
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200""#)
+        );
+        assert!(svg.contains(r##"fill="#202020""##));
+        assert!(svg.ends_with("</svg>"));
+
+        // the polygons are in order of site, each filled with the color of its value
+        let fills: Vec<u32> = svg
+            .split(r##"<polygon points=""##)
+            .skip(1)
+            .map(|polygon| {
+                let fill = polygon.split(r##"fill="#"##).nth(1).expect("Fill expected");
+                u32::from_str_radix(&fill[..6], 16).expect("Color expected")
+            })
+            .collect();
+        assert_eq!(fills.len(), 60);
+        assert_eq!(fills[0], VIRIDIS[0]);
+        assert_eq!(fills[59], VIRIDIS[8]);
+        assert_eq!(fills[7], MISSING);
+        for (site, &fill) in fills.iter().enumerate() {
+            assert_eq!(fill, legend.color(values[site]));
+        }
+
+        // the pixels of the same style have the same colors, and the buffer is left untouched by the export
+        assert!(rasterizer.pixels().iter().all(|&p| p == 0));
+        rasterizer.render_style(&voronoi, &style);
+        for (site, color) in cell_colors(&rasterizer, 60).iter().enumerate() {
+            assert!(color.is_none() || *color == Some(fills[site]));
+        }
+        assert_eq!(rasterizer.render_style_svg(&voronoi, &style).0, svg);
+
+        rasterizer.set_outline(Some(0xff_0000));
+        let (outlined, _) = rasterizer.render_style_svg(&voronoi, &style);
+        assert_eq!(outlined.matches(r##"stroke="#ff0000""##).count(), 60);
+        let (explicit, legend) =
+            rasterizer.render_style_svg(&voronoi, &choropleth(&values, Some((0.0, 1.0))));
+        assert_eq!(legend.range, Some((0.0, 1.0)));
+        assert_ne!(explicit, outlined);
+    }
+}
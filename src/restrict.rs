@@ -0,0 +1,274 @@
+use crate::{
+    CellAdjacency, CellEdge, ConvexBoundary, NeighborSiteIterator, Point, Voronoi, VoronoiCell,
+};
+
+/// A view of a diagram restricted to a subset of its cells, whose union may be concave or have several components and holes.
+///
+/// The cells kept have the exact geometry and site indices they have in the diagram, and the cells discarded are dropped from every query.
+/// Use [Voronoi::restrict_to_cells()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct RestrictedDiagram<'v, T: ConvexBoundary> {
+    voronoi: &'v Voronoi<T>,
+    kept: Vec<bool>,
+    sites: Vec<usize>,
+}
+
+impl<'v, T: ConvexBoundary> RestrictedDiagram<'v, T> {
+    /// Gets the diagram this view is restricted from.
+    #[inline]
+    pub fn voronoi(&self) -> &'v Voronoi<T> {
+        self.voronoi
+    }
+
+    /// Gets the sites whose cells are kept, in ascending order.
+    #[inline]
+    pub fn sites(&self) -> &[usize] {
+        &self.sites
+    }
+
+    /// Returns ```true``` if the cell of ```site``` is kept. Sites that do not exist are not kept.
+    #[inline]
+    pub fn is_kept(&self, site: usize) -> bool {
+        self.kept.get(site).copied().unwrap_or(false)
+    }
+
+    /// Gets the number of cells kept.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Returns ```true``` if no cell is kept.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    /// Gets an iterator over the cells kept, in the order of [Self::sites()].
+    pub fn iter_cells(&self) -> impl Iterator<Item = VoronoiCell<'v, T>> + '_ {
+        let voronoi = self.voronoi;
+        self.sites.iter().map(move |&site| voronoi.cell(site))
+    }
+
+    /// Gets an iterator over the neighbors of the cell of ```site``` that are kept, in the order returned by [VoronoiCell::iter_neighbors()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is not kept.
+    pub fn iter_neighbors(&self, site: usize) -> impl Iterator<Item = usize> + '_ {
+        assert!(self.is_kept(site), "Site {} is not kept", site);
+        NeighborSiteIterator::new(self.voronoi, site).filter(move |&neighbor| self.kept[neighbor])
+    }
+
+    /// Gets an iterator that returns a [CellEdge] for each edge of the cell of ```site```, as [VoronoiCell::iter_edge_metadata()] does.
+    ///
+    /// Edges shared with a discarded cell are on the boundary of the kept region, so they have no neighbor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is not kept.
+    pub fn iter_edge_metadata(&self, site: usize) -> impl Iterator<Item = CellEdge> + '_ {
+        assert!(self.is_kept(site), "Site {} is not kept", site);
+        self.voronoi
+            .cell(site)
+            .iter_edge_metadata()
+            .map(move |mut edge| {
+                edge.neighbor = edge.neighbor.filter(|&neighbor| self.kept[neighbor]);
+                edge
+            })
+    }
+
+    /// Builds the cell adjacency graph of the kept cells, indexed by the sites of the diagram.
+    ///
+    /// Only kept cells are neighbors of each other, and discarded sites have no neighbors, so the graph keeps the site indices of the diagram.
+    pub fn adjacency(&self) -> CellAdjacency {
+        CellAdjacency::from_neighbors((0..self.kept.len()).map(|site| {
+            let neighbors: Vec<usize> = if self.kept[site] {
+                self.iter_neighbors(site).collect()
+            } else {
+                Vec::new()
+            };
+            neighbors.into_iter()
+        }))
+    }
+
+    /// Returns ```true``` if the cell of ```site``` is on the hull of the kept region: it is on the hull of the diagram, see [VoronoiCell::is_on_hull()],
+    /// or it shares an edge with a discarded cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is not kept.
+    pub fn is_on_hull(&self, site: usize) -> bool {
+        assert!(self.is_kept(site), "Site {} is not kept", site);
+        let cell = self.voronoi.cell(site);
+        cell.is_on_hull() || cell.iter_neighbors().any(|neighbor| !self.kept[neighbor])
+    }
+
+    /// Gets the closed loops of the boundary of the kept region, made of the edges of kept cells not shared with another kept cell.
+    ///
+    /// These are the loops of [RegionSdf::boundary_loops()](crate::RegionSdf::boundary_loops()) for the kept sites: the last vertex of each loop connects back to the first,
+    /// and loops around holes wind in the opposite direction to the loops around components of the region.
+    pub fn boundary_loops(&self) -> Vec<Vec<Point>> {
+        self.voronoi.region_boundary_loops(&self.kept)
+    }
+
+    /// Gets the total area of the kept cells.
+    pub fn area(&self) -> f64 {
+        self.iter_cells().map(|cell| cell.area()).sum()
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Restricts this diagram to the cells of the sites for which ```keep``` returns ```true```, e.g. to the land cells of a map, discarding the ocean cells.
+    ///
+    /// The union of the cells kept is the region of the restricted diagram, whose cells are still a proper Voronoi partition of it. See [RestrictedDiagram].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // an L-shaped region of three quarters of the cells
+    /// let restricted = voronoi.restrict_to_cells(|site| {
+    ///     let p = &voronoi.sites()[site];
+    ///     p.x < 0.0 || p.y < 0.0
+    /// });
+    /// assert_eq!(restricted.len(), 75);
+    /// assert_eq!(restricted.boundary_loops().len(), 1);
+    /// assert!(restricted.area() < voronoi.iter_cells().map(|c| c.area()).sum());
+    ///```
+    pub fn restrict_to_cells(&self, keep: impl Fn(usize) -> bool) -> RestrictedDiagram<'_, T> {
+        let kept: Vec<bool> = (0..self.sites.len()).map(keep).collect();
+        let sites = (0..self.sites.len()).filter(|&site| kept[site]).collect();
+
+        RestrictedDiagram {
+            voronoi: self,
+            kept,
+            sites,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{raster::Rasterizer, utils, utils::test::random_voronoi, BoundingBox};
+
+    /// Keeps the cells of a ring, so the region is concave and has a hole.
+    fn ring(voronoi: &Voronoi<BoundingBox>) -> RestrictedDiagram<'_, BoundingBox> {
+        voronoi.restrict_to_cells(|site| {
+            let p = &voronoi.sites()[site];
+            (0.16..0.64).contains(&(p.x * p.x + p.y * p.y))
+        })
+    }
+
+    #[test]
+    fn kept_cells_match_parent() {
+        let voronoi = random_voronoi(300);
+        let restricted = ring(&voronoi);
+        assert!(!restricted.is_empty());
+        assert_eq!(restricted.iter_cells().count(), restricted.len());
+
+        let adjacency = restricted.adjacency();
+        assert_eq!(adjacency.len(), voronoi.sites().len());
+        let mut area = 0.0;
+        for site in 0..voronoi.sites().len() {
+            if !restricted.is_kept(site) {
+                assert!(adjacency.neighbors_of(site).is_empty());
+                continue;
+            }
+
+            let cell = voronoi.cell(site);
+            area += cell.area();
+            let expected: Vec<usize> = cell
+                .iter_neighbors()
+                .filter(|&n| restricted.is_kept(n))
+                .collect();
+            assert_eq!(adjacency.neighbors_of(site), expected.as_slice());
+
+            // edges keep their geometry, and only lose the neighbors discarded
+            for (edge, parent) in restricted
+                .iter_edge_metadata(site)
+                .zip(cell.iter_edge_metadata())
+            {
+                assert_eq!(edge.vertices, parent.vertices);
+                assert_eq!(edge.length, parent.length);
+                match parent.neighbor {
+                    Some(n) if restricted.is_kept(n) => assert_eq!(edge.neighbor, Some(n)),
+                    _ => assert_eq!(edge.neighbor, None),
+                }
+            }
+
+            let discarded_neighbor = cell.iter_neighbors().any(|n| !restricted.is_kept(n));
+            assert_eq!(
+                restricted.is_on_hull(site),
+                cell.is_on_hull() || discarded_neighbor
+            );
+        }
+        assert_eq!(restricted.area(), area);
+    }
+
+    #[test]
+    fn boundary_matches_region_of_kept_cells() {
+        let voronoi = random_voronoi(300);
+        let restricted = ring(&voronoi);
+
+        let loops = restricted.boundary_loops();
+        assert_eq!(
+            loops,
+            voronoi.region_sdf(restricted.sites()).boundary_loops()
+        );
+        assert!(loops.len() >= 2, "A ring expected to have a hole");
+
+        // the outer loop and the hole wind in opposite directions, and their signed areas add up to the area of the region
+        let signed_area = |l: &Vec<Point>| -> f64 {
+            l.iter()
+                .zip(l.iter().cycle().skip(1))
+                .map(|(a, b)| a.x * b.y - b.x * a.y)
+                .sum::<f64>()
+                / 2.0
+        };
+        let total: f64 = loops.iter().map(signed_area).sum();
+        assert!(utils::abs_diff_eq(total.abs(), restricted.area(), 1e-9));
+    }
+
+    #[test]
+    fn rasterizer_renders_kept_cells_only() {
+        let voronoi = random_voronoi(200);
+        let restricted = ring(&voronoi);
+        let mut rasterizer = Rasterizer::new(1, 1);
+        rasterizer.set_view_transform(&voronoi.view_transform((200, 200), crate::FitMode::Contain));
+        rasterizer.render(&voronoi, |site| site as u32);
+        assert!(rasterizer
+            .pixels()
+            .iter()
+            .any(|&p| !restricted.is_kept(p as usize)));
+
+        let drawn = rasterizer.render_restricted(&restricted, |site| site as u32);
+        assert_eq!(drawn, restricted.len());
+        let mut covered = 0;
+        for y in 0..200 {
+            for x in 0..200 {
+                match rasterizer.owner(x, y) {
+                    Some(site) => {
+                        assert!(restricted.is_kept(site));
+                        covered += 1;
+                    }
+                    None => assert_eq!(rasterizer.pixels()[y * 200 + x], 0),
+                }
+            }
+        }
+        let fraction = covered as f64 / (200.0 * 200.0);
+        assert!(utils::abs_diff_eq(fraction, restricted.area() / 4.0, 0.02));
+
+        // rendering the whole diagram afterwards draws every cell again
+        assert_eq!(
+            rasterizer.render(&voronoi, |site| site as u32),
+            voronoi.sites().len()
+        );
+    }
+}
@@ -0,0 +1,530 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    utils::{closest_point_on_segment, dist2},
+    ConvexBoundary, Point, Voronoi,
+};
+
+/// The signed distance field of the union of a set of cells of a diagram.
+///
+/// Distances are positive inside the region, negative outside of it, and zero on its boundary. The region may have several components and holes,
+/// and points inside a hole are outside of the region.
+///
+/// Use [Voronoi::region_sdf()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct RegionSdf {
+    /// The closed loops of the boundary of the region. The last vertex of each loop connects back to the first.
+    loops: Vec<Vec<Point>>,
+
+    /// The segments of all loops.
+    segments: Vec<(Point, Point)>,
+
+    /// The corner of the grid with the lowest coordinates.
+    origin: Point,
+
+    /// The width and height of each cell of the grid.
+    cell_size: (f64, f64),
+
+    /// The number of columns and rows of the grid.
+    dimensions: (usize, usize),
+
+    /// Where the segments overlapping each cell of the grid start in ```cell_segments```, row by row, and where the last ends.
+    cell_offsets: Vec<usize>,
+    cell_segments: Vec<usize>,
+
+    /// Where the segments overlapping each row of the grid start in ```row_segments```, and where the last ends.
+    row_offsets: Vec<usize>,
+    row_segments: Vec<usize>,
+
+    /// The bounding box of the boundary of the diagram, as its corners with the lowest and highest coordinates, sampled by [Self::rasterize_sdf()].
+    extent: (Point, Point),
+}
+
+impl RegionSdf {
+    fn new(loops: Vec<Vec<Point>>, extent: (Point, Point)) -> Self {
+        let segments: Vec<(Point, Point)> = loops
+            .iter()
+            .flat_map(|l| {
+                l.iter()
+                    .zip(l.iter().cycle().skip(1))
+                    .map(|(a, b)| (a.clone(), b.clone()))
+            })
+            .collect();
+
+        let mut min = Point {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        };
+        let mut max = Point {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        };
+        for p in segments.iter().flat_map(|(a, b)| [a, b]) {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+
+        // about one segment per cell of the grid
+        let side = ((segments.len() as f64).sqrt().ceil() as usize).max(1);
+        let dimensions = (side, side);
+        let cell_size = if segments.is_empty() {
+            min = Point { x: 0.0, y: 0.0 };
+            (1.0, 1.0)
+        } else {
+            (
+                ((max.x - min.x) / side as f64).max(f64::MIN_POSITIVE),
+                ((max.y - min.y) / side as f64).max(f64::MIN_POSITIVE),
+            )
+        };
+
+        let mut sdf = Self {
+            loops,
+            segments: Vec::new(),
+            origin: min,
+            cell_size,
+            dimensions,
+            cell_offsets: Vec::new(),
+            cell_segments: Vec::new(),
+            row_offsets: Vec::new(),
+            row_segments: Vec::new(),
+            extent,
+        };
+
+        // conservatively register each segment in every cell its bounding box overlaps
+        let ranges: Vec<((usize, usize), (usize, usize))> = segments
+            .iter()
+            .map(|(a, b)| {
+                let (x0, y0) = sdf.grid_cell(a);
+                let (x1, y1) = sdf.grid_cell(b);
+                ((x0.min(x1), x0.max(x1)), (y0.min(y1), y0.max(y1)))
+            })
+            .collect();
+
+        let mut cells = vec![Vec::new(); side * side];
+        let mut rows = vec![Vec::new(); side];
+        for (segment, &((x0, x1), (y0, y1))) in ranges.iter().enumerate() {
+            for y in y0..=y1 {
+                rows[y].push(segment);
+                for x in x0..=x1 {
+                    cells[y * side + x].push(segment);
+                }
+            }
+        }
+
+        let (cell_offsets, cell_segments) = flatten(cells);
+        let (row_offsets, row_segments) = flatten(rows);
+        sdf.cell_offsets = cell_offsets;
+        sdf.cell_segments = cell_segments;
+        sdf.row_offsets = row_offsets;
+        sdf.row_segments = row_segments;
+        sdf.segments = segments;
+        sdf
+    }
+
+    /// Gets the closed loops of the boundary of the region, as their vertices. The last vertex of each loop connects back to the first.
+    ///
+    /// Loops around holes wind in the opposite direction to the loops around components of the region.
+    #[inline]
+    pub fn boundary_loops(&self) -> &[Vec<Point>] {
+        &self.loops
+    }
+
+    /// Gets the signed distance from ```p``` to the boundary of the region: positive inside the region, negative outside of it.
+    ///
+    /// The distance to an empty region is negative infinity everywhere.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let sdf = v.region_sdf(&[0, 1, 2]);
+    /// assert!(sdf.distance(&v.sites()[1]) > 0.0);
+    /// assert!(sdf.distance(&v.sites()[50]) < 0.0);
+    ///```
+    pub fn distance(&self, p: &Point) -> f64 {
+        if self.segments.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+
+        let distance = self.nearest_segment_distance2(p).sqrt();
+        if self.contains(p) {
+            distance
+        } else {
+            -distance
+        }
+    }
+
+    /// Evaluates [Self::distance()] at the center of each pixel of a grid of ```width``` by ```height``` pixels covering the bounding box of the boundary of the diagram.
+    ///
+    /// The distances are returned row by row, from the row with the lowest Y coordinates, so the grid can be uploaded as a texture as is.
+    pub fn rasterize_sdf(&self, width: usize, height: usize) -> Vec<f64> {
+        let (min, max) = &self.extent;
+        let step_x = (max.x - min.x) / width as f64;
+        let step_y = (max.y - min.y) / height as f64;
+
+        let mut distances = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                distances.push(self.distance(&Point {
+                    x: min.x + (x as f64 + 0.5) * step_x,
+                    y: min.y + (y as f64 + 0.5) * step_y,
+                }));
+            }
+        }
+
+        distances
+    }
+
+    /// Gets the cell of the grid containing ```p```, or the closest cell if ```p``` is outside of the grid.
+    fn grid_cell(&self, p: &Point) -> (usize, usize) {
+        let clamp = |v: f64, size: f64, count: usize| {
+            let i = (v / size).floor();
+            if i <= 0.0 {
+                0
+            } else {
+                (i as usize).min(count - 1)
+            }
+        };
+
+        (
+            clamp(p.x - self.origin.x, self.cell_size.0, self.dimensions.0),
+            clamp(p.y - self.origin.y, self.cell_size.1, self.dimensions.1),
+        )
+    }
+
+    /// Gets the squared distance from ```p``` to the closest segment, by visiting rings of cells of the grid around the cell of ```p```
+    /// until no unvisited cell can contain a closer segment.
+    fn nearest_segment_distance2(&self, p: &Point) -> f64 {
+        let (columns, rows) = self.dimensions;
+        let (cx, cy) = self.grid_cell(p);
+        let min_size = self.cell_size.0.min(self.cell_size.1);
+
+        let mut best = f64::INFINITY;
+        for ring in 0..columns.max(rows) {
+            let x0 = cx.saturating_sub(ring);
+            let x1 = (cx + ring).min(columns - 1);
+            let y0 = cy.saturating_sub(ring);
+            let y1 = (cy + ring).min(rows - 1);
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    // only the cells on the ring, the ones inside it were visited before
+                    if x.max(cx) - x.min(cx) != ring && y.max(cy) - y.min(cy) != ring {
+                        continue;
+                    }
+
+                    let cell = y * columns + x;
+                    for &segment in
+                        &self.cell_segments[self.cell_offsets[cell]..self.cell_offsets[cell + 1]]
+                    {
+                        let (a, b) = &self.segments[segment];
+                        best = best.min(segment_distance2(p, a, b));
+                    }
+                }
+            }
+
+            // cells outside of this ring are at least this far from the cell of p, or from its projection on the grid when outside of it
+            let reach = ring as f64 * min_size;
+            if best <= reach * reach {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Gets whether ```p``` is inside the region, by counting the crossings of the boundary on a ray from ```p``` towards increasing X.
+    fn contains(&self, p: &Point) -> bool {
+        let (_, row) = self.grid_cell(p);
+        let mut inside = false;
+        for &segment in &self.row_segments[self.row_offsets[row]..self.row_offsets[row + 1]] {
+            let (a, b) = &self.segments[segment];
+            if (a.y > p.y) != (b.y > p.y) {
+                let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+                if p.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        inside
+    }
+}
+
+/// Flattens lists of indices into offsets and indices.
+fn flatten(lists: Vec<Vec<usize>>) -> (Vec<usize>, Vec<usize>) {
+    let mut offsets = Vec::with_capacity(lists.len() + 1);
+    offsets.push(0);
+    let mut indices = Vec::with_capacity(lists.iter().map(Vec::len).sum());
+    for list in lists {
+        indices.extend(list);
+        offsets.push(indices.len());
+    }
+
+    (offsets, indices)
+}
+
+/// Gets the squared distance from ```p``` to the segment from ```a``` to ```b```.
+fn segment_distance2(p: &Point, a: &Point, b: &Point) -> f64 {
+    dist2(p, &closest_point_on_segment(a, b, p))
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Builds the signed distance field of the union of the cells of ```cells```, e.g. to render a soft mask of a region.
+    ///
+    /// The boundary of the region is made of the edges of its cells that are not shared with another cell of the region, as cells sharing an edge share its vertices.
+    /// These are chained into the loops returned by [RegionSdf::boundary_loops()] and indexed by a grid, so each evaluation only visits the segments close to the point.
+    /// Sites may be repeated in ```cells```. Hull cells are only closed if the diagram was clipped, see [crate::VoronoiCell::iter_edges()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any site of ```cells``` does not exist.
+    pub fn region_sdf(&self, cells: &[usize]) -> RegionSdf {
+        let mut selected = vec![false; self.sites.len()];
+        for &site in cells {
+            selected[site] = true;
+        }
+        let loops = self.region_boundary_loops(&selected);
+
+        let mut min = Point {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        };
+        let mut max = Point {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        };
+        for p in self.boundary.vertices() {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+
+        RegionSdf::new(loops, (min, max))
+    }
+
+    /// Chains the edges of the cells of the ```selected``` sites that are not shared with another selected cell into closed loops.
+    ///
+    /// The last vertex of each loop connects back to the first. Loops around holes wind in the opposite direction to the loops around components of the region.
+    pub(crate) fn region_boundary_loops(&self, selected: &[bool]) -> Vec<Vec<Point>> {
+        let edges: HashSet<(usize, usize)> = (0..self.sites.len())
+            .filter(|&site| selected[site] && !self.cells[site].is_empty())
+            .flat_map(|site| self.cell(site).iter_edges())
+            .filter(|(u, v)| u != v)
+            .collect();
+
+        // an edge is interior to the region if its twin, in the opposite direction, belongs to another cell of the region
+        let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut boundary: Vec<(usize, usize)> = edges
+            .iter()
+            .filter(|(u, v)| !edges.contains(&(*v, *u)))
+            .copied()
+            .collect();
+        // visit edges in a deterministic order, as hash sets are not
+        boundary.sort_unstable();
+        for &(u, v) in &boundary {
+            outgoing.entry(u).or_default().push(v);
+        }
+
+        let mut loops = Vec::new();
+        for &(start, _) in &boundary {
+            while let Some(mut next) = outgoing.get_mut(&start).and_then(Vec::pop) {
+                let mut vertices = vec![self.circumcenters[start].clone()];
+                // a loop may pass through a vertex more than once where components or holes touch, any unused edge continues it
+                while next != start {
+                    vertices.push(self.circumcenters[next].clone());
+                    match outgoing.get_mut(&next).and_then(Vec::pop) {
+                        Some(v) => next = v,
+                        None => break,
+                    }
+                }
+                loops.push(vertices);
+            }
+        }
+
+        loops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox};
+
+    /// Selects the cells of an annulus, so the region has a hole, and of a disjoint disk.
+    fn annulus_and_disk(voronoi: &Voronoi<BoundingBox>) -> Vec<usize> {
+        voronoi
+            .sites()
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| {
+                let r2 = p.x * p.x + p.y * p.y;
+                let d2 = (p.x - 0.8) * (p.x - 0.8) + (p.y + 0.8) * (p.y + 0.8);
+                (0.09..0.36).contains(&r2) || d2 < 0.02
+            })
+            .map(|(site, _)| site)
+            .collect()
+    }
+
+    fn brute_force_distance(sdf: &RegionSdf, p: &Point) -> f64 {
+        sdf.segments
+            .iter()
+            .map(|(a, b)| segment_distance2(p, a, b))
+            .fold(f64::INFINITY, f64::min)
+            .sqrt()
+    }
+
+    #[test]
+    fn sign_matches_containment() {
+        let mut rng = crate::utils::test::rng();
+        for _ in 0..5 {
+            let voronoi = random_voronoi(400);
+            let cells = annulus_and_disk(&voronoi);
+            let mut selected = vec![false; 400];
+            cells.iter().for_each(|&c| selected[c] = true);
+            let sdf = voronoi.region_sdf(&cells);
+
+            for _ in 0..2000 {
+                let p = Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                };
+                let distance = sdf.distance(&p);
+                if distance.abs() < 1e-9 {
+                    continue;
+                }
+
+                // the cell containing p is the one of the closest site
+                let (site, _) = voronoi
+                    .sites()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| (i, dist2(s, &p)))
+                    .fold((0, f64::INFINITY), |a, b| if b.1 < a.1 { b } else { a });
+                assert_eq!(
+                    distance > 0.0,
+                    selected[site],
+                    "Point {:?} has distance {}.",
+                    p,
+                    distance
+                );
+            }
+
+            // the center of the annulus is inside its hole
+            assert!(sdf.distance(&Point { x: 0.0, y: 0.0 }) < 0.0);
+        }
+    }
+
+    #[test]
+    fn magnitude_matches_brute_force() {
+        let mut rng = crate::utils::test::rng();
+        let voronoi = random_voronoi(1000);
+        let cells = annulus_and_disk(&voronoi);
+        let sdf = voronoi.region_sdf(&cells);
+
+        // points outside of the grid of the segments too
+        for _ in 0..5000 {
+            let p = Point {
+                x: rng.gen_range(-2.0..2.0),
+                y: rng.gen_range(-2.0..2.0),
+            };
+            let expected = brute_force_distance(&sdf, &p);
+            assert!(
+                (sdf.distance(&p).abs() - expected).abs() < 1e-12,
+                "Point {:?} expected at {}.",
+                p,
+                expected
+            );
+        }
+
+        let raster = sdf.rasterize_sdf(16, 8);
+        assert_eq!(raster.len(), 16 * 8);
+        // the bounding box is [-1, 1], the center of the last pixel of the first row is at (15/16, -7/8)
+        assert_eq!(
+            raster[15],
+            sdf.distance(&Point {
+                x: 15.0 / 16.0,
+                y: -7.0 / 8.0
+            })
+        );
+    }
+
+    #[test]
+    fn distance_is_continuous_across_boundary() {
+        let mut rng = crate::utils::test::rng();
+        let voronoi = random_voronoi(500);
+        let cells = annulus_and_disk(&voronoi);
+        let sdf = voronoi.region_sdf(&cells);
+
+        // outer boundaries and holes wind in opposite directions, and enclose the area of the cells
+        let signed_area = |l: &Vec<Point>| -> f64 {
+            l.iter()
+                .zip(l.iter().cycle().skip(1))
+                .map(|(a, b)| a.x * b.y - b.x * a.y)
+                .sum::<f64>()
+                / 2.0
+        };
+        let loop_area: f64 = sdf.boundary_loops().iter().map(signed_area).sum();
+        let cell_area: f64 = cells.iter().map(|&c| voronoi.cell(c).area()).sum();
+        assert!((loop_area.abs() - cell_area).abs() < 1e-9);
+        assert!(sdf.boundary_loops().len() >= 3);
+
+        for (a, b) in &sdf.segments {
+            // away from the vertices, where stepping off one segment may cross another
+            let length = dist2(a, b).sqrt();
+            if length < 1e-5 {
+                continue;
+            }
+            let t = rng.gen_range(0.1..0.9);
+            let p = Point {
+                x: a.x + t * (b.x - a.x),
+                y: a.y + t * (b.y - a.y),
+            };
+            assert!(sdf.distance(&p).abs() < 1e-12);
+
+            // stepping off the boundary changes the distance by the step, with opposite signs on each side
+            let normal = Point {
+                x: -(b.y - a.y) / length * 1e-7,
+                y: (b.x - a.x) / length * 1e-7,
+            };
+            let left = sdf.distance(&Point {
+                x: p.x + normal.x,
+                y: p.y + normal.y,
+            });
+            let right = sdf.distance(&Point {
+                x: p.x - normal.x,
+                y: p.y - normal.y,
+            });
+            assert!(left.abs() <= 1.01e-7 && right.abs() <= 1.01e-7);
+            assert!(
+                left * right <= 0.0,
+                "{} and {} have the same sign.",
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn empty_and_whole_regions() {
+        let voronoi = random_voronoi(50);
+        let empty = voronoi.region_sdf(&[]);
+        assert!(empty.boundary_loops().is_empty());
+        assert_eq!(empty.distance(&Point { x: 0.0, y: 0.0 }), f64::NEG_INFINITY);
+
+        // the whole diagram is bounded by the boundary only
+        let all: Vec<usize> = (0..50).chain(0..50).collect();
+        let whole = voronoi.region_sdf(&all);
+        assert_eq!(whole.boundary_loops().len(), 1);
+        let d = whole.distance(&Point { x: 0.0, y: 0.5 });
+        assert!((d - 0.5).abs() < 1e-12);
+    }
+}
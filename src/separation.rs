@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use crate::{utils::dist2, BuildError, BuildWarning, Point};
+
+/// What to do with sites closer to each other than the minimum separation set by [crate::VoronoiBuilder::set_min_separation()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeparationPolicy {
+    /// Fail the build with [BuildError::SitesTooClose], listing every pair of sites that is too close.
+    Error,
+
+    /// Keep the sites in the order given, dropping each one that is too close to a site already kept.
+    /// Each dropped site is reported as [BuildWarning::DroppedSite].
+    DropLater,
+
+    /// Replace each cluster of sites that are too close to each other by their centroid, reported as [BuildWarning::MergedSites].
+    /// Clusters are repeatedly merged until no two sites are too close, since a centroid may be too close to another site.
+    Average,
+}
+
+/// Enforces a minimum separation of ```distance``` between ```sites``` per ```policy```, returning the sites kept and the warnings of what was done.
+///
+/// Pairs of sites strictly closer than ```distance``` violate the separation. The indices reported are those of ```sites```.
+pub(crate) fn enforce_min_separation(
+    sites: Vec<Point>,
+    distance: f64,
+    policy: SeparationPolicy,
+) -> Result<(Vec<Point>, Vec<BuildWarning>), BuildError> {
+    match policy {
+        SeparationPolicy::Error => {
+            let pairs = violating_pairs(&sites, distance);
+            if pairs.is_empty() {
+                Ok((sites, Vec::new()))
+            } else {
+                Err(BuildError::SitesTooClose(pairs))
+            }
+        }
+        SeparationPolicy::DropLater => Ok(drop_later(sites, distance)),
+        SeparationPolicy::Average => Ok(average(sites, distance)),
+    }
+}
+
+/// A uniform grid of sites, with cells as large as the separation, so sites too close to each other are in the same or adjacent cells.
+struct SiteGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SiteGrid {
+    fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn key(&self, p: &Point) -> (i64, i64) {
+        (
+            (p.x / self.cell_size).floor() as i64,
+            (p.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, site: usize, p: &Point) {
+        self.cells.entry(self.key(p)).or_default().push(site);
+    }
+
+    /// Calls ```f``` with every site inserted in the cell of ```p``` or one of its eight adjacent cells.
+    fn for_each_near(&self, p: &Point, mut f: impl FnMut(usize)) {
+        let (x, y) = self.key(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(sites) = self
+                    .cells
+                    .get(&(x.saturating_add(dx), y.saturating_add(dy)))
+                {
+                    sites.iter().copied().for_each(&mut f);
+                }
+            }
+        }
+    }
+}
+
+/// Gets every pair of sites closer than ```distance```, with the lowest index first, and their distance, sorted by index.
+fn violating_pairs(sites: &[Point], distance: f64) -> Vec<(usize, usize, f64)> {
+    let mut pairs = Vec::new();
+    if distance.is_nan() || distance <= 0.0 {
+        return pairs;
+    }
+
+    let distance2 = distance * distance;
+    let mut grid = SiteGrid::new(distance);
+    for (site, p) in sites.iter().enumerate() {
+        grid.for_each_near(p, |other| {
+            let d2 = dist2(p, &sites[other]);
+            if d2 < distance2 {
+                pairs.push((other, site, d2.sqrt()));
+            }
+        });
+        grid.insert(site, p);
+    }
+
+    pairs.sort_unstable_by_key(|&(a, b, _)| (a, b));
+    pairs
+}
+
+fn drop_later(sites: Vec<Point>, distance: f64) -> (Vec<Point>, Vec<BuildWarning>) {
+    if distance.is_nan() || distance <= 0.0 {
+        return (sites, Vec::new());
+    }
+
+    let distance2 = distance * distance;
+    let mut grid = SiteGrid::new(distance);
+    let mut warnings = Vec::new();
+    let mut kept = Vec::with_capacity(sites.len());
+    for (site, p) in sites.iter().enumerate() {
+        let mut closest: Option<(usize, f64)> = None;
+        grid.for_each_near(p, |other| {
+            let d2 = dist2(p, &sites[other]);
+            if d2 < distance2 && !matches!(closest, Some((_, c)) if c <= d2) {
+                closest = Some((other, d2));
+            }
+        });
+
+        match closest {
+            Some((other, d2)) => warnings.push(BuildWarning::DroppedSite {
+                site,
+                kept: other,
+                distance: d2.sqrt(),
+            }),
+            None => {
+                grid.insert(site, p);
+                kept.push(p.clone());
+            }
+        }
+    }
+
+    (kept, warnings)
+}
+
+fn average(sites: Vec<Point>, distance: f64) -> (Vec<Point>, Vec<BuildWarning>) {
+    // the sites given that were merged into each current site
+    let mut groups: Vec<Vec<usize>> = (0..sites.len()).map(|s| vec![s]).collect();
+    let original = sites.clone();
+    let mut sites = sites;
+
+    loop {
+        let pairs = violating_pairs(&sites, distance);
+        if pairs.is_empty() {
+            break;
+        }
+
+        let mut parents: Vec<usize> = (0..sites.len()).collect();
+        for &(a, b, _) in &pairs {
+            let (a, b) = (find(&mut parents, a), find(&mut parents, b));
+            // the root of each cluster is its lowest site, so merged sites take its place
+            parents[a.max(b)] = a.min(b);
+        }
+
+        let mut merged_groups: Vec<Vec<usize>> = Vec::new();
+        let mut merged_index = vec![usize::MAX; sites.len()];
+        for site in 0..sites.len() {
+            let root = find(&mut parents, site);
+            if root == site {
+                merged_index[site] = merged_groups.len();
+                merged_groups.push(std::mem::take(&mut groups[site]));
+            } else {
+                let group = std::mem::take(&mut groups[site]);
+                merged_groups[merged_index[root]].extend(group);
+            }
+        }
+
+        // each centroid is calculated from the sites given, so merging in several rounds is the same as merging all at once
+        sites = merged_groups
+            .iter()
+            .map(|group| {
+                let (x, y) = group.iter().fold((0.0, 0.0), |(x, y), &s| {
+                    (x + original[s].x, y + original[s].y)
+                });
+                let n = group.len() as f64;
+                Point { x: x / n, y: y / n }
+            })
+            .collect();
+        groups = merged_groups;
+    }
+
+    let warnings = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort_unstable();
+            BuildWarning::MergedSites { sites: group }
+        })
+        .collect();
+
+    (sites, warnings)
+}
+
+fn find(parents: &mut [usize], mut v: usize) -> usize {
+    while parents[v] != v {
+        parents[v] = parents[parents[v]];
+        v = parents[v];
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Voronoi, VoronoiBuilder};
+
+    const SEPARATION: f64 = 0.05;
+
+    /// A grid of ```100``` well separated sites followed by planted violations: two close pairs and a cluster of five mutually close sites.
+    fn planted_sites() -> Vec<Point> {
+        let mut sites: Vec<Point> = (0..100)
+            .map(|i| Point {
+                x: (i % 10) as f64 * 0.18 - 0.81,
+                y: (i / 10) as f64 * 0.18 - 0.81,
+            })
+            .collect();
+        // pairs with sites 11 and 56
+        sites.push(Point {
+            x: sites[11].x + 0.02,
+            y: sites[11].y,
+        });
+        sites.push(Point {
+            x: sites[56].x,
+            y: sites[56].y - 0.03,
+        });
+        // cluster between sites 33, 34, 43 and 44
+        for (dx, dy) in [
+            (0.0, 0.0),
+            (0.01, 0.0),
+            (0.0, 0.01),
+            (-0.01, 0.0),
+            (0.0, -0.01),
+        ] {
+            sites.push(Point {
+                x: -0.18 + dx,
+                y: -0.18 + dy,
+            });
+        }
+        sites
+    }
+
+    fn build(policy: SeparationPolicy) -> Result<Voronoi<BoundingBox>, BuildError> {
+        VoronoiBuilder::default()
+            .set_sites(planted_sites())
+            .set_min_separation(SEPARATION, policy)
+            .try_build()
+    }
+
+    fn assert_separated(voronoi: &Voronoi<BoundingBox>) {
+        let sites = voronoi.sites();
+        for (i, a) in sites.iter().enumerate() {
+            for b in &sites[i + 1..] {
+                assert!(dist2(a, b).sqrt() >= SEPARATION);
+            }
+        }
+    }
+
+    fn brute_force_pairs(sites: &[Point]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..sites.len() {
+            for j in i + 1..sites.len() {
+                if dist2(&sites[i], &sites[j]).sqrt() < SEPARATION {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn error_reports_all_pairs() {
+        let pairs = match build(SeparationPolicy::Error) {
+            Err(BuildError::SitesTooClose(pairs)) => pairs,
+            r => panic!("Separation error expected, got {:?}", r.map(|_| ())),
+        };
+        let sites = planted_sites();
+        assert_eq!(
+            pairs.iter().map(|&(a, b, _)| (a, b)).collect::<Vec<_>>(),
+            brute_force_pairs(&sites)
+        );
+        assert!(pairs.contains(&(11, 100, dist2(&sites[11], &sites[100]).sqrt())));
+        assert!(pairs.iter().any(|&(a, b, _)| (a, b) == (56, 101)));
+        // every pair of the cluster is too close
+        assert_eq!(pairs.len(), 2 + 10);
+
+        assert!(VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(10)
+            .set_min_separation(SEPARATION, SeparationPolicy::Error)
+            .try_build()
+            .is_ok());
+    }
+
+    #[test]
+    fn drop_later_keeps_earlier_sites() {
+        let voronoi = build(SeparationPolicy::DropLater).expect("Some voronoi expected");
+        let dropped: Vec<(usize, usize)> = voronoi
+            .build_warnings()
+            .iter()
+            .map(|w| match w {
+                BuildWarning::DroppedSite { site, kept, .. } => (*site, *kept),
+                w => panic!("Unexpected warning {:?}", w),
+            })
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                (100, 11),
+                (101, 56),
+                (103, 102),
+                (104, 102),
+                (105, 102),
+                (106, 102)
+            ]
+        );
+
+        let sites = planted_sites();
+        assert_eq!(voronoi.sites().len(), 101);
+        assert_eq!(&voronoi.sites()[..100], &sites[..100]);
+        assert_eq!(voronoi.sites()[100], sites[102]);
+        assert_separated(&voronoi);
+    }
+
+    #[test]
+    fn average_merges_clusters() {
+        let voronoi = build(SeparationPolicy::Average).expect("Some voronoi expected");
+        let groups: Vec<Vec<usize>> = voronoi
+            .build_warnings()
+            .iter()
+            .map(|w| match w {
+                BuildWarning::MergedSites { sites } => sites.clone(),
+                w => panic!("Unexpected warning {:?}", w),
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![vec![11, 100], vec![56, 101], vec![102, 103, 104, 105, 106]]
+        );
+
+        let sites = planted_sites();
+        assert_eq!(voronoi.sites().len(), 101);
+        assert_eq!(
+            voronoi.sites()[11],
+            Point {
+                x: (sites[11].x + sites[100].x) / 2.0,
+                y: (sites[11].y + sites[100].y) / 2.0,
+            }
+        );
+        let cluster = &voronoi.sites()[100];
+        assert!((cluster.x + 0.18).abs() < 1e-12 && (cluster.y + 0.18).abs() < 1e-12);
+        assert_separated(&voronoi);
+    }
+
+    #[test]
+    fn centroids_too_close_are_merged_again() {
+        // the centroid of the first two sites is too close to the third one, which is far enough from both
+        let sites = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.04, y: 0.0 },
+            Point { x: 0.02, y: 0.048 },
+        ];
+        assert_eq!(violating_pairs(&sites, SEPARATION).len(), 1);
+        let (merged, warnings) = average(sites, SEPARATION);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(
+            warnings,
+            vec![BuildWarning::MergedSites {
+                sites: vec![0, 1, 2]
+            }]
+        );
+        assert!((merged[0].x - 0.02).abs() < 1e-12 && (merged[0].y - 0.016).abs() < 1e-12);
+    }
+}
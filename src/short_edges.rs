@@ -0,0 +1,274 @@
+use delaunator::{next_halfedge, prev_halfedge, EMPTY};
+
+use crate::{
+    cell_builder::CellBuilderArena, incremental::SiteChanges, utils::dist2, BuildWarning,
+    ClipBehavior, ConvexBoundary, Point, Voronoi,
+};
+
+/// How much longer than the minimum length a nudged edge is made, to keep it above the threshold despite floating point errors.
+const NUDGE_MARGIN: f64 = 1.05;
+
+/// Voronoi edge between the circumcenters of the two triangles sharing ```halfedge```.
+struct ShortEdge {
+    halfedge: usize,
+    length: f64,
+}
+
+/// Perturbs sites so that no Voronoi edge is shorter than ```min_edge_length```, updating the diagram after each pass.
+///
+/// Short edges are dual to a Delaunay edge whose four surrounding sites are nearly cocircular.
+/// For each short edge, one of the two sites opposite the Delaunay edge is moved radially, onto the circle that places the circumcenter of its triangle
+/// ```min_edge_length``` away from the circumcenter of the other triangle. The candidate requiring the smallest displacement is picked.
+/// Sites involved in one nudge are not touched again in the same pass.
+/// The diagram is updated in place around the nudged sites, see [Voronoi::update_sites()], and only built again from all of its sites if that fails.
+///
+/// Returns the resulting diagram and the number of passes performed, which is never greater than ```max_passes```.
+pub(crate) fn eliminate_short_edges<T: ConvexBoundary>(
+    mut voronoi: Voronoi<T>,
+    min_edge_length: f64,
+    max_passes: usize,
+) -> (Voronoi<T>, usize) {
+    let mut passes = 0;
+    let mut arena = CellBuilderArena::default();
+
+    while passes < max_passes {
+        let short_edges = find_short_edges(&voronoi, min_edge_length);
+        if short_edges.is_empty() {
+            break;
+        }
+
+        passes += 1;
+        let mut changes = SiteChanges::default();
+        let mut touched = vec![false; voronoi.sites.len()];
+
+        for edge in short_edges {
+            let quadruple = quadruple_of(&voronoi, edge.halfedge);
+            if quadruple.iter().any(|&s| touched[s]) {
+                continue;
+            }
+
+            if let Some((site, position)) =
+                nudge(&voronoi, edge.halfedge, min_edge_length * NUDGE_MARGIN)
+            {
+                changes.moved.push((site, position));
+                quadruple.iter().for_each(|&s| touched[s] = true);
+            }
+        }
+
+        if changes.moved.is_empty() {
+            break;
+        }
+
+        if voronoi.update_sites(&changes).is_some() {
+            continue;
+        }
+        match Voronoi::new(
+            changes.apply(&voronoi.sites),
+            voronoi.boundary.clone(),
+            voronoi.clip_behavior,
+            voronoi.adjacency_epsilon,
+            voronoi.engine,
+            &mut arena,
+        ) {
+            Some(v) => voronoi = v,
+            None => break,
+        }
+    }
+
+    voronoi.warnings = find_short_edges(&voronoi, min_edge_length)
+        .into_iter()
+        .map(|edge| BuildWarning::ShortEdge {
+            sites: (
+                voronoi.triangulation.triangles[edge.halfedge],
+                voronoi.triangulation.triangles[next_halfedge(edge.halfedge)],
+            ),
+            length: edge.length,
+        })
+        .collect();
+
+    (voronoi, passes)
+}
+
+/// Finds the Voronoi edges shorter than ```min_edge_length```.
+///
+/// Only edges between two circumcenters are considered. When clipping, edges with a circumcenter outside of the boundary are clipped and not considered.
+fn find_short_edges<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    min_edge_length: f64,
+) -> Vec<ShortEdge> {
+    let triangulation = &voronoi.triangulation;
+    (0..triangulation.halfedges.len())
+        .filter(|&e| triangulation.halfedges[e] != EMPTY && triangulation.halfedges[e] > e)
+        .filter_map(|e| {
+            let c1 = &voronoi.circumcenters[e / 3];
+            let c2 = &voronoi.circumcenters[triangulation.halfedges[e] / 3];
+            if voronoi.clip_behavior == ClipBehavior::Clip
+                && !(voronoi.boundary.is_inside(c1) && voronoi.boundary.is_inside(c2))
+            {
+                return None;
+            }
+
+            let length = dist2(c1, c2).sqrt();
+            if length < min_edge_length {
+                Some(ShortEdge {
+                    halfedge: e,
+                    length,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the sites ```[a, b, c, d]``` around a Delaunay edge ```a -> b```, where ```c``` and ```d``` are the opposite sites of the triangles sharing the edge.
+fn quadruple_of<T: ConvexBoundary>(voronoi: &Voronoi<T>, halfedge: usize) -> [usize; 4] {
+    let triangles = &voronoi.triangulation.triangles;
+    let opposite = voronoi.triangulation.halfedges[halfedge];
+    [
+        triangles[halfedge],
+        triangles[next_halfedge(halfedge)],
+        triangles[prev_halfedge(halfedge)],
+        triangles[prev_halfedge(opposite)],
+    ]
+}
+
+/// Calculates the smallest nudge of one of the sites opposite to ```halfedge``` that makes its dual Voronoi edge ```target_length``` long.
+fn nudge<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    halfedge: usize,
+    target_length: f64,
+) -> Option<(usize, Point)> {
+    let [a, b, c, d] = quadruple_of(voronoi, halfedge);
+    let opposite = voronoi.triangulation.halfedges[halfedge];
+
+    // moving the apex of one triangle moves its circumcenter away from the circumcenter of the other triangle
+    [
+        (d, &voronoi.circumcenters[halfedge / 3]),
+        (c, &voronoi.circumcenters[opposite / 3]),
+    ]
+    .iter()
+    .filter_map(|&(apex, fixed_circumcenter)| {
+        let position = nudge_apex(
+            &voronoi.sites[a],
+            &voronoi.sites[b],
+            &voronoi.sites[apex],
+            fixed_circumcenter,
+            target_length,
+        )?;
+
+        if voronoi.clip_behavior == ClipBehavior::None || voronoi.boundary.is_inside(&position) {
+            Some((apex, position))
+        } else {
+            None
+        }
+    })
+    .min_by(|(s1, p1), (s2, p2)| {
+        dist2(&voronoi.sites[*s1], p1).total_cmp(&dist2(&voronoi.sites[*s2], p2))
+    })
+}
+
+/// Moves ```apex``` radially onto the circle through ```a``` and ```b``` whose center is ```target_length``` away from ```fixed_circumcenter```, on the side of the apex.
+fn nudge_apex(
+    a: &Point,
+    b: &Point,
+    apex: &Point,
+    fixed_circumcenter: &Point,
+    target_length: f64,
+) -> Option<Point> {
+    // unit normal of a -> b, pointing towards the apex
+    let mut normal = Point {
+        x: a.y - b.y,
+        y: b.x - a.x,
+    };
+    let norm = (normal.x * normal.x + normal.y * normal.y).sqrt();
+    if norm == 0.0 {
+        return None;
+    }
+    normal.x /= norm;
+    normal.y /= norm;
+    if normal.x * (apex.x - a.x) + normal.y * (apex.y - a.y) < 0.0 {
+        normal.x = -normal.x;
+        normal.y = -normal.y;
+    }
+
+    let center = Point {
+        x: fixed_circumcenter.x + target_length * normal.x,
+        y: fixed_circumcenter.y + target_length * normal.y,
+    };
+    let radius = dist2(&center, a).sqrt();
+    let distance = dist2(&center, apex).sqrt();
+    if distance == 0.0 {
+        return None;
+    }
+
+    Some(Point {
+        x: center.x + (apex.x - center.x) * radius / distance,
+        y: center.y + (apex.y - center.y) * radius / distance,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, BoundingBox, VoronoiBuilder};
+
+    #[test]
+    fn near_cocircular_edge_is_lengthened() {
+        let sites = vec![
+            Point { x: -0.5, y: 0.0 },
+            Point { x: 0.5, y: 0.0 },
+            Point { x: 0.0, y: 0.5 },
+            Point {
+                x: 0.0,
+                y: -0.5 - 1e-6,
+            },
+        ];
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(find_short_edges(&voronoi, 0.05).len(), 1);
+
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .set_short_edge_elimination(0.05, 5)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(find_short_edges(&voronoi, 0.05).is_empty());
+        assert!(voronoi.build_warnings().is_empty());
+        utils::test::validate_voronoi(&voronoi);
+    }
+
+    #[test]
+    fn random_sites_terminate_within_pass_budget() {
+        let mut rng = utils::test::rng();
+        for _ in 0..20 {
+            let sites = (0..200)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(sites)
+                .set_lloyd_relaxation_iterations(2)
+                .build()
+                .expect("Some voronoi expected");
+
+            let (voronoi, passes) = eliminate_short_edges(voronoi, 0.01, 3);
+            assert!(passes <= 3, "Performed {} passes.", passes);
+
+            // every remaining violation is reported
+            utils::test::validate_voronoi(&voronoi);
+            let remaining = find_short_edges(&voronoi, 0.01);
+            assert_eq!(voronoi.build_warnings().len(), remaining.len());
+            assert!(voronoi
+                .build_warnings()
+                .iter()
+                .all(|w| matches!(w, BuildWarning::ShortEdge { length, .. } if *length < 0.01)));
+        }
+    }
+}
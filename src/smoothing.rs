@@ -0,0 +1,204 @@
+use crate::{utils::shared_voronoi_edge_lengths, ConvexBoundary, Voronoi};
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Smooths a value per site by diffusing it between neighboring cells for ```iterations``` steps.
+    ///
+    /// Each step moves the value of each cell towards the mean of its neighbors (as returned by [crate::VoronoiCell::iter_neighbors()]),
+    /// weighted by the length of the edge they share:
+    /// ```v[i] + lambda * sum(w[i][j] * (v[j] - v[i])) / sum(w[i][j])```.
+    /// Cells on the hull only diffuse with the neighbors they have, and cells without neighbors keep their value.
+    ///
+    /// With ```lambda``` within ```[0, 1]```, each new value is a convex combination of the values of the cell and its neighbors, so the smoothed values
+    /// never fall outside of the range of the input. As edge lengths are symmetric, the sum of the values weighted by the perimeter each cell shares with its neighbors is kept.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let moisture: Vec<f64> = v.sites().iter().map(|p| if p.x < 0.0 { 1.0 } else { 0.0 }).collect();
+    /// let smoothed = v.smooth(&moisture, 5, 0.5);
+    /// assert!(smoothed.iter().all(|&m| (0.0..=1.0).contains(&m)));
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```values``` does not have one value per site, or if ```lambda``` is not within ```[0, 1]```.
+    pub fn smooth(&self, values: &[f64], iterations: usize, lambda: f64) -> Vec<f64> {
+        let mut smoothed = vec![0.0; values.len()];
+        self.smooth_into(values, iterations, lambda, &mut smoothed);
+        smoothed
+    }
+
+    /// Smooths a value per site as [Self::smooth()] does, writing the result into ```out```.
+    ///
+    /// Only one buffer of one value per site is allocated, regardless of the number of iterations, which alternate between it and ```out```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```values``` or ```out``` do not have one value per site, or if ```lambda``` is not within ```[0, 1]```.
+    pub fn smooth_into(&self, values: &[f64], iterations: usize, lambda: f64, out: &mut [f64]) {
+        assert_eq!(
+            values.len(),
+            self.sites.len(),
+            "One value per site is expected."
+        );
+        assert_eq!(
+            out.len(),
+            self.sites.len(),
+            "One output value per site is expected."
+        );
+        assert!(
+            (0.0..=1.0).contains(&lambda),
+            "Lambda must be within [0, 1] for the smoothing to be stable, but it is {}.",
+            lambda
+        );
+
+        out.copy_from_slice(values);
+        if iterations == 0 {
+            return;
+        }
+
+        let adjacency = self.adjacency();
+        let weights: Vec<f64> = (0..self.sites.len())
+            .flat_map(|site| {
+                let lengths = shared_voronoi_edge_lengths(self, site);
+                adjacency.neighbors_of(site).iter().map(move |&neighbor| {
+                    lengths
+                        .iter()
+                        .find_map(|&(n, length)| (n == neighbor).then_some(length))
+                        .unwrap_or(0.0)
+                })
+            })
+            .collect();
+        let offsets = adjacency.offsets();
+        let neighbors = adjacency.neighbors();
+
+        let mut scratch = vec![0.0; out.len()];
+        for iteration in 0..iterations {
+            // even iterations read from the output and write to the scratch buffer, odd iterations the other way around
+            let (from, to) = if iteration % 2 == 0 {
+                (&*out, &mut scratch[..])
+            } else {
+                (&scratch[..], &mut *out)
+            };
+
+            for (site, value) in to.iter_mut().enumerate() {
+                let range = offsets[site]..offsets[site + 1];
+                let (total, weighted) = neighbors[range.clone()].iter().zip(&weights[range]).fold(
+                    (0.0, 0.0),
+                    |(total, weighted), (&neighbor, &w)| {
+                        (total + w, weighted + w * (from[neighbor] - from[site]))
+                    },
+                );
+
+                *value = if total > 0.0 {
+                    from[site] + lambda * weighted / total
+                } else {
+                    from[site]
+                };
+            }
+        }
+
+        if iterations % 2 == 1 {
+            out.copy_from_slice(&scratch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox};
+
+    /// Gets the sum of the absolute differences of the values of neighboring cells, counting each pair once.
+    fn total_variation(voronoi: &Voronoi<BoundingBox>, values: &[f64]) -> f64 {
+        let adjacency = voronoi.adjacency();
+        (0..voronoi.sites().len())
+            .flat_map(|site| {
+                adjacency
+                    .neighbors_of(site)
+                    .iter()
+                    .filter(move |&&n| n > site)
+                    .map(move |&n| (values[site] - values[n]).abs())
+            })
+            .sum()
+    }
+
+    #[test]
+    fn constant_fields_are_fixed_points() {
+        let voronoi = random_voronoi(300);
+        let values = vec![0.7; 300];
+        for &lambda in &[0.0, 0.5, 1.0] {
+            assert_eq!(voronoi.smooth(&values, 10, lambda), values);
+        }
+    }
+
+    #[test]
+    fn weighted_mass_is_conserved() {
+        let voronoi = random_voronoi(500);
+        // a bump in the interior, away from the hull
+        let values: Vec<f64> = voronoi
+            .sites()
+            .iter()
+            .map(|p| (0.25 - p.x * p.x - p.y * p.y).max(0.0))
+            .collect();
+        let perimeters: Vec<f64> = voronoi
+            .iter_cells()
+            .map(|cell| {
+                cell.iter_neighbors()
+                    .map(|n| cell.shared_edge_length(n))
+                    .sum()
+            })
+            .collect();
+        let mass =
+            |values: &[f64]| -> f64 { values.iter().zip(&perimeters).map(|(v, w)| v * w).sum() };
+
+        for &iterations in &[1, 2, 15] {
+            let smoothed = voronoi.smooth(&values, iterations, 0.8);
+            assert!(
+                (mass(&smoothed) - mass(&values)).abs() < 1e-9 * mass(&values),
+                "Mass {} became {}.",
+                mass(&values),
+                mass(&smoothed)
+            );
+        }
+    }
+
+    #[test]
+    fn smoothing_reduces_total_variation() {
+        let mut rng = crate::utils::test::rng();
+        for _ in 0..10 {
+            let voronoi = random_voronoi(200);
+            let values: Vec<f64> = (0..200).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            let lambda = rng.gen_range(0.1..=1.0);
+
+            let mut variation = total_variation(&voronoi, &values);
+            let mut smoothed = values.clone();
+            let mut out = vec![0.0; 200];
+            for iterations in 1..5 {
+                voronoi.smooth_into(&values, iterations, lambda, &mut out);
+                smoothed = voronoi.smooth(&smoothed, 1, lambda);
+                assert_eq!(out, smoothed);
+
+                let smoothed_variation = total_variation(&voronoi, &smoothed);
+                assert!(smoothed_variation < variation);
+                variation = smoothed_variation;
+                // the range of the values never grows
+                assert!(smoothed.iter().all(|v| (-1.0..1.0).contains(v)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Lambda must be within [0, 1]")]
+    fn unstable_lambda_is_rejected() {
+        let voronoi = random_voronoi(10);
+        voronoi.smooth(&[0.0; 10], 1, 1.5);
+    }
+}
@@ -0,0 +1,138 @@
+use spade::{
+    DelaunayTriangulation, HasPosition, HintGenerator, InsertionError, Point2, Triangulation,
+};
+
+use crate::{ConvexBoundary, Point, Voronoi, VoronoiBuilder};
+
+impl<T: ConvexBoundary> VoronoiBuilder<T> {
+    /// Creates a builder whose sites are the vertices of a [spade](https://docs.rs/spade) Delaunay ```triangulation```, in the order of their indices,
+    /// so the site ```i``` of the diagram is the vertex of [FixedVertexHandle::index()](spade::handles::FixedVertexHandle::index()) ```i```.
+    ///
+    /// Vertex data other than the position is dropped. The connectivity of the triangulation is not reused: it is recomputed by the [Engine](crate::Engine) set,
+    /// so the diagram built is identical to the one built from the same sites given with [Self::set_sites()].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// use spade::{DelaunayTriangulation, Point2, Triangulation};
+    /// let mut triangulation = DelaunayTriangulation::<Point2<f64>>::new();
+    /// for p in [(-0.5, -0.5), (0.5, -0.2), (0.1, 0.6)] {
+    ///     triangulation.insert(Point2::new(p.0, p.1)).unwrap();
+    /// }
+    ///
+    /// let voronoi = VoronoiBuilder::<BoundingBox>::from_spade(&triangulation)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(voronoi.sites()[1], Point { x: 0.5, y: -0.2 });
+    ///```
+    pub fn from_spade<V, DE, UE, F, L>(
+        triangulation: &DelaunayTriangulation<V, DE, UE, F, L>,
+    ) -> Self
+    where
+        V: HasPosition,
+        V::Scalar: Into<f64>,
+        DE: Default,
+        UE: Default,
+        F: Default,
+        L: HintGenerator<V::Scalar>,
+    {
+        let sites = triangulation
+            .vertices()
+            .map(|vertex| {
+                let position = vertex.position();
+                Point {
+                    x: position.x.into(),
+                    y: position.y.into(),
+                }
+            })
+            .collect();
+
+        Self::default().set_sites(sites)
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Builds a [spade](https://docs.rs/spade) Delaunay triangulation of the sites of this diagram, inserting them in order.
+    ///
+    /// The connectivity is recomputed by spade, so it may differ from [Self::triangulation()] where four or more sites are cocircular.
+    /// The order of the sites is kept, so the site ```i``` is the vertex of index ```i```, unless a site is at exactly the same position as an earlier one:
+    /// spade keeps a single vertex for both, so the triangulation has fewer vertices than [Self::sites()]. Sites that are only close to each other stay distinct.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [InsertionError] of the first site spade rejects, e.g. one too large or too close to zero.
+    pub fn to_spade(&self) -> Result<DelaunayTriangulation<Point2<f64>>, InsertionError> {
+        let mut triangulation = DelaunayTriangulation::<Point2<f64>>::new();
+        for site in self.sites.iter() {
+            triangulation.insert(Point2::new(site.x, site.y))?;
+        }
+
+        Ok(triangulation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test::{geometry_eq, random_sites},
+        BoundingBox,
+    };
+
+    #[test]
+    fn round_trip_matches_direct_build() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_sites(500))
+            .build()
+            .expect("Some voronoi expected");
+
+        let triangulation = voronoi.to_spade().expect("Sites expected to be accepted");
+        assert_eq!(triangulation.num_vertices(), voronoi.sites().len());
+        for (site, vertex) in voronoi.sites().iter().zip(triangulation.vertices()) {
+            let position = vertex.position();
+            assert_eq!((site.x, site.y), (position.x, position.y));
+        }
+
+        let round_trip = VoronoiBuilder::<BoundingBox>::from_spade(&triangulation)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(geometry_eq(&voronoi, &round_trip));
+        assert_eq!(round_trip.vertices().len(), voronoi.vertices().len());
+    }
+
+    #[test]
+    fn near_duplicate_sites_keep_their_indices() {
+        // sites a billionth apart, which spade does not merge as they are not at the same position
+        let mut sites = random_sites(100);
+        let first = sites[0].clone();
+        sites.extend((1..4).map(|i| Point {
+            x: first.x + 1e-9 * i as f64,
+            y: first.y,
+        }));
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+
+        let triangulation = voronoi.to_spade().expect("Sites expected to be accepted");
+        assert_eq!(triangulation.num_vertices(), voronoi.sites().len());
+        for (site, vertex) in voronoi.sites().iter().zip(triangulation.vertices()) {
+            let position = vertex.position();
+            assert_eq!((site.x, site.y), (position.x, position.y));
+        }
+    }
+
+    #[test]
+    fn rejected_site_is_reported() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1e-200, y: 0.5 },
+                Point { x: 0.5, y: 0.5 },
+            ])
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(voronoi.to_spade().err(), Some(InsertionError::TooSmall));
+    }
+}
@@ -0,0 +1,552 @@
+//! Voronoi diagrams of sites on a sphere, restricted to a spherical cap.
+//!
+//! The cap is mapped to a plane tangent to the sphere at the cap center with the [gnomonic projection](https://en.wikipedia.org/wiki/Gnomonic_projection).
+//! It maps great circles to straight lines, so the spherical cells, bounded by great circle arcs, are convex polygons in the projected plane,
+//! and the cap itself is a disk centered at the tangent point.
+//! The projection does not preserve distances though, so the bisector of two projected sites is not where their spherical cells meet.
+//! Instead, cells are built from the bisecting great circles of the sites, which are exactly the lines ```(a - b) · (x, y, 1) = 0``` in the projected plane,
+//! where ```a``` and ```b``` are the sites as unit vectors in the tangent frame.
+//!
+//! Neighboring sites are found with the Delaunay triangulation of the [stereographic projection](https://en.wikipedia.org/wiki/Stereographic_projection) of the sites,
+//! which maps circles on the sphere to circles on the plane and thus has the same triangles as the spherical Delaunay triangulation.
+//!
+//! Cell vertices and areas are calculated on the sphere, so the projections do not introduce any error besides rounding.
+
+use delaunator::next_halfedge;
+
+use crate::{utils::clip_half_plane, BuildError, Engine, Point};
+
+/// A Voronoi diagram of sites on the unit sphere, restricted to a spherical cap.
+///
+/// To obtain an instance of this type, use [build_local()].
+#[derive(Debug, Clone)]
+pub struct SphericalVoronoi {
+    sites: Vec<(f64, f64)>,
+    cap_center: (f64, f64),
+    cap_radius: f64,
+    cells: Vec<SphericalCell>,
+}
+
+/// A cell of a [SphericalVoronoi]: the region of the cap closer to its site than to any other site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphericalCell {
+    site: usize,
+    vertices: Vec<(f64, f64)>,
+    cap_arcs: Vec<bool>,
+    area: f64,
+}
+
+impl SphericalVoronoi {
+    /// Gets the sites of the diagram, as (longitude, latitude) pairs in degrees.
+    pub fn sites(&self) -> &[(f64, f64)] {
+        &self.sites
+    }
+
+    /// Gets the center of the cap, as a (longitude, latitude) pair in degrees.
+    pub fn cap_center(&self) -> (f64, f64) {
+        self.cap_center
+    }
+
+    /// Gets the angular radius of the cap, in degrees.
+    pub fn cap_radius(&self) -> f64 {
+        self.cap_radius
+    }
+
+    /// Gets the area of the cap on the unit sphere. This is the sum of the areas of all cells.
+    pub fn cap_area(&self) -> f64 {
+        cap_area(self.cap_radius.to_radians().cos())
+    }
+
+    /// Gets the cell of ```site```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` is out of range.
+    pub fn cell(&self, site: usize) -> &SphericalCell {
+        &self.cells[site]
+    }
+
+    /// Gets an iterator over the cells of the diagram, in site order.
+    pub fn iter_cells(&self) -> impl Iterator<Item = &SphericalCell> + Clone {
+        self.cells.iter()
+    }
+}
+
+impl SphericalCell {
+    /// Gets the index of the site of this cell.
+    pub fn site(&self) -> usize {
+        self.site
+    }
+
+    /// Gets the vertices of this cell, as (longitude, latitude) pairs in degrees, in counter-clockwise order when looking at the sphere from the outside.
+    ///
+    /// Cells with no vertices either cover the whole cap, if the diagram has a single site, or are empty, if their site is a duplicate of another one.
+    pub fn vertices(&self) -> &[(f64, f64)] {
+        &self.vertices
+    }
+
+    /// Returns whether the edge from vertex ```edge``` to the next vertex follows the boundary of the cap.
+    ///
+    /// The boundary of the cap is a small circle. Every other edge is a great circle arc, shared with the cell on the other side of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```edge``` is out of range.
+    pub fn is_cap_arc(&self, edge: usize) -> bool {
+        self.cap_arcs[edge]
+    }
+
+    /// Gets the area of this cell on the unit sphere, in steradians. Multiply by the square of the radius of the sphere to get the actual area.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+/// Builds the spherical Voronoi diagram of ```sites_lonlat``` restricted to the cap of angular radius ```cap_radius_deg``` around ```cap_center```.
+///
+/// Sites and the cap center are (longitude, latitude) pairs in degrees. All sites must be within the cap.
+/// Caps of 90° or more cannot be projected to a plane and are rejected with [BuildError::InvalidCapRadius].
+///
+/// # Examples
+///
+///```
+/// use voronoice::sphere;
+/// let v = sphere::build_local(&[(2.0, 48.0), (4.0, 50.0), (6.0, 47.0)], (4.0, 48.0), 5.0).unwrap();
+/// let area: f64 = v.iter_cells().map(|c| c.area()).sum();
+/// assert!((area - v.cap_area()).abs() < 1e-12);
+///```
+pub fn build_local(
+    sites_lonlat: &[(f64, f64)],
+    cap_center: (f64, f64),
+    cap_radius_deg: f64,
+) -> Result<SphericalVoronoi, BuildError> {
+    if !(cap_radius_deg > 0.0 && cap_radius_deg < 90.0) {
+        return Err(BuildError::InvalidCapRadius(cap_radius_deg));
+    }
+
+    let frame = TangentFrame::new(cap_center);
+    let cos_radius = cap_radius_deg.to_radians().cos();
+    let sites = sites_lonlat
+        .iter()
+        .enumerate()
+        .map(|(i, &site)| {
+            let v = frame.to_local(site);
+            // allow sites just on the boundary of the cap
+            if v[2] >= cos_radius - 1e-12 {
+                Ok(v)
+            } else {
+                Err(BuildError::SiteOutsideCap(i))
+            }
+        })
+        .collect::<Result<Vec<[f64; 3]>, BuildError>>()?;
+
+    let disk_radius = cap_radius_deg.to_radians().tan();
+    let cells = neighbors(&sites)
+        .into_iter()
+        .enumerate()
+        .map(|(site, neighbors)| {
+            let (polygon, covers_cap) = neighbors.map_or((Vec::new(), false), |neighbors| {
+                cell_polygon(&sites, site, &neighbors, disk_radius)
+            });
+            let area = if covers_cap {
+                cap_area(cos_radius)
+            } else {
+                polygon_area(&polygon, cos_radius)
+            };
+            let (vertices, cap_arcs) = polygon
+                .into_iter()
+                .map(|v| (frame.to_lonlat(&v.position), v.cap_arc))
+                .unzip();
+
+            SphericalCell {
+                site,
+                vertices,
+                cap_arcs,
+                area,
+            }
+        })
+        .collect();
+
+    Ok(SphericalVoronoi {
+        sites: sites_lonlat.to_vec(),
+        cap_center,
+        cap_radius: cap_radius_deg,
+        cells,
+    })
+}
+
+/// An orthonormal frame with ```z``` pointing at the cap center, ```x``` east and ```y``` north.
+struct TangentFrame {
+    east: [f64; 3],
+    north: [f64; 3],
+    up: [f64; 3],
+}
+
+impl TangentFrame {
+    fn new((lon, lat): (f64, f64)) -> Self {
+        let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+        let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+        Self {
+            east: [-sin_lon, cos_lon, 0.0],
+            north: [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            up: [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+        }
+    }
+
+    /// Converts a (longitude, latitude) pair to a unit vector in this frame.
+    fn to_local(&self, lonlat: (f64, f64)) -> [f64; 3] {
+        let v = unit_vector(lonlat);
+        [dot(&v, &self.east), dot(&v, &self.north), dot(&v, &self.up)]
+    }
+
+    /// Converts a point of the gnomonic projection in this frame back to a (longitude, latitude) pair.
+    fn to_lonlat(&self, p: &Point) -> (f64, f64) {
+        let v = [0, 1, 2].map(|i| p.x * self.east[i] + p.y * self.north[i] + self.up[i]);
+        let length = dot(&v, &v).sqrt();
+        (
+            v[1].atan2(v[0]).to_degrees(),
+            (v[2] / length).asin().to_degrees(),
+        )
+    }
+}
+
+/// A vertex of a cell in the gnomonic projection, and whether the edge from it to the next vertex follows the boundary of the cap.
+#[derive(Debug, Clone)]
+struct CellVertex {
+    position: Point,
+    cap_arc: bool,
+}
+
+fn unit_vector((lon, lat): (f64, f64)) -> [f64; 3] {
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Gets the neighbors of each site, or ```None``` for sites that are duplicates of other sites.
+///
+/// When the sites cannot be triangulated, such as when there are less than three of them, every site is a neighbor of every other site.
+fn neighbors(sites: &[[f64; 3]]) -> Vec<Option<Vec<usize>>> {
+    // stereographic projection from the antipode of the cap center
+    let projected: Vec<Point> = sites
+        .iter()
+        .map(|s| Point {
+            x: s[0] / (1.0 + s[2]),
+            y: s[1] / (1.0 + s[2]),
+        })
+        .collect();
+    let triangulation = Engine::default().triangulate(&projected);
+
+    if triangulation.triangles.is_empty() {
+        return (0..sites.len())
+            .map(|site| {
+                let is_duplicate = (0..site).any(|other| sites[other] == sites[site]);
+                if is_duplicate {
+                    None
+                } else {
+                    Some((0..sites.len()).filter(|&n| n != site).collect())
+                }
+            })
+            .collect();
+    }
+
+    let mut neighbors = vec![Vec::new(); sites.len()];
+    for e in 0..triangulation.triangles.len() {
+        let (a, b) = (
+            triangulation.triangles[e],
+            triangulation.triangles[next_halfedge(e)],
+        );
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+    }
+
+    // sites skipped by the triangulation are duplicates and have no neighbors
+    neighbors
+        .into_iter()
+        .map(|mut n: Vec<usize>| {
+            if n.is_empty() {
+                None
+            } else {
+                n.sort_unstable();
+                n.dedup();
+                Some(n)
+            }
+        })
+        .collect()
+}
+
+/// Calculates the cell of ```site``` in the gnomonic projection, counter-clockwise, clipped to the disk of radius ```disk_radius```.
+///
+/// Cells without vertices are returned along with whether they cover the whole disk.
+fn cell_polygon(
+    sites: &[[f64; 3]],
+    site: usize,
+    neighbors: &[usize],
+    disk_radius: f64,
+) -> (Vec<CellVertex>, bool) {
+    // start from a square around the disk, and cut it with the bisector of each neighbor
+    let r = 2.0 * disk_radius;
+    let mut polygon = vec![
+        Point { x: -r, y: -r },
+        Point { x: r, y: -r },
+        Point { x: r, y: r },
+        Point { x: -r, y: r },
+    ];
+    for &n in neighbors {
+        let (a, b) = (&sites[site], &sites[n]);
+        // points closer to a than b: (a - b) · (x, y, 1) >= 0
+        let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        polygon = clip_half_plane(&polygon, |p| d[0] * p.x + d[1] * p.y + d[2]);
+        if polygon.is_empty() {
+            break;
+        }
+    }
+
+    let clipped = clip_disk(&polygon, disk_radius);
+    // without vertices, the polygon has no edge crossing the disk, so it either contains the disk or is disjoint from it
+    let covers_disk = clipped.is_empty() && contains_origin(&polygon);
+    (clipped, covers_disk)
+}
+
+/// Clips a convex polygon to the disk of radius ```radius``` centered at the origin.
+fn clip_disk(polygon: &[Point], radius: f64) -> Vec<CellVertex> {
+    let r2 = radius * radius;
+    let is_inside = |p: &Point| p.x * p.x + p.y * p.y <= r2;
+    let vertex = |position: Point, cap_arc: bool| CellVertex { position, cap_arc };
+
+    let mut clipped = Vec::with_capacity(polygon.len() + 2);
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (a_inside, b_inside) = (is_inside(a), is_inside(b));
+        if a_inside {
+            clipped.push(vertex(a.clone(), false));
+        }
+
+        // |a + t (b - a)|² = r²
+        let d = Point {
+            x: b.x - a.x,
+            y: b.y - a.y,
+        };
+        let qa = d.x * d.x + d.y * d.y;
+        let qb = 2.0 * (a.x * d.x + a.y * d.y);
+        let qc = a.x * a.x + a.y * a.y - r2;
+        let discriminant = qb * qb - 4.0 * qa * qc;
+        if qa == 0.0 || discriminant <= 0.0 {
+            continue;
+        }
+
+        let root = discriminant.sqrt();
+        let (enter, exit) = ((-qb - root) / (2.0 * qa), (-qb + root) / (2.0 * qa));
+        match (a_inside, b_inside) {
+            (true, false) => clipped.push(vertex(on_circle(lerp(a, b, exit), radius), true)),
+            (false, true) => clipped.push(vertex(on_circle(lerp(a, b, enter), radius), false)),
+            (false, false) if enter > 0.0 && exit < 1.0 => {
+                clipped.push(vertex(on_circle(lerp(a, b, enter), radius), false));
+                clipped.push(vertex(on_circle(lerp(a, b, exit), radius), true));
+            }
+            _ => {}
+        }
+    }
+
+    clipped
+}
+
+fn lerp(a: &Point, b: &Point, t: f64) -> Point {
+    Point {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    }
+}
+
+/// Moves a point to the exact circle of radius ```radius```, removing rounding errors of the intersection.
+fn on_circle(p: Point, radius: f64) -> Point {
+    let length = (p.x * p.x + p.y * p.y).sqrt();
+    Point {
+        x: p.x * radius / length,
+        y: p.y * radius / length,
+    }
+}
+
+fn contains_origin(polygon: &[Point]) -> bool {
+    !polygon.is_empty()
+        && polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .all(|(a, b)| a.x * b.y - a.y * b.x >= 0.0)
+}
+
+/// Calculates the area of a cap on the unit sphere, given the cosine of its radius.
+fn cap_area(cos_radius: f64) -> f64 {
+    2.0 * std::f64::consts::PI * (1.0 - cos_radius)
+}
+
+/// Calculates the area on the unit sphere of a cell given in the gnomonic projection, where the cap has ```cos_radius``` as the cosine of its radius.
+///
+/// The cell is split in a fan of signed regions around the cap center: spherical triangles for the great circle edges,
+/// and sectors of the cap for the edges along the cap boundary.
+fn polygon_area(polygon: &[CellVertex], cos_radius: f64) -> f64 {
+    let center = [0.0, 0.0, 1.0];
+    let sector = |from: &Point, to: &Point| {
+        let mut angle = to.y.atan2(to.x) - from.y.atan2(from.x);
+        if angle <= 0.0 {
+            angle += 2.0 * std::f64::consts::PI;
+        }
+        (1.0 - cos_radius) * angle
+    };
+
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            if a.cap_arc {
+                sector(&a.position, &b.position)
+            } else {
+                // signed solid angle of the triangle, see Van Oosterom and Strackee (1983)
+                let (a, b) = (to_sphere(&a.position), to_sphere(&b.position));
+                let numerator = dot(&center, &cross(&a, &b));
+                let denominator = 1.0 + dot(&a, &b) + dot(&b, &center) + dot(&center, &a);
+                2.0 * numerator.atan2(denominator)
+            }
+        })
+        .sum()
+}
+
+/// Unprojects a point of the gnomonic projection to a unit vector of the tangent frame.
+fn to_sphere(p: &Point) -> [f64; 3] {
+    let length = (p.x * p.x + p.y * p.y + 1.0).sqrt();
+    [p.x / length, p.y / length, 1.0 / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    /// Angular distance, in radians, between two (longitude, latitude) pairs.
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        dot(&unit_vector(a), &unit_vector(b))
+            .clamp(-1.0, 1.0)
+            .acos()
+    }
+
+    #[test]
+    fn two_sites_split_along_bisector() {
+        let (a, b) = ((8.5, 43.0), (12.0, 47.5));
+        let center = (10.0, 45.0);
+        let radius = 5.0;
+        let v = build_local(&[a, b], center, radius).expect("Diagram expected");
+
+        // the spherical bisector is the great circle with normal a - b
+        let (ua, ub) = (unit_vector(a), unit_vector(b));
+        let normal = [ua[0] - ub[0], ua[1] - ub[1], ua[2] - ub[2]];
+        for (cell, sign) in v.iter_cells().zip([1.0, -1.0]) {
+            assert_eq!(cell.vertices().len(), 2);
+            for &vertex in cell.vertices() {
+                let side = dot(&normal, &unit_vector(vertex));
+                assert!(side.abs() < 1e-12, "Vertex {:?} not on bisector.", vertex);
+                assert!((distance(vertex, center) - radius.to_radians()).abs() < 1e-12);
+                assert!((distance(vertex, a) - distance(vertex, b)).abs() < 1e-12);
+            }
+
+            // one edge is the bisector, the other one the boundary of the cap
+            assert_ne!(cell.is_cap_arc(0), cell.is_cap_arc(1));
+
+            // the cell is on the side of its site
+            let site = unit_vector(v.sites()[cell.site()]);
+            assert!(sign * dot(&normal, &site) > 0.0);
+        }
+
+        let area: f64 = v.iter_cells().map(|c| c.area()).sum();
+        assert!((area - v.cap_area()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn areas_add_up_to_cap() {
+        let mut rng = crate::utils::test::rng();
+        for &(center, radius) in [
+            ((0.0, 0.0), 1.0_f64),
+            ((-70.0, 60.0), 10.0),
+            ((170.0, -85.0), 45.0),
+            ((30.0, 10.0), 89.0),
+        ]
+        .iter()
+        {
+            for &size in [1, 2, 3, 10, 100].iter() {
+                let sites: Vec<(f64, f64)> = (0..size)
+                    .map(|_| loop {
+                        let site = (rng.gen_range(-180.0..180.0), rng.gen_range(-90.0..90.0));
+                        if distance(site, center) < radius.to_radians() {
+                            break site;
+                        }
+                    })
+                    .collect();
+                let v = build_local(&sites, center, radius).expect("Diagram expected");
+
+                let area: f64 = v.iter_cells().map(|c| c.area()).sum();
+                assert!(
+                    (area - v.cap_area()).abs() < 1e-9 * v.cap_area(),
+                    "Area {} differs from cap area {}.",
+                    area,
+                    v.cap_area()
+                );
+
+                // every vertex is at least as close to the cell site as to any other site
+                for cell in v.iter_cells() {
+                    assert!(cell.area() > 0.0);
+                    let site = sites[cell.site()];
+                    for &vertex in cell.vertices() {
+                        assert!(distance(vertex, center) <= radius.to_radians() + 1e-12);
+                        let own = distance(vertex, site);
+                        assert!(sites
+                            .iter()
+                            .all(|&other| own <= distance(vertex, other) + 1e-9));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_site_covers_cap() {
+        let v = build_local(&[(1.0, 1.0)], (0.0, 0.0), 20.0).expect("Diagram expected");
+        assert!(v.cell(0).vertices().is_empty());
+        assert!((v.cell(0).area() - v.cap_area()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn duplicate_sites_have_empty_cells() {
+        let sites = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 0.0)];
+        let v = build_local(&sites, (0.0, 0.0), 5.0).expect("Diagram expected");
+        assert!(v.cell(3).vertices().is_empty());
+        assert_eq!(v.cell(3).area(), 0.0);
+        let area: f64 = v.iter_cells().map(|c| c.area()).sum();
+        assert!((area - v.cap_area()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid_caps_are_rejected() {
+        let sites = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        for &radius in [90.0, 120.0, 0.0, -1.0, f64::NAN].iter() {
+            assert!(matches!(
+                build_local(&sites, (0.0, 0.0), radius),
+                Err(BuildError::InvalidCapRadius(_))
+            ));
+        }
+        assert_eq!(
+            build_local(&sites, (0.0, 0.0), 0.5).unwrap_err(),
+            BuildError::SiteOutsideCap(1)
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{ConvexBoundary, Point, VoronoiCell};
+
+/// The result of splitting a cell by a line, as returned by [VoronoiCell::split_by_line()].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SplitResult {
+    /// The line does not cross the interior of the cell. It may still touch a vertex or run along an edge of the cell.
+    NotIntersected,
+
+    /// The cell was split in two convex pieces, whose vertices are in counter-clockwise order.
+    ///
+    /// ```left``` is on the left of the line when going from ```a``` to ```b```, and ```right``` is on the other side. By convention, the Y-axis points downwards.
+    /// Both pieces have the endpoints of the chord the line cuts through the cell, with identical coordinates, and no repeated vertices.
+    Split { left: Vec<Point>, right: Vec<Point> },
+}
+
+impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
+    /// Splits this cell in two by the line through ```a``` and ```b```, without changing the diagram.
+    ///
+    /// Vertices on the line go to both pieces, so a line through a vertex of the cell ends its chord at that vertex.
+    /// Lines that leave the cell entirely on one side, including lines only touching a vertex or collinear with an edge, do not split the cell, so no pieces with zero area are returned.
+    /// Sides are decided with exact predicates, and the endpoints of the chord are calculated once for both pieces, so the result only depends on the input.
+    /// The areas of the pieces add up to [Self::area()], up to rounding errors.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: -0.5, y: -0.5 }, Point { x: 0.5, y: -0.5 }, Point { x: -0.5, y: 0.5 }, Point { x: 0.5, y: 0.5 }])
+    ///     .build()
+    ///     .unwrap();
+    /// // cut the square cell from (0, 0) to (1, 1) through the middle
+    /// match v.cell(3).split_by_line(&Point { x: 0.0, y: 0.5 }, &Point { x: 1.0, y: 0.5 }) {
+    ///     SplitResult::Split { left, right } => assert_eq!((left.len(), right.len()), (4, 4)),
+    ///     SplitResult::NotIntersected => unreachable!(),
+    /// }
+    ///```
+    pub fn split_by_line(&self, a: &Point, b: &Point) -> SplitResult {
+        let vertices: Vec<&Point> = self.iter_vertices().collect();
+        // with the Y-axis pointing downwards, points on the left have a negative orientation
+        let sides: Vec<f64> = vertices.iter().map(|p| -orient(a, b, p)).collect();
+        if !sides.iter().any(|&s| s > 0.0) || !sides.iter().any(|&s| s < 0.0) {
+            return SplitResult::NotIntersected;
+        }
+
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let n = vertices.len();
+        for i in 0..n {
+            let j = (i + 1) % n;
+            let (p, q) = (vertices[i], vertices[j]);
+            let (sp, sq) = (sides[i], sides[j]);
+            if sp >= 0.0 {
+                push_vertex(&mut left, p);
+            }
+            if sp <= 0.0 {
+                push_vertex(&mut right, p);
+            }
+
+            if (sp > 0.0 && sq < 0.0) || (sp < 0.0 && sq > 0.0) {
+                let x = intersection(p, q, sp, sq);
+                push_vertex(&mut left, &x);
+                push_vertex(&mut right, &x);
+            }
+        }
+
+        for piece in [&mut left, &mut right] {
+            while piece.len() > 1 && piece.first() == piece.last() {
+                piece.pop();
+            }
+        }
+
+        SplitResult::Split { left, right }
+    }
+}
+
+fn orient(a: &Point, b: &Point, c: &Point) -> f64 {
+    robust::orient2d(
+        robust::Coord { x: a.x, y: a.y },
+        robust::Coord { x: b.x, y: b.y },
+        robust::Coord { x: c.x, y: c.y },
+    )
+}
+
+/// Pushes ```p``` unless it repeats the last vertex, since cells may have repeated vertices and a chord may end at a vertex.
+fn push_vertex(piece: &mut Vec<Point>, p: &Point) {
+    if piece.last() != Some(p) {
+        piece.push(p.clone());
+    }
+}
+
+/// Intersects the segment ```p -> q``` with the line, given the side of the line of both endpoints.
+///
+/// The segment is always interpolated from its lowest endpoint, by X and then Y, so the intersection depends neither on the direction of the segment nor on the direction of the line.
+fn intersection(p: &Point, q: &Point, sp: f64, sq: f64) -> Point {
+    let (p, q, sp, sq) = if (p.x, p.y) < (q.x, q.y) {
+        (p, q, sp, sq)
+    } else {
+        (q, p, sq, sp)
+    };
+    let t = sp.abs() / (sp.abs() + sq.abs());
+    Point {
+        x: p.x + t * (q.x - p.x),
+        y: p.y + t * (q.y - p.y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, BoundingBox, Voronoi, VoronoiBuilder};
+
+    /// A diagram whose cells are the four unit squares around the origin.
+    fn quadrants_voronoi() -> Voronoi<BoundingBox> {
+        VoronoiBuilder::default()
+            .set_sites(vec![
+                Point { x: 0.5, y: 0.5 },
+                Point { x: -0.5, y: 0.5 },
+                Point { x: -0.5, y: -0.5 },
+                Point { x: 0.5, y: -0.5 },
+            ])
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    /// Positive for counter-clockwise polygons, with the Y-axis pointing downwards.
+    fn signed_area(polygon: &[Point]) -> f64 {
+        let n = polygon.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (&polygon[i], &polygon[(i + 1) % n]);
+                b.x * a.y - a.x * b.y
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    fn assert_convex(polygon: &[Point], scale: f64) {
+        let n = polygon.len();
+        assert!(n >= 3, "Piece has {} vertices. {:?}", n, polygon);
+        for i in 0..n {
+            let (a, b, c) = (&polygon[i], &polygon[(i + 1) % n], &polygon[(i + 2) % n]);
+            assert_ne!(a, b, "Repeated vertex in {:?}", polygon);
+            let cross = (b.y - a.y) * (c.x - b.x) - (b.x - a.x) * (c.y - b.y);
+            assert!(
+                cross >= -1e-12 * scale * scale,
+                "Not convex at {}: {:?}",
+                i,
+                polygon
+            );
+        }
+    }
+
+    fn split(cell: &VoronoiCell<BoundingBox>, a: Point, b: Point) -> (Vec<Point>, Vec<Point>) {
+        match cell.split_by_line(&a, &b) {
+            SplitResult::Split { left, right } => (left, right),
+            SplitResult::NotIntersected => panic!("Expected a split of {:?}", cell),
+        }
+    }
+
+    #[test]
+    fn line_through_interior_splits_square() {
+        let voronoi = quadrants_voronoi();
+        let (left, right) = split(
+            &voronoi.cell(0),
+            Point { x: 0.25, y: -5.0 },
+            Point { x: 0.25, y: 5.0 },
+        );
+
+        // going down, left is towards positive X
+        assert!(utils::abs_diff_eq(signed_area(&left), 0.75, 1e-12));
+        assert!(utils::abs_diff_eq(signed_area(&right), 0.25, 1e-12));
+        assert!(left.iter().all(|p| p.x >= 0.25));
+        assert!(right.iter().all(|p| p.x <= 0.25));
+        let chord: Vec<&Point> = left.iter().filter(|p| p.x == 0.25).collect();
+        assert_eq!(chord.len(), 2);
+        assert!(chord.iter().all(|p| right.contains(p)));
+    }
+
+    #[test]
+    fn line_through_vertices_splits_at_vertices() {
+        let voronoi = quadrants_voronoi();
+        let (left, right) = split(
+            &voronoi.cell(0),
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+        );
+
+        assert_eq!(left.len(), 3);
+        assert_eq!(right.len(), 3);
+        assert!(utils::abs_diff_eq(signed_area(&left), 0.5, 1e-12));
+        assert!(utils::abs_diff_eq(signed_area(&right), 0.5, 1e-12));
+        for corner in [Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 }] {
+            assert!(left.contains(&corner) && right.contains(&corner));
+        }
+
+        // through one vertex and an edge
+        let (left, right) = split(
+            &voronoi.cell(0),
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 0.5 },
+        );
+        assert_eq!((left.len(), right.len()), (3, 4));
+        assert!(left.contains(&Point { x: 1.0, y: 0.5 }));
+        assert!(utils::abs_diff_eq(signed_area(&left), 0.25, 1e-12));
+    }
+
+    #[test]
+    fn lines_touching_cell_do_not_split() {
+        let voronoi = quadrants_voronoi();
+        let cell = voronoi.cell(0);
+        let lines = [
+            // along an edge, in both directions
+            (Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }),
+            (Point { x: 0.0, y: 1.0 }, Point { x: 0.0, y: 0.0 }),
+            // through a single vertex
+            (Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: -1.0 }),
+            (Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }),
+            // outside
+            (Point { x: -0.5, y: 0.0 }, Point { x: -0.5, y: 1.0 }),
+            // not a line
+            (Point { x: 0.5, y: 0.5 }, Point { x: 0.5, y: 0.5 }),
+        ];
+
+        for (a, b) in lines.iter() {
+            assert_eq!(
+                cell.split_by_line(a, b),
+                SplitResult::NotIntersected,
+                "{:?} -> {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn reversed_line_swaps_pieces() {
+        let voronoi = quadrants_voronoi();
+        let cell = voronoi.cell(2);
+        let (a, b) = (Point { x: -0.9, y: 0.1 }, Point { x: -0.1, y: -0.9 });
+        let (left, right) = split(&cell, a.clone(), b.clone());
+        let (reversed_left, reversed_right) = split(&cell, b, a);
+
+        assert_eq!(left, reversed_right);
+        assert_eq!(right, reversed_left);
+    }
+
+    #[test]
+    fn random_splits_conserve_area() {
+        let mut rng = utils::test::rng();
+        let sites = (0..100)
+            .map(|_| Point {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+            })
+            .collect();
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+
+        let mut splits = 0;
+        for cell in voronoi.iter_cells() {
+            for _ in 0..10 {
+                // lines through the cell site and random points, or through a random vertex
+                let vertices: Vec<&Point> = cell.iter_vertices().collect();
+                let a = if rng.gen_bool(0.5) {
+                    cell.site_position().clone()
+                } else {
+                    vertices[rng.gen_range(0..vertices.len())].clone()
+                };
+                let b = Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                };
+
+                match cell.split_by_line(&a, &b) {
+                    SplitResult::NotIntersected => {}
+                    SplitResult::Split { left, right } => {
+                        splits += 1;
+                        assert_convex(&left, 1.0);
+                        assert_convex(&right, 1.0);
+                        let (left_area, right_area) = (signed_area(&left), signed_area(&right));
+                        assert!(left_area > 0.0 && right_area > 0.0);
+                        assert!(
+                            utils::abs_diff_eq(left_area + right_area, cell.area(), 1e-12),
+                            "Areas {} + {} != {}",
+                            left_area,
+                            right_area,
+                            cell.area()
+                        );
+
+                        let chord: Vec<&Point> =
+                            left.iter().filter(|p| right.contains(p)).collect();
+                        assert_eq!(chord.len(), 2, "{:?} {:?}", left, right);
+
+                        let reversed = cell.split_by_line(&b, &a);
+                        assert_eq!(
+                            reversed,
+                            SplitResult::Split {
+                                left: right,
+                                right: left
+                            }
+                        );
+                    }
+                }
+            }
+        }
+
+        assert!(splits > 0);
+    }
+}
@@ -0,0 +1,543 @@
+use delaunator::{next_halfedge, prev_halfedge, Point, Triangulation, EMPTY};
+
+/// Scratch buffers of [triangulate_in()], which can be kept to triangulate other sites without allocating them again.
+#[derive(Debug, Default)]
+pub(crate) struct SweepHullArena {
+    triangles: Vec<usize>,
+    halfedges: Vec<usize>,
+    hull: Vec<usize>,
+    dists: Vec<(usize, f64)>,
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    tri: Vec<usize>,
+    hash: Vec<usize>,
+    edge_stack: Vec<usize>,
+}
+
+/// Triangulates ```points``` with the sweep-hull algorithm of [delaunator::triangulate()], step by step, so the triangulation is the same.
+///
+/// The triangulation is calculated in the buffers of ```arena```, and only copied to vectors of its exact size at the end,
+/// so triangulating many small sets of points with the same arena only allocates the three vectors of each triangulation.
+pub(crate) fn triangulate_in(points: &[Point], arena: &mut SweepHullArena) -> Triangulation {
+    let (i0, i1, i2) = match find_seed_triangle(points) {
+        Some(seed) => seed,
+        None => return collinear_hull(points),
+    };
+
+    let n = points.len();
+    let center = circumcenter(&points[i0], &points[i1], &points[i2]);
+    let max_triangles = if n > 2 { 2 * n - 5 } else { 0 };
+    let mut sweep = Sweep {
+        triangles: std::mem::take(&mut arena.triangles),
+        halfedges: std::mem::take(&mut arena.halfedges),
+        prev: std::mem::take(&mut arena.prev),
+        next: std::mem::take(&mut arena.next),
+        tri: std::mem::take(&mut arena.tri),
+        hash: std::mem::take(&mut arena.hash),
+        edge_stack: std::mem::take(&mut arena.edge_stack),
+        start: i0,
+        center: center.clone(),
+    };
+    sweep.reset(n, max_triangles);
+    sweep.add_triangle(i0, i1, i2, EMPTY, EMPTY, EMPTY);
+
+    // sort the points by distance from the seed triangle circumcenter
+    let dists = &mut arena.dists;
+    dists.clear();
+    dists.extend(
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| (i, dist2(&center, point))),
+    );
+    dists.sort_unstable_by(|&(_, da), &(_, db)| da.total_cmp(&db));
+
+    sweep.start_hull(i0, i1, i2, points);
+
+    // near duplicates are skipped by comparing them with the last point kept
+    let mut prev_kept: Option<&Point> = None;
+    for &(i, _) in dists.iter() {
+        let p = &points[i];
+        if prev_kept.is_some_and(|q| nearly_equals(p, q)) {
+            continue;
+        }
+        prev_kept = Some(p);
+
+        if i == i0 || i == i1 || i == i2 {
+            continue;
+        }
+
+        // find a visible edge on the convex hull using the edge hash
+        let (mut e, walk_back) = sweep.find_visible_edge(p, points);
+        if e == EMPTY {
+            continue;
+        }
+
+        // add the first triangle from the point, then flip triangles from it until they satisfy the Delaunay condition
+        let t = sweep.add_triangle(e, i, sweep.next[e], EMPTY, EMPTY, sweep.tri[e]);
+        sweep.tri[i] = sweep.legalize(t + 2, points);
+        sweep.tri[e] = t;
+
+        // walk forward through the hull, adding more triangles and flipping
+        let mut n = sweep.next[e];
+        loop {
+            let q = sweep.next[n];
+            if orient(p, &points[n], &points[q]) <= 0. {
+                break;
+            }
+            let t = sweep.add_triangle(n, i, q, sweep.tri[i], EMPTY, sweep.tri[n]);
+            sweep.tri[i] = sweep.legalize(t + 2, points);
+            sweep.next[n] = EMPTY;
+            n = q;
+        }
+
+        // walk backward from the other side, adding more triangles and flipping
+        if walk_back {
+            loop {
+                let q = sweep.prev[e];
+                if orient(p, &points[q], &points[e]) <= 0. {
+                    break;
+                }
+                let t = sweep.add_triangle(q, i, e, EMPTY, sweep.tri[e], sweep.tri[q]);
+                sweep.legalize(t + 2, points);
+                sweep.tri[q] = t;
+                sweep.next[e] = EMPTY;
+                e = q;
+            }
+        }
+
+        // update the hull indices, and save the two new edges in the hash table
+        sweep.prev[i] = e;
+        sweep.next[i] = n;
+        sweep.prev[n] = i;
+        sweep.next[e] = i;
+        sweep.start = e;
+        sweep.hash_edge(p, i);
+        sweep.hash_edge(&points[e], e);
+    }
+
+    let hull = &mut arena.hull;
+    hull.clear();
+    let mut e = sweep.start;
+    loop {
+        hull.push(e);
+        e = sweep.next[e];
+        if e == sweep.start {
+            break;
+        }
+    }
+
+    let triangulation = Triangulation {
+        triangles: sweep.triangles.clone(),
+        halfedges: sweep.halfedges.clone(),
+        hull: hull.clone(),
+    };
+    arena.triangles = sweep.triangles;
+    arena.halfedges = sweep.halfedges;
+    arena.prev = sweep.prev;
+    arena.next = sweep.next;
+    arena.tri = sweep.tri;
+    arena.hash = sweep.hash;
+    arena.edge_stack = sweep.edge_stack;
+
+    triangulation
+}
+
+/// The triangles being built and the hull swept around them.
+struct Sweep {
+    triangles: Vec<usize>,
+    halfedges: Vec<usize>,
+
+    /// Previous and next point of each point on the hull, ```next``` being [EMPTY] for points removed from it.
+    prev: Vec<usize>,
+    next: Vec<usize>,
+
+    /// Half-edge of the triangle of each hull edge, from the point.
+    tri: Vec<usize>,
+
+    /// Angular hash of the hull edges around ```center```.
+    hash: Vec<usize>,
+
+    /// The half-edges left to legalize.
+    edge_stack: Vec<usize>,
+    start: usize,
+    center: Point,
+}
+
+impl Sweep {
+    fn reset(&mut self, n: usize, max_triangles: usize) {
+        self.triangles.clear();
+        self.triangles.reserve(max_triangles * 3);
+        self.halfedges.clear();
+        self.halfedges.reserve(max_triangles * 3);
+        for buffer in [&mut self.prev, &mut self.next, &mut self.tri] {
+            buffer.clear();
+            buffer.resize(n, 0);
+        }
+        self.hash.clear();
+        self.hash.resize((n as f64).sqrt() as usize, EMPTY);
+        self.edge_stack.clear();
+    }
+
+    fn start_hull(&mut self, i0: usize, i1: usize, i2: usize, points: &[Point]) {
+        self.next[i0] = i1;
+        self.prev[i2] = i1;
+        self.next[i1] = i2;
+        self.prev[i0] = i2;
+        self.next[i2] = i0;
+        self.prev[i1] = i0;
+
+        self.tri[i0] = 0;
+        self.tri[i1] = 1;
+        self.tri[i2] = 2;
+
+        self.hash_edge(&points[i0], i0);
+        self.hash_edge(&points[i1], i1);
+        self.hash_edge(&points[i2], i2);
+    }
+
+    fn hash_key(&self, p: &Point) -> usize {
+        let dx = p.x - self.center.x;
+        let dy = p.y - self.center.y;
+
+        let p = dx / (dx.abs() + dy.abs());
+        let a = (if dy > 0.0 { 3.0 - p } else { 1.0 + p }) / 4.0;
+
+        let len = self.hash.len();
+        ((len as f64) * a).floor() as usize % len
+    }
+
+    fn hash_edge(&mut self, p: &Point, i: usize) {
+        let key = self.hash_key(p);
+        self.hash[key] = i;
+    }
+
+    fn find_visible_edge(&self, p: &Point, points: &[Point]) -> (usize, bool) {
+        let mut start: usize = 0;
+        let key = self.hash_key(p);
+        let len = self.hash.len();
+        for j in 0..len {
+            start = self.hash[(key + j) % len];
+            if start != EMPTY && self.next[start] != EMPTY {
+                break;
+            }
+        }
+        start = self.prev[start];
+        let mut e = start;
+
+        while orient(p, &points[e], &points[self.next[e]]) <= 0. {
+            e = self.next[e];
+            if e == start {
+                return (EMPTY, false);
+            }
+        }
+        (e, e == start)
+    }
+
+    fn add_triangle(
+        &mut self,
+        i0: usize,
+        i1: usize,
+        i2: usize,
+        a: usize,
+        b: usize,
+        c: usize,
+    ) -> usize {
+        let t = self.triangles.len();
+
+        self.triangles.extend_from_slice(&[i0, i1, i2]);
+        self.halfedges.extend_from_slice(&[a, b, c]);
+        if a != EMPTY {
+            self.halfedges[a] = t;
+        }
+        if b != EMPTY {
+            self.halfedges[b] = t + 1;
+        }
+        if c != EMPTY {
+            self.halfedges[c] = t + 2;
+        }
+
+        t
+    }
+
+    fn legalize(&mut self, mut a: usize, points: &[Point]) -> usize {
+        let mut ar;
+
+        loop {
+            let b = self.halfedges[a];
+            ar = prev_halfedge(a);
+
+            let illegal = b != EMPTY && {
+                let al = next_halfedge(a);
+                let bl = prev_halfedge(b);
+                let p0 = self.triangles[ar];
+                let pr = self.triangles[a];
+                let pl = self.triangles[al];
+                let p1 = self.triangles[bl];
+                in_circle(&points[p0], &points[pr], &points[pl], &points[p1])
+            };
+
+            if !illegal {
+                match self.edge_stack.pop() {
+                    Some(next) => a = next,
+                    None => return ar,
+                }
+                continue;
+            }
+
+            let bl = prev_halfedge(b);
+            let p1 = self.triangles[bl];
+            let p0 = self.triangles[ar];
+
+            self.triangles[a] = p1;
+            self.triangles[b] = p0;
+
+            let hbl = self.halfedges[bl];
+            let har = self.halfedges[ar];
+
+            // edge swapped on the other side of the hull, fix the half-edge reference
+            if hbl == EMPTY {
+                let mut e = self.start;
+                loop {
+                    if self.tri[e] == bl {
+                        self.tri[e] = a;
+                        break;
+                    }
+                    e = self.prev[e];
+                    if e == self.start {
+                        break;
+                    }
+                }
+            }
+
+            self.halfedges[a] = hbl;
+            self.halfedges[b] = har;
+            self.halfedges[ar] = bl;
+
+            if hbl != EMPTY {
+                self.halfedges[hbl] = a;
+            }
+            if har != EMPTY {
+                self.halfedges[har] = b;
+            }
+            if bl != EMPTY {
+                self.halfedges[bl] = ar;
+            }
+
+            self.edge_stack.push(next_halfedge(b));
+        }
+    }
+}
+
+fn dist2(p: &Point, q: &Point) -> f64 {
+    let dx = p.x - q.x;
+    let dy = p.y - q.y;
+    dx * dx + dy * dy
+}
+
+fn orient(p: &Point, q: &Point, r: &Point) -> f64 {
+    robust::orient2d(
+        robust::Coord { x: p.x, y: p.y },
+        robust::Coord { x: q.x, y: q.y },
+        robust::Coord { x: r.x, y: r.y },
+    )
+}
+
+fn circumdelta(a: &Point, b: &Point, c: &Point) -> (f64, f64) {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let ex = c.x - a.x;
+    let ey = c.y - a.y;
+
+    let bl = dx * dx + dy * dy;
+    let cl = ex * ex + ey * ey;
+    let d = 0.5 / (dx * ey - dy * ex);
+
+    let x = (ey * bl - dy * cl) * d;
+    let y = (dx * cl - ex * bl) * d;
+    (x, y)
+}
+
+fn circumradius2(a: &Point, b: &Point, c: &Point) -> f64 {
+    let (x, y) = circumdelta(a, b, c);
+    x * x + y * y
+}
+
+fn circumcenter(a: &Point, b: &Point, c: &Point) -> Point {
+    let (x, y) = circumdelta(a, b, c);
+    Point {
+        x: a.x + x,
+        y: a.y + y,
+    }
+}
+
+fn in_circle(a: &Point, b: &Point, c: &Point, p: &Point) -> bool {
+    let dx = a.x - p.x;
+    let dy = a.y - p.y;
+    let ex = b.x - p.x;
+    let ey = b.y - p.y;
+    let fx = c.x - p.x;
+    let fy = c.y - p.y;
+
+    let ap = dx * dx + dy * dy;
+    let bp = ex * ex + ey * ey;
+    let cp = fx * fx + fy * fy;
+
+    dx * (ey * cp - bp * fy) - dy * (ex * cp - bp * fx) + ap * (ex * fy - ey * fx) < 0.0
+}
+
+fn nearly_equals(p: &Point, q: &Point) -> bool {
+    (p.x - q.x).abs() <= delaunator::EPSILON && (p.y - q.y).abs() <= delaunator::EPSILON
+}
+
+/// Gets the index of the point closest to ```p0```, skipping the points at the same position, including ```p0``` itself.
+fn find_closest_point(points: &[Point], p0: &Point) -> Option<usize> {
+    let mut min_dist = f64::INFINITY;
+    let mut k: usize = 0;
+    for (i, p) in points.iter().enumerate() {
+        let d = dist2(p0, p);
+        if d > 0.0 && d < min_dist {
+            k = i;
+            min_dist = d;
+        }
+    }
+    if min_dist == f64::INFINITY {
+        None
+    } else {
+        Some(k)
+    }
+}
+
+fn find_seed_triangle(points: &[Point]) -> Option<(usize, usize, usize)> {
+    // pick a seed point close to the center of the bounding box
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let bbox_center = Point {
+        x: (min_x + max_x) / 2.0,
+        y: (min_y + max_y) / 2.0,
+    };
+    let i0 = find_closest_point(points, &bbox_center)?;
+    let p0 = &points[i0];
+
+    // find the point closest to the seed
+    let i1 = find_closest_point(points, p0)?;
+    let p1 = &points[i1];
+
+    // find the third point which forms the smallest circumcircle with the first two
+    let mut min_radius = f64::INFINITY;
+    let mut i2: usize = 0;
+    for (i, p) in points.iter().enumerate() {
+        if i == i0 || i == i1 {
+            continue;
+        }
+        let r = circumradius2(p0, p1, p);
+        if r < min_radius {
+            i2 = i;
+            min_radius = r;
+        }
+    }
+
+    if min_radius == f64::INFINITY {
+        None
+    } else if orient(p0, p1, &points[i2]) > 0. {
+        // counter-clockwise orientation of the seed points
+        Some((i0, i2, i1))
+    } else {
+        Some((i0, i1, i2))
+    }
+}
+
+/// Gets the triangulation of collinear ```points```, which has no triangles and a hull of the points sorted along their line, without duplicates.
+fn collinear_hull(points: &[Point]) -> Triangulation {
+    let mut triangulation = Triangulation {
+        triangles: Vec::new(),
+        halfedges: Vec::new(),
+        hull: Vec::new(),
+    };
+    let (x, y) = match points.first() {
+        Some(p) => (p.x, p.y),
+        None => return triangulation,
+    };
+
+    let mut dist: Vec<(usize, f64)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let d = p.x - x;
+            (i, if d == 0.0 { p.y - y } else { d })
+        })
+        .collect();
+    dist.sort_unstable_by(|&(_, da), &(_, db)| da.total_cmp(&db));
+
+    let mut d0 = f64::NEG_INFINITY;
+    for (i, distance) in dist {
+        if distance > d0 {
+            triangulation.hull.push(i);
+            d0 = distance;
+        }
+    }
+
+    triangulation
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::utils;
+
+    fn assert_same_triangulation(actual: &Triangulation, expected: &Triangulation) {
+        assert_eq!(actual.triangles, expected.triangles);
+        assert_eq!(actual.halfedges, expected.halfedges);
+        assert_eq!(actual.hull, expected.hull);
+    }
+
+    #[test]
+    fn arena_triangulates_like_delaunator() {
+        let mut rng = utils::test::rng();
+        let arena = &mut SweepHullArena::default();
+
+        // the arena is kept across sizes, so buffers left larger by a previous triangulation are reused
+        for _ in 0..50 {
+            let size = rng.gen_range(0..500);
+            let points: Vec<Point> = (0..size)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+            assert_same_triangulation(
+                &triangulate_in(&points, arena),
+                &delaunator::triangulate(&points),
+            );
+        }
+
+        // cocircular points of a grid, duplicates and collinear points
+        let grid: Vec<Point> = (0..100)
+            .map(|i| Point {
+                x: (i % 10) as f64,
+                y: (i / 10) as f64,
+            })
+            .collect();
+        let duplicates: Vec<Point> = grid.iter().chain(grid.iter().step_by(3)).cloned().collect();
+        let collinear: Vec<Point> = (0..20)
+            .map(|i| Point {
+                x: (i * 7 % 20) as f64,
+                y: 2.0 * (i * 7 % 20) as f64,
+            })
+            .collect();
+        for points in [grid, duplicates, collinear] {
+            assert_same_triangulation(
+                &triangulate_in(&points, arena),
+                &delaunator::triangulate(&points),
+            );
+        }
+    }
+}
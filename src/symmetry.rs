@@ -0,0 +1,490 @@
+use crate::{BuildError, ConvexBoundary, Point, Voronoi};
+
+/// Tolerance, relative to the size of the boundary, within which points are considered to be on the axis or at the same position.
+const SYMMETRY_EPSILON: f64 = 1e-9;
+
+/// The axis of a mirror-symmetric diagram, see [crate::VoronoiBuilder::set_symmetry()].
+///
+/// Sites are given on the side of the axis with the lowest coordinates and mirrored to the other side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymmetryAxis {
+    /// The vertical line at ```x```. Sites are given on its left, with lower X coordinates.
+    Vertical { x: f64 },
+
+    /// The horizontal line at ```y```. Sites are given above it, with lower Y coordinates, as the Y-axis points downwards.
+    Horizontal { y: f64 },
+}
+
+impl SymmetryAxis {
+    /// Reflects ```p``` across this axis.
+    ///
+    /// Points on the axis are their own reflection. The coordinates on the mirrored side of a symmetric diagram are exactly the reflection of those on the side
+    /// the sites are given on, but unless the axis is at zero, reflecting them back may differ from the original coordinates in the last bit.
+    pub fn reflect(&self, p: &Point) -> Point {
+        match *self {
+            SymmetryAxis::Vertical { x } => Point {
+                x: 2.0 * x - p.x,
+                y: p.y,
+            },
+            SymmetryAxis::Horizontal { y } => Point {
+                x: p.x,
+                y: 2.0 * y - p.y,
+            },
+        }
+    }
+
+    /// Gets the signed offset of ```p``` from this axis, negative on the side sites are given on.
+    fn offset(&self, p: &Point) -> f64 {
+        match *self {
+            SymmetryAxis::Vertical { x } => p.x - x,
+            SymmetryAxis::Horizontal { y } => p.y - y,
+        }
+    }
+
+    /// Gets the coordinate of ```p``` along this axis.
+    fn along(&self, p: &Point) -> f64 {
+        match self {
+            SymmetryAxis::Vertical { .. } => p.y,
+            SymmetryAxis::Horizontal { .. } => p.x,
+        }
+    }
+
+    /// Projects ```p``` on this axis.
+    fn snap(&self, p: &mut Point) {
+        match *self {
+            SymmetryAxis::Vertical { x } => p.x = x,
+            SymmetryAxis::Horizontal { y } => p.y = y,
+        }
+    }
+}
+
+/// What to do with the sites given on the mirrored side of a [SymmetryAxis].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrongSideSites {
+    /// The sites are removed before mirroring.
+    #[default]
+    Ignore,
+
+    /// The build fails with [BuildError::SiteOnMirroredSide].
+    Reject,
+}
+
+/// Gets the tolerance for positions within ```boundary```.
+pub(crate) fn tolerance<T: ConvexBoundary>(boundary: &T) -> f64 {
+    let (min, max) = boundary.vertices().iter().fold(
+        (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    );
+
+    SYMMETRY_EPSILON * (max.x - min.x).max(max.y - min.y).max(1.0)
+}
+
+/// Gets whether every vertex of ```boundary``` is reflected across ```axis``` onto another vertex.
+pub(crate) fn is_symmetric<T: ConvexBoundary>(boundary: &T, axis: &SymmetryAxis) -> bool {
+    let tolerance = tolerance(boundary);
+    let vertices = boundary.vertices();
+    vertices.iter().all(|v| {
+        let r = axis.reflect(v);
+        vertices
+            .iter()
+            .any(|w| (w.x - r.x).abs() <= tolerance && (w.y - r.y).abs() <= tolerance)
+    })
+}
+
+/// Prepares the sites given for a symmetric diagram: sites close to the axis are moved onto it, and the others are mirrored.
+///
+/// Sites on the mirrored side are removed or rejected per ```wrong_side```. The sites kept come first, in the order given, followed by the reflections of
+/// those not on the axis, in the same order. The index of the mirror of each site is returned along with the sites.
+pub(crate) fn mirror_sites(
+    sites: Vec<Point>,
+    axis: &SymmetryAxis,
+    wrong_side: WrongSideSites,
+    tolerance: f64,
+) -> Result<(Vec<Point>, Vec<usize>), BuildError> {
+    let mut kept = Vec::with_capacity(sites.len());
+    for (i, mut site) in sites.into_iter().enumerate() {
+        let offset = axis.offset(&site);
+        if offset.abs() <= tolerance {
+            axis.snap(&mut site);
+        } else if offset > 0.0 {
+            match wrong_side {
+                WrongSideSites::Ignore => continue,
+                WrongSideSites::Reject => return Err(BuildError::SiteOnMirroredSide(i)),
+            }
+        }
+        kept.push(site);
+    }
+
+    let mut mirrors: Vec<usize> = (0..kept.len()).collect();
+    let mut mirrored = Vec::with_capacity(kept.len());
+    for (i, site) in kept.iter().enumerate() {
+        if axis.offset(site) != 0.0 {
+            mirrors[i] = kept.len() + mirrored.len();
+            mirrored.push(axis.reflect(site));
+        }
+    }
+    let mirrored_from: Vec<usize> = (0..kept.len()).filter(|&i| mirrors[i] != i).collect();
+    mirrors.extend(mirrored_from);
+    kept.extend(mirrored);
+
+    Ok((kept, mirrors))
+}
+
+/// Finds the mirror of each of ```sites``` across ```axis```: the site within ```tolerance``` of its reflection, which is the site itself on the axis.
+///
+/// ```None``` is returned if the sites are not symmetric anymore, e.g. because one of them was moved without its mirror.
+pub(crate) fn find_mirrors(
+    sites: &[Point],
+    axis: &SymmetryAxis,
+    tolerance: f64,
+) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..sites.len()).collect();
+    order.sort_unstable_by(|&a, &b| axis.offset(&sites[a]).total_cmp(&axis.offset(&sites[b])));
+
+    let mut mirrors = Vec::with_capacity(sites.len());
+    for site in sites {
+        let reflection = axis.reflect(site);
+        let offset = axis.offset(&reflection);
+        let first = order.partition_point(|&i| axis.offset(&sites[i]) < offset - tolerance);
+        let mirror = order[first..]
+            .iter()
+            .take_while(|&&i| axis.offset(&sites[i]) <= offset + tolerance)
+            .find(|&&i| (axis.along(&sites[i]) - axis.along(&reflection)).abs() <= tolerance)?;
+        mirrors.push(*mirror);
+    }
+
+    // the mirror of the mirror of each site must be the site itself
+    mirrors
+        .iter()
+        .enumerate()
+        .all(|(i, &m)| mirrors[m] == i)
+        .then_some(mirrors)
+}
+
+/// Makes the vertices of a diagram of mirrored sites exactly symmetric across ```axis```.
+///
+/// Vertices within the tolerance of the axis are moved onto it, vertices at the same position within the tolerance are given the same coordinates,
+/// and each vertex on the mirrored side is replaced by the reflection of the vertex it mirrors.
+pub(crate) fn symmetrize<T: ConvexBoundary>(voronoi: &mut Voronoi<T>, axis: &SymmetryAxis) {
+    let tolerance = tolerance(&voronoi.boundary);
+    let mut used = vec![false; voronoi.circumcenters.len()];
+    voronoi.cells.iter().flatten().for_each(|&v| used[v] = true);
+
+    let vertices = &mut voronoi.circumcenters;
+    let mut on_axis = Vec::new();
+    let mut given_side = Vec::new();
+    let mut mirrored_side = Vec::new();
+    for (v, _) in used.iter().enumerate().filter(|(_, &used)| used) {
+        let offset = axis.offset(&vertices[v]);
+        if offset.abs() <= tolerance {
+            axis.snap(&mut vertices[v]);
+            on_axis.push(v);
+        } else if offset < 0.0 {
+            given_side.push(v);
+        } else {
+            mirrored_side.push(v);
+        }
+    }
+
+    // repeated vertices, e.g. where the triangulation had to pick a diagonal of the cocircular sites of two mirrored pairs, become the same point
+    on_axis.sort_by(|&a, &b| {
+        axis.along(&vertices[a])
+            .partial_cmp(&axis.along(&vertices[b]))
+            .unwrap()
+    });
+    let mut start = 0;
+    for i in 1..on_axis.len() {
+        let representative = vertices[on_axis[start]].clone();
+        if axis.along(&vertices[on_axis[i]]) - axis.along(&representative) <= tolerance {
+            vertices[on_axis[i]] = representative;
+        } else {
+            start = i;
+        }
+    }
+
+    let by_offset = |vertices: &[Point], list: &mut Vec<usize>| {
+        list.sort_by(|&a, &b| {
+            axis.offset(&vertices[a])
+                .partial_cmp(&axis.offset(&vertices[b]))
+                .unwrap()
+        });
+    };
+    let is_close =
+        |p: &Point, q: &Point| (p.x - q.x).abs() <= tolerance && (p.y - q.y).abs() <= tolerance;
+
+    by_offset(vertices, &mut given_side);
+    let mut merged = vec![false; given_side.len()];
+    for i in 0..given_side.len() {
+        if merged[i] {
+            continue;
+        }
+        let representative = vertices[given_side[i]].clone();
+        let offset = axis.offset(&representative);
+        for j in i + 1..given_side.len() {
+            let p = &vertices[given_side[j]];
+            if axis.offset(p) - offset > tolerance {
+                break;
+            }
+            if !merged[j] && is_close(p, &representative) {
+                merged[j] = true;
+                vertices[given_side[j]] = representative.clone();
+            }
+        }
+    }
+
+    // the given side is sorted by offset, and reflecting a point negates its offset
+    for &v in &mirrored_side {
+        let target = axis.reflect(&vertices[v]);
+        let offset = axis.offset(&target);
+        let first = given_side.partition_point(|&w| axis.offset(&vertices[w]) < offset - tolerance);
+        let partner = given_side[first..]
+            .iter()
+            .take_while(|&&w| axis.offset(&vertices[w]) <= offset + tolerance)
+            .filter(|&&w| is_close(&vertices[w], &target))
+            .min_by(|&&a, &&b| {
+                crate::utils::dist2(&vertices[a], &target)
+                    .partial_cmp(&crate::utils::dist2(&vertices[b], &target))
+                    .unwrap()
+            })
+            .copied();
+        if let Some(partner) = partner {
+            vertices[v] = axis.reflect(&vertices[partner]);
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the site whose cell mirrors the cell of ```site```, if the diagram was built with [crate::VoronoiBuilder::set_symmetry()].
+    ///
+    /// Sites on the axis are their own mirror. The mirroring is lost, and ```None``` returned, once the diagram is edited.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: -0.5, y: -0.2 }, Point { x: 0.0, y: 0.5 }, Point { x: -0.3, y: 0.3 }])
+    ///     .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Reject)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(v.sites().len(), 5);
+    /// assert_eq!(v.mirror_of(0), Some(3));
+    /// assert_eq!(v.mirror_of(1), Some(1));
+    /// assert_eq!(v.sites()[4], Point { x: 0.3, y: 0.3 });
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the diagram is symmetric and ```site``` does not exist.
+    pub fn mirror_of(&self, site: usize) -> Option<usize> {
+        self.symmetry.as_ref().map(|(_, mirrors)| mirrors[site])
+    }
+
+    /// Gets the axis the diagram is mirror-symmetric across, if it was built with [crate::VoronoiBuilder::set_symmetry()] and has not been edited since.
+    pub fn symmetry_axis(&self) -> Option<SymmetryAxis> {
+        self.symmetry.as_ref().map(|(axis, _)| *axis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, BoundingBox, ConvexPolygon, VoronoiBuilder};
+
+    fn random_half_sites(size: usize) -> Vec<Point> {
+        let mut rng = utils::test::rng();
+        let mut sites: Vec<Point> = (0..size)
+            .map(|_| Point {
+                x: rng.gen_range(-1.0..0.0),
+                y: rng.gen_range(-1.0..1.0),
+            })
+            .collect();
+        // sites on the axis, or close enough to be moved onto it
+        sites.extend((0..5).map(|i| Point {
+            x: if i % 2 == 0 { 0.0 } else { -1e-12 },
+            y: i as f64 / 3.0 - 0.7,
+        }));
+        sites
+    }
+
+    /// Gets the vertices of the cell of ```site```, without repeated vertices.
+    fn polygon<T: ConvexBoundary>(voronoi: &Voronoi<T>, site: usize) -> Vec<Point> {
+        let mut polygon: Vec<Point> = Vec::new();
+        for v in voronoi.cell(site).iter_vertices() {
+            if polygon.last() != Some(v) {
+                polygon.push(v.clone());
+            }
+        }
+        while polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+        polygon
+    }
+
+    /// Asserts that each vertex of the cell of ```mirror``` is, bit for bit, the reflection of the matching vertex of the cell of ```site```, or the other way around.
+    fn assert_reflected_cells<T: ConvexBoundary>(voronoi: &Voronoi<T>, site: usize, mirror: usize) {
+        let axis = voronoi.symmetry_axis().unwrap();
+        let a = polygon(voronoi, site);
+        // reflecting a cell reverses the order of its vertices
+        let mut b = polygon(voronoi, mirror);
+        b.reverse();
+
+        let bits = |p: &Point| (p.x.to_bits(), p.y.to_bits());
+        let reflects = |p: &Point, q: &Point| {
+            bits(&axis.reflect(p)) == bits(q) || bits(p) == bits(&axis.reflect(q))
+        };
+        assert_eq!(a.len(), b.len(), "{:?} and {:?} differ.", a, b);
+        let start = b
+            .iter()
+            .position(|q| reflects(&a[0], q))
+            .unwrap_or_else(|| panic!("{:?} and {:?} differ.", a, b));
+        assert!(
+            a.iter()
+                .zip(b.iter().cycle().skip(start))
+                .all(|(p, q)| reflects(p, q)),
+            "{:?} and {:?} differ.",
+            a,
+            b
+        );
+    }
+
+    fn assert_mirror_symmetric<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let axis = voronoi.symmetry_axis().expect("Symmetric diagram expected");
+        for site in 0..voronoi.sites().len() {
+            let mirror = voronoi.mirror_of(site).expect("Mirror expected");
+            assert_eq!(voronoi.mirror_of(mirror), Some(site));
+            if mirror >= site {
+                assert_eq!(
+                    voronoi.sites()[mirror],
+                    axis.reflect(&voronoi.sites()[site])
+                );
+            }
+            assert_reflected_cells(voronoi, site, mirror);
+        }
+    }
+
+    #[test]
+    fn mirrored_cells_are_exact_reflections() {
+        for _ in 0..10 {
+            let sites = random_half_sites(200);
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(sites.clone())
+                .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Reject)
+                .build()
+                .expect("Some voronoi expected");
+            assert_eq!(voronoi.sites().len(), 2 * 200 + 5);
+            assert_mirror_symmetric(&voronoi);
+            utils::test::validate_voronoi(&voronoi);
+
+            // sites on the axis are moved onto it and kept once
+            for site in 200..205 {
+                assert_eq!(voronoi.sites()[site].x, 0.0);
+                assert_eq!(voronoi.mirror_of(site), Some(site));
+            }
+
+            // above a horizontal axis, away from the center of the boundary
+            let boundary = BoundingBox::new(Point { x: 0.0, y: 0.5 }, 2.0, 3.0);
+            let flipped: Vec<Point> = sites
+                .iter()
+                .map(|p| Point {
+                    x: p.y,
+                    y: p.x + 0.5,
+                })
+                .collect();
+            let voronoi = VoronoiBuilder::default()
+                .set_sites(flipped)
+                .set_boundary(boundary)
+                .set_symmetry(SymmetryAxis::Horizontal { y: 0.5 }, WrongSideSites::Reject)
+                .build()
+                .expect("Some voronoi expected");
+            assert_mirror_symmetric(&voronoi);
+            utils::test::validate_voronoi(&voronoi);
+        }
+    }
+
+    #[test]
+    fn symmetry_is_kept_by_relaxation_and_other_boundaries() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_half_sites(100))
+            .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Ignore)
+            .set_lloyd_relaxation_iterations(3)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(voronoi.sites().len(), 205);
+        assert_mirror_symmetric(&voronoi);
+        utils::test::validate_voronoi(&voronoi);
+
+        // regular polygons are symmetric across their vertical diameter, and those with an odd number of sides have a vertex on it
+        for &sides in &[5, 6] {
+            let polygon = ConvexPolygon::regular(sides, 1.0);
+            let sites: Vec<Point> = random_half_sites(100)
+                .into_iter()
+                .filter(|p| polygon.is_inside(p))
+                .collect();
+            let voronoi = VoronoiBuilder::default()
+                .set_sites(sites)
+                .set_boundary(polygon.clone())
+                .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Reject)
+                .build()
+                .expect("Some voronoi expected");
+            assert_mirror_symmetric(&voronoi);
+        }
+    }
+
+    #[test]
+    fn wrong_sides_and_boundaries_are_reported() {
+        let sites = vec![
+            Point { x: -0.5, y: 0.0 },
+            Point { x: 0.5, y: 0.2 },
+            Point { x: -0.2, y: 0.5 },
+        ];
+        let builder = || VoronoiBuilder::<BoundingBox>::default().set_sites(sites.clone());
+
+        let rejected = builder()
+            .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Reject)
+            .try_build();
+        assert_eq!(rejected.unwrap_err(), BuildError::SiteOnMirroredSide(1));
+
+        let ignored = builder()
+            .set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Ignore)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(ignored.sites().len(), 4);
+        assert_eq!(ignored.sites()[1], Point { x: -0.2, y: 0.5 });
+
+        let asymmetric = builder()
+            .set_symmetry(SymmetryAxis::Vertical { x: 0.5 }, WrongSideSites::Reject)
+            .try_build();
+        assert_eq!(asymmetric.unwrap_err(), BuildError::AsymmetricBoundary);
+
+        // edits lose the symmetry
+        let mut edited = ignored;
+        edited
+            .insert_site(Point { x: 0.7, y: 0.7 })
+            .expect("Insert expected to succeed");
+        assert_eq!(edited.mirror_of(0), None);
+        assert_eq!(edited.symmetry_axis(), None);
+    }
+}
@@ -0,0 +1,298 @@
+use delaunator::{Triangulation, EMPTY};
+
+use crate::{
+    cell_builder::{calculate_incoming_edges, CellBuilderArena},
+    BuildError, CellAdjacency, ClipBehavior, ConvexBoundary, Engine, Point,
+    TopologicalNeighborSiteIterator, Voronoi,
+};
+
+/// The topology of a Voronoi diagram, without the geometry of its cells: the Delaunay triangulation of the sites and the adjacency it implies.
+///
+/// Two sites are neighbors in the topology if, and only if, they share a Delaunay edge, as in [TopologicalNeighborSiteIterator].
+/// The neighbors of a site in the full diagram, see [crate::VoronoiCell::iter_neighbors()], are the neighbors in the topology whose clipped cells
+/// share an edge longer than the adjacency epsilon, in the same order. So the full adjacency is always a subset of the topology adjacency, and they only differ for:
+/// * cells on the hull, whose shared edges may be clipped away by the boundary;
+/// * cells of nearly cocircular sites, whose shared edges may be shorter than the adjacency epsilon.
+///
+/// Use [crate::VoronoiBuilder::build_topology()] to obtain an instance of this type.
+#[derive(Debug, Clone)]
+pub struct VoronoiTopology {
+    sites: Vec<Point>,
+    triangulation: Triangulation,
+    site_to_incoming_leftmost_halfedge: Vec<usize>,
+    adjacency: CellAdjacency,
+    on_hull: Vec<bool>,
+    clip_behavior: ClipBehavior,
+    adjacency_epsilon: f64,
+    engine: Engine,
+}
+
+impl VoronoiTopology {
+    pub(crate) fn new(
+        sites: Vec<Point>,
+        clip_behavior: ClipBehavior,
+        adjacency_epsilon: f64,
+        engine: Engine,
+    ) -> Result<Self, BuildError> {
+        let mut triangulation = engine.triangulate(&sites);
+        if triangulation.triangles.is_empty() {
+            // a single site, or none, has no neighbors but is a valid topology
+            if sites.len() > 1 {
+                return Err(BuildError::Degenerate);
+            }
+            triangulation.hull = (0..sites.len()).collect();
+        }
+
+        let site_to_incoming_leftmost_halfedge =
+            calculate_incoming_edges(&triangulation, sites.len());
+        let adjacency = CellAdjacency::from_neighbors((0..sites.len()).map(|site| {
+            TopologicalNeighborSiteIterator::with_triangulation(
+                &triangulation,
+                &site_to_incoming_leftmost_halfedge,
+                site,
+            )
+        }));
+        // the left-most incoming edge of a site on the hull is a hull edge
+        let on_hull = site_to_incoming_leftmost_halfedge
+            .iter()
+            .map(|&e| e == EMPTY || triangulation.halfedges[e] == EMPTY)
+            .collect();
+
+        Ok(Self {
+            sites,
+            triangulation,
+            site_to_incoming_leftmost_halfedge,
+            adjacency,
+            on_hull,
+            clip_behavior,
+            adjacency_epsilon,
+            engine,
+        })
+    }
+
+    /// Gets the sites of the diagram. Sites outside of the boundary were removed, as they are by [crate::VoronoiBuilder::build()].
+    #[inline]
+    pub fn sites(&self) -> &Vec<Point> {
+        &self.sites
+    }
+
+    /// Gets the Delaunay triangulation of the sites.
+    #[inline]
+    pub fn triangulation(&self) -> &Triangulation {
+        &self.triangulation
+    }
+
+    /// Gets the adjacency graph of the sites. See [VoronoiTopology] for how it relates to [Voronoi::adjacency()].
+    #[inline]
+    pub fn adjacency(&self) -> &CellAdjacency {
+        &self.adjacency
+    }
+
+    /// Gets the neighbors of ```site```, in the same order returned by [TopologicalNeighborSiteIterator].
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist.
+    #[inline]
+    pub fn neighbors(&self, site: usize) -> &[usize] {
+        self.adjacency.neighbors_of(site)
+    }
+
+    /// Returns whether ```site``` is on the convex hull of the sites.
+    ///
+    /// Cells of these sites are always on the hull of the full diagram, see [crate::VoronoiCell::is_on_hull()], but clipping may put other cells on it too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```site``` does not exist.
+    #[inline]
+    pub fn is_on_hull(&self, site: usize) -> bool {
+        self.on_hull[site]
+    }
+
+    /// Gets an iterator over the neighbors of ```site```.
+    pub fn iter_neighbors(&self, site: usize) -> TopologicalNeighborSiteIterator<'_> {
+        TopologicalNeighborSiteIterator::with_triangulation(
+            &self.triangulation,
+            &self.site_to_incoming_leftmost_halfedge,
+            site,
+        )
+    }
+
+    /// Consumes this topology and calculates the cells of the diagram within ```boundary```, reusing the triangulation.
+    ///
+    /// The result is the same diagram built by [crate::VoronoiBuilder::build()] with the same sites and configuration.
+    /// Since the sites are not triangulated again, they must be inside ```boundary``` unless clipping was disabled with [ClipBehavior::None].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let topology = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build_topology()
+    ///     .unwrap();
+    /// let voronoi = topology.with_geometry(BoundingBox::default()).unwrap();
+    /// assert_eq!(voronoi.sites().len(), 100);
+    ///```
+    pub fn with_geometry<T: ConvexBoundary>(self, boundary: T) -> Result<Voronoi<T>, BuildError> {
+        if self.clip_behavior != ClipBehavior::None {
+            if let Some(site) = self.sites.iter().position(|p| !boundary.is_inside(p)) {
+                return Err(BuildError::SiteOutsideBoundary(site));
+            }
+        }
+
+        Voronoi::from_triangulation(
+            self.sites,
+            self.triangulation,
+            boundary,
+            self.clip_behavior,
+            self.adjacency_epsilon,
+            self.engine,
+            &mut CellBuilderArena::default(),
+        )
+        .ok_or(BuildError::Degenerate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, utils::test::random_sites, BoundingBox, VoronoiBuilder};
+
+    #[test]
+    fn topology_adjacency_contains_full_adjacency() {
+        let sites = random_sites(500);
+        let topology = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build_topology()
+            .expect("Some topology expected");
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(topology.sites(), voronoi.sites());
+
+        for cell in voronoi.iter_cells() {
+            let site = cell.site();
+            let neighbors: Vec<usize> = cell.iter_neighbors().collect();
+            let topology_neighbors = topology.neighbors(site);
+            assert_eq!(
+                topology_neighbors,
+                topology.iter_neighbors(site).collect::<Vec<usize>>()
+            );
+            assert_eq!(
+                topology_neighbors,
+                TopologicalNeighborSiteIterator::new(&voronoi, site).collect::<Vec<usize>>()
+            );
+
+            // full neighbors are the topology neighbors sharing a long enough edge, in order
+            let expected: Vec<usize> = topology_neighbors
+                .iter()
+                .copied()
+                .filter(|&n| cell.shared_edge_length(n) > voronoi.adjacency_epsilon())
+                .collect();
+            assert_eq!(neighbors, expected, "Site {}.", site);
+
+            if topology.is_on_hull(site) {
+                assert!(cell.is_on_hull());
+            }
+            if !cell.is_on_hull() {
+                assert_eq!(neighbors, topology_neighbors, "Interior site {}.", site);
+            }
+        }
+    }
+
+    #[test]
+    fn hull_neighbors_may_be_clipped_away() {
+        // the Voronoi edge between the first two sites is a ray going away from the box, which is all taken by the cell of the third site
+        let sites = vec![
+            Point { x: -0.75, y: 0.75 },
+            Point { x: 0.75, y: 0.75 },
+            Point { x: 0.0, y: 0.875 },
+        ];
+        let topology = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build_topology()
+            .expect("Some topology expected");
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+
+        assert!((0..3).all(|site| topology.is_on_hull(site)));
+        assert!(topology.neighbors(0).contains(&1));
+        assert!(topology.neighbors(1).contains(&0));
+        assert!(!voronoi.cell(0).iter_neighbors().any(|n| n == 1));
+        assert!(!voronoi.cell(1).iter_neighbors().any(|n| n == 0));
+        assert_eq!(voronoi.cell(2).iter_neighbors().count(), 2);
+    }
+
+    #[test]
+    fn with_geometry_equals_direct_build() {
+        for clip_behavior in [
+            ClipBehavior::Clip,
+            ClipBehavior::RemoveSitesOutsideBoundaryOnly,
+            ClipBehavior::None,
+        ] {
+            let sites = random_sites(300);
+            let builder = || {
+                VoronoiBuilder::default()
+                    .set_sites(sites.clone())
+                    .set_boundary(BoundingBox::new_centered_square(1.5))
+                    .set_clip_behavior(clip_behavior)
+                    .set_adjacency_epsilon(1e-3)
+            };
+
+            let voronoi = builder().build().expect("Some voronoi expected");
+            let topology = builder().build_topology().expect("Some topology expected");
+            let upgraded = topology
+                .with_geometry(BoundingBox::new_centered_square(1.5))
+                .expect("Upgrade expected to succeed");
+
+            assert!(utils::test::geometry_eq(&voronoi, &upgraded));
+            assert_eq!(voronoi.adjacency(), upgraded.adjacency());
+            assert_eq!(upgraded.adjacency_epsilon(), 1e-3);
+            if clip_behavior == ClipBehavior::Clip {
+                utils::test::validate_voronoi(&upgraded);
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let collinear = (0..10)
+            .map(|i| Point {
+                x: i as f64 / 10.0,
+                y: 0.0,
+            })
+            .collect();
+        assert_eq!(
+            VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(collinear)
+                .build_topology()
+                .unwrap_err(),
+            BuildError::Degenerate
+        );
+
+        let sites = vec![
+            Point { x: -0.5, y: 0.0 },
+            Point { x: 0.5, y: 0.0 },
+            Point { x: 0.0, y: 0.9 },
+        ];
+        let topology = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build_topology()
+            .expect("Some topology expected");
+        assert_eq!(
+            topology
+                .clone()
+                .with_geometry(BoundingBox::new_centered_square(1.0))
+                .unwrap_err(),
+            BuildError::SiteOutsideBoundary(2)
+        );
+        assert!(topology
+            .with_geometry(BoundingBox::new_centered_square(2.0))
+            .is_ok());
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    fmt::Display,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{debug_validation::validate_cell, utils, ConvexBoundary, EditError, Voronoi};
+
+/// An error returned when starting or committing a [Transaction].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionError {
+    /// A transaction was started while another transaction on the same diagram was open.
+    Nested,
+
+    /// An edit in the transaction failed.
+    Edit(EditError),
+
+    /// A cell changed by the transaction is not a valid cell. The diagram was rolled back.
+    InvalidCell {
+        /// The site of the cell, as an index after the edits of the transaction.
+        site: usize,
+
+        /// Why the cell is not valid.
+        reason: &'static str,
+    },
+}
+
+impl Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionError::Nested => write!(f, "Transactions cannot be nested"),
+            TransactionError::Edit(error) => write!(f, "Edit failed: {}", error),
+            TransactionError::InvalidCell { site, reason } => {
+                write!(f, "Cell {} is not valid: {}", site, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl From<EditError> for TransactionError {
+    fn from(error: EditError) -> Self {
+        TransactionError::Edit(error)
+    }
+}
+
+/// Whether a [Transaction] is open on a diagram.
+///
+/// The transaction edits only the diagram it was started on, so a clone of that diagram, such as the snapshot the transaction restores, never has one open.
+#[derive(Debug, Default)]
+pub(crate) struct TransactionFlag(bool);
+
+impl TransactionFlag {
+    pub(crate) fn is_open(&self) -> bool {
+        self.0
+    }
+}
+
+impl Clone for TransactionFlag {
+    fn clone(&self) -> Self {
+        TransactionFlag(false)
+    }
+}
+
+/// A group of edits to a [Voronoi] diagram that are either all kept or all discarded.
+///
+/// The diagram is edited through the transaction with the same methods used to edit it directly, such as [Voronoi::insert_site()],
+/// and can be inspected in between. [Self::commit()] validates the cells changed by the edits and keeps them.
+/// Dropping the transaction without committing it, or calling [Self::rollback()], restores the diagram exactly as it was when the transaction started,
+/// including its generation.
+///
+/// Use [Voronoi::transaction()] to obtain an instance of this type.
+///
+/// # Examples
+///
+///```
+/// use voronoice::*;
+/// let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+///     .generate_square_sites(2)
+///     .build()
+///     .unwrap();
+///
+/// let result = (|| -> Result<(), TransactionError> {
+///     let mut transaction = voronoi.transaction()?;
+///     transaction.remove_site(0)?;
+///     // only two sites would be left, so this fails and the first removal is discarded
+///     transaction.remove_site(0)?;
+///     transaction.commit()
+/// })();
+///
+/// assert_eq!(result, Err(TransactionError::Edit(EditError::Degenerate)));
+/// assert_eq!(voronoi.sites().len(), 4);
+///```
+pub struct Transaction<'v, T: ConvexBoundary> {
+    voronoi: &'v mut Voronoi<T>,
+
+    /// The diagram as it was when the transaction started, or ```None``` once committed.
+    snapshot: Option<Voronoi<T>>,
+}
+
+impl<T: ConvexBoundary> Transaction<'_, T> {
+    /// Validates the cells changed by the edits of the transaction and keeps the edits.
+    ///
+    /// If a changed cell is not valid, the diagram is rolled back and the error is returned.
+    pub fn commit(mut self) -> Result<(), TransactionError> {
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .expect("Snapshot expected until committed");
+        validate_changed_cells(snapshot, self.voronoi)?;
+
+        self.snapshot = None;
+        self.voronoi.in_transaction = TransactionFlag(false);
+        Ok(())
+    }
+
+    /// Discards the edits of the transaction, restoring the diagram as it was when the transaction started.
+    ///
+    /// This is the same as dropping the transaction.
+    pub fn rollback(self) {}
+}
+
+impl<T: ConvexBoundary> Deref for Transaction<'_, T> {
+    type Target = Voronoi<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.voronoi
+    }
+}
+
+impl<T: ConvexBoundary> DerefMut for Transaction<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.voronoi
+    }
+}
+
+impl<T: ConvexBoundary> Drop for Transaction<'_, T> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.voronoi = snapshot;
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Starts a transaction on this diagram. See [Transaction].
+    ///
+    /// Returns [TransactionError::Nested] if called through an open transaction on this diagram.
+    pub fn transaction(&mut self) -> Result<Transaction<'_, T>, TransactionError> {
+        if self.in_transaction.is_open() {
+            return Err(TransactionError::Nested);
+        }
+
+        let snapshot = self.clone();
+        self.in_transaction = TransactionFlag(true);
+        Ok(Transaction {
+            voronoi: self,
+            snapshot: Some(snapshot),
+        })
+    }
+}
+
+/// Validates the cells of ```edited``` that do not have the same vertices as the corresponding cell of ```original```, including inserted cells
+/// and the neighbors reshaped by an edit.
+fn validate_changed_cells<T: ConvexBoundary>(
+    original: &Voronoi<T>,
+    edited: &Voronoi<T>,
+) -> Result<(), TransactionError> {
+    let edits = edited
+        .edits_since(original.generation())
+        .expect("Edits expected since the start of the transaction");
+
+    let mut unchanged = vec![false; edited.sites().len()];
+    for site in 0..original.sites().len() {
+        let current = edits
+            .iter()
+            .try_fold(site, |site, (_, edit)| edit.forward(site).ok());
+        if let Some(current) = current {
+            unchanged[current] = utils::same_cycle(
+                &original.cells[site],
+                &original.circumcenters,
+                &edited.cells[current],
+                &edited.circumcenters,
+            );
+        }
+    }
+
+    (0..edited.sites().len())
+        .filter(|&site| !unchanged[site])
+        .try_for_each(|site| {
+            validate_cell(edited, site)
+                .map_err(|reason| TransactionError::InvalidCell { site, reason })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils::test::random_voronoi, BoundingBox, Point, VoronoiBuilder};
+
+    #[test]
+    fn failed_edit_rolls_back_exactly() {
+        let mut voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(2)
+            .build()
+            .expect("Some voronoi expected");
+        let hash = utils::test::geometry_hash(&voronoi);
+        let generation = voronoi.generation();
+
+        {
+            let mut transaction = voronoi.transaction().expect("Transaction expected");
+            transaction
+                .move_site(1, Point { x: 0.1, y: 0.2 })
+                .expect("Move expected to succeed");
+            transaction
+                .remove_site(0)
+                .expect("Remove expected to succeed");
+            assert_eq!(transaction.sites().len(), 3);
+            assert_eq!(transaction.remove_site(0), Err(EditError::Degenerate));
+        }
+
+        assert_eq!(utils::test::geometry_hash(&voronoi), hash);
+        assert_eq!(voronoi.generation(), generation);
+
+        // the diagram can be edited again afterwards
+        let mut transaction = voronoi.transaction().expect("Transaction expected");
+        transaction
+            .insert_site(Point { x: 0.3, y: -0.4 })
+            .expect("Insert expected to succeed");
+        transaction.rollback();
+        assert_eq!(utils::test::geometry_hash(&voronoi), hash);
+    }
+
+    #[test]
+    fn committed_transaction_equals_direct_edits() {
+        let mut rng = utils::test::rng();
+        for _ in 0..10 {
+            let mut direct = random_voronoi(100);
+            let mut transacted = direct.clone();
+
+            let mut transaction = transacted.transaction().expect("Transaction expected");
+            for _ in 0..20 {
+                let site = rng.gen_range(0..direct.sites().len());
+                let position = Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                };
+                match rng.gen_range(0..4) {
+                    0 => assert_eq!(
+                        transaction.move_site(site, position.clone()),
+                        direct.move_site(site, position)
+                    ),
+                    1 => assert_eq!(
+                        transaction.insert_site(position.clone()),
+                        direct.insert_site(position)
+                    ),
+                    2 => assert_eq!(transaction.remove_site(site), direct.remove_site(site)),
+                    _ => {
+                        let other = (site + 1) % direct.sites().len();
+                        assert_eq!(
+                            transaction.merge_sites(site, other),
+                            direct.merge_sites(site, other)
+                        )
+                    }
+                }
+            }
+            transaction.commit().expect("Commit expected to succeed");
+
+            assert!(utils::test::geometry_eq(&transacted, &direct));
+            assert_eq!(transacted.adjacency(), direct.adjacency());
+            utils::test::validate_voronoi(&transacted);
+        }
+    }
+
+    #[test]
+    fn nested_transactions_are_rejected() {
+        let mut voronoi = random_voronoi(20);
+        let hash = utils::test::geometry_hash(&voronoi);
+
+        let mut transaction = voronoi.transaction().expect("Transaction expected");
+        transaction
+            .remove_site(3)
+            .expect("Remove expected to succeed");
+        assert!(matches!(
+            transaction.transaction(),
+            Err(TransactionError::Nested)
+        ));
+
+        // the outer transaction is still open
+        transaction
+            .remove_site(3)
+            .expect("Remove expected to succeed");
+        drop(transaction);
+        assert_eq!(utils::test::geometry_hash(&voronoi), hash);
+
+        let transaction = voronoi.transaction().expect("Transaction expected");
+        transaction.commit().expect("Commit expected to succeed");
+        assert!(voronoi.transaction().is_ok());
+    }
+
+    #[test]
+    fn invalid_cells_are_reported() {
+        let voronoi = random_voronoi(20);
+        let site = (0..20)
+            .find(|&s| !voronoi.cell(s).is_on_hull())
+            .expect("Some interior cell expected");
+        assert_eq!(validate_cell(&voronoi, site), Ok(()));
+
+        // the same cell, with its vertices reversed
+        let mut reversed = voronoi.clone();
+        reversed.cells[site].reverse();
+        assert_eq!(validate_cell(&reversed, site), Err("not counter-clockwise"));
+        assert!(matches!(
+            validate_changed_cells(&voronoi, &reversed),
+            Err(TransactionError::InvalidCell { site: s, .. }) if s == site
+        ));
+    }
+
+    #[test]
+    fn reshaped_neighbors_are_validated() {
+        let voronoi = random_voronoi(50);
+        let site = (0..50)
+            .find(|&s| !voronoi.cell(s).is_on_hull())
+            .expect("Some interior cell expected");
+        let mut edited = voronoi.clone();
+        let position = voronoi.sites()[site].clone();
+        edited
+            .move_site(
+                site,
+                Point {
+                    x: position.x + 1e-2,
+                    y: position.y,
+                },
+            )
+            .expect("Move expected to succeed");
+        assert_eq!(validate_changed_cells(&voronoi, &edited), Ok(()));
+
+        // a neighbor keeps its index but not its shape, so it is validated as well
+        let neighbor = edited
+            .cell(site)
+            .iter_neighbors()
+            .find(|&n| {
+                !utils::same_cycle(
+                    &voronoi.cells[n],
+                    &voronoi.circumcenters,
+                    &edited.cells[n],
+                    &edited.circumcenters,
+                )
+            })
+            .expect("Some reshaped neighbor expected");
+        edited.cells[neighbor].reverse();
+        assert_eq!(
+            validate_changed_cells(&voronoi, &edited),
+            Err(TransactionError::InvalidCell {
+                site: neighbor,
+                reason: "not counter-clockwise"
+            })
+        );
+    }
+
+    #[test]
+    fn clones_have_no_open_transaction() {
+        let mut voronoi = random_voronoi(20);
+        let transaction = voronoi.transaction().expect("Transaction expected");
+        let mut clone = (*transaction).clone();
+        assert!(clone.transaction().is_ok());
+        drop(transaction);
+
+        voronoi.in_transaction = TransactionFlag(true);
+        let mut dynamic = voronoi.into_dyn();
+        assert!(dynamic.transaction().is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+use delaunator::{Point, Triangulation};
+
+use crate::{instrument::phase, sweep_hull::SweepHullArena};
+
+/// The algorithm used to calculate the Delaunay triangulation of the sites.
+///
+/// Every engine produces a [Triangulation] with the same layout, so the rest of the diagram construction does not depend on the engine used.
+/// Use [crate::VoronoiBuilder::set_triangulator()] to select an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// The [delaunator](https://docs.rs/delaunator/*/delaunator) crate. This is the fastest engine.
+    ///
+    /// Diagrams built with a kept arena, as by [crate::batch::build_all()], are triangulated by a port of its sweep-hull algorithm
+    /// which keeps its scratch buffers, and gives the same triangulation.
+    #[default]
+    Delaunator,
+
+    /// An incremental Bowyer–Watson triangulation, inserting sites in Hilbert curve order and using robust geometric predicates.
+    ///
+    /// Slower than [Engine::Delaunator], but independently implemented, so it is useful to cross-check the diagrams of nearly degenerate sites.
+    #[cfg(feature = "alt-triangulator")]
+    BowyerWatson,
+}
+
+impl Engine {
+    /// Calculates the Delaunay triangulation of ```sites```.
+    pub(crate) fn triangulate(&self, sites: &[Point]) -> Triangulation {
+        self.triangulate_with(sites, delaunator::triangulate)
+    }
+
+    /// Calculates the Delaunay triangulation of ```sites```, keeping the scratch buffers of [Engine::Delaunator] in ```arena```.
+    pub(crate) fn triangulate_in(
+        &self,
+        sites: &[Point],
+        arena: &mut SweepHullArena,
+    ) -> Triangulation {
+        self.triangulate_with(sites, |sites| {
+            crate::sweep_hull::triangulate_in(sites, arena)
+        })
+    }
+
+    fn triangulate_with(
+        &self,
+        sites: &[Point],
+        delaunator: impl FnOnce(&[Point]) -> Triangulation,
+    ) -> Triangulation {
+        let phase = phase!(
+            debug_span,
+            "triangulation",
+            sites = sites.len(),
+            triangles = tracing::field::Empty
+        );
+        let triangulation = match self {
+            Engine::Delaunator => delaunator(sites),
+            #[cfg(feature = "alt-triangulator")]
+            Engine::BowyerWatson => crate::bowyer_watson::triangulate(sites),
+        };
+        phase.record("triangles", triangulation.triangles.len() / 3);
+
+        triangulation
+    }
+}
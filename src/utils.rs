@@ -1,6 +1,6 @@
-use delaunator::{next_halfedge, Point, Triangulation};
+use delaunator::{next_halfedge, Point, Triangulation, EMPTY};
 
-use crate::{ConvexBoundary, Voronoi};
+use crate::{ConvexBoundary, TopologicalNeighborSiteIterator, Voronoi};
 
 pub(crate) const EQ_EPSILON: f64 = 4. * std::f64::EPSILON;
 
@@ -83,39 +83,388 @@ pub fn dist2(a: &Point, b: &Point) -> f64 {
     (x * x) + (y * y)
 }
 
+/// Returns the point of segment ```a -> b``` closest to ```p```.
+pub fn closest_point_on_segment(a: &Point, b: &Point, p: &Point) -> Point {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length2 = dx * dx + dy * dy;
+    if length2 == 0.0 {
+        return a.clone();
+    }
+
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length2).clamp(0.0, 1.0);
+    Point {
+        x: a.x + t * dx,
+        y: a.y + t * dy,
+    }
+}
+
+/// Returns the smallest floating point value greater than ```x```.
+pub fn next_up(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        x
+    } else if x == 0.0 {
+        f64::from_bits(1)
+    } else if x > 0.0 {
+        f64::from_bits(x.to_bits() + 1)
+    } else {
+        f64::from_bits(x.to_bits() - 1)
+    }
+}
+
 #[inline]
 pub fn abs_diff_eq(a: f64, b: f64, epsilon: f64) -> bool {
     (if a > b { a - b } else { b - a }) <= epsilon
 }
 
 /// Given a voronoi and two sites, returns whether they share a common voronoi edge.
+///
+/// This is the single definition of adjacency used throughout the crate: two cells are neighbors if and only if the edge shared by their clipped polygons
+/// is longer than the diagram's adjacency epsilon (see [crate::VoronoiBuilder::set_adjacency_epsilon()]).
+/// Cells that only touch at a single point are not neighbors.
 pub fn has_common_voronoi_edge<T: ConvexBoundary>(
     voronoi: &Voronoi<T>,
     a: usize,
     b: usize,
 ) -> bool {
-    let mut common = 0;
-    for &ta in voronoi.cell(a).triangles() {
-        for &tb in voronoi.cell(b).triangles() {
-            if ta == tb {
-                common += 1;
+    a != b && shared_voronoi_edge_length(voronoi, a, b) > voronoi.adjacency_epsilon
+}
+
+/// Given a voronoi and two sites, returns the length of the voronoi edge shared by their cells.
+///
+/// An edge ```u -> v``` of the cell of ```a``` is shared with the cell of ```b``` when ```v -> u``` is an edge of the cell of ```b```.
+pub fn shared_voronoi_edge_length<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    a: usize,
+    b: usize,
+) -> f64 {
+    let cell_b = &voronoi.cells[b];
+    cyclic_pairs(&voronoi.cells[a])
+        .filter(|&(u, v)| u != v && cyclic_pairs(cell_b).any(|(x, y)| x == v && y == u))
+        .map(|(u, v)| dist2(&voronoi.circumcenters[u], &voronoi.circumcenters[v]).sqrt())
+        .sum()
+}
+
+/// Given a voronoi, a site and one of its cell edges ```u -> v```, returns the neighboring site on the other side of the edge.
+///
+/// ```None``` is returned if the edge is on the boundary or if the cells on both sides of the edge are not neighbors as defined by [has_common_voronoi_edge].
+pub fn edge_neighbor<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+    u: usize,
+    v: usize,
+) -> Option<usize> {
+    if u == v {
+        return None;
+    }
+
+    site_across_edge(voronoi, site, u, v).filter(|&n| has_common_voronoi_edge(voronoi, site, n))
+}
+
+/// Given a voronoi, a site and one of its cell edges ```u -> v```, returns the site whose cell has the reversed edge ```v -> u```, however short the edge is.
+///
+/// An edge between the circumcenters of two Delaunay triangles is dual to the Delaunay edge the triangles share, so its other site is checked first.
+/// Other edges, such as edges to vertices added by clipping, search for the site among the topological neighbors.
+pub fn site_across_edge<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+    u: usize,
+    v: usize,
+) -> Option<usize> {
+    if u == v {
+        return None;
+    }
+
+    let shares_edge = |n: usize| cyclic_pairs(&voronoi.cells[n]).any(|(x, y)| x == v && y == u);
+    let triangles = voronoi.number_of_triangles();
+    if u < triangles && v < triangles {
+        let triangulation = &voronoi.triangulation;
+        let dual = (3 * u..3 * u + 3).find_map(|e| {
+            let twin = triangulation.halfedges[e];
+            let (a, b) = (
+                triangulation.triangles[e],
+                triangulation.triangles[next_halfedge(e)],
+            );
+            match (twin != EMPTY && triangle_of_edge(twin) == v, a == site) {
+                (true, true) => Some(b),
+                (true, false) if b == site => Some(a),
+                _ => None,
+            }
+        });
+        if let Some(n) = dual.filter(|&n| shares_edge(n)) {
+            return Some(n);
+        }
+    }
+
+    TopologicalNeighborSiteIterator::new(voronoi, site).find(|&n| shares_edge(n))
+}
+
+/// Given a voronoi and a site, returns the length of the voronoi edge shared with each site across the edges of its cell, in a single pass over its cell.
+///
+/// Each site is listed once, in the order its first edge is found, with the same length [shared_voronoi_edge_length] returns for the pair.
+pub fn shared_voronoi_edge_lengths<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+) -> Vec<(usize, f64)> {
+    let mut lengths: Vec<(usize, f64)> = Vec::new();
+    for (u, v) in cyclic_pairs(&voronoi.cells[site]) {
+        if let Some(neighbor) = site_across_edge(voronoi, site, u, v) {
+            let length = dist2(&voronoi.circumcenters[u], &voronoi.circumcenters[v]).sqrt();
+            match lengths.iter_mut().find(|(n, _)| *n == neighbor) {
+                Some((_, total)) => *total += length,
+                None => lengths.push((neighbor, length)),
+            }
+        }
+    }
+
+    lengths
+}
+
+/// Given a voronoi, a site and one of its cell edges ```u -> v```, returns the delaunay half-edge dual to the voronoi edge ```u -> v``` lies on.
+///
+/// Unlike [edge_neighbor], this does not require the cells on both sides of the edge to be neighbors, only to share the edge.
+/// ```None``` is returned if the edge is on the boundary.
+pub fn dual_delaunay_edge<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+    u: usize,
+    v: usize,
+) -> Option<usize> {
+    if u == v {
+        return None;
+    }
+
+    let triangulation = &voronoi.triangulation;
+    let shares_edge = |n: usize| cyclic_pairs(&voronoi.cells[n]).any(|(x, y)| x == v && y == u);
+
+    // incoming edges start at every neighbor, except for the last neighbor of a hull site which is only reached by an outgoing edge
+    crate::iterator::EdgesAroundSiteIterator::new(
+        triangulation,
+        voronoi.site_to_incoming_leftmost_halfedge[site],
+    )
+    .flat_map(|incoming| [incoming, next_halfedge(incoming)])
+    .find(|&e| {
+        let n = if triangulation.triangles[e] == site {
+            triangulation.triangles[next_halfedge(e)]
+        } else {
+            triangulation.triangles[e]
+        };
+        shares_edge(n)
+    })
+}
+
+/// Finds the index of the vertex of a convex polygon with ```n``` vertices maximizing ```f```, the dot product of each vertex with a direction.
+///
+/// Because the polygon is convex, ```f``` is unimodal around the cycle of vertices and the maximum is binary searched.
+/// Repeated vertices and collinear edges create runs of equal values the binary search cannot reason about,
+/// so the result is then climbed to the actual maximum and, if multiple vertices are maximal, moved to the first of the run in counter-clockwise order.
+/// Values of repeated vertices may differ by rounding errors, so runs are detected with a small relative tolerance.
+///
+/// This evaluates ```f``` ```O(log n)``` times for a polygon without runs of equal values, plus the length of every run climbed or scanned,
+/// as each run is walked vertex by vertex. That is ```O(n)``` in the worst case, e.g. when most vertices are repeated.
+///
+/// # Panics
+///
+/// Panics if ```n``` is zero.
+pub fn extreme_vertex(n: usize, f: impl Fn(usize) -> f64) -> usize {
+    assert!(n > 0, "Polygon must have at least one vertex.");
+
+    let f = |i: usize| f(i % n);
+    let is_max = |i: usize| f(i + n - 1) <= f(i) && f(i + 1) <= f(i);
+
+    // binary search, see "Extreme Point of a 2D Polygon" at geomalgorithms.com
+    let mut i = 0;
+    if n > 3 && !is_max(0) {
+        let (mut a, mut b) = (0, n);
+        loop {
+            let c = (a + b) / 2;
+            if is_max(c) || b <= a + 1 {
+                i = c;
                 break;
             }
+
+            let up_a = f(a + 1) > f(a);
+            let up_c = f(c + 1) > f(c);
+            let go_left = if up_a {
+                !up_c || f(a) > f(c)
+            } else {
+                !up_c && f(a) < f(c)
+            };
+
+            if go_left {
+                b = c;
+            } else {
+                a = c;
+            }
+        }
+    }
+
+    // climb towards the maximum, skipping runs of equal values
+    // values of repeated vertices may differ by rounding errors, so they are compared with a tolerance
+    let eq = |a: f64, b: f64| abs_diff_eq(a, b, EQ_EPSILON * (1.0 + a.abs().max(b.abs())));
+    let distinct = |i: usize, forward: bool| {
+        (1..n)
+            .map(|k| if forward { i + k } else { i + n - k })
+            .find(|&j| !eq(f(j), f(i)))
+    };
+    for _ in 0..n {
+        match (distinct(i, true), distinct(i, false)) {
+            (Some(j), _) if f(j) > f(i) => i = j % n,
+            (_, Some(j)) if f(j) > f(i) => i = j % n,
+            _ => break,
+        }
+    }
+
+    // pick the greatest value of the run of maximal values, the first one in counter-clockwise order on ties
+    let start = match distinct(i, false) {
+        Some(j) => j + 1,
+        None => 0,
+    };
+    let mut extreme = start % n;
+    for j in (start + 1..start + n).take_while(|&j| eq(f(j), f(i))) {
+        if f(j) > f(extreme) {
+            extreme = j % n;
+        }
+    }
+
+    extreme
+}
+
+/// Gets whether cycles ```a``` and ```b``` visit the same positions in the same order, whichever vertex each cycle starts from.
+///
+/// The cycles are given as indices into ```a_vertices``` and ```b_vertices``` respectively, so cells of two different diagrams can be compared.
+/// Consecutive vertices at the same position count once, as the circumcenters of cocircular sites do not depend on how they are triangulated.
+pub fn same_cycle(a: &[usize], a_vertices: &[Point], b: &[usize], b_vertices: &[Point]) -> bool {
+    let (mut a_positions, mut b_positions) = (Vec::new(), Vec::new());
+    cycle_positions(a, a_vertices, 0.0, &mut a_positions);
+    cycle_positions(b, b_vertices, 0.0, &mut b_positions);
+    same_rotation(&a_positions, &b_positions, 0.0)
+}
+
+/// Appends the positions of the vertices of cycle ```cycle``` to ```positions```, counting consecutive vertices at the same position once, as [same_cycle()] does.
+///
+/// Positions are the same if their coordinates differ by at most ```epsilon``` relative to their magnitude, see [same_rotation()].
+pub fn cycle_positions(
+    cycle: &[usize],
+    vertices: &[Point],
+    epsilon: f64,
+    positions: &mut Vec<Point>,
+) {
+    let start = positions.len();
+    for &v in cycle {
+        if positions.len() == start
+            || !same_position(&positions[positions.len() - 1], &vertices[v], epsilon)
+        {
+            positions.push(vertices[v].clone());
         }
     }
+    while positions.len() > start + 1
+        && same_position(&positions[start], &positions[positions.len() - 1], epsilon)
+    {
+        positions.pop();
+    }
+}
 
-    common >= 2
+/// Gets whether ```b``` is a rotation of ```a```, with coordinates that differ by at most ```epsilon``` relative to their magnitude.
+///
+/// An ```epsilon``` of zero compares positions exactly. The circumcenters of cocircular sites calculated from different triangles differ by a rounding error.
+pub fn same_rotation(a: &[Point], b: &[Point], epsilon: f64) -> bool {
+    let n = a.len();
+    n == b.len()
+        && (n == 0
+            || (0..n).any(|k| (0..n).all(|i| same_position(&a[(i + k) % n], &b[i], epsilon))))
+}
+
+#[inline]
+fn same_position(a: &Point, b: &Point, epsilon: f64) -> bool {
+    let tolerance = epsilon * (1.0 + a.x.abs().max(a.y.abs()).max(b.x.abs()).max(b.y.abs()));
+    abs_diff_eq(a.x, b.x, tolerance) && abs_diff_eq(a.y, b.y, tolerance)
+}
+
+/// Iterates over consecutive pairs of a closed cycle, including the pair closing the cycle.
+pub fn cyclic_pairs(cycle: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    cycle
+        .iter()
+        .copied()
+        .zip(cycle.iter().copied().cycle().skip(1))
+}
+
+/// Clips a convex polygon, keeping the points where ```f``` is not negative. ```f``` must be linear.
+pub fn clip_half_plane(polygon: &[Point], f: impl Fn(&Point) -> f64) -> Vec<Point> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (fa, fb) = (f(a), f(b));
+        if fa >= 0.0 {
+            clipped.push(a.clone());
+        }
+        if (fa >= 0.0) != (fb >= 0.0) {
+            let t = fa / (fa - fb);
+            clipped.push(Point {
+                x: a.x + (b.x - a.x) * t,
+                y: a.y + (b.y - a.y) * t,
+            });
+        }
+    }
+
+    clipped
 }
 
 #[cfg(test)]
 pub(crate) mod test {
+    use std::cell::Cell;
+
     use delaunator::Point;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use crate::{BoundingBox, ConvexBoundary, Voronoi, VoronoiBuilder};
 
+    /// The seed of the first generator returned by [rng()] on each test thread.
+    pub const SEED: u64 = 0x766f_726f_6e6f_6963;
+
+    thread_local! {
+        static NEXT_SEED: Cell<u64> = const { Cell::new(SEED) };
+    }
+
+    /// Gets a seeded random number generator. Each call on a thread gets the next seed, so a test sees the same numbers on every run.
+    pub fn rng() -> StdRng {
+        let seed = NEXT_SEED.with(|next| next.replace(next.get().wrapping_add(1)));
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// Gets ```size``` random sites, uniformly distributed in the default boundary, [-1, 1] on both axes.
+    pub fn random_sites(size: usize) -> Vec<Point> {
+        let mut rng = rng();
+        (0..size)
+            .map(|_| Point {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+            })
+            .collect()
+    }
+
+    /// Builds a diagram of [random_sites()] with the default builder.
+    pub fn random_voronoi(size: usize) -> Voronoi<BoundingBox> {
+        VoronoiBuilder::default()
+            .set_sites(random_sites(size))
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    /// Builds a diagram of a ```size``` by ```size``` grid of sites with the default builder.
+    pub fn square_voronoi(size: usize) -> Voronoi<BoundingBox> {
+        VoronoiBuilder::default()
+            .generate_square_sites(size)
+            .build()
+            .expect("Some voronoi expected")
+    }
+
     pub fn validate_voronoi<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
         for cell in voronoi.iter_cells() {
-            let vertices: Vec<Point> = cell.iter_vertices().cloned().collect();
+            // repeated vertices, such as those on the axis of a symmetric diagram, are checked once
+            let mut vertices: Vec<Point> = cell.iter_vertices().cloned().collect();
+            vertices.dedup();
+            while vertices.len() > 1 && vertices.first() == vertices.last() {
+                vertices.pop();
+            }
 
             let area = calculate_area(&vertices);
             if area <= 0. {
@@ -186,14 +535,143 @@ pub(crate) mod test {
         }
     }
 
-    pub fn new_voronoi_builder_from_asset(
-        asset: &str,
-    ) -> std::io::Result<VoronoiBuilder<BoundingBox>> {
+    /// Returns whether two diagrams have exactly the same sites, vertices and cells.
+    pub fn geometry_eq<T: ConvexBoundary, U: ConvexBoundary>(
+        a: &Voronoi<T>,
+        b: &Voronoi<U>,
+    ) -> bool {
+        a.sites() == b.sites()
+            && a.vertices() == b.vertices()
+            && a.cells() == b.cells()
+            && a.triangulation().triangles == b.triangulation().triangles
+    }
+
+    /// Returns whether two diagrams have the same sites and cells whose vertices are within ```epsilon``` of each other.
+    ///
+    /// Unlike [geometry_eq], vertex indices and triangulations are not compared, so diagrams built from different triangulations of the same sites can be compared.
+    /// Consecutive cell vertices closer than ```epsilon```, such as those of cocircular sites, are collapsed, and cells may start at any of their vertices.
+    #[allow(dead_code)]
+    pub fn geometry_approx_eq<T: ConvexBoundary, U: ConvexBoundary>(
+        a: &Voronoi<T>,
+        b: &Voronoi<U>,
+        epsilon: f64,
+    ) -> bool {
+        let close = |p: &Point, q: &Point| {
+            super::abs_diff_eq(p.x, q.x, epsilon) && super::abs_diff_eq(p.y, q.y, epsilon)
+        };
+        let collapse = |vertices: Vec<Point>| {
+            let mut collapsed: Vec<Point> = Vec::with_capacity(vertices.len());
+            for v in vertices {
+                if !collapsed.last().is_some_and(|last| close(last, &v)) {
+                    collapsed.push(v);
+                }
+            }
+            while collapsed.len() > 1 && close(&collapsed[0], collapsed.last().unwrap()) {
+                collapsed.pop();
+            }
+            collapsed
+        };
+
+        a.sites() == b.sites()
+            && a.iter_cells().zip(b.iter_cells()).all(|(ca, cb)| {
+                let va = collapse(ca.iter_vertices().cloned().collect());
+                let vb = collapse(cb.iter_vertices().cloned().collect());
+                va.len() == vb.len()
+                    && (va.is_empty()
+                        || (0..vb.len()).any(|r| {
+                            va.iter()
+                                .zip(vb.iter().cycle().skip(r))
+                                .all(|(p, q)| close(p, q))
+                        }))
+            })
+    }
+
+    /// Reduces ```items``` to a subset that still ```fails```, by delta debugging.
+    ///
+    /// The result is 1-minimal: removing any single item from it makes ```fails``` return ```false```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```items``` does not fail to begin with.
+    #[allow(dead_code)]
+    pub fn minimize_failure<T: Clone>(mut items: Vec<T>, fails: impl Fn(&[T]) -> bool) -> Vec<T> {
+        assert!(
+            fails(&items),
+            "Items are expected to fail before minimizing."
+        );
+
+        let mut granularity = 2;
+        while items.len() > 1 {
+            let chunk = items.len().div_ceil(granularity);
+            let reduced = (0..items.len()).step_by(chunk).find_map(|start| {
+                let complement: Vec<T> = items[..start]
+                    .iter()
+                    .chain(items[(start + chunk).min(items.len())..].iter())
+                    .cloned()
+                    .collect();
+                if fails(&complement) {
+                    Some(complement)
+                } else {
+                    None
+                }
+            });
+
+            match reduced {
+                Some(complement) => {
+                    items = complement;
+                    granularity = (granularity - 1).max(2);
+                }
+                None if chunk == 1 => break,
+                None => granularity = (granularity * 2).min(items.len()),
+            }
+        }
+
+        items
+    }
+
+    /// Hashes the sites, vertices and cells of a diagram, bit by bit.
+    pub fn geometry_hash<T: ConvexBoundary>(voronoi: &Voronoi<T>) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for p in voronoi.sites().iter().chain(voronoi.vertices().iter()) {
+            p.x.to_bits().hash(&mut hasher);
+            p.y.to_bits().hash(&mut hasher);
+        }
+        voronoi.cells().hash(&mut hasher);
+        voronoi.triangulation().triangles.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    /// The bundled assets that build valid diagrams. degenerated11.json is a known failure of the cell builder.
+    pub const VALID_ASSETS: [&str; 12] = [
+        "clockwise1.json",
+        "degenerated1.json",
+        "degenerated2.json",
+        "degenerated3.json",
+        "degenerated4.json",
+        "degenerated5.json",
+        "degenerated6.json",
+        "degenerated7.json",
+        "degenerated8.json",
+        "degenerated9.json",
+        "degenerated10.json",
+        "touching_corner1.json",
+    ];
+
+    pub fn sites_from_asset(asset: &str) -> std::io::Result<Vec<Point>> {
         let basepath = "examples/assets/";
 
         let file = std::fs::File::open(basepath.to_string() + asset)?;
         let sites: Vec<[f64; 2]> = serde_json::from_reader(file)?;
-        let sites: Vec<Point> = sites.iter().map(|&[x, y]| Point { x, y }).collect();
+        Ok(sites.iter().map(|&[x, y]| Point { x, y }).collect())
+    }
+
+    pub fn new_voronoi_builder_from_asset(
+        asset: &str,
+    ) -> std::io::Result<VoronoiBuilder<BoundingBox>> {
+        let sites = sites_from_asset(asset)?;
 
         let mut center = sites.iter().fold(Point { x: 0., y: 0. }, |acc, p| Point {
             x: acc.x + p.x,
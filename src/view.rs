@@ -0,0 +1,274 @@
+use crate::{ConvexBoundary, Point, Voronoi, VoronoiCell};
+
+/// How [Voronoi::view_transform()] fits the boundary of a diagram into a viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// Scales each axis independently so the boundary fills the viewport exactly, distorting it if the aspect ratios differ.
+    Stretch,
+
+    /// Scales both axes equally so the whole boundary fits in the viewport, centered, with equal margins on both sides of the axis with spare room.
+    Contain,
+
+    /// Scales both axes equally so the boundary covers the whole viewport, centered, cropping equally on both sides of the axis that does not fit.
+    Cover,
+}
+
+/// An affine map from world coordinates to pixel coordinates of a viewport.
+///
+/// A world point is mapped by scaling its offset from the world point at the center of the viewport, per axis.
+/// In pixel coordinates, the origin is the top left corner of the viewport and the center of pixel (```x```, ```y```) is at (```x + 0.5```, ```y + 0.5```).
+/// As in the rest of the crate, the Y-axis points downwards, so rows grow with world Y unless the transform is flipped with [Self::flip_y()].
+///
+/// Use [Voronoi::view_transform()] to obtain an instance of this type, and [crate::raster::Rasterizer::set_view_transform()] to render through it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewTransform {
+    viewport: (u32, u32),
+    center: Point,
+    scale: (f64, f64),
+}
+
+impl ViewTransform {
+    /// Creates a transform for a viewport of ```viewport``` pixels that maps ```center``` to the center of the viewport, with ```scale``` pixels per world unit on each axis.
+    ///
+    /// A negative scale mirrors its axis.
+    pub fn new(viewport: (u32, u32), center: Point, scale: (f64, f64)) -> Self {
+        Self {
+            viewport,
+            center,
+            scale,
+        }
+    }
+
+    /// Creates the transform that fits the rectangle from ```min``` to ```max``` into ```viewport``` per ```fit```.
+    pub(crate) fn fit(min: &Point, max: &Point, viewport: (u32, u32), fit: FitMode) -> Self {
+        let center = Point {
+            x: (min.x + max.x) / 2.0,
+            y: (min.y + max.y) / 2.0,
+        };
+        let scale_x = viewport.0 as f64 / (max.x - min.x);
+        let scale_y = viewport.1 as f64 / (max.y - min.y);
+        let scale = match fit {
+            FitMode::Stretch => (scale_x, scale_y),
+            FitMode::Contain => (scale_x.min(scale_y), scale_x.min(scale_y)),
+            FitMode::Cover => (scale_x.max(scale_y), scale_x.max(scale_y)),
+        };
+
+        Self::new(viewport, center, scale)
+    }
+
+    /// Gets the width and height of the viewport, in pixels.
+    pub fn viewport(&self) -> (u32, u32) {
+        self.viewport
+    }
+
+    /// Gets the world point mapped to the center of the viewport.
+    pub fn center(&self) -> &Point {
+        &self.center
+    }
+
+    /// Gets the number of pixels per world unit on each axis, negative for a mirrored axis.
+    pub fn scale(&self) -> (f64, f64) {
+        self.scale
+    }
+
+    /// Mirrors the Y-axis, keeping the center in place, so rows grow as world Y decreases. Useful for sites given with the Y-axis pointing upwards.
+    pub fn flip_y(mut self) -> Self {
+        self.scale.1 = -self.scale.1;
+        self
+    }
+
+    /// Maps a world point to pixel coordinates.
+    pub fn apply(&self, p: &Point) -> (f32, f32) {
+        let (x, y) = self.to_pixels(p);
+        (x as f32, y as f32)
+    }
+
+    /// Maps pixel coordinates back to a world point.
+    pub fn apply_inverse(&self, pixel: (f32, f32)) -> Point {
+        self.to_world(pixel.0 as f64, pixel.1 as f64)
+    }
+
+    /// Replaces the contents of ```out``` with the pixel coordinates of the vertices of ```cell```, in order.
+    pub fn transform_cell<T: ConvexBoundary>(
+        &self,
+        cell: &VoronoiCell<'_, T>,
+        out: &mut Vec<(f32, f32)>,
+    ) {
+        out.clear();
+        out.extend(cell.iter_vertices().map(|v| self.apply(v)));
+    }
+
+    /// Same as [Self::apply()], at full precision.
+    pub(crate) fn to_pixels(&self, p: &Point) -> (f64, f64) {
+        (
+            (p.x - self.center.x) * self.scale.0 + self.viewport.0 as f64 / 2.0,
+            (p.y - self.center.y) * self.scale.1 + self.viewport.1 as f64 / 2.0,
+        )
+    }
+
+    /// Same as [Self::apply_inverse()], at full precision.
+    pub(crate) fn to_world(&self, x: f64, y: f64) -> Point {
+        Point {
+            x: self.center.x + (x - self.viewport.0 as f64 / 2.0) / self.scale.0,
+            y: self.center.y + (y - self.viewport.1 as f64 / 2.0) / self.scale.1,
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Gets the transform that fits the extents of the boundary of this diagram into a viewport of ```viewport``` pixels per ```fit```.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let voronoi = VoronoiBuilder::default()
+    ///     .set_boundary(BoundingBox::new_centered_square(2.0))
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // the square boundary is letterboxed with 50 pixels on each side
+    /// let transform = voronoi.view_transform((300, 200), FitMode::Contain);
+    /// assert_eq!(transform.apply(&Point { x: -1.0, y: -1.0 }), (50.0, 0.0));
+    /// assert_eq!(transform.apply(&Point { x: 1.0, y: 1.0 }), (250.0, 200.0));
+    ///```
+    pub fn view_transform(&self, viewport: (u32, u32), fit: FitMode) -> ViewTransform {
+        let (min, max) = self.boundary.vertices().iter().fold(
+            (
+                Point {
+                    x: f64::INFINITY,
+                    y: f64::INFINITY,
+                },
+                Point {
+                    x: f64::NEG_INFINITY,
+                    y: f64::NEG_INFINITY,
+                },
+            ),
+            |(min, max), v| {
+                (
+                    Point {
+                        x: min.x.min(v.x),
+                        y: min.y.min(v.y),
+                    },
+                    Point {
+                        x: max.x.max(v.x),
+                        y: max.y.max(v.y),
+                    },
+                )
+            },
+        );
+
+        ViewTransform::fit(&min, &max, viewport, fit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{raster::Rasterizer, BoundingBox, PickResult, VoronoiBuilder};
+
+    /// A diagram whose boundary spans from (-2, -1) to (2, 1).
+    fn wide_voronoi() -> Voronoi<BoundingBox> {
+        let mut rng = crate::utils::test::rng();
+        let sites = (0..60)
+            .map(|_| Point {
+                x: rng.gen_range(-2.0..2.0),
+                y: rng.gen_range(-1.0..1.0),
+            })
+            .collect();
+        VoronoiBuilder::default()
+            .set_boundary(BoundingBox::new(Point { x: 0.0, y: 0.0 }, 4.0, 2.0))
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected")
+    }
+
+    fn corners(transform: &ViewTransform) -> [(f32, f32); 2] {
+        [
+            transform.apply(&Point { x: -2.0, y: -1.0 }),
+            transform.apply(&Point { x: 2.0, y: 1.0 }),
+        ]
+    }
+
+    #[test]
+    fn corners_map_to_expected_pixels() {
+        let voronoi = wide_voronoi();
+
+        let stretch = voronoi.view_transform((100, 100), FitMode::Stretch);
+        assert_eq!(corners(&stretch), [(0.0, 0.0), (100.0, 100.0)]);
+
+        // the boundary is twice as wide as it is high, so it is letterboxed vertically
+        let contain = voronoi.view_transform((100, 100), FitMode::Contain);
+        assert_eq!(corners(&contain), [(0.0, 25.0), (100.0, 75.0)]);
+        let contain = voronoi.view_transform((300, 100), FitMode::Contain);
+        assert_eq!(corners(&contain), [(50.0, 0.0), (250.0, 100.0)]);
+
+        // and cropped horizontally
+        let cover = voronoi.view_transform((100, 100), FitMode::Cover);
+        assert_eq!(corners(&cover), [(-50.0, 0.0), (150.0, 100.0)]);
+
+        let flipped = voronoi
+            .view_transform((100, 100), FitMode::Stretch)
+            .flip_y();
+        assert_eq!(corners(&flipped), [(0.0, 100.0), (100.0, 0.0)]);
+    }
+
+    #[test]
+    fn apply_inverse_round_trips() {
+        let voronoi = wide_voronoi();
+        for fit in [FitMode::Stretch, FitMode::Contain, FitMode::Cover] {
+            let transform = voronoi.view_transform((640, 480), fit).flip_y();
+            // the vertices of the cells are within the boundary, while circumcenters outside of it may be too far for the precision of pixel coordinates
+            for v in voronoi
+                .cells()
+                .iter()
+                .flatten()
+                .map(|&v| &voronoi.vertices()[v])
+            {
+                let back = transform.apply_inverse(transform.apply(v));
+                assert!((back.x - v.x).abs() < 1e-5 && (back.y - v.y).abs() < 1e-5);
+            }
+
+            let mut out = vec![(1.0, 1.0)];
+            let cell = voronoi.cell(3);
+            transform.transform_cell(&cell, &mut out);
+            assert_eq!(out.len(), cell.iter_vertices().count());
+            assert_eq!(
+                out[0],
+                transform.apply(cell.iter_vertices().next().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn rasterizer_agrees_with_transform() {
+        let voronoi = wide_voronoi();
+        let mut rng = crate::utils::test::rng();
+        for fit in [FitMode::Stretch, FitMode::Contain, FitMode::Cover] {
+            let transform = voronoi.view_transform((160, 120), fit);
+            let mut rasterizer = Rasterizer::new(1, 1);
+            rasterizer.set_view_transform(&transform);
+            assert_eq!((rasterizer.width(), rasterizer.height()), (160, 120));
+            rasterizer.render(&voronoi, |site| site as u32);
+
+            let mut checked = 0;
+            for _ in 0..2000 {
+                let (x, y) = (rng.gen_range(0..160), rng.gen_range(0..120));
+                let p = transform.apply_inverse((x as f32 + 0.5, y as f32 + 0.5));
+                let radius = 0.5 / transform.scale().0.min(transform.scale().1);
+                match voronoi.pick(&p, radius) {
+                    PickResult::Cell(site) => {
+                        checked += 1;
+                        assert_eq!(rasterizer.owner(x, y), Some(site), "Pixel ({}, {}).", x, y);
+                    }
+                    PickResult::Outside => assert_eq!(rasterizer.owner(x, y), None),
+                    _ => {}
+                }
+            }
+            assert!(checked > 1000);
+        }
+    }
+}
@@ -1,12 +1,159 @@
-use super::{utils::calculate_approximated_cetroid, ClipBehavior, ConvexBoundary, Point, Voronoi};
+use std::{fmt::Display, hash::Hasher, sync::Arc};
+
+use super::{
+    auto_boundary::choose_boundary,
+    cell_builder::CellBuilderArena,
+    debug_validation::debug_validate,
+    decimation::cap_cell_vertices,
+    editing::RebuildOptions,
+    fingerprint::{hash_point, hash_points, FingerprintHasher},
+    instrument::{phase, trace_warnings},
+    quasi_random,
+    separation::enforce_min_separation,
+    short_edges::eliminate_short_edges,
+    sites_inside_boundary,
+    symmetry::{is_symmetric, mirror_sites, symmetrize, tolerance},
+    utils::{calculate_approximated_cetroid, dist2},
+    welding::weld_vertices,
+    AnyBoundary, AutoBoundary, BuildStats, ClipBehavior, ConvexBoundary, ConvexPolygon,
+    DebugValidation, Engine, Point, SeparationPolicy, SymmetryAxis, Voronoi, VoronoiTopology,
+    WrongSideSites,
+};
+
+/// A non-fatal issue found while building a Voronoi diagram.
+///
+/// Use [Voronoi::build_warnings()] to get the warnings of a diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildWarning {
+    /// A Voronoi edge shorter than the minimum length set by [VoronoiBuilder::set_short_edge_elimination()] remained after all passes.
+    /// The edge separates the cells of ```sites```.
+    ShortEdge { sites: (usize, usize), length: f64 },
+
+    /// The cell of ```site``` had more vertices than the maximum set by [VoronoiBuilder::set_max_cell_vertices()] and was simplified
+    /// to ```vertices``` vertices, losing ```area_loss``` of its area near the boundary.
+    SimplifiedCell {
+        site: usize,
+        vertices: usize,
+        area_loss: f64,
+    },
+
+    /// The Voronoi edge shared by the cells of ```sites``` collapsed to a point when welding vertices, as set by [VoronoiBuilder::set_vertex_welding()],
+    /// so the cells are no longer neighbors.
+    CollapsedEdge { sites: (usize, usize) },
+
+    /// The site at index ```site``` of the sites set was closer than the minimum separation set by [VoronoiBuilder::set_min_separation()]
+    /// to the site at index ```kept```, at ```distance```, and was dropped per [SeparationPolicy::DropLater].
+    DroppedSite {
+        site: usize,
+        kept: usize,
+        distance: f64,
+    },
+
+    /// The sites at indices ```sites``` of the sites set were closer to each other than the minimum separation set by [VoronoiBuilder::set_min_separation()],
+    /// and were replaced by their centroid per [SeparationPolicy::Average]. The centroid takes the place of the first of them.
+    MergedSites { sites: Vec<usize> },
+}
+
+/// An error returned when a diagram cannot be built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The radius, in degrees, given to [crate::sphere::build_local()] is not within the supported range of spherical caps, between 0° and 90° exclusive.
+    InvalidCapRadius(f64),
+
+    /// The site index given is outside of the spherical cap passed to [crate::sphere::build_local()].
+    SiteOutsideCap(usize),
+
+    /// The sites do not generate a valid diagram (e.g. all sites are collinear).
+    Degenerate,
+
+    /// The site index given is outside of the boundary passed to [crate::VoronoiTopology::with_geometry()], and the clip behavior would remove it.
+    SiteOutsideBoundary(usize),
+
+    /// The site index given is on the mirrored side of the axis set by [VoronoiBuilder::set_symmetry()], with [WrongSideSites::Reject].
+    SiteOnMirroredSide(usize),
+
+    /// The boundary is not symmetric across the axis set by [VoronoiBuilder::set_symmetry()].
+    AsymmetricBoundary,
+
+    /// The pairs of sites closer than the minimum separation set by [VoronoiBuilder::set_min_separation()], with [SeparationPolicy::Error].
+    /// Each pair is given as the indices of the sites set, lowest first, and their distance.
+    SitesTooClose(Vec<(usize, usize, f64)>),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::InvalidCapRadius(radius) => write!(
+                f,
+                "Spherical cap radius {}° is not between 0° and 90°",
+                radius
+            ),
+            BuildError::SiteOutsideCap(site) => {
+                write!(f, "Site {} is outside of the spherical cap", site)
+            }
+            BuildError::Degenerate => write!(f, "Sites do not generate a valid diagram"),
+            BuildError::SiteOutsideBoundary(site) => {
+                write!(f, "Site {} is outside of the boundary", site)
+            }
+            BuildError::SiteOnMirroredSide(site) => {
+                write!(
+                    f,
+                    "Site {} is on the mirrored side of the symmetry axis",
+                    site
+                )
+            }
+            BuildError::AsymmetricBoundary => {
+                write!(f, "Boundary is not symmetric across the symmetry axis")
+            }
+            BuildError::SitesTooClose(pairs) => {
+                write!(
+                    f,
+                    "{} pairs of sites are closer than the minimum separation",
+                    pairs.len()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// How far the sites moved in each iteration of Lloyd relaxation, as returned by [VoronoiBuilder::relax_sites_with_report()].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelaxationReport {
+    /// The largest distance a site moved in each iteration run. It decreases as the relaxation converges.
+    pub max_displacements: Vec<f64>,
+
+    /// The mean distance the sites moved in each iteration run.
+    pub mean_displacements: Vec<f64>,
+}
+
+/// Chooses a boundary for the sites, see [VoronoiBuilder::set_auto_boundary()].
+type BoundaryChooser<T> = fn(&AutoBoundary, &[Point], Engine) -> (T, BuildStats);
 
 /// Provides a convenient way to construct a Voronoi diagram.
-#[derive(Default)]
+///
+/// Cloning a builder is cheap, so one configured builder can be fanned out into variants: the clones share their sites,
+/// which are only copied when a clone is built while another one still holds them.
+#[derive(Default, Clone)]
 pub struct VoronoiBuilder<T: ConvexBoundary> {
-    sites: Option<Vec<Point>>,
+    sites: Option<Arc<Vec<Point>>>,
     lloyd_iterations: usize,
     boundary: T,
     clip_behavior: ClipBehavior,
+    adjacency_epsilon: f64,
+    engine: Engine,
+    short_edge_elimination: Option<(f64, usize)>,
+    max_cell_vertices: Option<usize>,
+    vertex_welding: Option<f64>,
+    auto_boundary: Option<(AutoBoundary, BoundaryChooser<T>)>,
+    symmetry: Option<(SymmetryAxis, WrongSideSites)>,
+    min_separation: Option<(f64, SeparationPolicy)>,
+    debug_validation: DebugValidation,
+
+    /// Changes the diagram after it is built and before it is validated, to test that validation catches it.
+    #[cfg(test)]
+    corruption: Option<fn(&mut Voronoi<T>)>,
 }
 
 impl<T: ConvexBoundary> VoronoiBuilder<T> {
@@ -26,11 +173,32 @@ impl<T: ConvexBoundary> VoronoiBuilder<T> {
         self
     }
 
+    /// Sets the minimum length a Voronoi edge shared by two cells must have for the cells to be considered neighbors.
+    ///
+    /// Two cells are neighbors if, and only if, the common edge of their clipped polygons is strictly longer than ```epsilon```.
+    /// All adjacency queries, such as [crate::VoronoiCell::iter_neighbors()] and [Voronoi::adjacency()], follow this definition.
+    ///
+    /// Default value is 0.0, meaning cells that only touch at a single point are not neighbors.
+    pub fn set_adjacency_epsilon(mut self, epsilon: f64) -> Self {
+        self.adjacency_epsilon = epsilon;
+        self
+    }
+
+    /// Sets the [Engine] used to calculate the Delaunay triangulation of the sites.
+    ///
+    /// The engine is kept by the diagram and also used when it is edited.
+    ///
+    /// Default value is [Engine::default()].
+    pub fn set_triangulator(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
     /// Sets a vector of [Point]s representing the sites of each Voronoi cell that should be constructed.
     ///
     /// By convention, the Y-axis points downwards.
     pub fn set_sites(mut self, sites: Vec<Point>) -> Self {
-        self.sites.replace(sites);
+        self.sites.replace(Arc::new(sites));
         self
     }
 
@@ -42,8 +210,113 @@ impl<T: ConvexBoundary> VoronoiBuilder<T> {
         self
     }
 
+    /// Enables a pass, run after Lloyd relaxation, that eliminates Voronoi edges shorter than ```min_edge_length```.
+    ///
+    /// Very short edges appear when four sites are nearly cocircular. For each short edge, one of the four sites is nudged just enough to lengthen
+    /// the edge above ```min_edge_length``` and the diagram is rebuilt; this is repeated up to ```max_passes``` times, since nudging a site may shorten other edges.
+    /// Edges still shorter than ```min_edge_length``` afterwards are reported as [BuildWarning::ShortEdge].
+    ///
+    /// Only edges between two Delaunay circumcenters are considered; edges created by clipping against the boundary are left as is.
+    /// Sites are never nudged outside of the boundary.
+    ///
+    /// Disabled by default.
+    pub fn set_short_edge_elimination(mut self, min_edge_length: f64, max_passes: usize) -> Self {
+        self.short_edge_elimination = Some((min_edge_length, max_passes));
+        self
+    }
+
+    /// Sets the maximum number of vertices a clipped cell should have.
+    ///
+    /// Cells with more than ```max_vertices``` vertices are simplified by removing corners of the boundary polygon from them, which may leave
+    /// the cell slightly short of the boundary. Circumcenters and the points where Voronoi edges cross the boundary are never removed, so cells with
+    /// more than ```max_vertices``` of those are left with more vertices than requested. Simplified cells stay convex, within the boundary,
+    /// and contain their site. Each simplified cell is reported as [BuildWarning::SimplifiedCell].
+    ///
+    /// The corners between two kept vertices are decimated evenly, so each new cell edge replaces at most ```⌈(c + 1) / (k + 1)⌉``` boundary edges,
+    /// where ```c``` is the number of corners and ```k``` the number of corners kept, with ```k``` roughly ```max_vertices``` minus the number of vertices that cannot be removed.
+    /// For a boundary polygon of ```m``` vertices inscribed in a circle of radius ```r```, such as [crate::ConvexPolygon::regular()], a new edge spanning an arc of
+    /// ```φ = 2π⌈(c + 1) / (k + 1)⌉ / m``` cuts off at most ```r²(φ - sin φ) / 2 ≤ r²φ³ / 12```, so the area lost by a cell is at most ```(k + 1) r²φ³ / 12```.
+    ///
+    /// Only applies when clipping with [ClipBehavior::Clip]. Disabled by default.
+    pub fn set_max_cell_vertices(mut self, max_vertices: usize) -> Self {
+        self.max_cell_vertices = Some(max_vertices);
+        self
+    }
+
+    /// Enables a pass, run last, that welds the vertices of Voronoi edges no longer than ```epsilon```.
+    ///
+    /// Nearly cocircular sites produce very short edges, and their vertices jitter with rounding errors. Welding moves the vertices of each short edge,
+    /// and of any chain of short edges, to the same position, so the edges have a length of zero. The cells on both sides of a collapsed edge are then
+    /// no longer neighbors, for [crate::VoronoiCell::iter_neighbors()], [Voronoi::adjacency()] and every other adjacency query, and each such pair is
+    /// reported as [BuildWarning::CollapsedEdge]. Cells keep their vertex indices, so a cell repeats a vertex where one of its edges collapsed.
+    ///
+    /// Moving vertices by up to ```epsilon``` may leave cells very slightly concave. Groups of vertices that include one created by clipping are moved onto it,
+    /// so cells stay within the boundary.
+    ///
+    /// Disabled by default.
+    pub fn set_vertex_welding(mut self, epsilon: f64) -> Self {
+        self.vertex_welding = Some(epsilon);
+        self
+    }
+
+    /// Builds a diagram that is mirror-symmetric across ```axis```, from the sites on one side of it.
+    ///
+    /// The sites are given on the side of the axis with the lowest coordinates, see [SymmetryAxis], and each one not on the axis is mirrored to the other side.
+    /// Sites within a small tolerance of the axis are moved onto it and kept once. Sites on the other side are removed or rejected per ```wrong_side```.
+    /// The diagram has the sites kept first, in the order given, followed by their mirrors; use [Voronoi::mirror_of()] to map a site to its mirror.
+    ///
+    /// The vertices of each mirrored cell are the exact reflection of the vertices of the cell it mirrors, and vertices on the axis are exactly on it.
+    /// Where the sites of two mirrored pairs are cocircular, the cells on the axis may repeat a vertex, i.e. have an edge of zero length.
+    /// The cells on the hull of a diagram that is not clipped with [ClipBehavior::Clip] are open, and are not mirrored.
+    /// Lloyd relaxation keeps the diagram symmetric, while short edge elimination, the cell vertex cap and vertex welding are not applied.
+    ///
+    /// The boundary, including the one chosen by [Self::set_auto_boundary()], must be symmetric across the axis, or building fails with [BuildError::AsymmetricBoundary].
+    /// The symmetry is lost once the diagram is edited.
+    ///
+    /// Disabled by default.
+    pub fn set_symmetry(mut self, axis: SymmetryAxis, wrong_side: WrongSideSites) -> Self {
+        self.symmetry = Some((axis, wrong_side));
+        self
+    }
+
+    /// Enforces a minimum ```distance``` between any two sites, handling the sites closer than it per ```policy```.
+    ///
+    /// The separation is enforced on the sites as set, before any of them is removed for being outside of the boundary, and every index reported,
+    /// by [BuildError::SitesTooClose], [BuildWarning::DroppedSite] or [BuildWarning::MergedSites], refers to them. Sites closer than ```distance``` are found with a grid
+    /// of cells of that size, so only nearby sites are compared. Lloyd relaxation runs afterwards and may move sites closer again, as may [Self::set_symmetry()] when mirroring them.
+    ///
+    /// Disabled by default.
+    pub fn set_min_separation(mut self, distance: f64, policy: SeparationPolicy) -> Self {
+        self.min_separation = Some((distance, policy));
+        self
+    }
+
+    /// Sets how much of the diagram is validated after it is built, in debug builds, to catch invalid geometry where it is produced.
+    ///
+    /// An invalid cell panics with its site and the reason it is invalid. The diagram is first written with [Voronoi::write_binary()] to a file in the
+    /// temporary directory, named after its [Voronoi::input_fingerprint()], and the path of the file is included in the panic message.
+    /// See [DebugValidation] for what each level checks. [DebugValidation::Fast] is a single pass over the edges of the cells, adding about 5% to the debug build
+    /// time of a diagram of 20,000 random sites, and [DebugValidation::Full] about 35%.
+    ///
+    /// Validation is compiled out of release builds, where this setting is ignored. Diagrams changed by editing, e.g. with [Voronoi::move_site()],
+    /// are validated again at the same level after each edit, whether they were updated in place or built again.
+    ///
+    /// Default value is [DebugValidation::Off].
+    pub fn set_debug_validation(mut self, level: DebugValidation) -> Self {
+        self.debug_validation = level;
+        self
+    }
+
+    /// Sets a change made to the diagram after it is built and before it is validated.
+    #[cfg(test)]
+    pub(crate) fn set_corruption(mut self, corruption: fn(&mut Voronoi<T>)) -> Self {
+        self.corruption = Some(corruption);
+        self
+    }
+
     /// Consumes this builder and generates a Voronoi diagram/graph.
     /// An ```Option<Voronoi>``` is returned. None may be a valid return value if the set of sites do not generate a valid graph.
+    /// No sites, including when all of them are outside of the boundary, generate an empty diagram, and a single site generates a diagram whose cell is the boundary.
     ///
     /// # Examples
     ///
@@ -62,31 +335,438 @@ impl<T: ConvexBoundary> VoronoiBuilder<T> {
     /// # Panics
     ///
     /// Panics if no sites have been provided through [Self::set_sites] or one of the generate_*_sites methods.
-    pub fn build(mut self) -> Option<Voronoi<T>> {
-        let v = Voronoi::new(
-            self.sites
-                .take()
-                .expect("Cannot build voronoi without sites. Call set_sites() first."),
+    pub fn build(self) -> Option<Voronoi<T>> {
+        self.try_build().ok()
+    }
+
+    /// Same as [Self::build()], but returns why the diagram could not be built.
+    ///
+    /// Sites that do not generate a valid diagram result in [BuildError::Degenerate], and the sites and boundary of a diagram set by [Self::set_symmetry()]
+    /// may also be rejected.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let collinear = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![Point { x: 0.0, y: 0.0 }, Point { x: 0.1, y: 0.1 }, Point { x: 0.2, y: 0.2 }])
+    ///     .try_build();
+    /// assert_eq!(collinear.unwrap_err(), BuildError::Degenerate);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no sites have been provided through [Self::set_sites] or one of the generate_*_sites methods.
+    pub fn try_build(self) -> Result<Voronoi<T>, BuildError> {
+        self.build_in(&mut CellBuilderArena::default())
+    }
+
+    /// Same as [Self::try_build()], but reusing the scratch buffers of ```arena``` to build the cells.
+    pub(crate) fn build_in(self, arena: &mut CellBuilderArena) -> Result<Voronoi<T>, BuildError> {
+        self.build_with_report(arena, &mut RelaxationReport::default(), true)
+    }
+
+    /// Same as [Self::build_in()], recording how far the sites moved in each relaxation iteration in ```report```.
+    ///
+    /// The sites are only hashed into the [Voronoi::input_fingerprint()] of the diagram if ```hash_input``` is set, as the diagrams built for the iterations
+    /// of Lloyd relaxation, or only for their sites, are not returned to be looked up by it. Their fingerprint is 0 otherwise.
+    fn build_with_report(
+        mut self,
+        arena: &mut CellBuilderArena,
+        report: &mut RelaxationReport,
+        hash_input: bool,
+    ) -> Result<Voronoi<T>, BuildError> {
+        let sites = self.take_sites();
+        let phase = phase!(
+            info_span,
+            "build",
+            sites = sites.len(),
+            cells = tracing::field::Empty,
+            warnings = tracing::field::Empty
+        );
+        let mut fingerprint = FingerprintHasher::default();
+        self.hash_configuration(sites.len(), &mut fingerprint);
+        let (sites, separation_warnings) = match self.min_separation {
+            Some((distance, policy)) => {
+                if hash_input {
+                    sites.iter().for_each(|p| hash_point(p, &mut fingerprint));
+                }
+                enforce_min_separation(sites, distance, policy)?
+            }
+            None => (sites, Vec::new()),
+        };
+        let (sites, mirrors, stats) = match self.symmetry {
+            Some((axis, wrong_side)) => {
+                if hash_input && self.min_separation.is_none() {
+                    sites.iter().for_each(|p| hash_point(p, &mut fingerprint));
+                }
+                let tolerance = tolerance(&self.boundary);
+                let (mut sites, mirrors) = mirror_sites(sites, &axis, wrong_side, tolerance)?;
+                let stats = self.choose_auto_boundary(&sites);
+                if !is_symmetric(&self.boundary, &axis) {
+                    return Err(BuildError::AsymmetricBoundary);
+                }
+
+                // the sites kept come first, and are removed along with their mirrors if outside of the boundary
+                let kept = mirrors.iter().enumerate().filter(|&(i, &m)| m >= i).count();
+                sites.truncate(kept);
+                let sites =
+                    sites_inside_boundary(sites, &self.boundary, self.clip_behavior, |_| {});
+                let (sites, mirrors) =
+                    mirror_sites(sites, &axis, WrongSideSites::Ignore, tolerance)?;
+                (sites, Some((axis, mirrors)), stats)
+            }
+            None => {
+                let stats = self.choose_auto_boundary(&sites);
+                let hash_sites = hash_input && self.min_separation.is_none();
+                let sites = sites_inside_boundary(sites, &self.boundary, self.clip_behavior, |p| {
+                    if hash_sites {
+                        hash_point(p, &mut fingerprint)
+                    }
+                });
+                (sites, None, stats)
+            }
+        };
+        let triangulation = self.engine.triangulate_in(&sites, &mut arena.triangulation);
+        let v = Voronoi::from_triangulation(
+            sites,
+            triangulation,
             self.boundary.clone(),
             self.clip_behavior,
+            self.adjacency_epsilon,
+            self.engine,
+            arena,
+        );
+
+        let v = match (v, mirrors) {
+            (Some(mut voronoi), Some((axis, mirrors))) => {
+                symmetrize(&mut voronoi, &axis);
+                voronoi.symmetry = Some((axis, mirrors));
+                self.perform_lloyd_relaxation(Some(voronoi), arena, report)
+            }
+            (v, _) => {
+                let v = self.perform_lloyd_relaxation(v, arena, report);
+                let v = self.perform_short_edge_elimination(v);
+                let v = self.perform_cell_vertex_cap(v);
+                self.perform_vertex_welding(v)
+            }
+        };
+        let mut voronoi = v.ok_or(BuildError::Degenerate)?;
+        voronoi.warnings.splice(0..0, separation_warnings);
+        voronoi.stats = stats;
+        if hash_input {
+            voronoi.input_fingerprint = fingerprint.finish();
+        }
+        voronoi.rebuild_options = RebuildOptions {
+            short_edge_elimination: self.short_edge_elimination,
+            max_cell_vertices: self.max_cell_vertices,
+            vertex_welding: self.vertex_welding,
+            symmetry: self.symmetry.map(|(axis, _)| axis),
+            min_separation: self.min_separation,
+            debug_validation: self.debug_validation,
+        };
+        phase.record("cells", voronoi.cells.len());
+        phase.record("warnings", voronoi.warnings.len());
+        trace_warnings(&voronoi.warnings);
+        #[cfg(test)]
+        if let Some(corruption) = self.corruption {
+            corruption(&mut voronoi);
+        }
+        if cfg!(debug_assertions) {
+            debug_validate(&voronoi, self.debug_validation);
+        }
+
+        Ok(voronoi)
+    }
+
+    /// Consumes this builder and runs ```iterations``` of Lloyd relaxation, instead of those set by [Self::set_lloyd_relaxation_iterations()],
+    /// returning only the relaxed sites.
+    ///
+    /// The sites are the same as the [Voronoi::sites()] of the diagram [Self::build()] returns with as many iterations, but the cells of the last relaxed sites are not built,
+    /// so this is faster when only the sites are needed, e.g. to feed them to another system. The diagram has to be built anyway
+    /// when the sites depend on it, with [Self::set_symmetry()] or [Self::set_short_edge_elimination()], or without iterations.
+    /// As the diagram of the relaxed sites may not be built, relaxed sites that do not generate a valid diagram are not reported as [BuildError::Degenerate].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let builder = VoronoiBuilder::<BoundingBox>::default().generate_circle_sites(50, 0.5);
+    /// let sites = builder.clone().relax_sites(3).unwrap();
+    /// let voronoi = builder.set_lloyd_relaxation_iterations(3).build().unwrap();
+    /// assert_eq!(&sites, voronoi.sites());
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no sites have been provided through [Self::set_sites] or one of the generate_*_sites methods.
+    pub fn relax_sites(self, iterations: usize) -> Result<Vec<Point>, BuildError> {
+        self.relax_sites_with_report(iterations)
+            .map(|(sites, _)| sites)
+    }
+
+    /// Same as [Self::relax_sites()], but also returns how far the sites moved in each iteration, to tell how close to convergence the relaxation got.
+    pub fn relax_sites_with_report(
+        mut self,
+        iterations: usize,
+    ) -> Result<(Vec<Point>, RelaxationReport), BuildError> {
+        let mut report = RelaxationReport::default();
+        let arena = &mut CellBuilderArena::default();
+        if iterations == 0 || self.symmetry.is_some() || self.short_edge_elimination.is_some() {
+            self.lloyd_iterations = iterations;
+            let voronoi = self.build_with_report(arena, &mut report, false)?;
+            return Ok((voronoi.sites, report));
+        }
+
+        // the steps after relaxation do not change the sites, and are not run on the diagrams relaxed
+        self.lloyd_iterations = iterations - 1;
+        self.max_cell_vertices = None;
+        self.vertex_welding = None;
+        let voronoi = self.build_with_report(arena, &mut report, false)?;
+        let sites = sites_inside_boundary(
+            relaxed_sites(&voronoi, &mut report),
+            &voronoi.boundary,
+            voronoi.clip_behavior,
+            |_| {},
         );
 
-        self.perform_lloyd_relaxation(v)
+        Ok((sites, report))
+    }
+
+    /// Consumes this builder and calculates only the topology of the diagram: the Delaunay triangulation of the sites and the adjacency it implies.
+    ///
+    /// No cell geometry is calculated, so this is faster than [Self::build()] when only adjacency is needed. Sites outside of the boundary are removed
+    /// as in [Self::build()], per the clip behavior. Lloyd relaxation, short edge elimination and the cell vertex cap need the cell geometry and are not applied.
+    /// Use [VoronoiTopology::with_geometry()] to build the full diagram later, reusing the triangulation.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let topology = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build_topology()
+    ///     .unwrap();
+    /// let max_degree = (0..topology.sites().len()).map(|s| topology.neighbors(s).len()).max();
+    /// println!("The maximum number of neighbors is {:?}", max_degree);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no sites have been provided through [Self::set_sites] or one of the generate_*_sites methods.
+    pub fn build_topology(mut self) -> Result<VoronoiTopology, BuildError> {
+        let sites = self.take_sites();
+        self.choose_auto_boundary(&sites);
+        let sites = sites_inside_boundary(sites, &self.boundary, self.clip_behavior, |_| {});
+
+        VoronoiTopology::new(
+            sites,
+            self.clip_behavior,
+            self.adjacency_epsilon,
+            self.engine,
+        )
+    }
+
+    /// Calculates the fingerprint of the inputs of this builder: its sites, boundary and configuration.
+    ///
+    /// This is the value [Voronoi::input_fingerprint()] returns for the diagram built by [Self::build()], so a cache of diagrams can be looked up before building.
+    /// Coordinates are hashed by their bit patterns, so a change of a single bit of any coordinate changes the fingerprint,
+    /// while ```0.0``` and ```-0.0``` are different inputs. The fingerprint is not stable across versions of this crate.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let builder = VoronoiBuilder::<BoundingBox>::default().generate_square_sites(10);
+    /// let fingerprint = builder.fingerprint();
+    /// assert_eq!(builder.build().unwrap().input_fingerprint(), fingerprint);
+    ///```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FingerprintHasher::default();
+        self.hash_input(&mut hasher);
+        hasher.finish()
+    }
+
+    pub(crate) fn hash_input<H: Hasher>(&self, state: &mut H) {
+        let sites = self
+            .sites
+            .as_ref()
+            .map_or(&[][..], |sites| sites.as_slice());
+        self.hash_configuration(sites.len(), state);
+        sites.iter().for_each(|p| hash_point(p, state));
+    }
+
+    /// Takes the sites to build the diagram from, copying them only if they are shared with a clone of this builder.
+    fn take_sites(&mut self) -> Vec<Point> {
+        let sites = self
+            .sites
+            .take()
+            .expect("Cannot build voronoi without sites. Call set_sites() first.");
+        Arc::try_unwrap(sites).unwrap_or_else(|shared| shared.as_ref().clone())
+    }
+
+    /// Hashes everything in the input but the sites, given their count.
+    fn hash_configuration<H: Hasher>(&self, site_count: usize, state: &mut H) {
+        state.write_u8(match self.clip_behavior {
+            ClipBehavior::None => 0,
+            ClipBehavior::RemoveSitesOutsideBoundaryOnly => 1,
+            ClipBehavior::Clip => 2,
+        });
+        state.write_u8(match self.engine {
+            Engine::Delaunator => 0,
+            #[cfg(feature = "alt-triangulator")]
+            Engine::BowyerWatson => 1,
+        });
+        state.write_u64(self.adjacency_epsilon.to_bits());
+        state.write_usize(self.lloyd_iterations);
+        match self.short_edge_elimination {
+            Some((min_edge_length, max_passes)) => {
+                state.write_u8(1);
+                state.write_u64(min_edge_length.to_bits());
+                state.write_usize(max_passes);
+            }
+            None => state.write_u8(0),
+        }
+        match self.max_cell_vertices {
+            Some(max_vertices) => {
+                state.write_u8(1);
+                state.write_usize(max_vertices);
+            }
+            None => state.write_u8(0),
+        }
+        match self.vertex_welding {
+            Some(epsilon) => {
+                state.write_u8(1);
+                state.write_u64(epsilon.to_bits());
+            }
+            None => state.write_u8(0),
+        }
+        match self.auto_boundary {
+            Some((AutoBoundary::TightBox { padding }, _)) => {
+                state.write_u8(1);
+                state.write_u64(padding.to_bits());
+            }
+            Some((AutoBoundary::HullWithMargin { margin }, _)) => {
+                state.write_u8(2);
+                state.write_u64(margin.to_bits());
+            }
+            Some((AutoBoundary::MinimalBias { target_fraction }, _)) => {
+                state.write_u8(3);
+                state.write_u64(target_fraction.to_bits());
+            }
+            None => state.write_u8(0),
+        }
+        match self.symmetry {
+            Some((axis, wrong_side)) => {
+                let (tag, coordinate) = match axis {
+                    SymmetryAxis::Vertical { x } => (1, x),
+                    SymmetryAxis::Horizontal { y } => (2, y),
+                };
+                state.write_u8(tag);
+                state.write_u64(coordinate.to_bits());
+                state.write_u8(match wrong_side {
+                    WrongSideSites::Ignore => 0,
+                    WrongSideSites::Reject => 1,
+                });
+            }
+            None => state.write_u8(0),
+        }
+        match self.min_separation {
+            Some((distance, policy)) => {
+                state.write_u8(1);
+                state.write_u64(distance.to_bits());
+                state.write_u8(match policy {
+                    SeparationPolicy::Error => 0,
+                    SeparationPolicy::DropLater => 1,
+                    SeparationPolicy::Average => 2,
+                });
+            }
+            None => state.write_u8(0),
+        }
+        hash_points(self.boundary.vertices(), state);
+        state.write_usize(site_count);
+    }
+
+    /// Replaces the boundary by the one chosen by [Self::set_auto_boundary()] for ```sites```, if set, and returns the statistics of the choice.
+    fn choose_auto_boundary(&mut self, sites: &[Point]) -> BuildStats {
+        match self.auto_boundary {
+            Some((auto, choose)) => {
+                let (boundary, stats) = choose(&auto, sites, self.engine);
+                self.boundary = boundary;
+                stats
+            }
+            None => BuildStats::default(),
+        }
+    }
+
+    fn perform_cell_vertex_cap(&self, v: Option<Voronoi<T>>) -> Option<Voronoi<T>> {
+        match (v, self.max_cell_vertices) {
+            (Some(mut voronoi), Some(max_vertices)) => {
+                let phase = phase!(
+                    debug_span,
+                    "cell_vertex_cap",
+                    warnings = tracing::field::Empty
+                );
+                let warnings = voronoi.warnings.len();
+                cap_cell_vertices(&mut voronoi, max_vertices);
+                phase.record("warnings", voronoi.warnings.len() - warnings);
+                Some(voronoi)
+            }
+            (v, _) => v,
+        }
+    }
+
+    fn perform_vertex_welding(&self, v: Option<Voronoi<T>>) -> Option<Voronoi<T>> {
+        match (v, self.vertex_welding) {
+            (Some(mut voronoi), Some(epsilon)) => {
+                let phase = phase!(
+                    debug_span,
+                    "vertex_welding",
+                    warnings = tracing::field::Empty
+                );
+                let warnings = voronoi.warnings.len();
+                weld_vertices(&mut voronoi, epsilon);
+                phase.record("warnings", voronoi.warnings.len() - warnings);
+                Some(voronoi)
+            }
+            (v, _) => v,
+        }
     }
 
-    fn perform_lloyd_relaxation(&mut self, mut v: Option<Voronoi<T>>) -> Option<Voronoi<T>> {
-        for _ in 0..self.lloyd_iterations {
+    fn perform_short_edge_elimination(&self, v: Option<Voronoi<T>>) -> Option<Voronoi<T>> {
+        match (v, self.short_edge_elimination) {
+            (Some(voronoi), Some((min_edge_length, max_passes))) => {
+                let phase = phase!(
+                    debug_span,
+                    "short_edge_elimination",
+                    passes = tracing::field::Empty,
+                    warnings = tracing::field::Empty
+                );
+                let (voronoi, passes) = eliminate_short_edges(voronoi, min_edge_length, max_passes);
+                phase.record("passes", passes);
+                phase.record("warnings", voronoi.warnings.len());
+                Some(voronoi)
+            }
+            (v, _) => v,
+        }
+    }
+
+    fn perform_lloyd_relaxation(
+        &mut self,
+        mut v: Option<Voronoi<T>>,
+        arena: &mut CellBuilderArena,
+        report: &mut RelaxationReport,
+    ) -> Option<Voronoi<T>> {
+        for _iteration in 0..self.lloyd_iterations {
+            let _phase = phase!(debug_span, "relaxation", iteration = _iteration);
             if let Some(voronoi) = v {
-                // get vertices for each cell and approximate centroid
-                let new_sites = voronoi
-                    .iter_cells()
-                    .map(|c| calculate_approximated_cetroid(c.iter_vertices()))
-                    .collect::<Vec<Point>>();
+                let new_sites = relaxed_sites(&voronoi, report);
 
-                // recompute new voronoi with sites after relaxation
+                // recompute new voronoi with sites after relaxation, reusing the arena of the build
                 v = Self::create_builder_from_voronoi_without_sites(&voronoi)
                     .set_sites(new_sites)
-                    .build();
+                    .build_with_report(arena, &mut RelaxationReport::default(), false)
+                    .ok();
             } else {
                 break;
             }
@@ -137,12 +817,167 @@ impl<T: ConvexBoundary> VoronoiBuilder<T> {
         self.generate_rect_sites(width, width)
     }
 
+    /// Generates ```count``` sites from the [Halton sequence](https://en.wikipedia.org/wiki/Halton_sequence) of ```bases```, within the boundary.
+    /// Internally calls [Self::set_sites] with the generated value.
+    ///
+    /// See [Self::generate_rotated_halton_sites()] for how the sequence is mapped into the boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either base is less than 2.
+    pub fn generate_halton_sites(self, count: usize, bases: (u32, u32)) -> Self {
+        self.generate_rotated_halton_sites(count, bases, (0.0, 0.0))
+    }
+
+    /// Same as [Self::generate_halton_sites()], with each point of the sequence shifted by ```rotation```, modulo 1, before being mapped into the boundary.
+    /// Internally calls [Self::set_sites] with the generated value.
+    ///
+    /// This is the Cranley-Patterson rotation of the sequence: each rotation gives a different set of sites, as evenly spread as the sequence itself.
+    /// The unit square is mapped onto the axis-aligned box around the vertices of the boundary, and points outside of the boundary are skipped,
+    /// continuing the sequence until ```count``` sites are within it. The first point of the sequence, at the origin, is always skipped.
+    /// The bases should be coprime, such as (2, 3), for the sites to be evenly spread. Sites are generated within the boundary set when this is called.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::default()
+    ///     .set_boundary(ConvexPolygon::regular(6, 1.0))
+    ///     .generate_rotated_halton_sites(100, (2, 3), (0.25, 0.5))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(v.sites().len(), 100);
+    ///```
+    ///
+    /// # Panics
+    ///
+    /// Panics if either base is less than 2.
+    pub fn generate_rotated_halton_sites(
+        self,
+        count: usize,
+        bases: (u32, u32),
+        rotation: (f64, f64),
+    ) -> Self {
+        assert!(
+            bases.0 >= 2 && bases.1 >= 2,
+            "Halton sequence bases must be at least 2, got {:?}.",
+            bases
+        );
+
+        let sites = quasi_random::sequence_sites(&self.boundary, count, rotation, |i| {
+            quasi_random::halton(i, bases)
+        });
+        self.set_sites(sites)
+    }
+
+    /// Generates ```count``` sites from the R2 sequence, the additive recurrence of the inverse powers of the plastic number, within the boundary.
+    /// Internally calls [Self::set_sites] with the generated value.
+    ///
+    /// The R2 sequence is as evenly spread as the Halton sequence, and has no parameters to tune.
+    /// See [Self::generate_rotated_halton_sites()] for how the sequence is mapped into the boundary.
+    pub fn generate_r2_sites(self, count: usize) -> Self {
+        self.generate_rotated_r2_sites(count, (0.0, 0.0))
+    }
+
+    /// Same as [Self::generate_r2_sites()], with each point of the sequence shifted by ```rotation```, modulo 1, before being mapped into the boundary.
+    /// Internally calls [Self::set_sites] with the generated value.
+    ///
+    /// See [Self::generate_rotated_halton_sites()].
+    pub fn generate_rotated_r2_sites(self, count: usize, rotation: (f64, f64)) -> Self {
+        let sites = quasi_random::sequence_sites(&self.boundary, count, rotation, quasi_random::r2);
+        self.set_sites(sites)
+    }
+
+    /// Creates a builder with the options ```v``` was built with, which are applied again when it is edited, see [Voronoi::move_site()].
+    /// The diagrams of the iterations of Lloyd relaxation have none of these options yet, so relaxation builds them without.
     fn create_builder_from_voronoi_without_sites(v: &Voronoi<T>) -> Self {
+        let options = v.rebuild_options;
         Self {
             boundary: v.boundary.clone(),
             clip_behavior: v.clip_behavior,
+            adjacency_epsilon: v.adjacency_epsilon,
+            engine: v.engine,
+            short_edge_elimination: options.short_edge_elimination,
+            max_cell_vertices: options.max_cell_vertices,
+            vertex_welding: options.vertex_welding,
+            min_separation: options.min_separation,
+            debug_validation: options.debug_validation,
+            #[cfg(test)]
+            corruption: None,
+            auto_boundary: None,
             lloyd_iterations: 0,
             sites: None,
+            symmetry: v.symmetry_axis().map(|axis| (axis, WrongSideSites::Ignore)),
+        }
+    }
+}
+
+/// Gets the approximated centroid of each cell, the sites of the next iteration of Lloyd relaxation, and records how far the sites move to them in ```report```.
+///
+/// Only the sites kept of a symmetric diagram are relaxed, and mirrored again when rebuilding it.
+fn relaxed_sites<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    report: &mut RelaxationReport,
+) -> Vec<Point> {
+    let (sites, displacements): (Vec<Point>, Vec<f64>) = voronoi
+        .iter_cells()
+        .filter(|c| !matches!(voronoi.mirror_of(c.site()), Some(m) if m < c.site()))
+        .map(|c| {
+            let centroid = calculate_approximated_cetroid(c.iter_vertices());
+            let displacement = dist2(c.site_position(), &centroid).sqrt();
+            (centroid, displacement)
+        })
+        .unzip();
+
+    report
+        .max_displacements
+        .push(displacements.iter().copied().fold(0.0, f64::max));
+    report
+        .mean_displacements
+        .push(displacements.iter().sum::<f64>() / displacements.len().max(1) as f64);
+
+    sites
+}
+
+impl VoronoiBuilder<ConvexPolygon> {
+    /// Chooses the boundary from the sites when building, instead of using the one set by [Self::set_boundary()]. See [AutoBoundary] for the available modes.
+    ///
+    /// The boundary always contains all the sites, so none is removed. It is chosen for the sites as set, before Lloyd relaxation,
+    /// which then moves the sites within it; the other post-processing steps keep it too. The chosen boundary and the fraction of cells it clips
+    /// are reported in [Voronoi::build_stats()].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::default()
+    ///     .generate_square_sites(10)
+    ///     .set_auto_boundary(AutoBoundary::MinimalBias { target_fraction: 0.5 })
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(v.build_stats().clipped_fraction.unwrap() <= 0.5);
+    ///```
+    pub fn set_auto_boundary(mut self, auto: AutoBoundary) -> Self {
+        self.auto_boundary = Some((auto, choose_boundary));
+        self
+    }
+}
+
+impl VoronoiBuilder<AnyBoundary> {
+    /// Creates a builder of diagrams within ```boundary```, any of the built-in boundaries. See [AnyBoundary].
+    pub fn with_boundary(boundary: impl Into<AnyBoundary>) -> Self {
+        Self::default().set_boundary(boundary.into())
+    }
+
+    /// Creates a builder that chooses the boundary from the sites when building, as [VoronoiBuilder::<ConvexPolygon>::set_auto_boundary()] does.
+    /// The chosen boundary is always a [AnyBoundary::ConvexPolygon].
+    pub fn with_auto_boundary(auto: AutoBoundary) -> Self {
+        Self {
+            auto_boundary: Some((auto, |auto, sites, engine| {
+                let (boundary, stats) = choose_boundary(auto, sites, engine);
+                (boundary.into(), stats)
+            })),
+            ..Self::default()
         }
     }
 }
@@ -152,7 +987,7 @@ impl<T: ConvexBoundary> From<&Voronoi<T>> for VoronoiBuilder<T> {
     /// Useful for performing Lloyd relaxation or storing the configuration to generate a identical diagram.
     fn from(v: &Voronoi<T>) -> Self {
         let mut builder = Self::create_builder_from_voronoi_without_sites(v);
-        builder.sites = Some(v.sites.clone());
+        builder.sites = Some(Arc::new(v.sites.clone()));
 
         builder
     }
@@ -163,8 +998,186 @@ impl<T: ConvexBoundary> From<Voronoi<T>> for VoronoiBuilder<T> {
     /// Useful for performing Lloyd relaxation or storing the configuration to generate a identical diagram.
     fn from(v: Voronoi<T>) -> Self {
         let mut builder = Self::create_builder_from_voronoi_without_sites(&v);
-        builder.sites = Some(v.sites);
+        builder.sites = Some(Arc::new(v.sites));
 
         builder
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test::{geometry_eq, random_sites},
+        BoundingBox,
+    };
+
+    #[test]
+    fn clones_share_sites_until_built() {
+        let builder = VoronoiBuilder::<BoundingBox>::default().generate_square_sites(20);
+        let relaxed = builder.clone().set_lloyd_relaxation_iterations(2);
+        let welded = builder.clone().set_vertex_welding(1e-9);
+        let sites = builder.sites.as_ref().expect("Sites expected");
+        assert_eq!(Arc::strong_count(sites), 3);
+        assert!(Arc::ptr_eq(sites, relaxed.sites.as_ref().unwrap()));
+        assert!(Arc::ptr_eq(sites, welded.sites.as_ref().unwrap()));
+        assert_eq!(builder.fingerprint(), builder.clone().fingerprint());
+
+        // building a clone copies the shared sites, leaving the others untouched
+        let expected = builder.sites.as_deref().unwrap().clone();
+        let voronoi = relaxed.build().expect("Some voronoi expected");
+        assert_eq!(Arc::strong_count(builder.sites.as_ref().unwrap()), 2);
+        assert_ne!(voronoi.sites(), &expected);
+        assert_eq!(builder.sites.as_deref(), Some(&expected));
+
+        // the last holder builds from the sites without copying them
+        drop(welded);
+        let mut last = builder.clone();
+        drop(builder);
+        let pointer = last.sites.as_ref().unwrap().as_ptr();
+        let sites = last.take_sites();
+        assert_eq!(sites.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn clones_with_different_boundaries_are_independent() {
+        let builder = VoronoiBuilder::<BoundingBox>::default().generate_square_sites(10);
+        let small = builder
+            .clone()
+            .set_boundary(BoundingBox::new_centered_square(0.5));
+        let direct = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(10)
+            .build()
+            .expect("Some voronoi expected");
+        let direct_small = VoronoiBuilder::<BoundingBox>::default()
+            .set_boundary(BoundingBox::new_centered_square(0.5))
+            .generate_square_sites(10)
+            .build()
+            .expect("Some voronoi expected");
+
+        let small = small.build().expect("Some voronoi expected");
+        assert!(geometry_eq(&small, &direct_small));
+        assert!(small.sites().len() < direct.sites().len());
+        assert!(geometry_eq(
+            &builder.build().expect("Some voronoi expected"),
+            &direct
+        ));
+    }
+
+    #[test]
+    fn mutating_clone_does_not_affect_sibling() {
+        let builder = VoronoiBuilder::<BoundingBox>::default().generate_square_sites(10);
+        let expected = builder.sites.as_deref().unwrap().clone();
+        let mutated = builder.clone().generate_square_sites(5);
+        let replaced = builder.clone().set_sites(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.5, y: 0.0 },
+            Point { x: 0.0, y: 0.5 },
+        ]);
+
+        assert_eq!(mutated.sites.as_ref().unwrap().len(), 25);
+        assert_eq!(replaced.sites.as_ref().unwrap().len(), 3);
+        assert_eq!(builder.sites.as_deref(), Some(&expected));
+        assert_eq!(Arc::strong_count(builder.sites.as_ref().unwrap()), 1);
+        assert_eq!(
+            builder.build().expect("Some voronoi expected").sites(),
+            &expected
+        );
+    }
+
+    fn assert_relaxes_as_build(builder: VoronoiBuilder<BoundingBox>, iterations: usize) {
+        let (sites, report) = builder
+            .clone()
+            .relax_sites_with_report(iterations)
+            .expect("Relaxed sites expected");
+        let voronoi = builder
+            .set_lloyd_relaxation_iterations(iterations)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(&sites, voronoi.sites());
+        assert_eq!(report.max_displacements.len(), iterations);
+        assert_eq!(report.mean_displacements.len(), iterations);
+    }
+
+    #[test]
+    fn relaxed_sites_match_built_sites() {
+        let builder = VoronoiBuilder::<BoundingBox>::default().set_sites(random_sites(300));
+        for iterations in 0..4 {
+            assert_relaxes_as_build(builder.clone(), iterations);
+        }
+        assert_relaxes_as_build(
+            builder
+                .clone()
+                .set_clip_behavior(ClipBehavior::RemoveSitesOutsideBoundaryOnly)
+                .set_boundary(BoundingBox::new_centered_square(1.5)),
+            2,
+        );
+        assert_relaxes_as_build(
+            builder
+                .clone()
+                .set_max_cell_vertices(5)
+                .set_vertex_welding(1e-3),
+            3,
+        );
+        assert_relaxes_as_build(builder.clone().set_short_edge_elimination(0.01, 2), 2);
+        assert_relaxes_as_build(
+            builder.set_symmetry(SymmetryAxis::Vertical { x: 0.0 }, WrongSideSites::Ignore),
+            2,
+        );
+    }
+
+    #[test]
+    fn builder_from_voronoi_keeps_build_options() {
+        let options = |builder: VoronoiBuilder<BoundingBox>| {
+            builder
+                .set_short_edge_elimination(0.01, 2)
+                .set_max_cell_vertices(6)
+                .set_vertex_welding(1e-3)
+                .set_min_separation(0.005, SeparationPolicy::DropLater)
+                .set_debug_validation(DebugValidation::Full)
+        };
+        let voronoi = options(VoronoiBuilder::default().set_sites(random_sites(300)))
+            .build()
+            .expect("Some voronoi expected");
+
+        let from_ref = VoronoiBuilder::from(&voronoi);
+        assert_eq!(from_ref.short_edge_elimination, Some((0.01, 2)));
+        assert_eq!(from_ref.max_cell_vertices, Some(6));
+        assert_eq!(from_ref.vertex_welding, Some(1e-3));
+        assert_eq!(
+            from_ref.min_separation,
+            Some((0.005, SeparationPolicy::DropLater))
+        );
+        assert_eq!(from_ref.debug_validation, DebugValidation::Full);
+
+        // the sites of the diagram were nudged when it was built, so the options are applied to them again
+        let expected = options(VoronoiBuilder::default().set_sites(voronoi.sites().clone()))
+            .build()
+            .expect("Some voronoi expected");
+        let rebuilt = from_ref.build().expect("Some voronoi expected");
+        assert!(geometry_eq(&expected, &rebuilt));
+        assert_eq!(expected.build_warnings(), rebuilt.build_warnings());
+        let rebuilt = VoronoiBuilder::from(voronoi)
+            .build()
+            .expect("Some voronoi expected");
+        assert!(geometry_eq(&expected, &rebuilt));
+    }
+
+    #[test]
+    fn relaxation_report_shows_convergence() {
+        let (_, report) = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(random_sites(500))
+            .relax_sites_with_report(10)
+            .expect("Relaxed sites expected");
+        let (first, last) = (report.max_displacements[0], report.max_displacements[9]);
+        assert!(last < first);
+        assert!(report.mean_displacements[9] < report.mean_displacements[0]);
+        for (mean, max) in report
+            .mean_displacements
+            .iter()
+            .zip(&report.max_displacements)
+        {
+            assert!(mean > &0.0 && mean <= max);
+        }
+    }
+}
@@ -2,8 +2,99 @@ use std::fmt;
 
 use delaunator::EMPTY;
 
-use super::{iterator::NeighborSiteIterator, Point, Voronoi};
-use crate::ConvexBoundary;
+use super::{
+    iterator::{EdgesAroundSiteIterator, NeighborSiteIterator},
+    Point, Voronoi,
+};
+use crate::{cell_builder::extend_hull_circumcenter, utils, ConvexBoundary, PerimeterWalker};
+
+/// Describes an edge of a Voronoi cell, as returned by [VoronoiCell::iter_edge_metadata()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellEdge {
+    /// The pair of indices into [Voronoi::vertices()] that form this edge, in the same order returned by [VoronoiCell::iter_edges()].
+    pub vertices: (usize, usize),
+
+    /// The site on the other side of this edge, if any.
+    ///
+    /// This is ```Some``` if, and only if, the site is returned by [VoronoiCell::iter_neighbors()].
+    /// Edges on the boundary and edges that are not longer than [Voronoi::adjacency_epsilon()] have no neighbor.
+    pub neighbor: Option<usize>,
+
+    /// The length of this edge.
+    pub length: f64,
+
+    /// Whether this edge is part of a Voronoi edge that was shortened by clipping it to the boundary.
+    ///
+    /// Edges along the boundary itself are not clipped Voronoi edges, so this is ```false``` for them.
+    pub clipped: bool,
+
+    /// The endpoints of the Voronoi edge before it was clipped, in the same direction as [Self::vertices], if this edge was clipped.
+    ///
+    /// These are circumcenters of the Delaunay triangles on both sides of the edge, as returned by [VoronoiCell::unclipped_vertices()].
+    /// For edges dual to a hull edge of the triangulation, which extend to infinity, the second endpoint is far beyond the boundary instead.
+    pub original_endpoints: Option<(Point, Point)>,
+}
+
+/// Gets the endpoints, oriented as ```u -> v```, of the unclipped Voronoi edge the cell edge ```u -> v``` lies on, if the edge was clipped.
+fn original_voronoi_edge<T: ConvexBoundary>(
+    voronoi: &Voronoi<T>,
+    site: usize,
+    u: usize,
+    v: usize,
+) -> Option<(Point, Point)> {
+    // circumcenters come first in the vertices, an edge between two of them was not clipped
+    let number_of_triangles = voronoi.number_of_triangles();
+    if u < number_of_triangles && v < number_of_triangles {
+        return None;
+    }
+
+    let e = utils::dual_delaunay_edge(voronoi, site, u, v)?;
+    let triangulation = &voronoi.triangulation;
+    let a = voronoi.circumcenters[utils::triangle_of_edge(e)].clone();
+    let b = match triangulation.halfedges[e] {
+        EMPTY => extend_hull_circumcenter(triangulation, &voronoi.sites, &a, e),
+        twin => voronoi.circumcenters[utils::triangle_of_edge(twin)].clone(),
+    };
+
+    let (pu, pv) = (&voronoi.circumcenters[u], &voronoi.circumcenters[v]);
+    if (b.x - a.x) * (pv.x - pu.x) + (b.y - a.y) * (pv.y - pu.y) < 0.0 {
+        Some((b, a))
+    } else {
+        Some((a, b))
+    }
+}
+
+/// Asserts that the vertices of a [VoronoiCell] are approximately equal to a slice of [Point]s.
+///
+/// Vertices are compared in counter-clockwise order, but the comparison may start at any vertex. See [VoronoiCell::vertices_approx_eq()].
+///
+/// # Example
+///
+///```
+/// use voronoice::*;
+/// let v = VoronoiBuilder::<BoundingBox>::default()
+///     .set_sites(vec![Point { x: -0.5, y: 0.0 }, Point { x: 0.5, y: 0.0 }, Point { x: 0.0, y: 0.5 }])
+///     .build()
+///     .unwrap();
+/// let expected: Vec<Point> = v.cell(0).iter_vertices().cloned().collect();
+/// assert_cell_approx_eq!(v.cell(0), &expected, 1e-12);
+///```
+#[macro_export]
+macro_rules! assert_cell_approx_eq {
+    ($cell:expr, $expected:expr, $epsilon:expr) => {{
+        let cell = &$cell;
+        let expected: &[$crate::Point] = $expected;
+        if !cell.vertices_approx_eq(expected, $epsilon) {
+            panic!(
+                "assertion failed: cell vertices are not approximately equal (epsilon {:?})\n     cell: {:?}\n vertices: {:?}\n expected: {:?}",
+                $epsilon,
+                cell,
+                cell.iter_vertices().collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }};
+}
 
 /// Represents a Voronoi cell. This is an ergonomic way to access cell details.
 ///
@@ -20,6 +111,11 @@ impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
         Self { site, voronoi }
     }
 
+    #[inline]
+    pub(crate) fn voronoi(&self) -> &'v Voronoi<T> {
+        self.voronoi
+    }
+
     /// Gets a reference to the position of the site associated with this cell.
     ///
     /// # Examples
@@ -76,6 +172,37 @@ impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
         )
     }
 
+    /// Gets an iterator that returns a [CellEdge] for each edge of this voronoi cell.
+    ///
+    /// Edges are returned in the same order as [Self::iter_edges()], each with the site on its other side and its length.
+    /// Every neighbor returned by [Self::iter_neighbors()] is on the other side of exactly the edges whose lengths add up to [Self::shared_edge_length()].
+    pub fn iter_edge_metadata(&self) -> impl Iterator<Item = CellEdge> + 'v {
+        let voronoi = self.voronoi;
+        let site = self.site;
+        self.iter_edges().map(move |(u, v)| {
+            let original_endpoints = original_voronoi_edge(voronoi, site, u, v);
+            CellEdge {
+                vertices: (u, v),
+                neighbor: utils::edge_neighbor(voronoi, site, u, v),
+                length: utils::dist2(&voronoi.circumcenters[u], &voronoi.circumcenters[v]).sqrt(),
+                clipped: original_endpoints.is_some(),
+                original_endpoints,
+            }
+        })
+    }
+
+    /// Gets the length of the Voronoi edge shared by this cell and the cell of site ```other```.
+    ///
+    /// This is zero if the cells do not share an edge, or if the cells only touch at a single point.
+    /// ```other``` is returned by [Self::iter_neighbors()] if, and only if, this length is greater than [Voronoi::adjacency_epsilon()].
+    pub fn shared_edge_length(&self, other: usize) -> f64 {
+        if other == self.site {
+            0.0
+        } else {
+            utils::shared_voronoi_edge_length(self.voronoi, self.site, other)
+        }
+    }
+
     /// Gets an iterator for the vertices of this cell.
     ///
     /// Vertices are returned in sequential counter-clockwise order.
@@ -87,6 +214,171 @@ impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
             .map(move |&t| &self.voronoi.circumcenters[t])
     }
 
+    /// Gets an iterator for the circumcenters of the Delaunay triangles of this cell, before any clipping or hull closing.
+    ///
+    /// Circumcenters are returned in the same counter-clockwise order as [Self::iter_vertices()], but without boundary intersections or boundary vertices,
+    /// so vertices outside the boundary are included. For cells that were not clipped, this returns the same vertices as [Self::iter_vertices()], starting at the same vertex.
+    /// Hull cells are open, and their circumcenters are returned from the first to the last one around the site.
+    pub fn unclipped_vertices(&self) -> impl Iterator<Item = &'v Point> {
+        let voronoi = self.voronoi;
+        let incoming = voronoi.site_to_incoming_leftmost_halfedge[self.site];
+        let mut triangles: Vec<usize> =
+            EdgesAroundSiteIterator::new(&voronoi.triangulation, incoming)
+                .map(utils::triangle_of_edge)
+                .collect();
+
+        // edges around the site are iterated clockwise, and closed around sites not on the hull of the triangulation
+        triangles.reverse();
+        if incoming != EMPTY && voronoi.triangulation.halfedges[incoming] != EMPTY {
+            if let Some(start) = self
+                .triangles()
+                .first()
+                .and_then(|first| triangles.iter().position(|t| t == first))
+            {
+                triangles.rotate_left(start);
+            }
+        }
+
+        triangles
+            .into_iter()
+            .map(move |t| &voronoi.circumcenters[t])
+    }
+
+    /// Gets the support point of this cell in direction ```dir```, i.e. the vertex maximizing the dot product with ```dir```.
+    ///
+    /// The position of the vertex within [Self::triangles()] is returned along with the vertex.
+    /// If multiple vertices are equally extreme, such as when an edge of the cell is perpendicular to ```dir```, the first of them in counter-clockwise order is returned.
+    ///
+    /// This binary searches over the convex vertex fan of the cell, in ```O(log n)``` on its number of vertices. Runs of equal values,
+    /// from repeated vertices such as the coincident circumcenters of cocircular sites or from collinear vertices, are walked one vertex at a time,
+    /// so this is ```O(n)``` in the worst case.
+    pub fn support(&self, dir: &Point) -> (usize, &'v Point) {
+        let vertices = &self.voronoi.circumcenters;
+        let triangles = self.triangles();
+        let i = utils::extreme_vertex(triangles.len(), |i| {
+            let v = &vertices[triangles[i]];
+            v.x * dir.x + v.y * dir.y
+        });
+
+        (i, &vertices[triangles[i]])
+    }
+
+    /// Gets the vertex of this cell farthest from ```p```.
+    ///
+    /// The position of the vertex within [Self::triangles()] is returned along with the vertex.
+    /// If multiple vertices are equally far, the first of them within [Self::triangles()] is returned.
+    pub fn farthest_vertex_from(&self, p: &Point) -> (usize, &'v Point) {
+        let vertices = &self.voronoi.circumcenters;
+        let triangles = self.triangles();
+        let mut farthest = 0;
+        let mut farthest_distance = f64::NEG_INFINITY;
+        for (i, &t) in triangles.iter().enumerate() {
+            let distance = utils::dist2(p, &vertices[t]);
+            if distance > farthest_distance {
+                farthest = i;
+                farthest_distance = distance;
+            }
+        }
+
+        (farthest, &vertices[triangles[farthest]])
+    }
+
+    /// Returns whether the vertices of this cell are equal to ```expected``` within an absolute tolerance of ```epsilon``` on each coordinate.
+    ///
+    /// Vertices are compared in counter-clockwise order, but the comparison may start at any vertex, since the first vertex of a cell is arbitrary.
+    pub fn vertices_approx_eq(&self, expected: &[Point], epsilon: f64) -> bool {
+        let triangles = self.triangles();
+        let n = triangles.len();
+        if n != expected.len() {
+            return false;
+        }
+
+        let vertices = &self.voronoi.circumcenters;
+        (0..n.max(1)).any(|start| {
+            (0..n).all(|i| {
+                let (v, e) = (&vertices[triangles[(start + i) % n]], &expected[i]);
+                utils::abs_diff_eq(v.x, e.x, epsilon) && utils::abs_diff_eq(v.y, e.y, epsilon)
+            })
+        })
+    }
+
+    /// Gets the area of this cell.
+    ///
+    /// If the voronoi diagram was built with clipping disabled, the cells in the hull are not closed and their area is the area of the polygon formed by their vertices.
+    pub fn area(&self) -> f64 {
+        let vertices = &self.voronoi.circumcenters;
+        let doubled: f64 = self
+            .iter_edges()
+            .map(|(a, b)| {
+                let (a, b) = (&vertices[a], &vertices[b]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+
+        doubled.abs() / 2.0
+    }
+
+    /// Gets the centroid of the area of this cell.
+    ///
+    /// Unlike the average of the vertices used by Lloyd relaxation, this is not biased towards the parts of the cell with more vertices.
+    /// If the cell has no area, the average of its vertices is returned instead.
+    pub fn centroid(&self) -> Point {
+        let vertices = &self.voronoi.circumcenters;
+        let (doubled, x, y) = self
+            .iter_edges()
+            .fold((0.0, 0.0, 0.0), |(d, x, y), (a, b)| {
+                let (a, b) = (&vertices[a], &vertices[b]);
+                let cross = a.x * b.y - b.x * a.y;
+                (d + cross, x + (a.x + b.x) * cross, y + (a.y + b.y) * cross)
+            });
+
+        if doubled == 0.0 {
+            utils::calculate_approximated_cetroid(self.iter_vertices())
+        } else {
+            Point {
+                x: x / (3.0 * doubled),
+                y: y / (3.0 * doubled),
+            }
+        }
+    }
+
+    /// Gets the distance from ```p``` to the closest point on the edges of this cell, whether ```p``` is inside the cell or not.
+    ///
+    /// If the voronoi diagram was built with clipping disabled, the closing edge of the cells in the hull is not discarded, see [Self::iter_edges()].
+    pub fn distance_to_edges(&self, p: &Point) -> f64 {
+        let vertices = &self.voronoi.circumcenters;
+        self.iter_edges()
+            .map(|(a, b)| {
+                utils::dist2(
+                    p,
+                    &utils::closest_point_on_segment(&vertices[a], &vertices[b], p),
+                )
+            })
+            .fold(f64::INFINITY, f64::min)
+            .sqrt()
+    }
+
+    /// Gets a walker to evaluate positions and outward normals along the perimeter of this cell by arc length.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .generate_square_sites(10)
+    ///     .build()
+    ///     .unwrap();
+    /// let walker = v.cell(0).perimeter_walker();
+    /// // dashes along the border of the cell
+    /// let dashes: Vec<(Point, Point)> = (0..10)
+    ///     .map(|i| i as f64 * walker.length() / 10.0)
+    ///     .map(|t| (walker.position_at(t), walker.position_at(t + walker.length() / 20.0)))
+    ///     .collect();
+    ///```
+    pub fn perimeter_walker(&self) -> PerimeterWalker<'v> {
+        PerimeterWalker::new(self.triangles(), &self.voronoi.circumcenters)
+    }
+
     /// Gets an iterator that returns the index of each site that shared an edge with this cell/site, in a counter-clockwise manner.
     ///
     /// # Example
@@ -119,66 +411,416 @@ impl<'v, T: ConvexBoundary> VoronoiCell<'v, T> {
     /// A Voronoi cell is on the hull if its associated site is on the Delaunay hull or if clipping is enabled and the cell intersects with the bounding geometry.
     pub fn is_on_hull(&self) -> bool {
         // if there is no half-edge associated with the left-most edge, the edge is on the hull
+        // a lone site has no edges at all
         let incoming_leftmost_edge = self.voronoi.site_to_incoming_leftmost_halfedge[self.site];
-        self.voronoi.triangulation.halfedges[incoming_leftmost_edge] == EMPTY
+        incoming_leftmost_edge == EMPTY
+            || self.voronoi.triangulation.halfedges[incoming_leftmost_edge] == EMPTY
             // if the cell vertex index is higher than the # of triangles/circumcenters, it means the vertex was added either because
             // it was extending a hull cell or because of clipping (against boundary), thus the cell is on the hull
             || self.triangles().iter().any(|&t| t > self.voronoi.number_of_triangles())
     }
 }
 
-#[allow(dead_code)]
 impl<'v, T: ConvexBoundary> fmt::Debug for VoronoiCell<'v, T> {
+    /// Formats a summary of the cell: its site, number of vertices, area and whether it is on the hull.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        #[derive(Debug)]
-        struct Edge {
-            edge: usize,
-            incoming_site: usize,
-            outgoing_site: usize,
+        f.debug_struct("VoronoiCell")
+            .field("site", &self.site)
+            .field("vertices", &self.triangles().len())
+            .field("area", &self.area())
+            .field("is_on_hull", &self.is_on_hull())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{fixtures, utils::extreme_vertex, BoundingBox, VoronoiBuilder};
+
+    /// Linear scan equivalent of [extreme_vertex].
+    fn linear_extreme_vertex(values: &[f64]) -> usize {
+        let n = values.len();
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (0..n)
+            .find(|&i| values[i] == max && values[(i + n - 1) % n] != max)
+            .unwrap_or(0)
+    }
+
+    /// Asserts that ```i``` is the first vertex, in counter-clockwise order, of a run of maximal ```values```.
+    fn assert_extreme(i: usize, values: &[f64]) {
+        let n = values.len();
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(
+            values[i], max,
+            "Vertex {} is not extreme. Values {:?}",
+            i, values
+        );
+        assert!(
+            values[(i + n - 1) % n] != max || values.iter().all(|&v| v == max),
+            "Vertex {} is not the first extreme vertex. Values {:?}",
+            i,
+            values
+        );
+    }
+
+    fn random_direction(rng: &mut impl Rng) -> Point {
+        let angle: f64 = rng.gen_range(0.0..std::f64::consts::TAU);
+        Point {
+            x: angle.cos(),
+            y: angle.sin(),
         }
+    }
+
+    #[test]
+    fn support_matches_linear_scan() {
+        let mut rng = utils::test::rng();
+        let mut triangular_cells = 0;
+
+        for size in [3, 4, 10, 100, 1_000] {
+            let sites = (0..size)
+                .map(|_| Point {
+                    x: rng.gen_range(-1.0..1.0),
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect();
+            let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                .set_sites(sites)
+                .build()
+                .expect("Some voronoi expected");
 
-        #[derive(Debug)]
-        struct Site {
-            site: usize,
-            position: Point,
-            is_on_hull: bool,
-            leftmost_incoming_edge: Edge,
+            for cell in voronoi.iter_cells() {
+                if cell.triangles().len() == 3 {
+                    triangular_cells += 1;
+                }
+                for _ in 0..20 {
+                    let dir = random_direction(&mut rng);
+                    let values: Vec<f64> = cell
+                        .iter_vertices()
+                        .map(|v| v.x * dir.x + v.y * dir.y)
+                        .collect();
+                    let (i, vertex) = cell.support(&dir);
+                    assert_extreme(i, &values);
+                    assert_eq!(vertex, &voronoi.vertices()[cell.triangles()[i]]);
+                }
+            }
         }
 
-        #[derive(Debug)]
-        struct Cellvertices {
-            /// Each vertex is the circumcenter of a associated Delaunay triangle
-            triangles: Vec<usize>,
-            positions: Vec<Point>,
+        assert!(triangular_cells > 0, "Some 3-vertex cell expected.");
+    }
+
+    #[test]
+    fn support_ties_resolve_to_first_counter_clockwise_vertex() {
+        // square with repeated and collinear vertices
+        let polygon = [
+            Point { x: -1.0, y: -1.0 },
+            Point { x: -1.0, y: 0.0 },
+            Point { x: -1.0, y: 1.0 },
+            Point { x: -1.0, y: 1.0 },
+            Point { x: 0.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 1.0, y: -1.0 },
+            Point { x: 0.0, y: -1.0 },
+            Point { x: -1.0, y: -1.0 },
+        ];
+        let mut rng = utils::test::rng();
+        let mut directions: Vec<Point> = (0..200).map(|_| random_direction(&mut rng)).collect();
+        directions.extend(
+            [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0), (1.0, 1.0)]
+                .iter()
+                .map(|&(x, y)| Point { x, y }),
+        );
+
+        for start in 0..polygon.len() {
+            for dir in &directions {
+                let values: Vec<f64> = (0..polygon.len())
+                    .map(|i| {
+                        let v = &polygon[(start + i) % polygon.len()];
+                        v.x * dir.x + v.y * dir.y
+                    })
+                    .collect();
+
+                assert_eq!(
+                    extreme_vertex(values.len(), |i| values[i]),
+                    linear_extreme_vertex(&values),
+                    "Direction {:?}, values {:?}",
+                    dir,
+                    values
+                );
+            }
         }
-        let leftmost_edge = self.voronoi.site_to_incoming_leftmost_halfedge[self.site];
+    }
 
-        f.debug_struct("VoronoiCell")
-            .field(
-                "site",
-                &Site {
-                    site: self.site,
-                    position: self.site_position().clone(),
-                    is_on_hull: self.is_on_hull(),
-                    leftmost_incoming_edge: Edge {
-                        edge: leftmost_edge,
-                        incoming_site: self.site,
-                        outgoing_site: self.voronoi.triangulation.triangles[leftmost_edge],
-                    },
-                },
-            )
-            .field(
-                "vertices",
-                &Cellvertices {
-                    triangles: self.triangles().iter().copied().collect(),
-                    positions: self
-                        .triangles()
-                        .iter()
-                        .copied()
-                        .map(|t| self.voronoi.circumcenters[t].clone())
-                        .collect(),
-                },
-            )
-            .finish()
+    #[test]
+    fn extreme_vertex_is_logarithmic_without_runs() {
+        for n in [16, 256, 4096, 65536] {
+            let polygon = crate::ConvexPolygon::regular(n as i32, 1.0);
+            let vertices = polygon.vertices();
+            let mut rng = utils::test::rng();
+            for _ in 0..20 {
+                let dir = random_direction(&mut rng);
+                let evaluations = std::cell::Cell::new(0);
+                let i = extreme_vertex(n, |i| {
+                    evaluations.set(evaluations.get() + 1);
+                    vertices[i].x * dir.x + vertices[i].y * dir.y
+                });
+
+                let values: Vec<f64> = vertices.iter().map(|v| v.x * dir.x + v.y * dir.y).collect();
+                assert_extreme(i, &values);
+                assert!(
+                    evaluations.get() <= 16 * n.ilog2() as usize,
+                    "{} evaluations for {} vertices.",
+                    evaluations.get(),
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn support_handles_repeated_vertices() {
+        // cocircular sites have coincident circumcenters
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_square_sites(5)
+            .build()
+            .expect("Some voronoi expected");
+        let mut rng = utils::test::rng();
+
+        for cell in voronoi.iter_cells() {
+            for _ in 0..50 {
+                let dir = random_direction(&mut rng);
+                let values: Vec<f64> = cell
+                    .iter_vertices()
+                    .map(|v| v.x * dir.x + v.y * dir.y)
+                    .collect();
+                assert_extreme(cell.support(&dir).0, &values);
+            }
+        }
+    }
+
+    #[test]
+    fn farthest_vertex_matches_linear_scan() {
+        let mut rng = utils::test::rng();
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_circle_sites(30, 0.7)
+            .build()
+            .expect("Some voronoi expected");
+
+        for cell in voronoi.iter_cells() {
+            let p = Point {
+                x: rng.gen_range(-2.0..2.0),
+                y: rng.gen_range(-2.0..2.0),
+            };
+            let (i, vertex) = cell.farthest_vertex_from(&p);
+            let max = cell
+                .iter_vertices()
+                .map(|v| utils::dist2(v, &p))
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert_eq!(utils::dist2(vertex, &p), max);
+            assert!(cell
+                .iter_vertices()
+                .take(i)
+                .all(|v| utils::dist2(v, &p) < max));
+        }
+    }
+
+    fn three_site_voronoi() -> Voronoi<BoundingBox> {
+        let (voronoi, expected) = fixtures::three_sites_meeting_at_center();
+        expected.assert_matches(&voronoi, 1e-12);
+        voronoi
+    }
+
+    #[test]
+    fn debug_summaries() {
+        let voronoi = three_site_voronoi();
+        assert_eq!(
+            format!("{:?}", voronoi),
+            "Voronoi { sites: 3, vertices: 9, boundary: Boundary { vertices: 4, min: [-1.0, -1.0], max: [1.0, 1.0] }, clip_behavior: Clip, hull_cells: 3 }"
+        );
+        assert_eq!(
+            format!("{:?}", voronoi.cell(2)),
+            "VoronoiCell { site: 2, vertices: 3, area: 1.0, is_on_hull: true }"
+        );
+    }
+
+    #[test]
+    fn area_adds_up_to_boundary() {
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .generate_circle_sites(50, 0.8)
+            .build()
+            .expect("Some voronoi expected");
+        let area: f64 = voronoi.iter_cells().map(|c| c.area()).sum();
+        assert!(utils::abs_diff_eq(area, 4.0, 1e-9), "Area is {}.", area);
+    }
+
+    #[test]
+    fn centroids_and_edge_distances_of_quarters() {
+        // one site in each quarter of the boundary, off the center of its quarter
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(vec![
+                Point { x: -0.4, y: -0.4 },
+                Point { x: 0.4, y: -0.4 },
+                Point { x: 0.4, y: 0.4 },
+                Point { x: -0.4, y: 0.4 },
+            ])
+            .build()
+            .expect("Some voronoi expected");
+        for cell in voronoi.iter_cells() {
+            let site = cell.site_position();
+            let centroid = cell.centroid();
+            assert!(utils::abs_diff_eq(centroid.x, site.x.signum() * 0.5, 1e-12));
+            assert!(utils::abs_diff_eq(centroid.y, site.y.signum() * 0.5, 1e-12));
+            assert!(utils::abs_diff_eq(cell.distance_to_edges(site), 0.4, 1e-12));
+            assert!(utils::abs_diff_eq(
+                cell.distance_to_edges(&Point { x: 0.0, y: 0.0 }),
+                0.0,
+                1e-12
+            ));
+        }
+    }
+
+    #[test]
+    fn cell_approx_eq_accepts_any_starting_vertex() {
+        let voronoi = three_site_voronoi();
+        let cell = voronoi.cell(0);
+        let mut expected: Vec<Point> = cell
+            .iter_vertices()
+            .map(|p| Point {
+                x: p.x + 1e-10,
+                y: p.y - 1e-10,
+            })
+            .collect();
+        expected.rotate_left(1);
+
+        crate::assert_cell_approx_eq!(cell, &expected, 1e-9);
+        assert!(!cell.vertices_approx_eq(&expected, 1e-11));
+        expected.reverse();
+        assert!(!cell.vertices_approx_eq(&expected, 1e-9));
+        assert!(!cell.vertices_approx_eq(&expected[1..], 1e-9));
+    }
+
+    #[test]
+    #[should_panic(expected = "cell vertices are not approximately equal")]
+    fn cell_approx_eq_fails() {
+        let voronoi = three_site_voronoi();
+        crate::assert_cell_approx_eq!(voronoi.cell(1), &[Point { x: 0.0, y: 0.0 }], 1e-9);
+    }
+
+    /// Returns whether ```p``` is on the segment ```a -> b```, up to rounding errors.
+    fn is_on_segment(p: &Point, a: &Point, b: &Point) -> bool {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length2 = dx * dx + dy * dy;
+        let t = ((p.x - a.x) * dx + (p.y - a.y) * dy) / length2;
+        let distance = ((p.x - a.x) * dy - (p.y - a.y) * dx).abs() / length2.sqrt();
+        // extended hull edges are very long, so the tolerance is relative to the distance from a
+        (-1e-9..=1.0 + 1e-9).contains(&t) && distance <= 1e-9 * (1.0 + utils::dist2(p, a).sqrt())
+    }
+
+    #[test]
+    fn clipped_circumcenter_is_recoverable() {
+        // the flat triangle of the first three sites has its circumcenter far below the box
+        let voronoi = VoronoiBuilder::default()
+            .set_sites(vec![
+                Point { x: -0.9, y: -0.9 },
+                Point { x: 0.9, y: -0.9 },
+                Point { x: 0.0, y: -0.8 },
+                Point { x: 0.0, y: 0.5 },
+            ])
+            .set_boundary(BoundingBox::new_centered_square(2.0))
+            .build()
+            .expect("Some voronoi expected");
+        let cell = voronoi.cell(2);
+
+        let outside: Vec<&Point> = cell
+            .unclipped_vertices()
+            .filter(|v| !voronoi.boundary().is_inside(v))
+            .collect();
+        assert_eq!(
+            outside.len(),
+            1,
+            "Unclipped vertices: {:?}",
+            cell.unclipped_vertices().collect::<Vec<_>>()
+        );
+        assert!((outside[0].x - 0.0).abs() < 1e-12 && (outside[0].y + 4.9).abs() < 1e-9);
+        assert!(cell
+            .iter_vertices()
+            .all(|v| voronoi.boundary().is_inside(v)));
+
+        let clipped: Vec<CellEdge> = cell.iter_edge_metadata().filter(|e| e.clipped).collect();
+        assert_eq!(clipped.len(), 2);
+        for edge in clipped {
+            let (a, b) = edge
+                .original_endpoints
+                .expect("Original endpoints expected for clipped edges");
+            assert!(a == *outside[0] || b == *outside[0]);
+            assert!(edge.neighbor.is_some());
+
+            let (u, v) = (
+                &voronoi.vertices()[edge.vertices.0],
+                &voronoi.vertices()[edge.vertices.1],
+            );
+            assert!(is_on_segment(u, &a, &b) && is_on_segment(v, &a, &b));
+            let clip = if edge.vertices.0 >= voronoi.number_of_triangles() {
+                u
+            } else {
+                v
+            };
+            assert_eq!(
+                clip.y, -1.0,
+                "Clipped endpoint {:?} expected on the boundary.",
+                clip
+            );
+        }
+    }
+
+    #[test]
+    fn unclipped_data_matches_for_interior_cells() {
+        let sites = utils::test::random_sites(200);
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .build()
+            .expect("Some voronoi expected");
+        let boundary = voronoi.boundary();
+
+        let mut interior_cells = 0;
+        for cell in voronoi.iter_cells() {
+            let edges: Vec<CellEdge> = cell.iter_edge_metadata().collect();
+            if !cell.is_on_hull() {
+                interior_cells += 1;
+                assert_eq!(
+                    cell.unclipped_vertices().collect::<Vec<_>>(),
+                    cell.iter_vertices().collect::<Vec<_>>()
+                );
+                assert!(edges
+                    .iter()
+                    .all(|e| !e.clipped && e.original_endpoints.is_none()));
+            }
+
+            // clipped edges are part of their original edges, and end on the boundary
+            for edge in edges.iter().filter(|e| e.clipped) {
+                let (a, b) = edge.original_endpoints.clone().unwrap();
+                assert!(!boundary.is_inside(&a) || !boundary.is_inside(&b));
+                for &vertex in [edge.vertices.0, edge.vertices.1].iter() {
+                    let p = &voronoi.vertices()[vertex];
+                    assert!(
+                        is_on_segment(p, &a, &b),
+                        "Vertex {:?} not on original edge {:?}.",
+                        p,
+                        (a, b)
+                    );
+                    if vertex >= voronoi.number_of_triangles() {
+                        assert!(
+                            boundary.which_edge(p).is_some(),
+                            "Vertex {:?} not on the boundary.",
+                            p
+                        );
+                    }
+                }
+            }
+        }
+
+        assert!(interior_cells > 0);
     }
 }
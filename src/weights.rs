@@ -0,0 +1,290 @@
+use crate::{utils::clip_half_plane, ConvexBoundary, ConvexPolygon, Point, Voronoi};
+
+/// Options for [Voronoi::dirichlet_weights()] and [Voronoi::weighted_mean()].
+#[derive(Debug, Clone, Copy)]
+pub struct WeightOptions<'a> {
+    /// Whether cells on the hull get a weight of zero.
+    ///
+    /// Hull cells are cut by the boundary of the diagram, so their area depends on the boundary rather than on the spacing of the sites.
+    pub exclude_hull: bool,
+
+    /// Whether the weights are scaled to sum to 1 after excluding cells.
+    ///
+    /// Otherwise, weights are fractions of the total area of the cells, and the weights of excluded cells are missing from their sum.
+    pub renormalize: bool,
+
+    /// A study region to clip the cells to before calculating their area, instead of the boundary of the diagram.
+    ///
+    /// Cells outside of the region get a weight of zero. The region is not clipped to the boundary, so it should be within it.
+    pub clip_to: Option<&'a ConvexPolygon>,
+}
+
+impl Default for WeightOptions<'_> {
+    fn default() -> Self {
+        Self {
+            exclude_hull: false,
+            renormalize: true,
+            clip_to: None,
+        }
+    }
+}
+
+impl<T: ConvexBoundary> Voronoi<T> {
+    /// Calculates the Dirichlet weight of each site: the area of its cell as a fraction of the total area of the cells.
+    ///
+    /// Weighting values sampled at the sites by these weights de-biases averages of irregularly sampled data, as densely sampled regions get smaller weights.
+    /// If the diagram was built with clipping disabled, the cells in the hull are not closed, so they should be excluded with [WeightOptions::exclude_hull].
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use voronoice::*;
+    /// // one site in each quarter of the boundary
+    /// let v = VoronoiBuilder::<BoundingBox>::default()
+    ///     .set_sites(vec![
+    ///         Point { x: -0.5, y: -0.5 }, Point { x: 0.5, y: -0.5 },
+    ///         Point { x: 0.5, y: 0.5 }, Point { x: -0.5, y: 0.5 },
+    ///     ])
+    ///     .build()
+    ///     .unwrap();
+    /// let weights = v.dirichlet_weights(&WeightOptions::default());
+    /// assert!(weights.iter().all(|&w| (w - 0.25).abs() < 1e-12));
+    ///```
+    pub fn dirichlet_weights(&self, options: &WeightOptions) -> Vec<f64> {
+        let areas: Vec<f64> = self
+            .iter_cells()
+            .map(|cell| match options.clip_to {
+                Some(region) => {
+                    let vertices: Vec<Point> = cell.iter_vertices().cloned().collect();
+                    polygon_area(&clip_to_polygon(vertices, region))
+                }
+                None => cell.area(),
+            })
+            .collect();
+        let total_area: f64 = areas.iter().sum();
+
+        let mut weights: Vec<f64> = areas
+            .into_iter()
+            .enumerate()
+            .map(|(site, area)| {
+                if options.exclude_hull && self.cell(site).is_on_hull() {
+                    0.0
+                } else {
+                    area
+                }
+            })
+            .collect();
+        let total = if options.renormalize {
+            weights.iter().sum()
+        } else {
+            total_area
+        };
+        if total > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= total);
+        }
+
+        weights
+    }
+
+    /// Calculates the mean of ```values```, given one value per site, weighted by the [Self::dirichlet_weights()] of the sites.
+    ///
+    /// Weights are always renormalized, regardless of [WeightOptions::renormalize]. If all weights are zero, e.g. for a diagram without sites,
+    /// there is no mean and ```None``` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```values``` does not have one value per site.
+    pub fn weighted_mean(&self, values: &[f64], options: &WeightOptions) -> Option<f64> {
+        assert_eq!(
+            values.len(),
+            self.sites.len(),
+            "Expected one value per site."
+        );
+
+        let weights = self.dirichlet_weights(options);
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        // accumulating deviations from a reference value keeps the mean of equal values exact
+        let reference = values.first().copied().unwrap_or(0.0);
+        let deviation: f64 = weights
+            .iter()
+            .zip(values)
+            .map(|(w, v)| w * (v - reference))
+            .sum();
+
+        Some(reference + deviation / total)
+    }
+}
+
+/// Clips the convex polygon ```vertices``` to ```region```, keeping their orientation.
+fn clip_to_polygon(vertices: Vec<Point>, region: &ConvexPolygon) -> Vec<Point> {
+    let corners = region.vertices();
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .fold(vertices, |polygon, (a, b)| {
+            if polygon.is_empty() {
+                return polygon;
+            }
+
+            // keeps the side of each edge considered inside by ConvexPolygon::is_inside()
+            clip_half_plane(&polygon, |p| {
+                (b.y - a.y) * (p.x - a.x) - (b.x - a.x) * (p.y - a.y)
+            })
+        })
+}
+
+fn polygon_area(vertices: &[Point]) -> f64 {
+    let doubled: f64 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+
+    doubled.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{utils, utils::test::random_voronoi, BoundingBox, VoronoiBuilder};
+
+    #[test]
+    fn weights_sum_to_one() {
+        let voronoi = random_voronoi(200);
+        let region = ConvexPolygon::regular(6, 0.5);
+        let options = [
+            WeightOptions::default(),
+            WeightOptions {
+                exclude_hull: true,
+                ..Default::default()
+            },
+            WeightOptions {
+                clip_to: Some(&region),
+                ..Default::default()
+            },
+            WeightOptions {
+                exclude_hull: true,
+                renormalize: true,
+                clip_to: Some(&region),
+            },
+        ];
+        for options in &options {
+            let weights = voronoi.dirichlet_weights(options);
+            assert_eq!(weights.len(), 200);
+            assert!(weights.iter().all(|&w| w >= 0.0));
+            let sum: f64 = weights.iter().sum();
+            assert!(utils::abs_diff_eq(sum, 1.0, 1e-12), "Sum {}.", sum);
+        }
+
+        // without renormalizing, excluded hull cells are missing from the sum
+        let weights = voronoi.dirichlet_weights(&WeightOptions {
+            exclude_hull: true,
+            renormalize: false,
+            clip_to: None,
+        });
+        let hull: f64 = voronoi
+            .iter_cells()
+            .filter(|c| c.is_on_hull())
+            .map(|c| c.area() / 4.0)
+            .sum();
+        let sum: f64 = weights.iter().sum();
+        assert!(utils::abs_diff_eq(sum, 1.0 - hull, 1e-12));
+    }
+
+    #[test]
+    fn clipped_weights_are_areas_within_region() {
+        let voronoi = random_voronoi(200);
+        let region = ConvexPolygon::new(vec![
+            Point { x: -0.5, y: -0.5 },
+            Point { x: -0.5, y: 0.5 },
+            Point { x: 0.5, y: 0.5 },
+            Point { x: 0.5, y: -0.5 },
+        ]);
+        let weights = voronoi.dirichlet_weights(&WeightOptions {
+            renormalize: false,
+            clip_to: Some(&region),
+            ..Default::default()
+        });
+
+        for cell in voronoi.iter_cells() {
+            let weight = weights[cell.site()];
+            if cell.iter_vertices().all(|p| region.is_inside(p)) {
+                assert!(utils::abs_diff_eq(weight, cell.area(), 1e-12));
+            } else if cell.iter_vertices().all(|p| p.x > 0.5)
+                || cell.iter_vertices().all(|p| p.x < -0.5)
+                || cell.iter_vertices().all(|p| p.y > 0.5)
+                || cell.iter_vertices().all(|p| p.y < -0.5)
+            {
+                assert_eq!(weight, 0.0);
+            } else {
+                assert!(weight <= cell.area() + 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_values_give_back_the_value() {
+        let voronoi = random_voronoi(100);
+        let values = vec![0.1; 100];
+        for options in [
+            WeightOptions::default(),
+            WeightOptions {
+                exclude_hull: true,
+                renormalize: false,
+                clip_to: None,
+            },
+        ] {
+            assert_eq!(voronoi.weighted_mean(&values, &options), Some(0.1));
+        }
+    }
+
+    #[test]
+    fn weighted_mean_corrects_sampling_bias() {
+        let mut rng = utils::test::rng();
+        // sites are denser to the right, with density proportional to x + 1, and the field is x, whose spatial mean is 0
+        let biased_sites = |size: usize, rng: &mut rand::rngs::StdRng| -> Vec<Point> {
+            (0..size)
+                .map(|_| Point {
+                    x: 2.0 * rng.gen_range(0.0f64..1.0).sqrt() - 1.0,
+                    y: rng.gen_range(-1.0..1.0),
+                })
+                .collect()
+        };
+
+        let mut errors = Vec::new();
+        for size in [100, 1_000, 10_000] {
+            let trials = 5;
+            let mut weighted_error = 0.0;
+            for _ in 0..trials {
+                let voronoi = VoronoiBuilder::<BoundingBox>::default()
+                    .set_sites(biased_sites(size, &mut rng))
+                    .build()
+                    .expect("Some voronoi expected");
+                let values: Vec<f64> = voronoi.sites().iter().map(|p| p.x).collect();
+                let naive = values.iter().sum::<f64>() / values.len() as f64;
+                let weighted = voronoi
+                    .weighted_mean(&values, &WeightOptions::default())
+                    .expect("Some mean expected");
+
+                // the naive mean is biased towards 1/3
+                assert!(
+                    weighted.abs() < naive.abs(),
+                    "Weighted {}, naive {}.",
+                    weighted,
+                    naive
+                );
+                weighted_error += weighted.abs() / trials as f64;
+            }
+            errors.push(weighted_error);
+        }
+
+        assert!(errors[2] < errors[0], "Errors {:?}.", errors);
+        assert!(errors[2] < 0.01, "Errors {:?}.", errors);
+    }
+}
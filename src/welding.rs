@@ -0,0 +1,177 @@
+use crate::{
+    utils::{self, dist2},
+    BuildWarning, ConvexBoundary, Voronoi,
+};
+
+/// Welds the vertices of cell edges no longer than ```epsilon```, recording a [BuildWarning::CollapsedEdge] for each pair of neighbors whose shared edge collapsed.
+///
+/// Vertices connected by such edges are moved to the same position, so the edges have a length of zero and the cells on both sides stop being neighbors,
+/// following the adjacency definition of [utils::has_common_voronoi_edge()]. The cells keep their vertex indices, so a cell repeats a vertex where one of its edges collapsed.
+/// A welded vertex is moved onto a vertex created by clipping, if there is one in its group, so cells stay within the boundary.
+pub(crate) fn weld_vertices<T: ConvexBoundary>(voronoi: &mut Voronoi<T>, epsilon: f64) {
+    let epsilon2 = epsilon * epsilon;
+    let mut parents: Vec<usize> = (0..voronoi.circumcenters.len()).collect();
+    let mut welded = false;
+    for cell in &voronoi.cells {
+        for (u, v) in utils::cyclic_pairs(cell) {
+            if u != v && dist2(&voronoi.circumcenters[u], &voronoi.circumcenters[v]) <= epsilon2 {
+                union(&mut parents, u, v);
+                welded = true;
+            }
+        }
+    }
+    if !welded {
+        return;
+    }
+
+    let mut neighbors = Vec::new();
+    for site in 0..voronoi.sites.len() {
+        neighbors.extend(
+            voronoi
+                .cell(site)
+                .iter_neighbors()
+                .filter(|&neighbor| site < neighbor)
+                .map(|neighbor| (site, neighbor)),
+        );
+    }
+
+    // vertices created by clipping come after the circumcenters, and the root of each group is the vertex with the highest index
+    for v in 0..parents.len() {
+        let root = find(&mut parents, v);
+        if root != v {
+            voronoi.circumcenters[v] = voronoi.circumcenters[root].clone();
+        }
+    }
+    voronoi.fan_indices.take();
+
+    for (a, b) in neighbors {
+        if !utils::has_common_voronoi_edge(voronoi, a, b) {
+            voronoi
+                .warnings
+                .push(BuildWarning::CollapsedEdge { sites: (a, b) });
+        }
+    }
+}
+
+fn find(parents: &mut [usize], mut v: usize) -> usize {
+    while parents[v] != v {
+        parents[v] = parents[parents[v]];
+        v = parents[v];
+    }
+    v
+}
+
+/// Joins the groups of ```a``` and ```b```, keeping the highest index as the root.
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    if a < b {
+        parents[a] = b;
+    } else if b < a {
+        parents[b] = a;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Point, VoronoiBuilder};
+
+    /// Four nearly cocircular sites, whose cells meet at two vertices about ```1e-7``` apart joined by an edge shared by the cells of the first and third sites.
+    fn nearly_cocircular_sites() -> Vec<Point> {
+        vec![
+            Point { x: -0.5, y: -0.5 },
+            Point {
+                x: 0.5 + 1e-7,
+                y: -0.5,
+            },
+            Point { x: 0.5, y: 0.5 },
+            Point { x: -0.5, y: 0.5 },
+        ]
+    }
+
+    fn assert_adjacency_is_consistent<T: ConvexBoundary>(voronoi: &Voronoi<T>) {
+        let adjacency = voronoi.adjacency();
+        for site in 0..voronoi.sites().len() {
+            let neighbors: Vec<usize> = voronoi.cell(site).iter_neighbors().collect();
+            assert_eq!(adjacency.neighbors_of(site), neighbors.as_slice());
+            for other in 0..voronoi.sites().len() {
+                assert_eq!(
+                    utils::has_common_voronoi_edge(voronoi, site, other),
+                    neighbors.contains(&other)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn collapsed_edges_separate_cells() {
+        let unwelded = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(nearly_cocircular_sites())
+            .build()
+            .expect("Some voronoi expected");
+        assert!(unwelded.cell(0).iter_neighbors().any(|n| n == 2));
+        assert_adjacency_is_consistent(&unwelded);
+
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(nearly_cocircular_sites())
+            .set_vertex_welding(1e-5)
+            .build()
+            .expect("Some voronoi expected");
+        assert_eq!(
+            voronoi.build_warnings(),
+            &[BuildWarning::CollapsedEdge { sites: (0, 2) }]
+        );
+        assert_adjacency_is_consistent(&voronoi);
+
+        // the four cells meet at the welded vertex, and only the diagonal pairs are not neighbors
+        for site in 0..4 {
+            let mut neighbors: Vec<usize> = voronoi.cell(site).iter_neighbors().collect();
+            neighbors.sort_unstable();
+            let mut expected = vec![(site + 1) % 4, (site + 3) % 4];
+            expected.sort_unstable();
+            assert_eq!(neighbors, expected);
+            assert!(voronoi
+                .cell(site)
+                .iter_vertices()
+                .any(|v| v.x.abs() < 1e-6 && v.y.abs() < 1e-6));
+        }
+        let areas: f64 = voronoi.iter_cells().map(|c| c.area()).sum();
+        assert!(utils::abs_diff_eq(areas, 4.0, 1e-9));
+    }
+
+    #[test]
+    fn welding_keeps_long_edges() {
+        let sites: Vec<Point> = utils::test::random_sites(200);
+        let unwelded = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites.clone())
+            .build()
+            .expect("Some voronoi expected");
+        let voronoi = VoronoiBuilder::<BoundingBox>::default()
+            .set_sites(sites)
+            .set_vertex_welding(1e-4)
+            .build()
+            .expect("Some voronoi expected");
+        assert_adjacency_is_consistent(&voronoi);
+
+        // only the neighbors reported as collapsed are lost, and their edges were short
+        let collapsed: Vec<(usize, usize)> = voronoi
+            .build_warnings()
+            .iter()
+            .map(|w| match w {
+                BuildWarning::CollapsedEdge { sites } => *sites,
+                w => panic!("Unexpected warning {:?}", w),
+            })
+            .collect();
+        for site in 0..unwelded.sites().len() {
+            for neighbor in unwelded.cell(site).iter_neighbors().filter(|&n| site < n) {
+                let kept = utils::has_common_voronoi_edge(&voronoi, site, neighbor);
+                assert_eq!(kept, !collapsed.contains(&(site, neighbor)));
+                if !kept {
+                    assert!(utils::shared_voronoi_edge_length(&unwelded, site, neighbor) <= 1e-3);
+                }
+            }
+        }
+        let areas: f64 = voronoi.iter_cells().map(|c| c.area()).sum();
+        assert!(utils::abs_diff_eq(areas, 4.0, 1e-6));
+    }
+}